@@ -12,7 +12,13 @@ use {
     },
     spl_stake_pool::{
         find_withdraw_authority_program_address,
-        state::{StakePool, ValidatorList},
+        state::{StakePool, ValidatorList, WithdrawalTicket},
+    },
+    spl_token_2022::{
+        extension::{
+            metadata_pointer::MetadataPointer, BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Mint,
     },
     std::collections::HashSet,
 };
@@ -24,7 +30,7 @@ pub fn get_stake_pool(
     stake_pool_address: &Pubkey,
 ) -> Result<StakePool, Error> {
     let account_data = rpc_client.get_account_data(stake_pool_address)?;
-    let stake_pool = try_from_slice_unchecked::<StakePool>(account_data.as_slice())
+    let stake_pool = StakePool::unpack_from_slice(account_data.as_slice())
         .map_err(|err| format!("Invalid stake pool {}: {}", stake_pool_address, err))?;
     Ok(stake_pool)
 }
@@ -39,6 +45,17 @@ pub fn get_validator_list(
     Ok(validator_list)
 }
 
+pub fn get_withdrawal_ticket(
+    rpc_client: &RpcClient,
+    withdrawal_ticket_address: &Pubkey,
+) -> Result<WithdrawalTicket, Error> {
+    let account_data = rpc_client.get_account_data(withdrawal_ticket_address)?;
+    match try_from_slice_unchecked::<WithdrawalTicket>(account_data.as_slice()) {
+        Ok(withdrawal_ticket) if withdrawal_ticket.is_valid() => Ok(withdrawal_ticket),
+        _ => Err(format!("Invalid withdrawal ticket {}", withdrawal_ticket_address).into()),
+    }
+}
+
 pub fn get_token_account(
     rpc_client: &RpcClient,
     token_account_address: &Pubkey,
@@ -70,6 +87,25 @@ pub fn get_token_mint(
     Ok(token_mint)
 }
 
+/// Check if a Token-2022 mint holds its own token metadata, through a metadata
+/// pointer to itself
+pub(crate) fn is_metadata_in_mint(
+    rpc_client: &RpcClient,
+    token_mint_address: &Pubkey,
+) -> Result<bool, Error> {
+    let account = rpc_client.get_account(token_mint_address)?;
+    if account.owner != spl_token_2022::id() {
+        return Ok(false);
+    }
+    let token_mint = StateWithExtensions::<Mint>::unpack(&account.data)
+        .map_err(|err| format!("Invalid token mint {}: {}", token_mint_address, err))?;
+    let metadata_address = token_mint
+        .get_extension::<MetadataPointer>()
+        .ok()
+        .and_then(|metadata_pointer| Option::<Pubkey>::from(metadata_pointer.metadata_address));
+    Ok(metadata_address == Some(*token_mint_address))
+}
+
 pub(crate) fn get_stake_state(
     rpc_client: &RpcClient,
     stake_address: &Pubkey,
@@ -105,7 +141,7 @@ pub(crate) fn get_stake_pools(
                 .filter_map(|(address, account)| {
                     let pool_withdraw_authority =
                         find_withdraw_authority_program_address(&spl_stake_pool::id(), &address).0;
-                    match try_from_slice_unchecked::<StakePool>(account.data.as_slice()) {
+                    match StakePool::unpack_from_slice(account.data.as_slice()) {
                         Ok(stake_pool) => {
                             get_validator_list(rpc_client, &stake_pool.validator_list)
                                 .map(|validator_list| {
@@ -148,3 +184,42 @@ pub(crate) fn get_all_stake(
         .map(|(address, _)| address)
         .collect())
 }
+
+pub(crate) fn get_withdrawal_tickets(
+    rpc_client: &RpcClient,
+    stake_pool_address: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Vec<(Pubkey, WithdrawalTicket)>, ClientError> {
+    rpc_client
+        .get_program_accounts_with_config(
+            &spl_stake_pool::id(),
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    // 3 is the withdrawal ticket account type, followed by the
+                    // stake pool and the owner
+                    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, vec![3])),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                        1,
+                        stake_pool_address.as_ref(),
+                    )),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(33, owner.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(rpc_client.commitment()),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+        .map(|accounts| {
+            accounts
+                .into_iter()
+                .filter_map(|(address, account)| {
+                    try_from_slice_unchecked::<WithdrawalTicket>(account.data.as_slice())
+                        .ok()
+                        .map(|withdrawal_ticket| (address, withdrawal_ticket))
+                })
+                .collect()
+        })
+}
@@ -8,7 +8,10 @@ use spl_associated_token_account::create_associated_token_account;
 use {
     crate::{
         client::*,
-        output::{CliStakePool, CliStakePoolDetails, CliStakePoolStakeAccountInfo, CliStakePools},
+        output::{
+            CliStakePool, CliStakePoolDetails, CliStakePoolSignature, CliStakePoolStakeAccountInfo,
+            CliStakePools,
+        },
     },
     bincode::deserialize,
     clap::{
@@ -38,15 +41,16 @@ use {
         hash::Hash,
         message::Message,
         native_token::{self, Mln},
-        signature::{Keypair, Signer},
+        signature::{Keypair, Signature, Signer},
         signers::Signers,
         system_instruction,
         transaction::Transaction,
     },
     spl_associated_token_account::get_associated_token_address,
     spl_stake_pool::{
-        self, find_stake_program_address, find_transient_stake_program_address,
-        find_withdraw_authority_program_address,
+        self, find_ephemeral_stake_program_address, find_stake_program_address,
+        find_transient_stake_program_address, find_withdraw_authority_program_address,
+        find_withdrawal_ticket_program_address,
         instruction::{FundingType, PreferredValidatorType},
         minimum_delegation,
         state::{Fee, FeeType, StakePool, ValidatorList, ValidatorStakeInfo},
@@ -73,6 +77,19 @@ type CommandResult = Result<(), Error>;
 
 const STAKE_STATE_LEN: usize = 200;
 
+/// The ephemeral stake account only lives within a single instruction, so the
+/// same seed can always be reused
+const EPHEMERAL_STAKE_SEED: u64 = 0;
+
+impl Config {
+    fn is_json_output(&self) -> bool {
+        matches!(
+            self.output_format,
+            OutputFormat::Json | OutputFormat::JsonCompact
+        )
+    }
+}
+
 macro_rules! unique_signers {
     ($vec:ident) => {
         $vec.sort_by_key(|l| l.pubkey());
@@ -156,7 +173,7 @@ fn send_transaction_no_wait(
         println!("Simulate result: {:?}", result);
     } else {
         let signature = config.rpc_client.send_transaction(&transaction)?;
-        println!("Signature: {}", signature);
+        print_signature(config, &signature);
     }
     Ok(())
 }
@@ -172,11 +189,22 @@ fn send_transaction(
         let signature = config
             .rpc_client
             .send_and_confirm_transaction_with_spinner(&transaction)?;
-        println!("Signature: {}", signature);
+        print_signature(config, &signature);
     }
     Ok(())
 }
 
+fn print_signature(config: &Config, signature: &Signature) {
+    println!(
+        "{}",
+        config
+            .output_format
+            .formatted_string(&CliStakePoolSignature {
+                signature: signature.to_string(),
+            })
+    );
+}
+
 fn checked_transaction_with_signers<T: Signers>(
     config: &Config,
     instructions: &[Instruction],
@@ -605,6 +633,169 @@ fn command_decrease_validator_stake(
     Ok(())
 }
 
+fn command_increase_additional_validator_stake(
+    config: &Config,
+    stake_pool_address: &Pubkey,
+    vote_account: &Pubkey,
+    amount: f64,
+) -> CommandResult {
+    let lamports = native_token::mln_to_lamports(amount);
+    if !config.no_update {
+        command_update(config, stake_pool_address, false, false)?;
+    }
+
+    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let validator_list = get_validator_list(&config.rpc_client, &stake_pool.validator_list)?;
+    let validator_stake_info = validator_list
+        .find(vote_account)
+        .ok_or("Vote account not found in validator list")?;
+    let validator_seed = NonZeroU32::new(validator_stake_info.validator_seed_suffix.into());
+
+    let mut signers = vec![config.fee_payer.as_ref(), config.staker.as_ref()];
+    unique_signers!(signers);
+    let transaction = checked_transaction_with_signers(
+        config,
+        &[
+            spl_stake_pool::instruction::increase_additional_validator_stake_with_vote(
+                &spl_stake_pool::id(),
+                &stake_pool,
+                stake_pool_address,
+                vote_account,
+                lamports,
+                validator_seed,
+                validator_stake_info.transient_seed_suffix.into(),
+                EPHEMERAL_STAKE_SEED,
+            ),
+        ],
+        &signers,
+    )?;
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
+fn command_decrease_additional_validator_stake(
+    config: &Config,
+    stake_pool_address: &Pubkey,
+    vote_account: &Pubkey,
+    amount: f64,
+) -> CommandResult {
+    let lamports = native_token::mln_to_lamports(amount);
+    if !config.no_update {
+        command_update(config, stake_pool_address, false, false)?;
+    }
+
+    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let validator_list = get_validator_list(&config.rpc_client, &stake_pool.validator_list)?;
+    let validator_stake_info = validator_list
+        .find(vote_account)
+        .ok_or("Vote account not found in validator list")?;
+    let validator_seed = NonZeroU32::new(validator_stake_info.validator_seed_suffix.into());
+
+    let mut signers = vec![config.fee_payer.as_ref(), config.staker.as_ref()];
+    unique_signers!(signers);
+    let transaction = checked_transaction_with_signers(
+        config,
+        &[
+            spl_stake_pool::instruction::decrease_additional_validator_stake_with_vote(
+                &spl_stake_pool::id(),
+                &stake_pool,
+                stake_pool_address,
+                vote_account,
+                lamports,
+                validator_seed,
+                validator_stake_info.transient_seed_suffix.into(),
+                EPHEMERAL_STAKE_SEED,
+            ),
+        ],
+        &signers,
+    )?;
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
+fn command_redelegate(
+    config: &Config,
+    stake_pool_address: &Pubkey,
+    source_vote_account: &Pubkey,
+    destination_vote_account: &Pubkey,
+    amount: f64,
+) -> CommandResult {
+    let lamports = native_token::mln_to_lamports(amount);
+    if !config.no_update {
+        command_update(config, stake_pool_address, false, false)?;
+    }
+
+    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let validator_list = get_validator_list(&config.rpc_client, &stake_pool.validator_list)?;
+    let source_stake_info = validator_list
+        .find(source_vote_account)
+        .ok_or("Source vote account not found in validator list")?;
+    let destination_stake_info = validator_list
+        .find(destination_vote_account)
+        .ok_or("Destination vote account not found in validator list")?;
+
+    let source_transient_stake_seed = source_stake_info.transient_seed_suffix.into();
+    let destination_transient_stake_seed = destination_stake_info.transient_seed_suffix.into();
+    let pool_withdraw_authority =
+        find_withdraw_authority_program_address(&spl_stake_pool::id(), stake_pool_address).0;
+    let (source_validator_stake, _) = find_stake_program_address(
+        &spl_stake_pool::id(),
+        source_vote_account,
+        stake_pool_address,
+        NonZeroU32::new(source_stake_info.validator_seed_suffix.into()),
+    );
+    let (source_transient_stake, _) = find_transient_stake_program_address(
+        &spl_stake_pool::id(),
+        source_vote_account,
+        stake_pool_address,
+        source_transient_stake_seed,
+    );
+    let (ephemeral_stake, _) = find_ephemeral_stake_program_address(
+        &spl_stake_pool::id(),
+        stake_pool_address,
+        EPHEMERAL_STAKE_SEED,
+    );
+    let (destination_transient_stake, _) = find_transient_stake_program_address(
+        &spl_stake_pool::id(),
+        destination_vote_account,
+        stake_pool_address,
+        destination_transient_stake_seed,
+    );
+    let (destination_validator_stake, _) = find_stake_program_address(
+        &spl_stake_pool::id(),
+        destination_vote_account,
+        stake_pool_address,
+        NonZeroU32::new(destination_stake_info.validator_seed_suffix.into()),
+    );
+
+    let mut signers = vec![config.fee_payer.as_ref(), config.staker.as_ref()];
+    unique_signers!(signers);
+    let transaction = checked_transaction_with_signers(
+        config,
+        &[spl_stake_pool::instruction::redelegate(
+            &spl_stake_pool::id(),
+            stake_pool_address,
+            &config.staker.pubkey(),
+            &pool_withdraw_authority,
+            &stake_pool.validator_list,
+            &stake_pool.reserve_stake,
+            &source_validator_stake,
+            &source_transient_stake,
+            &ephemeral_stake,
+            &destination_transient_stake,
+            &destination_validator_stake,
+            destination_vote_account,
+            lamports,
+            source_transient_stake_seed,
+            EPHEMERAL_STAKE_SEED,
+            destination_transient_stake_seed,
+        )],
+        &signers,
+    )?;
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
 fn command_set_preferred_validator(
     config: &Config,
     stake_pool_address: &Pubkey,
@@ -630,6 +821,50 @@ fn command_set_preferred_validator(
     Ok(())
 }
 
+fn command_set_validator_stake_policy(
+    config: &Config,
+    stake_pool_address: &Pubkey,
+    vote_account: &Pubkey,
+    max_stake_share_bps: u16,
+    stake_denied: bool,
+) -> CommandResult {
+    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let mut signers = vec![config.fee_payer.as_ref(), config.staker.as_ref()];
+    unique_signers!(signers);
+    let transaction = checked_transaction_with_signers(
+        config,
+        &[spl_stake_pool::instruction::set_validator_stake_policy(
+            &spl_stake_pool::id(),
+            stake_pool_address,
+            &config.staker.pubkey(),
+            &stake_pool.validator_list,
+            vote_account,
+            max_stake_share_bps,
+            stake_denied,
+        )],
+        &signers,
+    )?;
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
+/// Convert an optional UI amount of pool tokens into raw pool tokens
+fn pool_token_amount(
+    config: &Config,
+    stake_pool: &StakePool,
+    ui_amount: Option<f64>,
+) -> Result<Option<u64>, Error> {
+    ui_amount
+        .map(|ui_amount| {
+            let pool_mint = get_token_mint(&config.rpc_client, &stake_pool.pool_mint)?;
+            Ok(spl_token::ui_amount_to_amount(
+                ui_amount,
+                pool_mint.decimals,
+            ))
+        })
+        .transpose()
+}
+
 fn add_associated_token_account(
     config: &Config,
     mint: &Pubkey,
@@ -669,12 +904,14 @@ fn command_deposit_stake(
     withdraw_authority: Box<dyn Signer>,
     pool_token_receiver_account: &Option<Pubkey>,
     referrer_token_account: &Option<Pubkey>,
+    minimum_pool_tokens_out: Option<f64>,
 ) -> CommandResult {
     if !config.no_update {
         command_update(config, stake_pool_address, false, false)?;
     }
 
     let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let minimum_pool_tokens_out = pool_token_amount(config, &stake_pool, minimum_pool_tokens_out)?;
     let stake_state = get_stake_state(&config.rpc_client, stake)?;
 
     if config.verbose {
@@ -741,11 +978,47 @@ fn command_deposit_stake(
                 return Err(error.into());
             }
 
-            spl_stake_pool::instruction::deposit_stake_with_authority(
+            if let Some(minimum_pool_tokens_out) = minimum_pool_tokens_out {
+                spl_stake_pool::instruction::deposit_stake_with_authority_and_slippage(
+                    &spl_stake_pool::id(),
+                    stake_pool_address,
+                    &stake_pool.validator_list,
+                    &stake_deposit_authority.pubkey(),
+                    &pool_withdraw_authority,
+                    stake,
+                    &withdraw_authority.pubkey(),
+                    &validator_stake_account,
+                    &stake_pool.reserve_stake,
+                    &pool_token_receiver_account,
+                    &stake_pool.manager_fee_account,
+                    &referrer_token_account,
+                    &stake_pool.pool_mint,
+                    &spl_token::id(),
+                    minimum_pool_tokens_out,
+                )
+            } else {
+                spl_stake_pool::instruction::deposit_stake_with_authority(
+                    &spl_stake_pool::id(),
+                    stake_pool_address,
+                    &stake_pool.validator_list,
+                    &stake_deposit_authority.pubkey(),
+                    &pool_withdraw_authority,
+                    stake,
+                    &withdraw_authority.pubkey(),
+                    &validator_stake_account,
+                    &stake_pool.reserve_stake,
+                    &pool_token_receiver_account,
+                    &stake_pool.manager_fee_account,
+                    &referrer_token_account,
+                    &stake_pool.pool_mint,
+                    &spl_token::id(),
+                )
+            }
+        } else if let Some(minimum_pool_tokens_out) = minimum_pool_tokens_out {
+            spl_stake_pool::instruction::deposit_stake_with_slippage(
                 &spl_stake_pool::id(),
                 stake_pool_address,
                 &stake_pool.validator_list,
-                &stake_deposit_authority.pubkey(),
                 &pool_withdraw_authority,
                 stake,
                 &withdraw_authority.pubkey(),
@@ -756,6 +1029,7 @@ fn command_deposit_stake(
                 &referrer_token_account,
                 &stake_pool.pool_mint,
                 &spl_token::id(),
+                minimum_pool_tokens_out,
             )
         } else {
             spl_stake_pool::instruction::deposit_stake(
@@ -943,6 +1217,7 @@ fn command_deposit_mln(
     pool_token_receiver_account: &Option<Pubkey>,
     referrer_token_account: &Option<Pubkey>,
     amount: f64,
+    minimum_pool_tokens_out: Option<f64>,
 ) -> CommandResult {
     if !config.no_update {
         command_update(config, stake_pool_address, false, false)?;
@@ -965,6 +1240,7 @@ fn command_deposit_mln(
     }
 
     let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let minimum_pool_tokens_out = pool_token_amount(config, &stake_pool, minimum_pool_tokens_out)?;
 
     let mut instructions: Vec<Instruction> = vec![];
 
@@ -1013,10 +1289,42 @@ fn command_deposit_mln(
             return Err(error.into());
         }
 
-        spl_stake_pool::instruction::deposit_mln_with_authority(
+        if let Some(minimum_pool_tokens_out) = minimum_pool_tokens_out {
+            spl_stake_pool::instruction::deposit_mln_with_authority_and_slippage(
+                &spl_stake_pool::id(),
+                stake_pool_address,
+                &deposit_authority.pubkey(),
+                &pool_withdraw_authority,
+                &stake_pool.reserve_stake,
+                &user_mln_transfer.pubkey(),
+                &pool_token_receiver_account,
+                &stake_pool.manager_fee_account,
+                &referrer_token_account,
+                &stake_pool.pool_mint,
+                &spl_token::id(),
+                amount,
+                minimum_pool_tokens_out,
+            )
+        } else {
+            spl_stake_pool::instruction::deposit_mln_with_authority(
+                &spl_stake_pool::id(),
+                stake_pool_address,
+                &deposit_authority.pubkey(),
+                &pool_withdraw_authority,
+                &stake_pool.reserve_stake,
+                &user_mln_transfer.pubkey(),
+                &pool_token_receiver_account,
+                &stake_pool.manager_fee_account,
+                &referrer_token_account,
+                &stake_pool.pool_mint,
+                &spl_token::id(),
+                amount,
+            )
+        }
+    } else if let Some(minimum_pool_tokens_out) = minimum_pool_tokens_out {
+        spl_stake_pool::instruction::deposit_mln_with_slippage(
             &spl_stake_pool::id(),
             stake_pool_address,
-            &deposit_authority.pubkey(),
             &pool_withdraw_authority,
             &stake_pool.reserve_stake,
             &user_mln_transfer.pubkey(),
@@ -1026,6 +1334,7 @@ fn command_deposit_mln(
             &stake_pool.pool_mint,
             &spl_token::id(),
             amount,
+            minimum_pool_tokens_out,
         )
     } else {
         spl_stake_pool::instruction::deposit_mln(
@@ -1151,7 +1460,9 @@ fn command_update(
         if force {
             println!("Update not required, but --force flag specified, so doing it anyway");
         } else {
-            println!("Update not required");
+            if !config.is_json_output() {
+                println!("Update not required");
+            }
             return Ok(());
         }
     }
@@ -1350,6 +1661,7 @@ fn prepare_withdraw_accounts(
     Ok(withdraw_from)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn command_withdraw_stake(
     config: &Config,
     stake_pool_address: &Pubkey,
@@ -1358,6 +1670,7 @@ fn command_withdraw_stake(
     stake_receiver_param: &Option<Pubkey>,
     pool_token_account: &Option<Pubkey>,
     pool_amount: f64,
+    minimum_mln_out: Option<f64>,
 ) -> CommandResult {
     if !config.no_update {
         command_update(config, stake_pool_address, false, false)?;
@@ -1366,6 +1679,7 @@ fn command_withdraw_stake(
     let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
     let pool_mint = get_token_mint(&config.rpc_client, &stake_pool.pool_mint)?;
     let pool_amount = spl_token::ui_amount_to_amount(pool_amount, pool_mint.decimals);
+    let minimum_lamports_out = minimum_mln_out.map(native_token::mln_to_lamports);
 
     let pool_withdraw_authority =
         find_withdraw_authority_program_address(&spl_stake_pool::id(), stake_pool_address).0;
@@ -1576,21 +1890,48 @@ fn command_withdraw_stake(
                 stake_receiver_param.unwrap()
             };
 
-        instructions.push(spl_stake_pool::instruction::withdraw_stake(
-            &spl_stake_pool::id(),
-            stake_pool_address,
-            &stake_pool.validator_list,
-            &pool_withdraw_authority,
-            &withdraw_account.stake_address,
-            &stake_receiver,
-            &config.staker.pubkey(),
-            &user_transfer_authority.pubkey(),
-            &pool_token_account,
-            &stake_pool.manager_fee_account,
-            &stake_pool.pool_mint,
-            &spl_token::id(),
-            withdraw_account.pool_amount,
-        ));
+        if let Some(minimum_lamports_out) = minimum_lamports_out {
+            // Spread the minimum over the stake accounts, in proportion to the
+            // pool tokens withdrawn from each
+            let minimum_lamports_out = (minimum_lamports_out as u128)
+                .checked_mul(withdraw_account.pool_amount as u128)
+                .and_then(|lamports| lamports.checked_add((pool_amount as u128).saturating_sub(1)))
+                .and_then(|lamports| lamports.checked_div(pool_amount as u128))
+                .and_then(|lamports| u64::try_from(lamports).ok())
+                .ok_or("Invalid minimum MLN out")?;
+            instructions.push(spl_stake_pool::instruction::withdraw_stake_with_slippage(
+                &spl_stake_pool::id(),
+                stake_pool_address,
+                &stake_pool.validator_list,
+                &pool_withdraw_authority,
+                &withdraw_account.stake_address,
+                &stake_receiver,
+                &config.staker.pubkey(),
+                &user_transfer_authority.pubkey(),
+                &pool_token_account,
+                &stake_pool.manager_fee_account,
+                &stake_pool.pool_mint,
+                &spl_token::id(),
+                withdraw_account.pool_amount,
+                minimum_lamports_out,
+            ));
+        } else {
+            instructions.push(spl_stake_pool::instruction::withdraw_stake(
+                &spl_stake_pool::id(),
+                stake_pool_address,
+                &stake_pool.validator_list,
+                &pool_withdraw_authority,
+                &withdraw_account.stake_address,
+                &stake_receiver,
+                &config.staker.pubkey(),
+                &user_transfer_authority.pubkey(),
+                &pool_token_account,
+                &stake_pool.manager_fee_account,
+                &stake_pool.pool_mint,
+                &spl_token::id(),
+                withdraw_account.pool_amount,
+            ));
+        }
     }
 
     // Merging the stake with account provided by user
@@ -1629,6 +1970,7 @@ fn command_withdraw_mln(
     pool_token_account: &Option<Pubkey>,
     mln_receiver: &Pubkey,
     pool_amount: f64,
+    minimum_mln_out: Option<f64>,
 ) -> CommandResult {
     if !config.no_update {
         command_update(config, stake_pool_address, false, false)?;
@@ -1637,6 +1979,7 @@ fn command_withdraw_mln(
     let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
     let pool_mint = get_token_mint(&config.rpc_client, &stake_pool.pool_mint)?;
     let pool_amount = spl_token::ui_amount_to_amount(pool_amount, pool_mint.decimals);
+    let minimum_lamports_out = minimum_mln_out.map(native_token::mln_to_lamports);
 
     let pool_token_account = pool_token_account.unwrap_or(get_associated_token_address(
         &config.token_owner.pubkey(),
@@ -1696,10 +2039,42 @@ fn command_withdraw_mln(
             return Err(error.into());
         }
 
-        spl_stake_pool::instruction::withdraw_mln_with_authority(
+        if let Some(minimum_lamports_out) = minimum_lamports_out {
+            spl_stake_pool::instruction::withdraw_mln_with_authority_and_slippage(
+                &spl_stake_pool::id(),
+                stake_pool_address,
+                &withdraw_authority.pubkey(),
+                &pool_withdraw_authority,
+                &user_transfer_authority.pubkey(),
+                &pool_token_account,
+                &stake_pool.reserve_stake,
+                mln_receiver,
+                &stake_pool.manager_fee_account,
+                &stake_pool.pool_mint,
+                &spl_token::id(),
+                pool_amount,
+                minimum_lamports_out,
+            )
+        } else {
+            spl_stake_pool::instruction::withdraw_mln_with_authority(
+                &spl_stake_pool::id(),
+                stake_pool_address,
+                &withdraw_authority.pubkey(),
+                &pool_withdraw_authority,
+                &user_transfer_authority.pubkey(),
+                &pool_token_account,
+                &stake_pool.reserve_stake,
+                mln_receiver,
+                &stake_pool.manager_fee_account,
+                &stake_pool.pool_mint,
+                &spl_token::id(),
+                pool_amount,
+            )
+        }
+    } else if let Some(minimum_lamports_out) = minimum_lamports_out {
+        spl_stake_pool::instruction::withdraw_mln_with_slippage(
             &spl_stake_pool::id(),
             stake_pool_address,
-            &withdraw_authority.pubkey(),
             &pool_withdraw_authority,
             &user_transfer_authority.pubkey(),
             &pool_token_account,
@@ -1709,6 +2084,7 @@ fn command_withdraw_mln(
             &stake_pool.pool_mint,
             &spl_token::id(),
             pool_amount,
+            minimum_lamports_out,
         )
     } else {
         spl_stake_pool::instruction::withdraw_mln(
@@ -1741,18 +2117,231 @@ fn command_withdraw_mln(
     Ok(())
 }
 
-fn command_set_manager(
+fn command_request_withdrawal(
     config: &Config,
     stake_pool_address: &Pubkey,
-    new_manager: &Option<Box<dyn Signer>>,
-    new_fee_receiver: &Option<Pubkey>,
+    pool_token_account: &Option<Pubkey>,
+    pool_amount: f64,
+    ticket_seed: Option<u64>,
 ) -> CommandResult {
     if !config.no_update {
         command_update(config, stake_pool_address, false, false)?;
     }
+
     let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let pool_mint = get_token_mint(&config.rpc_client, &stake_pool.pool_mint)?;
+    let pool_amount = spl_token::ui_amount_to_amount(pool_amount, pool_mint.decimals);
+    let owner = config.token_owner.pubkey();
 
-    // If new accounts are missing in the arguments use the old ones
+    let pool_token_account =
+        pool_token_account.unwrap_or(get_associated_token_address(&owner, &stake_pool.pool_mint));
+    let token_account = get_token_account(
+        &config.rpc_client,
+        &pool_token_account,
+        &stake_pool.pool_mint,
+    )?;
+    if token_account.amount < pool_amount {
+        return Err(format!(
+            "Not enough token balance to withdraw {} pool tokens.\nMaximum withdraw amount is {} pool tokens.",
+            spl_token::amount_to_ui_amount(pool_amount, pool_mint.decimals),
+            spl_token::amount_to_ui_amount(token_account.amount, pool_mint.decimals)
+        )
+        .into());
+    }
+
+    // Use the first seed without a ticket unless one is given
+    let ticket_seed = match ticket_seed {
+        Some(ticket_seed) => ticket_seed,
+        None => {
+            let mut ticket_seed = 0;
+            loop {
+                let (withdrawal_ticket, _) = find_withdrawal_ticket_program_address(
+                    &spl_stake_pool::id(),
+                    stake_pool_address,
+                    &owner,
+                    ticket_seed,
+                );
+                if config
+                    .rpc_client
+                    .get_account_with_commitment(
+                        &withdrawal_ticket,
+                        config.rpc_client.commitment(),
+                    )?
+                    .value
+                    .is_none()
+                {
+                    break ticket_seed;
+                }
+                ticket_seed += 1;
+            }
+        }
+    };
+    let (withdrawal_ticket, _) = find_withdrawal_ticket_program_address(
+        &spl_stake_pool::id(),
+        stake_pool_address,
+        &owner,
+        ticket_seed,
+    );
+
+    let user_transfer_authority = Keypair::new(); // ephemeral keypair just to do the transfer
+    let mut signers = vec![
+        config.fee_payer.as_ref(),
+        config.token_owner.as_ref(),
+        &user_transfer_authority,
+    ];
+
+    let mln_withdraw_authority = if let Some(withdraw_authority) = config.funding_authority.as_ref()
+    {
+        let expected_mln_withdraw_authority =
+            stake_pool.mln_withdraw_authority.ok_or_else(|| {
+                "MLN withdraw authority specified in arguments but stake pool has none".to_string()
+            })?;
+        if withdraw_authority.pubkey() != expected_mln_withdraw_authority {
+            return Err(format!(
+                "Invalid MLN withdraw authority specified, expected {}, received {}",
+                expected_mln_withdraw_authority,
+                withdraw_authority.pubkey()
+            )
+            .into());
+        }
+        signers.push(withdraw_authority.as_ref());
+        Some(expected_mln_withdraw_authority)
+    } else {
+        None
+    };
+
+    let pool_withdraw_authority =
+        find_withdraw_authority_program_address(&spl_stake_pool::id(), stake_pool_address).0;
+    let instructions = vec![
+        spl_token::instruction::approve(
+            &spl_token::id(),
+            &pool_token_account,
+            &user_transfer_authority.pubkey(),
+            &owner,
+            &[],
+            pool_amount,
+        )?,
+        spl_stake_pool::instruction::request_withdrawal(
+            &spl_stake_pool::id(),
+            stake_pool_address,
+            &pool_withdraw_authority,
+            &user_transfer_authority.pubkey(),
+            &pool_token_account,
+            &stake_pool.manager_fee_account,
+            &stake_pool.pool_mint,
+            &owner,
+            &spl_token::id(),
+            mln_withdraw_authority.as_ref(),
+            pool_amount,
+            ticket_seed,
+        ),
+    ];
+
+    unique_signers!(signers);
+    let transaction = checked_transaction_with_signers(config, &instructions, &signers)?;
+    if !config.is_json_output() {
+        println!("Withdrawal ticket: {}", withdrawal_ticket);
+    }
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
+fn command_claim_withdrawal(
+    config: &Config,
+    stake_pool_address: &Pubkey,
+    withdrawal_ticket_address: &Pubkey,
+    mln_receiver: &Pubkey,
+) -> CommandResult {
+    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let withdrawal_ticket = get_withdrawal_ticket(&config.rpc_client, withdrawal_ticket_address)?;
+    if withdrawal_ticket.stake_pool != *stake_pool_address {
+        return Err(format!(
+            "Withdrawal ticket {} belongs to stake pool {}",
+            withdrawal_ticket_address, withdrawal_ticket.stake_pool
+        )
+        .into());
+    }
+    if withdrawal_ticket.owner != config.token_owner.pubkey() {
+        return Err(format!(
+            "Withdrawal ticket {} is owned by {}, not the token owner {}",
+            withdrawal_ticket_address,
+            withdrawal_ticket.owner,
+            config.token_owner.pubkey()
+        )
+        .into());
+    }
+    let epoch = config.rpc_client.get_epoch_info()?.epoch;
+    if !withdrawal_ticket.is_claimable(epoch) {
+        return Err(format!(
+            "Withdrawal ticket {} can be claimed from epoch {}, current epoch is {}",
+            withdrawal_ticket_address,
+            withdrawal_ticket.request_epoch + 1,
+            epoch
+        )
+        .into());
+    }
+
+    let mut signers = vec![config.fee_payer.as_ref(), config.token_owner.as_ref()];
+    unique_signers!(signers);
+    let pool_withdraw_authority =
+        find_withdraw_authority_program_address(&spl_stake_pool::id(), stake_pool_address).0;
+    let transaction = checked_transaction_with_signers(
+        config,
+        &[spl_stake_pool::instruction::claim_withdrawal(
+            &spl_stake_pool::id(),
+            stake_pool_address,
+            &pool_withdraw_authority,
+            withdrawal_ticket_address,
+            &withdrawal_ticket.owner,
+            &stake_pool.reserve_stake,
+            mln_receiver,
+        )],
+        &signers,
+    )?;
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
+fn command_list_withdrawal_tickets(config: &Config, stake_pool_address: &Pubkey) -> CommandResult {
+    let epoch = config.rpc_client.get_epoch_info()?.epoch;
+    let withdrawal_tickets = get_withdrawal_tickets(
+        &config.rpc_client,
+        stake_pool_address,
+        &config.token_owner.pubkey(),
+    )?;
+    if withdrawal_tickets.is_empty() {
+        println!("No withdrawal tickets");
+    }
+    for (address, withdrawal_ticket) in withdrawal_tickets {
+        println!(
+            "Withdrawal ticket {}: {}, {}",
+            address,
+            Mln(withdrawal_ticket.lamports),
+            if withdrawal_ticket.is_claimable(epoch) {
+                "claimable".to_string()
+            } else {
+                format!(
+                    "claimable from epoch {}",
+                    withdrawal_ticket.request_epoch + 1
+                )
+            }
+        );
+    }
+    Ok(())
+}
+
+fn command_set_manager(
+    config: &Config,
+    stake_pool_address: &Pubkey,
+    new_manager: &Option<Box<dyn Signer>>,
+    new_fee_receiver: &Option<Pubkey>,
+) -> CommandResult {
+    if !config.no_update {
+        command_update(config, stake_pool_address, false, false)?;
+    }
+    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+
+    // If new accounts are missing in the arguments use the old ones
     let (new_manager_pubkey, mut signers): (Pubkey, Vec<&dyn Signer>) = match new_manager {
         None => (stake_pool.manager, vec![]),
         Some(value) => (value.pubkey(), vec![value.as_ref()]),
@@ -1868,6 +2457,76 @@ fn command_set_fee(
     Ok(())
 }
 
+fn command_create_token_metadata(
+    config: &Config,
+    stake_pool_address: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> CommandResult {
+    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let create_token_metadata = if is_metadata_in_mint(&config.rpc_client, &stake_pool.pool_mint)? {
+        spl_stake_pool::instruction::create_token_metadata_in_mint
+    } else {
+        spl_stake_pool::instruction::create_token_metadata
+    };
+    let mut signers = vec![config.fee_payer.as_ref(), config.manager.as_ref()];
+    unique_signers!(signers);
+    let transaction = checked_transaction_with_signers(
+        config,
+        &[create_token_metadata(
+            &spl_stake_pool::id(),
+            stake_pool_address,
+            &config.manager.pubkey(),
+            &stake_pool.pool_mint,
+            &config.fee_payer.pubkey(),
+            name,
+            symbol,
+            uri,
+        )],
+        &signers,
+    )?;
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
+fn command_update_token_metadata(
+    config: &Config,
+    stake_pool_address: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> CommandResult {
+    let stake_pool = get_stake_pool(&config.rpc_client, stake_pool_address)?;
+    let instruction = if is_metadata_in_mint(&config.rpc_client, &stake_pool.pool_mint)? {
+        spl_stake_pool::instruction::update_token_metadata_in_mint(
+            &spl_stake_pool::id(),
+            stake_pool_address,
+            &config.manager.pubkey(),
+            &stake_pool.pool_mint,
+            &config.fee_payer.pubkey(),
+            name,
+            symbol,
+            uri,
+        )
+    } else {
+        spl_stake_pool::instruction::update_token_metadata(
+            &spl_stake_pool::id(),
+            stake_pool_address,
+            &config.manager.pubkey(),
+            &stake_pool.pool_mint,
+            name,
+            symbol,
+            uri,
+        )
+    };
+    let mut signers = vec![config.fee_payer.as_ref(), config.manager.as_ref()];
+    unique_signers!(signers);
+    let transaction = checked_transaction_with_signers(config, &[instruction], &signers)?;
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
 fn command_list_all_pools(config: &Config) -> CommandResult {
     let all_pools = get_stake_pools(&config.rpc_client)?;
     let cli_stake_pool_vec: Vec<CliStakePool> =
@@ -2211,6 +2870,105 @@ fn main() {
                     .help("Amount in MLN to remove from the validator stake account. Must be at least the rent-exempt amount for a stake."),
             )
         )
+        .subcommand(SubCommand::with_name("increase-additional-validator-stake")
+            .about("Increase stake to a validator again in an epoch, drawing from the stake pool reserve. Works even if the validator already has transient stake. Must be signed by the pool staker.")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address"),
+            )
+            .arg(
+                Arg::with_name("vote_account")
+                    .index(2)
+                    .validator(is_pubkey)
+                    .value_name("VOTE_ACCOUNT_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Vote account for the validator to increase stake to"),
+            )
+            .arg(
+                Arg::with_name("amount")
+                    .index(3)
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Amount in MLN to add to the validator stake account. Must be at least the minimum delegation."),
+            )
+        )
+        .subcommand(SubCommand::with_name("decrease-additional-validator-stake")
+            .about("Decrease stake to a validator again in an epoch, splitting from the active stake. Works even if the validator already has transient stake. Must be signed by the pool staker.")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address"),
+            )
+            .arg(
+                Arg::with_name("vote_account")
+                    .index(2)
+                    .validator(is_pubkey)
+                    .value_name("VOTE_ACCOUNT_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Vote account for the validator to decrease stake from"),
+            )
+            .arg(
+                Arg::with_name("amount")
+                    .index(3)
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Amount in MLN to remove from the validator stake account. Must be at least the minimum delegation."),
+            )
+        )
+        .subcommand(SubCommand::with_name("redelegate")
+            .about("Move stake from a validator to another, without going through the reserve. Must be signed by the pool staker.")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address"),
+            )
+            .arg(
+                Arg::with_name("source_vote_account")
+                    .index(2)
+                    .validator(is_pubkey)
+                    .value_name("SOURCE_VOTE_ACCOUNT_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Vote account for the validator to move stake from"),
+            )
+            .arg(
+                Arg::with_name("destination_vote_account")
+                    .index(3)
+                    .validator(is_pubkey)
+                    .value_name("DESTINATION_VOTE_ACCOUNT_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Vote account for the validator to move stake to"),
+            )
+            .arg(
+                Arg::with_name("amount")
+                    .index(4)
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Amount in MLN to redelegate. Must be at least the rent-exempt amount for a stake plus the minimum delegation."),
+            )
+        )
         .subcommand(SubCommand::with_name("set-preferred-validator")
             .about("Set the preferred validator for deposits or withdrawals. Must be signed by the pool staker.")
             .arg(
@@ -2251,6 +3009,41 @@ fn main() {
                 .required(true)
             )
         )
+        .subcommand(SubCommand::with_name("set-validator-stake-policy")
+            .about("Limit or deny new stake to a validator in the pool. Must be signed by the pool staker.")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address"),
+            )
+            .arg(
+                Arg::with_name("vote_account")
+                    .index(2)
+                    .validator(is_pubkey)
+                    .value_name("VOTE_ACCOUNT_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The validator vote account to set the policy of"),
+            )
+            .arg(
+                Arg::with_name("max_stake_share")
+                    .long("max-stake-share")
+                    .validator(is_parsable::<f64>)
+                    .value_name("PERCENT")
+                    .takes_value(true)
+                    .help("Maximum share of the pool lamports that deposits, increases and redelegations may bring the validator to. [default: no maximum]"),
+            )
+            .arg(
+                Arg::with_name("deny")
+                    .long("deny")
+                    .takes_value(false)
+                    .help("Deny all new stake to the validator. Stake already on the validator can still be decreased or withdrawn."),
+            )
+        )
         .subcommand(SubCommand::with_name("deposit-stake")
             .about("Deposit active stake account into the stake pool in exchange for pool tokens")
             .arg(
@@ -2297,6 +3090,14 @@ fn main() {
                     .help("Pool token account to receive the referral fees for deposits. \
                           Defaults to the token receiver."),
             )
+            .arg(
+                Arg::with_name("minimum_pool_tokens_out")
+                    .long("minimum-pool-tokens-out")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .help("Minimum amount of pool tokens to receive, failing the deposit otherwise."),
+            )
         )
         .subcommand(SubCommand::with_name("deposit-all-stake")
             .about("Deposit all active stake accounts into the stake pool in exchange for pool tokens")
@@ -2390,6 +3191,14 @@ fn main() {
                     .help("Account to receive the referral fees for deposits. \
                           Defaults to the token receiver."),
             )
+            .arg(
+                Arg::with_name("minimum_pool_tokens_out")
+                    .long("minimum-pool-tokens-out")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .help("Minimum amount of pool tokens to receive, failing the deposit otherwise."),
+            )
         )
         .subcommand(SubCommand::with_name("list")
             .about("List stake accounts managed by this pool")
@@ -2482,6 +3291,14 @@ fn main() {
                 .arg("use_reserve")
                 .arg("vote_account")
             )
+            .arg(
+                Arg::with_name("minimum_mln_out")
+                    .long("minimum-mln-out")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .help("Minimum amount in MLN to receive, spread over the withdrawn stake accounts, failing the withdrawal otherwise."),
+            )
         )
         .subcommand(SubCommand::with_name("withdraw-mln")
             .about("Withdraw MLN from the stake pool's reserve in exchange for pool tokens")
@@ -2520,6 +3337,92 @@ fn main() {
                     .takes_value(true)
                     .help("Pool token account to withdraw tokens from. Defaults to the token-owner's associated token account."),
             )
+            .arg(
+                Arg::with_name("minimum_mln_out")
+                    .long("minimum-mln-out")
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .help("Minimum amount in MLN to receive, failing the withdrawal otherwise."),
+            )
+        )
+        .subcommand(SubCommand::with_name("request-withdrawal")
+            .about("Burn pool tokens for a withdrawal ticket, to claim as MLN from the stake pool's reserve once the staker has deactivated stake for it")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address."),
+            )
+            .arg(
+                Arg::with_name("amount")
+                    .index(2)
+                    .validator(is_amount)
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Amount of pool tokens to withdraw for MLN."),
+            )
+            .arg(
+                Arg::with_name("pool_account")
+                    .long("pool-account")
+                    .validator(is_pubkey)
+                    .value_name("ADDRESS")
+                    .takes_value(true)
+                    .help("Pool token account to withdraw tokens from. Defaults to the token-owner's associated token account."),
+            )
+            .arg(
+                Arg::with_name("ticket_seed")
+                    .long("ticket-seed")
+                    .validator(is_parsable::<u64>)
+                    .value_name("SEED")
+                    .takes_value(true)
+                    .help("Seed of the withdrawal ticket account. Defaults to the first seed without a ticket for the token owner."),
+            )
+        )
+        .subcommand(SubCommand::with_name("claim-withdrawal")
+            .about("Claim a withdrawal ticket for MLN from the stake pool's reserve, from the epoch after its request")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address."),
+            )
+            .arg(
+                Arg::with_name("withdrawal_ticket")
+                    .index(2)
+                    .validator(is_pubkey)
+                    .value_name("TICKET_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Withdrawal ticket to claim, owned by the token owner."),
+            )
+            .arg(
+                Arg::with_name("mln_receiver")
+                    .long("mln-receiver")
+                    .validator(is_valid_pubkey)
+                    .value_name("SYSTEM_ACCOUNT_ADDRESS_OR_KEYPAIR")
+                    .takes_value(true)
+                    .help("System account to receive MLN from the stake pool. Defaults to the token owner."),
+            )
+        )
+        .subcommand(SubCommand::with_name("list-withdrawal-tickets")
+            .about("List the withdrawal tickets of the token owner in the stake pool")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address."),
+            )
         )
         .subcommand(SubCommand::with_name("set-manager")
             .about("Change manager or fee receiver account for the stake pool. Must be signed by the current manager.")
@@ -2682,6 +3585,78 @@ fn main() {
                     .help("Fee percentage, maximum 100"),
             )
         )
+        .subcommand(SubCommand::with_name("create-token-metadata")
+            .about("Create metadata for the pool token. Must be signed by the pool manager.")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address"),
+            )
+            .arg(
+                Arg::with_name("name")
+                    .index(2)
+                    .value_name("TOKEN_NAME")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Name of the pool token"),
+            )
+            .arg(
+                Arg::with_name("symbol")
+                    .index(3)
+                    .value_name("TOKEN_SYMBOL")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Symbol of the pool token"),
+            )
+            .arg(
+                Arg::with_name("uri")
+                    .index(4)
+                    .value_name("TOKEN_URI")
+                    .takes_value(true)
+                    .required(true)
+                    .help("URI of the pool token metadata"),
+            )
+        )
+        .subcommand(SubCommand::with_name("update-token-metadata")
+            .about("Update the metadata of the pool token. Must be signed by the pool manager.")
+            .arg(
+                Arg::with_name("pool")
+                    .index(1)
+                    .validator(is_pubkey)
+                    .value_name("POOL_ADDRESS")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Stake pool address"),
+            )
+            .arg(
+                Arg::with_name("name")
+                    .index(2)
+                    .value_name("TOKEN_NAME")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Name of the pool token"),
+            )
+            .arg(
+                Arg::with_name("symbol")
+                    .index(3)
+                    .value_name("TOKEN_SYMBOL")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Symbol of the pool token"),
+            )
+            .arg(
+                Arg::with_name("uri")
+                    .index(4)
+                    .value_name("TOKEN_URI")
+                    .takes_value(true)
+                    .required(true)
+                    .help("URI of the pool token metadata"),
+            )
+        )
         .subcommand(SubCommand::with_name("list-all")
             .about("List information about all stake pools")
         )
@@ -2839,6 +3814,42 @@ fn main() {
             let amount = value_t_or_exit!(arg_matches, "amount", f64);
             command_decrease_validator_stake(&config, &stake_pool_address, &vote_account, amount)
         }
+        ("increase-additional-validator-stake", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let amount = value_t_or_exit!(arg_matches, "amount", f64);
+            command_increase_additional_validator_stake(
+                &config,
+                &stake_pool_address,
+                &vote_account,
+                amount,
+            )
+        }
+        ("decrease-additional-validator-stake", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let amount = value_t_or_exit!(arg_matches, "amount", f64);
+            command_decrease_additional_validator_stake(
+                &config,
+                &stake_pool_address,
+                &vote_account,
+                amount,
+            )
+        }
+        ("redelegate", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            let source_vote_account = pubkey_of(arg_matches, "source_vote_account").unwrap();
+            let destination_vote_account =
+                pubkey_of(arg_matches, "destination_vote_account").unwrap();
+            let amount = value_t_or_exit!(arg_matches, "amount", f64);
+            command_redelegate(
+                &config,
+                &stake_pool_address,
+                &source_vote_account,
+                &destination_vote_account,
+                amount,
+            )
+        }
         ("set-preferred-validator", Some(arg_matches)) => {
             let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
             let preferred_type = match arg_matches.value_of("preferred_type").unwrap() {
@@ -2857,6 +3868,22 @@ fn main() {
                 vote_account,
             )
         }
+        ("set-validator-stake-policy", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            let vote_account = pubkey_of(arg_matches, "vote_account").unwrap();
+            let max_stake_share = value_t!(arg_matches, "max_stake_share", f64).unwrap_or(0.0);
+            if !(0.0..=100.0).contains(&max_stake_share) {
+                eprintln!("error: max stake share must be between 0 and 100 percent");
+                exit(1);
+            }
+            command_set_validator_stake_policy(
+                &config,
+                &stake_pool_address,
+                &vote_account,
+                (max_stake_share * 100.0).round() as u16,
+                arg_matches.is_present("deny"),
+            )
+        }
         ("deposit-stake", Some(arg_matches)) => {
             let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
             let stake_account = pubkey_of(arg_matches, "stake_account").unwrap();
@@ -2871,6 +3898,8 @@ fn main() {
                     allow_null_signer: false,
                 },
             );
+            let minimum_pool_tokens_out =
+                value_t!(arg_matches, "minimum_pool_tokens_out", f64).ok();
             command_deposit_stake(
                 &config,
                 &stake_pool_address,
@@ -2878,6 +3907,7 @@ fn main() {
                 withdraw_authority,
                 &token_receiver,
                 &referrer,
+                minimum_pool_tokens_out,
             )
         }
         ("deposit-mln", Some(arg_matches)) => {
@@ -2886,6 +3916,8 @@ fn main() {
             let referrer: Option<Pubkey> = pubkey_of(arg_matches, "referrer");
             let from = keypair_of(arg_matches, "from");
             let amount = value_t_or_exit!(arg_matches, "amount", f64);
+            let minimum_pool_tokens_out =
+                value_t!(arg_matches, "minimum_pool_tokens_out", f64).ok();
             command_deposit_mln(
                 &config,
                 &stake_pool_address,
@@ -2893,6 +3925,7 @@ fn main() {
                 &token_receiver,
                 &referrer,
                 amount,
+                minimum_pool_tokens_out,
             )
         }
         ("list", Some(arg_matches)) => {
@@ -2912,6 +3945,7 @@ fn main() {
             let pool_amount = value_t_or_exit!(arg_matches, "amount", f64);
             let stake_receiver = pubkey_of(arg_matches, "stake_receiver");
             let use_reserve = arg_matches.is_present("use_reserve");
+            let minimum_mln_out = value_t!(arg_matches, "minimum_mln_out", f64).ok();
             command_withdraw_stake(
                 &config,
                 &stake_pool_address,
@@ -2920,6 +3954,7 @@ fn main() {
                 &stake_receiver,
                 &pool_account,
                 pool_amount,
+                minimum_mln_out,
             )
         }
         ("withdraw-mln", Some(arg_matches)) => {
@@ -2936,14 +3971,45 @@ fn main() {
                 },
             )
             .pubkey();
+            let minimum_mln_out = value_t!(arg_matches, "minimum_mln_out", f64).ok();
             command_withdraw_mln(
                 &config,
                 &stake_pool_address,
                 &pool_account,
                 &mln_receiver,
                 pool_amount,
+                minimum_mln_out,
             )
         }
+        ("request-withdrawal", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            let pool_account = pubkey_of(arg_matches, "pool_account");
+            let pool_amount = value_t_or_exit!(arg_matches, "amount", f64);
+            let ticket_seed = value_t!(arg_matches, "ticket_seed", u64).ok();
+            command_request_withdrawal(
+                &config,
+                &stake_pool_address,
+                &pool_account,
+                pool_amount,
+                ticket_seed,
+            )
+        }
+        ("claim-withdrawal", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            let withdrawal_ticket = pubkey_of(arg_matches, "withdrawal_ticket").unwrap();
+            let mln_receiver = pubkey_of(arg_matches, "mln_receiver")
+                .unwrap_or_else(|| config.token_owner.pubkey());
+            command_claim_withdrawal(
+                &config,
+                &stake_pool_address,
+                &withdrawal_ticket,
+                &mln_receiver,
+            )
+        }
+        ("list-withdrawal-tickets", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            command_list_withdrawal_tickets(&config, &stake_pool_address)
+        }
         ("set-manager", Some(arg_matches)) => {
             let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
 
@@ -3033,6 +4099,20 @@ fn main() {
             };
             command_set_fee(&config, &stake_pool_address, fee_type)
         }
+        ("create-token-metadata", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            let name = value_t_or_exit!(arg_matches, "name", String);
+            let symbol = value_t_or_exit!(arg_matches, "symbol", String);
+            let uri = value_t_or_exit!(arg_matches, "uri", String);
+            command_create_token_metadata(&config, &stake_pool_address, name, symbol, uri)
+        }
+        ("update-token-metadata", Some(arg_matches)) => {
+            let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
+            let name = value_t_or_exit!(arg_matches, "name", String);
+            let symbol = value_t_or_exit!(arg_matches, "symbol", String);
+            let uri = value_t_or_exit!(arg_matches, "uri", String);
+            command_update_token_metadata(&config, &stake_pool_address, name, symbol, uri)
+        }
         ("list-all", _) => command_list_all_pools(&config),
         ("deposit-all-stake", Some(arg_matches)) => {
             let stake_pool_address = pubkey_of(arg_matches, "pool").unwrap();
@@ -35,6 +35,21 @@ impl Display for CliStakePools {
 impl QuietDisplay for CliStakePools {}
 impl VerboseDisplay for CliStakePools {}
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliStakePoolSignature {
+    pub signature: String,
+}
+
+impl Display for CliStakePoolSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Signature: {}", &self.signature)
+    }
+}
+
+impl QuietDisplay for CliStakePoolSignature {}
+impl VerboseDisplay for CliStakePoolSignature {}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct CliStakePool {
@@ -71,6 +86,7 @@ pub(crate) struct CliStakePool {
     pub next_mln_withdrawal_fee: Option<CliStakePoolFee>,
     pub last_epoch_pool_token_supply: u64,
     pub last_epoch_total_lamports: u64,
+    pub pending_withdrawal_lamports: u64,
     pub details: Option<CliStakePoolDetails>,
 }
 
@@ -364,7 +380,9 @@ pub(crate) struct CliStakePoolValidator {
     pub transient_stake_lamports: u64,
     pub last_update_epoch: u64,
     pub transient_seed_suffix: u64,
-    pub unused: u32,
+    pub max_stake_share_bps: u16,
+    pub stake_denied: bool,
+    pub unused: u8,
     pub validator_seed_suffix: u32,
     pub status: CliStakePoolValidatorStakeStatus,
     pub vote_account_address: String,
@@ -377,7 +395,9 @@ impl From<ValidatorStakeInfo> for CliStakePoolValidator {
             transient_stake_lamports: v.transient_stake_lamports.into(),
             last_update_epoch: v.last_update_epoch.into(),
             transient_seed_suffix: v.transient_seed_suffix.into(),
-            unused: v.unused.into(),
+            max_stake_share_bps: v.max_stake_share_bps.into(),
+            stake_denied: v.stake_denied.into(),
+            unused: v.unused,
             validator_seed_suffix: v.validator_seed_suffix.into(),
             status: CliStakePoolValidatorStakeStatus::from(v.status),
             vote_account_address: v.vote_account_address.to_string(),
@@ -499,6 +519,7 @@ impl From<(Pubkey, StakePool, ValidatorList, Pubkey)> for CliStakePool {
                 .map(CliStakePoolFee::from),
             last_epoch_pool_token_supply: stake_pool.last_epoch_pool_token_supply,
             last_epoch_total_lamports: stake_pool.last_epoch_total_lamports,
+            pending_withdrawal_lamports: stake_pool.pending_withdrawal_lamports,
             details: None,
         }
     }
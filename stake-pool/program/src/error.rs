@@ -164,6 +164,23 @@ pub enum StakePoolError {
     /// Missing required sysvar account
     #[error("Missing required sysvar account")]
     MissingRequiredSysvar,
+    /// The staker denied new stake to the validator
+    #[error("ValidatorStakeDenied")]
+    ValidatorStakeDenied,
+    /// The new stake would take the validator over its share of the pool
+    #[error("ValidatorStakeCapExceeded")]
+    ValidatorStakeCapExceeded,
+
+    // 45.
+    /// Provided stake share is above 100%
+    #[error("InvalidStakeShare")]
+    InvalidStakeShare,
+    /// Withdrawal ticket does not belong to the stake pool or the owner
+    #[error("InvalidWithdrawalTicket")]
+    InvalidWithdrawalTicket,
+    /// Withdrawal ticket can only be claimed from the epoch after its request
+    #[error("WithdrawalTicketNotReady")]
+    WithdrawalTicketNotReady,
 }
 impl From<StakePoolError> for ProgramError {
     fn from(e: StakePoolError) -> Self {
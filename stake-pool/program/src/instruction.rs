@@ -6,7 +6,7 @@ use {
     crate::{
         find_deposit_authority_program_address, find_ephemeral_stake_program_address,
         find_stake_program_address, find_transient_stake_program_address,
-        find_withdraw_authority_program_address,
+        find_withdraw_authority_program_address, find_withdrawal_ticket_program_address,
         inline_mpl_token_metadata::{self, pda::find_metadata_account},
         state::{Fee, FeeType, StakePool, ValidatorList},
         MAX_VALIDATORS_TO_UPDATE,
@@ -396,6 +396,11 @@ pub enum StakePoolInstruction {
 
     /// Create token metadata for the stake-pool token in the
     /// metaplex-token program
+    ///
+    /// A Token-2022 pool mint whose metadata pointer points to itself holds
+    /// its own metadata instead: pass the pool mint as token metadata account
+    /// and the Token-2022 program as metadata program.
+    ///
     /// 0. `[]` Stake pool
     /// 1. `[s]` Manager
     /// 2. `[]` Stake pool withdraw authority
@@ -415,11 +420,17 @@ pub enum StakePoolInstruction {
     /// Update token metadata for the stake-pool token in the
     /// metaplex-token program
     ///
+    /// For metadata held by a Token-2022 pool mint, pass the pool mint as
+    /// token metadata account and the Token-2022 program as metadata program,
+    /// along with a payer for the resized mint.
+    ///
     /// 0. `[]` Stake pool
     /// 1. `[s]` Manager
     /// 2. `[]` Stake pool withdraw authority
     /// 3. `[w]` Token metadata account
     /// 4. `[]` Metadata program id
+    /// 5. `[s, w]` (Token-2022 only) Payer for the resized pool mint
+    /// 6. `[]` (Token-2022 only) System program id
     UpdateTokenMetadata {
         /// Token name
         name: String,
@@ -719,6 +730,76 @@ pub enum StakePoolInstruction {
         /// Minimum amount of lamports that must be received
         minimum_lamports_out: u64,
     },
+
+    /// (Staker only) Set the stake policy of a validator in the pool
+    ///
+    /// Deposits, increases and redelegations to the validator fail if new
+    /// stake to it is denied, or if they would take its stake over the given
+    /// share of the pool lamports. Stake already on the validator stays where
+    /// it is, and can still be decreased or withdrawn.
+    ///
+    /// 0. `[]` Stake pool
+    /// 1. `[s]` Stake pool staker
+    /// 2. `[w]` Validator list
+    ///
+    /// Fails if the validator is not part of the stake pool.
+    SetValidatorStakePolicy {
+        /// Validator vote account to set the policy of
+        vote_account_address: Pubkey,
+        /// Maximum share of the pool lamports on the validator, in basis
+        /// points, or 0 for no maximum
+        max_stake_share_bps: u16,
+        /// Whether new stake to the validator is denied
+        stake_denied: bool,
+    },
+
+    ///   Request a delayed withdrawal of MLN from the stake pool, burning pool
+    ///   tokens in exchange for a withdrawal ticket. The pool tokens are
+    ///   converted at the rate of the request, and the MLN withdrawal fee is
+    ///   taken on request. The lamports owed to the ticket are added to the
+    ///   pending withdrawal lamports of the stake pool, which the other
+    ///   instructions leave in the reserve.
+    ///
+    ///   The staker is expected to deactivate stake into the reserve for the
+    ///   pending tickets, which can be claimed from the next epoch.
+    ///
+    ///   0. `[w]` Stake pool
+    ///   1. `[]` Stake pool withdraw authority
+    ///   2. `[w]` Withdrawal ticket account to create, derived from the stake
+    ///      pool, owner and ticket seed
+    ///   3. `[s]` User transfer authority, for pool token account
+    ///   4. `[w]` User account to burn pool tokens from
+    ///   5. `[w]` Account to receive pool fee tokens
+    ///   6. `[w]` Pool token mint account
+    ///   7. `[ws]` Ticket owner, paying for the ticket account, and for
+    ///      resizing a stake pool account created before pending withdrawal
+    ///      lamports were tracked
+    ///   8. `[]` System program account
+    ///   9. `[]` Token program id
+    ///  10. `[s]` (Optional) Stake pool mln withdraw authority
+    RequestWithdrawal {
+        /// Pool tokens to burn for the ticket
+        pool_tokens_in: u64,
+        /// Seed used to derive the withdrawal ticket account
+        ticket_seed: u64,
+    },
+
+    ///   Claim a withdrawal ticket from the epoch after its request, for the
+    ///   MLN it is owed from the reserve. Fails if the reserve does not have
+    ///   enough MLN yet. Closes the ticket account, returning its rent to the
+    ///   owner.
+    ///
+    ///   0. `[w]` Stake pool
+    ///   1. `[]` Stake pool withdraw authority
+    ///   2. `[w]` Withdrawal ticket account
+    ///   3. `[ws]` Ticket owner
+    ///   4. `[w]` Reserve stake account, to withdraw MLN
+    ///   5. `[w]` Account receiving the lamports from the reserve, must be a
+    ///      system account
+    ///   6. '[]' Clock sysvar
+    ///   7. '[]' Stake history sysvar
+    ///   8. `[]` Stake program account
+    ClaimWithdrawal,
 }
 
 /// Creates an 'initialize' instruction.
@@ -798,8 +879,10 @@ pub fn add_validator_to_pool(
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(stake::program::id(), false),
     ];
-    let data = borsh::to_vec(&StakePoolInstruction::AddValidatorToPool(seed.map(|s| s.get()).unwrap_or(0)))
-        .unwrap();
+    let data = borsh::to_vec(&StakePoolInstruction::AddValidatorToPool(
+        seed.map(|s| s.get()).unwrap_or(0),
+    ))
+    .unwrap();
     Instruction {
         program_id: *program_id,
         accounts,
@@ -831,8 +914,7 @@ pub fn remove_validator_from_pool(
     Instruction {
         program_id: *program_id,
         accounts,
-        data: borsh::to_vec(&StakePoolInstruction::RemoveValidatorFromPool)
-            .unwrap(),
+        data: borsh::to_vec(&StakePoolInstruction::RemoveValidatorFromPool).unwrap(),
     }
 }
 
@@ -1121,6 +1203,32 @@ pub fn set_preferred_validator(
     }
 }
 
+/// Creates a `SetValidatorStakePolicy` instruction.
+pub fn set_validator_stake_policy(
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    staker: &Pubkey,
+    validator_list_address: &Pubkey,
+    vote_account_address: &Pubkey,
+    max_stake_share_bps: u16,
+    stake_denied: bool,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*stake_pool_address, false),
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new(*validator_list_address, false),
+        ],
+        data: borsh::to_vec(&StakePoolInstruction::SetValidatorStakePolicy {
+            vote_account_address: *vote_account_address,
+            max_stake_share_bps,
+            stake_denied,
+        })
+        .unwrap(),
+    }
+}
+
 /// Create an `AddValidatorToPool` instruction given an existing stake pool and
 /// vote account
 pub fn add_validator_to_pool_with_vote(
@@ -1437,8 +1545,7 @@ pub fn update_stake_pool_balance(
     Instruction {
         program_id: *program_id,
         accounts,
-        data: borsh::to_vec(&StakePoolInstruction::UpdateStakePoolBalance)
-            .unwrap(),
+        data: borsh::to_vec(&StakePoolInstruction::UpdateStakePoolBalance).unwrap(),
     }
 }
 
@@ -1456,8 +1563,7 @@ pub fn cleanup_removed_validator_entries(
     Instruction {
         program_id: *program_id,
         accounts,
-        data: borsh::to_vec(&StakePoolInstruction::CleanupRemovedValidatorEntries)
-            .unwrap(),
+        data: borsh::to_vec(&StakePoolInstruction::CleanupRemovedValidatorEntries).unwrap(),
     }
 }
 
@@ -1814,8 +1920,7 @@ fn deposit_mln_internal(
         Instruction {
             program_id: *program_id,
             accounts,
-            data: borsh::to_vec(&StakePoolInstruction::DepositMln(lamports_in))
-                .unwrap(),
+            data: borsh::to_vec(&StakePoolInstruction::DepositMln(lamports_in)).unwrap(),
         }
     }
 }
@@ -1997,8 +2102,7 @@ fn withdraw_stake_internal(
         Instruction {
             program_id: *program_id,
             accounts,
-            data: borsh::to_vec(&StakePoolInstruction::WithdrawStake(pool_tokens_in))
-                .unwrap(),
+            data: borsh::to_vec(&StakePoolInstruction::WithdrawStake(pool_tokens_in)).unwrap(),
         }
     }
 }
@@ -2118,8 +2222,7 @@ fn withdraw_mln_internal(
         Instruction {
             program_id: *program_id,
             accounts,
-            data: borsh::to_vec(&StakePoolInstruction::WithdrawMln(pool_tokens_in))
-                .unwrap(),
+            data: borsh::to_vec(&StakePoolInstruction::WithdrawMln(pool_tokens_in)).unwrap(),
         }
     }
 }
@@ -2334,8 +2437,7 @@ pub fn set_funding_authority(
     Instruction {
         program_id: *program_id,
         accounts,
-        data: borsh::to_vec(&StakePoolInstruction::SetFundingAuthority(funding_type))
-            .unwrap(),
+        data: borsh::to_vec(&StakePoolInstruction::SetFundingAuthority(funding_type)).unwrap(),
     }
 }
 
@@ -2404,3 +2506,142 @@ pub fn create_token_metadata(
             .unwrap(),
     }
 }
+
+/// Creates an instruction to create metadata in a Token-2022 pool mint, which
+/// must have a metadata pointer to itself
+pub fn create_token_metadata_in_mint(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    manager: &Pubkey,
+    pool_mint: &Pubkey,
+    payer: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let (stake_pool_withdraw_authority, _) =
+        find_withdraw_authority_program_address(program_id, stake_pool);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*stake_pool, false),
+        AccountMeta::new_readonly(*manager, true),
+        AccountMeta::new_readonly(stake_pool_withdraw_authority, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(spl_token_2022::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakePoolInstruction::CreateTokenMetadata { name, symbol, uri })
+            .unwrap(),
+    }
+}
+
+/// Creates an instruction to update the metadata held by a Token-2022 pool
+/// mint
+pub fn update_token_metadata_in_mint(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    manager: &Pubkey,
+    pool_mint: &Pubkey,
+    payer: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let (stake_pool_withdraw_authority, _) =
+        find_withdraw_authority_program_address(program_id, stake_pool);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*stake_pool, false),
+        AccountMeta::new_readonly(*manager, true),
+        AccountMeta::new_readonly(stake_pool_withdraw_authority, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new_readonly(spl_token_2022::id(), false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakePoolInstruction::UpdateTokenMetadata { name, symbol, uri })
+            .unwrap(),
+    }
+}
+
+/// Creates a `RequestWithdrawal` instruction, for the ticket derived from the
+/// owner and ticket seed
+pub fn request_withdrawal(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    pool_tokens_from: &Pubkey,
+    manager_fee_account: &Pubkey,
+    pool_mint: &Pubkey,
+    owner: &Pubkey,
+    token_program_id: &Pubkey,
+    mln_withdraw_authority: Option<&Pubkey>,
+    pool_tokens_in: u64,
+    ticket_seed: u64,
+) -> Instruction {
+    let (withdrawal_ticket, _) =
+        find_withdrawal_ticket_program_address(program_id, stake_pool, owner, ticket_seed);
+    let mut accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(withdrawal_ticket, false),
+        AccountMeta::new_readonly(*user_transfer_authority, true),
+        AccountMeta::new(*pool_tokens_from, false),
+        AccountMeta::new(*manager_fee_account, false),
+        AccountMeta::new(*pool_mint, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(mln_withdraw_authority) = mln_withdraw_authority {
+        accounts.push(AccountMeta::new_readonly(*mln_withdraw_authority, true));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakePoolInstruction::RequestWithdrawal {
+            pool_tokens_in,
+            ticket_seed,
+        })
+        .unwrap(),
+    }
+}
+
+/// Creates a `ClaimWithdrawal` instruction
+pub fn claim_withdrawal(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    stake_pool_withdraw_authority: &Pubkey,
+    withdrawal_ticket: &Pubkey,
+    owner: &Pubkey,
+    reserve_stake_account: &Pubkey,
+    lamports_to: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*stake_pool, false),
+        AccountMeta::new_readonly(*stake_pool_withdraw_authority, false),
+        AccountMeta::new(*withdrawal_ticket, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(*reserve_stake_account, false),
+        AccountMeta::new(*lamports_to, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(stake::program::id(), false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: borsh::to_vec(&StakePoolInstruction::ClaimWithdrawal).unwrap(),
+    }
+}
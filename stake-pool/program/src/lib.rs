@@ -33,6 +33,9 @@ const TRANSIENT_STAKE_SEED_PREFIX: &[u8] = b"transient";
 /// Seed for ephemeral stake account
 const EPHEMERAL_STAKE_SEED_PREFIX: &[u8] = b"ephemeral";
 
+/// Seed for withdrawal ticket account
+const WITHDRAWAL_TICKET_SEED_PREFIX: &[u8] = b"ticket";
+
 /// Minimum amount of staked lamports required in a validator stake account to
 /// allow for merges without a mismatch on credits observed
 pub const MINIMUM_ACTIVE_STAKE: u64 = 1_000_000;
@@ -61,6 +64,9 @@ pub const WITHDRAWAL_BASELINE_FEE: Fee = Fee {
 /// transaction account limits.
 pub const MAX_TRANSIENT_STAKE_ACCOUNTS: usize = 10;
 
+/// Maximum share of the pool lamports staked to a validator, in basis points
+pub const MAX_VALIDATOR_STAKE_SHARE_BPS: u16 = 10_000;
+
 /// Get the stake amount under consideration when calculating pool token
 /// conversions
 #[inline]
@@ -157,6 +163,24 @@ pub fn find_ephemeral_stake_program_address(
     )
 }
 
+/// Generates the withdrawal ticket program address for a ticket owner
+pub fn find_withdrawal_ticket_program_address(
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    owner: &Pubkey,
+    seed: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            WITHDRAWAL_TICKET_SEED_PREFIX,
+            stake_pool_address.as_ref(),
+            owner.as_ref(),
+            &seed.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
 solana_program::declare_id!("spooqgqqDxZgVc3pR6EvuVFZJ1kj7ABM4Hccz1gwAN1");
 
 #[cfg(test)]
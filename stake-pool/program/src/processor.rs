@@ -13,18 +13,18 @@ use {
         instruction::{FundingType, PreferredValidatorType, StakePoolInstruction},
         minimum_delegation, minimum_reserve_lamports, minimum_stake_lamports,
         state::{
-            is_extension_supported_for_mint, AccountType, Fee, FeeType, FutureEpoch, StakePool,
-            StakeStatus, StakeWithdrawSource, ValidatorList, ValidatorListHeader,
-            ValidatorStakeInfo,
+            check_mint_extension_policy, is_extension_supported_for_mint, AccountType, Fee,
+            FeeType, FutureEpoch, StakePool, StakeStatus, StakeWithdrawSource, ValidatorList,
+            ValidatorListHeader, ValidatorStakeInfo, WithdrawalTicket,
         },
         AUTHORITY_DEPOSIT, AUTHORITY_WITHDRAW, EPHEMERAL_STAKE_SEED_PREFIX,
-        TRANSIENT_STAKE_SEED_PREFIX,
+        MAX_VALIDATOR_STAKE_SHARE_BPS, TRANSIENT_STAKE_SEED_PREFIX, WITHDRAWAL_TICKET_SEED_PREFIX,
     },
     borsh::BorshDeserialize,
     num_traits::FromPrimitive,
     solana_program::{
         account_info::{next_account_info, AccountInfo},
-        borsh1::try_from_slice_unchecked,
+        borsh1::{get_packed_len, try_from_slice_unchecked},
         clock::{Clock, Epoch},
         decode_error::DecodeError,
         entrypoint::ProgramResult,
@@ -38,10 +38,13 @@ use {
     },
     spl_token_2022::{
         check_spl_token_program_account,
-        extension::{BaseStateWithExtensions, StateWithExtensions},
+        extension::{
+            metadata_pointer::MetadataPointer, BaseStateWithExtensions, StateWithExtensions,
+        },
         native_mint,
         state::Mint,
     },
+    spl_token_metadata_interface::state::{Field, TokenMetadata},
     std::num::NonZeroU32,
 };
 
@@ -133,6 +136,54 @@ fn check_mpl_metadata_account_address(
     }
 }
 
+/// Check that the metadata of a Token-2022 pool mint is held by the mint itself
+fn check_metadata_in_mint(
+    stake_pool: &StakePool,
+    metadata_info: &AccountInfo,
+) -> Result<(), ProgramError> {
+    if stake_pool.token_program_id != spl_token_2022::id()
+        || *metadata_info.key != stake_pool.pool_mint
+    {
+        msg!("Token metadata can only be held by a Token-2022 pool mint");
+        return Err(StakePoolError::InvalidMetadataAccount.into());
+    }
+    let mint_data = metadata_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    let metadata_address = mint
+        .get_extension::<MetadataPointer>()
+        .ok()
+        .and_then(|metadata_pointer| Option::<Pubkey>::from(metadata_pointer.metadata_address));
+    if metadata_address != Some(stake_pool.pool_mint) {
+        msg!("Pool mint metadata pointer does not point to the pool mint");
+        return Err(StakePoolError::InvalidMetadataAccount.into());
+    }
+    Ok(())
+}
+
+/// Transfer lamports from the payer so that the account stays rent-exempt
+/// once resized to `space`
+fn top_up_rent_exemption<'a>(
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+) -> ProgramResult {
+    let required_lamports = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account_info.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, required_lamports),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
 /// Check system program address
 fn check_system_program(program_id: &Pubkey) -> Result<(), ProgramError> {
     if *program_id != system_program::id() {
@@ -355,6 +406,41 @@ fn create_stake_account(
     )
 }
 
+/// Create an account on a PDA, funding its rent exemption from the payer
+fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    rent: &Rent,
+    space: usize,
+    owner: &Pubkey,
+    system_program: &AccountInfo<'a>,
+    new_pda_account: &AccountInfo<'a>,
+    new_pda_signer_seeds: &[&[u8]],
+) -> Result<(), ProgramError> {
+    let required_lamports = rent
+        .minimum_balance(space)
+        .saturating_sub(new_pda_account.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, new_pda_account.key, required_lamports),
+            &[
+                payer.clone(),
+                new_pda_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(new_pda_account.key, space as u64),
+        &[new_pda_account.clone(), system_program.clone()],
+        &[new_pda_signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(new_pda_account.key, owner),
+        &[new_pda_account.clone(), system_program.clone()],
+        &[new_pda_signer_seeds],
+    )
+}
+
 /// Program state handler.
 pub struct Processor {}
 impl Processor {
@@ -745,7 +831,7 @@ impl Processor {
         // This check is unnecessary since the runtime will check the ownership,
         // but provides clarity that the parameter is in fact checked.
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_uninitialized() {
             msg!("Provided stake pool already in use");
             return Err(StakePoolError::AlreadyInUse.into());
@@ -859,6 +945,7 @@ impl Processor {
             {
                 return Err(StakePoolError::UnsupportedMintExtension.into());
             }
+            check_mint_extension_policy(&pool_mint, &withdraw_authority_key)?;
         }
         stake_pool.check_manager_fee_info(manager_fee_info)?;
 
@@ -947,9 +1034,9 @@ impl Processor {
         stake_pool.next_mln_withdrawal_fee = FutureEpoch::None;
         stake_pool.last_epoch_pool_token_supply = 0;
         stake_pool.last_epoch_total_lamports = 0;
+        stake_pool.pending_withdrawal_lamports = 0;
 
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)
-            .map_err(|e| e.into())
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())
     }
 
     /// Processes `AddValidatorToPool` instruction.
@@ -980,7 +1067,7 @@ impl Processor {
         check_stake_program(stake_program_info.key)?;
 
         check_account_owner(stake_pool_info, program_id)?;
-        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -1052,7 +1139,10 @@ impl Processor {
             .meta()
             .ok_or(StakePoolError::WrongStakeStake)?;
         let minimum_lamports = minimum_reserve_lamports(&reserve_meta);
-        let reserve_lamports = reserve_stake_info.lamports();
+        // the lamports owed to withdrawal tickets are not available
+        let reserve_lamports = reserve_stake_info
+            .lamports()
+            .saturating_sub(stake_pool.pending_withdrawal_lamports);
         if reserve_lamports.saturating_sub(required_lamports) < minimum_lamports {
             msg!(
                 "Need to add {} lamports for the reserve stake to be rent-exempt after adding a validator, reserve currently has {} lamports",
@@ -1094,7 +1184,9 @@ impl Processor {
             transient_stake_lamports: 0.into(),
             last_update_epoch: clock.epoch.into(),
             transient_seed_suffix: 0.into(),
-            unused: 0.into(),
+            max_stake_share_bps: 0.into(),
+            stake_denied: false.into(),
+            unused: 0,
             validator_seed_suffix: raw_validator_seed.into(),
         })?;
 
@@ -1121,7 +1213,7 @@ impl Processor {
         check_stake_program(stake_program_info.key)?;
         check_account_owner(stake_pool_info, program_id)?;
 
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -1234,7 +1326,7 @@ impl Processor {
         if stake_pool.preferred_withdraw_validator_vote_address == Some(vote_account_address) {
             stake_pool.preferred_withdraw_validator_vote_address = None;
         }
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
 
         Ok(())
     }
@@ -1279,7 +1371,7 @@ impl Processor {
         check_stake_program(stake_program_info.key)?;
         check_account_owner(stake_pool_info, program_id)?;
 
-        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             msg!("Expected valid stake pool");
             return Err(StakePoolError::InvalidState.into());
@@ -1404,7 +1496,11 @@ impl Processor {
                     let required_lamports_for_rent_exemption =
                         stake_rent.saturating_sub(ephemeral_stake_account_info.lamports());
                     if required_lamports_for_rent_exemption > 0 {
-                        if required_lamports_for_rent_exemption >= reserve_stake_info.lamports() {
+                        if required_lamports_for_rent_exemption
+                            >= reserve_stake_info
+                                .lamports()
+                                .saturating_sub(stake_pool.pending_withdrawal_lamports)
+                        {
                             return Err(StakePoolError::ReserveDepleted.into());
                         }
                         let stake_history_info = maybe_stake_history_info
@@ -1500,7 +1596,11 @@ impl Processor {
                 if source_stake_account_info.lamports() != split_lamports {
                     let stake_history_info =
                         maybe_stake_history_info.ok_or(StakePoolError::MissingRequiredSysvar)?;
-                    if required_lamports >= reserve_stake_info.lamports() {
+                    if required_lamports
+                        >= reserve_stake_info
+                            .lamports()
+                            .saturating_sub(stake_pool.pending_withdrawal_lamports)
+                    {
                         return Err(StakePoolError::ReserveDepleted.into());
                     }
                     if required_lamports > 0 {
@@ -1596,7 +1696,7 @@ impl Processor {
         check_stake_program(stake_program_info.key)?;
         check_account_owner(stake_pool_info, program_id)?;
 
-        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             msg!("Expected valid stake pool");
             return Err(StakePoolError::InvalidState.into());
@@ -1670,6 +1770,13 @@ impl Processor {
             msg!("Validator is marked for removal and no longer allows increases");
             return Err(StakePoolError::ValidatorNotFound.into());
         }
+        validator_stake_info.check_new_stake(
+            validator_stake_info
+                .stake_lamports()?
+                .checked_add(lamports)
+                .ok_or(StakePoolError::CalculationFailure)?,
+            stake_pool.total_lamports,
+        )?;
 
         let stake_space = std::mem::size_of::<stake::state::StakeStateV2>();
         let stake_rent = rent.minimum_balance(stake_space);
@@ -1691,14 +1798,13 @@ impl Processor {
         // with `lamports + stake_rent`
         let total_lamports = lamports.saturating_add(stake_rent);
 
-        if reserve_stake_account_info
+        // the lamports owed to withdrawal tickets stay in the reserve
+        let available_reserve_lamports = reserve_stake_account_info
             .lamports()
-            .saturating_sub(total_lamports)
-            < stake_rent
-        {
-            let max_split_amount = reserve_stake_account_info
-                .lamports()
-                .saturating_sub(stake_rent.saturating_mul(2));
+            .saturating_sub(stake_pool.pending_withdrawal_lamports);
+        if available_reserve_lamports.saturating_sub(total_lamports) < stake_rent {
+            let max_split_amount =
+                available_reserve_lamports.saturating_sub(stake_rent.saturating_mul(2));
             msg!(
                 "Reserve stake does not have enough lamports for increase, maximum amount {}, {} requested",
                 max_split_amount,
@@ -1875,7 +1981,7 @@ impl Processor {
         check_stake_program(stake_program_info.key)?;
         check_account_owner(stake_pool_info, program_id)?;
 
-        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             msg!("Expected valid stake pool");
             return Err(StakePoolError::InvalidState.into());
@@ -2013,7 +2119,11 @@ impl Processor {
             let required_lamports_for_rent_exemption =
                 stake_rent.saturating_sub(source_transient_stake_account_info.lamports());
             if required_lamports_for_rent_exemption > 0 {
-                if required_lamports_for_rent_exemption >= reserve_stake_info.lamports() {
+                if required_lamports_for_rent_exemption
+                    >= reserve_stake_info
+                        .lamports()
+                        .saturating_sub(stake_pool.pending_withdrawal_lamports)
+                {
                     return Err(StakePoolError::ReserveDepleted.into());
                 }
                 Self::stake_withdraw(
@@ -2100,6 +2210,13 @@ impl Processor {
                 );
                 return Err(StakePoolError::ValidatorNotFound.into());
             }
+            validator_stake_info.check_new_stake(
+                validator_stake_info
+                    .stake_lamports()?
+                    .checked_add(lamports)
+                    .ok_or(StakePoolError::CalculationFailure)?,
+                stake_pool.total_lamports,
+            )?;
             let transient_account_exists =
                 u64::from(validator_stake_info.transient_stake_lamports) > 0;
             validator_stake_info.transient_stake_lamports =
@@ -2198,7 +2315,7 @@ impl Processor {
         check_account_owner(stake_pool_info, program_id)?;
         check_account_owner(validator_list_info, program_id)?;
 
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             msg!("Expected valid stake pool");
             return Err(StakePoolError::InvalidState.into());
@@ -2240,7 +2357,65 @@ impl Processor {
                 stake_pool.preferred_withdraw_validator_vote_address = vote_account_address
             }
         };
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes `SetValidatorStakePolicy` instruction.
+    #[inline(never)] // needed due to stack size violation
+    fn process_set_validator_stake_policy(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        vote_account_address: &Pubkey,
+        max_stake_share_bps: u16,
+        stake_denied: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let staker_info = next_account_info(account_info_iter)?;
+        let validator_list_info = next_account_info(account_info_iter)?;
+
+        check_account_owner(stake_pool_info, program_id)?;
+        check_account_owner(validator_list_info, program_id)?;
+
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
+        if !stake_pool.is_valid() {
+            msg!("Expected valid stake pool");
+            return Err(StakePoolError::InvalidState.into());
+        }
+
+        stake_pool.check_staker(staker_info)?;
+        stake_pool.check_validator_list(validator_list_info)?;
+
+        if max_stake_share_bps > MAX_VALIDATOR_STAKE_SHARE_BPS {
+            msg!(
+                "Stake share of {} basis points is above the maximum of {}",
+                max_stake_share_bps,
+                MAX_VALIDATOR_STAKE_SHARE_BPS
+            );
+            return Err(StakePoolError::InvalidStakeShare.into());
+        }
+
+        let mut validator_list_data = validator_list_info.data.borrow_mut();
+        let (header, mut validator_list) =
+            ValidatorListHeader::deserialize_vec(&mut validator_list_data)?;
+        if !header.is_valid() {
+            return Err(StakePoolError::InvalidState.into());
+        }
+
+        let validator_stake_info = validator_list
+            .find_mut::<ValidatorStakeInfo, _>(|x| {
+                ValidatorStakeInfo::memcmp_pubkey(x, vote_account_address)
+            })
+            .ok_or_else(|| {
+                msg!(
+                    "Vote account {} not found in stake pool",
+                    vote_account_address
+                );
+                StakePoolError::ValidatorNotFound
+            })?;
+        validator_stake_info.max_stake_share_bps = max_stake_share_bps.into();
+        validator_stake_info.stake_denied = stake_denied.into();
         Ok(())
     }
 
@@ -2264,7 +2439,7 @@ impl Processor {
         let validator_stake_accounts = account_info_iter.as_slice();
 
         check_account_owner(stake_pool_info, program_id)?;
-        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -2555,7 +2730,7 @@ impl Processor {
         let clock = Clock::get()?;
 
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -2596,6 +2771,10 @@ impl Processor {
                 msg!("Reserve stake account in unknown state, aborting");
                 return Err(StakePoolError::WrongStakeStake.into());
             };
+        // the lamports owed to withdrawal tickets no longer belong to the pool
+        total_lamports = total_lamports
+            .checked_sub(stake_pool.pending_withdrawal_lamports)
+            .ok_or(StakePoolError::CalculationFailure)?;
         for validator_stake_record in validator_list
             .deserialize_slice::<ValidatorStakeInfo>(0, validator_list.len() as usize)?
         {
@@ -2657,7 +2836,7 @@ impl Processor {
         let pool_mint = StateWithExtensions::<Mint>::unpack(&pool_mint_data)?;
         stake_pool.pool_token_supply = pool_mint.base.supply;
 
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
 
         Ok(())
     }
@@ -2673,7 +2852,7 @@ impl Processor {
         let validator_list_info = next_account_info(account_info_iter)?;
 
         check_account_owner(stake_pool_info, program_id)?;
-        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -2720,7 +2899,7 @@ impl Processor {
         check_stake_program(stake_program_info.key)?;
 
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -2839,6 +3018,16 @@ impl Processor {
         let mln_deposit_lamports = total_deposit_lamports
             .checked_sub(stake_deposit_lamports)
             .ok_or(StakePoolError::CalculationFailure)?;
+        validator_stake_info.check_new_stake(
+            validator_stake_info
+                .stake_lamports()?
+                .checked_add(total_deposit_lamports)
+                .ok_or(StakePoolError::CalculationFailure)?,
+            stake_pool
+                .total_lamports
+                .checked_add(total_deposit_lamports)
+                .ok_or(StakePoolError::CalculationFailure)?,
+        )?;
 
         let new_pool_tokens = stake_pool
             .calc_pool_tokens_for_deposit(total_deposit_lamports)
@@ -2950,7 +3139,7 @@ impl Processor {
             .total_lamports
             .checked_add(total_deposit_lamports)
             .ok_or(StakePoolError::CalculationFailure)?;
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
 
         validator_stake_info.active_stake_lamports = validator_stake_account_info.lamports().into();
 
@@ -2981,7 +3170,7 @@ impl Processor {
         let clock = Clock::get()?;
 
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -3100,7 +3289,7 @@ impl Processor {
             .total_lamports
             .checked_add(deposit_lamports)
             .ok_or(StakePoolError::CalculationFailure)?;
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
 
         Ok(())
     }
@@ -3131,7 +3320,7 @@ impl Processor {
 
         check_stake_program(stake_program_info.key)?;
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -3235,6 +3424,20 @@ impl Processor {
                 .lamports()
                 .checked_sub(minimum_reserve_lamports(&meta))
                 .ok_or(StakePoolError::StakeLamportsNotEqualToMinimum)?;
+
+            // the lamports owed to withdrawal tickets stay in the reserve
+            let available_reserve_lamports = stake_split_from
+                .lamports()
+                .saturating_sub(minimum_reserve_lamports(&meta))
+                .saturating_sub(stake_pool.pending_withdrawal_lamports);
+            if withdraw_lamports > available_reserve_lamports {
+                msg!(
+                    "Attempting to withdraw {} lamports from the reserve, maximum possible is {} lamports",
+                    withdraw_lamports,
+                    available_reserve_lamports
+                );
+                return Err(StakePoolError::ReserveDepleted.into());
+            }
             None
         } else {
             let delegation = stake_state
@@ -3381,7 +3584,7 @@ impl Processor {
             .total_lamports
             .checked_sub(withdraw_lamports)
             .ok_or(StakePoolError::CalculationFailure)?;
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
 
         if let Some((validator_list_item, withdraw_source)) = validator_list_item_info {
             match withdraw_source {
@@ -3444,7 +3647,7 @@ impl Processor {
         let mln_withdraw_authority_info = next_account_info(account_info_iter);
 
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -3510,7 +3713,9 @@ impl Processor {
             &reserve_stake_info.data.borrow(),
         )?;
         if let stake::state::StakeStateV2::Initialized(meta) = stake_state {
-            let minimum_reserve_lamports = minimum_reserve_lamports(&meta);
+            // the lamports owed to withdrawal tickets stay in the reserve
+            let minimum_reserve_lamports = minimum_reserve_lamports(&meta)
+                .saturating_add(stake_pool.pending_withdrawal_lamports);
             if new_reserve_lamports < minimum_reserve_lamports {
                 msg!("Attempting to withdraw {} lamports, maximum possible MLN withdrawal is {} lamports",
                     withdraw_lamports,
@@ -3563,7 +3768,297 @@ impl Processor {
             .total_lamports
             .checked_sub(withdraw_lamports)
             .ok_or(StakePoolError::CalculationFailure)?;
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [RequestWithdrawal](enum.Instruction.html).
+    #[inline(never)] // needed to avoid stack size violation
+    fn process_request_withdrawal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        pool_tokens: u64,
+        ticket_seed: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let withdraw_authority_info = next_account_info(account_info_iter)?;
+        let withdrawal_ticket_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let pool_tokens_from_info = next_account_info(account_info_iter)?;
+        let manager_fee_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let mln_withdraw_authority_info = next_account_info(account_info_iter);
+        let clock = Clock::get()?;
+        let rent = Rent::get()?;
+
+        check_account_owner(stake_pool_info, program_id)?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
+        if !stake_pool.is_valid() {
+            return Err(StakePoolError::InvalidState.into());
+        }
+
+        stake_pool.check_authority_withdraw(
+            withdraw_authority_info.key,
+            program_id,
+            stake_pool_info.key,
+        )?;
+        stake_pool.check_mln_withdraw_authority(mln_withdraw_authority_info)?;
+        let decimals = stake_pool.check_mint(pool_mint_info)?;
+
+        if stake_pool.token_program_id != *token_program_info.key {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        check_system_program(system_program_info.key)?;
+
+        if stake_pool.manager_fee_account != *manager_fee_info.key {
+            return Err(StakePoolError::InvalidFeeAccount.into());
+        }
+
+        // Same as `WithdrawMln`, the pool tokens are burnt at the rate of the
+        // request, which must be up to date
+        if stake_pool.last_update_epoch < clock.epoch {
+            return Err(StakePoolError::StakeListAndPoolOutOfDate.into());
+        }
+
+        if !owner_info.is_signer {
+            msg!("Withdrawal ticket owner signature missing");
+            return Err(StakePoolError::SignatureMissing.into());
+        }
+
+        let (withdrawal_ticket_address, ticket_bump_seed) =
+            crate::find_withdrawal_ticket_program_address(
+                program_id,
+                stake_pool_info.key,
+                owner_info.key,
+                ticket_seed,
+            );
+        if withdrawal_ticket_address != *withdrawal_ticket_info.key {
+            msg!(
+                "Incorrect withdrawal ticket address, expected {}, received {}",
+                withdrawal_ticket_address,
+                withdrawal_ticket_info.key
+            );
+            return Err(StakePoolError::InvalidProgramAddress.into());
+        }
+        if !withdrawal_ticket_info.data_is_empty() {
+            msg!(
+                "Withdrawal ticket {} already exists",
+                withdrawal_ticket_address
+            );
+            return Err(StakePoolError::AlreadyInUse.into());
+        }
+
+        // Same as `WithdrawMln`, a faulty manager fee account should not
+        // prevent withdrawals
+        let pool_tokens_fee = if stake_pool.manager_fee_account == *pool_tokens_from_info.key
+            || stake_pool.check_manager_fee_info(manager_fee_info).is_err()
+        {
+            0
+        } else {
+            stake_pool
+                .calc_pool_tokens_mln_withdrawal_fee(pool_tokens)
+                .ok_or(StakePoolError::CalculationFailure)?
+        };
+        let pool_tokens_burnt = pool_tokens
+            .checked_sub(pool_tokens_fee)
+            .ok_or(StakePoolError::CalculationFailure)?;
+
+        let withdraw_lamports = stake_pool
+            .calc_lamports_withdraw_amount(pool_tokens_burnt)
+            .ok_or(StakePoolError::CalculationFailure)?;
+
+        if withdraw_lamports == 0 {
+            return Err(StakePoolError::WithdrawalTooSmall.into());
+        }
+
+        Self::token_burn(
+            token_program_info.clone(),
+            pool_tokens_from_info.clone(),
+            pool_mint_info.clone(),
+            user_transfer_authority_info.clone(),
+            pool_tokens_burnt,
+        )?;
+
+        if pool_tokens_fee > 0 {
+            Self::token_transfer(
+                token_program_info.clone(),
+                pool_tokens_from_info.clone(),
+                pool_mint_info.clone(),
+                manager_fee_info.clone(),
+                user_transfer_authority_info.clone(),
+                pool_tokens_fee,
+                decimals,
+            )?;
+        }
+
+        create_pda_account(
+            owner_info,
+            &rent,
+            get_packed_len::<WithdrawalTicket>(),
+            program_id,
+            system_program_info,
+            withdrawal_ticket_info,
+            &[
+                WITHDRAWAL_TICKET_SEED_PREFIX,
+                stake_pool_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &ticket_seed.to_le_bytes(),
+                &[ticket_bump_seed],
+            ],
+        )?;
+        let withdrawal_ticket = WithdrawalTicket {
+            account_type: AccountType::WithdrawalTicket,
+            stake_pool: *stake_pool_info.key,
+            owner: *owner_info.key,
+            lamports: withdraw_lamports,
+            request_epoch: clock.epoch,
+        };
+        borsh::to_writer(
+            &mut withdrawal_ticket_info.data.borrow_mut()[..],
+            &withdrawal_ticket,
+        )?;
+
+        // The lamports owed to the ticket stay in the reserve until claimed,
+        // but no longer belong to the pool token holders
+        stake_pool.pool_token_supply = stake_pool
+            .pool_token_supply
+            .checked_sub(pool_tokens_burnt)
+            .ok_or(StakePoolError::CalculationFailure)?;
+        stake_pool.total_lamports = stake_pool
+            .total_lamports
+            .checked_sub(withdraw_lamports)
+            .ok_or(StakePoolError::CalculationFailure)?;
+        stake_pool.pending_withdrawal_lamports = stake_pool
+            .pending_withdrawal_lamports
+            .checked_add(withdraw_lamports)
+            .ok_or(StakePoolError::CalculationFailure)?;
+
+        // Pools created before `pending_withdrawal_lamports` was added have no
+        // room for it, so they grow on their first request, at the owner's
+        // expense
+        let stake_pool_len = get_packed_len::<StakePool>();
+        if stake_pool_info.data_len() < stake_pool_len {
+            top_up_rent_exemption(
+                owner_info,
+                stake_pool_info,
+                system_program_info,
+                stake_pool_len,
+            )?;
+            stake_pool_info.realloc(stake_pool_len, true)?;
+        }
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes [ClaimWithdrawal](enum.Instruction.html).
+    #[inline(never)] // needed to avoid stack size violation
+    fn process_claim_withdrawal(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let withdraw_authority_info = next_account_info(account_info_iter)?;
+        let withdrawal_ticket_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let reserve_stake_info = next_account_info(account_info_iter)?;
+        let destination_lamports_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+        let stake_history_info = next_account_info(account_info_iter)?;
+        let stake_program_info = next_account_info(account_info_iter)?;
+        let clock = Clock::get()?;
+
+        check_account_owner(stake_pool_info, program_id)?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
+        if !stake_pool.is_valid() {
+            return Err(StakePoolError::InvalidState.into());
+        }
+
+        stake_pool.check_authority_withdraw(
+            withdraw_authority_info.key,
+            program_id,
+            stake_pool_info.key,
+        )?;
+        stake_pool.check_reserve_stake(reserve_stake_info)?;
+        check_stake_program(stake_program_info.key)?;
+
+        if !owner_info.is_signer {
+            msg!("Withdrawal ticket owner signature missing");
+            return Err(StakePoolError::SignatureMissing.into());
+        }
+
+        check_account_owner(withdrawal_ticket_info, program_id)?;
+        let withdrawal_ticket =
+            try_from_slice_unchecked::<WithdrawalTicket>(&withdrawal_ticket_info.data.borrow())?;
+        if !withdrawal_ticket.is_valid()
+            || withdrawal_ticket.stake_pool != *stake_pool_info.key
+            || withdrawal_ticket.owner != *owner_info.key
+        {
+            return Err(StakePoolError::InvalidWithdrawalTicket.into());
+        }
+        if !withdrawal_ticket.is_claimable(clock.epoch) {
+            msg!(
+                "Withdrawal ticket requested in epoch {} cannot be claimed before epoch {}",
+                withdrawal_ticket.request_epoch,
+                withdrawal_ticket.request_epoch.saturating_add(1)
+            );
+            return Err(StakePoolError::WithdrawalTicketNotReady.into());
+        }
+
+        let withdraw_lamports = withdrawal_ticket.lamports;
+        let new_reserve_lamports = reserve_stake_info
+            .lamports()
+            .saturating_sub(withdraw_lamports);
+        let stake_state = try_from_slice_unchecked::<stake::state::StakeStateV2>(
+            &reserve_stake_info.data.borrow(),
+        )?;
+        if let stake::state::StakeStateV2::Initialized(meta) = stake_state {
+            let minimum_reserve_lamports = minimum_reserve_lamports(&meta);
+            if new_reserve_lamports < minimum_reserve_lamports {
+                msg!(
+                    "Attempting to claim {} lamports, only {} lamports available in the reserve",
+                    withdraw_lamports,
+                    reserve_stake_info
+                        .lamports()
+                        .saturating_sub(minimum_reserve_lamports)
+                );
+                return Err(StakePoolError::MlnWithdrawalTooLarge.into());
+            }
+        } else {
+            msg!("Reserve stake account not in intialized state");
+            return Err(StakePoolError::WrongStakeStake.into());
+        };
+
+        Self::stake_withdraw(
+            stake_pool_info.key,
+            reserve_stake_info.clone(),
+            withdraw_authority_info.clone(),
+            AUTHORITY_WITHDRAW,
+            stake_pool.stake_withdraw_bump_seed,
+            destination_lamports_info.clone(),
+            clock_info.clone(),
+            stake_history_info.clone(),
+            withdraw_lamports,
+        )?;
+
+        // The ticket lamports already left `total_lamports` on request
+        stake_pool.pending_withdrawal_lamports = stake_pool
+            .pending_withdrawal_lamports
+            .checked_sub(withdraw_lamports)
+            .ok_or(StakePoolError::CalculationFailure)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
+
+        // Close the ticket, returning its rent to the owner
+        let ticket_lamports = withdrawal_ticket_info.lamports();
+        **withdrawal_ticket_info.lamports.borrow_mut() = 0;
+        **owner_info.lamports.borrow_mut() = owner_info
+            .lamports()
+            .checked_add(ticket_lamports)
+            .ok_or(StakePoolError::CalculationFailure)?;
+        withdrawal_ticket_info.data.borrow_mut().fill(0);
 
         Ok(())
     }
@@ -3594,9 +4089,8 @@ impl Processor {
         check_system_program(system_program_info.key)?;
         check_account_owner(payer_info, &system_program::id())?;
         check_account_owner(stake_pool_info, program_id)?;
-        check_mpl_metadata_program(mpl_token_metadata_program_info.key)?;
 
-        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -3608,11 +4102,62 @@ impl Processor {
             stake_pool_info.key,
         )?;
         stake_pool.check_mint(pool_mint_info)?;
-        check_mpl_metadata_account_address(metadata_info.key, &stake_pool.pool_mint)?;
 
         // Token mint authority for stake-pool token is stake-pool withdraw authority
         let token_mint_authority = withdraw_authority_info;
 
+        let (_, stake_withdraw_bump_seed) =
+            crate::find_withdraw_authority_program_address(program_id, stake_pool_info.key);
+
+        let token_mint_authority_signer_seeds: &[&[_]] = &[
+            stake_pool_info.key.as_ref(),
+            AUTHORITY_WITHDRAW,
+            &[stake_withdraw_bump_seed],
+        ];
+
+        // A Token-2022 pool mint holds its own metadata, written by the token
+        // program, with the withdraw authority as update authority
+        if *mpl_token_metadata_program_info.key == spl_token_2022::id() {
+            check_metadata_in_mint(&stake_pool, metadata_info)?;
+
+            let token_metadata = TokenMetadata {
+                update_authority: Some(*token_mint_authority.key).try_into()?,
+                mint: *pool_mint_info.key,
+                name: name.clone(),
+                symbol: symbol.clone(),
+                uri: uri.clone(),
+                additional_metadata: vec![],
+            };
+            let space = pool_mint_info
+                .data_len()
+                .checked_add(token_metadata.tlv_size_of()?)
+                .ok_or(StakePoolError::CalculationFailure)?;
+            top_up_rent_exemption(payer_info, pool_mint_info, system_program_info, space)?;
+
+            invoke_signed(
+                &spl_token_metadata_interface::instruction::initialize(
+                    mpl_token_metadata_program_info.key,
+                    pool_mint_info.key,
+                    token_mint_authority.key,
+                    pool_mint_info.key,
+                    token_mint_authority.key,
+                    name,
+                    symbol,
+                    uri,
+                ),
+                &[
+                    pool_mint_info.clone(),
+                    withdraw_authority_info.clone(),
+                    mpl_token_metadata_program_info.clone(),
+                ],
+                &[token_mint_authority_signer_seeds],
+            )?;
+            return Ok(());
+        }
+
+        check_mpl_metadata_program(mpl_token_metadata_program_info.key)?;
+        check_mpl_metadata_account_address(metadata_info.key, &stake_pool.pool_mint)?;
+
         let new_metadata_instruction = create_metadata_accounts_v3(
             *mpl_token_metadata_program_info.key,
             *metadata_info.key,
@@ -3625,15 +4170,6 @@ impl Processor {
             uri,
         );
 
-        let (_, stake_withdraw_bump_seed) =
-            crate::find_withdraw_authority_program_address(program_id, stake_pool_info.key);
-
-        let token_mint_authority_signer_seeds: &[&[_]] = &[
-            stake_pool_info.key.as_ref(),
-            AUTHORITY_WITHDRAW,
-            &[stake_withdraw_bump_seed],
-        ];
-
         invoke_signed(
             &new_metadata_instruction,
             &[
@@ -3668,9 +4204,7 @@ impl Processor {
 
         check_account_owner(stake_pool_info, program_id)?;
 
-        check_mpl_metadata_program(mpl_token_metadata_program_info.key)?;
-
-        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -3681,11 +4215,80 @@ impl Processor {
             program_id,
             stake_pool_info.key,
         )?;
-        check_mpl_metadata_account_address(metadata_info.key, &stake_pool.pool_mint)?;
 
         // Token mint authority for stake-pool token is withdraw authority only
         let token_mint_authority = withdraw_authority_info;
 
+        let (_, stake_withdraw_bump_seed) =
+            crate::find_withdraw_authority_program_address(program_id, stake_pool_info.key);
+
+        let token_mint_authority_signer_seeds: &[&[_]] = &[
+            stake_pool_info.key.as_ref(),
+            AUTHORITY_WITHDRAW,
+            &[stake_withdraw_bump_seed],
+        ];
+
+        if *mpl_token_metadata_program_info.key == spl_token_2022::id() {
+            let payer_info = next_account_info(account_info_iter)?;
+            let system_program_info = next_account_info(account_info_iter)?;
+            if !payer_info.is_signer {
+                msg!("Payer did not sign metadata update");
+                return Err(StakePoolError::SignatureMissing.into());
+            }
+            check_system_program(system_program_info.key)?;
+            check_metadata_in_mint(&stake_pool, metadata_info)?;
+
+            let fields = [
+                (Field::Name, name),
+                (Field::Symbol, symbol),
+                (Field::Uri, uri),
+            ];
+
+            // Each field update resizes the mint, so fund the largest
+            // intermediate size up front
+            let mut token_metadata = {
+                let mint_data = metadata_info.try_borrow_data()?;
+                let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+                mint.get_variable_len_extension::<TokenMetadata>()?
+            };
+            let base_len = metadata_info
+                .data_len()
+                .checked_sub(token_metadata.tlv_size_of()?)
+                .ok_or(StakePoolError::CalculationFailure)?;
+            let mut space = metadata_info.data_len();
+            for (field, value) in fields.iter() {
+                token_metadata.update(field.clone(), value.clone());
+                space = space.max(
+                    base_len
+                        .checked_add(token_metadata.tlv_size_of()?)
+                        .ok_or(StakePoolError::CalculationFailure)?,
+                );
+            }
+            top_up_rent_exemption(payer_info, metadata_info, system_program_info, space)?;
+
+            for (field, value) in fields {
+                invoke_signed(
+                    &spl_token_metadata_interface::instruction::update_field(
+                        mpl_token_metadata_program_info.key,
+                        metadata_info.key,
+                        token_mint_authority.key,
+                        field,
+                        value,
+                    ),
+                    &[
+                        metadata_info.clone(),
+                        withdraw_authority_info.clone(),
+                        mpl_token_metadata_program_info.clone(),
+                    ],
+                    &[token_mint_authority_signer_seeds],
+                )?;
+            }
+            return Ok(());
+        }
+
+        check_mpl_metadata_program(mpl_token_metadata_program_info.key)?;
+        check_mpl_metadata_account_address(metadata_info.key, &stake_pool.pool_mint)?;
+
         let update_metadata_accounts_instruction = update_metadata_accounts_v2(
             *mpl_token_metadata_program_info.key,
             *metadata_info.key,
@@ -3704,15 +4307,6 @@ impl Processor {
             Some(true),
         );
 
-        let (_, stake_withdraw_bump_seed) =
-            crate::find_withdraw_authority_program_address(program_id, stake_pool_info.key);
-
-        let token_mint_authority_signer_seeds: &[&[_]] = &[
-            stake_pool_info.key.as_ref(),
-            AUTHORITY_WITHDRAW,
-            &[stake_withdraw_bump_seed],
-        ];
-
         invoke_signed(
             &update_metadata_accounts_instruction,
             &[metadata_info.clone(), withdraw_authority_info.clone()],
@@ -3732,7 +4326,7 @@ impl Processor {
         let new_manager_fee_info = next_account_info(account_info_iter)?;
 
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         check_account_owner(new_manager_fee_info, &stake_pool.token_program_id)?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
@@ -3748,7 +4342,7 @@ impl Processor {
 
         stake_pool.manager = *new_manager_info.key;
         stake_pool.manager_fee_account = *new_manager_fee_info.key;
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
         Ok(())
     }
 
@@ -3765,7 +4359,7 @@ impl Processor {
         let clock = Clock::get()?;
 
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -3777,7 +4371,7 @@ impl Processor {
 
         fee.check_too_high()?;
         stake_pool.update_fee(&fee)?;
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
         Ok(())
     }
 
@@ -3790,7 +4384,7 @@ impl Processor {
         let new_staker_info = next_account_info(account_info_iter)?;
 
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -3801,7 +4395,7 @@ impl Processor {
             return Err(StakePoolError::SignatureMissing.into());
         }
         stake_pool.staker = *new_staker_info.key;
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
         Ok(())
     }
 
@@ -3821,7 +4415,7 @@ impl Processor {
             .map(|new_authority_account_info| *new_authority_account_info.key);
 
         check_account_owner(stake_pool_info, program_id)?;
-        let mut stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())?;
+        let mut stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
         if !stake_pool.is_valid() {
             return Err(StakePoolError::InvalidState.into());
         }
@@ -3835,7 +4429,7 @@ impl Processor {
             FundingType::MlnDeposit => stake_pool.mln_deposit_authority = new_authority,
             FundingType::MlnWithdraw => stake_pool.mln_withdraw_authority = new_authority,
         }
-        borsh::to_writer(&mut stake_pool_info.data.borrow_mut()[..], &stake_pool)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut())?;
         Ok(())
     }
 
@@ -4070,6 +4664,31 @@ impl Processor {
                     Some(minimum_lamports_out),
                 )
             }
+            StakePoolInstruction::SetValidatorStakePolicy {
+                vote_account_address,
+                max_stake_share_bps,
+                stake_denied,
+            } => {
+                msg!("Instruction: SetValidatorStakePolicy");
+                Self::process_set_validator_stake_policy(
+                    program_id,
+                    accounts,
+                    &vote_account_address,
+                    max_stake_share_bps,
+                    stake_denied,
+                )
+            }
+            StakePoolInstruction::RequestWithdrawal {
+                pool_tokens_in,
+                ticket_seed,
+            } => {
+                msg!("Instruction: RequestWithdrawal");
+                Self::process_request_withdrawal(program_id, accounts, pool_tokens_in, ticket_seed)
+            }
+            StakePoolInstruction::ClaimWithdrawal => {
+                msg!("Instruction: ClaimWithdrawal");
+                Self::process_claim_withdrawal(program_id, accounts)
+            }
         }
     }
 }
@@ -4125,6 +4744,11 @@ impl PrintProgramError for StakePoolError {
             StakePoolError::IncorrectMintDecimals => msg!("Error: Provided mint does not have 9 decimals to match MLN"),
             StakePoolError::ReserveDepleted => msg!("Error: Pool reserve does not have enough lamports to fund rent-exempt reserve in split destination. Deposit more MLN in reserve, or pre-fund split destination with the rent-exempt reserve for a stake account."),
             StakePoolError::MissingRequiredSysvar => msg!("Missing required sysvar account"),
+            StakePoolError::ValidatorStakeDenied => msg!("Error: The staker denied new stake to the validator"),
+            StakePoolError::ValidatorStakeCapExceeded => msg!("Error: The new stake would take the validator over its share of the pool"),
+            StakePoolError::InvalidStakeShare => msg!("Error: Provided stake share is above 100%"),
+            StakePoolError::InvalidWithdrawalTicket => msg!("Error: Withdrawal ticket does not belong to the stake pool or the owner"),
+            StakePoolError::WithdrawalTicketNotReady => msg!("Error: Withdrawal ticket can only be claimed from the epoch after its request"),
         }
     }
 }
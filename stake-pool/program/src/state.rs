@@ -2,8 +2,8 @@
 
 use {
     crate::{
-        big_vec::BigVec, error::StakePoolError, MAX_WITHDRAWAL_FEE_INCREASE,
-        WITHDRAWAL_BASELINE_FEE,
+        big_vec::BigVec, error::StakePoolError, MAX_VALIDATOR_STAKE_SHARE_BPS,
+        MAX_WITHDRAWAL_FEE_INCREASE, WITHDRAWAL_BASELINE_FEE,
     },
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
     bytemuck::{Pod, Zeroable},
@@ -11,7 +11,7 @@ use {
     num_traits::{FromPrimitive, ToPrimitive},
     solana_program::{
         account_info::AccountInfo,
-        borsh1::get_instance_packed_len,
+        borsh1::{get_instance_packed_len, get_packed_len, try_from_slice_unchecked},
         msg,
         program_error::ProgramError,
         program_memory::sol_memcmp,
@@ -19,9 +19,12 @@ use {
         pubkey::{Pubkey, PUBKEY_BYTES},
         stake::state::Lockup,
     },
-    spl_pod::primitives::{PodU32, PodU64},
+    spl_pod::primitives::{PodBool, PodU16, PodU32, PodU64},
     spl_token_2022::{
-        extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+        extension::{
+            permanent_delegate::get_permanent_delegate, transfer_hook::TransferHook,
+            BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+        },
         state::{Account, AccountState, Mint},
     },
     std::{borrow::Borrow, convert::TryFrom, fmt, matches},
@@ -37,6 +40,8 @@ pub enum AccountType {
     StakePool,
     /// Validator stake list
     ValidatorList,
+    /// Withdrawal ticket
+    WithdrawalTicket,
 }
 
 /// Initialized program details.
@@ -156,8 +161,49 @@ pub struct StakePool {
 
     /// Last epoch's total lamports, used only for APR estimation
     pub last_epoch_total_lamports: u64,
+
+    /// Lamports owed to the withdrawal tickets that have not been claimed
+    /// yet, kept in the reserve but no longer counted in `total_lamports`
+    pub pending_withdrawal_lamports: u64,
 }
 impl StakePool {
+    /// Deserialize the stake pool from its account data.
+    ///
+    /// Accounts created before `pending_withdrawal_lamports` was added are
+    /// shorter than the packed length, and the bytes after their last field
+    /// may be left over from a longer serialization, so they are read as
+    /// owing nothing to withdrawal tickets. `RequestWithdrawal` resizes them
+    /// before adding anything.
+    pub fn unpack_from_slice(data: &[u8]) -> Result<Self, ProgramError> {
+        let packed_len = get_packed_len::<Self>();
+        if data.len() >= packed_len {
+            return Ok(try_from_slice_unchecked(data)?);
+        }
+        let mut padded_data = data.to_vec();
+        padded_data.resize(packed_len, 0);
+        let mut stake_pool = try_from_slice_unchecked::<Self>(&padded_data)?;
+        stake_pool.pending_withdrawal_lamports = 0;
+        Ok(stake_pool)
+    }
+
+    /// Serialize the stake pool into its account data.
+    ///
+    /// An account shorter than the packed length can only be written while
+    /// nothing is owed to withdrawal tickets, and only the zeroed pending
+    /// lamports may be cut off the end.
+    pub fn pack_into_slice(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if data.len() < get_packed_len::<Self>() && self.pending_withdrawal_lamports != 0 {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let bytes = borsh::to_vec(self)?;
+        let len = bytes.len().min(data.len());
+        if bytes[len..].iter().any(|byte| *byte != 0) {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[..len].copy_from_slice(&bytes[..len]);
+        Ok(())
+    }
+
     /// calculate the pool tokens that should be minted for a deposit of
     /// `stake_lamports`
     #[inline]
@@ -500,7 +546,7 @@ impl StakePool {
 
 /// Checks if the given extension is supported for the stake pool mint
 pub fn is_extension_supported_for_mint(extension_type: &ExtensionType) -> bool {
-    const SUPPORTED_EXTENSIONS: [ExtensionType; 8] = [
+    const SUPPORTED_EXTENSIONS: [ExtensionType; 10] = [
         ExtensionType::Uninitialized,
         ExtensionType::TransferFeeConfig,
         ExtensionType::ConfidentialTransferMint,
@@ -509,6 +555,8 @@ pub fn is_extension_supported_for_mint(extension_type: &ExtensionType) -> bool {
         ExtensionType::InterestBearingConfig,
         ExtensionType::MetadataPointer,
         ExtensionType::TokenMetadata,
+        ExtensionType::TransferHook, // ok, see `check_mint_extension_policy`
+        ExtensionType::PermanentDelegate, // ok, see `check_mint_extension_policy`
     ];
     if !SUPPORTED_EXTENSIONS.contains(extension_type) {
         msg!(
@@ -521,16 +569,48 @@ pub fn is_extension_supported_for_mint(extension_type: &ExtensionType) -> bool {
     }
 }
 
+/// Checks the configuration of the mint extensions that the stake pool only
+/// supports under a policy:
+/// * a transfer hook can neither be set nor be settable later, since the pool
+///   does not pass the extra accounts of a hook when moving pool tokens
+/// * the permanent delegate, if any, must be the stake pool withdraw authority,
+///   so that nobody can move or burn pool tokens outside of the program
+pub fn check_mint_extension_policy(
+    mint: &StateWithExtensions<Mint>,
+    stake_withdraw_authority: &Pubkey,
+) -> Result<(), ProgramError> {
+    if let Ok(transfer_hook) = mint.get_extension::<TransferHook>() {
+        if Option::<Pubkey>::from(transfer_hook.authority).is_some()
+            || Option::<Pubkey>::from(transfer_hook.program_id).is_some()
+        {
+            msg!("Stake pool mint account cannot have a transfer hook program or authority");
+            return Err(StakePoolError::UnsupportedMintExtension.into());
+        }
+    }
+    if let Some(permanent_delegate) = get_permanent_delegate(mint) {
+        if permanent_delegate != *stake_withdraw_authority {
+            msg!(
+                "Stake pool mint permanent delegate must be the withdraw authority {}, received {}",
+                stake_withdraw_authority,
+                permanent_delegate
+            );
+            return Err(StakePoolError::UnsupportedMintExtension.into());
+        }
+    }
+    Ok(())
+}
+
 /// Checks if the given extension is supported for the stake pool's fee account
 pub fn is_extension_supported_for_fee_account(extension_type: &ExtensionType) -> bool {
     // Note: this does not include the `ConfidentialTransferAccount` extension
     // because it is possible to block non-confidential transfers with the
     // extension enabled.
-    const SUPPORTED_EXTENSIONS: [ExtensionType; 4] = [
+    const SUPPORTED_EXTENSIONS: [ExtensionType; 5] = [
         ExtensionType::Uninitialized,
         ExtensionType::TransferFeeAmount,
         ExtensionType::ImmutableOwner,
         ExtensionType::CpiGuard,
+        ExtensionType::TransferHookAccount,
     ];
     if !SUPPORTED_EXTENSIONS.contains(extension_type) {
         msg!("Fee account cannot have the {:?} extension", extension_type);
@@ -709,8 +789,15 @@ pub struct ValidatorStakeInfo {
     /// account address
     pub transient_seed_suffix: PodU64,
 
+    /// Maximum share of the pool lamports that new stake may bring this
+    /// validator to, in basis points, or 0 for no maximum
+    pub max_stake_share_bps: PodU16,
+
+    /// Whether new stake to this validator is denied
+    pub stake_denied: PodBool,
+
     /// Unused space, initially meant to specify the end of seed suffixes
-    pub unused: PodU32,
+    pub unused: u8,
 
     /// Validator account seed suffix
     pub validator_seed_suffix: PodU32, // really `Option<NonZeroU32>` so 0 is `None`
@@ -730,6 +817,41 @@ impl ValidatorStakeInfo {
             .ok_or(StakePoolError::CalculationFailure)
     }
 
+    /// Check that new stake may bring this validator to `validator_lamports`,
+    /// out of `total_lamports` in the pool
+    pub fn check_new_stake(
+        &self,
+        validator_lamports: u64,
+        total_lamports: u64,
+    ) -> Result<(), StakePoolError> {
+        if bool::from(self.stake_denied) {
+            msg!(
+                "New stake to validator {} is denied",
+                self.vote_account_address
+            );
+            return Err(StakePoolError::ValidatorStakeDenied);
+        }
+        let max_stake_share_bps = u16::from(self.max_stake_share_bps);
+        if max_stake_share_bps == 0 {
+            return Ok(());
+        }
+        let max_lamports = (total_lamports as u128)
+            .checked_mul(max_stake_share_bps as u128)
+            .ok_or(StakePoolError::CalculationFailure)?
+            .checked_div(MAX_VALIDATOR_STAKE_SHARE_BPS as u128)
+            .ok_or(StakePoolError::CalculationFailure)?;
+        if validator_lamports as u128 > max_lamports {
+            msg!(
+                "Validator {} would have {} lamports, over its maximum of {}",
+                self.vote_account_address,
+                validator_lamports,
+                max_lamports
+            );
+            return Err(StakePoolError::ValidatorStakeCapExceeded);
+        }
+        Ok(())
+    }
+
     /// Performs a very cheap comparison, for checking if this validator stake
     /// info matches the vote account address
     pub fn memcmp_pubkey(data: &[u8], vote_address: &Pubkey) -> bool {
@@ -863,6 +985,39 @@ impl ValidatorListHeader {
     }
 }
 
+/// MLN owed from the reserve for pool tokens burnt on request, waiting for the
+/// ticket to be claimed
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
+pub struct WithdrawalTicket {
+    /// Account type, must be WithdrawalTicket currently
+    pub account_type: AccountType,
+
+    /// Stake pool the pool tokens are withdrawn from
+    pub stake_pool: Pubkey,
+
+    /// Owner of the ticket, receiving the lamports on claim
+    pub owner: Pubkey,
+
+    /// Lamports owed to the owner, converted at the rate of the request
+    pub lamports: u64,
+
+    /// Epoch in which the withdrawal was requested
+    pub request_epoch: u64,
+}
+impl WithdrawalTicket {
+    /// Check if the account is actually initialized as a withdrawal ticket
+    pub fn is_valid(&self) -> bool {
+        self.account_type == AccountType::WithdrawalTicket
+    }
+
+    /// Check if the ticket can be claimed in the given epoch, leaving the
+    /// staker an epoch to deactivate stake for it
+    pub fn is_claimable(&self, epoch: u64) -> bool {
+        epoch > self.request_epoch
+    }
+}
+
 /// Wrapper type that "counts down" epochs, which is Borsh-compatible with the
 /// native `Option`
 #[repr(C)]
@@ -1082,7 +1237,9 @@ mod test {
                     transient_stake_lamports: u64::from_le_bytes([128; 8]).into(),
                     last_update_epoch: u64::from_le_bytes([64; 8]).into(),
                     transient_seed_suffix: 0.into(),
-                    unused: 0.into(),
+                    max_stake_share_bps: 0.into(),
+                    stake_denied: false.into(),
+                    unused: 0,
                     validator_seed_suffix: 0.into(),
                 },
                 ValidatorStakeInfo {
@@ -1092,7 +1249,9 @@ mod test {
                     transient_stake_lamports: 222222222.into(),
                     last_update_epoch: 11223445566.into(),
                     transient_seed_suffix: 0.into(),
-                    unused: 0.into(),
+                    max_stake_share_bps: 0.into(),
+                    stake_denied: false.into(),
+                    unused: 0,
                     validator_seed_suffix: 0.into(),
                 },
                 ValidatorStakeInfo {
@@ -1102,7 +1261,9 @@ mod test {
                     transient_stake_lamports: 0.into(),
                     last_update_epoch: 999999999999999.into(),
                     transient_seed_suffix: 0.into(),
-                    unused: 0.into(),
+                    max_stake_share_bps: 0.into(),
+                    stake_denied: false.into(),
+                    unused: 0,
                     validator_seed_suffix: 0.into(),
                 },
             ],
@@ -1143,6 +1304,70 @@ mod test {
         assert_eq!(stake_list_unpacked, stake_list);
     }
 
+    #[test]
+    fn stake_pool_packing_without_pending_withdrawals() {
+        // every optional field is set, so the pool fills an account created
+        // before `pending_withdrawal_lamports` was added
+        let fee = Fee {
+            denominator: 100,
+            numerator: 1,
+        };
+        let stake_pool = StakePool {
+            account_type: AccountType::StakePool,
+            next_epoch_fee: FutureEpoch::Two(fee),
+            preferred_deposit_validator_vote_address: Some(Pubkey::new_unique()),
+            preferred_withdraw_validator_vote_address: Some(Pubkey::new_unique()),
+            next_stake_withdrawal_fee: FutureEpoch::Two(fee),
+            mln_deposit_authority: Some(Pubkey::new_unique()),
+            mln_withdraw_authority: Some(Pubkey::new_unique()),
+            next_mln_withdrawal_fee: FutureEpoch::Two(fee),
+            last_epoch_pool_token_supply: u64::MAX,
+            last_epoch_total_lamports: u64::MAX,
+            ..StakePool::default()
+        };
+        let packed_len = get_packed_len::<StakePool>();
+        assert_eq!(get_instance_packed_len(&stake_pool).unwrap(), packed_len);
+        let old_len = packed_len - std::mem::size_of::<u64>();
+
+        let mut old_data = vec![0; old_len];
+        stake_pool.pack_into_slice(&mut old_data).unwrap();
+        assert_eq!(StakePool::unpack_from_slice(&old_data).unwrap(), stake_pool);
+
+        // a shorter pool leaves bytes behind, which are not pending lamports
+        let shorter_stake_pool = StakePool {
+            mln_withdraw_authority: None,
+            ..stake_pool.clone()
+        };
+        shorter_stake_pool.pack_into_slice(&mut old_data).unwrap();
+        assert_eq!(
+            StakePool::unpack_from_slice(&old_data).unwrap(),
+            shorter_stake_pool
+        );
+
+        // nothing can be owed to tickets until the account is resized
+        let pending_stake_pool = StakePool {
+            pending_withdrawal_lamports: 1,
+            ..shorter_stake_pool
+        };
+        assert_eq!(
+            pending_stake_pool.pack_into_slice(&mut old_data),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+        let mut data = vec![0; packed_len];
+        data[..old_len].copy_from_slice(&old_data);
+        pending_stake_pool.pack_into_slice(&mut data).unwrap();
+        assert_eq!(
+            StakePool::unpack_from_slice(&data).unwrap(),
+            pending_stake_pool
+        );
+
+        // an account too short for the pool itself is still rejected
+        assert_eq!(
+            stake_pool.pack_into_slice(&mut vec![0; old_len - 1]),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+    }
+
     #[test]
     fn validator_list_active_stake() {
         let max_validators = 10_000;
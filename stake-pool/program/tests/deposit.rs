@@ -4,10 +4,9 @@
 mod helpers;
 
 use {
-    borsh::BorshSerialize,
     helpers::*,
     solana_program::{
-        borsh0_10::try_from_slice_unchecked,
+        borsh1::try_from_slice_unchecked,
         instruction::{AccountMeta, Instruction, InstructionError},
         pubkey::Pubkey,
         stake, sysvar,
@@ -499,9 +498,7 @@ async fn fail_with_wrong_stake_program_id() {
     let instruction = Instruction {
         program_id: id(),
         accounts,
-        data: instruction::StakePoolInstruction::DepositStake
-            .try_to_vec()
-            .unwrap(),
+        data: borsh::to_vec(&instruction::StakePoolInstruction::DepositStake).unwrap(),
     };
 
     let mut transaction =
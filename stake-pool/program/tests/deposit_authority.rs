@@ -8,7 +8,7 @@ use {
     solana_program::{instruction::InstructionError, stake},
     solana_program_test::*,
     solana_sdk::{
-        borsh0_10::try_from_slice_unchecked,
+        borsh1::try_from_slice_unchecked,
         signature::{Keypair, Signer},
         transaction::TransactionError,
     },
@@ -6,7 +6,7 @@ mod helpers;
 use {
     helpers::*,
     solana_program::{
-        borsh0_10::try_from_slice_unchecked,
+        borsh1::try_from_slice_unchecked,
         instruction::InstructionError,
         pubkey::Pubkey,
         stake::{
@@ -67,7 +67,9 @@ async fn setup(
         transient_stake_lamports: 0.into(),
         last_update_epoch: 0.into(),
         transient_seed_suffix: 0.into(),
-        unused: 0.into(),
+        max_stake_share_bps: 0.into(),
+        stake_denied: false.into(),
+        unused: 0,
         validator_seed_suffix: raw_validator_seed.into(),
     });
 
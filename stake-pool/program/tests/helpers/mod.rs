@@ -1,9 +1,9 @@
 #![allow(dead_code)]
 
 use {
-    borsh::{BorshDeserialize, BorshSerialize},
+    borsh::BorshDeserialize,
     solana_program::{
-        borsh0_10::{get_instance_packed_len, get_packed_len, try_from_slice_unchecked},
+        borsh1::{get_instance_packed_len, get_packed_len, try_from_slice_unchecked},
         hash::Hash,
         instruction::Instruction,
         program_option::COption,
@@ -136,6 +136,20 @@ pub async fn create_mint(
             ),
             ExtensionType::NonTransferable =>
                 spl_token_2022::instruction::initialize_non_transferable_mint(program_id, &mint_pubkey),
+            ExtensionType::MetadataPointer => spl_token_2022::extension::metadata_pointer::instruction::initialize(
+                program_id,
+                &mint_pubkey,
+                None,
+                Some(mint_pubkey),
+            ),
+            ExtensionType::TransferHook => spl_token_2022::extension::transfer_hook::instruction::initialize(
+                program_id,
+                &mint_pubkey,
+                Some(*manager),
+                None,
+            ),
+            ExtensionType::PermanentDelegate =>
+                spl_token_2022::instruction::initialize_permanent_delegate(program_id, &mint_pubkey, manager),
             _ => unimplemented!(),
         };
         instructions.push(instruction.unwrap());
@@ -249,7 +263,8 @@ pub async fn create_token_account(
             ExtensionType::TransferFeeAmount
             | ExtensionType::MemoTransfer
             | ExtensionType::CpiGuard
-            | ExtensionType::NonTransferableAccount => (),
+            | ExtensionType::NonTransferableAccount
+            | ExtensionType::TransferHookAccount => (),
             _ => unimplemented!(),
         };
     }
@@ -293,7 +308,8 @@ pub async fn create_token_account(
             }
             ExtensionType::ImmutableOwner
             | ExtensionType::TransferFeeAmount
-            | ExtensionType::NonTransferableAccount => (),
+            | ExtensionType::NonTransferableAccount
+            | ExtensionType::TransferHookAccount => (),
             _ => unimplemented!(),
         }
     }
@@ -927,6 +943,24 @@ impl StakePoolAccounts {
         payer: &Keypair,
         recent_blockhash: &Hash,
         reserve_lamports: u64,
+    ) -> Result<(), TransportError> {
+        self.initialize_stake_pool_with_mint_extensions(
+            banks_client,
+            payer,
+            recent_blockhash,
+            reserve_lamports,
+            &[],
+        )
+        .await
+    }
+
+    pub async fn initialize_stake_pool_with_mint_extensions(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        reserve_lamports: u64,
+        mint_extensions: &[ExtensionType],
     ) -> Result<(), TransportError> {
         create_mint(
             banks_client,
@@ -936,7 +970,7 @@ impl StakePoolAccounts {
             &self.pool_mint,
             &self.withdraw_authority,
             self.pool_decimals,
-            &[],
+            mint_extensions,
         )
         .await?;
         create_token_account(
@@ -947,7 +981,7 @@ impl StakePoolAccounts {
             &self.pool_fee_account,
             &self.pool_mint.pubkey(),
             &self.manager,
-            &[],
+            &ExtensionType::get_required_init_account_extensions(mint_extensions),
         )
         .await?;
         create_independent_stake_account(
@@ -1397,9 +1431,82 @@ impl StakePoolAccounts {
             .err()
     }
 
+    /// Request a withdrawal ticket owned by the payer, for pool tokens burnt
+    /// from the user's pool account
+    #[allow(clippy::too_many_arguments)]
+    pub async fn request_withdrawal(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        user: &Keypair,
+        pool_account: &Pubkey,
+        amount: u64,
+        ticket_seed: u64,
+    ) -> Option<TransportError> {
+        let mut instructions = vec![instruction::request_withdrawal(
+            &id(),
+            &self.stake_pool.pubkey(),
+            &self.withdraw_authority,
+            &user.pubkey(),
+            pool_account,
+            &self.pool_fee_account.pubkey(),
+            &self.pool_mint.pubkey(),
+            &payer.pubkey(),
+            &self.token_program_id,
+            None,
+            amount,
+            ticket_seed,
+        )];
+        self.maybe_add_compute_budget_instruction(&mut instructions);
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[payer, user],
+            *recent_blockhash,
+        );
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.into())
+            .err()
+    }
+
+    /// Claim a withdrawal ticket owned by the payer
+    pub async fn claim_withdrawal(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        withdrawal_ticket: &Pubkey,
+        lamports_to: &Pubkey,
+    ) -> Option<TransportError> {
+        let mut instructions = vec![instruction::claim_withdrawal(
+            &id(),
+            &self.stake_pool.pubkey(),
+            &self.withdraw_authority,
+            withdrawal_ticket,
+            &payer.pubkey(),
+            &self.reserve_stake.pubkey(),
+            lamports_to,
+        )];
+        self.maybe_add_compute_budget_instruction(&mut instructions);
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[payer],
+            *recent_blockhash,
+        );
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.into())
+            .err()
+    }
+
     pub async fn get_stake_pool(&self, banks_client: &mut BanksClient) -> StakePool {
         let stake_pool_account = get_account(banks_client, &self.stake_pool.pubkey()).await;
-        try_from_slice_unchecked::<StakePool>(stake_pool_account.data.as_slice()).unwrap()
+        StakePool::unpack_from_slice(stake_pool_account.data.as_slice()).unwrap()
     }
 
     pub async fn get_validator_list(&self, banks_client: &mut BanksClient) -> ValidatorList {
@@ -2007,6 +2114,38 @@ impl StakePoolAccounts {
             .err()
     }
 
+    pub async fn set_validator_stake_policy(
+        &self,
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: &Hash,
+        vote_account_address: &Pubkey,
+        max_stake_share_bps: u16,
+        stake_denied: bool,
+    ) -> Option<TransportError> {
+        let mut instructions = vec![instruction::set_validator_stake_policy(
+            &id(),
+            &self.stake_pool.pubkey(),
+            &self.staker.pubkey(),
+            &self.validator_list.pubkey(),
+            vote_account_address,
+            max_stake_share_bps,
+            stake_denied,
+        )];
+        self.maybe_add_compute_budget_instruction(&mut instructions);
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[payer, &self.staker],
+            *recent_blockhash,
+        );
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.into())
+            .err()
+    }
+
     pub fn state(&self) -> (state::StakePool, state::ValidatorList) {
         let (_, stake_withdraw_bump_seed) =
             find_withdraw_authority_program_address(&id(), &self.stake_pool.pubkey());
@@ -2041,6 +2180,7 @@ impl StakePoolAccounts {
             next_mln_withdrawal_fee: FutureEpoch::None,
             last_epoch_pool_token_supply: 0,
             last_epoch_total_lamports: 0,
+            pending_withdrawal_lamports: 0,
         };
         let mut validator_list = ValidatorList::new(self.max_validators);
         validator_list.validators = vec![];
@@ -2486,7 +2626,9 @@ pub fn add_validator_stake_account(
         transient_stake_lamports: 0.into(),
         last_update_epoch: FIRST_NORMAL_EPOCH.into(),
         transient_seed_suffix: 0.into(),
-        unused: 0.into(),
+        max_stake_share_bps: 0.into(),
+        stake_denied: false.into(),
+        unused: 0,
         validator_seed_suffix: raw_suffix.into(),
     });
 
@@ -2526,7 +2668,7 @@ pub fn add_stake_pool_account(
     stake_pool_pubkey: &Pubkey,
     stake_pool: &state::StakePool,
 ) {
-    let mut stake_pool_bytes = stake_pool.try_to_vec().unwrap();
+    let mut stake_pool_bytes = borsh::to_vec(&stake_pool).unwrap();
     // more room for optionals
     stake_pool_bytes.extend_from_slice(Pubkey::default().as_ref());
     stake_pool_bytes.extend_from_slice(Pubkey::default().as_ref());
@@ -2546,11 +2688,11 @@ pub fn add_validator_list_account(
     validator_list: &state::ValidatorList,
     max_validators: u32,
 ) {
-    let mut validator_list_bytes = validator_list.try_to_vec().unwrap();
+    let mut validator_list_bytes = borsh::to_vec(&validator_list).unwrap();
     // add extra room if needed
     for _ in validator_list.validators.len()..max_validators as usize {
         validator_list_bytes
-            .append(&mut state::ValidatorStakeInfo::default().try_to_vec().unwrap());
+            .append(&mut borsh::to_vec(&state::ValidatorStakeInfo::default()).unwrap());
     }
     let validator_list_account = MiralandAccount::create(
         ACCOUNT_RENT_EXEMPTION,
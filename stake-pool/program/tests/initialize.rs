@@ -5,10 +5,9 @@
 mod helpers;
 
 use {
-    borsh::BorshSerialize,
     helpers::*,
     solana_program::{
-        borsh0_10::{get_instance_packed_len, get_packed_len, try_from_slice_unchecked},
+        borsh1::{get_instance_packed_len, get_packed_len, try_from_slice_unchecked},
         hash::Hash,
         instruction::{AccountMeta, Instruction},
         program_pack::Pack,
@@ -633,6 +632,54 @@ async fn fail_with_unsupported_mint_extension() {
     );
 }
 
+#[tokio::test]
+async fn success_with_withdraw_authority_permanent_delegate() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let stake_pool_accounts = StakePoolAccounts::new_with_token_program(spl_token_2022::id());
+
+    stake_pool_accounts
+        .initialize_stake_pool_with_mint_extensions(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            MINIMUM_RESERVE_LAMPORTS,
+            &[
+                ExtensionType::PermanentDelegate,
+                ExtensionType::MetadataPointer,
+            ],
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn fail_with_transfer_hook_authority() {
+    let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
+    let stake_pool_accounts = StakePoolAccounts::new_with_token_program(spl_token_2022::id());
+
+    let transaction_error = stake_pool_accounts
+        .initialize_stake_pool_with_mint_extensions(
+            &mut banks_client,
+            &payer,
+            &recent_blockhash,
+            MINIMUM_RESERVE_LAMPORTS,
+            &[ExtensionType::TransferHook],
+        )
+        .await
+        .err()
+        .unwrap();
+    match transaction_error {
+        TransportError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(error_index),
+        )) => {
+            let program_error = error::StakePoolError::UnsupportedMintExtension as u32;
+            assert_eq!(error_index, program_error);
+        }
+        _ => panic!("Wrong error occurs while try to initialize stake pool with a transfer hook"),
+    }
+}
+
 #[tokio::test]
 async fn fail_with_unsupported_account_extension() {
     let (mut banks_client, payer, recent_blockhash) = program_test().start().await;
@@ -1200,7 +1247,7 @@ async fn fail_without_manager_signature() {
         referral_fee: stake_pool_accounts.referral_fee,
         max_validators: stake_pool_accounts.max_validators,
     };
-    let data = init_data.try_to_vec().unwrap();
+    let data = borsh::to_vec(&init_data).unwrap();
     let accounts = vec![
         AccountMeta::new(stake_pool_accounts.stake_pool.pubkey(), true),
         AccountMeta::new_readonly(stake_pool_accounts.manager.pubkey(), false),
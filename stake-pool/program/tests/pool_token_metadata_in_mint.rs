@@ -0,0 +1,198 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program::{instruction::InstructionError, pubkey::Pubkey},
+    solana_program_test::*,
+    solana_sdk::{
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_stake_pool::{error::StakePoolError, instruction, MINIMUM_RESERVE_LAMPORTS},
+    spl_token_2022::{
+        extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensionsOwned},
+        state::Mint,
+    },
+    spl_token_metadata_interface::state::TokenMetadata,
+};
+
+async fn setup(mint_extensions: &[ExtensionType]) -> (ProgramTestContext, StakePoolAccounts) {
+    let mut context = program_test().start_with_context().await;
+    let stake_pool_accounts = StakePoolAccounts::new_with_token_program(spl_token_2022::id());
+    stake_pool_accounts
+        .initialize_stake_pool_with_mint_extensions(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            MINIMUM_RESERVE_LAMPORTS,
+            mint_extensions,
+        )
+        .await
+        .unwrap();
+
+    (context, stake_pool_accounts)
+}
+
+async fn get_token_metadata(
+    context: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+) -> TokenMetadata {
+    let mint_account = get_account(
+        &mut context.banks_client,
+        &stake_pool_accounts.pool_mint.pubkey(),
+    )
+    .await;
+    let mint = StateWithExtensionsOwned::<Mint>::unpack(mint_account.data).unwrap();
+    mint.get_variable_len_extension::<TokenMetadata>().unwrap()
+}
+
+async fn create_token_metadata(
+    context: &mut ProgramTestContext,
+    stake_pool_accounts: &StakePoolAccounts,
+    name: &str,
+    symbol: &str,
+    uri: &str,
+) -> Result<(), BanksClientError> {
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::create_token_metadata_in_mint(
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool.pubkey(),
+            &stake_pool_accounts.manager.pubkey(),
+            &stake_pool_accounts.pool_mint.pubkey(),
+            &context.payer.pubkey(),
+            name.to_string(),
+            symbol.to_string(),
+            uri.to_string(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &stake_pool_accounts.manager],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+#[tokio::test]
+async fn success_create_and_update() {
+    let (mut context, stake_pool_accounts) = setup(&[ExtensionType::MetadataPointer]).await;
+
+    create_token_metadata(
+        &mut context,
+        &stake_pool_accounts,
+        "test_name",
+        "SYM",
+        "test_uri",
+    )
+    .await
+    .unwrap();
+
+    let token_metadata = get_token_metadata(&mut context, &stake_pool_accounts).await;
+    assert_eq!(token_metadata.name, "test_name");
+    assert_eq!(token_metadata.symbol, "SYM");
+    assert_eq!(token_metadata.uri, "test_uri");
+    assert_eq!(
+        Option::<Pubkey>::from(token_metadata.update_authority),
+        Some(stake_pool_accounts.withdraw_authority)
+    );
+
+    // longer values grow the mint, paid by the payer
+    let updated_name = "updated_test_name";
+    let updated_symbol = "USYM";
+    let updated_uri = "https://example.com/updated_test_uri.json";
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::update_token_metadata_in_mint(
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool.pubkey(),
+            &stake_pool_accounts.manager.pubkey(),
+            &stake_pool_accounts.pool_mint.pubkey(),
+            &context.payer.pubkey(),
+            updated_name.to_string(),
+            updated_symbol.to_string(),
+            updated_uri.to_string(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &stake_pool_accounts.manager],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let token_metadata = get_token_metadata(&mut context, &stake_pool_accounts).await;
+    assert_eq!(token_metadata.name, updated_name);
+    assert_eq!(token_metadata.symbol, updated_symbol);
+    assert_eq!(token_metadata.uri, updated_uri);
+}
+
+#[tokio::test]
+async fn fail_without_metadata_pointer() {
+    let (mut context, stake_pool_accounts) = setup(&[]).await;
+
+    let error = create_token_metadata(
+        &mut context,
+        &stake_pool_accounts,
+        "test_name",
+        "SYM",
+        "test_uri",
+    )
+    .await
+    .err()
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(StakePoolError::InvalidMetadataAccount as u32),
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_update_wrong_manager() {
+    let (mut context, stake_pool_accounts) = setup(&[ExtensionType::MetadataPointer]).await;
+
+    create_token_metadata(
+        &mut context,
+        &stake_pool_accounts,
+        "test_name",
+        "SYM",
+        "test_uri",
+    )
+    .await
+    .unwrap();
+
+    let wrong_manager = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::update_token_metadata_in_mint(
+            &spl_stake_pool::id(),
+            &stake_pool_accounts.stake_pool.pubkey(),
+            &wrong_manager.pubkey(),
+            &stake_pool_accounts.pool_mint.pubkey(),
+            &context.payer.pubkey(),
+            "updated_test_name".to_string(),
+            "USYM".to_string(),
+            "updated_test_uri".to_string(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_manager],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .err()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(StakePoolError::WrongManager as u32),
+        )
+    );
+}
@@ -4,10 +4,9 @@
 mod helpers;
 
 use {
-    borsh::BorshSerialize,
     helpers::*,
     solana_program::{
-        borsh0_10::try_from_slice_unchecked,
+        borsh1::try_from_slice_unchecked,
         hash::Hash,
         instruction::{AccountMeta, Instruction},
     },
@@ -134,9 +133,7 @@ async fn test_set_manager_without_existing_signature() {
     let (mut banks_client, payer, recent_blockhash, stake_pool_accounts, new_pool_fee, new_manager) =
         setup().await;
 
-    let data = instruction::StakePoolInstruction::SetManager
-        .try_to_vec()
-        .unwrap();
+    let data = borsh::to_vec(&instruction::StakePoolInstruction::SetManager).unwrap();
     let accounts = vec![
         AccountMeta::new(stake_pool_accounts.stake_pool.pubkey(), false),
         AccountMeta::new_readonly(stake_pool_accounts.manager.pubkey(), false),
@@ -177,9 +174,7 @@ async fn test_set_manager_without_new_signature() {
     let (mut banks_client, payer, recent_blockhash, stake_pool_accounts, new_pool_fee, new_manager) =
         setup().await;
 
-    let data = instruction::StakePoolInstruction::SetManager
-        .try_to_vec()
-        .unwrap();
+    let data = borsh::to_vec(&instruction::StakePoolInstruction::SetManager).unwrap();
     let accounts = vec![
         AccountMeta::new(stake_pool_accounts.stake_pool.pubkey(), false),
         AccountMeta::new_readonly(stake_pool_accounts.manager.pubkey(), true),
@@ -4,10 +4,9 @@
 mod helpers;
 
 use {
-    borsh::BorshSerialize,
     helpers::*,
     solana_program::{
-        borsh0_10::try_from_slice_unchecked,
+        borsh1::try_from_slice_unchecked,
         hash::Hash,
         instruction::{AccountMeta, Instruction},
     },
@@ -150,9 +149,7 @@ async fn fail_set_staker_without_signature() {
     let (mut banks_client, payer, recent_blockhash, stake_pool_accounts, new_staker) =
         setup().await;
 
-    let data = instruction::StakePoolInstruction::SetStaker
-        .try_to_vec()
-        .unwrap();
+    let data = borsh::to_vec(&instruction::StakePoolInstruction::SetStaker).unwrap();
     let accounts = vec![
         AccountMeta::new(stake_pool_accounts.stake_pool.pubkey(), false),
         AccountMeta::new_readonly(stake_pool_accounts.manager.pubkey(), false),
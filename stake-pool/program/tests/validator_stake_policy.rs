@@ -0,0 +1,313 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program::{instruction::InstructionError, pubkey::Pubkey, stake},
+    solana_program_test::*,
+    solana_sdk::{
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_stake_pool::{
+        error::StakePoolError, id, instruction, MAX_VALIDATOR_STAKE_SHARE_BPS,
+        MINIMUM_RESERVE_LAMPORTS,
+    },
+};
+
+async fn setup() -> (
+    ProgramTestContext,
+    StakePoolAccounts,
+    ValidatorStakeAccount,
+    u64,
+) {
+    let mut context = program_test().start_with_context().await;
+    let stake_pool_accounts = StakePoolAccounts::default();
+    let reserve_lamports = 100_000_000_000 + MINIMUM_RESERVE_LAMPORTS;
+    stake_pool_accounts
+        .initialize_stake_pool(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            reserve_lamports,
+        )
+        .await
+        .unwrap();
+
+    let validator_stake_account = simple_add_validator_to_pool(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &stake_pool_accounts,
+        None,
+    )
+    .await;
+
+    (
+        context,
+        stake_pool_accounts,
+        validator_stake_account,
+        reserve_lamports,
+    )
+}
+
+async fn stake_deposit_lamports(context: &mut ProgramTestContext) -> u64 {
+    let current_minimum_delegation = stake_pool_get_minimum_delegation(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+    )
+    .await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let stake_rent = rent.minimum_balance(std::mem::size_of::<stake::state::StakeStateV2>());
+    current_minimum_delegation * 2 + stake_rent
+}
+
+fn assert_stake_pool_error(error: TransactionError, expected: StakePoolError) {
+    match error {
+        TransactionError::InstructionError(_, InstructionError::Custom(error_index)) => {
+            assert_eq!(error_index, expected as u32);
+        }
+        _ => panic!("Unexpected error {:?}, expected {:?}", error, expected),
+    }
+}
+
+#[tokio::test]
+async fn success() {
+    let (mut context, stake_pool_accounts, validator_stake, _) = setup().await;
+
+    let error = stake_pool_accounts
+        .set_validator_stake_policy(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.vote.pubkey(),
+            1_000,
+            true,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let validator_list = stake_pool_accounts
+        .get_validator_list(&mut context.banks_client)
+        .await;
+    let validator_stake_info = validator_list.find(&validator_stake.vote.pubkey()).unwrap();
+    assert_eq!(u16::from(validator_stake_info.max_stake_share_bps), 1_000);
+    assert!(bool::from(validator_stake_info.stake_denied));
+
+    let error = stake_pool_accounts
+        .set_validator_stake_policy(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.vote.pubkey(),
+            0,
+            false,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let validator_list = stake_pool_accounts
+        .get_validator_list(&mut context.banks_client)
+        .await;
+    let validator_stake_info = validator_list.find(&validator_stake.vote.pubkey()).unwrap();
+    assert_eq!(u16::from(validator_stake_info.max_stake_share_bps), 0);
+    assert!(!bool::from(validator_stake_info.stake_denied));
+
+    // stake goes through again once the policy is lifted
+    let stake_lamports = stake_deposit_lamports(&mut context).await;
+    let deposit_info = simple_deposit_stake(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &stake_pool_accounts,
+        &validator_stake,
+        stake_lamports,
+    )
+    .await;
+    assert!(deposit_info.is_some());
+}
+
+#[tokio::test]
+async fn fail_wrong_staker() {
+    let (mut context, stake_pool_accounts, validator_stake, _) = setup().await;
+
+    let wrong_staker = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_validator_stake_policy(
+            &id(),
+            &stake_pool_accounts.stake_pool.pubkey(),
+            &wrong_staker.pubkey(),
+            &stake_pool_accounts.validator_list.pubkey(),
+            &validator_stake.vote.pubkey(),
+            0,
+            true,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_staker],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .err()
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::WrongStaker);
+}
+
+#[tokio::test]
+async fn fail_share_too_high() {
+    let (mut context, stake_pool_accounts, validator_stake, _) = setup().await;
+
+    let error = stake_pool_accounts
+        .set_validator_stake_policy(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.vote.pubkey(),
+            MAX_VALIDATOR_STAKE_SHARE_BPS + 1,
+            false,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::InvalidStakeShare);
+}
+
+#[tokio::test]
+async fn fail_not_present_validator() {
+    let (mut context, stake_pool_accounts, _, _) = setup().await;
+
+    let error = stake_pool_accounts
+        .set_validator_stake_policy(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &Pubkey::new_unique(),
+            1_000,
+            false,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::ValidatorNotFound);
+}
+
+#[tokio::test]
+async fn fail_deposit_denied() {
+    let (mut context, stake_pool_accounts, validator_stake, _) = setup().await;
+
+    let error = stake_pool_accounts
+        .set_validator_stake_policy(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.vote.pubkey(),
+            0,
+            true,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let stake_lamports = stake_deposit_lamports(&mut context).await;
+    let deposit_info = simple_deposit_stake(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &stake_pool_accounts,
+        &validator_stake,
+        stake_lamports,
+    )
+    .await;
+    assert!(deposit_info.is_none());
+}
+
+#[tokio::test]
+async fn fail_increase_denied() {
+    let (mut context, stake_pool_accounts, validator_stake, _) = setup().await;
+
+    let error = stake_pool_accounts
+        .set_validator_stake_policy(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.vote.pubkey(),
+            0,
+            true,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let increase_amount = stake_deposit_lamports(&mut context).await;
+    let error = stake_pool_accounts
+        .increase_validator_stake(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.transient_stake_account,
+            &validator_stake.stake_account,
+            &validator_stake.vote.pubkey(),
+            increase_amount,
+            validator_stake.transient_stake_seed,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::ValidatorStakeDenied);
+}
+
+#[tokio::test]
+async fn fail_increase_over_cap() {
+    let (mut context, stake_pool_accounts, validator_stake, reserve_lamports) = setup().await;
+
+    // half of the pool at most, while the increase moves almost all of it
+    let error = stake_pool_accounts
+        .set_validator_stake_policy(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.vote.pubkey(),
+            MAX_VALIDATOR_STAKE_SHARE_BPS / 2,
+            false,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let stake_rent = rent.minimum_balance(std::mem::size_of::<stake::state::StakeStateV2>());
+    let increase_amount = reserve_lamports - stake_rent - MINIMUM_RESERVE_LAMPORTS;
+    let error = stake_pool_accounts
+        .increase_validator_stake(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.transient_stake_account,
+            &validator_stake.stake_account,
+            &validator_stake.vote.pubkey(),
+            increase_amount,
+            validator_stake.transient_stake_seed,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::ValidatorStakeCapExceeded);
+
+    // a smaller increase stays under the cap
+    let error = stake_pool_accounts
+        .increase_validator_stake(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.transient_stake_account,
+            &validator_stake.stake_account,
+            &validator_stake.vote.pubkey(),
+            increase_amount / 4,
+            validator_stake.transient_stake_seed,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+}
@@ -5,10 +5,9 @@ mod helpers;
 
 use {
     bincode::deserialize,
-    borsh::BorshSerialize,
     helpers::*,
     solana_program::{
-        borsh0_10::try_from_slice_unchecked,
+        borsh1::try_from_slice_unchecked,
         hash::Hash,
         instruction::{AccountMeta, Instruction, InstructionError},
         pubkey::Pubkey,
@@ -116,7 +115,9 @@ async fn success() {
                 active_stake_lamports: (stake_rent + current_minimum_delegation).into(),
                 transient_stake_lamports: 0.into(),
                 transient_seed_suffix: 0.into(),
-                unused: 0.into(),
+                max_stake_share_bps: 0.into(),
+                stake_denied: false.into(),
+                unused: 0,
                 validator_seed_suffix: validator_stake
                     .validator_stake_seed
                     .map(|s| s.get())
@@ -292,13 +293,12 @@ async fn fail_without_signature() {
     let instruction = Instruction {
         program_id: id(),
         accounts,
-        data: instruction::StakePoolInstruction::AddValidatorToPool(
+        data: borsh::to_vec(&instruction::StakePoolInstruction::AddValidatorToPool(
             validator_stake
                 .validator_stake_seed
                 .map(|s| s.get())
                 .unwrap_or(0),
-        )
-        .try_to_vec()
+        ))
         .unwrap(),
     };
 
@@ -348,13 +348,12 @@ async fn fail_with_wrong_stake_program_id() {
     let instruction = Instruction {
         program_id: id(),
         accounts,
-        data: instruction::StakePoolInstruction::AddValidatorToPool(
+        data: borsh::to_vec(&instruction::StakePoolInstruction::AddValidatorToPool(
             validator_stake
                 .validator_stake_seed
                 .map(|s| s.get())
                 .unwrap_or(0),
-        )
-        .try_to_vec()
+        ))
         .unwrap(),
     };
     let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
@@ -402,13 +401,12 @@ async fn fail_with_wrong_system_program_id() {
     let instruction = Instruction {
         program_id: id(),
         accounts,
-        data: instruction::StakePoolInstruction::AddValidatorToPool(
+        data: borsh::to_vec(&instruction::StakePoolInstruction::AddValidatorToPool(
             validator_stake
                 .validator_stake_seed
                 .map(|s| s.get())
                 .unwrap_or(0),
-        )
-        .try_to_vec()
+        ))
         .unwrap(),
     };
     let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
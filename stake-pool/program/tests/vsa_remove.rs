@@ -5,10 +5,9 @@ mod helpers;
 
 use {
     bincode::deserialize,
-    borsh::BorshSerialize,
     helpers::*,
     solana_program::{
-        borsh0_10::try_from_slice_unchecked,
+        borsh1::try_from_slice_unchecked,
         instruction::{AccountMeta, Instruction, InstructionError},
         pubkey::Pubkey,
         stake, system_instruction, sysvar,
@@ -140,9 +139,7 @@ async fn fail_with_wrong_stake_program_id() {
     let instruction = Instruction {
         program_id: id(),
         accounts,
-        data: instruction::StakePoolInstruction::RemoveValidatorFromPool
-            .try_to_vec()
-            .unwrap(),
+        data: borsh::to_vec(&instruction::StakePoolInstruction::RemoveValidatorFromPool).unwrap(),
     };
 
     let mut transaction =
@@ -358,9 +355,7 @@ async fn fail_no_signature() {
     let instruction = Instruction {
         program_id: id(),
         accounts,
-        data: instruction::StakePoolInstruction::RemoveValidatorFromPool
-            .try_to_vec()
-            .unwrap(),
+        data: borsh::to_vec(&instruction::StakePoolInstruction::RemoveValidatorFromPool).unwrap(),
     };
 
     let transaction = Transaction::new_signed_with_payer(
@@ -551,7 +546,9 @@ async fn success_with_deactivating_transient_stake() {
             active_stake_lamports: (stake_rent + current_minimum_delegation).into(),
             transient_stake_lamports: (TEST_STAKE_AMOUNT + stake_rent * 2).into(),
             transient_seed_suffix: validator_stake.transient_stake_seed.into(),
-            unused: 0.into(),
+            max_stake_share_bps: 0.into(),
+            stake_denied: false.into(),
+            unused: 0,
             validator_seed_suffix: validator_stake
                 .validator_stake_seed
                 .map(|s| s.get())
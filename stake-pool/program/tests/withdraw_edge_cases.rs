@@ -8,7 +8,7 @@ use {
     bincode::deserialize,
     helpers::*,
     solana_program::{
-        borsh0_10::try_from_slice_unchecked, instruction::InstructionError, pubkey::Pubkey, stake,
+        borsh1::try_from_slice_unchecked, instruction::InstructionError, pubkey::Pubkey, stake,
     },
     solana_program_test::*,
     solana_sdk::{signature::Signer, transaction::TransactionError},
@@ -0,0 +1,627 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program::{
+        borsh1::{get_packed_len, try_from_slice_unchecked},
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        stake,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        account::Account,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_stake_pool::{
+        error::StakePoolError,
+        find_withdrawal_ticket_program_address, id, instruction,
+        state::{Fee, FutureEpoch, StakePool, WithdrawalTicket},
+        MINIMUM_RESERVE_LAMPORTS,
+    },
+    test_case::test_case,
+};
+
+async fn setup(
+    token_program_id: Pubkey,
+) -> (ProgramTestContext, StakePoolAccounts, Keypair, Pubkey, u64) {
+    let mut context = program_test().start_with_context().await;
+
+    let stake_pool_accounts = StakePoolAccounts::new_with_token_program(token_program_id);
+    stake_pool_accounts
+        .initialize_stake_pool(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            MINIMUM_RESERVE_LAMPORTS,
+        )
+        .await
+        .unwrap();
+
+    let user = Keypair::new();
+
+    // make pool token account for user
+    let pool_token_account = Keypair::new();
+    create_token_account(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &stake_pool_accounts.token_program_id,
+        &pool_token_account,
+        &stake_pool_accounts.pool_mint.pubkey(),
+        &user,
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let error = stake_pool_accounts
+        .deposit_mln(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &pool_token_account.pubkey(),
+            TEST_STAKE_AMOUNT,
+            None,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let tokens_issued =
+        get_token_balance(&mut context.banks_client, &pool_token_account.pubkey()).await;
+
+    (
+        context,
+        stake_pool_accounts,
+        user,
+        pool_token_account.pubkey(),
+        tokens_issued,
+    )
+}
+
+async fn warp_one_epoch(context: &mut ProgramTestContext, stake_pool_accounts: &StakePoolAccounts) {
+    let first_normal_slot = context.genesis_config().epoch_schedule.first_normal_slot;
+    context.warp_to_slot(first_normal_slot + 1).unwrap();
+    let last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    stake_pool_accounts
+        .update_all(
+            &mut context.banks_client,
+            &context.payer,
+            &last_blockhash,
+            &[],
+            false,
+        )
+        .await;
+    context.last_blockhash = last_blockhash;
+}
+
+fn assert_stake_pool_error(error: TransactionError, expected: StakePoolError) {
+    match error {
+        TransactionError::InstructionError(_, InstructionError::Custom(error_index)) => {
+            assert_eq!(error_index, expected as u32);
+        }
+        _ => panic!("Unexpected error {:?}, expected {:?}", error, expected),
+    }
+}
+
+#[test_case(spl_token::id(); "token")]
+#[test_case(spl_token_2022::id(); "token-2022")]
+#[tokio::test]
+async fn success(token_program_id: Pubkey) {
+    let (mut context, stake_pool_accounts, user, pool_token_account, pool_tokens) =
+        setup(token_program_id).await;
+
+    let pre_stake_pool = stake_pool_accounts
+        .get_stake_pool(&mut context.banks_client)
+        .await;
+    let pre_fee_tokens = get_token_balance(
+        &mut context.banks_client,
+        &stake_pool_accounts.pool_fee_account.pubkey(),
+    )
+    .await;
+
+    let pool_fee = stake_pool_accounts.calculate_withdrawal_fee(pool_tokens);
+    let expected_lamports = pre_stake_pool
+        .calc_lamports_withdraw_amount(pool_tokens - pool_fee)
+        .unwrap();
+    let error = stake_pool_accounts
+        .request_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens,
+            0,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    // tokens burnt, fee to the manager
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, &pool_token_account).await,
+        0
+    );
+    assert_eq!(
+        get_token_balance(
+            &mut context.banks_client,
+            &stake_pool_accounts.pool_fee_account.pubkey(),
+        )
+        .await,
+        pre_fee_tokens + pool_fee
+    );
+
+    let (withdrawal_ticket_address, _) = find_withdrawal_ticket_program_address(
+        &id(),
+        &stake_pool_accounts.stake_pool.pubkey(),
+        &context.payer.pubkey(),
+        0,
+    );
+    let withdrawal_ticket_account =
+        get_account(&mut context.banks_client, &withdrawal_ticket_address).await;
+    let withdrawal_ticket =
+        try_from_slice_unchecked::<WithdrawalTicket>(&withdrawal_ticket_account.data).unwrap();
+    assert!(withdrawal_ticket.is_valid());
+    assert_eq!(
+        withdrawal_ticket.stake_pool,
+        stake_pool_accounts.stake_pool.pubkey()
+    );
+    assert_eq!(withdrawal_ticket.owner, context.payer.pubkey());
+    assert_eq!(withdrawal_ticket.lamports, expected_lamports);
+
+    // the ticket lamports leave the pool on request
+    let stake_pool = stake_pool_accounts
+        .get_stake_pool(&mut context.banks_client)
+        .await;
+    assert_eq!(
+        stake_pool.pool_token_supply,
+        pre_stake_pool.pool_token_supply - (pool_tokens - pool_fee)
+    );
+    assert_eq!(
+        stake_pool.total_lamports,
+        pre_stake_pool.total_lamports - expected_lamports
+    );
+    assert_eq!(stake_pool.pending_withdrawal_lamports, expected_lamports);
+
+    warp_one_epoch(&mut context, &stake_pool_accounts).await;
+
+    // the update keeps the ticket lamports out of the pool
+    let stake_pool = stake_pool_accounts
+        .get_stake_pool(&mut context.banks_client)
+        .await;
+    assert_eq!(
+        stake_pool.total_lamports,
+        pre_stake_pool.total_lamports - expected_lamports
+    );
+    assert_eq!(stake_pool.pending_withdrawal_lamports, expected_lamports);
+
+    let receiver = Pubkey::new_unique();
+    let error = stake_pool_accounts
+        .claim_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &withdrawal_ticket_address,
+            &receiver,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let receiver_account = get_account(&mut context.banks_client, &receiver).await;
+    assert_eq!(receiver_account.lamports, expected_lamports);
+    assert!(context
+        .banks_client
+        .get_account(withdrawal_ticket_address)
+        .await
+        .unwrap()
+        .is_none());
+
+    let post_stake_pool = stake_pool_accounts
+        .get_stake_pool(&mut context.banks_client)
+        .await;
+    assert_eq!(
+        post_stake_pool.pool_token_supply,
+        stake_pool.pool_token_supply
+    );
+    assert_eq!(post_stake_pool.total_lamports, stake_pool.total_lamports);
+    assert_eq!(post_stake_pool.pending_withdrawal_lamports, 0);
+}
+
+#[tokio::test]
+async fn success_stake_pool_account_without_pending_lamports() {
+    let (mut context, stake_pool_accounts, user, pool_token_account, pool_tokens) =
+        setup(spl_token::id()).await;
+
+    // shrink the stake pool account to its size from before pending
+    // withdrawal lamports were tracked, with every optional field set so that
+    // the pool fills it
+    let fee = Fee {
+        denominator: 100,
+        numerator: 1,
+    };
+    let mut stake_pool = stake_pool_accounts
+        .get_stake_pool(&mut context.banks_client)
+        .await;
+    stake_pool.next_epoch_fee = FutureEpoch::Two(fee);
+    stake_pool.preferred_deposit_validator_vote_address = Some(Pubkey::new_unique());
+    stake_pool.preferred_withdraw_validator_vote_address = Some(Pubkey::new_unique());
+    stake_pool.next_stake_withdrawal_fee = FutureEpoch::Two(fee);
+    stake_pool.mln_deposit_authority = Some(Pubkey::new_unique());
+    stake_pool.mln_withdraw_authority = Some(context.payer.pubkey());
+    stake_pool.next_mln_withdrawal_fee = FutureEpoch::Two(fee);
+    let packed_len = get_packed_len::<StakePool>();
+    let old_len = packed_len - std::mem::size_of::<u64>();
+    let mut data = borsh::to_vec(&stake_pool).unwrap();
+    assert_eq!(data.len(), packed_len);
+    data.truncate(old_len);
+    let rent = context.banks_client.get_rent().await.unwrap();
+    context.set_account(
+        &stake_pool_accounts.stake_pool.pubkey(),
+        &Account {
+            lamports: rent.minimum_balance(old_len),
+            data,
+            owner: id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+
+    // the pool is still read and written in place
+    let error = stake_pool_accounts
+        .update_stake_pool_balance(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+    let stake_pool_account = get_account(
+        &mut context.banks_client,
+        &stake_pool_accounts.stake_pool.pubkey(),
+    )
+    .await;
+    assert_eq!(stake_pool_account.data.len(), old_len);
+    assert_eq!(
+        StakePool::unpack_from_slice(&stake_pool_account.data).unwrap(),
+        stake_pool
+    );
+
+    // the first request grows the account, at the owner's expense
+    let mut transaction = Transaction::new_with_payer(
+        &[instruction::request_withdrawal(
+            &id(),
+            &stake_pool_accounts.stake_pool.pubkey(),
+            &stake_pool_accounts.withdraw_authority,
+            &user.pubkey(),
+            &pool_token_account,
+            &stake_pool_accounts.pool_fee_account.pubkey(),
+            &stake_pool_accounts.pool_mint.pubkey(),
+            &context.payer.pubkey(),
+            &stake_pool_accounts.token_program_id,
+            Some(&context.payer.pubkey()),
+            pool_tokens,
+            0,
+        )],
+        Some(&context.payer.pubkey()),
+    );
+    transaction.sign(&[&context.payer, &user], context.last_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let stake_pool_account = get_account(
+        &mut context.banks_client,
+        &stake_pool_accounts.stake_pool.pubkey(),
+    )
+    .await;
+    assert_eq!(stake_pool_account.data.len(), packed_len);
+    assert_eq!(
+        stake_pool_account.lamports,
+        rent.minimum_balance(packed_len)
+    );
+
+    let (withdrawal_ticket_address, _) = find_withdrawal_ticket_program_address(
+        &id(),
+        &stake_pool_accounts.stake_pool.pubkey(),
+        &context.payer.pubkey(),
+        0,
+    );
+    let withdrawal_ticket_account =
+        get_account(&mut context.banks_client, &withdrawal_ticket_address).await;
+    let withdrawal_ticket =
+        try_from_slice_unchecked::<WithdrawalTicket>(&withdrawal_ticket_account.data).unwrap();
+    let post_stake_pool = stake_pool_accounts
+        .get_stake_pool(&mut context.banks_client)
+        .await;
+    assert_eq!(
+        post_stake_pool.pending_withdrawal_lamports,
+        withdrawal_ticket.lamports
+    );
+    assert_eq!(
+        post_stake_pool.mln_withdraw_authority,
+        Some(context.payer.pubkey())
+    );
+}
+
+#[tokio::test]
+async fn fail_claim_same_epoch() {
+    let (mut context, stake_pool_accounts, user, pool_token_account, pool_tokens) =
+        setup(spl_token::id()).await;
+
+    let error = stake_pool_accounts
+        .request_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens,
+            0,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let (withdrawal_ticket_address, _) = find_withdrawal_ticket_program_address(
+        &id(),
+        &stake_pool_accounts.stake_pool.pubkey(),
+        &context.payer.pubkey(),
+        0,
+    );
+    let error = stake_pool_accounts
+        .claim_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &withdrawal_ticket_address,
+            &Pubkey::new_unique(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::WithdrawalTicketNotReady);
+}
+
+#[tokio::test]
+async fn fail_ticket_seed_in_use() {
+    let (mut context, stake_pool_accounts, user, pool_token_account, pool_tokens) =
+        setup(spl_token::id()).await;
+
+    let error = stake_pool_accounts
+        .request_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens / 2,
+            0,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let error = stake_pool_accounts
+        .request_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens / 4,
+            0,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::AlreadyInUse);
+
+    // another seed opens a second ticket
+    let error = stake_pool_accounts
+        .request_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens / 4,
+            1,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+}
+
+#[tokio::test]
+async fn fail_wrong_owner() {
+    let (mut context, stake_pool_accounts, user, pool_token_account, pool_tokens) =
+        setup(spl_token::id()).await;
+
+    let error = stake_pool_accounts
+        .request_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens,
+            0,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    warp_one_epoch(&mut context, &stake_pool_accounts).await;
+
+    let (withdrawal_ticket_address, _) = find_withdrawal_ticket_program_address(
+        &id(),
+        &stake_pool_accounts.stake_pool.pubkey(),
+        &context.payer.pubkey(),
+        0,
+    );
+    let wrong_owner = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::claim_withdrawal(
+            &id(),
+            &stake_pool_accounts.stake_pool.pubkey(),
+            &stake_pool_accounts.withdraw_authority,
+            &withdrawal_ticket_address,
+            &wrong_owner.pubkey(),
+            &stake_pool_accounts.reserve_stake.pubkey(),
+            &wrong_owner.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_owner],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .err()
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::InvalidWithdrawalTicket);
+}
+
+#[tokio::test]
+async fn fail_increase_pending_lamports() {
+    let (mut context, stake_pool_accounts, user, pool_token_account, pool_tokens) =
+        setup(spl_token::id()).await;
+
+    let error = stake_pool_accounts
+        .request_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens,
+            0,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    // the staker cannot move the ticket lamports out of the reserve
+    let validator_stake = simple_add_validator_to_pool(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &stake_pool_accounts,
+        None,
+    )
+    .await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let stake_rent = rent.minimum_balance(std::mem::size_of::<stake::state::StakeStateV2>());
+    let reserve_lamports = context
+        .banks_client
+        .get_balance(stake_pool_accounts.reserve_stake.pubkey())
+        .await
+        .unwrap();
+    let error = stake_pool_accounts
+        .increase_validator_stake(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.transient_stake_account,
+            &validator_stake.stake_account,
+            &validator_stake.vote.pubkey(),
+            reserve_lamports - MINIMUM_RESERVE_LAMPORTS - stake_rent,
+            validator_stake.transient_stake_seed,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(0, InstructionError::InsufficientFunds)
+    );
+}
+
+#[tokio::test]
+async fn fail_withdraw_mln_pending_lamports() {
+    let (mut context, stake_pool_accounts, user, pool_token_account, pool_tokens) =
+        setup(spl_token::id()).await;
+
+    // enough for the staker to meet the minimum delegation
+    let error = stake_pool_accounts
+        .deposit_mln(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &pool_token_account,
+            TEST_STAKE_AMOUNT * 2,
+            None,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    let error = stake_pool_accounts
+        .request_withdrawal(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens / 2,
+            0,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    // the staker moves everything but the ticket lamports to a validator
+    let validator_stake = simple_add_validator_to_pool(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &stake_pool_accounts,
+        None,
+    )
+    .await;
+    let stake_pool = stake_pool_accounts
+        .get_stake_pool(&mut context.banks_client)
+        .await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let stake_rent = rent.minimum_balance(std::mem::size_of::<stake::state::StakeStateV2>());
+    let reserve_lamports = context
+        .banks_client
+        .get_balance(stake_pool_accounts.reserve_stake.pubkey())
+        .await
+        .unwrap();
+    let error = stake_pool_accounts
+        .increase_validator_stake(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &validator_stake.transient_stake_account,
+            &validator_stake.stake_account,
+            &validator_stake.vote.pubkey(),
+            reserve_lamports - stake_pool.pending_withdrawal_lamports - stake_rent * 2,
+            validator_stake.transient_stake_seed,
+        )
+        .await;
+    assert!(error.is_none(), "{:?}", error);
+
+    // the ticket lamports cannot be withdrawn instantly
+    let error = stake_pool_accounts
+        .withdraw_mln(
+            &mut context.banks_client,
+            &context.payer,
+            &context.last_blockhash,
+            &user,
+            &pool_token_account,
+            pool_tokens / 2,
+            None,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    assert_stake_pool_error(error, StakePoolError::MlnWithdrawalTooLarge);
+}
@@ -2,7 +2,7 @@
 
 from enum import IntEnum
 from typing import List, NamedTuple, Optional
-from construct import Bytes, Container, Struct, Switch, Int8ul, Int32ul, Int64ul, Pass  # type: ignore
+from construct import Bytes, Container, Struct, Switch, Int8ul, Int16ul, Int32ul, Int64ul, Pass  # type: ignore
 
 from solana.publickey import PublicKey
 from solana.utils.helpers import decode_byte_string
@@ -135,6 +135,12 @@ class ValidatorStakeInfo(NamedTuple):
     transient_seed_suffix: int
     """Transient account seed suffix."""
 
+    max_stake_share_bps: int
+    """Maximum share of the pool lamports on this validator, in basis points, or 0 for no maximum."""
+
+    stake_denied: bool
+    """Whether new stake to this validator is denied."""
+
     unused: int
     """Unused space, initially meant to specify the range of transient stake account suffixes."""
 
@@ -154,6 +160,8 @@ class ValidatorStakeInfo(NamedTuple):
             transient_stake_lamports=container['transient_stake_lamports'],
             last_update_epoch=container['last_update_epoch'],
             transient_seed_suffix=container['transient_seed_suffix'],
+            max_stake_share_bps=container['max_stake_share_bps'],
+            stake_denied=bool(container['stake_denied']),
             unused=container['unused'],
             validator_seed_suffix=container['validator_seed_suffix'],
             status=container['status'],
@@ -311,7 +319,9 @@ VALIDATOR_INFO_LAYOUT = Struct(
     "transient_stake_lamports" / Int64ul,
     "last_update_epoch" / Int64ul,
     "transient_seed_suffix" / Int64ul,
-    "unused" / Int32ul,
+    "max_stake_share_bps" / Int16ul,
+    "stake_denied" / Int8ul,
+    "unused" / Int8ul,
     "validator_seed_suffix" / Int32ul,
     "status" / Int8ul,
     "vote_account_address" / PUBLIC_KEY_LAYOUT,
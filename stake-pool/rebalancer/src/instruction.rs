@@ -0,0 +1,214 @@
+//! Instructions submitted by the rebalancer
+
+use {
+    crate::plan::StakeMove,
+    solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey},
+    spl_stake_pool::{
+        error::StakePoolError,
+        find_ephemeral_stake_program_address, find_stake_program_address,
+        find_transient_stake_program_address, find_withdraw_authority_program_address,
+        instruction::{
+            decrease_validator_stake_with_vote, increase_additional_validator_stake_with_vote,
+            redelegate,
+        },
+        state::{StakePool, ValidatorList, ValidatorStakeInfo},
+    },
+    std::num::NonZeroU32,
+};
+
+/// Seed of the ephemeral stake account, which only lives within an
+/// instruction
+const EPHEMERAL_STAKE_SEED: u64 = 0;
+
+/// Create the staker instruction performing a stake move
+pub fn stake_move_instruction(
+    program_id: &Pubkey,
+    stake_pool_address: &Pubkey,
+    stake_pool: &StakePool,
+    validator_list: &ValidatorList,
+    stake_move: &StakeMove,
+) -> Result<Instruction, ProgramError> {
+    match *stake_move {
+        StakeMove::Increase {
+            vote_account,
+            lamports,
+        } => {
+            let validator = find_validator(validator_list, &vote_account)?;
+            Ok(increase_additional_validator_stake_with_vote(
+                program_id,
+                stake_pool,
+                stake_pool_address,
+                &vote_account,
+                lamports,
+                NonZeroU32::new(validator.validator_seed_suffix.into()),
+                validator.transient_seed_suffix.into(),
+                EPHEMERAL_STAKE_SEED,
+            ))
+        }
+        StakeMove::Decrease {
+            vote_account,
+            lamports,
+        } => {
+            let validator = find_validator(validator_list, &vote_account)?;
+            Ok(decrease_validator_stake_with_vote(
+                program_id,
+                stake_pool,
+                stake_pool_address,
+                &vote_account,
+                lamports,
+                NonZeroU32::new(validator.validator_seed_suffix.into()),
+                validator.transient_seed_suffix.into(),
+            ))
+        }
+        StakeMove::Redelegate {
+            source_vote_account,
+            destination_vote_account,
+            lamports,
+        } => {
+            let source = find_validator(validator_list, &source_vote_account)?;
+            let destination = find_validator(validator_list, &destination_vote_account)?;
+            let source_transient_stake_seed = source.transient_seed_suffix.into();
+            let destination_transient_stake_seed = destination.transient_seed_suffix.into();
+            let (pool_withdraw_authority, _) =
+                find_withdraw_authority_program_address(program_id, stake_pool_address);
+            let (source_validator_stake, _) = find_stake_program_address(
+                program_id,
+                &source_vote_account,
+                stake_pool_address,
+                NonZeroU32::new(source.validator_seed_suffix.into()),
+            );
+            let (source_transient_stake, _) = find_transient_stake_program_address(
+                program_id,
+                &source_vote_account,
+                stake_pool_address,
+                source_transient_stake_seed,
+            );
+            let (ephemeral_stake, _) = find_ephemeral_stake_program_address(
+                program_id,
+                stake_pool_address,
+                EPHEMERAL_STAKE_SEED,
+            );
+            let (destination_transient_stake, _) = find_transient_stake_program_address(
+                program_id,
+                &destination_vote_account,
+                stake_pool_address,
+                destination_transient_stake_seed,
+            );
+            let (destination_validator_stake, _) = find_stake_program_address(
+                program_id,
+                &destination_vote_account,
+                stake_pool_address,
+                NonZeroU32::new(destination.validator_seed_suffix.into()),
+            );
+            Ok(redelegate(
+                program_id,
+                stake_pool_address,
+                &stake_pool.staker,
+                &pool_withdraw_authority,
+                &stake_pool.validator_list,
+                &stake_pool.reserve_stake,
+                &source_validator_stake,
+                &source_transient_stake,
+                &ephemeral_stake,
+                &destination_transient_stake,
+                &destination_validator_stake,
+                &destination_vote_account,
+                lamports,
+                source_transient_stake_seed,
+                EPHEMERAL_STAKE_SEED,
+                destination_transient_stake_seed,
+            ))
+        }
+    }
+}
+
+fn find_validator<'a>(
+    validator_list: &'a ValidatorList,
+    vote_account: &Pubkey,
+) -> Result<&'a ValidatorStakeInfo, ProgramError> {
+    validator_list
+        .find(vote_account)
+        .ok_or_else(|| StakePoolError::ValidatorNotFound.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, spl_stake_pool::instruction::StakePoolInstruction};
+
+    #[test]
+    fn redelegate_accounts() {
+        let program_id = spl_stake_pool::id();
+        let stake_pool_address = Pubkey::new_unique();
+        let stake_pool = StakePool {
+            staker: Pubkey::new_unique(),
+            validator_list: Pubkey::new_unique(),
+            reserve_stake: Pubkey::new_unique(),
+            ..StakePool::default()
+        };
+        let source_vote_account = Pubkey::new_unique();
+        let destination_vote_account = Pubkey::new_unique();
+        let mut validator_list = ValidatorList::new(2);
+        validator_list.validators = vec![
+            ValidatorStakeInfo {
+                vote_account_address: source_vote_account,
+                transient_seed_suffix: 3u64.into(),
+                ..ValidatorStakeInfo::default()
+            },
+            ValidatorStakeInfo {
+                vote_account_address: destination_vote_account,
+                transient_seed_suffix: 5u64.into(),
+                validator_seed_suffix: 7u32.into(),
+                ..ValidatorStakeInfo::default()
+            },
+        ];
+
+        let instruction = stake_move_instruction(
+            &program_id,
+            &stake_pool_address,
+            &stake_pool,
+            &validator_list,
+            &StakeMove::Redelegate {
+                source_vote_account,
+                destination_vote_account,
+                lamports: 1_000,
+            },
+        )
+        .unwrap();
+        assert!(instruction.accounts[1].is_signer);
+        assert_eq!(instruction.accounts[1].pubkey, stake_pool.staker);
+        assert_eq!(
+            instruction.accounts[9].pubkey,
+            find_stake_program_address(
+                &program_id,
+                &destination_vote_account,
+                &stake_pool_address,
+                NonZeroU32::new(7),
+            )
+            .0
+        );
+        assert_eq!(instruction.accounts[10].pubkey, destination_vote_account);
+        assert_eq!(
+            borsh::from_slice::<StakePoolInstruction>(&instruction.data).unwrap(),
+            StakePoolInstruction::Redelegate {
+                lamports: 1_000,
+                source_transient_stake_seed: 3,
+                ephemeral_stake_seed: EPHEMERAL_STAKE_SEED,
+                destination_transient_stake_seed: 5,
+            }
+        );
+
+        assert_eq!(
+            stake_move_instruction(
+                &program_id,
+                &stake_pool_address,
+                &stake_pool,
+                &validator_list,
+                &StakeMove::Decrease {
+                    vote_account: Pubkey::new_unique(),
+                    lamports: 1_000,
+                },
+            ),
+            Err(StakePoolError::ValidatorNotFound.into())
+        );
+    }
+}
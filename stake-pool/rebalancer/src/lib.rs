@@ -0,0 +1,17 @@
+#![deny(missing_docs)]
+
+//! A crank rebalancing the stake of a stake pool between its validators.
+//!
+//! The rebalancer scores the validators of a stake pool from their commission,
+//! skipped leader slots and delinquency, allocates the pool lamports in
+//! proportion to the scores, and moves stake towards those targets within
+//! limits on the stake moved per epoch.
+
+pub mod instruction;
+pub mod plan;
+pub mod rpc;
+pub mod score;
+
+// Export current sdk types for downstream users building with a different sdk
+// version
+pub use solana_program;
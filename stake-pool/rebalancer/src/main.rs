@@ -0,0 +1,370 @@
+use {
+    clap::{
+        crate_description, crate_name, crate_version, value_t, value_t_or_exit, App, Arg,
+        ArgMatches,
+    },
+    miraland_clap_utils::{
+        input_parsers::pubkey_of,
+        input_validators::{
+            is_amount, is_keypair, is_parsable, is_pubkey, is_url, is_valid_percentage,
+        },
+        keypair::signer_from_path,
+    },
+    miraland_client::rpc_client::RpcClient,
+    solana_program::{instruction::Instruction, pubkey::Pubkey},
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        message::Message,
+        native_token::{self, Mln},
+        signature::Signer,
+        transaction::Transaction,
+    },
+    spl_stake_pool_rebalancer::{
+        instruction::stake_move_instruction,
+        plan::{plan_rebalance, RebalanceConfig, RebalancePlan, StakeMove},
+        rpc::{get_stake_minimums, get_stake_pool, get_validator_list, get_validator_metrics},
+        score::{ScoreConfig, BASIS_POINTS},
+    },
+    std::{process::exit, thread::sleep, time::Duration},
+};
+
+struct Config {
+    rpc_client: RpcClient,
+    staker: Box<dyn Signer>,
+    stake_pool_address: Pubkey,
+    rebalance_config: RebalanceConfig,
+    dry_run: bool,
+}
+
+type Error = Box<dyn std::error::Error>;
+
+fn main() {
+    miraland_logger::setup_with_default("solana=info,miraland=info");
+
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .arg({
+            let arg = Arg::with_name("config_file")
+                .short("C")
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Configuration file to use");
+            if let Some(ref config_file) = *miraland_cli_config::CONFIG_FILE {
+                arg.default_value(config_file)
+            } else {
+                arg
+            }
+        })
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .validator(is_url)
+                .help("JSON RPC URL for the cluster.  Default from the configuration file."),
+        )
+        .arg(
+            Arg::with_name("staker")
+                .long("staker")
+                .validator(is_keypair)
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .help("Stake pool staker and fee payer.  Default from the configuration file."),
+        )
+        .arg(
+            Arg::with_name("stake_pool")
+                .long("pool")
+                .validator(is_pubkey)
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .required(true)
+                .help("Stake pool to rebalance"),
+        )
+        .arg(
+            Arg::with_name("retained_reserve")
+                .long("retained-reserve")
+                .validator(is_amount)
+                .value_name("AMOUNT")
+                .takes_value(true)
+                .default_value("0")
+                .help("Amount in MLN to keep in the reserve, not allocated to validators"),
+        )
+        .arg(
+            Arg::with_name("max_commission")
+                .long("max-commission")
+                .validator(is_valid_percentage)
+                .value_name("PERCENT")
+                .takes_value(true)
+                .default_value("10")
+                .help("Maximum commission of a validator receiving stake"),
+        )
+        .arg(
+            Arg::with_name("max_skip_rate")
+                .long("max-skip-rate")
+                .validator(is_valid_percentage)
+                .value_name("PERCENT")
+                .takes_value(true)
+                .default_value("10")
+                .help("Maximum skipped leader slots of a validator receiving stake"),
+        )
+        .arg(
+            Arg::with_name("max_epoch_move")
+                .long("max-epoch-move")
+                .validator(is_amount)
+                .value_name("AMOUNT")
+                .takes_value(true)
+                .help("Maximum amount in MLN moved per epoch.  Default unlimited."),
+        )
+        .arg(
+            Arg::with_name("max_epoch_moves")
+                .long("max-epoch-moves")
+                .validator(is_parsable::<usize>)
+                .value_name("NUMBER")
+                .takes_value(true)
+                .default_value("10")
+                .help("Maximum number of stake moves per epoch"),
+        )
+        .arg(
+            Arg::with_name("redelegate")
+                .long("redelegate")
+                .takes_value(false)
+                .help(
+                    "Move stake directly between validators with Redelegate, \
+                    instead of through the reserve",
+                ),
+        )
+        .arg(
+            Arg::with_name("interval")
+                .long("interval")
+                .validator(is_parsable::<u64>)
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("600")
+                .help("Seconds to wait between rebalancing attempts"),
+        )
+        .arg(
+            Arg::with_name("once")
+                .long("once")
+                .takes_value(false)
+                .help("Rebalance once and exit"),
+        )
+        .arg(
+            Arg::with_name("dry_run")
+                .long("dry-run")
+                .takes_value(false)
+                .help("Print the rebalancing plan without submitting it"),
+        )
+        .get_matches();
+
+    let mut wallet_manager = None;
+    let config = {
+        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+            miraland_cli_config::Config::load(config_file).unwrap_or_default()
+        } else {
+            miraland_cli_config::Config::default()
+        };
+        let json_rpc_url = value_t!(matches, "json_rpc_url", String)
+            .unwrap_or_else(|_| cli_config.json_rpc_url.clone());
+
+        let staker = signer_from_path(
+            &matches,
+            matches
+                .value_of("staker")
+                .unwrap_or(&cli_config.keypair_path),
+            "staker",
+            &mut wallet_manager,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            exit(1);
+        });
+
+        Config {
+            rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
+            staker,
+            stake_pool_address: pubkey_of(&matches, "stake_pool").unwrap(),
+            rebalance_config: rebalance_config_of(&matches),
+            dry_run: matches.is_present("dry_run"),
+        }
+    };
+
+    let interval = Duration::from_secs(value_t_or_exit!(matches, "interval", u64));
+    loop {
+        if let Err(err) = rebalance(&config) {
+            eprintln!("Failed to rebalance stake pool: {}", err);
+        }
+        if matches.is_present("once") {
+            break;
+        }
+        sleep(interval);
+    }
+}
+
+/// Update the stake pool if needed, then plan and submit the stake moves
+fn rebalance(config: &Config) -> Result<(), Error> {
+    let mut stake_pool = get_stake_pool(&config.rpc_client, &config.stake_pool_address)?;
+    let epoch = config.rpc_client.get_epoch_info()?.epoch;
+    if stake_pool.last_update_epoch != epoch {
+        if config.dry_run {
+            println!(
+                "Stake pool last updated in epoch {}, planning from stale balances",
+                stake_pool.last_update_epoch
+            );
+        } else {
+            println!("Updating stake pool for epoch {}", epoch);
+            update_stake_pool(config, &stake_pool)?;
+            stake_pool = get_stake_pool(&config.rpc_client, &config.stake_pool_address)?;
+        }
+    }
+
+    let validator_list = get_validator_list(&config.rpc_client, &stake_pool.validator_list)?;
+    // The lamports owed to the pending withdrawal tickets are not part of the
+    // pool anymore, and stay in the reserve until claimed
+    let reserve_lamports = config
+        .rpc_client
+        .get_balance(&stake_pool.reserve_stake)?
+        .saturating_sub(stake_pool.pending_withdrawal_lamports);
+    let plan = plan_rebalance(
+        &validator_list,
+        stake_pool.total_lamports,
+        reserve_lamports,
+        &get_validator_metrics(&config.rpc_client)?,
+        &get_stake_minimums(&config.rpc_client)?,
+        &config.rebalance_config,
+    );
+    print_plan(epoch, stake_pool.total_lamports, reserve_lamports, &plan);
+    if stake_pool.pending_withdrawal_lamports > 0 {
+        println!(
+            "Retaining {} in the reserve for withdrawal tickets",
+            Mln(stake_pool.pending_withdrawal_lamports)
+        );
+    }
+
+    if config.dry_run {
+        return Ok(());
+    }
+    for stake_move in plan.moves.iter() {
+        let instruction = stake_move_instruction(
+            &spl_stake_pool::id(),
+            &config.stake_pool_address,
+            &stake_pool,
+            &validator_list,
+            stake_move,
+        )?;
+        if let Err(err) = send_instructions(config, &[instruction]) {
+            eprintln!("Failed to {}: {}", describe_move(stake_move), err);
+        }
+    }
+    Ok(())
+}
+
+fn update_stake_pool(
+    config: &Config,
+    stake_pool: &spl_stake_pool::state::StakePool,
+) -> Result<(), Error> {
+    let validator_list = get_validator_list(&config.rpc_client, &stake_pool.validator_list)?;
+    let (update_list_instructions, final_instructions) =
+        spl_stake_pool::instruction::update_stake_pool(
+            &spl_stake_pool::id(),
+            stake_pool,
+            &validator_list,
+            &config.stake_pool_address,
+            false,
+        );
+    for instruction in update_list_instructions {
+        send_instructions(config, &[instruction])?;
+    }
+    send_instructions(config, &final_instructions)
+}
+
+fn print_plan(epoch: u64, total_lamports: u64, reserve_lamports: u64, plan: &RebalancePlan) {
+    println!(
+        "Epoch {}: {} in the pool, {} in the reserve",
+        epoch,
+        Mln(total_lamports),
+        Mln(reserve_lamports)
+    );
+    println!(
+        "{:<44}  {:>9}  {:>20}  {:>20}  {:>20}",
+        "Vote Account", "Score", "Active", "Transient", "Target"
+    );
+    for allocation in plan.allocations.iter() {
+        println!(
+            "{:<44}  {:>9}  {:>20}  {:>20}  {:>20}",
+            allocation.vote_account.to_string(),
+            allocation.score,
+            Mln(allocation.active_lamports).to_string(),
+            Mln(allocation.transient_lamports).to_string(),
+            allocation
+                .target_lamports
+                .map(|target_lamports| Mln(target_lamports).to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        );
+    }
+    if plan.moves.is_empty() {
+        println!("No stake to move");
+    }
+    for stake_move in plan.moves.iter() {
+        println!("Planned: {}", describe_move(stake_move));
+    }
+}
+
+fn describe_move(stake_move: &StakeMove) -> String {
+    match stake_move {
+        StakeMove::Increase {
+            vote_account,
+            lamports,
+        } => format!("increase {} on {}", Mln(*lamports), vote_account),
+        StakeMove::Decrease {
+            vote_account,
+            lamports,
+        } => format!("decrease {} on {}", Mln(*lamports), vote_account),
+        StakeMove::Redelegate {
+            source_vote_account,
+            destination_vote_account,
+            lamports,
+        } => format!(
+            "redelegate {} from {} to {}",
+            Mln(*lamports),
+            source_vote_account,
+            destination_vote_account
+        ),
+    }
+}
+
+fn send_instructions(config: &Config, instructions: &[Instruction]) -> Result<(), Error> {
+    let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+    let message = Message::new_with_blockhash(
+        instructions,
+        Some(&config.staker.pubkey()),
+        &recent_blockhash,
+    );
+    let transaction = Transaction::new(&[config.staker.as_ref()], message, recent_blockhash);
+    let signature = config
+        .rpc_client
+        .send_and_confirm_transaction(&transaction)?;
+    println!("Signature: {}", signature);
+    Ok(())
+}
+
+fn rebalance_config_of(matches: &ArgMatches<'_>) -> RebalanceConfig {
+    RebalanceConfig {
+        score: ScoreConfig {
+            max_commission: value_t_or_exit!(matches, "max_commission", u8),
+            max_skip_rate_bps: value_t_or_exit!(matches, "max_skip_rate", u64)
+                .saturating_mul(BASIS_POINTS / 100),
+        },
+        retained_reserve_lamports: native_token::mln_to_lamports(value_t_or_exit!(
+            matches,
+            "retained_reserve",
+            f64
+        )),
+        max_epoch_move_lamports: value_t!(matches, "max_epoch_move", f64)
+            .map(native_token::mln_to_lamports)
+            .unwrap_or(u64::MAX),
+        max_epoch_moves: value_t_or_exit!(matches, "max_epoch_moves", usize),
+        redelegate: matches.is_present("redelegate"),
+    }
+}
@@ -0,0 +1,539 @@
+//! Target allocations and stake moves
+
+use {
+    crate::score::{score, ScoreConfig, ValidatorMetrics},
+    solana_program::pubkey::Pubkey,
+    spl_stake_pool::{
+        state::{StakeStatus, ValidatorList},
+        MAX_VALIDATOR_STAKE_SHARE_BPS,
+    },
+    std::{cmp::Ordering, collections::HashMap},
+};
+
+/// Sizes of stake accounts constraining the stake moves
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakeMinimums {
+    /// Rent exemption of a stake account
+    pub stake_rent: u64,
+    /// Minimum delegation of a stake pool stake account, as given by
+    /// `spl_stake_pool::minimum_delegation`
+    pub minimum_delegation: u64,
+}
+
+impl StakeMinimums {
+    /// Minimum lamports of a validator stake account, which can never be
+    /// decreased or redelegated
+    pub fn validator_lamports(&self) -> u64 {
+        self.stake_rent.saturating_add(self.minimum_delegation)
+    }
+}
+
+/// Rebalancing strategy and limits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RebalanceConfig {
+    /// Thresholds of the validator scores
+    pub score: ScoreConfig,
+    /// Lamports kept in the reserve, not allocated to validators
+    pub retained_reserve_lamports: u64,
+    /// Maximum lamports moved per epoch, including the transient stake
+    /// already in the validator list
+    pub max_epoch_move_lamports: u64,
+    /// Maximum stake moves per epoch, including the validators already having
+    /// transient stake
+    pub max_epoch_moves: usize,
+    /// Move stake between validators with `Redelegate`, instead of decreasing
+    /// stake into the reserve and increasing stake from the reserve
+    pub redelegate: bool,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        Self {
+            score: ScoreConfig::default(),
+            retained_reserve_lamports: 0,
+            max_epoch_move_lamports: u64::MAX,
+            max_epoch_moves: usize::MAX,
+            redelegate: false,
+        }
+    }
+}
+
+/// Current and target stake of a validator of the pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatorAllocation {
+    /// Vote account of the validator
+    pub vote_account: Pubkey,
+    /// Score of the validator, zero for validators being removed or denied
+    /// new stake
+    pub score: u64,
+    /// Lamports of the validator stake account
+    pub active_lamports: u64,
+    /// Lamports of the validator transient stake account
+    pub transient_lamports: u64,
+    /// Lamports allocated to the validator, `None` for validators being
+    /// removed or when no validator has a score
+    pub target_lamports: Option<u64>,
+}
+
+impl ValidatorAllocation {
+    /// Lamports currently staked to the validator
+    pub fn current_lamports(&self) -> u64 {
+        self.active_lamports.saturating_add(self.transient_lamports)
+    }
+}
+
+/// Stake moved by the rebalancer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakeMove {
+    /// `IncreaseAdditionalValidatorStake` from the reserve
+    Increase {
+        /// Vote account of the validator
+        vote_account: Pubkey,
+        /// Lamports to delegate
+        lamports: u64,
+    },
+    /// `DecreaseValidatorStakeWithReserve` into the reserve
+    Decrease {
+        /// Vote account of the validator
+        vote_account: Pubkey,
+        /// Lamports to deactivate
+        lamports: u64,
+    },
+    /// `Redelegate` from a validator to another
+    Redelegate {
+        /// Vote account of the validator losing stake
+        source_vote_account: Pubkey,
+        /// Vote account of the validator receiving stake
+        destination_vote_account: Pubkey,
+        /// Lamports to redelegate
+        lamports: u64,
+    },
+}
+
+impl StakeMove {
+    /// Lamports moved
+    pub fn lamports(&self) -> u64 {
+        match self {
+            Self::Increase { lamports, .. }
+            | Self::Decrease { lamports, .. }
+            | Self::Redelegate { lamports, .. } => *lamports,
+        }
+    }
+}
+
+/// Allocations of the validators of a pool and the stake moves towards them
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RebalancePlan {
+    /// Allocation of each validator, in validator list order
+    pub allocations: Vec<ValidatorAllocation>,
+    /// Stake moves to submit this epoch
+    pub moves: Vec<StakeMove>,
+}
+
+/// Plan the rebalancing of an up-to-date stake pool.
+///
+/// Every active validator keeps the minimum validator stake, and the
+/// remaining pool lamports, minus the retained reserve and the stake of the
+/// validators being removed, are allocated in proportion to the scores.
+/// Validators without metrics or denied new stake are scored zero, and no
+/// target exceeds the maximum stake share of its validator, the lamports over
+/// it staying in the reserve.
+///
+/// Validators with transient stake are left alone, their transient stake
+/// counting towards the epoch limits. Increases are planned before decreases,
+/// the largest differences from the targets first.
+pub fn plan_rebalance(
+    validator_list: &ValidatorList,
+    total_lamports: u64,
+    reserve_lamports: u64,
+    metrics: &HashMap<Pubkey, ValidatorMetrics>,
+    minimums: &StakeMinimums,
+    config: &RebalanceConfig,
+) -> RebalancePlan {
+    let max_validator_lamports = |max_stake_share_bps: u16| {
+        if max_stake_share_bps == 0 {
+            return u64::MAX;
+        }
+        let max_lamports = (total_lamports as u128).saturating_mul(max_stake_share_bps as u128)
+            / MAX_VALIDATOR_STAKE_SHARE_BPS as u128;
+        u64::try_from(max_lamports).unwrap_or(u64::MAX)
+    };
+    let mut allocated_lamports = total_lamports.saturating_sub(config.retained_reserve_lamports);
+    let mut allocations: Vec<ValidatorAllocation> = validator_list
+        .validators
+        .iter()
+        .map(|validator| {
+            let is_active = matches!(
+                StakeStatus::try_from(validator.status),
+                Ok(StakeStatus::Active)
+            );
+            let allocation = ValidatorAllocation {
+                vote_account: validator.vote_account_address,
+                score: if is_active && !bool::from(validator.stake_denied) {
+                    metrics
+                        .get(&validator.vote_account_address)
+                        .map(|metrics| score(metrics, &config.score))
+                        .unwrap_or(0)
+                } else {
+                    0
+                },
+                active_lamports: validator.active_stake_lamports.into(),
+                transient_lamports: validator.transient_stake_lamports.into(),
+                // set once the scores are known
+                target_lamports: is_active.then_some(0),
+            };
+            allocated_lamports = allocated_lamports.saturating_sub(if is_active {
+                minimums.validator_lamports()
+            } else {
+                allocation.current_lamports()
+            });
+            allocation
+        })
+        .collect();
+
+    let total_score: u128 = allocations
+        .iter()
+        .map(|allocation| allocation.score as u128)
+        .sum();
+    if total_score == 0 {
+        for allocation in allocations.iter_mut() {
+            allocation.target_lamports = None;
+        }
+        return RebalancePlan {
+            allocations,
+            moves: vec![],
+        };
+    }
+    for (allocation, validator) in allocations.iter_mut().zip(&validator_list.validators) {
+        if allocation.target_lamports.is_some() {
+            let share = (allocated_lamports as u128)
+                .saturating_mul(allocation.score as u128)
+                .checked_div(total_score)
+                .unwrap_or(0);
+            let target_lamports = minimums
+                .validator_lamports()
+                .saturating_add(u64::try_from(share).unwrap_or(u64::MAX))
+                .min(max_validator_lamports(validator.max_stake_share_bps.into()));
+            allocation.target_lamports = Some(target_lamports.max(minimums.validator_lamports()));
+        }
+    }
+
+    let mut remaining_lamports = config.max_epoch_move_lamports.saturating_sub(
+        allocations
+            .iter()
+            .map(|allocation| allocation.transient_lamports)
+            .sum(),
+    );
+    let mut remaining_moves = config.max_epoch_moves.saturating_sub(
+        allocations
+            .iter()
+            .filter(|allocation| allocation.transient_lamports > 0)
+            .count(),
+    );
+
+    let mut excesses = vec![];
+    let mut deficits = vec![];
+    for allocation in allocations.iter() {
+        if allocation.transient_lamports > 0 {
+            continue;
+        }
+        if let Some(target_lamports) = allocation.target_lamports {
+            let current_lamports = allocation.current_lamports();
+            match current_lamports.cmp(&target_lamports) {
+                Ordering::Greater => {
+                    excesses.push((allocation.vote_account, current_lamports - target_lamports))
+                }
+                Ordering::Less => {
+                    deficits.push((allocation.vote_account, target_lamports - current_lamports))
+                }
+                Ordering::Equal => {}
+            }
+        }
+    }
+    excesses.sort_by(|(_, a), (_, b)| b.cmp(a));
+    deficits.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    let mut moves = vec![];
+    if config.redelegate {
+        for ((source_vote_account, excess), (destination_vote_account, deficit)) in
+            excesses.into_iter().zip(deficits)
+        {
+            if remaining_moves == 0 {
+                break;
+            }
+            // the rent exemption of the destination transient stake account is
+            // taken from the redelegated lamports
+            let lamports = excess
+                .min(deficit.saturating_add(minimums.stake_rent))
+                .min(remaining_lamports);
+            if lamports < minimums.validator_lamports() {
+                continue;
+            }
+            moves.push(StakeMove::Redelegate {
+                source_vote_account,
+                destination_vote_account,
+                lamports,
+            });
+            remaining_lamports -= lamports;
+            remaining_moves -= 1;
+        }
+    } else {
+        // an increase takes the rent exemption of the transient stake account
+        // from the reserve, which must stay rent exempt itself
+        let mut reserve_available_lamports =
+            reserve_lamports.saturating_sub(config.retained_reserve_lamports);
+        for (vote_account, deficit) in deficits {
+            if remaining_moves == 0 {
+                break;
+            }
+            let lamports = deficit.min(remaining_lamports).min(
+                reserve_available_lamports.saturating_sub(minimums.stake_rent.saturating_mul(2)),
+            );
+            if lamports < minimums.minimum_delegation {
+                continue;
+            }
+            moves.push(StakeMove::Increase {
+                vote_account,
+                lamports,
+            });
+            reserve_available_lamports = reserve_available_lamports
+                .saturating_sub(lamports.saturating_add(minimums.stake_rent));
+            remaining_lamports -= lamports;
+            remaining_moves -= 1;
+        }
+        for (vote_account, excess) in excesses {
+            if remaining_moves == 0 {
+                break;
+            }
+            let lamports = excess.min(remaining_lamports);
+            if lamports < minimums.minimum_delegation {
+                continue;
+            }
+            moves.push(StakeMove::Decrease {
+                vote_account,
+                lamports,
+            });
+            remaining_lamports -= lamports;
+            remaining_moves -= 1;
+        }
+    }
+
+    RebalancePlan { allocations, moves }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, spl_stake_pool::state::ValidatorStakeInfo};
+
+    const MINIMUMS: StakeMinimums = StakeMinimums {
+        stake_rent: 2_282_880,
+        minimum_delegation: 1_000_000_000,
+    };
+
+    fn validator_list(validators: &[(Pubkey, u64, u64)]) -> ValidatorList {
+        let mut validator_list = ValidatorList::new(validators.len() as u32);
+        validator_list.validators = validators
+            .iter()
+            .map(
+                |(vote_account_address, active_stake_lamports, transient_stake_lamports)| {
+                    ValidatorStakeInfo {
+                        status: StakeStatus::Active.into(),
+                        vote_account_address: *vote_account_address,
+                        active_stake_lamports: (*active_stake_lamports).into(),
+                        transient_stake_lamports: (*transient_stake_lamports).into(),
+                        ..ValidatorStakeInfo::default()
+                    }
+                },
+            )
+            .collect();
+        validator_list
+    }
+
+    fn metrics(vote_account: Pubkey, commission: u8) -> (Pubkey, ValidatorMetrics) {
+        (
+            vote_account,
+            ValidatorMetrics {
+                vote_account,
+                commission,
+                ..ValidatorMetrics::default()
+            },
+        )
+    }
+
+    #[test]
+    fn increase_and_decrease() {
+        let good = Pubkey::new_unique();
+        let bad = Pubkey::new_unique();
+        let validator_lamports = MINIMUMS.validator_lamports();
+        let reserve_lamports = 100 * validator_lamports;
+        let validator_list = validator_list(&[
+            (good, validator_lamports, 0),
+            (bad, 50 * validator_lamports, 0),
+        ]);
+        let total_lamports = reserve_lamports + 51 * validator_lamports;
+        let metrics = HashMap::from([metrics(good, 0), metrics(bad, 50)]);
+
+        let plan = plan_rebalance(
+            &validator_list,
+            total_lamports,
+            reserve_lamports,
+            &metrics,
+            &MINIMUMS,
+            &RebalanceConfig::default(),
+        );
+        assert_eq!(plan.allocations[1].score, 0);
+        assert_eq!(
+            plan.allocations[1].target_lamports,
+            Some(validator_lamports)
+        );
+        assert_eq!(
+            plan.allocations[0].target_lamports,
+            Some(total_lamports - validator_lamports)
+        );
+        assert_eq!(
+            plan.moves,
+            vec![
+                StakeMove::Increase {
+                    vote_account: good,
+                    lamports: reserve_lamports - 2 * MINIMUMS.stake_rent,
+                },
+                StakeMove::Decrease {
+                    vote_account: bad,
+                    lamports: 49 * validator_lamports,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn epoch_limits() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let c = Pubkey::new_unique();
+        let validator_lamports = MINIMUMS.validator_lamports();
+        let validator_list = validator_list(&[
+            (a, 10 * validator_lamports, 0),
+            (b, validator_lamports, 0),
+            (c, validator_lamports, validator_lamports),
+        ]);
+        let total_lamports = 13 * validator_lamports;
+        let metrics = HashMap::from([metrics(a, 100), metrics(b, 0), metrics(c, 0)]);
+        let config = RebalanceConfig {
+            score: ScoreConfig {
+                max_commission: 100,
+                ..ScoreConfig::default()
+            },
+            max_epoch_move_lamports: 3 * validator_lamports,
+            max_epoch_moves: 3,
+            redelegate: true,
+            ..RebalanceConfig::default()
+        };
+
+        let plan = plan_rebalance(
+            &validator_list,
+            total_lamports,
+            0,
+            &metrics,
+            &MINIMUMS,
+            &config,
+        );
+        // the transient stake of c counts towards the epoch limit
+        assert_eq!(
+            plan.moves,
+            vec![StakeMove::Redelegate {
+                source_vote_account: a,
+                destination_vote_account: b,
+                lamports: 2 * validator_lamports,
+            }]
+        );
+
+        let plan = plan_rebalance(
+            &validator_list,
+            total_lamports,
+            0,
+            &metrics,
+            &MINIMUMS,
+            &RebalanceConfig {
+                max_epoch_moves: 1,
+                ..config
+            },
+        );
+        assert!(plan.moves.is_empty());
+    }
+
+    #[test]
+    fn denied_and_capped_validators() {
+        let capped = Pubkey::new_unique();
+        let denied = Pubkey::new_unique();
+        let uncapped = Pubkey::new_unique();
+        let validator_lamports = MINIMUMS.validator_lamports();
+        let reserve_lamports = 28 * validator_lamports;
+        let mut validator_list = validator_list(&[
+            (capped, validator_lamports, 0),
+            (denied, 10 * validator_lamports, 0),
+            (uncapped, validator_lamports, 0),
+        ]);
+        validator_list.validators[0].max_stake_share_bps = 2_000.into();
+        validator_list.validators[1].stake_denied = true.into();
+        let total_lamports = reserve_lamports + 12 * validator_lamports;
+        let metrics = HashMap::from([metrics(capped, 0), metrics(denied, 0), metrics(uncapped, 0)]);
+
+        let plan = plan_rebalance(
+            &validator_list,
+            total_lamports,
+            reserve_lamports,
+            &metrics,
+            &MINIMUMS,
+            &RebalanceConfig::default(),
+        );
+        // the capped validator is held to 20% of the pool, its share over the
+        // cap staying in the reserve, and the denied validator only keeps the
+        // minimum
+        let share = 37 * validator_lamports / 2;
+        assert_eq!(
+            plan.allocations
+                .iter()
+                .map(|allocation| allocation.target_lamports)
+                .collect::<Vec<_>>(),
+            vec![
+                Some(total_lamports / 5),
+                Some(validator_lamports),
+                Some(validator_lamports + share),
+            ]
+        );
+        assert_eq!(plan.allocations[1].score, 0);
+        assert_eq!(
+            plan.moves,
+            vec![
+                StakeMove::Increase {
+                    vote_account: uncapped,
+                    lamports: share,
+                },
+                StakeMove::Increase {
+                    vote_account: capped,
+                    lamports: total_lamports / 5 - validator_lamports,
+                },
+                StakeMove::Decrease {
+                    vote_account: denied,
+                    lamports: 9 * validator_lamports,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn no_score() {
+        let vote_account = Pubkey::new_unique();
+        let validator_list = validator_list(&[(vote_account, MINIMUMS.validator_lamports(), 0)]);
+        let plan = plan_rebalance(
+            &validator_list,
+            10 * MINIMUMS.validator_lamports(),
+            9 * MINIMUMS.validator_lamports(),
+            &HashMap::new(),
+            &MINIMUMS,
+            &RebalanceConfig::default(),
+        );
+        assert_eq!(plan.allocations[0].target_lamports, None);
+        assert!(plan.moves.is_empty());
+    }
+}
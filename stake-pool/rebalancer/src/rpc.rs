@@ -0,0 +1,82 @@
+//! Stake pool accounts and validator metrics read over RPC
+
+use {
+    crate::{plan::StakeMinimums, score::ValidatorMetrics},
+    miraland_client::rpc_client::RpcClient,
+    solana_program::{borsh1::try_from_slice_unchecked, pubkey::Pubkey, stake},
+    spl_stake_pool::{
+        minimum_delegation,
+        state::{StakePool, ValidatorList},
+    },
+    std::{collections::HashMap, str::FromStr},
+};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Read a stake pool
+pub fn get_stake_pool(
+    rpc_client: &RpcClient,
+    stake_pool_address: &Pubkey,
+) -> Result<StakePool, Error> {
+    let account_data = rpc_client.get_account_data(stake_pool_address)?;
+    let stake_pool = StakePool::unpack_from_slice(account_data.as_slice())
+        .map_err(|err| format!("Invalid stake pool {}: {}", stake_pool_address, err))?;
+    Ok(stake_pool)
+}
+
+/// Read the validator list of a stake pool
+pub fn get_validator_list(
+    rpc_client: &RpcClient,
+    validator_list_address: &Pubkey,
+) -> Result<ValidatorList, Error> {
+    let account_data = rpc_client.get_account_data(validator_list_address)?;
+    let validator_list = try_from_slice_unchecked::<ValidatorList>(account_data.as_slice())
+        .map_err(|err| format!("Invalid validator list {}: {}", validator_list_address, err))?;
+    Ok(validator_list)
+}
+
+/// Read the rent exemption of a stake account and the minimum delegation of
+/// the stake program
+pub fn get_stake_minimums(rpc_client: &RpcClient) -> Result<StakeMinimums, Error> {
+    let stake_rent = rpc_client
+        .get_minimum_balance_for_rent_exemption(std::mem::size_of::<stake::state::StakeStateV2>())?;
+    let stake_minimum_delegation = rpc_client.get_stake_minimum_delegation()?;
+    Ok(StakeMinimums {
+        stake_rent,
+        minimum_delegation: minimum_delegation(stake_minimum_delegation),
+    })
+}
+
+/// Read the commission and delinquency of every vote account, with the block
+/// production of its identity over the current epoch, keyed by vote account
+pub fn get_validator_metrics(
+    rpc_client: &RpcClient,
+) -> Result<HashMap<Pubkey, ValidatorMetrics>, Error> {
+    let vote_accounts = rpc_client.get_vote_accounts()?;
+    let block_production = rpc_client.get_block_production()?.value.by_identity;
+
+    let mut metrics = HashMap::new();
+    for (vote_account_info, delinquent) in vote_accounts
+        .current
+        .iter()
+        .map(|info| (info, false))
+        .chain(vote_accounts.delinquent.iter().map(|info| (info, true)))
+    {
+        let vote_account = Pubkey::from_str(&vote_account_info.vote_pubkey)?;
+        let (leader_slots, blocks_produced) = block_production
+            .get(&vote_account_info.node_pubkey)
+            .copied()
+            .unwrap_or_default();
+        metrics.insert(
+            vote_account,
+            ValidatorMetrics {
+                vote_account,
+                commission: vote_account_info.commission,
+                leader_slots: leader_slots as u64,
+                blocks_produced: blocks_produced as u64,
+                delinquent,
+            },
+        );
+    }
+    Ok(metrics)
+}
@@ -0,0 +1,121 @@
+//! Validator scoring
+
+use solana_program::pubkey::Pubkey;
+
+/// Basis points in a whole
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// Performance of a validator over the current epoch, from its vote account
+/// and the block production of its identity
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ValidatorMetrics {
+    /// Vote account of the validator
+    pub vote_account: Pubkey,
+    /// Commission taken on the staking rewards, in percent
+    pub commission: u8,
+    /// Leader slots assigned to the validator identity
+    pub leader_slots: u64,
+    /// Blocks produced in those leader slots
+    pub blocks_produced: u64,
+    /// Whether the vote account is delinquent
+    pub delinquent: bool,
+}
+
+impl ValidatorMetrics {
+    /// Skipped leader slots, in basis points of the leader slots
+    pub fn skip_rate_bps(&self) -> u64 {
+        if self.leader_slots == 0 {
+            return 0;
+        }
+        self.leader_slots
+            .saturating_sub(self.blocks_produced)
+            .saturating_mul(BASIS_POINTS)
+            / self.leader_slots
+    }
+}
+
+/// Thresholds above which validators are scored zero
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScoreConfig {
+    /// Maximum commission, in percent
+    pub max_commission: u8,
+    /// Maximum skip rate, in basis points
+    pub max_skip_rate_bps: u64,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self {
+            max_commission: 10,
+            max_skip_rate_bps: 1_000,
+        }
+    }
+}
+
+/// Score a validator: zero when delinquent or above the thresholds, otherwise
+/// `(100 - commission) * (10_000 - skip_rate_bps)`, so that stake goes to the
+/// validators passing on more rewards and producing more of their blocks
+pub fn score(metrics: &ValidatorMetrics, config: &ScoreConfig) -> u64 {
+    let skip_rate_bps = metrics.skip_rate_bps();
+    if metrics.delinquent
+        || metrics.commission > config.max_commission
+        || skip_rate_bps > config.max_skip_rate_bps
+    {
+        return 0;
+    }
+    100u64
+        .saturating_sub(metrics.commission as u64)
+        .saturating_mul(BASIS_POINTS.saturating_sub(skip_rate_bps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_rate() {
+        let metrics = ValidatorMetrics {
+            leader_slots: 400,
+            blocks_produced: 390,
+            ..ValidatorMetrics::default()
+        };
+        assert_eq!(metrics.skip_rate_bps(), 250);
+        assert_eq!(ValidatorMetrics::default().skip_rate_bps(), 0);
+    }
+
+    #[test]
+    fn score_thresholds() {
+        let config = ScoreConfig::default();
+        let metrics = ValidatorMetrics {
+            commission: 5,
+            leader_slots: 100,
+            blocks_produced: 95,
+            ..ValidatorMetrics::default()
+        };
+        assert_eq!(score(&metrics, &config), 95 * 9_500);
+
+        let no_commission = ValidatorMetrics {
+            commission: 0,
+            ..metrics
+        };
+        assert!(score(&no_commission, &config) > score(&metrics, &config));
+
+        let delinquent = ValidatorMetrics {
+            delinquent: true,
+            ..metrics
+        };
+        assert_eq!(score(&delinquent, &config), 0);
+
+        let high_commission = ValidatorMetrics {
+            commission: 11,
+            ..metrics
+        };
+        assert_eq!(score(&high_commission, &config), 0);
+
+        let skipping = ValidatorMetrics {
+            blocks_produced: 89,
+            ..metrics
+        };
+        assert_eq!(score(&skipping, &config), 0);
+    }
+}
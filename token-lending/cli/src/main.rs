@@ -1,17 +1,35 @@
 #![allow(clippy::arithmetic_side_effects)]
+mod output;
 use {
+    crate::output::{CliLendingMarket, CliObligation, CliReserve},
     clap::{
         crate_description, crate_name, crate_version, value_t, App, AppSettings, Arg, ArgMatches,
         SubCommand,
     },
+    miraland_account_decoder::UiAccountEncoding,
     miraland_clap_utils::{
         fee_payer::fee_payer_arg,
         input_parsers::{keypair_of, pubkey_of, value_of},
-        input_validators::{is_amount, is_keypair, is_parsable, is_pubkey, is_url},
+        input_validators::{
+            is_amount, is_amount_or_all, is_keypair, is_parsable, is_pubkey, is_url,
+        },
         keypair::signer_from_path,
     },
-    miraland_client::rpc_client::RpcClient,
-    solana_program::{native_token::lamports_to_mln, program_pack::Pack, pubkey::Pubkey},
+    miraland_cli_output::OutputFormat,
+    miraland_client::{
+        rpc_client::RpcClient,
+        rpc_config::{
+            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionAccountsConfig,
+            RpcSimulateTransactionConfig,
+        },
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        native_token::lamports_to_mln,
+        program_pack::Pack,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
     solana_sdk::{
         commitment_config::CommitmentConfig,
         message::Message,
@@ -19,17 +37,30 @@ use {
         system_instruction,
         transaction::Transaction,
     },
-    spl_token::{
+    spl_token_2022::{
+        extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
         instruction::{approve, revoke},
         state::{Account as Token, Mint},
         ui_amount_to_amount,
     },
     spl_token_lending::{
         self,
-        instruction::{init_lending_market, init_reserve},
+        instruction::{
+            borrow_obligation_liquidity, deposit_obligation_collateral, deposit_reserve_liquidity,
+            flash_loan, init_lending_market, init_obligation, init_reserve, liquidate_obligation,
+            modify_reserve_config, redeem_reserve_collateral, refresh_obligation, refresh_reserve,
+            refresh_reserve_with_token_swap_oracle, repay_obligation_liquidity,
+            self_liquidate_obligation, set_lending_market_owner, with_token_program,
+            withdraw_obligation_collateral,
+        },
         math::WAD,
-        state::{LendingMarket, Reserve, ReserveConfig, ReserveFees},
+        oracle::OracleKind,
+        state::{
+            LendingMarket, Obligation, RateCurve, RateCurvePoint, Reserve, ReserveConfig,
+            ReserveFees, ReserveOracleConfig,
+        },
     },
+    spl_token_swap::state::SwapVersion,
     std::{borrow::Borrow, process::exit, str::FromStr},
     system_instruction::create_account,
 };
@@ -39,6 +70,7 @@ struct Config {
     fee_payer: Box<dyn Signer>,
     lending_program_id: Pubkey,
     verbose: bool,
+    output_format: OutputFormat,
     dry_run: bool,
 }
 
@@ -47,6 +79,17 @@ type CommandResult = Result<(), Error>;
 
 const PYTH_PROGRAM_ID: &str = "gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s";
 
+/// Offset of the lending market in the reserve account data, after the
+/// version and the last update
+const RESERVE_LENDING_MARKET_OFFSET: usize = 10;
+
+macro_rules! unique_signers {
+    ($vec:ident) => {
+        $vec.sort_by_key(|l| l.pubkey());
+        $vec.dedup();
+    };
+}
+
 fn main() {
     miraland_logger::setup_with_default("solana=info,miraland=info");
 
@@ -101,6 +144,15 @@ fn main() {
                 .global(true)
                 .help("Show additional information"),
         )
+        .arg(
+            Arg::with_name("output_format")
+                .long("output")
+                .value_name("FORMAT")
+                .global(true)
+                .takes_value(true)
+                .possible_values(&["json", "json-compact"])
+                .help("Return information in specified output format"),
+        )
         .arg(
             Arg::with_name("dry_run")
                 .long("dry-run")
@@ -309,107 +361,1217 @@ fn main() {
                         .default_value("20")
                         .help("Amount of fee going to host account: [0, 100]"),
                 )
+                .arg(
+                    Arg::with_name("deposit_limit")
+                        .long("deposit-limit")
+                        .validator(is_parsable::<u64>)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .default_value("0")
+                        .help("Maximum total liquidity supplied to the reserve, in base units, or 0 for no limit"),
+                )
+                .arg(
+                    Arg::with_name("borrow_limit")
+                        .long("borrow-limit")
+                        .validator(is_parsable::<u64>)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .default_value("0")
+                        .help("Maximum liquidity borrowed from the reserve, in base units, or 0 for no limit"),
+                )
+                .arg(
+                    Arg::with_name("isolated")
+                        .long("isolated")
+                        .takes_value(false)
+                        .help("Prevent the reserve collateral from being combined with other collateral"),
+                )
+                .arg(
+                    Arg::with_name("rate_curve")
+                        .long("rate-curve")
+                        .validator(is_rate_curve)
+                        .value_name("UTILIZATION_BPS:RATE_BPS,...")
+                        .takes_value(true)
+                        .help("Borrow rate curve replacing the utilization and borrow rates above, \
+                            e.g. 0:0,8000:400,10000:10000"),
+                )
+                .arg(
+                    Arg::with_name("soft_liquidation_band")
+                        .long("soft-liquidation-band")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .required(true)
+                        .default_value("0")
+                        .help("Band above the liquidation threshold, as a percentage of the unhealthy \
+                            borrow value, in which obligations are soft liquidated, or 0 to disable: [0, 100]"),
+                )
+                .arg(
+                    Arg::with_name("soft_liquidation_bonus")
+                        .long("soft-liquidation-bonus")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .required(true)
+                        .default_value("0")
+                        .help("Bonus a liquidator gets inside the soft liquidation band: [0, liquidation bonus]"),
+                )
         )
-        .get_matches();
-
-    let mut wallet_manager = None;
-    let config = {
-        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
-            miraland_cli_config::Config::load(config_file).unwrap_or_default()
-        } else {
-            miraland_cli_config::Config::default()
-        };
-        let json_rpc_url = value_t!(matches, "json_rpc_url", String)
-            .unwrap_or_else(|_| cli_config.json_rpc_url.clone());
-
-        let fee_payer = signer_from_path(
-            &matches,
-            matches
-                .value_of("fee_payer")
-                .unwrap_or(&cli_config.keypair_path),
-            "fee_payer",
-            &mut wallet_manager,
+        .subcommand(
+            SubCommand::with_name("set-market-owner")
+                .about("Transfer ownership of a lending market")
+                .arg(
+                    Arg::with_name("lending_market_owner")
+                        .long("market-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Current owner of the lending market"),
+                )
+                .arg(
+                    Arg::with_name("lending_market")
+                        .long("market")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Lending market address"),
+                )
+                .arg(
+                    Arg::with_name("new_owner")
+                        .long("new-owner")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("New owner of the lending market"),
+                ),
         )
-        .unwrap_or_else(|e| {
-            eprintln!("error: {}", e);
-            exit(1);
-        });
-
-        let lending_program_id = pubkey_of(&matches, "lending_program_id").unwrap();
-        let verbose = matches.is_present("verbose");
-        let dry_run = matches.is_present("dry_run");
-
-        Config {
-            rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
-            fee_payer,
-            lending_program_id,
-            verbose,
-            dry_run,
-        }
-    };
-
-    let _ = match matches.subcommand() {
-        ("create-market", Some(arg_matches)) => {
-            let lending_market_owner = pubkey_of(arg_matches, "lending_market_owner").unwrap();
-            let quote_currency = quote_currency_of(arg_matches, "quote_currency").unwrap();
-            let oracle_program_id = pubkey_of(arg_matches, "oracle_program_id").unwrap();
-            command_create_lending_market(
-                &config,
-                lending_market_owner,
-                quote_currency,
-                oracle_program_id,
-            )
-        }
-        ("add-reserve", Some(arg_matches)) => {
-            let lending_market_owner_keypair =
-                keypair_of(arg_matches, "lending_market_owner").unwrap();
-            let source_liquidity_owner_keypair =
-                keypair_of(arg_matches, "source_liquidity_owner").unwrap();
-            let lending_market_pubkey = pubkey_of(arg_matches, "lending_market").unwrap();
-            let source_liquidity_pubkey = pubkey_of(arg_matches, "source_liquidity").unwrap();
-            let ui_amount = value_of(arg_matches, "liquidity_amount").unwrap();
-            let pyth_product_pubkey = pubkey_of(arg_matches, "pyth_product").unwrap();
-            let pyth_price_pubkey = pubkey_of(arg_matches, "pyth_price").unwrap();
-            let optimal_utilization_rate =
-                value_of(arg_matches, "optimal_utilization_rate").unwrap();
-            let loan_to_value_ratio = value_of(arg_matches, "loan_to_value_ratio").unwrap();
-            let liquidation_bonus = value_of(arg_matches, "liquidation_bonus").unwrap();
-            let liquidation_threshold = value_of(arg_matches, "liquidation_threshold").unwrap();
-            let min_borrow_rate = value_of(arg_matches, "min_borrow_rate").unwrap();
-            let optimal_borrow_rate = value_of(arg_matches, "optimal_borrow_rate").unwrap();
-            let max_borrow_rate = value_of(arg_matches, "max_borrow_rate").unwrap();
-            let borrow_fee = value_of::<f64>(arg_matches, "borrow_fee").unwrap();
-            let flash_loan_fee = value_of::<f64>(arg_matches, "flash_loan_fee").unwrap();
-            let host_fee_percentage = value_of(arg_matches, "host_fee_percentage").unwrap();
-
-            let borrow_fee_wad = (borrow_fee * WAD as f64) as u64;
-            let flash_loan_fee_wad = (flash_loan_fee * WAD as f64) as u64;
-
-            command_add_reserve(
-                &config,
-                ui_amount,
-                ReserveConfig {
-                    optimal_utilization_rate,
-                    loan_to_value_ratio,
-                    liquidation_bonus,
-                    liquidation_threshold,
-                    min_borrow_rate,
-                    optimal_borrow_rate,
-                    max_borrow_rate,
-                    fees: ReserveFees {
-                        borrow_fee_wad,
-                        flash_loan_fee_wad,
-                        host_fee_percentage,
-                    },
-                },
-                source_liquidity_pubkey,
-                source_liquidity_owner_keypair,
-                lending_market_pubkey,
-                lending_market_owner_keypair,
-                pyth_product_pubkey,
-                pyth_price_pubkey,
+        .subcommand(
+            SubCommand::with_name("update-reserve")
+                .about("Update the configuration of a reserve, keeping the values not provided")
+                .arg(
+                    Arg::with_name("lending_market_owner")
+                        .long("market-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the lending market"),
+                )
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve address"),
+                )
+                .arg(
+                    Arg::with_name("optimal_utilization_rate")
+                        .long("optimal-utilization-rate")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Optimal utilization rate: [0, 100]"),
+                )
+                .arg(
+                    Arg::with_name("loan_to_value_ratio")
+                        .long("loan-to-value-ratio")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Target ratio of the value of borrows to deposits: [0, 100)"),
+                )
+                .arg(
+                    Arg::with_name("liquidation_bonus")
+                        .long("liquidation-bonus")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Bonus a liquidator gets when repaying part of an unhealthy obligation: [0, 100]"),
+                )
+                .arg(
+                    Arg::with_name("liquidation_threshold")
+                        .long("liquidation-threshold")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Loan to value ratio at which an obligation can be liquidated: (LTV, 100]"),
+                )
+                .arg(
+                    Arg::with_name("min_borrow_rate")
+                        .long("min-borrow-rate")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Min borrow APY: min <= optimal <= max"),
+                )
+                .arg(
+                    Arg::with_name("optimal_borrow_rate")
+                        .long("optimal-borrow-rate")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Optimal (utilization) borrow APY: min <= optimal <= max"),
+                )
+                .arg(
+                    Arg::with_name("max_borrow_rate")
+                        .long("max-borrow-rate")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Max borrow APY: min <= optimal <= max"),
+                )
+                .arg(
+                    Arg::with_name("borrow_fee")
+                        .long("borrow-fee")
+                        .validator(is_parsable::<f64>)
+                        .value_name("DECIMAL_PERCENT")
+                        .takes_value(true)
+                        .help("Fee assessed on borrow, expressed as a percentage: [0, 1)"),
+                )
+                .arg(
+                    Arg::with_name("flash_loan_fee")
+                        .long("flash-loan-fee")
+                        .validator(is_parsable::<f64>)
+                        .value_name("DECIMAL_PERCENT")
+                        .takes_value(true)
+                        .help("Fee assessed for flash loans, expressed as a percentage: [0, 1)"),
+                )
+                .arg(
+                    Arg::with_name("host_fee_percentage")
+                        .long("host-fee-percentage")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Amount of fee going to host account: [0, 100]"),
+                )
+                .arg(
+                    Arg::with_name("max_staleness_slots")
+                        .long("max-staleness-slots")
+                        .validator(is_parsable::<u64>)
+                        .value_name("SLOTS")
+                        .takes_value(true)
+                        .help("Number of slots after which an oracle price is stale, or 0 for the default"),
+                )
+                .arg(
+                    Arg::with_name("max_confidence_bps")
+                        .long("max-confidence-bps")
+                        .validator(is_parsable::<u16>)
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .help("Maximum confidence interval of an oracle price, or 0 to accept any. Required for token-swap oracles"),
+                )
+                .arg(
+                    Arg::with_name("twap_window_seconds")
+                        .long("twap-window-seconds")
+                        .validator(is_parsable::<u32>)
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .help("Minimum number of seconds over which token-swap prices are averaged, or 0 for the default"),
+                )
+                .arg(
+                    Arg::with_name("deposit_limit")
+                        .long("deposit-limit")
+                        .validator(is_parsable::<u64>)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .help("Maximum total liquidity supplied to the reserve, in base units, or 0 for no limit"),
+                )
+                .arg(
+                    Arg::with_name("borrow_limit")
+                        .long("borrow-limit")
+                        .validator(is_parsable::<u64>)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .help("Maximum liquidity borrowed from the reserve, in base units, or 0 for no limit"),
+                )
+                .arg(
+                    Arg::with_name("isolated")
+                        .long("isolated")
+                        .value_name("BOOL")
+                        .takes_value(true)
+                        .possible_values(&["true", "false"])
+                        .help("Whether the reserve collateral can be combined with other collateral"),
+                )
+                .arg(
+                    Arg::with_name("rate_curve")
+                        .long("rate-curve")
+                        .validator(is_rate_curve)
+                        .value_name("UTILIZATION_BPS:RATE_BPS,...")
+                        .takes_value(true)
+                        .conflicts_with("no_rate_curve")
+                        .help("Borrow rate curve replacing the utilization and borrow rates, \
+                            e.g. 0:0,8000:400,10000:10000"),
+                )
+                .arg(
+                    Arg::with_name("no_rate_curve")
+                        .long("no-rate-curve")
+                        .takes_value(false)
+                        .help("Remove the borrow rate curve, using the utilization and borrow rates"),
+                )
+                .arg(
+                    Arg::with_name("soft_liquidation_band")
+                        .long("soft-liquidation-band")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Band above the liquidation threshold, as a percentage of the unhealthy \
+                            borrow value, in which obligations are soft liquidated, or 0 to disable: [0, 100]"),
+                )
+                .arg(
+                    Arg::with_name("soft_liquidation_bonus")
+                        .long("soft-liquidation-bonus")
+                        .validator(is_parsable::<u8>)
+                        .value_name("INTEGER_PERCENT")
+                        .takes_value(true)
+                        .help("Bonus a liquidator gets inside the soft liquidation band: [0, liquidation bonus]"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("refresh-reserve")
+                .about("Refresh the market price and accrued interest of a reserve")
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve address"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("deposit")
+                .about("Deposit liquidity into a reserve in exchange for collateral")
+                .arg(
+                    Arg::with_name("source_liquidity_owner")
+                        .long("source-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the token account to deposit liquidity from"),
+                )
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve address"),
+                )
+                .arg(
+                    Arg::with_name("source_liquidity")
+                        .long("source")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to deposit liquidity from"),
+                )
+                .arg(
+                    Arg::with_name("destination_collateral")
+                        .long("destination")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to receive the reserve collateral"),
+                )
+                .arg(
+                    Arg::with_name("liquidity_amount")
+                        .long("amount")
+                        .validator(is_amount)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of liquidity to deposit"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("redeem")
+                .about("Redeem reserve collateral in exchange for liquidity")
+                .arg(
+                    Arg::with_name("source_collateral_owner")
+                        .long("source-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the token account to redeem collateral from"),
+                )
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve address"),
+                )
+                .arg(
+                    Arg::with_name("source_collateral")
+                        .long("source")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to redeem collateral from"),
+                )
+                .arg(
+                    Arg::with_name("destination_liquidity")
+                        .long("destination")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to receive the liquidity"),
+                )
+                .arg(
+                    Arg::with_name("collateral_amount")
+                        .long("amount")
+                        .validator(is_amount)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of collateral to redeem"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("create-obligation")
+                .about("Create a new obligation in a lending market")
+                .arg(
+                    Arg::with_name("obligation_owner")
+                        .long("obligation-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the obligation"),
+                )
+                .arg(
+                    Arg::with_name("lending_market")
+                        .long("market")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Lending market address"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("refresh-obligation")
+                .about("Refresh the reserves and market values of an obligation")
+                .arg(
+                    Arg::with_name("obligation")
+                        .long("obligation")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Obligation address"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("deposit-collateral")
+                .about("Deposit reserve collateral into an obligation")
+                .arg(
+                    Arg::with_name("obligation_owner")
+                        .long("obligation-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the obligation and of the token account to deposit collateral from"),
+                )
+                .arg(
+                    Arg::with_name("obligation")
+                        .long("obligation")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Obligation address"),
+                )
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve of the collateral"),
+                )
+                .arg(
+                    Arg::with_name("source_collateral")
+                        .long("source")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to deposit collateral from"),
+                )
+                .arg(
+                    Arg::with_name("collateral_amount")
+                        .long("amount")
+                        .validator(is_amount)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of collateral to deposit"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw-collateral")
+                .about("Withdraw reserve collateral from an obligation")
+                .arg(
+                    Arg::with_name("obligation_owner")
+                        .long("obligation-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the obligation"),
+                )
+                .arg(
+                    Arg::with_name("obligation")
+                        .long("obligation")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Obligation address"),
+                )
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve of the collateral"),
+                )
+                .arg(
+                    Arg::with_name("destination_collateral")
+                        .long("destination")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to receive the collateral"),
+                )
+                .arg(
+                    Arg::with_name("collateral_amount")
+                        .long("amount")
+                        .validator(is_amount_or_all)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of collateral to withdraw, or ALL to withdraw as much as the obligation allows"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("borrow")
+                .about("Borrow liquidity from a reserve against the collateral of an obligation")
+                .arg(
+                    Arg::with_name("obligation_owner")
+                        .long("obligation-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the obligation"),
+                )
+                .arg(
+                    Arg::with_name("obligation")
+                        .long("obligation")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Obligation address"),
+                )
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve to borrow from"),
+                )
+                .arg(
+                    Arg::with_name("destination_liquidity")
+                        .long("destination")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to receive the liquidity"),
+                )
+                .arg(
+                    Arg::with_name("liquidity_amount")
+                        .long("amount")
+                        .validator(is_amount_or_all)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of liquidity to borrow, or ALL to borrow as much as the obligation allows"),
+                )
+                .arg(
+                    Arg::with_name("slippage_limit")
+                        .long("slippage-limit")
+                        .validator(is_amount)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .help("Minimum amount of liquidity to receive when borrowing ALL"),
+                )
+                .arg(
+                    Arg::with_name("host_fee_receiver")
+                        .long("host-fee-receiver")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .help("Token account to receive the host share of the borrow fee"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("repay")
+                .about("Repay liquidity borrowed by an obligation")
+                .arg(
+                    Arg::with_name("source_liquidity_owner")
+                        .long("source-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the token account to repay liquidity from"),
+                )
+                .arg(
+                    Arg::with_name("obligation")
+                        .long("obligation")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Obligation address"),
+                )
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve the liquidity was borrowed from"),
+                )
+                .arg(
+                    Arg::with_name("source_liquidity")
+                        .long("source")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to repay liquidity from"),
+                )
+                .arg(
+                    Arg::with_name("liquidity_amount")
+                        .long("amount")
+                        .validator(is_amount_or_all)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of liquidity to repay, or ALL to repay the whole borrow"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("liquidate")
+                .about("Repay liquidity borrowed by an unhealthy obligation in exchange for its collateral")
+                .arg(
+                    Arg::with_name("source_liquidity_owner")
+                        .long("source-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the token account to repay liquidity from"),
+                )
+                .arg(
+                    Arg::with_name("obligation")
+                        .long("obligation")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Obligation address"),
+                )
+                .arg(
+                    Arg::with_name("repay_reserve")
+                        .long("repay-reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve the liquidity was borrowed from"),
+                )
+                .arg(
+                    Arg::with_name("withdraw_reserve")
+                        .long("withdraw-reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve of the collateral to receive"),
+                )
+                .arg(
+                    Arg::with_name("source_liquidity")
+                        .long("source")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to repay liquidity from"),
+                )
+                .arg(
+                    Arg::with_name("destination_collateral")
+                        .long("destination")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to receive the collateral"),
+                )
+                .arg(
+                    Arg::with_name("liquidity_amount")
+                        .long("amount")
+                        .validator(is_amount_or_all)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of liquidity to repay, or ALL to repay as much as allowed"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("self-liquidate")
+                .about("Repay liquidity borrowed by your own obligation and withdraw the same value of \
+                    collateral, without a liquidation bonus")
+                .arg(
+                    Arg::with_name("obligation_owner")
+                        .long("obligation-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Owner of the obligation"),
+                )
+                .arg(
+                    Arg::with_name("source_liquidity_owner")
+                        .long("source-owner")
+                        .validator(is_keypair)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .help("Owner of the token account to repay liquidity from \
+                            [default: obligation owner]"),
+                )
+                .arg(
+                    Arg::with_name("obligation")
+                        .long("obligation")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Obligation address"),
+                )
+                .arg(
+                    Arg::with_name("repay_reserve")
+                        .long("repay-reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve the liquidity was borrowed from"),
+                )
+                .arg(
+                    Arg::with_name("withdraw_reserve")
+                        .long("withdraw-reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve of the collateral to receive"),
+                )
+                .arg(
+                    Arg::with_name("source_liquidity")
+                        .long("source")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to repay liquidity from"),
+                )
+                .arg(
+                    Arg::with_name("destination_collateral")
+                        .long("destination")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account to receive the collateral"),
+                )
+                .arg(
+                    Arg::with_name("liquidity_amount")
+                        .long("amount")
+                        .validator(is_amount_or_all)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of liquidity to repay, or ALL to repay the whole borrow"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("flash-loan")
+                .about("Borrow liquidity from a reserve to be returned within the same instruction")
+                .arg(
+                    Arg::with_name("reserve")
+                        .long("reserve")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Reserve to borrow from"),
+                )
+                .arg(
+                    Arg::with_name("destination_liquidity")
+                        .long("destination")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Token account of the receiver program to receive the liquidity"),
+                )
+                .arg(
+                    Arg::with_name("receiver_program_id")
+                        .long("receiver-program")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Program receiving the flash loan"),
+                )
+                .arg(
+                    Arg::with_name("receiver_accounts")
+                        .long("account")
+                        .validator(is_account_meta)
+                        .value_name("PUBKEY[:readonly]")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("Account passed to the receiver program, writable unless suffixed \
+                            with :readonly. May be specified multiple times, in order"),
+                )
+                .arg(
+                    Arg::with_name("host_fee_receiver")
+                        .long("host-fee-receiver")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .help("Token account to receive the host share of the flash loan fee. \
+                            Defaults to the reserve fee receiver"),
+                )
+                .arg(
+                    Arg::with_name("liquidity_amount")
+                        .long("amount")
+                        .validator(is_amount)
+                        .value_name("DECIMAL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Amount of liquidity to borrow"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-market")
+                .about("Show a lending market and its reserves")
+                .arg(
+                    Arg::with_name("lending_market")
+                        .long("market")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Lending market address"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show-obligation")
+                .about("Show the deposits, borrows and health of an obligation")
+                .arg(
+                    Arg::with_name("obligation")
+                        .long("obligation")
+                        .validator(is_pubkey)
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Obligation address"),
+                )
+                .arg(
+                    Arg::with_name("refresh")
+                        .long("refresh")
+                        .takes_value(false)
+                        .help("Show the values the obligation would have if refreshed now, \
+                            by simulating a refresh of its reserves"),
+                ),
+        )
+        .get_matches();
+
+    let mut wallet_manager = None;
+    let config = {
+        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+            miraland_cli_config::Config::load(config_file).unwrap_or_default()
+        } else {
+            miraland_cli_config::Config::default()
+        };
+        let json_rpc_url = value_t!(matches, "json_rpc_url", String)
+            .unwrap_or_else(|_| cli_config.json_rpc_url.clone());
+
+        let fee_payer = signer_from_path(
+            &matches,
+            matches
+                .value_of("fee_payer")
+                .unwrap_or(&cli_config.keypair_path),
+            "fee_payer",
+            &mut wallet_manager,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            exit(1);
+        });
+
+        let lending_program_id = pubkey_of(&matches, "lending_program_id").unwrap();
+        let verbose = matches.is_present("verbose");
+        let output_format = matches
+            .value_of("output_format")
+            .map(|value| match value {
+                "json" => OutputFormat::Json,
+                "json-compact" => OutputFormat::JsonCompact,
+                _ => unreachable!(),
+            })
+            .unwrap_or(if verbose {
+                OutputFormat::DisplayVerbose
+            } else {
+                OutputFormat::Display
+            });
+        let dry_run = matches.is_present("dry_run");
+
+        Config {
+            rpc_client: RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed()),
+            fee_payer,
+            lending_program_id,
+            verbose,
+            output_format,
+            dry_run,
+        }
+    };
+
+    let _ = match matches.subcommand() {
+        ("create-market", Some(arg_matches)) => {
+            let lending_market_owner = pubkey_of(arg_matches, "lending_market_owner").unwrap();
+            let quote_currency = quote_currency_of(arg_matches, "quote_currency").unwrap();
+            let oracle_program_id = pubkey_of(arg_matches, "oracle_program_id").unwrap();
+            command_create_lending_market(
+                &config,
+                lending_market_owner,
+                quote_currency,
+                oracle_program_id,
+            )
+        }
+        ("add-reserve", Some(arg_matches)) => {
+            let lending_market_owner_keypair =
+                keypair_of(arg_matches, "lending_market_owner").unwrap();
+            let source_liquidity_owner_keypair =
+                keypair_of(arg_matches, "source_liquidity_owner").unwrap();
+            let lending_market_pubkey = pubkey_of(arg_matches, "lending_market").unwrap();
+            let source_liquidity_pubkey = pubkey_of(arg_matches, "source_liquidity").unwrap();
+            let ui_amount = value_of(arg_matches, "liquidity_amount").unwrap();
+            let pyth_product_pubkey = pubkey_of(arg_matches, "pyth_product").unwrap();
+            let pyth_price_pubkey = pubkey_of(arg_matches, "pyth_price").unwrap();
+            let optimal_utilization_rate =
+                value_of(arg_matches, "optimal_utilization_rate").unwrap();
+            let loan_to_value_ratio = value_of(arg_matches, "loan_to_value_ratio").unwrap();
+            let liquidation_bonus = value_of(arg_matches, "liquidation_bonus").unwrap();
+            let liquidation_threshold = value_of(arg_matches, "liquidation_threshold").unwrap();
+            let min_borrow_rate = value_of(arg_matches, "min_borrow_rate").unwrap();
+            let optimal_borrow_rate = value_of(arg_matches, "optimal_borrow_rate").unwrap();
+            let max_borrow_rate = value_of(arg_matches, "max_borrow_rate").unwrap();
+            let borrow_fee = value_of::<f64>(arg_matches, "borrow_fee").unwrap();
+            let flash_loan_fee = value_of::<f64>(arg_matches, "flash_loan_fee").unwrap();
+            let host_fee_percentage = value_of(arg_matches, "host_fee_percentage").unwrap();
+            let deposit_limit = value_of(arg_matches, "deposit_limit").unwrap();
+            let borrow_limit = value_of(arg_matches, "borrow_limit").unwrap();
+            let isolated = arg_matches.is_present("isolated");
+            let rate_curve = arg_matches
+                .value_of("rate_curve")
+                .map(|value| parse_rate_curve(value).unwrap())
+                .unwrap_or_default();
+            let soft_liquidation_band = value_of(arg_matches, "soft_liquidation_band").unwrap();
+            let soft_liquidation_bonus = value_of(arg_matches, "soft_liquidation_bonus").unwrap();
+
+            let borrow_fee_wad = (borrow_fee * WAD as f64) as u64;
+            let flash_loan_fee_wad = (flash_loan_fee * WAD as f64) as u64;
+
+            command_add_reserve(
+                &config,
+                ui_amount,
+                ReserveConfig {
+                    optimal_utilization_rate,
+                    loan_to_value_ratio,
+                    liquidation_bonus,
+                    liquidation_threshold,
+                    min_borrow_rate,
+                    optimal_borrow_rate,
+                    max_borrow_rate,
+                    fees: ReserveFees {
+                        borrow_fee_wad,
+                        flash_loan_fee_wad,
+                        host_fee_percentage,
+                    },
+                    oracle: ReserveOracleConfig::default(),
+                    deposit_limit,
+                    borrow_limit,
+                    isolated,
+                    rate_curve,
+                    soft_liquidation_band,
+                    soft_liquidation_bonus,
+                },
+                source_liquidity_pubkey,
+                source_liquidity_owner_keypair,
+                lending_market_pubkey,
+                lending_market_owner_keypair,
+                pyth_product_pubkey,
+                pyth_price_pubkey,
+            )
+        }
+        ("set-market-owner", Some(arg_matches)) => {
+            let lending_market_owner_keypair =
+                keypair_of(arg_matches, "lending_market_owner").unwrap();
+            let lending_market_pubkey = pubkey_of(arg_matches, "lending_market").unwrap();
+            let new_owner = pubkey_of(arg_matches, "new_owner").unwrap();
+            command_set_lending_market_owner(
+                &config,
+                lending_market_pubkey,
+                lending_market_owner_keypair,
+                new_owner,
+            )
+        }
+        ("update-reserve", Some(arg_matches)) => {
+            let lending_market_owner_keypair =
+                keypair_of(arg_matches, "lending_market_owner").unwrap();
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            command_update_reserve(
+                &config,
+                arg_matches,
+                reserve_pubkey,
+                lending_market_owner_keypair,
+            )
+        }
+        ("refresh-reserve", Some(arg_matches)) => {
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            command_refresh_reserve(&config, reserve_pubkey)
+        }
+        ("deposit", Some(arg_matches)) => {
+            let source_liquidity_owner_keypair =
+                keypair_of(arg_matches, "source_liquidity_owner").unwrap();
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            let source_liquidity_pubkey = pubkey_of(arg_matches, "source_liquidity").unwrap();
+            let destination_collateral_pubkey =
+                pubkey_of(arg_matches, "destination_collateral").unwrap();
+            let ui_amount = value_of(arg_matches, "liquidity_amount").unwrap();
+            command_deposit_reserve_liquidity(
+                &config,
+                reserve_pubkey,
+                ui_amount,
+                source_liquidity_pubkey,
+                source_liquidity_owner_keypair,
+                destination_collateral_pubkey,
+            )
+        }
+        ("redeem", Some(arg_matches)) => {
+            let source_collateral_owner_keypair =
+                keypair_of(arg_matches, "source_collateral_owner").unwrap();
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            let source_collateral_pubkey = pubkey_of(arg_matches, "source_collateral").unwrap();
+            let destination_liquidity_pubkey =
+                pubkey_of(arg_matches, "destination_liquidity").unwrap();
+            let ui_amount = value_of(arg_matches, "collateral_amount").unwrap();
+            command_redeem_reserve_collateral(
+                &config,
+                reserve_pubkey,
+                ui_amount,
+                source_collateral_pubkey,
+                source_collateral_owner_keypair,
+                destination_liquidity_pubkey,
+            )
+        }
+        ("create-obligation", Some(arg_matches)) => {
+            let obligation_owner_keypair = keypair_of(arg_matches, "obligation_owner").unwrap();
+            let lending_market_pubkey = pubkey_of(arg_matches, "lending_market").unwrap();
+            command_create_obligation(&config, lending_market_pubkey, obligation_owner_keypair)
+        }
+        ("refresh-obligation", Some(arg_matches)) => {
+            let obligation_pubkey = pubkey_of(arg_matches, "obligation").unwrap();
+            command_refresh_obligation(&config, obligation_pubkey)
+        }
+        ("deposit-collateral", Some(arg_matches)) => {
+            let obligation_owner_keypair = keypair_of(arg_matches, "obligation_owner").unwrap();
+            let obligation_pubkey = pubkey_of(arg_matches, "obligation").unwrap();
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            let source_collateral_pubkey = pubkey_of(arg_matches, "source_collateral").unwrap();
+            let ui_amount = value_of(arg_matches, "collateral_amount").unwrap();
+            command_deposit_obligation_collateral(
+                &config,
+                obligation_pubkey,
+                obligation_owner_keypair,
+                reserve_pubkey,
+                ui_amount,
+                source_collateral_pubkey,
+            )
+        }
+        ("withdraw-collateral", Some(arg_matches)) => {
+            let obligation_owner_keypair = keypair_of(arg_matches, "obligation_owner").unwrap();
+            let obligation_pubkey = pubkey_of(arg_matches, "obligation").unwrap();
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            let destination_collateral_pubkey =
+                pubkey_of(arg_matches, "destination_collateral").unwrap();
+            let ui_amount = ui_amount_or_all_of(arg_matches, "collateral_amount");
+            command_withdraw_obligation_collateral(
+                &config,
+                obligation_pubkey,
+                obligation_owner_keypair,
+                reserve_pubkey,
+                ui_amount,
+                destination_collateral_pubkey,
+            )
+        }
+        ("borrow", Some(arg_matches)) => {
+            let obligation_owner_keypair = keypair_of(arg_matches, "obligation_owner").unwrap();
+            let obligation_pubkey = pubkey_of(arg_matches, "obligation").unwrap();
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            let destination_liquidity_pubkey =
+                pubkey_of(arg_matches, "destination_liquidity").unwrap();
+            let ui_amount = ui_amount_or_all_of(arg_matches, "liquidity_amount");
+            let ui_slippage_limit = value_of(arg_matches, "slippage_limit");
+            let host_fee_receiver_pubkey = pubkey_of(arg_matches, "host_fee_receiver");
+            command_borrow_obligation_liquidity(
+                &config,
+                obligation_pubkey,
+                obligation_owner_keypair,
+                reserve_pubkey,
+                ui_amount,
+                ui_slippage_limit,
+                destination_liquidity_pubkey,
+                host_fee_receiver_pubkey,
+            )
+        }
+        ("repay", Some(arg_matches)) => {
+            let source_liquidity_owner_keypair =
+                keypair_of(arg_matches, "source_liquidity_owner").unwrap();
+            let obligation_pubkey = pubkey_of(arg_matches, "obligation").unwrap();
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            let source_liquidity_pubkey = pubkey_of(arg_matches, "source_liquidity").unwrap();
+            let ui_amount = ui_amount_or_all_of(arg_matches, "liquidity_amount");
+            command_repay_obligation_liquidity(
+                &config,
+                obligation_pubkey,
+                reserve_pubkey,
+                ui_amount,
+                source_liquidity_pubkey,
+                source_liquidity_owner_keypair,
+            )
+        }
+        ("liquidate", Some(arg_matches)) => {
+            let source_liquidity_owner_keypair =
+                keypair_of(arg_matches, "source_liquidity_owner").unwrap();
+            let obligation_pubkey = pubkey_of(arg_matches, "obligation").unwrap();
+            let repay_reserve_pubkey = pubkey_of(arg_matches, "repay_reserve").unwrap();
+            let withdraw_reserve_pubkey = pubkey_of(arg_matches, "withdraw_reserve").unwrap();
+            let source_liquidity_pubkey = pubkey_of(arg_matches, "source_liquidity").unwrap();
+            let destination_collateral_pubkey =
+                pubkey_of(arg_matches, "destination_collateral").unwrap();
+            let ui_amount = ui_amount_or_all_of(arg_matches, "liquidity_amount");
+            command_liquidate_obligation(
+                &config,
+                obligation_pubkey,
+                None,
+                repay_reserve_pubkey,
+                withdraw_reserve_pubkey,
+                ui_amount,
+                source_liquidity_pubkey,
+                source_liquidity_owner_keypair,
+                destination_collateral_pubkey,
+            )
+        }
+        ("self-liquidate", Some(arg_matches)) => {
+            let obligation_owner_keypair = keypair_of(arg_matches, "obligation_owner").unwrap();
+            let source_liquidity_owner_keypair = keypair_of(arg_matches, "source_liquidity_owner")
+                .unwrap_or_else(|| keypair_of(arg_matches, "obligation_owner").unwrap());
+            let obligation_pubkey = pubkey_of(arg_matches, "obligation").unwrap();
+            let repay_reserve_pubkey = pubkey_of(arg_matches, "repay_reserve").unwrap();
+            let withdraw_reserve_pubkey = pubkey_of(arg_matches, "withdraw_reserve").unwrap();
+            let source_liquidity_pubkey = pubkey_of(arg_matches, "source_liquidity").unwrap();
+            let destination_collateral_pubkey =
+                pubkey_of(arg_matches, "destination_collateral").unwrap();
+            let ui_amount = ui_amount_or_all_of(arg_matches, "liquidity_amount");
+            command_liquidate_obligation(
+                &config,
+                obligation_pubkey,
+                Some(obligation_owner_keypair),
+                repay_reserve_pubkey,
+                withdraw_reserve_pubkey,
+                ui_amount,
+                source_liquidity_pubkey,
+                source_liquidity_owner_keypair,
+                destination_collateral_pubkey,
+            )
+        }
+        ("flash-loan", Some(arg_matches)) => {
+            let reserve_pubkey = pubkey_of(arg_matches, "reserve").unwrap();
+            let destination_liquidity_pubkey =
+                pubkey_of(arg_matches, "destination_liquidity").unwrap();
+            let receiver_program_id = pubkey_of(arg_matches, "receiver_program_id").unwrap();
+            let receiver_accounts = arg_matches
+                .values_of("receiver_accounts")
+                .map(|values| {
+                    values
+                        .map(|value| parse_account_meta(value).unwrap())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let host_fee_receiver_pubkey = pubkey_of(arg_matches, "host_fee_receiver");
+            let ui_amount = value_of(arg_matches, "liquidity_amount").unwrap();
+            command_flash_loan(
+                &config,
+                reserve_pubkey,
+                ui_amount,
+                destination_liquidity_pubkey,
+                host_fee_receiver_pubkey,
+                receiver_program_id,
+                receiver_accounts,
             )
         }
+        ("show-market", Some(arg_matches)) => {
+            let lending_market_pubkey = pubkey_of(arg_matches, "lending_market").unwrap();
+            command_show_lending_market(&config, lending_market_pubkey)
+        }
+        ("show-obligation", Some(arg_matches)) => {
+            let obligation_pubkey = pubkey_of(arg_matches, "obligation").unwrap();
+            let refresh = arg_matches.is_present("refresh");
+            command_show_obligation(&config, obligation_pubkey, refresh)
+        }
         _ => unreachable!(),
     }
     .map_err(|err| {
@@ -486,12 +1648,21 @@ fn command_add_reserve(
     pyth_price_pubkey: Pubkey,
 ) -> CommandResult {
     let source_liquidity_account = config.rpc_client.get_account(&source_liquidity_pubkey)?;
-    let source_liquidity = Token::unpack_from_slice(source_liquidity_account.data.borrow())?;
+    let source_liquidity =
+        StateWithExtensions::<Token>::unpack(source_liquidity_account.data.borrow())?.base;
 
+    // the reserve mints and token accounts are owned by the token program of
+    // the liquidity mint, either the token program or the Token-2022 program
     let source_liquidity_mint_account = config.rpc_client.get_account(&source_liquidity.mint)?;
+    let token_program_id = source_liquidity_mint_account.owner;
     let source_liquidity_mint =
-        Mint::unpack_from_slice(source_liquidity_mint_account.data.borrow())?;
-    let liquidity_amount = ui_amount_to_amount(ui_amount, source_liquidity_mint.decimals);
+        StateWithExtensions::<Mint>::unpack(source_liquidity_mint_account.data.borrow())?;
+    let liquidity_amount = ui_amount_to_amount(ui_amount, source_liquidity_mint.base.decimals);
+    let liquidity_account_len = ExtensionType::try_calculate_account_len::<Token>(
+        &ExtensionType::get_required_init_account_extensions(
+            &source_liquidity_mint.get_extension_types()?,
+        ),
+    )?;
 
     let reserve_keypair = Keypair::new();
     let collateral_mint_keypair = Keypair::new();
@@ -540,8 +1711,11 @@ fn command_add_reserve(
         .get_minimum_balance_for_rent_exemption(Token::LEN)?;
     let collateral_supply_balance = token_account_balance;
     let user_collateral_balance = token_account_balance;
-    let liquidity_supply_balance = token_account_balance;
-    let liquidity_fee_receiver_balance = token_account_balance;
+    let liquidity_account_balance = config
+        .rpc_client
+        .get_minimum_balance_for_rent_exemption(liquidity_account_len)?;
+    let liquidity_supply_balance = liquidity_account_balance;
+    let liquidity_fee_receiver_balance = liquidity_account_balance;
 
     let total_balance = reserve_balance
         + collateral_mint_balance
@@ -565,21 +1739,21 @@ fn command_add_reserve(
                 &collateral_mint_keypair.pubkey(),
                 collateral_mint_balance,
                 Mint::LEN as u64,
-                &spl_token::id(),
+                &token_program_id,
             ),
             create_account(
                 &config.fee_payer.pubkey(),
                 &collateral_supply_keypair.pubkey(),
                 collateral_supply_balance,
                 Token::LEN as u64,
-                &spl_token::id(),
+                &token_program_id,
             ),
             create_account(
                 &config.fee_payer.pubkey(),
                 &user_collateral_keypair.pubkey(),
                 user_collateral_balance,
                 Token::LEN as u64,
-                &spl_token::id(),
+                &token_program_id,
             ),
         ],
         Some(&config.fee_payer.pubkey()),
@@ -592,15 +1766,15 @@ fn command_add_reserve(
                 &config.fee_payer.pubkey(),
                 &liquidity_supply_keypair.pubkey(),
                 liquidity_supply_balance,
-                Token::LEN as u64,
-                &spl_token::id(),
+                liquidity_account_len as u64,
+                &token_program_id,
             ),
             create_account(
                 &config.fee_payer.pubkey(),
                 &liquidity_fee_receiver_keypair.pubkey(),
                 liquidity_fee_receiver_balance,
-                Token::LEN as u64,
-                &spl_token::id(),
+                liquidity_account_len as u64,
+                &token_program_id,
             ),
         ],
         Some(&config.fee_payer.pubkey()),
@@ -610,7 +1784,7 @@ fn command_add_reserve(
     let message_3 = Message::new_with_blockhash(
         &[
             approve(
-                &spl_token::id(),
+                &token_program_id,
                 &source_liquidity_pubkey,
                 &user_transfer_authority_keypair.pubkey(),
                 &source_liquidity_owner_keypair.pubkey(),
@@ -618,26 +1792,30 @@ fn command_add_reserve(
                 liquidity_amount,
             )
             .unwrap(),
-            init_reserve(
-                config.lending_program_id,
-                liquidity_amount,
-                reserve_config,
-                source_liquidity_pubkey,
-                user_collateral_keypair.pubkey(),
-                reserve_keypair.pubkey(),
-                source_liquidity.mint,
-                liquidity_supply_keypair.pubkey(),
-                liquidity_fee_receiver_keypair.pubkey(),
-                collateral_mint_keypair.pubkey(),
-                collateral_supply_keypair.pubkey(),
-                pyth_product_pubkey,
-                pyth_price_pubkey,
-                lending_market_pubkey,
-                lending_market_owner_keypair.pubkey(),
-                user_transfer_authority_keypair.pubkey(),
+            with_token_program(
+                init_reserve(
+                    config.lending_program_id,
+                    liquidity_amount,
+                    reserve_config,
+                    source_liquidity_pubkey,
+                    user_collateral_keypair.pubkey(),
+                    reserve_keypair.pubkey(),
+                    source_liquidity.mint,
+                    liquidity_supply_keypair.pubkey(),
+                    liquidity_fee_receiver_keypair.pubkey(),
+                    collateral_mint_keypair.pubkey(),
+                    collateral_supply_keypair.pubkey(),
+                    pyth_product_pubkey,
+                    pyth_price_pubkey,
+                    lending_market_pubkey,
+                    lending_market_owner_keypair.pubkey(),
+                    user_transfer_authority_keypair.pubkey(),
+                ),
+                token_program_id,
+                None,
             ),
             revoke(
-                &spl_token::id(),
+                &token_program_id,
                 &source_liquidity_pubkey,
                 &source_liquidity_owner_keypair.pubkey(),
                 &[],
@@ -691,6 +1869,609 @@ fn command_add_reserve(
     Ok(())
 }
 
+fn command_set_lending_market_owner(
+    config: &Config,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner_keypair: Keypair,
+    new_owner: Pubkey,
+) -> CommandResult {
+    println!(
+        "Setting owner of lending market {} to {}",
+        lending_market_pubkey, new_owner
+    );
+    send_instructions(
+        config,
+        &[set_lending_market_owner(
+            config.lending_program_id,
+            lending_market_pubkey,
+            lending_market_owner_keypair.pubkey(),
+            new_owner,
+        )],
+        &[&lending_market_owner_keypair],
+    )
+}
+
+fn command_update_reserve(
+    config: &Config,
+    arg_matches: &ArgMatches<'_>,
+    reserve_pubkey: Pubkey,
+    lending_market_owner_keypair: Keypair,
+) -> CommandResult {
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    let mut reserve_config = reserve.config;
+
+    macro_rules! update {
+        ($field:expr, $name:literal) => {
+            if let Some(value) = value_of(arg_matches, $name) {
+                $field = value;
+            }
+        };
+    }
+    update!(
+        reserve_config.optimal_utilization_rate,
+        "optimal_utilization_rate"
+    );
+    update!(reserve_config.loan_to_value_ratio, "loan_to_value_ratio");
+    update!(reserve_config.liquidation_bonus, "liquidation_bonus");
+    update!(
+        reserve_config.liquidation_threshold,
+        "liquidation_threshold"
+    );
+    update!(reserve_config.min_borrow_rate, "min_borrow_rate");
+    update!(reserve_config.optimal_borrow_rate, "optimal_borrow_rate");
+    update!(reserve_config.max_borrow_rate, "max_borrow_rate");
+    update!(
+        reserve_config.fees.host_fee_percentage,
+        "host_fee_percentage"
+    );
+    update!(
+        reserve_config.oracle.max_staleness_slots,
+        "max_staleness_slots"
+    );
+    update!(
+        reserve_config.oracle.max_confidence_bps,
+        "max_confidence_bps"
+    );
+    update!(
+        reserve_config.oracle.twap_window_seconds,
+        "twap_window_seconds"
+    );
+    update!(reserve_config.deposit_limit, "deposit_limit");
+    update!(reserve_config.borrow_limit, "borrow_limit");
+    update!(reserve_config.isolated, "isolated");
+    update!(
+        reserve_config.soft_liquidation_band,
+        "soft_liquidation_band"
+    );
+    update!(
+        reserve_config.soft_liquidation_bonus,
+        "soft_liquidation_bonus"
+    );
+    if let Some(borrow_fee) = value_of::<f64>(arg_matches, "borrow_fee") {
+        reserve_config.fees.borrow_fee_wad = (borrow_fee * WAD as f64) as u64;
+    }
+    if let Some(flash_loan_fee) = value_of::<f64>(arg_matches, "flash_loan_fee") {
+        reserve_config.fees.flash_loan_fee_wad = (flash_loan_fee * WAD as f64) as u64;
+    }
+    if let Some(rate_curve) = arg_matches.value_of("rate_curve") {
+        reserve_config.rate_curve = parse_rate_curve(rate_curve)?;
+    } else if arg_matches.is_present("no_rate_curve") {
+        reserve_config.rate_curve = RateCurve::default();
+    }
+    reserve_config
+        .validate()
+        .map_err(|e| format!("Invalid reserve config: {}", e))?;
+
+    println!("Updating reserve {}", reserve_pubkey);
+    send_instructions(
+        config,
+        &[modify_reserve_config(
+            config.lending_program_id,
+            reserve_config,
+            reserve_pubkey,
+            reserve.lending_market,
+            lending_market_owner_keypair.pubkey(),
+        )],
+        &[&lending_market_owner_keypair],
+    )
+}
+
+fn command_refresh_reserve(config: &Config, reserve_pubkey: Pubkey) -> CommandResult {
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    println!("Refreshing reserve {}", reserve_pubkey);
+    send_instructions(
+        config,
+        &[refresh_reserve_instruction(
+            config,
+            reserve_pubkey,
+            &reserve,
+        )?],
+        &[],
+    )
+}
+
+fn command_deposit_reserve_liquidity(
+    config: &Config,
+    reserve_pubkey: Pubkey,
+    ui_amount: f64,
+    source_liquidity_pubkey: Pubkey,
+    source_liquidity_owner_keypair: Keypair,
+    destination_collateral_pubkey: Pubkey,
+) -> CommandResult {
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    let lending_market = get_lending_market(config, &reserve.lending_market)?;
+    let liquidity_amount = ui_amount_to_amount(ui_amount, reserve.liquidity.mint_decimals);
+
+    println!("Depositing {} into reserve {}", ui_amount, reserve_pubkey);
+    send_instructions(
+        config,
+        &[
+            refresh_reserve_instruction(config, reserve_pubkey, &reserve)?,
+            with_reserve_token_program(
+                deposit_reserve_liquidity(
+                    config.lending_program_id,
+                    liquidity_amount,
+                    source_liquidity_pubkey,
+                    destination_collateral_pubkey,
+                    reserve_pubkey,
+                    reserve.liquidity.supply_pubkey,
+                    reserve.collateral.mint_pubkey,
+                    reserve.lending_market,
+                    source_liquidity_owner_keypair.pubkey(),
+                ),
+                &reserve,
+                &lending_market,
+                true,
+            ),
+        ],
+        &[&source_liquidity_owner_keypair],
+    )
+}
+
+fn command_redeem_reserve_collateral(
+    config: &Config,
+    reserve_pubkey: Pubkey,
+    ui_amount: f64,
+    source_collateral_pubkey: Pubkey,
+    source_collateral_owner_keypair: Keypair,
+    destination_liquidity_pubkey: Pubkey,
+) -> CommandResult {
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    let lending_market = get_lending_market(config, &reserve.lending_market)?;
+    // the collateral mint has the decimals of the liquidity mint
+    let collateral_amount = ui_amount_to_amount(ui_amount, reserve.liquidity.mint_decimals);
+
+    println!(
+        "Redeeming {} collateral from reserve {}",
+        ui_amount, reserve_pubkey
+    );
+    send_instructions(
+        config,
+        &[
+            refresh_reserve_instruction(config, reserve_pubkey, &reserve)?,
+            with_reserve_token_program(
+                redeem_reserve_collateral(
+                    config.lending_program_id,
+                    collateral_amount,
+                    source_collateral_pubkey,
+                    destination_liquidity_pubkey,
+                    reserve_pubkey,
+                    reserve.collateral.mint_pubkey,
+                    reserve.liquidity.supply_pubkey,
+                    reserve.lending_market,
+                    source_collateral_owner_keypair.pubkey(),
+                ),
+                &reserve,
+                &lending_market,
+                true,
+            ),
+        ],
+        &[&source_collateral_owner_keypair],
+    )
+}
+
+fn command_create_obligation(
+    config: &Config,
+    lending_market_pubkey: Pubkey,
+    obligation_owner_keypair: Keypair,
+) -> CommandResult {
+    let obligation_keypair = Keypair::new();
+    println!("Creating obligation {}", obligation_keypair.pubkey());
+
+    let obligation_balance = config
+        .rpc_client
+        .get_minimum_balance_for_rent_exemption(Obligation::LEN)?;
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+    let message = Message::new_with_blockhash(
+        &[
+            create_account(
+                &config.fee_payer.pubkey(),
+                &obligation_keypair.pubkey(),
+                obligation_balance,
+                Obligation::LEN as u64,
+                &config.lending_program_id,
+            ),
+            init_obligation(
+                config.lending_program_id,
+                obligation_keypair.pubkey(),
+                lending_market_pubkey,
+                obligation_owner_keypair.pubkey(),
+            ),
+        ],
+        Some(&config.fee_payer.pubkey()),
+        &recent_blockhash,
+    );
+
+    check_fee_payer_balance(
+        config,
+        obligation_balance + config.rpc_client.get_fee_for_message(&message)?,
+    )?;
+    let mut signers = vec![
+        config.fee_payer.as_ref(),
+        &obligation_keypair,
+        &obligation_owner_keypair,
+    ];
+    unique_signers!(signers);
+    let transaction = Transaction::new(&signers, message, recent_blockhash);
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
+fn command_refresh_obligation(config: &Config, obligation_pubkey: Pubkey) -> CommandResult {
+    let obligation = get_obligation(config, &obligation_pubkey)?;
+    println!("Refreshing obligation {}", obligation_pubkey);
+    send_instructions(
+        config,
+        &refresh_obligation_instructions(config, obligation_pubkey, &obligation, &[])?,
+        &[],
+    )
+}
+
+fn command_deposit_obligation_collateral(
+    config: &Config,
+    obligation_pubkey: Pubkey,
+    obligation_owner_keypair: Keypair,
+    reserve_pubkey: Pubkey,
+    ui_amount: f64,
+    source_collateral_pubkey: Pubkey,
+) -> CommandResult {
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    let lending_market = get_lending_market(config, &reserve.lending_market)?;
+    let collateral_amount = ui_amount_to_amount(ui_amount, reserve.liquidity.mint_decimals);
+
+    println!(
+        "Depositing {} collateral of reserve {} into obligation {}",
+        ui_amount, reserve_pubkey, obligation_pubkey
+    );
+    send_instructions(
+        config,
+        &[
+            refresh_reserve_instruction(config, reserve_pubkey, &reserve)?,
+            with_reserve_token_program(
+                deposit_obligation_collateral(
+                    config.lending_program_id,
+                    collateral_amount,
+                    source_collateral_pubkey,
+                    reserve.collateral.supply_pubkey,
+                    reserve_pubkey,
+                    obligation_pubkey,
+                    reserve.lending_market,
+                    obligation_owner_keypair.pubkey(),
+                    obligation_owner_keypair.pubkey(),
+                ),
+                &reserve,
+                &lending_market,
+                false,
+            ),
+        ],
+        &[&obligation_owner_keypair],
+    )
+}
+
+fn command_withdraw_obligation_collateral(
+    config: &Config,
+    obligation_pubkey: Pubkey,
+    obligation_owner_keypair: Keypair,
+    reserve_pubkey: Pubkey,
+    ui_amount: Option<f64>,
+    destination_collateral_pubkey: Pubkey,
+) -> CommandResult {
+    let obligation = get_obligation(config, &obligation_pubkey)?;
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    let lending_market = get_lending_market(config, &reserve.lending_market)?;
+    let collateral_amount = amount_or_all(ui_amount, reserve.liquidity.mint_decimals);
+
+    println!(
+        "Withdrawing {} collateral of reserve {} from obligation {}",
+        ui_amount_or_all_to_string(ui_amount),
+        reserve_pubkey,
+        obligation_pubkey
+    );
+    let mut instructions =
+        refresh_obligation_instructions(config, obligation_pubkey, &obligation, &[])?;
+    instructions.push(with_reserve_token_program(
+        withdraw_obligation_collateral(
+            config.lending_program_id,
+            collateral_amount,
+            reserve.collateral.supply_pubkey,
+            destination_collateral_pubkey,
+            reserve_pubkey,
+            obligation_pubkey,
+            reserve.lending_market,
+            obligation_owner_keypair.pubkey(),
+        ),
+        &reserve,
+        &lending_market,
+        false,
+    ));
+    send_instructions(config, &instructions, &[&obligation_owner_keypair])
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_borrow_obligation_liquidity(
+    config: &Config,
+    obligation_pubkey: Pubkey,
+    obligation_owner_keypair: Keypair,
+    reserve_pubkey: Pubkey,
+    ui_amount: Option<f64>,
+    ui_slippage_limit: Option<f64>,
+    destination_liquidity_pubkey: Pubkey,
+    host_fee_receiver_pubkey: Option<Pubkey>,
+) -> CommandResult {
+    let obligation = get_obligation(config, &obligation_pubkey)?;
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    let lending_market = get_lending_market(config, &reserve.lending_market)?;
+    let liquidity_amount = amount_or_all(ui_amount, reserve.liquidity.mint_decimals);
+    let slippage_limit = ui_slippage_limit.map(|ui_slippage_limit| {
+        ui_amount_to_amount(ui_slippage_limit, reserve.liquidity.mint_decimals)
+    });
+
+    println!(
+        "Borrowing {} from reserve {} against obligation {}",
+        ui_amount_or_all_to_string(ui_amount),
+        reserve_pubkey,
+        obligation_pubkey
+    );
+    let mut instructions =
+        refresh_obligation_instructions(config, obligation_pubkey, &obligation, &[reserve_pubkey])?;
+    instructions.push(with_reserve_token_program(
+        borrow_obligation_liquidity(
+            config.lending_program_id,
+            liquidity_amount,
+            slippage_limit,
+            reserve.liquidity.supply_pubkey,
+            destination_liquidity_pubkey,
+            reserve_pubkey,
+            reserve.liquidity.fee_receiver,
+            obligation_pubkey,
+            reserve.lending_market,
+            obligation_owner_keypair.pubkey(),
+            host_fee_receiver_pubkey,
+        ),
+        &reserve,
+        &lending_market,
+        true,
+    ));
+    send_instructions(config, &instructions, &[&obligation_owner_keypair])
+}
+
+fn command_repay_obligation_liquidity(
+    config: &Config,
+    obligation_pubkey: Pubkey,
+    reserve_pubkey: Pubkey,
+    ui_amount: Option<f64>,
+    source_liquidity_pubkey: Pubkey,
+    source_liquidity_owner_keypair: Keypair,
+) -> CommandResult {
+    let obligation = get_obligation(config, &obligation_pubkey)?;
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    let lending_market = get_lending_market(config, &reserve.lending_market)?;
+    let liquidity_amount = amount_or_all(ui_amount, reserve.liquidity.mint_decimals);
+
+    println!(
+        "Repaying {} to reserve {} for obligation {}",
+        ui_amount_or_all_to_string(ui_amount),
+        reserve_pubkey,
+        obligation_pubkey
+    );
+    let mut instructions =
+        refresh_obligation_instructions(config, obligation_pubkey, &obligation, &[])?;
+    instructions.push(with_reserve_token_program(
+        repay_obligation_liquidity(
+            config.lending_program_id,
+            liquidity_amount,
+            source_liquidity_pubkey,
+            reserve.liquidity.supply_pubkey,
+            reserve_pubkey,
+            obligation_pubkey,
+            reserve.lending_market,
+            source_liquidity_owner_keypair.pubkey(),
+        ),
+        &reserve,
+        &lending_market,
+        true,
+    ));
+    send_instructions(config, &instructions, &[&source_liquidity_owner_keypair])
+}
+
+#[allow(clippy::too_many_arguments)]
+fn command_liquidate_obligation(
+    config: &Config,
+    obligation_pubkey: Pubkey,
+    obligation_owner_keypair: Option<Keypair>,
+    repay_reserve_pubkey: Pubkey,
+    withdraw_reserve_pubkey: Pubkey,
+    ui_amount: Option<f64>,
+    source_liquidity_pubkey: Pubkey,
+    source_liquidity_owner_keypair: Keypair,
+    destination_collateral_pubkey: Pubkey,
+) -> CommandResult {
+    let obligation = get_obligation(config, &obligation_pubkey)?;
+    let repay_reserve = get_reserve(config, &repay_reserve_pubkey)?;
+    let withdraw_reserve = get_reserve(config, &withdraw_reserve_pubkey)?;
+    let lending_market = get_lending_market(config, &obligation.lending_market)?;
+    let liquidity_amount = amount_or_all(ui_amount, repay_reserve.liquidity.mint_decimals);
+
+    println!(
+        "{} obligation {}, repaying {} to reserve {}",
+        if obligation_owner_keypair.is_some() {
+            "Self-liquidating"
+        } else {
+            "Liquidating"
+        },
+        obligation_pubkey,
+        ui_amount_or_all_to_string(ui_amount),
+        repay_reserve_pubkey
+    );
+    let mut instructions =
+        refresh_obligation_instructions(config, obligation_pubkey, &obligation, &[])?;
+    let instruction = if let Some(obligation_owner_keypair) = &obligation_owner_keypair {
+        self_liquidate_obligation(
+            config.lending_program_id,
+            liquidity_amount,
+            source_liquidity_pubkey,
+            destination_collateral_pubkey,
+            repay_reserve_pubkey,
+            repay_reserve.liquidity.supply_pubkey,
+            withdraw_reserve_pubkey,
+            withdraw_reserve.collateral.supply_pubkey,
+            obligation_pubkey,
+            obligation.lending_market,
+            source_liquidity_owner_keypair.pubkey(),
+            obligation_owner_keypair.pubkey(),
+        )
+    } else {
+        liquidate_obligation(
+            config.lending_program_id,
+            liquidity_amount,
+            source_liquidity_pubkey,
+            destination_collateral_pubkey,
+            repay_reserve_pubkey,
+            repay_reserve.liquidity.supply_pubkey,
+            withdraw_reserve_pubkey,
+            withdraw_reserve.collateral.supply_pubkey,
+            obligation_pubkey,
+            obligation.lending_market,
+            source_liquidity_owner_keypair.pubkey(),
+        )
+    };
+    let mut instruction =
+        with_reserve_token_program(instruction, &repay_reserve, &lending_market, true);
+    let withdraw_token_program_id = withdraw_reserve.mint_token_program_id(&lending_market);
+    if withdraw_token_program_id != repay_reserve.mint_token_program_id(&lending_market) {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(*withdraw_token_program_id, false));
+    }
+    instructions.push(instruction);
+    let mut signers: Vec<&dyn Signer> = vec![&source_liquidity_owner_keypair];
+    if let Some(obligation_owner_keypair) = &obligation_owner_keypair {
+        signers.push(obligation_owner_keypair);
+    }
+    send_instructions(config, &instructions, &signers)
+}
+
+fn command_flash_loan(
+    config: &Config,
+    reserve_pubkey: Pubkey,
+    ui_amount: f64,
+    destination_liquidity_pubkey: Pubkey,
+    host_fee_receiver_pubkey: Option<Pubkey>,
+    receiver_program_id: Pubkey,
+    receiver_accounts: Vec<AccountMeta>,
+) -> CommandResult {
+    let reserve = get_reserve(config, &reserve_pubkey)?;
+    let lending_market = get_lending_market(config, &reserve.lending_market)?;
+    let liquidity_amount = ui_amount_to_amount(ui_amount, reserve.liquidity.mint_decimals);
+
+    println!(
+        "Flash borrowing {} from reserve {} into program {}",
+        ui_amount, reserve_pubkey, receiver_program_id
+    );
+    send_instructions(
+        config,
+        &[with_reserve_token_program(
+            flash_loan(
+                config.lending_program_id,
+                liquidity_amount,
+                reserve.liquidity.supply_pubkey,
+                destination_liquidity_pubkey,
+                reserve_pubkey,
+                reserve.liquidity.fee_receiver,
+                host_fee_receiver_pubkey.unwrap_or(reserve.liquidity.fee_receiver),
+                reserve.lending_market,
+                receiver_program_id,
+                receiver_accounts,
+            ),
+            &reserve,
+            &lending_market,
+            true,
+        )],
+        &[],
+    )
+}
+
+fn command_show_lending_market(config: &Config, lending_market_pubkey: Pubkey) -> CommandResult {
+    let lending_market = get_lending_market(config, &lending_market_pubkey)?;
+    let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+        &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+        &config.lending_program_id,
+    );
+
+    let accounts = config.rpc_client.get_program_accounts_with_config(
+        &config.lending_program_id,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(Reserve::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    RESERVE_LENDING_MARKET_OFFSET,
+                    lending_market_pubkey.as_ref(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+    let mut reserves = accounts
+        .into_iter()
+        .map(|(address, account)| -> Result<CliReserve, Error> {
+            let reserve = Reserve::unpack(&account.data)?;
+            Ok(CliReserve::from((address, reserve, &lending_market)))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    reserves.sort_by(|a, b| a.address.cmp(&b.address));
+
+    let cli_lending_market = CliLendingMarket::from((
+        lending_market_pubkey,
+        lending_market_authority_pubkey,
+        lending_market,
+        reserves,
+    ));
+    println!(
+        "{}",
+        config.output_format.formatted_string(&cli_lending_market)
+    );
+    Ok(())
+}
+
+fn command_show_obligation(
+    config: &Config,
+    obligation_pubkey: Pubkey,
+    refresh: bool,
+) -> CommandResult {
+    let mut obligation = get_obligation(config, &obligation_pubkey)?;
+    if refresh {
+        obligation = simulate_refresh_obligation(config, obligation_pubkey, &obligation)?;
+    }
+    let cli_obligation = CliObligation::from((obligation_pubkey, obligation));
+    println!("{}", config.output_format.formatted_string(&cli_obligation));
+    Ok(())
+}
+
 // HELPERS
 
 fn check_fee_payer_balance(config: &Config, required_balance: u64) -> Result<(), Error> {
@@ -724,6 +2505,206 @@ fn send_transaction(
     Ok(())
 }
 
+fn send_instructions(
+    config: &Config,
+    instructions: &[Instruction],
+    signers: &[&(dyn Signer + 'static)],
+) -> CommandResult {
+    let mut signers = [&[config.fee_payer.as_ref()][..], signers].concat();
+    unique_signers!(signers);
+
+    let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+    let message = Message::new_with_blockhash(
+        instructions,
+        Some(&config.fee_payer.pubkey()),
+        &recent_blockhash,
+    );
+    check_fee_payer_balance(config, config.rpc_client.get_fee_for_message(&message)?)?;
+    let transaction = Transaction::new(&signers, message, recent_blockhash);
+    send_transaction(config, transaction)?;
+    Ok(())
+}
+
+fn get_lending_market(config: &Config, pubkey: &Pubkey) -> Result<LendingMarket, Error> {
+    let account = config.rpc_client.get_account(pubkey)?;
+    Ok(LendingMarket::unpack(&account.data)?)
+}
+
+fn get_reserve(config: &Config, pubkey: &Pubkey) -> Result<Reserve, Error> {
+    let account = config.rpc_client.get_account(pubkey)?;
+    Ok(Reserve::unpack(&account.data)?)
+}
+
+fn get_obligation(config: &Config, pubkey: &Pubkey) -> Result<Obligation, Error> {
+    let account = config.rpc_client.get_account(pubkey)?;
+    Ok(Obligation::unpack(&account.data)?)
+}
+
+/// Creates a `RefreshReserve` instruction with the oracle accounts of the
+/// reserve
+fn refresh_reserve_instruction(
+    config: &Config,
+    reserve_pubkey: Pubkey,
+    reserve: &Reserve,
+) -> Result<Instruction, Error> {
+    match reserve.liquidity.oracle_kind {
+        OracleKind::Pyth | OracleKind::Switchboard => Ok(refresh_reserve(
+            config.lending_program_id,
+            reserve_pubkey,
+            reserve.liquidity.oracle_pubkey,
+        )),
+        OracleKind::TokenSwapTwap => {
+            let swap_account = config
+                .rpc_client
+                .get_account(&reserve.liquidity.oracle_pubkey)?;
+            let swap = SwapVersion::unpack(&swap_account.data)?;
+            Ok(refresh_reserve_with_token_swap_oracle(
+                config.lending_program_id,
+                reserve_pubkey,
+                reserve.liquidity.oracle_pubkey,
+                *swap.token_a_account(),
+                *swap.token_b_account(),
+            ))
+        }
+    }
+}
+
+/// Creates the instructions refreshing the reserves of an obligation, and
+/// `additional_reserves`, followed by the obligation itself
+fn refresh_obligation_instructions(
+    config: &Config,
+    obligation_pubkey: Pubkey,
+    obligation: &Obligation,
+    additional_reserves: &[Pubkey],
+) -> Result<Vec<Instruction>, Error> {
+    let obligation_reserves: Vec<Pubkey> = obligation
+        .deposits
+        .iter()
+        .map(|collateral| collateral.deposit_reserve)
+        .chain(
+            obligation
+                .borrows
+                .iter()
+                .map(|liquidity| liquidity.borrow_reserve),
+        )
+        .collect();
+
+    let mut refreshed_reserves: Vec<Pubkey> = vec![];
+    let mut instructions = vec![];
+    for reserve_pubkey in obligation_reserves.iter().chain(additional_reserves) {
+        if refreshed_reserves.contains(reserve_pubkey) {
+            continue;
+        }
+        let reserve = get_reserve(config, reserve_pubkey)?;
+        instructions.push(refresh_reserve_instruction(
+            config,
+            *reserve_pubkey,
+            &reserve,
+        )?);
+        refreshed_reserves.push(*reserve_pubkey);
+    }
+    instructions.push(refresh_obligation(
+        config.lending_program_id,
+        obligation_pubkey,
+        obligation_reserves,
+    ));
+    Ok(instructions)
+}
+
+/// Reads an obligation as it would be after being refreshed, without
+/// sending a transaction
+fn simulate_refresh_obligation(
+    config: &Config,
+    obligation_pubkey: Pubkey,
+    obligation: &Obligation,
+) -> Result<Obligation, Error> {
+    let instructions = refresh_obligation_instructions(config, obligation_pubkey, obligation, &[])?;
+    let message = Message::new(&instructions, Some(&config.fee_payer.pubkey()));
+    let transaction = Transaction::new_unsigned(message);
+    let result = config
+        .rpc_client
+        .simulate_transaction_with_config(
+            &transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                accounts: Some(RpcSimulateTransactionAccountsConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    addresses: vec![obligation_pubkey.to_string()],
+                }),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+    if let Some(err) = result.err {
+        return Err(format!(
+            "Failed to refresh obligation {}: {}",
+            obligation_pubkey, err
+        )
+        .into());
+    }
+    let data = result
+        .accounts
+        .and_then(|accounts| accounts.into_iter().next().flatten())
+        .and_then(|account| account.data.decode())
+        .ok_or_else(|| format!("Failed to read refreshed obligation {}", obligation_pubkey))?;
+    Ok(Obligation::unpack(&data)?)
+}
+
+/// Adapts an instruction to the token program of a reserve, providing the
+/// liquidity mint of Token-2022 reserves to instructions transferring
+/// liquidity
+fn with_reserve_token_program(
+    instruction: Instruction,
+    reserve: &Reserve,
+    lending_market: &LendingMarket,
+    transfers_liquidity: bool,
+) -> Instruction {
+    let token_program_id = *reserve.mint_token_program_id(lending_market);
+    let liquidity_mint_pubkey = if transfers_liquidity && token_program_id != spl_token::id() {
+        Some(reserve.liquidity.mint_pubkey)
+    } else {
+        None
+    };
+    with_token_program(instruction, token_program_id, liquidity_mint_pubkey)
+}
+
+/// Returns `None` for `ALL`
+fn ui_amount_or_all_of(matches: &ArgMatches<'_>, name: &str) -> Option<f64> {
+    match matches.value_of(name) {
+        Some("ALL") => None,
+        _ => value_of(matches, name),
+    }
+}
+
+fn amount_or_all(ui_amount: Option<f64>, decimals: u8) -> u64 {
+    ui_amount.map_or(u64::MAX, |ui_amount| {
+        ui_amount_to_amount(ui_amount, decimals)
+    })
+}
+
+fn ui_amount_or_all_to_string(ui_amount: Option<f64>) -> String {
+    ui_amount.map_or("ALL".to_string(), |ui_amount| ui_amount.to_string())
+}
+
+fn parse_account_meta(value: &str) -> Result<AccountMeta, String> {
+    let (pubkey, is_writable) = match value.strip_suffix(":readonly") {
+        Some(pubkey) => (pubkey, false),
+        None => (value, true),
+    };
+    let pubkey =
+        Pubkey::from_str(pubkey).map_err(|e| format!("Invalid account {}: {}", value, e))?;
+    Ok(if is_writable {
+        AccountMeta::new(pubkey, false)
+    } else {
+        AccountMeta::new_readonly(pubkey, false)
+    })
+}
+
+fn is_account_meta(value: String) -> Result<(), String> {
+    parse_account_meta(&value).map(|_| ())
+}
+
 fn quote_currency_of(matches: &ArgMatches<'_>, name: &str) -> Option<[u8; 32]> {
     if let Some(value) = matches.value_of(name) {
         if value == "USD" {
@@ -739,3 +2720,33 @@ fn quote_currency_of(matches: &ArgMatches<'_>, name: &str) -> Option<[u8; 32]> {
         None
     }
 }
+
+fn parse_rate_curve(value: &str) -> Result<RateCurve, String> {
+    let points = value
+        .split(',')
+        .map(|point| {
+            let (utilization_bps, borrow_rate_bps) = point
+                .split_once(':')
+                .ok_or_else(|| format!("Invalid rate curve point: {}", point))?;
+            Ok(RateCurvePoint {
+                utilization_bps: utilization_bps
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("Invalid utilization rate {}: {}", utilization_bps, e))?,
+                borrow_rate_bps: borrow_rate_bps
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("Invalid borrow rate {}: {}", borrow_rate_bps, e))?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let rate_curve = RateCurve::new(&points).map_err(|e| format!("Invalid rate curve: {}", e))?;
+    rate_curve
+        .validate()
+        .map_err(|e| format!("Invalid rate curve: {}", e))?;
+    Ok(rate_curve)
+}
+
+fn is_rate_curve(value: String) -> Result<(), String> {
+    parse_rate_curve(&value).map(|_| ())
+}
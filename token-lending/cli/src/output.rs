@@ -0,0 +1,547 @@
+use {
+    miraland_cli_output::{QuietDisplay, VerboseDisplay},
+    serde::{Deserialize, Serialize},
+    solana_program::pubkey::Pubkey,
+    spl_token_lending::{
+        math::{Decimal, TryDiv},
+        oracle::OracleKind,
+        state::{
+            LendingMarket, Obligation, ObligationCollateral, ObligationLiquidity, RateCurvePoint,
+            Reserve, ReserveConfig,
+        },
+    },
+    std::fmt::{Display, Formatter, Result, Write},
+};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliLendingMarket {
+    pub address: String,
+    pub authority: String,
+    pub owner: String,
+    pub quote_currency: String,
+    pub token_program_id: String,
+    pub oracle_program_id: String,
+    pub reserves: Vec<CliReserve>,
+}
+
+impl Display for CliLendingMarket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "Lending Market: {}", &self.address)?;
+        writeln!(f, "Owner: {}", &self.owner)?;
+        writeln!(f, "Quote Currency: {}", &self.quote_currency)?;
+        writeln!(f)?;
+        for reserve in &self.reserves {
+            writeln!(
+                f,
+                "Reserve: {}\tMint: {}\tAvailable: {}\tBorrowed: {}\tUtilization: {}\tBorrow Rate: {}",
+                reserve.address,
+                reserve.liquidity_mint,
+                reserve.available_amount,
+                reserve.borrowed_amount,
+                reserve.utilization_rate,
+                reserve.borrow_rate,
+            )?;
+        }
+        writeln!(f, "Total number of reserves: {}", &self.reserves.len())?;
+        Ok(())
+    }
+}
+
+impl QuietDisplay for CliLendingMarket {}
+impl VerboseDisplay for CliLendingMarket {
+    fn write_str(&self, w: &mut dyn Write) -> Result {
+        writeln!(w, "Lending Market Info")?;
+        writeln!(w, "===================")?;
+        writeln!(w, "Lending Market: {}", &self.address)?;
+        writeln!(w, "Authority: {}", &self.authority)?;
+        writeln!(w, "Owner: {}", &self.owner)?;
+        writeln!(w, "Quote Currency: {}", &self.quote_currency)?;
+        writeln!(w, "Token Program: {}", &self.token_program_id)?;
+        writeln!(w, "Oracle Program: {}", &self.oracle_program_id)?;
+        for reserve in &self.reserves {
+            writeln!(w)?;
+            VerboseDisplay::write_str(reserve, w)?;
+        }
+        writeln!(w)?;
+        writeln!(w, "Total number of reserves: {}", &self.reserves.len())?;
+        Ok(())
+    }
+}
+
+impl From<(Pubkey, Pubkey, LendingMarket, Vec<CliReserve>)> for CliLendingMarket {
+    fn from(m: (Pubkey, Pubkey, LendingMarket, Vec<CliReserve>)) -> Self {
+        let (address, authority, lending_market, reserves) = m;
+        Self {
+            address: address.to_string(),
+            authority: authority.to_string(),
+            owner: lending_market.owner.to_string(),
+            quote_currency: quote_currency_to_string(&lending_market.quote_currency),
+            token_program_id: lending_market.token_program_id.to_string(),
+            oracle_program_id: lending_market.oracle_program_id.to_string(),
+            reserves,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliReserve {
+    pub address: String,
+    pub lending_market: String,
+    pub token_program_id: String,
+    pub liquidity_mint: String,
+    pub liquidity_mint_decimals: u8,
+    pub liquidity_supply: String,
+    pub liquidity_fee_receiver: String,
+    pub oracle_kind: String,
+    pub oracle: String,
+    pub market_price: String,
+    pub available_amount: u64,
+    pub borrowed_amount: String,
+    pub cumulative_borrow_rate: String,
+    pub collateral_mint: String,
+    pub collateral_supply: String,
+    pub collateral_mint_total_supply: u64,
+    pub utilization_rate: String,
+    pub borrow_rate: String,
+    pub last_update_slot: u64,
+    pub stale: bool,
+    pub config: CliReserveConfig,
+}
+
+impl Display for CliReserve {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "Reserve: {}", &self.address)?;
+        writeln!(f, "Liquidity Mint: {}", &self.liquidity_mint)?;
+        writeln!(f, "Market Price: {}", &self.market_price)?;
+        writeln!(f, "Available Amount: {}", &self.available_amount)?;
+        writeln!(f, "Borrowed Amount: {}", &self.borrowed_amount)?;
+        writeln!(f, "Utilization Rate: {}", &self.utilization_rate)?;
+        writeln!(f, "Borrow Rate: {}", &self.borrow_rate)?;
+        Ok(())
+    }
+}
+
+impl QuietDisplay for CliReserve {}
+impl VerboseDisplay for CliReserve {
+    fn write_str(&self, w: &mut dyn Write) -> Result {
+        writeln!(w, "Reserve: {}", &self.address)?;
+        writeln!(w, "Lending Market: {}", &self.lending_market)?;
+        writeln!(w, "Token Program: {}", &self.token_program_id)?;
+        writeln!(
+            w,
+            "Liquidity Mint: {} ({} decimals)",
+            &self.liquidity_mint, &self.liquidity_mint_decimals
+        )?;
+        writeln!(w, "Liquidity Supply: {}", &self.liquidity_supply)?;
+        writeln!(
+            w,
+            "Liquidity Fee Receiver: {}",
+            &self.liquidity_fee_receiver
+        )?;
+        writeln!(w, "Collateral Mint: {}", &self.collateral_mint)?;
+        writeln!(w, "Collateral Supply: {}", &self.collateral_supply)?;
+        writeln!(w, "Oracle: {} ({})", &self.oracle, &self.oracle_kind)?;
+        writeln!(w, "Market Price: {}", &self.market_price)?;
+        writeln!(w, "Available Amount: {}", &self.available_amount)?;
+        writeln!(w, "Borrowed Amount: {}", &self.borrowed_amount)?;
+        writeln!(
+            w,
+            "Cumulative Borrow Rate: {}",
+            &self.cumulative_borrow_rate
+        )?;
+        writeln!(
+            w,
+            "Collateral Total Supply: {}",
+            &self.collateral_mint_total_supply
+        )?;
+        writeln!(w, "Utilization Rate: {}", &self.utilization_rate)?;
+        writeln!(w, "Borrow Rate: {}", &self.borrow_rate)?;
+        writeln!(
+            w,
+            "Last Update Slot: {}{}",
+            &self.last_update_slot,
+            if self.stale { " (stale)" } else { "" }
+        )?;
+        writeln!(w, "{}", &self.config)?;
+        Ok(())
+    }
+}
+
+impl From<(Pubkey, Reserve, &LendingMarket)> for CliReserve {
+    fn from(r: (Pubkey, Reserve, &LendingMarket)) -> Self {
+        let (address, reserve, lending_market) = r;
+        let utilization_rate = reserve
+            .liquidity
+            .utilization_rate()
+            .map(|rate| rate.to_string())
+            .unwrap_or_default();
+        let borrow_rate = reserve
+            .current_borrow_rate()
+            .map(|rate| rate.to_string())
+            .unwrap_or_default();
+        Self {
+            address: address.to_string(),
+            lending_market: reserve.lending_market.to_string(),
+            token_program_id: reserve.mint_token_program_id(lending_market).to_string(),
+            liquidity_mint: reserve.liquidity.mint_pubkey.to_string(),
+            liquidity_mint_decimals: reserve.liquidity.mint_decimals,
+            liquidity_supply: reserve.liquidity.supply_pubkey.to_string(),
+            liquidity_fee_receiver: reserve.liquidity.fee_receiver.to_string(),
+            oracle_kind: oracle_kind_to_string(reserve.liquidity.oracle_kind),
+            oracle: reserve.liquidity.oracle_pubkey.to_string(),
+            market_price: reserve.liquidity.market_price.to_string(),
+            available_amount: reserve.liquidity.available_amount,
+            borrowed_amount: reserve.liquidity.borrowed_amount_wads.to_string(),
+            cumulative_borrow_rate: reserve.liquidity.cumulative_borrow_rate_wads.to_string(),
+            collateral_mint: reserve.collateral.mint_pubkey.to_string(),
+            collateral_supply: reserve.collateral.supply_pubkey.to_string(),
+            collateral_mint_total_supply: reserve.collateral.mint_total_supply,
+            utilization_rate,
+            borrow_rate,
+            last_update_slot: reserve.last_update.slot,
+            stale: reserve.last_update.stale,
+            config: CliReserveConfig::from(reserve.config),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliReserveConfig {
+    pub optimal_utilization_rate: u8,
+    pub loan_to_value_ratio: u8,
+    pub liquidation_bonus: u8,
+    pub liquidation_threshold: u8,
+    pub soft_liquidation_band: u8,
+    pub soft_liquidation_bonus: u8,
+    pub min_borrow_rate: u8,
+    pub optimal_borrow_rate: u8,
+    pub max_borrow_rate: u8,
+    pub rate_curve: Vec<CliRateCurvePoint>,
+    pub borrow_fee_wad: u64,
+    pub flash_loan_fee_wad: u64,
+    pub host_fee_percentage: u8,
+    pub max_staleness_slots: u64,
+    pub max_confidence_bps: u16,
+    pub twap_window_seconds: u32,
+    pub deposit_limit: u64,
+    pub borrow_limit: u64,
+    pub isolated: bool,
+}
+
+impl Display for CliReserveConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(
+            f,
+            "Optimal Utilization Rate: {}%",
+            &self.optimal_utilization_rate
+        )?;
+        writeln!(f, "Loan To Value Ratio: {}%", &self.loan_to_value_ratio)?;
+        writeln!(f, "Liquidation Bonus: {}%", &self.liquidation_bonus)?;
+        writeln!(f, "Liquidation Threshold: {}%", &self.liquidation_threshold)?;
+        if self.soft_liquidation_band == 0 {
+            writeln!(f, "Soft Liquidation: disabled")?;
+        } else {
+            writeln!(
+                f,
+                "Soft Liquidation: {}% band, {}% bonus",
+                &self.soft_liquidation_band, &self.soft_liquidation_bonus
+            )?;
+        }
+        if self.rate_curve.is_empty() {
+            writeln!(
+                f,
+                "Borrow Rate: {}% min, {}% optimal, {}% max",
+                &self.min_borrow_rate, &self.optimal_borrow_rate, &self.max_borrow_rate
+            )?;
+        } else {
+            let points: Vec<String> = self
+                .rate_curve
+                .iter()
+                .map(|point| format!("{}:{}", point.utilization_bps, point.borrow_rate_bps))
+                .collect();
+            writeln!(f, "Borrow Rate Curve (bps): {}", points.join(","))?;
+        }
+        writeln!(f, "Borrow Fee (WAD): {}", &self.borrow_fee_wad)?;
+        writeln!(f, "Flash Loan Fee (WAD): {}", &self.flash_loan_fee_wad)?;
+        writeln!(f, "Host Fee: {}%", &self.host_fee_percentage)?;
+        writeln!(
+            f,
+            "Oracle Limits: {} slots staleness, {} bps confidence, {} s TWAP window",
+            &self.max_staleness_slots, &self.max_confidence_bps, &self.twap_window_seconds
+        )?;
+        writeln!(f, "Deposit Limit: {}", limit_to_string(self.deposit_limit))?;
+        writeln!(f, "Borrow Limit: {}", limit_to_string(self.borrow_limit))?;
+        write!(f, "Isolated: {}", &self.isolated)?;
+        Ok(())
+    }
+}
+
+impl From<ReserveConfig> for CliReserveConfig {
+    fn from(c: ReserveConfig) -> Self {
+        Self {
+            optimal_utilization_rate: c.optimal_utilization_rate,
+            loan_to_value_ratio: c.loan_to_value_ratio,
+            liquidation_bonus: c.liquidation_bonus,
+            liquidation_threshold: c.liquidation_threshold,
+            soft_liquidation_band: c.soft_liquidation_band,
+            soft_liquidation_bonus: c.soft_liquidation_bonus,
+            min_borrow_rate: c.min_borrow_rate,
+            optimal_borrow_rate: c.optimal_borrow_rate,
+            max_borrow_rate: c.max_borrow_rate,
+            rate_curve: c
+                .rate_curve
+                .points()
+                .iter()
+                .map(CliRateCurvePoint::from)
+                .collect(),
+            borrow_fee_wad: c.fees.borrow_fee_wad,
+            flash_loan_fee_wad: c.fees.flash_loan_fee_wad,
+            host_fee_percentage: c.fees.host_fee_percentage,
+            max_staleness_slots: c.oracle.max_staleness_slots(),
+            max_confidence_bps: c.oracle.max_confidence_bps,
+            twap_window_seconds: c.oracle.twap_window_seconds(),
+            deposit_limit: c.deposit_limit,
+            borrow_limit: c.borrow_limit,
+            isolated: c.isolated,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliRateCurvePoint {
+    pub utilization_bps: u16,
+    pub borrow_rate_bps: u32,
+}
+
+impl From<&RateCurvePoint> for CliRateCurvePoint {
+    fn from(p: &RateCurvePoint) -> Self {
+        Self {
+            utilization_bps: p.utilization_bps,
+            borrow_rate_bps: p.borrow_rate_bps,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliObligation {
+    pub address: String,
+    pub lending_market: String,
+    pub owner: String,
+    pub deposits: Vec<CliObligationCollateral>,
+    pub borrows: Vec<CliObligationLiquidity>,
+    pub deposited_value: String,
+    pub borrowed_value: String,
+    pub allowed_borrow_value: String,
+    pub unhealthy_borrow_value: String,
+    pub loan_to_value: Option<String>,
+    pub health_factor: Option<String>,
+    pub liquidatable: bool,
+    pub last_update_slot: u64,
+    pub stale: bool,
+}
+
+impl Display for CliObligation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "Obligation: {}", &self.address)?;
+        writeln!(f, "Owner: {}", &self.owner)?;
+        for deposit in &self.deposits {
+            writeln!(
+                f,
+                "Deposit: {}\tCollateral: {}\tValue: {}",
+                deposit.deposit_reserve, deposit.deposited_amount, deposit.market_value
+            )?;
+        }
+        for borrow in &self.borrows {
+            writeln!(
+                f,
+                "Borrow: {}\tLiquidity: {}\tValue: {}",
+                borrow.borrow_reserve, borrow.borrowed_amount, borrow.market_value
+            )?;
+        }
+        writeln!(f, "Deposited Value: {}", &self.deposited_value)?;
+        writeln!(f, "Borrowed Value: {}", &self.borrowed_value)?;
+        writeln!(
+            f,
+            "Health Factor: {}",
+            self.health_factor.as_deref().unwrap_or("None")
+        )?;
+        writeln!(f, "Liquidatable: {}", &self.liquidatable)?;
+        Ok(())
+    }
+}
+
+impl QuietDisplay for CliObligation {}
+impl VerboseDisplay for CliObligation {
+    fn write_str(&self, w: &mut dyn Write) -> Result {
+        writeln!(w, "Obligation Info")?;
+        writeln!(w, "===============")?;
+        writeln!(w, "Obligation: {}", &self.address)?;
+        writeln!(w, "Lending Market: {}", &self.lending_market)?;
+        writeln!(w, "Owner: {}", &self.owner)?;
+        writeln!(
+            w,
+            "Last Update Slot: {}{}",
+            &self.last_update_slot,
+            if self.stale { " (stale)" } else { "" }
+        )?;
+        writeln!(w)?;
+        writeln!(w, "Deposits")?;
+        writeln!(w, "--------")?;
+        for deposit in &self.deposits {
+            writeln!(w, "Reserve: {}", deposit.deposit_reserve)?;
+            writeln!(w, "Collateral Amount: {}", deposit.deposited_amount)?;
+            writeln!(w, "Market Value: {}", deposit.market_value)?;
+        }
+        writeln!(w)?;
+        writeln!(w, "Borrows")?;
+        writeln!(w, "-------")?;
+        for borrow in &self.borrows {
+            writeln!(w, "Reserve: {}", borrow.borrow_reserve)?;
+            writeln!(w, "Liquidity Amount: {}", borrow.borrowed_amount)?;
+            writeln!(
+                w,
+                "Cumulative Borrow Rate: {}",
+                borrow.cumulative_borrow_rate
+            )?;
+            writeln!(w, "Market Value: {}", borrow.market_value)?;
+        }
+        writeln!(w)?;
+        writeln!(w, "Deposited Value: {}", &self.deposited_value)?;
+        writeln!(w, "Borrowed Value: {}", &self.borrowed_value)?;
+        writeln!(w, "Allowed Borrow Value: {}", &self.allowed_borrow_value)?;
+        writeln!(
+            w,
+            "Unhealthy Borrow Value: {}",
+            &self.unhealthy_borrow_value
+        )?;
+        writeln!(
+            w,
+            "Loan To Value: {}",
+            self.loan_to_value.as_deref().unwrap_or("None")
+        )?;
+        writeln!(
+            w,
+            "Health Factor: {}",
+            self.health_factor.as_deref().unwrap_or("None")
+        )?;
+        writeln!(w, "Liquidatable: {}", &self.liquidatable)?;
+        Ok(())
+    }
+}
+
+impl From<(Pubkey, Obligation)> for CliObligation {
+    fn from(o: (Pubkey, Obligation)) -> Self {
+        let (address, obligation) = o;
+        // the health factor is the ratio of the unhealthy borrow value to the
+        // borrow value, the obligation can be liquidated once it reaches 1
+        let health_factor = if obligation.borrowed_value == Decimal::zero() {
+            None
+        } else {
+            obligation
+                .unhealthy_borrow_value
+                .try_div(obligation.borrowed_value)
+                .ok()
+                .map(|health_factor| health_factor.to_string())
+        };
+        let liquidatable = obligation.borrowed_value > Decimal::zero()
+            && obligation.borrowed_value >= obligation.unhealthy_borrow_value;
+        Self {
+            address: address.to_string(),
+            lending_market: obligation.lending_market.to_string(),
+            owner: obligation.owner.to_string(),
+            deposits: obligation
+                .deposits
+                .iter()
+                .map(CliObligationCollateral::from)
+                .collect(),
+            borrows: obligation
+                .borrows
+                .iter()
+                .map(CliObligationLiquidity::from)
+                .collect(),
+            deposited_value: obligation.deposited_value.to_string(),
+            borrowed_value: obligation.borrowed_value.to_string(),
+            allowed_borrow_value: obligation.allowed_borrow_value.to_string(),
+            unhealthy_borrow_value: obligation.unhealthy_borrow_value.to_string(),
+            loan_to_value: obligation
+                .loan_to_value()
+                .ok()
+                .map(|loan_to_value| loan_to_value.to_string()),
+            health_factor,
+            liquidatable,
+            last_update_slot: obligation.last_update.slot,
+            stale: obligation.last_update.stale,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliObligationCollateral {
+    pub deposit_reserve: String,
+    pub deposited_amount: u64,
+    pub market_value: String,
+}
+
+impl From<&ObligationCollateral> for CliObligationCollateral {
+    fn from(c: &ObligationCollateral) -> Self {
+        Self {
+            deposit_reserve: c.deposit_reserve.to_string(),
+            deposited_amount: c.deposited_amount,
+            market_value: c.market_value.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliObligationLiquidity {
+    pub borrow_reserve: String,
+    pub borrowed_amount: String,
+    pub cumulative_borrow_rate: String,
+    pub market_value: String,
+}
+
+impl From<&ObligationLiquidity> for CliObligationLiquidity {
+    fn from(l: &ObligationLiquidity) -> Self {
+        Self {
+            borrow_reserve: l.borrow_reserve.to_string(),
+            borrowed_amount: l.borrowed_amount_wads.to_string(),
+            cumulative_borrow_rate: l.cumulative_borrow_rate_wads.to_string(),
+            market_value: l.market_value.to_string(),
+        }
+    }
+}
+
+fn quote_currency_to_string(quote_currency: &[u8; 32]) -> String {
+    let len = quote_currency
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(quote_currency.len());
+    match std::str::from_utf8(&quote_currency[..len]) {
+        Ok(currency) if quote_currency[len..].iter().all(|byte| *byte == 0) => currency.to_string(),
+        _ => Pubkey::new_from_array(*quote_currency).to_string(),
+    }
+}
+
+fn oracle_kind_to_string(oracle_kind: OracleKind) -> String {
+    match oracle_kind {
+        OracleKind::Pyth => "Pyth",
+        OracleKind::Switchboard => "Switchboard",
+        OracleKind::TokenSwapTwap => "Token swap TWAP",
+    }
+    .to_string()
+}
+
+fn limit_to_string(limit: u64) -> String {
+    if limit == 0 {
+        "None".to_string()
+    } else {
+        limit.to_string()
+    }
+}
@@ -7,6 +7,9 @@ use {
         program_error::ProgramError,
         pubkey::Pubkey,
     },
+    spl_flash_loan_receiver_interface::instruction::{
+        FlashLoanAmount, FlashLoanReceiverInstruction, ACCOUNTS_PER_LOAN,
+    },
     std::convert::TryInto,
 };
 
@@ -16,6 +19,12 @@ pub fn process_instruction(
     input: &[u8],
 ) -> ProgramResult {
     msg!("Flash Loan Receiver invoked.");
+    if let Ok(FlashLoanReceiverInstruction::ReceiveFlashLoan { loans }) =
+        FlashLoanReceiverInstruction::unpack(input)
+    {
+        return process_receive_flash_loan(accounts, &loans);
+    }
+
     let account_info_iter = &mut accounts.iter();
     let destination_liq_info = next_account_info(account_info_iter)?;
     let source_liq_info = next_account_info(account_info_iter)?;
@@ -55,6 +64,43 @@ pub fn process_instruction(
     Ok(())
 }
 
+/// Returns each loan of a multi-reserve flash loan, signed by the user
+/// transfer authority given as the first additional account
+fn process_receive_flash_loan(
+    accounts: &[AccountInfo],
+    loans: &[FlashLoanAmount],
+) -> ProgramResult {
+    let loan_accounts_len = loans.len() * ACCOUNTS_PER_LOAN;
+    if accounts.len() <= loan_accounts_len {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (loan_accounts, additional_accounts) = accounts.split_at(loan_accounts_len);
+    let user_transfer_authority_info = &additional_accounts[0];
+
+    for (loan, accounts) in loans.iter().zip(loan_accounts.chunks(ACCOUNTS_PER_LOAN)) {
+        let destination_liq_info = &accounts[0];
+        let source_liq_info = &accounts[1];
+        let spl_token_program_info = &accounts[3];
+        invoke(
+            &spl_token::instruction::transfer(
+                spl_token_program_info.key,
+                destination_liq_info.key,
+                source_liq_info.key,
+                user_transfer_authority_info.key,
+                &[],
+                loan.repay_amount()?,
+            )?,
+            &[
+                source_liq_info.clone(),
+                destination_liq_info.clone(),
+                user_transfer_authority_info.clone(),
+                spl_token_program_info.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
 fn unpack_amount(input: &[u8]) -> Result<u64, ProgramError> {
     let amount = input
         .get(..8)
@@ -0,0 +1,244 @@
+//! Instruction types
+
+use {
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    spl_discriminator::{ArrayDiscriminator, SplDiscriminate},
+    std::convert::TryInto,
+};
+
+/// Number of accounts passed to the receiver for each loan
+pub const ACCOUNTS_PER_LOAN: usize = 4;
+
+/// Liquidity lent by a reserve
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlashLoanAmount {
+    /// Amount of liquidity lent
+    pub amount: u64,
+    /// Fee owed to the reserve on top of the amount lent
+    pub fee: u64,
+}
+
+impl FlashLoanAmount {
+    /// Packed length of a loan in instruction data
+    pub const LEN: usize = 16;
+
+    /// Amount that must be returned to the reserve liquidity supply
+    pub fn repay_amount(&self) -> Result<u64, ProgramError> {
+        self.amount
+            .checked_add(self.fee)
+            .ok_or(ProgramError::ArithmeticOverflow)
+    }
+}
+
+/// Accounts of a loan passed to the receiver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlashLoanAccounts {
+    /// Token account holding the lent liquidity
+    pub destination_liquidity: Pubkey,
+    /// Reserve liquidity supply, to return the loan to
+    pub reserve_liquidity_supply: Pubkey,
+    /// Reserve liquidity mint
+    pub liquidity_mint: Pubkey,
+    /// Token program of the liquidity mint
+    pub token_program_id: Pubkey,
+}
+
+/// Instructions supported by the flash loan receiver interface.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlashLoanReceiverInstruction {
+    /// Receives the liquidity lent by one or more reserves, runs the receiver
+    /// logic and returns each loan with its fee to the reserve liquidity
+    /// supply before returning.
+    ///
+    /// Accounts expected by this instruction, for each loan in order:
+    ///
+    ///   0. `[writable]` Destination liquidity token account, holding the lent
+    ///      liquidity
+    ///   1. `[writable]` Reserve liquidity supply
+    ///   2. `[]` Reserve liquidity mint
+    ///   3. `[]` Token program id
+    ///
+    /// followed by:
+    ///
+    ///   .. `[any]` Additional accounts provided to the lending program's
+    ///   flash loan instruction
+    ReceiveFlashLoan {
+        /// Amount lent and fee owed for each reserve
+        loans: Vec<FlashLoanAmount>,
+    },
+}
+
+/// Instruction type only used to define the discriminator of
+/// `ReceiveFlashLoan`
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("solarti-flash-loan-receiver-interface:receive-flash-loan")]
+pub struct ReceiveFlashLoanInstruction;
+
+impl FlashLoanReceiverInstruction {
+    /// Unpacks a byte buffer into a
+    /// [FlashLoanReceiverInstruction](enum.FlashLoanReceiverInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < ArrayDiscriminator::LENGTH {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (discriminator, rest) = input.split_at(ArrayDiscriminator::LENGTH);
+        Ok(match discriminator {
+            ReceiveFlashLoanInstruction::SPL_DISCRIMINATOR_SLICE => {
+                let (&count, rest) = rest
+                    .split_first()
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                if rest.len() != count as usize * FlashLoanAmount::LEN {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                let loans = rest
+                    .chunks_exact(FlashLoanAmount::LEN)
+                    .map(|chunk| {
+                        let (amount, fee) = chunk.split_at(8);
+                        FlashLoanAmount {
+                            amount: u64::from_le_bytes(amount.try_into().unwrap()),
+                            fee: u64::from_le_bytes(fee.try_into().unwrap()),
+                        }
+                    })
+                    .collect();
+                Self::ReceiveFlashLoan { loans }
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+
+    /// Packs a
+    /// [FlashLoanReceiverInstruction](enum.FlashLoanReceiverInstruction.html)
+    /// into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        match self {
+            Self::ReceiveFlashLoan { loans } => {
+                buf.extend_from_slice(ReceiveFlashLoanInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.push(loans.len() as u8);
+                for loan in loans {
+                    buf.extend_from_slice(&loan.amount.to_le_bytes());
+                    buf.extend_from_slice(&loan.fee.to_le_bytes());
+                }
+            }
+        };
+        buf
+    }
+}
+
+/// Creates a `ReceiveFlashLoan` instruction, with the accounts of each loan
+/// followed by `additional_accounts`
+pub fn receive_flash_loan(
+    program_id: &Pubkey,
+    loan_accounts: &[FlashLoanAccounts],
+    loans: Vec<FlashLoanAmount>,
+    additional_accounts: &[AccountMeta],
+) -> Instruction {
+    let mut accounts =
+        Vec::with_capacity(loan_accounts.len() * ACCOUNTS_PER_LOAN + additional_accounts.len());
+    for loan in loan_accounts {
+        accounts.push(AccountMeta::new(loan.destination_liquidity, false));
+        accounts.push(AccountMeta::new(loan.reserve_liquidity_supply, false));
+        accounts.push(AccountMeta::new_readonly(loan.liquidity_mint, false));
+        accounts.push(AccountMeta::new_readonly(loan.token_program_id, false));
+    }
+    accounts.extend_from_slice(additional_accounts);
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FlashLoanReceiverInstruction::ReceiveFlashLoan { loans }.pack(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::NAMESPACE, solana_program::hash};
+
+    #[test]
+    fn validate_packing() {
+        let loans = vec![
+            FlashLoanAmount {
+                amount: 1_000_000,
+                fee: 3_000,
+            },
+            FlashLoanAmount { amount: 42, fee: 1 },
+        ];
+        let check = FlashLoanReceiverInstruction::ReceiveFlashLoan {
+            loans: loans.clone(),
+        };
+        let packed = check.pack();
+        // Please use ReceiveFlashLoanInstruction::SPL_DISCRIMINATOR in your
+        // program, the following is just for test purposes
+        let preimage = hash::hashv(&[format!("{NAMESPACE}:receive-flash-loan").as_bytes()]);
+        let discriminator = &preimage.as_ref()[..ArrayDiscriminator::LENGTH];
+        let mut expect = vec![];
+        expect.extend_from_slice(discriminator.as_ref());
+        expect.push(2);
+        for loan in loans {
+            expect.extend_from_slice(&loan.amount.to_le_bytes());
+            expect.extend_from_slice(&loan.fee.to_le_bytes());
+        }
+        assert_eq!(packed, expect);
+        let unpacked = FlashLoanReceiverInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        // truncated loans
+        assert_eq!(
+            FlashLoanReceiverInstruction::unpack(&expect[..expect.len() - 1]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        // legacy tag 0 `(amount)` instruction
+        let mut legacy = vec![0];
+        legacy.extend_from_slice(&1_000u64.to_le_bytes());
+        assert_eq!(
+            FlashLoanReceiverInstruction::unpack(&legacy),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn receive_flash_loan_accounts() {
+        let program_id = Pubkey::new_unique();
+        let loan_accounts = [
+            FlashLoanAccounts {
+                destination_liquidity: Pubkey::new_unique(),
+                reserve_liquidity_supply: Pubkey::new_unique(),
+                liquidity_mint: Pubkey::new_unique(),
+                token_program_id: Pubkey::new_unique(),
+            },
+            FlashLoanAccounts {
+                destination_liquidity: Pubkey::new_unique(),
+                reserve_liquidity_supply: Pubkey::new_unique(),
+                liquidity_mint: Pubkey::new_unique(),
+                token_program_id: Pubkey::new_unique(),
+            },
+        ];
+        let additional_account = AccountMeta::new_readonly(Pubkey::new_unique(), true);
+        let loans = vec![FlashLoanAmount::default(); 2];
+        let instruction = receive_flash_loan(
+            &program_id,
+            &loan_accounts,
+            loans.clone(),
+            &[additional_account.clone()],
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 2 * ACCOUNTS_PER_LOAN + 1);
+        assert_eq!(
+            instruction.accounts[ACCOUNTS_PER_LOAN].pubkey,
+            loan_accounts[1].destination_liquidity
+        );
+        assert!(instruction.accounts[1].is_writable);
+        assert!(!instruction.accounts[2].is_writable);
+        assert_eq!(
+            instruction.accounts[2 * ACCOUNTS_PER_LOAN],
+            additional_account
+        );
+        assert_eq!(
+            FlashLoanReceiverInstruction::unpack(&instruction.data).unwrap(),
+            FlashLoanReceiverInstruction::ReceiveFlashLoan { loans }
+        );
+    }
+}
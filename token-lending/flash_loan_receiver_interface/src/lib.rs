@@ -0,0 +1,16 @@
+//! Crate defining the interface of flash loan receiver programs, invoked by
+//! the lending program with the liquidity lent by one or more reserves and
+//! required to return each loan with its fee before the invocation ends.
+
+#![allow(clippy::arithmetic_side_effects)]
+#![deny(missing_docs)]
+#![cfg_attr(not(test), forbid(unsafe_code))]
+
+pub mod instruction;
+
+// Export current sdk types for downstream users building with a different sdk
+// version
+pub use solana_program;
+
+/// Namespace for all programs implementing flash-loan-receiver
+pub const NAMESPACE: &str = "solarti-flash-loan-receiver-interface";
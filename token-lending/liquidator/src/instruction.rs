@@ -0,0 +1,250 @@
+//! Instructions submitted by the liquidator
+
+use {
+    crate::{
+        liquidation::{FlashLoanFunding, Liquidation},
+        market::MarketSnapshot,
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+    spl_token_lending::{
+        error::LendingError,
+        instruction::{
+            flash_loan, liquidate_obligation, refresh_obligation, refresh_reserve,
+            refresh_reserve_with_token_swap_oracle, with_token_program,
+        },
+        oracle::OracleKind,
+        state::{Obligation, Reserve},
+    },
+};
+
+/// Token accounts of a liquidator, the associated token accounts of its
+/// wallet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidatorAccounts {
+    /// Liquidity repaid to the repay reserve
+    pub source_liquidity: Pubkey,
+    /// Collateral received from the withdraw reserve
+    pub destination_collateral: Pubkey,
+}
+
+impl LiquidatorAccounts {
+    /// Get the token accounts of `liquidator` used by a liquidation
+    pub fn new(
+        snapshot: &MarketSnapshot,
+        liquidation: &Liquidation,
+        liquidator: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let repay_reserve = get_reserve(snapshot, &liquidation.repay_reserve_pubkey)?;
+        let withdraw_reserve = get_reserve(snapshot, &liquidation.withdraw_reserve_pubkey)?;
+        Ok(Self {
+            source_liquidity: get_associated_token_address_with_program_id(
+                liquidator,
+                &repay_reserve.liquidity.mint_pubkey,
+                repay_reserve.mint_token_program_id(&snapshot.lending_market),
+            ),
+            destination_collateral: get_associated_token_address_with_program_id(
+                liquidator,
+                &withdraw_reserve.collateral.mint_pubkey,
+                withdraw_reserve.mint_token_program_id(&snapshot.lending_market),
+            ),
+        })
+    }
+}
+
+/// Create a `RefreshReserve` instruction with the oracle accounts of a
+/// reserve
+pub fn refresh_reserve_instruction(
+    program_id: Pubkey,
+    snapshot: &MarketSnapshot,
+    reserve_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let reserve = get_reserve(snapshot, &reserve_pubkey)?;
+    match reserve.liquidity.oracle_kind {
+        OracleKind::Pyth | OracleKind::Switchboard => Ok(refresh_reserve(
+            program_id,
+            reserve_pubkey,
+            reserve.liquidity.oracle_pubkey,
+        )),
+        OracleKind::TokenSwapTwap => {
+            let (token_a_pubkey, token_b_pubkey) = snapshot
+                .swap_token_accounts
+                .get(&reserve.liquidity.oracle_pubkey)
+                .ok_or(LendingError::InvalidOracleConfig)?;
+            Ok(refresh_reserve_with_token_swap_oracle(
+                program_id,
+                reserve_pubkey,
+                reserve.liquidity.oracle_pubkey,
+                *token_a_pubkey,
+                *token_b_pubkey,
+            ))
+        }
+    }
+}
+
+/// Create the instructions refreshing the reserves of an obligation,
+/// followed by the obligation itself
+pub fn refresh_obligation_instructions(
+    program_id: Pubkey,
+    snapshot: &MarketSnapshot,
+    obligation_pubkey: Pubkey,
+    obligation: &Obligation,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let obligation_reserves: Vec<Pubkey> = obligation
+        .deposits
+        .iter()
+        .map(|collateral| collateral.deposit_reserve)
+        .chain(
+            obligation
+                .borrows
+                .iter()
+                .map(|liquidity| liquidity.borrow_reserve),
+        )
+        .collect();
+
+    let mut refreshed_reserves: Vec<Pubkey> = vec![];
+    let mut instructions = vec![];
+    for reserve_pubkey in obligation_reserves.iter() {
+        if refreshed_reserves.contains(reserve_pubkey) {
+            continue;
+        }
+        instructions.push(refresh_reserve_instruction(
+            program_id,
+            snapshot,
+            *reserve_pubkey,
+        )?);
+        refreshed_reserves.push(*reserve_pubkey);
+    }
+    instructions.push(refresh_obligation(
+        program_id,
+        obligation_pubkey,
+        obligation_reserves,
+    ));
+    Ok(instructions)
+}
+
+/// Create the instructions liquidating an obligation from the token accounts
+/// of `liquidator`, which must sign the transaction.
+///
+/// With a flash loan, `LiquidateObligation` is not submitted directly: the
+/// flash loan receiver program is expected to invoke it with the accounts it
+/// is given, after `program_id`.
+pub fn liquidation_instructions(
+    program_id: Pubkey,
+    snapshot: &MarketSnapshot,
+    liquidation: &Liquidation,
+    liquidator: Pubkey,
+    flash_loan_funding: Option<&FlashLoanFunding>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let obligation = snapshot
+        .obligations
+        .get(&liquidation.obligation_pubkey)
+        .ok_or(LendingError::InvalidAccountInput)?;
+    let repay_reserve = get_reserve(snapshot, &liquidation.repay_reserve_pubkey)?;
+    let withdraw_reserve = get_reserve(snapshot, &liquidation.withdraw_reserve_pubkey)?;
+    let accounts = LiquidatorAccounts::new(snapshot, liquidation, &liquidator)?;
+
+    let mut instructions = refresh_obligation_instructions(
+        program_id,
+        snapshot,
+        liquidation.obligation_pubkey,
+        obligation,
+    )?;
+
+    let repay_token_program_id = *repay_reserve.mint_token_program_id(&snapshot.lending_market);
+    let mut instruction = with_token_program(
+        liquidate_obligation(
+            program_id,
+            liquidation.repay_amount,
+            accounts.source_liquidity,
+            accounts.destination_collateral,
+            liquidation.repay_reserve_pubkey,
+            repay_reserve.liquidity.supply_pubkey,
+            liquidation.withdraw_reserve_pubkey,
+            withdraw_reserve.collateral.supply_pubkey,
+            liquidation.obligation_pubkey,
+            snapshot.lending_market_pubkey,
+            liquidator,
+        ),
+        repay_token_program_id,
+        if repay_token_program_id != spl_token::id() {
+            Some(repay_reserve.liquidity.mint_pubkey)
+        } else {
+            None
+        },
+    );
+    let withdraw_token_program_id =
+        withdraw_reserve.mint_token_program_id(&snapshot.lending_market);
+    if *withdraw_token_program_id != repay_token_program_id {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(*withdraw_token_program_id, false));
+    }
+
+    match flash_loan_funding {
+        Some(funding) => {
+            let mut receiver_accounts = vec![AccountMeta::new_readonly(program_id, false)];
+            receiver_accounts.extend(instruction.accounts);
+            receiver_accounts.extend(funding.receiver_accounts.iter().cloned());
+            instructions.push(flash_loan_instruction(
+                funding,
+                liquidation.repay_amount,
+                accounts.source_liquidity,
+                receiver_accounts,
+            ));
+        }
+        None => instructions.push(instruction),
+    }
+    Ok(instructions)
+}
+
+/// Create a `FlashLoan` instruction lending liquidity to the liquidator,
+/// which also receives the host fee
+fn flash_loan_instruction(
+    funding: &FlashLoanFunding,
+    amount: u64,
+    destination_liquidity: Pubkey,
+    receiver_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    // the token program of the funding reserve is set before adding the
+    // receiver accounts, which may include the token program of the liquidated
+    // reserves
+    let mut instruction = with_token_program(
+        flash_loan(
+            funding.program_id,
+            amount,
+            funding.reserve.liquidity.supply_pubkey,
+            destination_liquidity,
+            funding.reserve_pubkey,
+            funding.reserve.liquidity.fee_receiver,
+            destination_liquidity,
+            funding.reserve.lending_market,
+            funding.receiver_program_id,
+            vec![],
+        ),
+        funding.token_program_id,
+        None,
+    );
+    instruction.accounts.extend(receiver_accounts);
+    if funding.token_program_id != spl_token::id() {
+        instruction.accounts.push(AccountMeta::new_readonly(
+            funding.reserve.liquidity.mint_pubkey,
+            false,
+        ));
+    }
+    instruction
+}
+
+fn get_reserve<'a>(
+    snapshot: &'a MarketSnapshot,
+    reserve_pubkey: &Pubkey,
+) -> Result<&'a Reserve, ProgramError> {
+    snapshot
+        .reserves
+        .get(reserve_pubkey)
+        .ok_or_else(|| LendingError::InvalidAccountInput.into())
+}
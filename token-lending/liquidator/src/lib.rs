@@ -0,0 +1,17 @@
+#![deny(missing_docs)]
+
+//! A liquidation bot for the token-lending program.
+//!
+//! The liquidator reads the reserves and obligations of a lending market,
+//! values the obligations as `RefreshObligation` would, picks the most
+//! profitable repay and withdraw reserve pair of each unhealthy obligation and
+//! builds the transaction liquidating it, optionally funded by a flash loan.
+
+pub mod instruction;
+pub mod liquidation;
+pub mod market;
+pub mod rpc;
+
+// Export current sdk types for downstream users building with a different sdk
+// version
+pub use solana_program;
@@ -0,0 +1,439 @@
+//! Liquidation planning
+
+use {
+    crate::market::{mint_decimals_factor, MarketSnapshot},
+    solana_program::{
+        clock::Slot, instruction::AccountMeta, program_error::ProgramError, pubkey::Pubkey,
+    },
+    spl_token_lending::{
+        math::{Decimal, TryDiv, TryMul, TrySub},
+        state::{Obligation, Reserve},
+    },
+    std::collections::HashMap,
+};
+
+/// Reserve of another lending program deployment lending the liquidity
+/// repaid by a liquidation.
+///
+/// The lending program rejects reentrant calls, so a flash loan receiver can
+/// only call `LiquidateObligation` on a different deployment than the one
+/// lending it the liquidity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlashLoanFunding {
+    /// Lending program lending the liquidity
+    pub program_id: Pubkey,
+    /// Reserve lending the liquidity
+    pub reserve_pubkey: Pubkey,
+    /// Reserve state
+    pub reserve: Reserve,
+    /// Token program of the reserve liquidity mint
+    pub token_program_id: Pubkey,
+    /// Flash loan receiver program. It is given the liquidated lending
+    /// program id, the `LiquidateObligation` accounts and
+    /// `receiver_accounts`, and must return the loan with its fee.
+    pub receiver_program_id: Pubkey,
+    /// Additional accounts required by the receiver program
+    pub receiver_accounts: Vec<AccountMeta>,
+}
+
+/// Liquidation settings
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LiquidationConfig {
+    /// Minimum profit of a liquidation, in quote currency
+    pub min_profit: Decimal,
+    /// Flash loans funding liquidations of the reserve liquidity they lend,
+    /// used instead of the liquidator balances
+    pub flash_loans: Vec<FlashLoanFunding>,
+}
+
+impl LiquidationConfig {
+    /// Find the flash loan lending the liquidity of a reserve
+    pub fn flash_loan_for(&self, reserve: &Reserve) -> Option<&FlashLoanFunding> {
+        self.flash_loans
+            .iter()
+            .find(|funding| funding.reserve.liquidity.mint_pubkey == reserve.liquidity.mint_pubkey)
+    }
+}
+
+/// Liquidation of an obligation
+#[derive(Clone, Debug, PartialEq)]
+pub struct Liquidation {
+    /// Obligation to liquidate
+    pub obligation_pubkey: Pubkey,
+    /// Reserve of the borrowed liquidity to repay
+    pub repay_reserve_pubkey: Pubkey,
+    /// Reserve of the deposited collateral to withdraw
+    pub withdraw_reserve_pubkey: Pubkey,
+    /// Amount of liquidity repaid
+    pub repay_amount: u64,
+    /// Amount of collateral received
+    pub withdraw_amount: u64,
+    /// Flash loan fee paid to fund the repaid liquidity, less the host fee
+    /// returned to the liquidator
+    pub flash_loan_fee: u64,
+    /// Value of the collateral received less the value of the liquidity
+    /// repaid and the flash loan fee, in quote currency
+    pub profit: Decimal,
+}
+
+/// Find the most profitable liquidation of a refreshed obligation, if any
+/// reaches the configured minimum profit.
+///
+/// The liquidity repaid to each reserve is limited by `liquidity_balances`,
+/// keyed by liquidity mint, or by the available liquidity of the flash loan
+/// reserve lending it.
+pub fn plan_liquidation(
+    snapshot: &MarketSnapshot,
+    obligation_pubkey: Pubkey,
+    obligation: &Obligation,
+    slot: Slot,
+    config: &LiquidationConfig,
+    liquidity_balances: &HashMap<Pubkey, u64>,
+) -> Result<Option<Liquidation>, ProgramError> {
+    let mut best_liquidation: Option<Liquidation> = None;
+
+    for liquidity in obligation.borrows.iter() {
+        if liquidity.market_value == Decimal::zero() {
+            continue;
+        }
+        let repay_reserve = snapshot.reserve_at(&liquidity.borrow_reserve, slot)?;
+        let flash_loan = config.flash_loan_for(&repay_reserve);
+        let available_amount = match flash_loan {
+            Some(funding) => funding.reserve.liquidity.available_amount,
+            None => liquidity_balances
+                .get(&repay_reserve.liquidity.mint_pubkey)
+                .copied()
+                .unwrap_or_default(),
+        };
+        if available_amount == 0 {
+            continue;
+        }
+
+        for collateral in obligation.deposits.iter() {
+            if collateral.market_value == Decimal::zero() {
+                continue;
+            }
+            let withdraw_reserve = snapshot.reserve_at(&collateral.deposit_reserve, slot)?;
+            let result = withdraw_reserve.calculate_liquidation(
+                available_amount,
+                obligation,
+                liquidity,
+                collateral,
+            )?;
+            if result.repay_amount == 0 || result.withdraw_amount == 0 {
+                continue;
+            }
+
+            let flash_loan_fee = match flash_loan {
+                Some(funding) => match funding
+                    .reserve
+                    .config
+                    .fees
+                    .calculate_flash_loan_fees(result.repay_amount.into())
+                {
+                    Ok((total_fee, host_fee)) => total_fee.saturating_sub(host_fee),
+                    Err(_) => continue,
+                },
+                None => 0,
+            };
+
+            let repay_value = liquidity_value(
+                &repay_reserve,
+                Decimal::from(result.repay_amount.saturating_add(flash_loan_fee)),
+            )?;
+            let withdraw_value = liquidity_value(
+                &withdraw_reserve,
+                withdraw_reserve
+                    .collateral_exchange_rate()?
+                    .decimal_collateral_to_liquidity(result.withdraw_amount.into())?,
+            )?;
+            if withdraw_value <= repay_value {
+                continue;
+            }
+            let profit = withdraw_value.try_sub(repay_value)?;
+            if profit < config.min_profit {
+                continue;
+            }
+
+            if best_liquidation
+                .as_ref()
+                .map_or(true, |best| profit > best.profit)
+            {
+                best_liquidation = Some(Liquidation {
+                    obligation_pubkey,
+                    repay_reserve_pubkey: liquidity.borrow_reserve,
+                    withdraw_reserve_pubkey: collateral.deposit_reserve,
+                    repay_amount: result.repay_amount,
+                    withdraw_amount: result.withdraw_amount,
+                    flash_loan_fee,
+                    profit,
+                });
+            }
+        }
+    }
+
+    Ok(best_liquidation)
+}
+
+/// Value of an amount of reserve liquidity, in quote currency
+fn liquidity_value(reserve: &Reserve, liquidity_amount: Decimal) -> Result<Decimal, ProgramError> {
+    liquidity_amount
+        .try_mul(reserve.liquidity.market_price)?
+        .try_div(mint_decimals_factor(reserve)?)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        spl_token_lending::{
+            oracle::OracleKind,
+            state::{
+                InitObligationParams, InitReserveParams, LendingMarket, NewReserveCollateralParams,
+                NewReserveLiquidityParams, ObligationCollateral, ObligationLiquidity,
+                ReserveCollateral, ReserveConfig, ReserveFees, ReserveLiquidity,
+            },
+        },
+    };
+
+    const SLOT: Slot = 1;
+
+    fn reserve(lending_market: Pubkey, decimals: u8, price: u64, liquidity_amount: u64) -> Reserve {
+        let mut reserve = Reserve::new(InitReserveParams {
+            current_slot: SLOT,
+            lending_market,
+            liquidity: ReserveLiquidity::new(NewReserveLiquidityParams {
+                mint_pubkey: Pubkey::new_unique(),
+                mint_decimals: decimals,
+                supply_pubkey: Pubkey::new_unique(),
+                fee_receiver: Pubkey::new_unique(),
+                oracle_pubkey: Pubkey::new_unique(),
+                market_price: Decimal::from(price),
+                oracle_kind: OracleKind::Pyth,
+                oracle_quote_decimals: 0,
+            }),
+            collateral: ReserveCollateral::new(NewReserveCollateralParams {
+                mint_pubkey: Pubkey::new_unique(),
+                supply_pubkey: Pubkey::new_unique(),
+            }),
+            config: ReserveConfig {
+                loan_to_value_ratio: 50,
+                liquidation_threshold: 80,
+                liquidation_bonus: 10,
+                fees: ReserveFees {
+                    flash_loan_fee_wad: 3_000_000_000_000_000,
+                    host_fee_percentage: 20,
+                    ..ReserveFees::default()
+                },
+                ..ReserveConfig::default()
+            },
+            token_program_id: Pubkey::default(),
+        });
+        reserve.deposit_liquidity(liquidity_amount).unwrap();
+        reserve
+    }
+
+    struct Market {
+        snapshot: MarketSnapshot,
+        mln_pubkey: Pubkey,
+        usdc_pubkey: Pubkey,
+        obligation_pubkey: Pubkey,
+        obligation: Obligation,
+    }
+
+    // 100 MLN at 20 USDC deposited, 1600 USDC borrowed at an 80% liquidation
+    // threshold
+    fn market() -> Market {
+        let lending_market_pubkey = Pubkey::new_unique();
+        let mut snapshot = MarketSnapshot::new(lending_market_pubkey, LendingMarket::default());
+
+        let mln_pubkey = Pubkey::new_unique();
+        snapshot
+            .reserves
+            .insert(mln_pubkey, reserve(lending_market_pubkey, 9, 20, 0));
+        let usdc_pubkey = Pubkey::new_unique();
+        snapshot.reserves.insert(
+            usdc_pubkey,
+            reserve(lending_market_pubkey, 6, 1, 10_000_000_000),
+        );
+
+        let mut collateral = ObligationCollateral::new(mln_pubkey);
+        collateral.deposited_amount = 100_000_000_000;
+        let mut liquidity = ObligationLiquidity::new(usdc_pubkey);
+        liquidity.borrowed_amount_wads = Decimal::from(1_600_000_000u64);
+        let obligation = Obligation::new(InitObligationParams {
+            current_slot: SLOT,
+            lending_market: lending_market_pubkey,
+            owner: Pubkey::new_unique(),
+            deposits: vec![collateral],
+            borrows: vec![liquidity],
+        });
+        let obligation_pubkey = Pubkey::new_unique();
+        snapshot
+            .obligations
+            .insert(obligation_pubkey, obligation.clone());
+
+        Market {
+            snapshot,
+            mln_pubkey,
+            usdc_pubkey,
+            obligation_pubkey,
+            obligation,
+        }
+    }
+
+    fn usdc_balance(market: &Market, amount: u64) -> HashMap<Pubkey, u64> {
+        let usdc_mint = market.snapshot.reserves[&market.usdc_pubkey]
+            .liquidity
+            .mint_pubkey;
+        HashMap::from([(usdc_mint, amount)])
+    }
+
+    #[test]
+    fn unhealthy_obligations() {
+        let mut market = market();
+        let unhealthy_obligations = market.snapshot.unhealthy_obligations(SLOT);
+        assert_eq!(unhealthy_obligations.len(), 1);
+        let (obligation_pubkey, obligation) = &unhealthy_obligations[0];
+        assert_eq!(*obligation_pubkey, market.obligation_pubkey);
+        assert_eq!(obligation.deposited_value, Decimal::from(2_000u64));
+        assert_eq!(obligation.borrowed_value, Decimal::from(1_600u64));
+        assert_eq!(obligation.unhealthy_borrow_value, Decimal::from(1_600u64));
+
+        // 1500 USDC borrowed is below the liquidation threshold
+        market
+            .snapshot
+            .obligations
+            .get_mut(&market.obligation_pubkey)
+            .unwrap()
+            .borrows[0]
+            .borrowed_amount_wads = Decimal::from(1_500_000_000u64);
+        assert!(market.snapshot.unhealthy_obligations(SLOT).is_empty());
+    }
+
+    #[test]
+    fn plan_with_balance() {
+        let market = market();
+        let obligation = market
+            .snapshot
+            .refresh_obligation(&market.obligation, SLOT)
+            .unwrap();
+
+        // 800 USDC repaid for 40 MLN + 10% bonus, worth 880 USDC
+        let liquidation = plan_liquidation(
+            &market.snapshot,
+            market.obligation_pubkey,
+            &obligation,
+            SLOT,
+            &LiquidationConfig::default(),
+            &usdc_balance(&market, 1_000_000_000),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            liquidation,
+            Liquidation {
+                obligation_pubkey: market.obligation_pubkey,
+                repay_reserve_pubkey: market.usdc_pubkey,
+                withdraw_reserve_pubkey: market.mln_pubkey,
+                repay_amount: 800_000_000,
+                withdraw_amount: 44_000_000_000,
+                flash_loan_fee: 0,
+                profit: Decimal::from(80u64),
+            }
+        );
+
+        // 400 USDC repaid for 22 MLN
+        let liquidation = plan_liquidation(
+            &market.snapshot,
+            market.obligation_pubkey,
+            &obligation,
+            SLOT,
+            &LiquidationConfig::default(),
+            &usdc_balance(&market, 400_000_000),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(liquidation.repay_amount, 400_000_000);
+        assert_eq!(liquidation.withdraw_amount, 22_000_000_000);
+        assert_eq!(liquidation.profit, Decimal::from(40u64));
+
+        // no USDC to repay with
+        assert_eq!(
+            plan_liquidation(
+                &market.snapshot,
+                market.obligation_pubkey,
+                &obligation,
+                SLOT,
+                &LiquidationConfig::default(),
+                &HashMap::new(),
+            )
+            .unwrap(),
+            None
+        );
+
+        // profit below the minimum
+        assert_eq!(
+            plan_liquidation(
+                &market.snapshot,
+                market.obligation_pubkey,
+                &obligation,
+                SLOT,
+                &LiquidationConfig {
+                    min_profit: Decimal::from(100u64),
+                    ..LiquidationConfig::default()
+                },
+                &usdc_balance(&market, 1_000_000_000),
+            )
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn plan_with_flash_loan() {
+        let market = market();
+        let obligation = market
+            .snapshot
+            .refresh_obligation(&market.obligation, SLOT)
+            .unwrap();
+
+        let mut funding_reserve = reserve(Pubkey::new_unique(), 6, 1, 10_000_000_000);
+        funding_reserve.liquidity.mint_pubkey = market.snapshot.reserves[&market.usdc_pubkey]
+            .liquidity
+            .mint_pubkey;
+        let config = LiquidationConfig {
+            min_profit: Decimal::zero(),
+            flash_loans: vec![FlashLoanFunding {
+                program_id: Pubkey::new_unique(),
+                reserve_pubkey: Pubkey::new_unique(),
+                reserve: funding_reserve,
+                token_program_id: spl_token::id(),
+                receiver_program_id: Pubkey::new_unique(),
+                receiver_accounts: vec![],
+            }],
+        };
+
+        // 0.3% fee on 800 USDC is 2.4 USDC, of which 20% is returned as host
+        // fee
+        let liquidation = plan_liquidation(
+            &market.snapshot,
+            market.obligation_pubkey,
+            &obligation,
+            SLOT,
+            &config,
+            &HashMap::new(),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(liquidation.repay_amount, 800_000_000);
+        assert_eq!(liquidation.withdraw_amount, 44_000_000_000);
+        assert_eq!(liquidation.flash_loan_fee, 1_920_000);
+        assert_eq!(
+            liquidation.profit,
+            Decimal::from(80_000_000u64 - 1_920_000)
+                .try_div(1_000_000)
+                .unwrap()
+        );
+    }
+}
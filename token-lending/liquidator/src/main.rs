@@ -0,0 +1,416 @@
+use {
+    clap::{
+        crate_description, crate_name, crate_version, value_t, value_t_or_exit, App, Arg,
+        ArgMatches,
+    },
+    miraland_clap_utils::{
+        input_parsers::{pubkey_of, pubkeys_of},
+        input_validators::{is_amount, is_keypair, is_parsable, is_pubkey, is_url},
+        keypair::signer_from_path,
+    },
+    miraland_client::rpc_client::RpcClient,
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_pack::Pack,
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        commitment_config::CommitmentConfig, message::Message, signature::Signer,
+        transaction::Transaction,
+    },
+    spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    spl_token_lending::{
+        math::{Decimal, WAD},
+        state::{LendingMarket, Reserve},
+    },
+    spl_token_lending_liquidator::{
+        instruction::{liquidation_instructions, LiquidatorAccounts},
+        liquidation::{plan_liquidation, FlashLoanFunding, Liquidation, LiquidationConfig},
+        market::MarketSnapshot,
+        rpc::{get_liquidity_balances, get_market_snapshot},
+    },
+    std::{process::exit, str::FromStr, thread::sleep, time::Duration},
+};
+
+struct Config {
+    rpc_client: RpcClient,
+    liquidator: Box<dyn Signer>,
+    lending_program_id: Pubkey,
+    lending_market_pubkey: Pubkey,
+    liquidation_config: LiquidationConfig,
+    dry_run: bool,
+}
+
+type Error = Box<dyn std::error::Error>;
+
+fn main() {
+    miraland_logger::setup_with_default("solana=info,miraland=info");
+
+    let default_lending_program_id: &str = &spl_token_lending::id().to_string();
+
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .arg({
+            let arg = Arg::with_name("config_file")
+                .short("C")
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("Configuration file to use");
+            if let Some(ref config_file) = *miraland_cli_config::CONFIG_FILE {
+                arg.default_value(config_file)
+            } else {
+                arg
+            }
+        })
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .validator(is_url)
+                .help("JSON RPC URL for the cluster.  Default from the configuration file."),
+        )
+        .arg(
+            Arg::with_name("liquidator")
+                .long("keypair")
+                .validator(is_keypair)
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .help("Liquidator and fee payer.  Default from the configuration file."),
+        )
+        .arg(
+            Arg::with_name("lending_program_id")
+                .long("program")
+                .validator(is_pubkey)
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .required(true)
+                .default_value(default_lending_program_id)
+                .help("Lending program ID"),
+        )
+        .arg(
+            Arg::with_name("lending_market")
+                .long("market")
+                .validator(is_pubkey)
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .required(true)
+                .help("Lending market to liquidate obligations of"),
+        )
+        .arg(
+            Arg::with_name("min_profit")
+                .long("min-profit")
+                .validator(is_amount)
+                .value_name("AMOUNT")
+                .takes_value(true)
+                .default_value("0")
+                .help("Minimum profit of a liquidation, in the market quote currency"),
+        )
+        .arg(
+            Arg::with_name("interval")
+                .long("interval")
+                .validator(is_parsable::<u64>)
+                .value_name("SECONDS")
+                .takes_value(true)
+                .default_value("10")
+                .help("Seconds to wait between scans of the lending market"),
+        )
+        .arg(
+            Arg::with_name("once")
+                .long("once")
+                .takes_value(false)
+                .help("Scan the lending market once and exit"),
+        )
+        .arg(
+            Arg::with_name("dry_run")
+                .long("dry-run")
+                .takes_value(false)
+                .help("Simulate liquidations instead of executing them"),
+        )
+        .arg(
+            Arg::with_name("flash_loan_program_id")
+                .long("flash-loan-program")
+                .validator(is_pubkey)
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .requires_all(&["flash_loan_reserves", "flash_loan_receiver_program_id"])
+                .help(
+                    "Lending program to flash borrow the repaid liquidity from. \
+                    Must be a different deployment than --program",
+                ),
+        )
+        .arg(
+            Arg::with_name("flash_loan_reserves")
+                .long("flash-loan-reserve")
+                .validator(is_pubkey)
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .multiple(true)
+                .requires("flash_loan_program_id")
+                .help(
+                    "Reserve to flash borrow the liquidity it lends from. \
+                    May be specified multiple times",
+                ),
+        )
+        .arg(
+            Arg::with_name("flash_loan_receiver_program_id")
+                .long("flash-loan-receiver")
+                .validator(is_pubkey)
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .requires("flash_loan_program_id")
+                .help("Flash loan receiver program liquidating the obligation"),
+        )
+        .arg(
+            Arg::with_name("flash_loan_receiver_accounts")
+                .long("flash-loan-account")
+                .validator(is_account_meta)
+                .value_name("PUBKEY[:readonly]")
+                .takes_value(true)
+                .multiple(true)
+                .requires("flash_loan_program_id")
+                .help(
+                    "Additional account required by the flash loan receiver program, \
+                    writable unless suffixed with :readonly. \
+                    May be specified multiple times",
+                ),
+        )
+        .get_matches();
+
+    let mut wallet_manager = None;
+    let config = {
+        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+            miraland_cli_config::Config::load(config_file).unwrap_or_default()
+        } else {
+            miraland_cli_config::Config::default()
+        };
+        let json_rpc_url = value_t!(matches, "json_rpc_url", String)
+            .unwrap_or_else(|_| cli_config.json_rpc_url.clone());
+
+        let liquidator = signer_from_path(
+            &matches,
+            matches
+                .value_of("liquidator")
+                .unwrap_or(&cli_config.keypair_path),
+            "liquidator",
+            &mut wallet_manager,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            exit(1);
+        });
+
+        let rpc_client =
+            RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed());
+        let lending_program_id = pubkey_of(&matches, "lending_program_id").unwrap();
+        let liquidation_config = liquidation_config_of(&rpc_client, &lending_program_id, &matches)
+            .unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                exit(1);
+            });
+
+        Config {
+            rpc_client,
+            liquidator,
+            lending_program_id,
+            lending_market_pubkey: pubkey_of(&matches, "lending_market").unwrap(),
+            liquidation_config,
+            dry_run: matches.is_present("dry_run"),
+        }
+    };
+
+    let interval = Duration::from_secs(value_t_or_exit!(matches, "interval", u64));
+    loop {
+        if let Err(err) = liquidate_market(&config) {
+            eprintln!("Failed to scan lending market: {}", err);
+        }
+        if matches.is_present("once") {
+            break;
+        }
+        sleep(interval);
+    }
+}
+
+/// Liquidate the unhealthy obligations of the lending market
+fn liquidate_market(config: &Config) -> Result<(), Error> {
+    let snapshot = get_market_snapshot(
+        &config.rpc_client,
+        &config.lending_program_id,
+        &config.lending_market_pubkey,
+    )?;
+    let slot = config.rpc_client.get_slot()?;
+    let mut liquidity_balances =
+        get_liquidity_balances(&config.rpc_client, &snapshot, &config.liquidator.pubkey())?;
+
+    let unhealthy_obligations = snapshot.unhealthy_obligations(slot);
+    println!(
+        "Scanned {} obligations at slot {}, {} unhealthy",
+        snapshot.obligations.len(),
+        slot,
+        unhealthy_obligations.len()
+    );
+
+    for (obligation_pubkey, obligation) in unhealthy_obligations {
+        let liquidation = match plan_liquidation(
+            &snapshot,
+            obligation_pubkey,
+            &obligation,
+            slot,
+            &config.liquidation_config,
+            &liquidity_balances,
+        ) {
+            Ok(Some(liquidation)) => liquidation,
+            Ok(None) => continue,
+            Err(err) => {
+                eprintln!(
+                    "Failed to plan liquidation of {}: {}",
+                    obligation_pubkey, err
+                );
+                continue;
+            }
+        };
+
+        println!(
+            "Liquidating obligation {}, repaying {} to reserve {} for {} collateral of reserve {}, profit {}",
+            obligation_pubkey,
+            liquidation.repay_amount,
+            liquidation.repay_reserve_pubkey,
+            liquidation.withdraw_amount,
+            liquidation.withdraw_reserve_pubkey,
+            liquidation.profit
+        );
+        match send_liquidation(config, &snapshot, &liquidation) {
+            Ok(()) => {
+                let repay_mint = snapshot.reserves[&liquidation.repay_reserve_pubkey]
+                    .liquidity
+                    .mint_pubkey;
+                if let Some(balance) = liquidity_balances.get_mut(&repay_mint) {
+                    *balance = balance.saturating_sub(liquidation.repay_amount);
+                }
+            }
+            Err(err) => eprintln!("Failed to liquidate {}: {}", obligation_pubkey, err),
+        }
+    }
+    Ok(())
+}
+
+fn send_liquidation(
+    config: &Config,
+    snapshot: &MarketSnapshot,
+    liquidation: &Liquidation,
+) -> Result<(), Error> {
+    let liquidator = config.liquidator.pubkey();
+    let repay_reserve = &snapshot.reserves[&liquidation.repay_reserve_pubkey];
+    let withdraw_reserve = &snapshot.reserves[&liquidation.withdraw_reserve_pubkey];
+    let flash_loan_funding = config.liquidation_config.flash_loan_for(repay_reserve);
+
+    let mut instructions = vec![create_associated_token_account_idempotent(
+        &liquidator,
+        &liquidator,
+        &withdraw_reserve.collateral.mint_pubkey,
+        withdraw_reserve.mint_token_program_id(&snapshot.lending_market),
+    )];
+    if flash_loan_funding.is_some() {
+        instructions.push(create_associated_token_account_idempotent(
+            &liquidator,
+            &liquidator,
+            &repay_reserve.liquidity.mint_pubkey,
+            repay_reserve.mint_token_program_id(&snapshot.lending_market),
+        ));
+    }
+    instructions.extend(liquidation_instructions(
+        config.lending_program_id,
+        snapshot,
+        liquidation,
+        liquidator,
+        flash_loan_funding,
+    )?);
+
+    let accounts = LiquidatorAccounts::new(snapshot, liquidation, &liquidator)?;
+    println!(
+        "Repaying from {}, receiving collateral in {}",
+        accounts.source_liquidity, accounts.destination_collateral
+    );
+    send_instructions(config, &instructions)
+}
+
+fn send_instructions(config: &Config, instructions: &[Instruction]) -> Result<(), Error> {
+    let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+    let message = Message::new_with_blockhash(
+        instructions,
+        Some(&config.liquidator.pubkey()),
+        &recent_blockhash,
+    );
+    let transaction = Transaction::new(&[config.liquidator.as_ref()], message, recent_blockhash);
+    if config.dry_run {
+        let result = config.rpc_client.simulate_transaction(&transaction)?;
+        println!("Simulate result: {:?}", result);
+    } else {
+        let signature = config
+            .rpc_client
+            .send_and_confirm_transaction(&transaction)?;
+        println!("Signature: {}", signature);
+    }
+    Ok(())
+}
+
+fn liquidation_config_of(
+    rpc_client: &RpcClient,
+    lending_program_id: &Pubkey,
+    matches: &ArgMatches<'_>,
+) -> Result<LiquidationConfig, Error> {
+    let min_profit = value_t_or_exit!(matches, "min_profit", f64);
+    let mut liquidation_config = LiquidationConfig {
+        min_profit: Decimal::from_scaled_val((min_profit * WAD as f64) as u128),
+        flash_loans: vec![],
+    };
+
+    if let Some(program_id) = pubkey_of(matches, "flash_loan_program_id") {
+        if program_id == *lending_program_id {
+            return Err("Flash loans must be borrowed from a different lending program".into());
+        }
+        let receiver_program_id = pubkey_of(matches, "flash_loan_receiver_program_id").unwrap();
+        let receiver_accounts: Vec<AccountMeta> = matches
+            .values_of("flash_loan_receiver_accounts")
+            .map(|values| {
+                values
+                    .map(|value| parse_account_meta(value).unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for reserve_pubkey in pubkeys_of(matches, "flash_loan_reserves").unwrap() {
+            let reserve = Reserve::unpack(&rpc_client.get_account(&reserve_pubkey)?.data)?;
+            let lending_market =
+                LendingMarket::unpack(&rpc_client.get_account(&reserve.lending_market)?.data)?;
+            liquidation_config.flash_loans.push(FlashLoanFunding {
+                program_id,
+                reserve_pubkey,
+                token_program_id: *reserve.mint_token_program_id(&lending_market),
+                reserve,
+                receiver_program_id,
+                receiver_accounts: receiver_accounts.clone(),
+            });
+        }
+    }
+    Ok(liquidation_config)
+}
+
+fn parse_account_meta(value: &str) -> Result<AccountMeta, String> {
+    let (pubkey, is_writable) = match value.strip_suffix(":readonly") {
+        Some(pubkey) => (pubkey, false),
+        None => (value, true),
+    };
+    let pubkey =
+        Pubkey::from_str(pubkey).map_err(|e| format!("Invalid account {}: {}", value, e))?;
+    Ok(if is_writable {
+        AccountMeta::new(pubkey, false)
+    } else {
+        AccountMeta::new_readonly(pubkey, false)
+    })
+}
+
+fn is_account_meta(value: String) -> Result<(), String> {
+    parse_account_meta(&value).map(|_| ())
+}
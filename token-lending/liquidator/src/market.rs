@@ -0,0 +1,166 @@
+//! Snapshot of the accounts of a lending market
+
+use {
+    solana_program::{
+        clock::Slot, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    },
+    spl_token_lending::{
+        error::LendingError,
+        math::{Decimal, Rate, TryAdd, TryDiv, TryMul},
+        state::{LendingMarket, Obligation, Reserve},
+    },
+    std::collections::HashMap,
+};
+
+/// Offset of the lending market in reserve and obligation account data, after
+/// the version and the last update
+pub const LENDING_MARKET_OFFSET: usize = 10;
+
+/// Lending market accounts read at a point in time
+#[derive(Clone, Debug, Default)]
+pub struct MarketSnapshot {
+    /// Lending market address
+    pub lending_market_pubkey: Pubkey,
+    /// Lending market state
+    pub lending_market: LendingMarket,
+    /// Reserves of the lending market
+    pub reserves: HashMap<Pubkey, Reserve>,
+    /// Obligations of the lending market
+    pub obligations: HashMap<Pubkey, Obligation>,
+    /// Token A and token B accounts of the token-swap pools used as reserve
+    /// oracles, by pool address
+    pub swap_token_accounts: HashMap<Pubkey, (Pubkey, Pubkey)>,
+}
+
+impl MarketSnapshot {
+    /// Create an empty snapshot of a lending market
+    pub fn new(lending_market_pubkey: Pubkey, lending_market: LendingMarket) -> Self {
+        Self {
+            lending_market_pubkey,
+            lending_market,
+            ..Self::default()
+        }
+    }
+
+    /// Add a program account to the snapshot. Reserves and obligations of
+    /// the lending market are kept, other accounts are ignored.
+    pub fn add_account(&mut self, pubkey: Pubkey, data: &[u8]) -> Result<(), ProgramError> {
+        match data.len() {
+            Reserve::LEN => {
+                let reserve = Reserve::unpack(data)?;
+                if reserve.lending_market == self.lending_market_pubkey {
+                    self.reserves.insert(pubkey, reserve);
+                }
+            }
+            Obligation::LEN => {
+                let obligation = Obligation::unpack(data)?;
+                if obligation.lending_market == self.lending_market_pubkey {
+                    self.obligations.insert(pubkey, obligation);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Get a reserve with interest accrued up to `slot`, as `RefreshReserve`
+    /// would leave it, at its last refreshed market price
+    pub fn reserve_at(&self, reserve_pubkey: &Pubkey, slot: Slot) -> Result<Reserve, ProgramError> {
+        let mut reserve = self
+            .reserves
+            .get(reserve_pubkey)
+            .cloned()
+            .ok_or(LendingError::InvalidAccountInput)?;
+        reserve.accrue_interest(slot)?;
+        Ok(reserve)
+    }
+
+    /// Value an obligation as `RefreshObligation` would at `slot`, using the
+    /// reserves of the snapshot
+    pub fn refresh_obligation(
+        &self,
+        obligation: &Obligation,
+        slot: Slot,
+    ) -> Result<Obligation, ProgramError> {
+        let mut obligation = obligation.clone();
+
+        let mut deposited_value = Decimal::zero();
+        let mut borrowed_value = Decimal::zero();
+        let mut allowed_borrow_value = Decimal::zero();
+        let mut unhealthy_borrow_value = Decimal::zero();
+        let has_other_collateral = obligation.deposits.len() > 1;
+
+        for collateral in obligation.deposits.iter_mut() {
+            let deposit_reserve = self.reserve_at(&collateral.deposit_reserve, slot)?;
+            let market_value = deposit_reserve
+                .collateral_exchange_rate()?
+                .decimal_collateral_to_liquidity(collateral.deposited_amount.into())?
+                .try_mul(deposit_reserve.liquidity.market_price)?
+                .try_div(mint_decimals_factor(&deposit_reserve)?)?;
+            collateral.market_value = market_value;
+
+            deposited_value = deposited_value.try_add(market_value)?;
+            if !deposit_reserve.config.isolated || !has_other_collateral {
+                allowed_borrow_value = allowed_borrow_value.try_add(market_value.try_mul(
+                    Rate::from_percent(deposit_reserve.config.loan_to_value_ratio),
+                )?)?;
+            }
+            unhealthy_borrow_value = unhealthy_borrow_value.try_add(market_value.try_mul(
+                Rate::from_percent(deposit_reserve.config.liquidation_threshold),
+            )?)?;
+        }
+
+        for liquidity in obligation.borrows.iter_mut() {
+            let borrow_reserve = self.reserve_at(&liquidity.borrow_reserve, slot)?;
+            liquidity.accrue_interest(borrow_reserve.liquidity.cumulative_borrow_rate_wads)?;
+            let market_value = liquidity
+                .borrowed_amount_wads
+                .try_mul(borrow_reserve.liquidity.market_price)?
+                .try_div(mint_decimals_factor(&borrow_reserve)?)?;
+            liquidity.market_value = market_value;
+
+            borrowed_value = borrowed_value.try_add(market_value)?;
+        }
+
+        obligation.deposited_value = deposited_value;
+        obligation.borrowed_value = borrowed_value;
+        obligation.allowed_borrow_value = allowed_borrow_value;
+        obligation.unhealthy_borrow_value = unhealthy_borrow_value;
+        obligation.last_update.update_slot(slot);
+
+        Ok(obligation)
+    }
+
+    /// Find the obligations that can be liquidated at `slot`, refreshed, with
+    /// the largest borrowed value first. Obligations borrowing from or
+    /// depositing into a reserve missing from the snapshot are skipped.
+    pub fn unhealthy_obligations(&self, slot: Slot) -> Vec<(Pubkey, Obligation)> {
+        let mut unhealthy_obligations: Vec<(Pubkey, Obligation)> = self
+            .obligations
+            .iter()
+            .filter(|(_, obligation)| !obligation.borrows.is_empty())
+            .filter_map(|(pubkey, obligation)| {
+                self.refresh_obligation(obligation, slot)
+                    .ok()
+                    .map(|obligation| (*pubkey, obligation))
+            })
+            .filter(|(_, obligation)| is_unhealthy(obligation))
+            .collect();
+        unhealthy_obligations.sort_by(|(_, a), (_, b)| b.borrowed_value.cmp(&a.borrowed_value));
+        unhealthy_obligations
+    }
+}
+
+/// Whether a refreshed obligation can be liquidated by `LiquidateObligation`
+pub fn is_unhealthy(obligation: &Obligation) -> bool {
+    obligation.deposited_value > Decimal::zero()
+        && obligation.borrowed_value > Decimal::zero()
+        && obligation.borrowed_value >= obligation.unhealthy_borrow_value
+}
+
+/// Factor converting token amounts of the reserve liquidity to whole tokens
+pub(crate) fn mint_decimals_factor(reserve: &Reserve) -> Result<u64, ProgramError> {
+    Ok(10u64
+        .checked_pow(reserve.liquidity.mint_decimals as u32)
+        .ok_or(LendingError::MathOverflow)?)
+}
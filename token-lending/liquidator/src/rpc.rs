@@ -0,0 +1,107 @@
+//! Lending market accounts read over RPC
+
+use {
+    crate::market::{MarketSnapshot, LENDING_MARKET_OFFSET},
+    miraland_account_decoder::UiAccountEncoding,
+    miraland_client::{
+        rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_program::{program_pack::Pack, pubkey::Pubkey},
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+    spl_token_2022::{extension::StateWithExtensions, state::Account as Token},
+    spl_token_lending::{oracle::OracleKind, state::LendingMarket},
+    spl_token_swap::state::SwapVersion,
+    std::collections::HashMap,
+};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Read the lending market, its reserves and obligations, and the token
+/// accounts of the token-swap pools used as reserve oracles
+pub fn get_market_snapshot(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    lending_market_pubkey: &Pubkey,
+) -> Result<MarketSnapshot, Error> {
+    let lending_market_account = rpc_client.get_account(lending_market_pubkey)?;
+    let lending_market = LendingMarket::unpack(&lending_market_account.data)?;
+    let mut snapshot = MarketSnapshot::new(*lending_market_pubkey, lending_market);
+
+    let accounts = rpc_client.get_program_accounts_with_config(
+        program_id,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                LENDING_MARKET_OFFSET,
+                lending_market_pubkey.as_ref(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+    for (pubkey, account) in accounts {
+        snapshot.add_account(pubkey, &account.data)?;
+    }
+
+    let swap_pubkeys: Vec<Pubkey> = snapshot
+        .reserves
+        .values()
+        .filter(|reserve| reserve.liquidity.oracle_kind == OracleKind::TokenSwapTwap)
+        .map(|reserve| reserve.liquidity.oracle_pubkey)
+        .collect();
+    for (swap_pubkey, swap_account) in swap_pubkeys
+        .iter()
+        .zip(rpc_client.get_multiple_accounts(&swap_pubkeys)?)
+    {
+        if let Some(swap_account) = swap_account {
+            let swap = SwapVersion::unpack(&swap_account.data)?;
+            snapshot.swap_token_accounts.insert(
+                *swap_pubkey,
+                (*swap.token_a_account(), *swap.token_b_account()),
+            );
+        }
+    }
+
+    Ok(snapshot)
+}
+
+/// Read the balances of the associated token accounts of `liquidator` for the
+/// liquidity mints of the reserves, keyed by liquidity mint
+pub fn get_liquidity_balances(
+    rpc_client: &RpcClient,
+    snapshot: &MarketSnapshot,
+    liquidator: &Pubkey,
+) -> Result<HashMap<Pubkey, u64>, Error> {
+    let mints: Vec<(Pubkey, Pubkey)> = snapshot
+        .reserves
+        .values()
+        .map(|reserve| {
+            (
+                reserve.liquidity.mint_pubkey,
+                *reserve.mint_token_program_id(&snapshot.lending_market),
+            )
+        })
+        .collect();
+    let token_account_pubkeys: Vec<Pubkey> = mints
+        .iter()
+        .map(|(mint_pubkey, token_program_id)| {
+            get_associated_token_address_with_program_id(liquidator, mint_pubkey, token_program_id)
+        })
+        .collect();
+
+    let mut balances = HashMap::new();
+    for ((mint_pubkey, _), token_account) in mints
+        .iter()
+        .zip(rpc_client.get_multiple_accounts(&token_account_pubkeys)?)
+    {
+        if let Some(token_account) = token_account {
+            let token_account = StateWithExtensions::<Token>::unpack(&token_account.data)?;
+            balances.insert(*mint_pubkey, token_account.base.amount);
+        }
+    }
+    Ok(balances)
+}
@@ -0,0 +1,320 @@
+#![cfg(feature = "test-sbf")]
+
+use {
+    solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey},
+    solana_program_test::*,
+    solana_sdk::{
+        account::Account,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    spl_associated_token_account::get_associated_token_address,
+    spl_token::state::{Account as Token, AccountState},
+    spl_token_lending::{
+        math::Decimal,
+        oracle::OracleKind,
+        processor::process_instruction,
+        pyth,
+        state::{
+            InitLendingMarketParams, InitObligationParams, InitReserveParams, LendingMarket,
+            NewReserveCollateralParams, NewReserveLiquidityParams, Obligation,
+            ObligationCollateral, ObligationLiquidity, Reserve, ReserveCollateral, ReserveConfig,
+            ReserveLiquidity,
+        },
+    },
+    spl_token_lending_liquidator::{
+        instruction::liquidation_instructions,
+        liquidation::{plan_liquidation, LiquidationConfig},
+        market::MarketSnapshot,
+    },
+    std::collections::HashMap,
+};
+
+const QUOTE_CURRENCY: [u8; 32] = *b"USD\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
+
+const LAMPORTS_TO_MLN: u64 = 1_000_000_000;
+const FRACTIONAL_TO_USDC: u64 = 1_000_000;
+
+// 100 MLN collateral
+const MLN_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_MLN;
+// 100 MLN * 80% liquidation threshold -> 80 MLN * 20 USDC -> 1600 USDC borrow
+const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = 1_600 * FRACTIONAL_TO_USDC;
+// 1600 USDC * 50% -> 800 USDC liquidation
+const USDC_LIQUIDATION_AMOUNT_FRACTIONAL: u64 = USDC_BORROW_AMOUNT_FRACTIONAL / 2;
+// 800 USDC / 20 USDC per MLN -> 40 MLN + 10% bonus -> 44 MLN
+const MLN_LIQUIDATION_AMOUNT_LAMPORTS: u64 = 44 * LAMPORTS_TO_MLN;
+const LIQUIDATOR_USDC_FRACTIONAL: u64 = 1_000 * FRACTIONAL_TO_USDC;
+
+struct TestReserve {
+    pubkey: Pubkey,
+    liquidity_mint_pubkey: Pubkey,
+    collateral_mint_pubkey: Pubkey,
+}
+
+fn add_token_account(
+    test: &mut ProgramTest,
+    pubkey: Pubkey,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) {
+    let mut account = Account::new(u32::MAX as u64, Token::LEN, &spl_token::id());
+    Token {
+        mint,
+        owner,
+        amount,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        ..Token::default()
+    }
+    .pack_into_slice(&mut account.data);
+    test.add_account(pubkey, account);
+}
+
+fn add_pyth_price(test: &mut ProgramTest, price: i64) -> Pubkey {
+    let mut data = vec![0; std::mem::size_of::<pyth::Price>()];
+    let pyth_price = pyth::load_mut::<pyth::Price>(data.as_mut_slice()).unwrap();
+    pyth_price.magic = pyth::MAGIC;
+    pyth_price.ver = pyth::VERSION;
+    pyth_price.ptype = pyth::PriceType::Price;
+    pyth_price.agg.status = pyth::PriceStatus::Trading;
+    pyth_price.agg.price = price;
+
+    let price_pubkey = Pubkey::new_unique();
+    test.add_account(
+        price_pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    price_pubkey
+}
+
+fn add_reserve(
+    test: &mut ProgramTest,
+    lending_market_pubkey: Pubkey,
+    lending_market_authority: Pubkey,
+    decimals: u8,
+    price: u64,
+    liquidity_amount: u64,
+    borrow_amount: u64,
+    collateral_amount: u64,
+) -> TestReserve {
+    let liquidity_mint_pubkey = Pubkey::new_unique();
+    let collateral_mint_pubkey = Pubkey::new_unique();
+
+    let liquidity_supply_pubkey = Pubkey::new_unique();
+    add_token_account(
+        test,
+        liquidity_supply_pubkey,
+        liquidity_mint_pubkey,
+        lending_market_authority,
+        liquidity_amount,
+    );
+    let collateral_supply_pubkey = Pubkey::new_unique();
+    add_token_account(
+        test,
+        collateral_supply_pubkey,
+        collateral_mint_pubkey,
+        lending_market_authority,
+        collateral_amount,
+    );
+
+    let mut reserve = Reserve::new(InitReserveParams {
+        current_slot: 1,
+        lending_market: lending_market_pubkey,
+        liquidity: ReserveLiquidity::new(NewReserveLiquidityParams {
+            mint_pubkey: liquidity_mint_pubkey,
+            mint_decimals: decimals,
+            supply_pubkey: liquidity_supply_pubkey,
+            fee_receiver: Pubkey::new_unique(),
+            oracle_pubkey: add_pyth_price(test, price as i64),
+            market_price: Decimal::from(price),
+            oracle_kind: OracleKind::Pyth,
+            oracle_quote_decimals: 0,
+        }),
+        collateral: ReserveCollateral::new(NewReserveCollateralParams {
+            mint_pubkey: collateral_mint_pubkey,
+            supply_pubkey: collateral_supply_pubkey,
+        }),
+        config: ReserveConfig {
+            optimal_utilization_rate: 80,
+            loan_to_value_ratio: 50,
+            liquidation_bonus: 10,
+            liquidation_threshold: 80,
+            max_borrow_rate: 30,
+            ..ReserveConfig::default()
+        },
+        token_program_id: spl_token::id(),
+    });
+    reserve.deposit_liquidity(liquidity_amount).unwrap();
+    reserve.liquidity.borrow(borrow_amount.into()).unwrap();
+
+    let reserve_pubkey = Pubkey::new_unique();
+    let mut account = Account::new(u32::MAX as u64, Reserve::LEN, &spl_token_lending::id());
+    reserve.pack_into_slice(&mut account.data);
+    test.add_account(reserve_pubkey, account);
+
+    TestReserve {
+        pubkey: reserve_pubkey,
+        liquidity_mint_pubkey,
+        collateral_mint_pubkey,
+    }
+}
+
+async fn get_token_balance(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    let account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+    Token::unpack(&account.data).unwrap().amount
+}
+
+#[tokio::test]
+async fn test_liquidate_unhealthy_obligation() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let lending_market_pubkey = Pubkey::new_unique();
+    let (lending_market_authority, bump_seed) =
+        Pubkey::find_program_address(&[lending_market_pubkey.as_ref()], &spl_token_lending::id());
+    let mut account = Account::new(
+        u32::MAX as u64,
+        LendingMarket::LEN,
+        &spl_token_lending::id(),
+    );
+    LendingMarket::new(InitLendingMarketParams {
+        bump_seed,
+        owner: Pubkey::new_unique(),
+        quote_currency: QUOTE_CURRENCY,
+        token_program_id: spl_token::id(),
+        oracle_program_id: Pubkey::new_unique(),
+    })
+    .pack_into_slice(&mut account.data);
+    test.add_account(lending_market_pubkey, account);
+
+    let mln_reserve = add_reserve(
+        &mut test,
+        lending_market_pubkey,
+        lending_market_authority,
+        9,
+        20,
+        0,
+        0,
+        MLN_DEPOSIT_AMOUNT_LAMPORTS,
+    );
+    let usdc_reserve = add_reserve(
+        &mut test,
+        lending_market_pubkey,
+        lending_market_authority,
+        6,
+        1,
+        2 * USDC_BORROW_AMOUNT_FRACTIONAL,
+        USDC_BORROW_AMOUNT_FRACTIONAL,
+        0,
+    );
+
+    let mut collateral = ObligationCollateral::new(mln_reserve.pubkey);
+    collateral.deposited_amount = MLN_DEPOSIT_AMOUNT_LAMPORTS;
+    let mut liquidity = ObligationLiquidity::new(usdc_reserve.pubkey);
+    liquidity.borrowed_amount_wads = Decimal::from(USDC_BORROW_AMOUNT_FRACTIONAL);
+    let obligation_pubkey = Pubkey::new_unique();
+    let mut account = Account::new(u32::MAX as u64, Obligation::LEN, &spl_token_lending::id());
+    Obligation::new(InitObligationParams {
+        current_slot: 1,
+        lending_market: lending_market_pubkey,
+        owner: Pubkey::new_unique(),
+        deposits: vec![collateral],
+        borrows: vec![liquidity],
+    })
+    .pack_into_slice(&mut account.data);
+    test.add_account(obligation_pubkey, account);
+
+    let liquidator = Keypair::new();
+    let source_liquidity =
+        get_associated_token_address(&liquidator.pubkey(), &usdc_reserve.liquidity_mint_pubkey);
+    add_token_account(
+        &mut test,
+        source_liquidity,
+        usdc_reserve.liquidity_mint_pubkey,
+        liquidator.pubkey(),
+        LIQUIDATOR_USDC_FRACTIONAL,
+    );
+    let destination_collateral =
+        get_associated_token_address(&liquidator.pubkey(), &mln_reserve.collateral_mint_pubkey);
+    add_token_account(
+        &mut test,
+        destination_collateral,
+        mln_reserve.collateral_mint_pubkey,
+        liquidator.pubkey(),
+        0,
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let lending_market_account = banks_client
+        .get_account(lending_market_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut snapshot = MarketSnapshot::new(
+        lending_market_pubkey,
+        LendingMarket::unpack(&lending_market_account.data).unwrap(),
+    );
+    for pubkey in [mln_reserve.pubkey, usdc_reserve.pubkey, obligation_pubkey] {
+        let account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+        snapshot.add_account(pubkey, &account.data).unwrap();
+    }
+    let slot = banks_client.get_root_slot().await.unwrap();
+
+    let unhealthy_obligations = snapshot.unhealthy_obligations(slot);
+    assert_eq!(unhealthy_obligations.len(), 1);
+    let (unhealthy_pubkey, obligation) = &unhealthy_obligations[0];
+    assert_eq!(*unhealthy_pubkey, obligation_pubkey);
+
+    let liquidation = plan_liquidation(
+        &snapshot,
+        obligation_pubkey,
+        obligation,
+        slot,
+        &LiquidationConfig::default(),
+        &HashMap::from([(
+            usdc_reserve.liquidity_mint_pubkey,
+            LIQUIDATOR_USDC_FRACTIONAL,
+        )]),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(liquidation.repay_reserve_pubkey, usdc_reserve.pubkey);
+    assert_eq!(liquidation.withdraw_reserve_pubkey, mln_reserve.pubkey);
+    assert_eq!(liquidation.repay_amount, USDC_LIQUIDATION_AMOUNT_FRACTIONAL);
+    assert_eq!(liquidation.withdraw_amount, MLN_LIQUIDATION_AMOUNT_LAMPORTS);
+    assert_eq!(liquidation.profit, Decimal::from(80u64));
+
+    let mut transaction = Transaction::new_with_payer(
+        &liquidation_instructions(
+            spl_token_lending::id(),
+            &snapshot,
+            &liquidation,
+            liquidator.pubkey(),
+            None,
+        )
+        .unwrap(),
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &liquidator], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, source_liquidity).await,
+        LIQUIDATOR_USDC_FRACTIONAL - USDC_LIQUIDATION_AMOUNT_FRACTIONAL
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, destination_collateral).await,
+        MLN_LIQUIDATION_AMOUNT_LAMPORTS
+    );
+}
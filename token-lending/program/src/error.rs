@@ -165,6 +165,21 @@ pub enum LendingError {
     /// Lending instruction exceeds desired slippage limit
     #[error("Amount smaller than desired slippage limit")]
     ExceededSlippage,
+    /// Deposit would exceed the reserve deposit limit
+    #[error("Reserve deposit limit exceeded")]
+    DepositLimitExceeded,
+    /// Borrow would exceed the reserve borrow limit
+    #[error("Reserve borrow limit exceeded")]
+    BorrowLimitExceeded,
+    /// Isolated collateral cannot be combined with other collateral
+    #[error("Isolated collateral cannot be combined with other collateral")]
+    IsolatedCollateral,
+    /// Token mint has an extension not supported by reserves
+    #[error("Token mint extension is not supported")]
+    UnsupportedTokenExtension,
+    /// Self-liquidation would decrease the obligation health
+    #[error("Obligation health would decrease")]
+    ObligationHealthDecreased,
 }
 
 impl From<LendingError> for ProgramError {
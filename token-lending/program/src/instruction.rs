@@ -3,7 +3,8 @@
 use {
     crate::{
         error::LendingError,
-        state::{ReserveConfig, ReserveFees},
+        oracle::OracleKind,
+        state::{RateCurve, ReserveConfig, ReserveFees, ReserveOracleConfig, RATE_CURVE_LEN},
     },
     solana_program::{
         instruction::{AccountMeta, Instruction},
@@ -12,9 +13,15 @@ use {
         pubkey::{Pubkey, PUBKEY_BYTES},
         sysvar,
     },
-    std::{convert::TryInto, mem::size_of},
+    std::{
+        convert::{TryFrom, TryInto},
+        mem::size_of,
+    },
 };
 
+/// Maximum number of reserves lending liquidity in a `MultiReserveFlashLoan`
+pub const MAX_FLASH_LOAN_RESERVES: usize = 8;
+
 /// Instructions supported by the lending program.
 #[derive(Clone, Debug, PartialEq)]
 pub enum LendingInstruction {
@@ -64,21 +71,32 @@ pub enum LendingInstruction {
     ///   5. `[writable]` Reserve liquidity fee receiver - uninitialized.
     ///   6. `[writable]` Reserve collateral SPL Token mint - uninitialized.
     ///   7. `[writable]` Reserve collateral token supply - uninitialized.
-    ///   8. `[]` Pyth product account.
-    ///   9. `[]` Pyth price account. This will be used as the reserve liquidity
-    ///      oracle account.
+    ///   8. `[]` Oracle product account, depending on the oracle kind:
+    ///      - Pyth: Pyth product account.
+    ///      - Switchboard: Switchboard program id, owner of the aggregator.
+    ///      - TokenSwapTwap: Quote currency mint, the other mint of the swap.
+    ///   9. `[]` Oracle price account: Pyth price account, Switchboard
+    ///      aggregator account, or token-swap account. This will be used as the
+    ///      reserve liquidity oracle account.
     ///   10 `[]` Lending market account.
     ///   11 `[]` Derived lending market authority.
     ///   12 `[signer]` Lending market owner.
     ///   13 `[signer]` User transfer authority ($authority).
     ///   14 `[]` Clock sysvar.
     ///   15 `[]` Rent sysvar.
-    ///   16 `[]` Token program id.
+    ///   16 `[]` Token program id, owner of the reserve liquidity mint: the
+    ///      token program or the Token-2022 program.
+    ///   17 `[]` Token-swap token A account, for TokenSwapTwap only.
+    ///   18 `[]` Token-swap token B account, for TokenSwapTwap only.
+    ///   .. `[]` Accounts required by a transfer hook of a Token-2022
+    ///      liquidity mint.
     InitReserve {
         /// Initial amount of liquidity to deposit into the new reserve
         liquidity_amount: u64,
         /// Reserve configuration values
         config: ReserveConfig,
+        /// Source of the market price of the reserve liquidity
+        oracle_kind: OracleKind,
     },
 
     // 3
@@ -87,9 +105,11 @@ pub enum LendingInstruction {
     /// Accounts expected by this instruction:
     ///
     ///   0. `[writable]` Reserve account.
-    ///   1. `[]` Reserve liquidity oracle account. Must be the Pyth price
+    ///   1. `[]` Reserve liquidity oracle account. Must be the oracle price
     ///      account specified at InitReserve.
     ///   2. `[]` Clock sysvar.
+    ///   3. `[]` Token-swap token A account, for TokenSwapTwap only.
+    ///   4. `[]` Token-swap token B account, for TokenSwapTwap only.
     RefreshReserve,
 
     // 4
@@ -108,7 +128,9 @@ pub enum LendingInstruction {
     ///   6. `[]` Derived lending market authority.
     ///   7. `[signer]` User transfer authority ($authority).
     ///   8. `[]` Clock sysvar.
-    ///   9. `[]` Token program id.
+    ///   9. `[]` Reserve token program id.
+    ///   .. `[]` Reserve liquidity mint, for Token-2022 reserves only,
+    ///      followed by accounts required by its transfer hook.
     DepositReserveLiquidity {
         /// Amount of liquidity to deposit in exchange for collateral tokens
         liquidity_amount: u64,
@@ -129,7 +151,9 @@ pub enum LendingInstruction {
     ///   6. `[]` Derived lending market authority.
     ///   7. `[signer]` User transfer authority ($authority).
     ///   8. `[]` Clock sysvar.
-    ///   9. `[]` Token program id.
+    ///   9. `[]` Reserve token program id.
+    ///   .. `[]` Reserve liquidity mint, for Token-2022 reserves only,
+    ///      followed by accounts required by its transfer hook.
     RedeemReserveCollateral {
         /// Amount of collateral tokens to redeem in exchange for liquidity
         collateral_amount: u64,
@@ -179,7 +203,7 @@ pub enum LendingInstruction {
     ///   5. `[signer]` Obligation owner.
     ///   6. `[signer]` User transfer authority ($authority).
     ///   7. `[]` Clock sysvar.
-    ///   8. `[]` Token program id.
+    ///   8. `[]` Deposit reserve token program id.
     DepositObligationCollateral {
         /// Amount of collateral tokens to deposit
         collateral_amount: u64,
@@ -201,7 +225,7 @@ pub enum LendingInstruction {
     ///   5. `[]` Derived lending market authority.
     ///   6. `[signer]` Obligation owner.
     ///   7. `[]` Clock sysvar.
-    ///   8. `[]` Token program id.
+    ///   8. `[]` Withdraw reserve token program id.
     WithdrawObligationCollateral {
         /// Amount of collateral tokens to withdraw - u64::MAX for up to 100% of
         /// deposited amount
@@ -226,8 +250,10 @@ pub enum LendingInstruction {
     ///   6. `[]` Derived lending market authority.
     ///   7. `[signer]` Obligation owner.
     ///   8. `[]` Clock sysvar.
-    ///   9. `[]` Token program id.
+    ///   9. `[]` Borrow reserve token program id.
     ///   10 `[optional, writable]` Host fee receiver account.
+    ///   .. `[]` Borrow reserve liquidity mint, for Token-2022 reserves only,
+    ///      followed by accounts required by its transfer hook.
     BorrowObligationLiquidity {
         /// Amount of liquidity to borrow - u64::MAX for 100% of borrowing power
         liquidity_amount: u64,
@@ -251,7 +277,9 @@ pub enum LendingInstruction {
     ///   4. `[]` Lending market account.
     ///   5. `[signer]` User transfer authority ($authority).
     ///   6. `[]` Clock sysvar.
-    ///   7. `[]` Token program id.
+    ///   7. `[]` Repay reserve token program id.
+    ///   .. `[]` Repay reserve liquidity mint, for Token-2022 reserves only,
+    ///      followed by accounts required by its transfer hook.
     RepayObligationLiquidity {
         /// Amount of liquidity to repay - u64::MAX for 100% of borrowed amount
         liquidity_amount: u64,
@@ -277,7 +305,11 @@ pub enum LendingInstruction {
     ///   8. `[]` Derived lending market authority.
     ///   9. `[signer]` User transfer authority ($authority).
     ///   10 `[]` Clock sysvar.
-    ///   11 `[]` Token program id.
+    ///   11 `[]` Repay reserve token program id.
+    ///   .. `[]` Withdraw reserve token program id, if different from the
+    ///      repay reserve token program id.
+    ///   .. `[]` Repay reserve liquidity mint, for Token-2022 reserves only,
+    ///      followed by accounts required by its transfer hook.
     LiquidateObligation {
         /// Amount of liquidity to repay - u64::MAX for up to 100% of borrowed
         /// amount
@@ -299,12 +331,14 @@ pub enum LendingInstruction {
     ///   4. `[writable]` Host fee receiver.
     ///   5. `[]` Lending market account.
     ///   6. `[]` Derived lending market authority.
-    ///   7. `[]` Token program id.
+    ///   7. `[]` Reserve token program id.
     ///   8. `[]` Flash loan receiver program id. Must implement an instruction
     ///      that has tag of 0 and a signature of `(amount: u64)` This
     ///      instruction must return the amount to the source liquidity account.
     ///   .. `[any]` Additional accounts expected by the receiving program's
-    /// `ReceiveFlashLoan` instruction.
+    /// `ReceiveFlashLoan` instruction. For Token-2022 reserves, these must
+    /// include the reserve liquidity mint and accounts required by its
+    /// transfer hook.
     ///
     ///   The flash loan receiver program that is to be invoked should contain
     /// an instruction with   tag `0` and accept the total amount (including
@@ -343,6 +377,76 @@ pub enum LendingInstruction {
         /// Reserve configuration updated values
         new_config: ReserveConfig,
     },
+
+    // 15
+    /// Repay borrowed liquidity to a reserve and withdraw the matching value
+    /// of collateral from an obligation, on behalf of the obligation owner and
+    /// without a liquidation bonus. The obligation does not need to be
+    /// unhealthy, but its health must not decrease. Requires a refreshed
+    /// obligation and reserves.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source liquidity token account. Minted by repay
+    ///      reserve liquidity mint. $authority can transfer $liquidity_amount.
+    ///   1. `[writable]` Destination collateral token account. Minted by
+    ///      withdraw reserve collateral mint.
+    ///   2. `[writable]` Repay reserve account - refreshed.
+    ///   3. `[writable]` Repay reserve liquidity supply SPL Token account.
+    ///   4. `[]` Withdraw reserve account - refreshed.
+    ///   5. `[writable]` Withdraw reserve collateral supply SPL Token account.
+    ///   6. `[writable]` Obligation account - refreshed.
+    ///   7. `[]` Lending market account.
+    ///   8. `[]` Derived lending market authority.
+    ///   9. `[signer]` User transfer authority ($authority).
+    ///   10 `[signer]` Obligation owner.
+    ///   11 `[]` Clock sysvar.
+    ///   12 `[]` Repay reserve token program id.
+    ///   .. `[]` Withdraw reserve token program id, if different from the
+    ///      repay reserve token program id.
+    ///   .. `[]` Repay reserve liquidity mint, for Token-2022 reserves only,
+    ///      followed by accounts required by its transfer hook.
+    SelfLiquidateObligation {
+        /// Amount of liquidity to repay - u64::MAX for up to 100% of borrowed
+        /// amount
+        liquidity_amount: u64,
+    },
+
+    // 16
+    /// Make a flash loan from several reserves of a lending market at once.
+    ///
+    /// The flash loan receiver program is invoked once with the
+    /// `ReceiveFlashLoan` instruction of the flash loan receiver interface,
+    /// carrying the amount lent and the fee owed for each reserve. It must
+    /// return the amount and fee of each loan to the reserve liquidity supply.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Lending market account.
+    ///   1. `[]` Derived lending market authority.
+    ///   2. `[]` Flash loan receiver program id. Must implement the flash loan
+    ///      receiver interface.
+    ///
+    ///   For each reserve, in the order of `liquidity_amounts`:
+    ///
+    ///   0. `[writable]` Reserve account.
+    ///   1. `[writable]` Reserve liquidity supply SPL Token account.
+    ///   2. `[writable]` Destination liquidity token account. Minted by reserve
+    ///      liquidity mint.
+    ///   3. `[writable]` Flash loan fee receiver account. Must match the
+    ///      reserve liquidity fee receiver.
+    ///   4. `[writable]` Host fee receiver.
+    ///   5. `[]` Reserve liquidity mint.
+    ///   6. `[]` Reserve token program id.
+    ///
+    ///   .. `[any]` Additional accounts expected by the receiving program's
+    ///   `ReceiveFlashLoan` instruction, including accounts required by the
+    ///   transfer hooks of Token-2022 reserve liquidity mints.
+    MultiReserveFlashLoan {
+        /// The amount to borrow from each reserve - u64::MAX for up to 100% of
+        /// available liquidity
+        liquidity_amounts: Vec<u64>,
+    },
 }
 
 impl LendingInstruction {
@@ -367,10 +471,12 @@ impl LendingInstruction {
             }
             2 => {
                 let (liquidity_amount, rest) = Self::unpack_u64(rest)?;
-                let config = Self::unpack_reserve_config(rest)?;
+                let (config, rest) = Self::unpack_reserve_config(rest)?;
+                let (oracle_kind, _rest) = Self::unpack_u8(rest).unwrap_or((0, &[]));
                 Self::InitReserve {
                     liquidity_amount,
                     config,
+                    oracle_kind: OracleKind::try_from(oracle_kind)?,
                 }
             }
             3 => Self::RefreshReserve,
@@ -413,9 +519,27 @@ impl LendingInstruction {
                 Self::FlashLoan { amount }
             }
             14 => {
-                let new_config = Self::unpack_reserve_config(rest)?;
+                let (new_config, _rest) = Self::unpack_reserve_config(rest)?;
                 Self::ModifyReserveConfig { new_config }
             }
+            15 => {
+                let (liquidity_amount, _rest) = Self::unpack_u64(rest)?;
+                Self::SelfLiquidateObligation { liquidity_amount }
+            }
+            16 => {
+                let (count, mut rest) = Self::unpack_u8(rest)?;
+                if count == 0 || count as usize > MAX_FLASH_LOAN_RESERVES {
+                    msg!("Flash loan reserve count is invalid");
+                    return Err(LendingError::InstructionUnpackError.into());
+                }
+                let mut liquidity_amounts = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (liquidity_amount, next) = Self::unpack_u64(rest)?;
+                    liquidity_amounts.push(liquidity_amount);
+                    rest = next;
+                }
+                Self::MultiReserveFlashLoan { liquidity_amounts }
+            }
             _ => {
                 msg!("Instruction cannot be unpacked");
                 return Err(LendingError::InstructionUnpackError.into());
@@ -437,6 +561,34 @@ impl LendingInstruction {
         Ok((value, rest))
     }
 
+    fn unpack_u32(input: &[u8]) -> Result<(u32, &[u8]), ProgramError> {
+        if input.len() < 4 {
+            msg!("u32 cannot be unpacked");
+            return Err(LendingError::InstructionUnpackError.into());
+        }
+        let (bytes, rest) = input.split_at(4);
+        let value = bytes
+            .get(..4)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or(LendingError::InstructionUnpackError)?;
+        Ok((value, rest))
+    }
+
+    fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+        if input.len() < 2 {
+            msg!("u16 cannot be unpacked");
+            return Err(LendingError::InstructionUnpackError.into());
+        }
+        let (bytes, rest) = input.split_at(2);
+        let value = bytes
+            .get(..2)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u16::from_le_bytes)
+            .ok_or(LendingError::InstructionUnpackError)?;
+        Ok((value, rest))
+    }
+
     fn unpack_u8(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
         if input.is_empty() {
             msg!("u8 cannot be unpacked");
@@ -475,7 +627,7 @@ impl LendingInstruction {
         Ok((pk, rest))
     }
 
-    fn unpack_reserve_config(input: &[u8]) -> Result<ReserveConfig, ProgramError> {
+    fn unpack_reserve_config(input: &[u8]) -> Result<(ReserveConfig, &[u8]), ProgramError> {
         let (optimal_utilization_rate, rest) = Self::unpack_u8(input)?;
         let (loan_to_value_ratio, rest) = Self::unpack_u8(rest)?;
         let (liquidation_bonus, rest) = Self::unpack_u8(rest)?;
@@ -485,9 +637,56 @@ impl LendingInstruction {
         let (max_borrow_rate, rest) = Self::unpack_u8(rest)?;
         let (borrow_fee_wad, rest) = Self::unpack_u64(rest)?;
         let (flash_loan_fee_wad, rest) = Self::unpack_u64(rest)?;
-        let (host_fee_percentage, _rest) = Self::unpack_u8(rest)?;
+        let (host_fee_percentage, rest) = Self::unpack_u8(rest)?;
+        // oracle checks were added later, older instructions use the defaults
+        let (oracle, rest) = if rest.len() >= 14 {
+            let (max_staleness_slots, rest) = Self::unpack_u64(rest)?;
+            let (max_confidence_bps, rest) = Self::unpack_u16(rest)?;
+            let (twap_window_seconds, rest) = Self::unpack_u32(rest)?;
+            (
+                ReserveOracleConfig {
+                    max_staleness_slots,
+                    max_confidence_bps,
+                    twap_window_seconds,
+                },
+                rest,
+            )
+        } else {
+            (ReserveOracleConfig::default(), rest)
+        };
+        // as were limits and isolation
+        let (deposit_limit, borrow_limit, isolated, rest) = if rest.len() >= 17 {
+            let (deposit_limit, rest) = Self::unpack_u64(rest)?;
+            let (borrow_limit, rest) = Self::unpack_u64(rest)?;
+            let (isolated, rest) = Self::unpack_u8(rest)?;
+            let isolated = match isolated {
+                0 => false,
+                1 => true,
+                _ => {
+                    msg!("Isolated flag cannot be unpacked");
+                    return Err(LendingError::InstructionUnpackError.into());
+                }
+            };
+            (deposit_limit, borrow_limit, isolated, rest)
+        } else {
+            (0, 0, false, rest)
+        };
+        // and the borrow rate curve
+        let (rate_curve, rest) = if rest.len() >= RATE_CURVE_LEN {
+            RateCurve::unpack_from_buffer(rest)?
+        } else {
+            (RateCurve::empty(), rest)
+        };
+        // and soft liquidation
+        let (soft_liquidation_band, soft_liquidation_bonus, rest) = if rest.len() >= 2 {
+            let (soft_liquidation_band, rest) = Self::unpack_u8(rest)?;
+            let (soft_liquidation_bonus, rest) = Self::unpack_u8(rest)?;
+            (soft_liquidation_band, soft_liquidation_bonus, rest)
+        } else {
+            (0, 0, rest)
+        };
 
-        Ok(ReserveConfig {
+        let config = ReserveConfig {
             optimal_utilization_rate,
             loan_to_value_ratio,
             liquidation_bonus,
@@ -500,7 +699,15 @@ impl LendingInstruction {
                 flash_loan_fee_wad,
                 host_fee_percentage,
             },
-        })
+            oracle,
+            deposit_limit,
+            borrow_limit,
+            isolated,
+            rate_curve,
+            soft_liquidation_band,
+            soft_liquidation_bonus,
+        };
+        Ok((config, rest))
     }
 
     /// Packs a [LendingInstruction](enum.LendingInstruction.html) into a byte
@@ -523,10 +730,12 @@ impl LendingInstruction {
             Self::InitReserve {
                 liquidity_amount,
                 config,
+                oracle_kind,
             } => {
                 buf.push(2);
                 buf.extend_from_slice(&liquidity_amount.to_le_bytes());
                 Self::extend_buffer_from_reserve_config(&mut buf, &config);
+                buf.push(oracle_kind as u8);
             }
             Self::RefreshReserve => {
                 buf.push(3);
@@ -577,6 +786,19 @@ impl LendingInstruction {
                 buf.push(14);
                 Self::extend_buffer_from_reserve_config(&mut buf, &new_config);
             }
+            Self::SelfLiquidateObligation { liquidity_amount } => {
+                buf.push(15);
+                buf.extend_from_slice(&liquidity_amount.to_le_bytes());
+            }
+            Self::MultiReserveFlashLoan {
+                ref liquidity_amounts,
+            } => {
+                buf.push(16);
+                buf.push(liquidity_amounts.len() as u8);
+                for liquidity_amount in liquidity_amounts {
+                    buf.extend_from_slice(&liquidity_amount.to_le_bytes());
+                }
+            }
         }
         buf
     }
@@ -593,6 +815,15 @@ impl LendingInstruction {
         buf.extend_from_slice(&config.fees.borrow_fee_wad.to_le_bytes());
         buf.extend_from_slice(&config.fees.flash_loan_fee_wad.to_le_bytes());
         buf.extend_from_slice(&config.fees.host_fee_percentage.to_le_bytes());
+        buf.extend_from_slice(&config.oracle.max_staleness_slots.to_le_bytes());
+        buf.extend_from_slice(&config.oracle.max_confidence_bps.to_le_bytes());
+        buf.extend_from_slice(&config.oracle.twap_window_seconds.to_le_bytes());
+        buf.extend_from_slice(&config.deposit_limit.to_le_bytes());
+        buf.extend_from_slice(&config.borrow_limit.to_le_bytes());
+        buf.push(config.isolated as u8);
+        config.rate_curve.extend_buffer(buf);
+        buf.extend_from_slice(&config.soft_liquidation_band.to_le_bytes());
+        buf.extend_from_slice(&config.soft_liquidation_bonus.to_le_bytes());
     }
 }
 
@@ -637,7 +868,7 @@ pub fn set_lending_market_owner(
     }
 }
 
-/// Creates an 'InitReserve' instruction.
+/// Creates an 'InitReserve' instruction, priced by a Pyth oracle.
 #[allow(clippy::too_many_arguments)]
 pub fn init_reserve(
     program_id: Pubkey,
@@ -656,12 +887,57 @@ pub fn init_reserve(
     lending_market_pubkey: Pubkey,
     lending_market_owner_pubkey: Pubkey,
     user_transfer_authority_pubkey: Pubkey,
+) -> Instruction {
+    init_reserve_with_oracle(
+        program_id,
+        liquidity_amount,
+        config,
+        OracleKind::Pyth,
+        source_liquidity_pubkey,
+        destination_collateral_pubkey,
+        reserve_pubkey,
+        reserve_liquidity_mint_pubkey,
+        reserve_liquidity_supply_pubkey,
+        reserve_liquidity_fee_receiver_pubkey,
+        reserve_collateral_mint_pubkey,
+        reserve_collateral_supply_pubkey,
+        pyth_product_pubkey,
+        pyth_price_pubkey,
+        lending_market_pubkey,
+        lending_market_owner_pubkey,
+        user_transfer_authority_pubkey,
+        None,
+    )
+}
+
+/// Creates an 'InitReserve' instruction for any kind of oracle. Token-swap
+/// oracles also need the swap token A and B accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn init_reserve_with_oracle(
+    program_id: Pubkey,
+    liquidity_amount: u64,
+    config: ReserveConfig,
+    oracle_kind: OracleKind,
+    source_liquidity_pubkey: Pubkey,
+    destination_collateral_pubkey: Pubkey,
+    reserve_pubkey: Pubkey,
+    reserve_liquidity_mint_pubkey: Pubkey,
+    reserve_liquidity_supply_pubkey: Pubkey,
+    reserve_liquidity_fee_receiver_pubkey: Pubkey,
+    reserve_collateral_mint_pubkey: Pubkey,
+    reserve_collateral_supply_pubkey: Pubkey,
+    oracle_product_pubkey: Pubkey,
+    oracle_price_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    lending_market_owner_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    swap_token_accounts: Option<(Pubkey, Pubkey)>,
 ) -> Instruction {
     let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
         &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
         &program_id,
     );
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new(source_liquidity_pubkey, false),
         AccountMeta::new(destination_collateral_pubkey, false),
         AccountMeta::new(reserve_pubkey, false),
@@ -670,8 +946,8 @@ pub fn init_reserve(
         AccountMeta::new(reserve_liquidity_fee_receiver_pubkey, false),
         AccountMeta::new(reserve_collateral_mint_pubkey, false),
         AccountMeta::new(reserve_collateral_supply_pubkey, false),
-        AccountMeta::new_readonly(pyth_product_pubkey, false),
-        AccountMeta::new_readonly(pyth_price_pubkey, false),
+        AccountMeta::new_readonly(oracle_product_pubkey, false),
+        AccountMeta::new_readonly(oracle_price_pubkey, false),
         AccountMeta::new_readonly(lending_market_pubkey, false),
         AccountMeta::new_readonly(lending_market_authority_pubkey, false),
         AccountMeta::new_readonly(lending_market_owner_pubkey, true),
@@ -680,12 +956,17 @@ pub fn init_reserve(
         AccountMeta::new_readonly(sysvar::rent::id(), false),
         AccountMeta::new_readonly(spl_token::id(), false),
     ];
+    if let Some((swap_token_a_pubkey, swap_token_b_pubkey)) = swap_token_accounts {
+        accounts.push(AccountMeta::new_readonly(swap_token_a_pubkey, false));
+        accounts.push(AccountMeta::new_readonly(swap_token_b_pubkey, false));
+    }
     Instruction {
         program_id,
         accounts,
         data: LendingInstruction::InitReserve {
             liquidity_amount,
             config,
+            oracle_kind,
         }
         .pack(),
     }
@@ -709,6 +990,29 @@ pub fn refresh_reserve(
     }
 }
 
+/// Creates a `RefreshReserve` instruction for a reserve priced by a token-swap
+/// oracle
+pub fn refresh_reserve_with_token_swap_oracle(
+    program_id: Pubkey,
+    reserve_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(reserve_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(swap_token_a_pubkey, false),
+        AccountMeta::new_readonly(swap_token_b_pubkey, false),
+    ];
+    Instruction {
+        program_id,
+        accounts,
+        data: LendingInstruction::RefreshReserve.pack(),
+    }
+}
+
 /// Creates a 'DepositReserveLiquidity' instruction.
 #[allow(clippy::too_many_arguments)]
 pub fn deposit_reserve_liquidity(
@@ -1000,6 +1304,47 @@ pub fn liquidate_obligation(
     }
 }
 
+/// Creates a `SelfLiquidateObligation` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn self_liquidate_obligation(
+    program_id: Pubkey,
+    liquidity_amount: u64,
+    source_liquidity_pubkey: Pubkey,
+    destination_collateral_pubkey: Pubkey,
+    repay_reserve_pubkey: Pubkey,
+    repay_reserve_liquidity_supply_pubkey: Pubkey,
+    withdraw_reserve_pubkey: Pubkey,
+    withdraw_reserve_collateral_supply_pubkey: Pubkey,
+    obligation_pubkey: Pubkey,
+    lending_market_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    obligation_owner_pubkey: Pubkey,
+) -> Instruction {
+    let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+        &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+        &program_id,
+    );
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(source_liquidity_pubkey, false),
+            AccountMeta::new(destination_collateral_pubkey, false),
+            AccountMeta::new(repay_reserve_pubkey, false),
+            AccountMeta::new(repay_reserve_liquidity_supply_pubkey, false),
+            AccountMeta::new_readonly(withdraw_reserve_pubkey, false),
+            AccountMeta::new(withdraw_reserve_collateral_supply_pubkey, false),
+            AccountMeta::new(obligation_pubkey, false),
+            AccountMeta::new_readonly(lending_market_pubkey, false),
+            AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+            AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+            AccountMeta::new_readonly(obligation_owner_pubkey, true),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: LendingInstruction::SelfLiquidateObligation { liquidity_amount }.pack(),
+    }
+}
+
 /// Creates a `FlashLoan` instruction.
 #[allow(clippy::too_many_arguments)]
 pub fn flash_loan(
@@ -1037,6 +1382,69 @@ pub fn flash_loan(
     }
 }
 
+/// Reserve lending liquidity in a `MultiReserveFlashLoan` instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlashLoanReserve {
+    /// Reserve account
+    pub reserve_pubkey: Pubkey,
+    /// Reserve liquidity supply
+    pub liquidity_supply_pubkey: Pubkey,
+    /// Token account receiving the lent liquidity
+    pub destination_liquidity_pubkey: Pubkey,
+    /// Reserve liquidity fee receiver
+    pub liquidity_fee_receiver_pubkey: Pubkey,
+    /// Host fee receiver
+    pub host_fee_receiver_pubkey: Pubkey,
+    /// Reserve liquidity mint
+    pub liquidity_mint_pubkey: Pubkey,
+    /// Token program of the reserve mints
+    pub token_program_id: Pubkey,
+    /// Amount to borrow - u64::MAX for up to 100% of available liquidity
+    pub liquidity_amount: u64,
+}
+
+/// Creates a `MultiReserveFlashLoan` instruction.
+pub fn multi_reserve_flash_loan(
+    program_id: Pubkey,
+    lending_market_pubkey: Pubkey,
+    flash_loan_receiver_program_id: Pubkey,
+    reserves: &[FlashLoanReserve],
+    flash_loan_receiver_program_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+        &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+        &program_id,
+    );
+    let mut accounts = vec![
+        AccountMeta::new_readonly(lending_market_pubkey, false),
+        AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+        AccountMeta::new_readonly(flash_loan_receiver_program_id, false),
+    ];
+    for reserve in reserves {
+        accounts.extend([
+            AccountMeta::new(reserve.reserve_pubkey, false),
+            AccountMeta::new(reserve.liquidity_supply_pubkey, false),
+            AccountMeta::new(reserve.destination_liquidity_pubkey, false),
+            AccountMeta::new(reserve.liquidity_fee_receiver_pubkey, false),
+            AccountMeta::new(reserve.host_fee_receiver_pubkey, false),
+            AccountMeta::new_readonly(reserve.liquidity_mint_pubkey, false),
+            AccountMeta::new_readonly(reserve.token_program_id, false),
+        ]);
+    }
+    accounts.extend(flash_loan_receiver_program_accounts);
+    Instruction {
+        program_id,
+        accounts,
+        data: LendingInstruction::MultiReserveFlashLoan {
+            liquidity_amounts: reserves
+                .iter()
+                .map(|reserve| reserve.liquidity_amount)
+                .collect(),
+        }
+        .pack(),
+    }
+}
+
 /// Creates a 'ModifyReserveConfig` instruction.
 #[allow(clippy::too_many_arguments)]
 pub fn modify_reserve_config(
@@ -1058,9 +1466,38 @@ pub fn modify_reserve_config(
     }
 }
 
+/// Adapts an instruction created by this module to a reserve whose mints are
+/// owned by `token_program_id`, such as the Token-2022 program, by replacing
+/// the token program accounts.
+///
+/// Token-2022 liquidity is transferred with `TransferChecked`, so the reserve
+/// liquidity mint must be provided to instructions moving liquidity in or out
+/// of a Token-2022 reserve, except `InitReserve` which already has it.
+/// Accounts required by a transfer hook of the mint can be appended to the
+/// returned instruction. When liquidating with reserves of different token
+/// programs, adapt the instruction to the repay reserve token program and
+/// append the withdraw reserve token program.
+pub fn with_token_program(
+    mut instruction: Instruction,
+    token_program_id: Pubkey,
+    liquidity_mint_pubkey: Option<Pubkey>,
+) -> Instruction {
+    for account in instruction.accounts.iter_mut() {
+        if account.pubkey == spl_token::id() {
+            account.pubkey = token_program_id;
+        }
+    }
+    if let Some(liquidity_mint_pubkey) = liquidity_mint_pubkey {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(liquidity_mint_pubkey, false));
+    }
+    instruction
+}
+
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {super::*, crate::state::RateCurvePoint};
 
     #[test]
     fn test_init_lending_market() {
@@ -1127,6 +1564,17 @@ mod tests {
                 flash_loan_fee_wad: 3,
                 host_fee_percentage: 1,
             },
+            oracle: ReserveOracleConfig {
+                max_staleness_slots: 10,
+                max_confidence_bps: 100,
+                twap_window_seconds: 300,
+            },
+            deposit_limit: 1_000_000,
+            borrow_limit: 800_000,
+            isolated: true,
+            rate_curve: RateCurve::empty(),
+            soft_liquidation_band: 5,
+            soft_liquidation_bonus: 2,
         };
         let source_liquidity_pubkey = Pubkey::new_unique();
         let destination_collateral_pubkey = Pubkey::new_unique();
@@ -1166,11 +1614,87 @@ mod tests {
             LendingInstruction::InitReserve {
                 liquidity_amount,
                 config,
+                oracle_kind: OracleKind::Pyth,
             }
             .pack()
         );
     }
 
+    #[test]
+    fn test_init_reserve_with_oracle() {
+        let program_id = Pubkey::new_unique();
+        let liquidity_amount = 1_000;
+        let config = ReserveConfig {
+            liquidation_threshold: 5,
+            oracle: ReserveOracleConfig {
+                max_confidence_bps: 50,
+                ..ReserveOracleConfig::default()
+            },
+            deposit_limit: 5_000,
+            isolated: true,
+            soft_liquidation_band: 10,
+            ..ReserveConfig::default()
+        };
+        let swap_token_a_pubkey = Pubkey::new_unique();
+        let swap_token_b_pubkey = Pubkey::new_unique();
+        let instruction = init_reserve_with_oracle(
+            program_id,
+            liquidity_amount,
+            config,
+            OracleKind::TokenSwapTwap,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Some((swap_token_a_pubkey, swap_token_b_pubkey)),
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 19);
+        assert_eq!(instruction.accounts[17].pubkey, swap_token_a_pubkey);
+        assert_eq!(instruction.accounts[18].pubkey, swap_token_b_pubkey);
+        let data = LendingInstruction::InitReserve {
+            liquidity_amount,
+            config,
+            oracle_kind: OracleKind::TokenSwapTwap,
+        }
+        .pack();
+        assert_eq!(instruction.data, data);
+        assert_eq!(
+            LendingInstruction::unpack(&data).unwrap(),
+            LendingInstruction::InitReserve {
+                liquidity_amount,
+                config,
+                oracle_kind: OracleKind::TokenSwapTwap,
+            }
+        );
+
+        // instructions packed before oracle configuration use the defaults
+        let legacy_len = 1 + 8 + 7 + 8 + 8 + 1;
+        assert_eq!(
+            LendingInstruction::unpack(&data[..legacy_len]).unwrap(),
+            LendingInstruction::InitReserve {
+                liquidity_amount,
+                config: ReserveConfig {
+                    oracle: ReserveOracleConfig::default(),
+                    deposit_limit: 0,
+                    isolated: false,
+                    soft_liquidation_band: 0,
+                    ..config
+                },
+                oracle_kind: OracleKind::Pyth,
+            }
+        );
+    }
+
     #[test]
     fn test_refresh_reserve() {
         let program_id = Pubkey::new_unique();
@@ -1183,6 +1707,26 @@ mod tests {
         assert_eq!(instruction.data, LendingInstruction::RefreshReserve.pack());
     }
 
+    #[test]
+    fn test_refresh_reserve_with_token_swap_oracle() {
+        let program_id = Pubkey::new_unique();
+        let reserve_pubkey = Pubkey::new_unique();
+        let swap_pubkey = Pubkey::new_unique();
+        let swap_token_a_pubkey = Pubkey::new_unique();
+        let swap_token_b_pubkey = Pubkey::new_unique();
+        let instruction = refresh_reserve_with_token_swap_oracle(
+            program_id,
+            reserve_pubkey,
+            swap_pubkey,
+            swap_token_a_pubkey,
+            swap_token_b_pubkey,
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 5);
+        assert_eq!(instruction.accounts[1].pubkey, swap_pubkey);
+        assert_eq!(instruction.data, LendingInstruction::RefreshReserve.pack());
+    }
+
     #[test]
     fn test_deposit_reserve_liquidity() {
         let program_id = Pubkey::new_unique();
@@ -1431,6 +1975,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_self_liquidate_obligation() {
+        let program_id = Pubkey::new_unique();
+        let liquidity_amount = 500;
+        let instruction = self_liquidate_obligation(
+            program_id,
+            liquidity_amount,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 13);
+        assert!(instruction.accounts[10].is_signer);
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::SelfLiquidateObligation { liquidity_amount }.pack()
+        );
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::SelfLiquidateObligation { liquidity_amount }
+        );
+    }
+
     #[test]
     fn test_flash_loan() {
         let program_id = Pubkey::new_unique();
@@ -1468,6 +2043,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_multi_reserve_flash_loan() {
+        let program_id = Pubkey::new_unique();
+        let lending_market_pubkey = Pubkey::new_unique();
+        let flash_loan_receiver_program_id = Pubkey::new_unique();
+        let reserves: Vec<FlashLoanReserve> = [1_000, u64::MAX]
+            .into_iter()
+            .map(|liquidity_amount| FlashLoanReserve {
+                reserve_pubkey: Pubkey::new_unique(),
+                liquidity_supply_pubkey: Pubkey::new_unique(),
+                destination_liquidity_pubkey: Pubkey::new_unique(),
+                liquidity_fee_receiver_pubkey: Pubkey::new_unique(),
+                host_fee_receiver_pubkey: Pubkey::new_unique(),
+                liquidity_mint_pubkey: Pubkey::new_unique(),
+                token_program_id: spl_token::id(),
+                liquidity_amount,
+            })
+            .collect();
+        let account_meta = AccountMeta::new_readonly(Pubkey::new_unique(), true);
+        let instruction = multi_reserve_flash_loan(
+            program_id,
+            lending_market_pubkey,
+            flash_loan_receiver_program_id,
+            &reserves,
+            vec![account_meta.clone()],
+        );
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts.len(), 18);
+        assert_eq!(instruction.accounts[10].pubkey, reserves[1].reserve_pubkey);
+        assert!(!instruction.accounts[15].is_writable);
+        assert_eq!(instruction.accounts[17], account_meta);
+        let liquidity_amounts = vec![1_000, u64::MAX];
+        assert_eq!(
+            instruction.data,
+            LendingInstruction::MultiReserveFlashLoan {
+                liquidity_amounts: liquidity_amounts.clone()
+            }
+            .pack()
+        );
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::MultiReserveFlashLoan { liquidity_amounts }
+        );
+
+        // no reserve
+        assert_eq!(
+            LendingInstruction::unpack(&[16, 0]),
+            Err(LendingError::InstructionUnpackError.into())
+        );
+        // missing amount
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data[..instruction.data.len() - 8]),
+            Err(LendingError::InstructionUnpackError.into())
+        );
+        // too many reserves
+        let mut data = vec![16, MAX_FLASH_LOAN_RESERVES as u8 + 1];
+        for _ in 0..=MAX_FLASH_LOAN_RESERVES {
+            data.extend_from_slice(&1u64.to_le_bytes());
+        }
+        assert_eq!(
+            LendingInstruction::unpack(&data),
+            Err(LendingError::InstructionUnpackError.into())
+        );
+    }
+
     #[test]
     fn test_modify_reserve_config() {
         let program_id = Pubkey::new_unique();
@@ -1484,6 +2124,31 @@ mod tests {
                 flash_loan_fee_wad: 3,
                 host_fee_percentage: 1,
             },
+            oracle: ReserveOracleConfig {
+                max_staleness_slots: 10,
+                max_confidence_bps: 100,
+                twap_window_seconds: 300,
+            },
+            deposit_limit: 1_000_000,
+            borrow_limit: 800_000,
+            isolated: true,
+            rate_curve: RateCurve::new(&[
+                RateCurvePoint {
+                    utilization_bps: 0,
+                    borrow_rate_bps: 0,
+                },
+                RateCurvePoint {
+                    utilization_bps: 9_000,
+                    borrow_rate_bps: 800,
+                },
+                RateCurvePoint {
+                    utilization_bps: 10_000,
+                    borrow_rate_bps: 15_000,
+                },
+            ])
+            .unwrap(),
+            soft_liquidation_band: 10,
+            soft_liquidation_bonus: 4,
         };
         let reserve_pubkey = Pubkey::new_unique();
         let lending_market_pubkey = Pubkey::new_unique();
@@ -1501,5 +2166,49 @@ mod tests {
             instruction.data,
             LendingInstruction::ModifyReserveConfig { new_config: config }.pack()
         );
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data).unwrap(),
+            LendingInstruction::ModifyReserveConfig { new_config: config }
+        );
+
+        // instructions packed before soft liquidation disable it
+        let legacy_len = instruction.data.len() - 2;
+        assert_eq!(
+            LendingInstruction::unpack(&instruction.data[..legacy_len]).unwrap(),
+            LendingInstruction::ModifyReserveConfig {
+                new_config: ReserveConfig {
+                    soft_liquidation_band: 0,
+                    soft_liquidation_bonus: 0,
+                    ..config
+                }
+            }
+        );
+    }
+    #[test]
+    fn test_with_token_program() {
+        let program_id = Pubkey::new_unique();
+        let liquidity_mint_pubkey = Pubkey::new_unique();
+        let instruction = with_token_program(
+            repay_obligation_liquidity(
+                program_id,
+                1_000,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            ),
+            spl_token_2022::id(),
+            Some(liquidity_mint_pubkey),
+        );
+        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(instruction.accounts[7].pubkey, spl_token_2022::id());
+        assert_eq!(instruction.accounts[8].pubkey, liquidity_mint_pubkey);
+        assert!(!instruction.accounts[8].is_writable);
+        assert!(instruction
+            .accounts
+            .iter()
+            .all(|account| account.pubkey != spl_token::id()));
     }
 }
@@ -0,0 +1,464 @@
+//! Price oracle adapters for reserves
+
+use {
+    crate::{
+        error::LendingError,
+        math::{Decimal, TryDiv, TryMul, TrySub},
+        pyth,
+        state::ReserveOracleConfig,
+        switchboard,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::{Clock, Slot, UnixTimestamp},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    spl_token_2022::{extension::StateWithExtensions, state::Account},
+    spl_token_swap::{error::SwapError, oracle::Observation, state::SwapVersion},
+    std::convert::{TryFrom, TryInto},
+};
+
+/// Number of slots after which an oracle price is stale, unless configured on
+/// the reserve
+pub const DEFAULT_MAX_STALENESS_SLOTS: u64 = 5;
+
+/// Minimum number of seconds over which a token-swap price is averaged, unless
+/// configured on the reserve
+pub const DEFAULT_TWAP_WINDOW_SECONDS: u32 = 600;
+
+/// Source of the market price of a reserve
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OracleKind {
+    /// Pyth price account, with its product account
+    #[default]
+    Pyth,
+    /// Switchboard V2 style aggregator account
+    Switchboard,
+    /// Time-weighted average price of a token-swap pool
+    TokenSwapTwap,
+}
+
+impl TryFrom<u8> for OracleKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OracleKind::Pyth),
+            1 => Ok(OracleKind::Switchboard),
+            2 => Ok(OracleKind::TokenSwapTwap),
+            _ => Err(LendingError::InvalidOracleConfig.into()),
+        }
+    }
+}
+
+/// Price read from an oracle
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OraclePrice {
+    /// Price in quote currency
+    pub price: Decimal,
+    /// Uncertainty of the price, in quote currency
+    pub confidence: Decimal,
+    /// Slot at which the price was published
+    pub publish_slot: Slot,
+}
+
+impl OraclePrice {
+    /// Check that the price is fresh and precise enough for the reserve
+    pub fn validate(&self, config: &ReserveOracleConfig, clock: &Clock) -> ProgramResult {
+        let slots_elapsed = clock
+            .slot
+            .checked_sub(self.publish_slot)
+            .ok_or(LendingError::MathOverflow)?;
+        if slots_elapsed >= config.max_staleness_slots() {
+            msg!("Oracle price is stale");
+            return Err(LendingError::InvalidOracleConfig.into());
+        }
+
+        if config.max_confidence_bps > 0
+            && self.confidence.try_mul(10_000)?
+                > self.price.try_mul(config.max_confidence_bps as u64)?
+        {
+            msg!("Oracle price confidence interval is too wide");
+            return Err(LendingError::InvalidOracleConfig.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the quote currency of a Pyth product account
+pub fn get_pyth_product_quote_currency(
+    pyth_product: &pyth::Product,
+) -> Result<[u8; 32], ProgramError> {
+    const LEN: usize = 14;
+    const KEY: &[u8; LEN] = b"quote_currency";
+
+    let mut start = 0;
+    while start < pyth::PROD_ATTR_SIZE {
+        let mut length = pyth_product.attr[start] as usize;
+        start += 1;
+
+        if length == LEN {
+            let mut end = start + length;
+            if end > pyth::PROD_ATTR_SIZE {
+                msg!("Pyth product attribute key length too long");
+                return Err(LendingError::InvalidOracleConfig.into());
+            }
+
+            let key = &pyth_product.attr[start..end];
+            if key == KEY {
+                start += length;
+                length = pyth_product.attr[start] as usize;
+                start += 1;
+
+                end = start + length;
+                if length > 32 || end > pyth::PROD_ATTR_SIZE {
+                    msg!("Pyth product quote currency value too long");
+                    return Err(LendingError::InvalidOracleConfig.into());
+                }
+
+                let mut value = [0u8; 32];
+                value[0..length].copy_from_slice(&pyth_product.attr[start..end]);
+                return Ok(value);
+            }
+        }
+
+        start += length;
+        start += 1 + pyth_product.attr[start] as usize;
+    }
+
+    msg!("Pyth product quote currency not found");
+    Err(LendingError::InvalidOracleConfig.into())
+}
+
+/// Read the aggregate price of a Pyth price account
+pub fn get_pyth_price(pyth_price_info: &AccountInfo) -> Result<OraclePrice, ProgramError> {
+    let pyth_price_data = pyth_price_info.try_borrow_data()?;
+    let pyth_price = pyth::load::<pyth::Price>(&pyth_price_data)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    if pyth_price.ptype != pyth::PriceType::Price {
+        msg!("Oracle price type is invalid");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+
+    if pyth_price.agg.status != pyth::PriceStatus::Trading {
+        msg!("Oracle price status is invalid");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+
+    let price: u64 = pyth_price.agg.price.try_into().map_err(|_| {
+        msg!("Oracle price cannot be negative");
+        LendingError::InvalidOracleConfig
+    })?;
+
+    Ok(OraclePrice {
+        price: scale_by_exponent(price, pyth_price.expo)?,
+        confidence: scale_by_exponent(pyth_price.agg.conf, pyth_price.expo)?,
+        publish_slot: pyth_price.valid_slot,
+    })
+}
+
+fn scale_by_exponent(value: u64, exponent: i32) -> Result<Decimal, ProgramError> {
+    if exponent >= 0 {
+        let exponent = exponent
+            .try_into()
+            .map_err(|_| LendingError::MathOverflow)?;
+        let zeros = 10u64
+            .checked_pow(exponent)
+            .ok_or(LendingError::MathOverflow)?;
+        Decimal::from(value).try_mul(zeros)
+    } else {
+        let exponent = exponent
+            .checked_abs()
+            .ok_or(LendingError::MathOverflow)?
+            .try_into()
+            .map_err(|_| LendingError::MathOverflow)?;
+        let decimals = 10u64
+            .checked_pow(exponent)
+            .ok_or(LendingError::MathOverflow)?;
+        Decimal::from(value).try_div(decimals)
+    }
+}
+
+/// Read the latest confirmed result of a Switchboard aggregator account
+pub fn get_switchboard_price(aggregator_info: &AccountInfo) -> Result<OraclePrice, ProgramError> {
+    let aggregator = switchboard::Aggregator::unpack(&aggregator_info.try_borrow_data()?)?;
+
+    if aggregator.num_success < aggregator.min_oracle_results.max(1) {
+        msg!("Oracle round does not have enough oracle results");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+
+    Ok(OraclePrice {
+        price: switchboard_decimal_to_decimal(aggregator.result)?,
+        confidence: switchboard_decimal_to_decimal(aggregator.std_deviation)?,
+        publish_slot: aggregator.round_open_slot,
+    })
+}
+
+fn switchboard_decimal_to_decimal(
+    value: switchboard::SwitchboardDecimal,
+) -> Result<Decimal, ProgramError> {
+    let mantissa: u128 = value.mantissa.try_into().map_err(|_| {
+        msg!("Oracle price cannot be negative");
+        LendingError::InvalidOracleConfig
+    })?;
+    let decimals = 10u64
+        .checked_pow(value.scale)
+        .ok_or(LendingError::MathOverflow)?;
+    Decimal::from(mantissa).try_div(decimals)
+}
+
+/// Price of a token in a token-swap pool, observed at the current time
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TokenSwapObservation {
+    /// Mint of the other token of the pool, in which the price is quoted
+    pub quote_mint: Pubkey,
+    /// Current price, as a fixed-point ratio of raw token amounts
+    pub spot_price: u128,
+    /// Sum of the price for each second, as a fixed-point ratio of raw token
+    /// amounts
+    pub price_cumulative: u128,
+    /// Timestamp of the observation
+    pub timestamp: UnixTimestamp,
+}
+
+/// Observe the price of `base_mint` in a token-swap pool, given the pool token
+/// accounts
+pub fn observe_token_swap(
+    swap_info: &AccountInfo,
+    swap_token_a_info: &AccountInfo,
+    swap_token_b_info: &AccountInfo,
+    base_mint: &Pubkey,
+    clock: &Clock,
+) -> Result<TokenSwapObservation, ProgramError> {
+    if swap_info.owner != &spl_token_swap::id() {
+        msg!("Token swap account provided is not owned by the token swap program");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+    // swaps created before the price oracle existed have no accumulators
+    let swap = match SwapVersion::unpack_latest(&swap_info.try_borrow_data()?) {
+        Ok(swap) => swap,
+        Err(err) if err == SwapError::UnsupportedSwapVersion.into() => {
+            msg!("Token swap version does not support the price oracle");
+            return Err(LendingError::InvalidOracleConfig.into());
+        }
+        Err(err) => return Err(err),
+    };
+    if swap.token_a != *swap_token_a_info.key || swap.token_b != *swap_token_b_info.key {
+        msg!("Token swap token accounts do not match the token accounts provided");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+    let token_a_amount =
+        StateWithExtensions::<Account>::unpack(&swap_token_a_info.try_borrow_data()?)?
+            .base
+            .amount;
+    let token_b_amount =
+        StateWithExtensions::<Account>::unpack(&swap_token_b_info.try_borrow_data()?)?
+            .base
+            .amount;
+
+    let observation = Observation::from_swap_state(&swap).accumulate(
+        token_a_amount,
+        token_b_amount,
+        clock.slot,
+        clock.unix_timestamp,
+    );
+    let (quote_mint, base_amount, quote_amount, price_cumulative) =
+        if swap.token_a_mint == *base_mint {
+            (
+                swap.token_b_mint,
+                token_a_amount,
+                token_b_amount,
+                observation.price_a_cumulative,
+            )
+        } else if swap.token_b_mint == *base_mint {
+            (
+                swap.token_a_mint,
+                token_b_amount,
+                token_a_amount,
+                observation.price_b_cumulative,
+            )
+        } else {
+            msg!("Token swap does not trade the reserve liquidity mint");
+            return Err(LendingError::InvalidOracleConfig.into());
+        };
+
+    let spot_price =
+        spl_token_swap::oracle::spot_price(base_amount, quote_amount).ok_or_else(|| {
+            msg!("Token swap pool is empty");
+            LendingError::InvalidOracleConfig
+        })?;
+
+    Ok(TokenSwapObservation {
+        quote_mint,
+        spot_price,
+        price_cumulative,
+        timestamp: observation.timestamp,
+    })
+}
+
+/// Convert a token-swap fixed-point ratio of raw token amounts into the price
+/// of a whole base token in whole quote tokens
+pub fn token_swap_price_to_decimal(
+    price: u128,
+    base_decimals: u8,
+    quote_decimals: u8,
+) -> Result<Decimal, ProgramError> {
+    // divide in two steps since 2^64 does not fit in a u64
+    let half_fractional_bits = spl_token_swap::oracle::PRICE_FRACTIONAL_BITS / 2;
+    Decimal::from(price)
+        .try_div(1u64 << half_fractional_bits)?
+        .try_div(1u64 << half_fractional_bits)?
+        .try_mul(
+            10u64
+                .checked_pow(base_decimals as u32)
+                .ok_or(LendingError::MathOverflow)?,
+        )?
+        .try_div(
+            10u64
+                .checked_pow(quote_decimals as u32)
+                .ok_or(LendingError::MathOverflow)?,
+        )
+}
+
+/// Distance between two prices, used as the confidence interval of a
+/// time-weighted average price relative to the spot price
+pub fn price_deviation(a: Decimal, b: Decimal) -> Result<Decimal, ProgramError> {
+    if a > b {
+        a.try_sub(b)
+    } else {
+        b.try_sub(a)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn clock(slot: Slot) -> Clock {
+        Clock {
+            slot,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn oracle_kind_from_u8() {
+        assert_eq!(OracleKind::try_from(0).unwrap(), OracleKind::Pyth);
+        assert_eq!(OracleKind::try_from(1).unwrap(), OracleKind::Switchboard);
+        assert_eq!(OracleKind::try_from(2).unwrap(), OracleKind::TokenSwapTwap);
+        assert_eq!(
+            OracleKind::try_from(3),
+            Err(LendingError::InvalidOracleConfig.into())
+        );
+    }
+
+    #[test]
+    fn validate_staleness() {
+        let price = OraclePrice {
+            price: Decimal::from(10u64),
+            confidence: Decimal::zero(),
+            publish_slot: 100,
+        };
+        let config = ReserveOracleConfig::default();
+        assert_eq!(price.validate(&config, &clock(104)), Ok(()));
+        assert_eq!(
+            price.validate(&config, &clock(105)),
+            Err(LendingError::InvalidOracleConfig.into())
+        );
+        assert_eq!(
+            price.validate(&config, &clock(99)),
+            Err(LendingError::MathOverflow.into())
+        );
+
+        let config = ReserveOracleConfig {
+            max_staleness_slots: 50,
+            ..ReserveOracleConfig::default()
+        };
+        assert_eq!(price.validate(&config, &clock(149)), Ok(()));
+        assert_eq!(
+            price.validate(&config, &clock(150)),
+            Err(LendingError::InvalidOracleConfig.into())
+        );
+    }
+
+    #[test]
+    fn validate_confidence() {
+        let price = OraclePrice {
+            price: Decimal::from(100u64),
+            confidence: Decimal::from(2u64),
+            publish_slot: 0,
+        };
+        let config = ReserveOracleConfig::default();
+        assert_eq!(price.validate(&config, &clock(0)), Ok(()));
+
+        let config = ReserveOracleConfig {
+            max_confidence_bps: 200,
+            ..ReserveOracleConfig::default()
+        };
+        assert_eq!(price.validate(&config, &clock(0)), Ok(()));
+
+        let config = ReserveOracleConfig {
+            max_confidence_bps: 199,
+            ..ReserveOracleConfig::default()
+        };
+        assert_eq!(
+            price.validate(&config, &clock(0)),
+            Err(LendingError::InvalidOracleConfig.into())
+        );
+    }
+
+    #[test]
+    fn switchboard_decimal() {
+        let value = switchboard::SwitchboardDecimal {
+            mantissa: 21_345,
+            scale: 3,
+        };
+        assert_eq!(
+            switchboard_decimal_to_decimal(value).unwrap(),
+            Decimal::from(21_345u64).try_div(1_000).unwrap()
+        );
+        let value = switchboard::SwitchboardDecimal {
+            mantissa: -1,
+            scale: 0,
+        };
+        assert_eq!(
+            switchboard_decimal_to_decimal(value),
+            Err(LendingError::InvalidOracleConfig.into())
+        );
+    }
+
+    #[test]
+    fn token_swap_price() {
+        let one = 1u128 << spl_token_swap::oracle::PRICE_FRACTIONAL_BITS;
+        // 1 raw base token is worth 2.5 raw quote tokens
+        let price = one * 5 / 2;
+        assert_eq!(
+            token_swap_price_to_decimal(price, 0, 0).unwrap(),
+            Decimal::from(5u64).try_div(2).unwrap()
+        );
+        // 9 base decimals, 6 quote decimals: 1 base token is 2500 quote tokens
+        assert_eq!(
+            token_swap_price_to_decimal(price, 9, 6).unwrap(),
+            Decimal::from(2_500u64)
+        );
+        // 6 base decimals, 9 quote decimals: 1 base token is 0.0025 quote tokens
+        assert_eq!(
+            token_swap_price_to_decimal(price, 6, 9).unwrap(),
+            Decimal::from(25u64).try_div(10_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn deviation() {
+        let a = Decimal::from(3u64);
+        let b = Decimal::from(5u64);
+        assert_eq!(price_deviation(a, b).unwrap(), Decimal::from(2u64));
+        assert_eq!(price_deviation(b, a).unwrap(), Decimal::from(2u64));
+    }
+}
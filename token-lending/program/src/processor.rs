@@ -5,6 +5,11 @@ use {
         error::LendingError,
         instruction::LendingInstruction,
         math::{Decimal, Rate, TryAdd, TryDiv, TryMul},
+        oracle::{
+            get_pyth_price, get_pyth_product_quote_currency, get_switchboard_price,
+            observe_token_swap, price_deviation, token_swap_price_to_decimal, OracleKind,
+            OraclePrice,
+        },
         pyth,
         state::{
             CalculateBorrowResult, CalculateLiquidationResult, CalculateRepayResult,
@@ -16,6 +21,7 @@ use {
     num_traits::FromPrimitive,
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Epoch,
         decode_error::DecodeError,
         entrypoint::ProgramResult,
         instruction::Instruction,
@@ -26,8 +32,16 @@ use {
         pubkey::Pubkey,
         sysvar::{clock::Clock, rent::Rent, Sysvar},
     },
-    spl_token::{
-        solana_program::instruction::AccountMeta,
+    spl_flash_loan_receiver_interface::instruction::{
+        receive_flash_loan, FlashLoanAccounts, FlashLoanAmount,
+    },
+    spl_token::solana_program::instruction::AccountMeta,
+    spl_token_2022::{
+        check_spl_token_program_account,
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+            StateWithExtensions,
+        },
         state::{Account, Mint},
     },
     std::convert::TryInto,
@@ -55,9 +69,10 @@ pub fn process_instruction(
         LendingInstruction::InitReserve {
             liquidity_amount,
             config,
+            oracle_kind,
         } => {
             msg!("Instruction: Init Reserve");
-            process_init_reserve(program_id, liquidity_amount, config, accounts)
+            process_init_reserve(program_id, liquidity_amount, config, oracle_kind, accounts)
         }
         LendingInstruction::RefreshReserve => {
             msg!("Instruction: Refresh Reserve");
@@ -105,7 +120,7 @@ pub fn process_instruction(
         }
         LendingInstruction::LiquidateObligation { liquidity_amount } => {
             msg!("Instruction: Liquidate Obligation");
-            process_liquidate_obligation(program_id, liquidity_amount, accounts)
+            process_liquidate_obligation(program_id, liquidity_amount, false, accounts)
         }
         LendingInstruction::FlashLoan { amount } => {
             msg!("Instruction: Flash Loan");
@@ -115,6 +130,14 @@ pub fn process_instruction(
             msg!("Instruction: Modify Reserve Config");
             process_modify_reserve_config(program_id, new_config, accounts)
         }
+        LendingInstruction::SelfLiquidateObligation { liquidity_amount } => {
+            msg!("Instruction: Self Liquidate Obligation");
+            process_liquidate_obligation(program_id, liquidity_amount, true, accounts)
+        }
+        LendingInstruction::MultiReserveFlashLoan { liquidity_amounts } => {
+            msg!("Instruction: Multi-Reserve Flash Loan");
+            process_multi_reserve_flash_loan(program_id, liquidity_amounts, accounts)
+        }
     }
 }
 
@@ -183,6 +206,7 @@ fn process_init_reserve(
     program_id: &Pubkey,
     liquidity_amount: u64,
     config: ReserveConfig,
+    oracle_kind: OracleKind,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     if liquidity_amount == 0 {
@@ -192,7 +216,7 @@ fn process_init_reserve(
 
     config.validate()?;
 
-    let account_info_iter = &mut accounts.iter().peekable();
+    let account_info_iter = &mut accounts.iter();
     let source_liquidity_info = next_account_info(account_info_iter)?;
     let destination_collateral_info = next_account_info(account_info_iter)?;
     let reserve_info = next_account_info(account_info_iter)?;
@@ -201,8 +225,8 @@ fn process_init_reserve(
     let reserve_liquidity_fee_receiver_info = next_account_info(account_info_iter)?;
     let reserve_collateral_mint_info = next_account_info(account_info_iter)?;
     let reserve_collateral_supply_info = next_account_info(account_info_iter)?;
-    let pyth_product_info = next_account_info(account_info_iter)?;
-    let pyth_price_info = next_account_info(account_info_iter)?;
+    let oracle_product_info = next_account_info(account_info_iter)?;
+    let oracle_price_info = next_account_info(account_info_iter)?;
     let lending_market_info = next_account_info(account_info_iter)?;
     let lending_market_authority_info = next_account_info(account_info_iter)?;
     let lending_market_owner_info = next_account_info(account_info_iter)?;
@@ -229,8 +253,8 @@ fn process_init_reserve(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
+    if check_spl_token_program_account(token_program_id.key).is_err() {
+        msg!("Token program provided is not a supported token program");
         return Err(LendingError::InvalidTokenProgram.into());
     }
     if &lending_market.owner != lending_market_owner_info.key {
@@ -242,48 +266,61 @@ fn process_init_reserve(
         return Err(LendingError::InvalidSigner.into());
     }
 
-    if &lending_market.oracle_program_id != pyth_product_info.owner {
-        msg!("Pyth product account provided is not owned by the lending market oracle program");
-        return Err(LendingError::InvalidOracleConfig.into());
-    }
-    if &lending_market.oracle_program_id != pyth_price_info.owner {
-        msg!("Pyth price account provided is not owned by the lending market oracle program");
-        return Err(LendingError::InvalidOracleConfig.into());
-    }
-
-    let pyth_product_data = pyth_product_info.try_borrow_data()?;
-    let pyth_product = pyth::load::<pyth::Product>(&pyth_product_data)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
-    if pyth_product.magic != pyth::MAGIC {
-        msg!("Pyth product account provided is not a valid Pyth account");
-        return Err(LendingError::InvalidOracleConfig.into());
-    }
-    if pyth_product.ver != pyth::VERSION_2 {
-        msg!("Pyth product account provided has a different version than expected");
-        return Err(LendingError::InvalidOracleConfig.into());
-    }
-    if pyth_product.atype != pyth::AccountType::Product as u32 {
-        msg!("Pyth product account provided is not a valid Pyth product account");
-        return Err(LendingError::InvalidOracleConfig.into());
-    }
-
-    let pyth_price_pubkey_bytes: &[u8; 32] = pyth_price_info
-        .key
-        .as_ref()
-        .try_into()
-        .map_err(|_| LendingError::InvalidAccountInput)?;
-    if &pyth_product.px_acc.val != pyth_price_pubkey_bytes {
-        msg!("Pyth product price account does not match the Pyth price provided");
-        return Err(LendingError::InvalidOracleConfig.into());
-    }
-
-    let quote_currency = get_pyth_product_quote_currency(pyth_product)?;
-    if lending_market.quote_currency != quote_currency {
-        msg!("Lending market quote currency does not match the oracle quote currency");
-        return Err(LendingError::InvalidOracleConfig.into());
+    let reserve_liquidity_mint = unpack_mint(&reserve_liquidity_mint_info.data.borrow())?;
+    if reserve_liquidity_mint_info.owner != token_program_id.key {
+        msg!("Reserve liquidity mint is not owned by the token program provided");
+        return Err(LendingError::InvalidTokenOwner.into());
     }
-
-    let market_price = get_pyth_price(pyth_price_info, clock)?;
+    check_liquidity_mint_extensions(reserve_liquidity_mint_info)?;
+
+    let mut oracle_quote_decimals = 0;
+    let mut oracle_twap_cumulative = 0;
+    let mut oracle_twap_timestamp = 0;
+    let market_price = match oracle_kind {
+        OracleKind::Pyth => {
+            check_pyth_oracle(&lending_market, oracle_product_info, oracle_price_info)?;
+            let price = get_pyth_price(oracle_price_info)?;
+            price.validate(&config.oracle, clock)?;
+            price.price
+        }
+        OracleKind::Switchboard => {
+            // aggregators do not record their quote currency, the lending market
+            // owner is trusted to provide one quoted in the market currency
+            if oracle_product_info.key != oracle_price_info.owner {
+                msg!("Switchboard aggregator provided is not owned by the Switchboard program provided");
+                return Err(LendingError::InvalidOracleConfig.into());
+            }
+            let price = get_switchboard_price(oracle_price_info)?;
+            price.validate(&config.oracle, clock)?;
+            price.price
+        }
+        OracleKind::TokenSwapTwap => {
+            config.oracle.validate_for(oracle_kind)?;
+            let swap_token_a_info = next_account_info(account_info_iter)?;
+            let swap_token_b_info = next_account_info(account_info_iter)?;
+            if lending_market.quote_currency != oracle_product_info.key.to_bytes() {
+                msg!("Lending market quote currency does not match the oracle quote mint");
+                return Err(LendingError::InvalidOracleConfig.into());
+            }
+            let observation = observe_token_swap(
+                oracle_price_info,
+                swap_token_a_info,
+                swap_token_b_info,
+                reserve_liquidity_mint_info.key,
+                clock,
+            )?;
+            if &observation.quote_mint != oracle_product_info.key {
+                msg!("Token swap does not trade the reserve liquidity against the quote mint");
+                return Err(LendingError::InvalidOracleConfig.into());
+            }
+            oracle_quote_decimals = unpack_mint(&oracle_product_info.data.borrow())?.decimals;
+            oracle_twap_cumulative = observation.price_cumulative;
+            oracle_twap_timestamp = observation.timestamp;
+            // the spot price can be moved within a single transaction, so the
+            // reserve stays unpriced until a full window has been averaged
+            Decimal::zero()
+        }
+    };
 
     let authority_signer_seeds = &[
         lending_market_info.key.as_ref(),
@@ -298,10 +335,20 @@ fn process_init_reserve(
         return Err(LendingError::InvalidMarketAuthority.into());
     }
 
-    let reserve_liquidity_mint = unpack_mint(&reserve_liquidity_mint_info.data.borrow())?;
-    if reserve_liquidity_mint_info.owner != token_program_id.key {
-        msg!("Reserve liquidity mint is not owned by the token program provided");
-        return Err(LendingError::InvalidTokenOwner.into());
+    let liquidity_mint = if *token_program_id.key == spl_token_2022::id() {
+        Some(LiquidityMint {
+            info: reserve_liquidity_mint_info,
+            decimals: reserve_liquidity_mint.decimals,
+            additional_accounts: account_info_iter.as_slice(),
+        })
+    } else {
+        None
+    };
+    let received_amount =
+        amount_after_transfer_fee(liquidity_mint.as_ref(), clock.epoch, liquidity_amount)?;
+    if received_amount == 0 {
+        msg!("Reserve must be initialized with liquidity after transfer fees");
+        return Err(LendingError::InvalidAmount.into());
     }
 
     reserve.init(InitReserveParams {
@@ -312,17 +359,22 @@ fn process_init_reserve(
             mint_decimals: reserve_liquidity_mint.decimals,
             supply_pubkey: *reserve_liquidity_supply_info.key,
             fee_receiver: *reserve_liquidity_fee_receiver_info.key,
-            oracle_pubkey: *pyth_price_info.key,
+            oracle_pubkey: *oracle_price_info.key,
             market_price,
+            oracle_kind,
+            oracle_quote_decimals,
         }),
         collateral: ReserveCollateral::new(NewReserveCollateralParams {
             mint_pubkey: *reserve_collateral_mint_info.key,
             supply_pubkey: *reserve_collateral_supply_info.key,
         }),
         config,
+        token_program_id: *token_program_id.key,
     });
+    reserve.liquidity.oracle_twap_cumulative = oracle_twap_cumulative;
+    reserve.liquidity.oracle_twap_timestamp = oracle_twap_timestamp;
 
-    let collateral_amount = reserve.deposit_liquidity(liquidity_amount)?;
+    let collateral_amount = reserve.deposit_liquidity(received_amount)?;
     Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
 
     spl_token_init_account(TokenInitializeAccountParams {
@@ -372,6 +424,7 @@ fn process_init_reserve(
         authority: user_transfer_authority_info.clone(),
         authority_signer_seeds: &[],
         token_program: token_program_id.clone(),
+        liquidity_mint: liquidity_mint.as_ref(),
     })?;
 
     spl_token_mint_to(TokenMintToParams {
@@ -402,7 +455,29 @@ fn process_refresh_reserve(program_id: &Pubkey, accounts: &[AccountInfo]) -> Pro
         return Err(LendingError::InvalidAccountInput.into());
     }
 
-    reserve.liquidity.market_price = get_pyth_price(reserve_liquidity_oracle_info, clock)?;
+    match reserve.liquidity.oracle_kind {
+        OracleKind::Pyth => {
+            let price = get_pyth_price(reserve_liquidity_oracle_info)?;
+            price.validate(&reserve.config.oracle, clock)?;
+            reserve.liquidity.market_price = price.price;
+        }
+        OracleKind::Switchboard => {
+            let price = get_switchboard_price(reserve_liquidity_oracle_info)?;
+            price.validate(&reserve.config.oracle, clock)?;
+            reserve.liquidity.market_price = price.price;
+        }
+        OracleKind::TokenSwapTwap => {
+            let swap_token_a_info = next_account_info(account_info_iter)?;
+            let swap_token_b_info = next_account_info(account_info_iter)?;
+            refresh_token_swap_price(
+                &mut reserve,
+                reserve_liquidity_oracle_info,
+                swap_token_a_info,
+                swap_token_b_info,
+                clock,
+            )?;
+        }
+    }
 
     reserve.accrue_interest(clock.slot)?;
     reserve.last_update.update_slot(clock.slot);
@@ -438,10 +513,6 @@ fn process_deposit_reserve_liquidity(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
-        return Err(LendingError::InvalidTokenProgram.into());
-    }
 
     let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
     if reserve_info.owner != program_id {
@@ -472,6 +543,9 @@ fn process_deposit_reserve_liquidity(
         msg!("Reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    check_reserve_token_program(&reserve, &lending_market, token_program_id)?;
+    let liquidity_mint =
+        find_liquidity_mint(&reserve, token_program_id.key, account_info_iter.as_slice())?;
 
     let authority_signer_seeds = &[
         lending_market_info.key.as_ref(),
@@ -488,7 +562,15 @@ fn process_deposit_reserve_liquidity(
         return Err(LendingError::InvalidMarketAuthority.into());
     }
 
-    let collateral_amount = reserve.deposit_liquidity(liquidity_amount)?;
+    let received_amount =
+        amount_after_transfer_fee(liquidity_mint.as_ref(), clock.epoch, liquidity_amount)?;
+    if received_amount == 0 {
+        msg!("Liquidity amount is too small to deposit after transfer fees");
+        return Err(LendingError::InvalidAmount.into());
+    }
+
+    reserve.check_deposit_limit(received_amount)?;
+    let collateral_amount = reserve.deposit_liquidity(received_amount)?;
     reserve.last_update.mark_stale();
     Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
 
@@ -499,6 +581,7 @@ fn process_deposit_reserve_liquidity(
         authority: user_transfer_authority_info.clone(),
         authority_signer_seeds: &[],
         token_program: token_program_id.clone(),
+        liquidity_mint: liquidity_mint.as_ref(),
     })?;
 
     spl_token_mint_to(TokenMintToParams {
@@ -540,10 +623,6 @@ fn process_redeem_reserve_collateral(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
-        return Err(LendingError::InvalidTokenProgram.into());
-    }
 
     let mut reserve = Reserve::unpack(&reserve_info.data.borrow())?;
     if reserve_info.owner != program_id {
@@ -574,6 +653,9 @@ fn process_redeem_reserve_collateral(
         msg!("Reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    check_reserve_token_program(&reserve, &lending_market, token_program_id)?;
+    let liquidity_mint =
+        find_liquidity_mint(&reserve, token_program_id.key, account_info_iter.as_slice())?;
 
     let authority_signer_seeds = &[
         lending_market_info.key.as_ref(),
@@ -608,6 +690,7 @@ fn process_redeem_reserve_collateral(
         authority: lending_market_authority_info.clone(),
         authority_signer_seeds,
         token_program: token_program_id.clone(),
+        liquidity_mint: liquidity_mint.as_ref(),
     })?;
 
     Ok(())
@@ -672,6 +755,7 @@ fn process_refresh_obligation(program_id: &Pubkey, accounts: &[AccountInfo]) ->
     let mut borrowed_value = Decimal::zero();
     let mut allowed_borrow_value = Decimal::zero();
     let mut unhealthy_borrow_value = Decimal::zero();
+    let has_other_collateral = obligation.deposits.len() > 1;
 
     for (index, collateral) in obligation.deposits.iter_mut().enumerate() {
         let deposit_reserve_info = next_account_info(account_info_iter)?;
@@ -716,8 +800,18 @@ fn process_refresh_obligation(program_id: &Pubkey, accounts: &[AccountInfo]) ->
             Rate::from_percent(deposit_reserve.config.liquidation_threshold);
 
         deposited_value = deposited_value.try_add(market_value)?;
-        allowed_borrow_value =
-            allowed_borrow_value.try_add(market_value.try_mul(loan_to_value_rate)?)?;
+        // isolated collateral combined with other collateral, deposited after
+        // it or before the reserve was isolated, no longer allows new borrows
+        // but still counts towards the liquidation threshold
+        if deposit_reserve.config.isolated && has_other_collateral {
+            msg!(
+                "Deposit reserve provided for collateral {} is isolated and cannot be borrowed against with other collateral",
+                index
+            );
+        } else {
+            allowed_borrow_value =
+                allowed_borrow_value.try_add(market_value.try_mul(loan_to_value_rate)?)?;
+        }
         unhealthy_borrow_value =
             unhealthy_borrow_value.try_add(market_value.try_mul(liquidation_threshold_rate)?)?;
     }
@@ -807,10 +901,6 @@ fn process_deposit_obligation_collateral(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
-        return Err(LendingError::InvalidTokenProgram.into());
-    }
 
     let deposit_reserve = Reserve::unpack(&deposit_reserve_info.data.borrow())?;
     if deposit_reserve_info.owner != program_id {
@@ -839,6 +929,7 @@ fn process_deposit_obligation_collateral(
         msg!("Deposit reserve has collateral disabled for borrowing");
         return Err(LendingError::ReserveCollateralDisabled.into());
     }
+    check_reserve_token_program(&deposit_reserve, &lending_market, token_program_id)?;
 
     let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
     if obligation_info.owner != program_id {
@@ -857,6 +948,15 @@ fn process_deposit_obligation_collateral(
         msg!("Obligation owner provided must be a signer");
         return Err(LendingError::InvalidSigner.into());
     }
+    if deposit_reserve.config.isolated
+        && obligation
+            .deposits
+            .iter()
+            .any(|collateral| collateral.deposit_reserve != *deposit_reserve_info.key)
+    {
+        msg!("Deposit reserve is isolated and cannot be combined with other collateral");
+        return Err(LendingError::IsolatedCollateral.into());
+    }
 
     obligation
         .find_or_add_collateral_to_deposits(*deposit_reserve_info.key)?
@@ -871,6 +971,7 @@ fn process_deposit_obligation_collateral(
         authority: user_transfer_authority_info.clone(),
         authority_signer_seeds: &[],
         token_program: token_program_id.clone(),
+        liquidity_mint: None,
     })?;
 
     Ok(())
@@ -903,10 +1004,6 @@ fn process_withdraw_obligation_collateral(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
-        return Err(LendingError::InvalidTokenProgram.into());
-    }
 
     let withdraw_reserve = Reserve::unpack(&withdraw_reserve_info.data.borrow())?;
     if withdraw_reserve_info.owner != program_id {
@@ -929,6 +1026,7 @@ fn process_withdraw_obligation_collateral(
         msg!("Withdraw reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    check_reserve_token_program(&withdraw_reserve, &lending_market, token_program_id)?;
 
     let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
     if obligation_info.owner != program_id {
@@ -1026,6 +1124,7 @@ fn process_withdraw_obligation_collateral(
         authority: lending_market_authority_info.clone(),
         authority_signer_seeds,
         token_program: token_program_id.clone(),
+        liquidity_mint: None,
     })?;
 
     Ok(())
@@ -1060,10 +1159,6 @@ fn process_borrow_obligation_liquidity(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
-        return Err(LendingError::InvalidTokenProgram.into());
-    }
 
     let mut borrow_reserve = Reserve::unpack(&borrow_reserve_info.data.borrow())?;
     if borrow_reserve_info.owner != program_id {
@@ -1092,6 +1187,14 @@ fn process_borrow_obligation_liquidity(
         msg!("Borrow reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    check_reserve_token_program(&borrow_reserve, &lending_market, token_program_id)?;
+
+    let optional_accounts = account_info_iter.as_slice();
+    let host_fee_receiver_info = optional_accounts
+        .first()
+        .filter(|account_info| account_info.key != &borrow_reserve.liquidity.mint_pubkey);
+    let liquidity_mint =
+        find_liquidity_mint(&borrow_reserve, token_program_id.key, optional_accounts)?;
 
     let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
     if obligation_info.owner != program_id {
@@ -1149,16 +1252,19 @@ fn process_borrow_obligation_liquidity(
         host_fee,
     } = borrow_reserve.calculate_borrow(liquidity_amount, remaining_borrow_value)?;
 
-    if receive_amount == 0 {
+    let received_amount =
+        amount_after_transfer_fee(liquidity_mint.as_ref(), clock.epoch, receive_amount)?;
+    if received_amount == 0 {
         msg!("Borrow amount is too small to receive liquidity after fees");
         return Err(LendingError::BorrowTooSmall.into());
     }
 
-    if liquidity_amount == u64::MAX && receive_amount < slippage_limit {
+    if liquidity_amount == u64::MAX && received_amount < slippage_limit {
         msg!("Received liquidity would be smaller than the desired slippage limit");
         return Err(LendingError::ExceededSlippage.into());
     }
 
+    borrow_reserve.check_borrow_limit(borrow_amount)?;
     borrow_reserve.liquidity.borrow(borrow_amount)?;
     borrow_reserve.last_update.mark_stale();
     Reserve::pack(borrow_reserve, &mut borrow_reserve_info.data.borrow_mut())?;
@@ -1170,7 +1276,7 @@ fn process_borrow_obligation_liquidity(
     Obligation::pack(obligation, &mut obligation_info.data.borrow_mut())?;
 
     let mut owner_fee = borrow_fee;
-    if let Ok(host_fee_receiver_info) = next_account_info(account_info_iter) {
+    if let Some(host_fee_receiver_info) = host_fee_receiver_info {
         if host_fee > 0 {
             owner_fee = owner_fee
                 .checked_sub(host_fee)
@@ -1183,6 +1289,7 @@ fn process_borrow_obligation_liquidity(
                 authority: lending_market_authority_info.clone(),
                 authority_signer_seeds,
                 token_program: token_program_id.clone(),
+                liquidity_mint: liquidity_mint.as_ref(),
             })?;
         }
     }
@@ -1194,6 +1301,7 @@ fn process_borrow_obligation_liquidity(
             authority: lending_market_authority_info.clone(),
             authority_signer_seeds,
             token_program: token_program_id.clone(),
+            liquidity_mint: liquidity_mint.as_ref(),
         })?;
     }
 
@@ -1204,6 +1312,7 @@ fn process_borrow_obligation_liquidity(
         authority: lending_market_authority_info.clone(),
         authority_signer_seeds,
         token_program: token_program_id.clone(),
+        liquidity_mint: liquidity_mint.as_ref(),
     })?;
 
     Ok(())
@@ -1235,10 +1344,6 @@ fn process_repay_obligation_liquidity(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
-        return Err(LendingError::InvalidTokenProgram.into());
-    }
 
     let mut repay_reserve = Reserve::unpack(&repay_reserve_info.data.borrow())?;
     if repay_reserve_info.owner != program_id {
@@ -1261,6 +1366,12 @@ fn process_repay_obligation_liquidity(
         msg!("Repay reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    check_reserve_token_program(&repay_reserve, &lending_market, token_program_id)?;
+    let liquidity_mint = find_liquidity_mint(
+        &repay_reserve,
+        token_program_id.key,
+        account_info_iter.as_slice(),
+    )?;
 
     let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
     if obligation_info.owner != program_id {
@@ -1292,6 +1403,9 @@ fn process_repay_obligation_liquidity(
         msg!("Repay amount is too small to transfer liquidity");
         return Err(LendingError::RepayTooSmall.into());
     }
+    // the reserve must receive the repay amount, the repayer pays transfer fees
+    let transfer_amount =
+        amount_before_transfer_fee(liquidity_mint.as_ref(), clock.epoch, repay_amount)?;
 
     repay_reserve.liquidity.repay(repay_amount, settle_amount)?;
     repay_reserve.last_update.mark_stale();
@@ -1304,10 +1418,11 @@ fn process_repay_obligation_liquidity(
     spl_token_transfer(TokenTransferParams {
         source: source_liquidity_info.clone(),
         destination: destination_liquidity_info.clone(),
-        amount: repay_amount,
+        amount: transfer_amount,
         authority: user_transfer_authority_info.clone(),
         authority_signer_seeds: &[],
         token_program: token_program_id.clone(),
+        liquidity_mint: liquidity_mint.as_ref(),
     })?;
 
     Ok(())
@@ -1317,6 +1432,7 @@ fn process_repay_obligation_liquidity(
 fn process_liquidate_obligation(
     program_id: &Pubkey,
     liquidity_amount: u64,
+    self_liquidation: bool,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     if liquidity_amount == 0 {
@@ -1335,6 +1451,11 @@ fn process_liquidate_obligation(
     let lending_market_info = next_account_info(account_info_iter)?;
     let lending_market_authority_info = next_account_info(account_info_iter)?;
     let user_transfer_authority_info = next_account_info(account_info_iter)?;
+    let obligation_owner_info = if self_liquidation {
+        Some(next_account_info(account_info_iter)?)
+    } else {
+        None
+    };
     let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
     let token_program_id = next_account_info(account_info_iter)?;
 
@@ -1343,10 +1464,6 @@ fn process_liquidate_obligation(
         msg!("Lending market provided is not owned by the lending program");
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
-        return Err(LendingError::InvalidTokenProgram.into());
-    }
 
     let mut repay_reserve = Reserve::unpack(&repay_reserve_info.data.borrow())?;
     if repay_reserve_info.owner != program_id {
@@ -1375,6 +1492,7 @@ fn process_liquidate_obligation(
         msg!("Repay reserve is stale and must be refreshed in the current slot");
         return Err(LendingError::ReserveStale.into());
     }
+    check_reserve_token_program(&repay_reserve, &lending_market, token_program_id)?;
 
     let withdraw_reserve = Reserve::unpack(&withdraw_reserve_info.data.borrow())?;
     if withdraw_reserve_info.owner != program_id {
@@ -1402,6 +1520,24 @@ fn process_liquidate_obligation(
         return Err(LendingError::ReserveStale.into());
     }
 
+    // the token program provided is the one of the repay reserve, the one of
+    // the withdraw reserve is looked up in the optional accounts if different
+    let optional_accounts = account_info_iter.as_slice();
+    let withdraw_token_program_id = withdraw_reserve.mint_token_program_id(&lending_market);
+    let withdraw_token_program_info = if withdraw_token_program_id == token_program_id.key {
+        token_program_id
+    } else {
+        optional_accounts
+            .iter()
+            .find(|account_info| account_info.key == withdraw_token_program_id)
+            .ok_or_else(|| {
+                msg!("Withdraw reserve token program must be provided");
+                LendingError::InvalidTokenProgram
+            })?
+    };
+    let liquidity_mint =
+        find_liquidity_mint(&repay_reserve, token_program_id.key, optional_accounts)?;
+
     let mut obligation = Obligation::unpack(&obligation_info.data.borrow())?;
     if obligation_info.owner != program_id {
         msg!("Obligation provided is not owned by the lending program");
@@ -1423,7 +1559,17 @@ fn process_liquidate_obligation(
         msg!("Obligation borrowed value is zero");
         return Err(LendingError::ObligationBorrowsZero.into());
     }
-    if obligation.borrowed_value < obligation.unhealthy_borrow_value {
+    if let Some(obligation_owner_info) = obligation_owner_info {
+        // the owner can deleverage a healthy obligation
+        if &obligation.owner != obligation_owner_info.key {
+            msg!("Obligation owner does not match the obligation owner provided");
+            return Err(LendingError::InvalidObligationOwner.into());
+        }
+        if !obligation_owner_info.is_signer {
+            msg!("Obligation owner provided must be a signer");
+            return Err(LendingError::InvalidSigner.into());
+        }
+    } else if obligation.borrowed_value < obligation.unhealthy_borrow_value {
         msg!("Obligation is healthy and cannot be liquidated");
         return Err(LendingError::ObligationHealthy.into());
     }
@@ -1459,12 +1605,21 @@ fn process_liquidate_obligation(
         settle_amount,
         repay_amount,
         withdraw_amount,
-    } = withdraw_reserve.calculate_liquidation(
-        liquidity_amount,
-        &obligation,
-        liquidity,
-        collateral,
-    )?;
+    } = if self_liquidation {
+        withdraw_reserve.calculate_self_liquidation(
+            liquidity_amount,
+            &obligation,
+            liquidity,
+            collateral,
+        )?
+    } else {
+        withdraw_reserve.calculate_liquidation(
+            liquidity_amount,
+            &obligation,
+            liquidity,
+            collateral,
+        )?
+    };
 
     if repay_amount == 0 {
         msg!("Liquidation is too small to transfer liquidity");
@@ -1474,6 +1629,22 @@ fn process_liquidate_obligation(
         msg!("Liquidation is too small to receive collateral");
         return Err(LendingError::LiquidationTooSmall.into());
     }
+    if self_liquidation
+        && !obligation.is_health_preserved(
+            liquidity,
+            settle_amount,
+            collateral,
+            withdraw_amount,
+            withdraw_reserve.config.liquidation_threshold,
+        )?
+    {
+        msg!("Self-liquidation cannot decrease the obligation health");
+        return Err(LendingError::ObligationHealthDecreased.into());
+    }
+    // the repay reserve must receive the repay amount, the liquidator pays
+    // transfer fees
+    let transfer_amount =
+        amount_before_transfer_fee(liquidity_mint.as_ref(), clock.epoch, repay_amount)?;
 
     repay_reserve.liquidity.repay(repay_amount, settle_amount)?;
     repay_reserve.last_update.mark_stale();
@@ -1487,10 +1658,11 @@ fn process_liquidate_obligation(
     spl_token_transfer(TokenTransferParams {
         source: source_liquidity_info.clone(),
         destination: repay_reserve_liquidity_supply_info.clone(),
-        amount: repay_amount,
+        amount: transfer_amount,
         authority: user_transfer_authority_info.clone(),
         authority_signer_seeds: &[],
         token_program: token_program_id.clone(),
+        liquidity_mint: liquidity_mint.as_ref(),
     })?;
 
     spl_token_transfer(TokenTransferParams {
@@ -1499,7 +1671,8 @@ fn process_liquidate_obligation(
         amount: withdraw_amount,
         authority: lending_market_authority_info.clone(),
         authority_signer_seeds,
-        token_program: token_program_id.clone(),
+        token_program: withdraw_token_program_info.clone(),
+        liquidity_mint: None,
     })?;
 
     Ok(())
@@ -1536,10 +1709,6 @@ fn process_flash_loan(
     if lending_market_info.owner != program_id {
         return Err(LendingError::InvalidAccountOwner.into());
     }
-    if &lending_market.token_program_id != token_program_id.key {
-        msg!("Lending market token program does not match the token program provided");
-        return Err(LendingError::InvalidTokenProgram.into());
-    }
 
     let authority_signer_seeds = &[
         lending_market_info.key.as_ref(),
@@ -1571,6 +1740,9 @@ fn process_flash_loan(
         msg!("Reserve liquidity fee receiver does not match the reserve liquidity fee receiver provided");
         return Err(LendingError::InvalidAccountInput.into());
     }
+    check_reserve_token_program(&reserve, &lending_market, token_program_id)?;
+    let liquidity_mint =
+        find_liquidity_mint(&reserve, token_program_id.key, account_info_iter.as_slice())?;
 
     // @FIXME: if u64::MAX is flash loaned, fees should be inclusive as with
     // ordinary borrows
@@ -1586,7 +1758,7 @@ fn process_flash_loan(
         .fees
         .calculate_flash_loan_fees(flash_loan_amount_decimal)?;
 
-    let balance_before_flash_loan = Account::unpack(&source_liquidity_info.data.borrow())?.amount;
+    let balance_before_flash_loan = unpack_token_account_amount(source_liquidity_info)?;
     let expected_balance_after_flash_loan = balance_before_flash_loan
         .checked_add(origination_fee)
         .ok_or(LendingError::MathOverflow)?;
@@ -1624,6 +1796,7 @@ fn process_flash_loan(
         authority: lending_market_authority_info.clone(),
         authority_signer_seeds,
         token_program: token_program_id.clone(),
+        liquidity_mint: liquidity_mint.as_ref(),
     })?;
 
     const RECEIVE_FLASH_LOAN_INSTRUCTION_DATA_SIZE: usize = 9;
@@ -1649,8 +1822,7 @@ fn process_flash_loan(
         .repay(flash_loan_amount, flash_loan_amount_decimal)?;
     Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
 
-    let actual_balance_after_flash_loan =
-        Account::unpack(&source_liquidity_info.data.borrow())?.amount;
+    let actual_balance_after_flash_loan = unpack_token_account_amount(source_liquidity_info)?;
     if actual_balance_after_flash_loan < expected_balance_after_flash_loan {
         msg!("Insufficient reserve liquidity after flash loan");
         return Err(LendingError::NotEnoughLiquidityAfterFlashLoan.into());
@@ -1668,6 +1840,7 @@ fn process_flash_loan(
             authority: lending_market_authority_info.clone(),
             authority_signer_seeds,
             token_program: token_program_id.clone(),
+            liquidity_mint: liquidity_mint.as_ref(),
         })?;
     }
 
@@ -1679,12 +1852,230 @@ fn process_flash_loan(
             authority: lending_market_authority_info.clone(),
             authority_signer_seeds,
             token_program: token_program_id.clone(),
+            liquidity_mint: liquidity_mint.as_ref(),
         })?;
     }
 
     Ok(())
 }
 
+fn process_multi_reserve_flash_loan(
+    program_id: &Pubkey,
+    liquidity_amounts: Vec<u64>,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let lending_market_info = next_account_info(account_info_iter)?;
+    let lending_market_authority_info = next_account_info(account_info_iter)?;
+    let flash_loan_receiver_program_id = next_account_info(account_info_iter)?;
+
+    if program_id == flash_loan_receiver_program_id.key {
+        msg!("Lending program cannot be used as the flash loan receiver program provided");
+        return Err(LendingError::InvalidFlashLoanReceiverProgram.into());
+    }
+
+    let lending_market = LendingMarket::unpack(&lending_market_info.data.borrow())?;
+    if lending_market_info.owner != program_id {
+        return Err(LendingError::InvalidAccountOwner.into());
+    }
+
+    let authority_signer_seeds = &[
+        lending_market_info.key.as_ref(),
+        &[lending_market.bump_seed],
+    ];
+    let lending_market_authority_pubkey =
+        Pubkey::create_program_address(authority_signer_seeds, program_id)?;
+    if &lending_market_authority_pubkey != lending_market_authority_info.key {
+        msg!(
+            "Derived lending market authority does not match the lending market authority provided"
+        );
+        return Err(LendingError::InvalidMarketAuthority.into());
+    }
+
+    let mut loans: Vec<ReserveFlashLoan> = Vec::with_capacity(liquidity_amounts.len());
+    for liquidity_amount in liquidity_amounts {
+        if liquidity_amount == 0 {
+            msg!("Liquidity amount provided cannot be zero");
+            return Err(LendingError::InvalidAmount.into());
+        }
+
+        let reserve_info = next_account_info(account_info_iter)?;
+        let reserve_liquidity_supply_info = next_account_info(account_info_iter)?;
+        let destination_liquidity_info = next_account_info(account_info_iter)?;
+        let reserve_liquidity_fee_receiver_info = next_account_info(account_info_iter)?;
+        let host_fee_receiver_info = next_account_info(account_info_iter)?;
+        let liquidity_mint_info = next_account_info(account_info_iter)?;
+        let token_program_id = next_account_info(account_info_iter)?;
+
+        if loans
+            .iter()
+            .any(|loan| loan.reserve_info.key == reserve_info.key)
+        {
+            msg!("Reserve cannot be provided more than once");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+
+        let reserve = Reserve::unpack(&reserve_info.data.borrow())?;
+        if reserve_info.owner != program_id {
+            msg!("Reserve provided is not owned by the lending program");
+            return Err(LendingError::InvalidAccountOwner.into());
+        }
+        if &reserve.lending_market != lending_market_info.key {
+            msg!("Invalid reserve lending market account");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+        if &reserve.liquidity.supply_pubkey != reserve_liquidity_supply_info.key {
+            msg!("Reserve liquidity supply does not match the reserve liquidity supply provided");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+        if &reserve.liquidity.fee_receiver != reserve_liquidity_fee_receiver_info.key {
+            msg!("Reserve liquidity fee receiver does not match the reserve liquidity fee receiver provided");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+        if &reserve.liquidity.mint_pubkey != liquidity_mint_info.key {
+            msg!("Reserve liquidity mint does not match the reserve liquidity mint provided");
+            return Err(LendingError::InvalidAccountInput.into());
+        }
+        check_reserve_token_program(&reserve, &lending_market, token_program_id)?;
+
+        let amount = if liquidity_amount == u64::MAX {
+            reserve.liquidity.available_amount
+        } else {
+            liquidity_amount
+        };
+        let (origination_fee, host_fee) = reserve
+            .config
+            .fees
+            .calculate_flash_loan_fees(Decimal::from(amount))?;
+        let expected_balance_after_flash_loan =
+            unpack_token_account_amount(reserve_liquidity_supply_info)?
+                .checked_add(origination_fee)
+                .ok_or(LendingError::MathOverflow)?;
+
+        loans.push(ReserveFlashLoan {
+            reserve_info,
+            reserve_liquidity_supply_info,
+            destination_liquidity_info,
+            reserve_liquidity_fee_receiver_info,
+            host_fee_receiver_info,
+            liquidity_mint_info,
+            token_program_id,
+            mint_decimals: reserve.liquidity.mint_decimals,
+            amount,
+            origination_fee,
+            host_fee,
+            expected_balance_after_flash_loan,
+        });
+    }
+    let receiver_account_infos = account_info_iter.as_slice();
+
+    for loan in loans.iter() {
+        let mut reserve = Reserve::unpack(&loan.reserve_info.data.borrow())?;
+        reserve.liquidity.borrow(Decimal::from(loan.amount))?;
+        Reserve::pack(reserve, &mut loan.reserve_info.data.borrow_mut())?;
+
+        spl_token_transfer(TokenTransferParams {
+            source: loan.reserve_liquidity_supply_info.clone(),
+            destination: loan.destination_liquidity_info.clone(),
+            amount: loan.amount,
+            authority: lending_market_authority_info.clone(),
+            authority_signer_seeds,
+            token_program: loan.token_program_id.clone(),
+            liquidity_mint: loan.liquidity_mint(receiver_account_infos).as_ref(),
+        })?;
+    }
+
+    let mut flash_loan_instruction_account_infos = vec![];
+    for loan in loans.iter() {
+        flash_loan_instruction_account_infos.extend([
+            loan.destination_liquidity_info.clone(),
+            loan.reserve_liquidity_supply_info.clone(),
+            loan.liquidity_mint_info.clone(),
+            loan.token_program_id.clone(),
+        ]);
+    }
+    flash_loan_instruction_account_infos.push(flash_loan_receiver_program_id.clone());
+    flash_loan_instruction_account_infos.extend(receiver_account_infos.iter().cloned());
+    let receiver_accounts: Vec<AccountMeta> = receiver_account_infos
+        .iter()
+        .map(|account_info| AccountMeta {
+            pubkey: *account_info.key,
+            is_signer: account_info.is_signer,
+            is_writable: account_info.is_writable,
+        })
+        .collect();
+
+    invoke(
+        &receive_flash_loan(
+            flash_loan_receiver_program_id.key,
+            &loans
+                .iter()
+                .map(|loan| FlashLoanAccounts {
+                    destination_liquidity: *loan.destination_liquidity_info.key,
+                    reserve_liquidity_supply: *loan.reserve_liquidity_supply_info.key,
+                    liquidity_mint: *loan.liquidity_mint_info.key,
+                    token_program_id: *loan.token_program_id.key,
+                })
+                .collect::<Vec<_>>(),
+            loans
+                .iter()
+                .map(|loan| FlashLoanAmount {
+                    amount: loan.amount,
+                    fee: loan.origination_fee,
+                })
+                .collect(),
+            &receiver_accounts,
+        ),
+        &flash_loan_instruction_account_infos[..],
+    )?;
+
+    for loan in loans.iter() {
+        let mut reserve = Reserve::unpack(&loan.reserve_info.data.borrow())?;
+        reserve
+            .liquidity
+            .repay(loan.amount, Decimal::from(loan.amount))?;
+        Reserve::pack(reserve, &mut loan.reserve_info.data.borrow_mut())?;
+
+        let actual_balance_after_flash_loan =
+            unpack_token_account_amount(loan.reserve_liquidity_supply_info)?;
+        if actual_balance_after_flash_loan < loan.expected_balance_after_flash_loan {
+            msg!("Insufficient reserve liquidity after flash loan");
+            return Err(LendingError::NotEnoughLiquidityAfterFlashLoan.into());
+        }
+
+        let liquidity_mint = loan.liquidity_mint(receiver_account_infos);
+        let mut owner_fee = loan.origination_fee;
+        if loan.host_fee > 0 {
+            owner_fee = owner_fee
+                .checked_sub(loan.host_fee)
+                .ok_or(LendingError::MathOverflow)?;
+            spl_token_transfer(TokenTransferParams {
+                source: loan.reserve_liquidity_supply_info.clone(),
+                destination: loan.host_fee_receiver_info.clone(),
+                amount: loan.host_fee,
+                authority: lending_market_authority_info.clone(),
+                authority_signer_seeds,
+                token_program: loan.token_program_id.clone(),
+                liquidity_mint: liquidity_mint.as_ref(),
+            })?;
+        }
+
+        if owner_fee > 0 {
+            spl_token_transfer(TokenTransferParams {
+                source: loan.reserve_liquidity_supply_info.clone(),
+                destination: loan.reserve_liquidity_fee_receiver_info.clone(),
+                amount: owner_fee,
+                authority: lending_market_authority_info.clone(),
+                authority_signer_seeds,
+                token_program: loan.token_program_id.clone(),
+                liquidity_mint: liquidity_mint.as_ref(),
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
 fn process_modify_reserve_config(
     program_id: &Pubkey,
     new_config: ReserveConfig,
@@ -1726,6 +2117,9 @@ fn process_modify_reserve_config(
         return Err(LendingError::InvalidAccountInput.into());
     }
 
+    new_config
+        .oracle
+        .validate_for(reserve.liquidity.oracle_kind)?;
     reserve.config = new_config;
 
     Reserve::pack(reserve, &mut reserve_info.data.borrow_mut())?;
@@ -1753,107 +2147,214 @@ fn assert_uninitialized<T: Pack + IsInitialized>(
     }
 }
 
-/// Unpacks a spl_token `Mint`.
+/// Unpacks a spl_token or spl_token_2022 `Mint`.
 fn unpack_mint(data: &[u8]) -> Result<Mint, LendingError> {
-    Mint::unpack(data).map_err(|_| LendingError::InvalidTokenMint)
+    StateWithExtensions::<Mint>::unpack(data)
+        .map(|mint| mint.base)
+        .map_err(|_| LendingError::InvalidTokenMint)
 }
 
-fn get_pyth_product_quote_currency(pyth_product: &pyth::Product) -> Result<[u8; 32], ProgramError> {
-    const LEN: usize = 14;
-    const KEY: &[u8; LEN] = b"quote_currency";
-
-    let mut start = 0;
-    while start < pyth::PROD_ATTR_SIZE {
-        let mut length = pyth_product.attr[start] as usize;
-        start += 1;
-
-        if length == LEN {
-            let mut end = start + length;
-            if end > pyth::PROD_ATTR_SIZE {
-                msg!("Pyth product attribute key length too long");
-                return Err(LendingError::InvalidOracleConfig.into());
-            }
+/// Unpacks the amount of a spl_token or spl_token_2022 `Account`.
+fn unpack_token_account_amount(account_info: &AccountInfo) -> Result<u64, ProgramError> {
+    let account_data = account_info.try_borrow_data()?;
+    Ok(StateWithExtensions::<Account>::unpack(&account_data)?
+        .base
+        .amount)
+}
 
-            let key = &pyth_product.attr[start..end];
-            if key == KEY {
-                start += length;
-                length = pyth_product.attr[start] as usize;
-                start += 1;
-
-                end = start + length;
-                if length > 32 || end > pyth::PROD_ATTR_SIZE {
-                    msg!("Pyth product quote currency value too long");
-                    return Err(LendingError::InvalidOracleConfig.into());
-                }
-
-                let mut value = [0u8; 32];
-                value[0..length].copy_from_slice(&pyth_product.attr[start..end]);
-                return Ok(value);
-            }
+/// Rejects liquidity mints with extensions breaking the reserve accounting:
+/// non-transferable liquidity cannot be borrowed or redeemed, and a permanent
+/// delegate could move liquidity out of the reserve supply.
+fn check_liquidity_mint_extensions(mint_info: &AccountInfo) -> ProgramResult {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)
+        .map_err(|_| LendingError::InvalidTokenMint)?;
+    for extension_type in mint.get_extension_types()? {
+        if matches!(
+            extension_type,
+            ExtensionType::NonTransferable | ExtensionType::PermanentDelegate
+        ) {
+            msg!(
+                "Reserve liquidity mint extension {:?} is not supported",
+                extension_type
+            );
+            return Err(LendingError::UnsupportedTokenExtension.into());
         }
+    }
+    Ok(())
+}
 
-        start += length;
-        start += 1 + pyth_product.attr[start] as usize;
+/// Checks that the token program provided owns the mints of the reserve
+fn check_reserve_token_program(
+    reserve: &Reserve,
+    lending_market: &LendingMarket,
+    token_program_info: &AccountInfo,
+) -> ProgramResult {
+    if reserve.mint_token_program_id(lending_market) != token_program_info.key {
+        msg!("Reserve token program does not match the token program provided");
+        return Err(LendingError::InvalidTokenProgram.into());
     }
+    Ok(())
+}
+
+/// Finds the reserve liquidity mint in the optional accounts of an
+/// instruction. Token-2022 liquidity is moved with `TransferChecked`, which
+/// requires the mint, so it must be provided for Token-2022 reserves.
+fn find_liquidity_mint<'a, 'b>(
+    reserve: &Reserve,
+    token_program_id: &Pubkey,
+    optional_accounts: &'b [AccountInfo<'a>],
+) -> Result<Option<LiquidityMint<'a, 'b>>, ProgramError> {
+    if *token_program_id != spl_token_2022::id() {
+        return Ok(None);
+    }
+    let info = optional_accounts
+        .iter()
+        .find(|account_info| account_info.key == &reserve.liquidity.mint_pubkey)
+        .ok_or_else(|| {
+            msg!("Reserve liquidity mint must be provided to transfer Token-2022 liquidity");
+            LendingError::InvalidAccountInput
+        })?;
+    Ok(Some(LiquidityMint {
+        info,
+        decimals: reserve.liquidity.mint_decimals,
+        additional_accounts: optional_accounts,
+    }))
+}
 
-    msg!("Pyth product quote currency not found");
-    Err(LendingError::InvalidOracleConfig.into())
+/// Amount received when transferring `amount` of reserve liquidity
+fn amount_after_transfer_fee(
+    liquidity_mint: Option<&LiquidityMint>,
+    epoch: Epoch,
+    amount: u64,
+) -> Result<u64, ProgramError> {
+    let fee = match liquidity_mint {
+        Some(liquidity_mint) => liquidity_mint.transfer_fee(epoch, amount)?,
+        None => 0,
+    };
+    amount
+        .checked_sub(fee)
+        .ok_or_else(|| LendingError::MathOverflow.into())
 }
 
-fn get_pyth_price(pyth_price_info: &AccountInfo, clock: &Clock) -> Result<Decimal, ProgramError> {
-    const STALE_AFTER_SLOTS_ELAPSED: u64 = 5;
+/// Amount of reserve liquidity to transfer for `amount` to be received
+fn amount_before_transfer_fee(
+    liquidity_mint: Option<&LiquidityMint>,
+    epoch: Epoch,
+    amount: u64,
+) -> Result<u64, ProgramError> {
+    let fee = match liquidity_mint {
+        Some(liquidity_mint) => liquidity_mint.inverse_transfer_fee(epoch, amount)?,
+        None => 0,
+    };
+    amount
+        .checked_add(fee)
+        .ok_or_else(|| LendingError::MathOverflow.into())
+}
 
-    let pyth_price_data = pyth_price_info.try_borrow_data()?;
-    let pyth_price = pyth::load::<pyth::Price>(&pyth_price_data)
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+fn check_pyth_oracle(
+    lending_market: &LendingMarket,
+    pyth_product_info: &AccountInfo,
+    pyth_price_info: &AccountInfo,
+) -> ProgramResult {
+    if &lending_market.oracle_program_id != pyth_product_info.owner {
+        msg!("Pyth product account provided is not owned by the lending market oracle program");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+    if &lending_market.oracle_program_id != pyth_price_info.owner {
+        msg!("Pyth price account provided is not owned by the lending market oracle program");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
 
-    if pyth_price.ptype != pyth::PriceType::Price {
-        msg!("Oracle price type is invalid");
+    let pyth_product_data = pyth_product_info.try_borrow_data()?;
+    let pyth_product = pyth::load::<pyth::Product>(&pyth_product_data)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if pyth_product.magic != pyth::MAGIC {
+        msg!("Pyth product account provided is not a valid Pyth account");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+    if pyth_product.ver != pyth::VERSION_2 {
+        msg!("Pyth product account provided has a different version than expected");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+    if pyth_product.atype != pyth::AccountType::Product as u32 {
+        msg!("Pyth product account provided is not a valid Pyth product account");
         return Err(LendingError::InvalidOracleConfig.into());
     }
 
-    if pyth_price.agg.status != pyth::PriceStatus::Trading {
-        msg!("Oracle price status is invalid");
+    let pyth_price_pubkey_bytes: &[u8; 32] = pyth_price_info
+        .key
+        .as_ref()
+        .try_into()
+        .map_err(|_| LendingError::InvalidAccountInput)?;
+    if &pyth_product.px_acc.val != pyth_price_pubkey_bytes {
+        msg!("Pyth product price account does not match the Pyth price provided");
         return Err(LendingError::InvalidOracleConfig.into());
     }
 
-    let slots_elapsed = clock
-        .slot
-        .checked_sub(pyth_price.valid_slot)
-        .ok_or(LendingError::MathOverflow)?;
-    if slots_elapsed >= STALE_AFTER_SLOTS_ELAPSED {
-        msg!("Oracle price is stale");
+    let quote_currency = get_pyth_product_quote_currency(pyth_product)?;
+    if lending_market.quote_currency != quote_currency {
+        msg!("Lending market quote currency does not match the oracle quote currency");
         return Err(LendingError::InvalidOracleConfig.into());
     }
 
-    let price: u64 = pyth_price.agg.price.try_into().map_err(|_| {
-        msg!("Oracle price cannot be negative");
-        LendingError::InvalidOracleConfig
-    })?;
+    Ok(())
+}
 
-    let market_price = if pyth_price.expo >= 0 {
-        let exponent = pyth_price
-            .expo
-            .try_into()
-            .map_err(|_| LendingError::MathOverflow)?;
-        let zeros = 10u64
-            .checked_pow(exponent)
-            .ok_or(LendingError::MathOverflow)?;
-        Decimal::from(price).try_mul(zeros)?
-    } else {
-        let exponent = pyth_price
-            .expo
-            .checked_abs()
-            .ok_or(LendingError::MathOverflow)?
-            .try_into()
-            .map_err(|_| LendingError::MathOverflow)?;
-        let decimals = 10u64
-            .checked_pow(exponent)
-            .ok_or(LendingError::MathOverflow)?;
-        Decimal::from(price).try_div(decimals)?
-    };
+/// Update the market price of a reserve priced by a token-swap oracle.
+///
+/// The price is the average over at least the reserve TWAP window: until the
+/// window has elapsed since the last update, the previous average is kept.
+/// The distance between the spot price and the average is checked against
+/// the reserve max confidence, so that a manipulated or crashing pool makes
+/// the reserve stale instead of mispriced.
+fn refresh_token_swap_price(
+    reserve: &mut Reserve,
+    swap_info: &AccountInfo,
+    swap_token_a_info: &AccountInfo,
+    swap_token_b_info: &AccountInfo,
+    clock: &Clock,
+) -> ProgramResult {
+    let observation = observe_token_swap(
+        swap_info,
+        swap_token_a_info,
+        swap_token_b_info,
+        &reserve.liquidity.mint_pubkey,
+        clock,
+    )?;
 
-    Ok(market_price)
+    let elapsed = observation
+        .timestamp
+        .saturating_sub(reserve.liquidity.oracle_twap_timestamp);
+    if elapsed >= reserve.config.oracle.twap_window_seconds() as i64 {
+        let average_price = observation
+            .price_cumulative
+            .wrapping_sub(reserve.liquidity.oracle_twap_cumulative)
+            / elapsed as u128;
+        reserve.liquidity.market_price = token_swap_price_to_decimal(
+            average_price,
+            reserve.liquidity.mint_decimals,
+            reserve.liquidity.oracle_quote_decimals,
+        )?;
+        reserve.liquidity.oracle_twap_cumulative = observation.price_cumulative;
+        reserve.liquidity.oracle_twap_timestamp = observation.timestamp;
+    }
+    if reserve.liquidity.market_price == Decimal::zero() {
+        msg!("Token swap price has not been averaged over a full window yet");
+        return Err(LendingError::InvalidOracleConfig.into());
+    }
+
+    let spot_price = token_swap_price_to_decimal(
+        observation.spot_price,
+        reserve.liquidity.mint_decimals,
+        reserve.liquidity.oracle_quote_decimals,
+    )?;
+    OraclePrice {
+        price: reserve.liquidity.market_price,
+        confidence: price_deviation(spot_price, reserve.liquidity.market_price)?,
+        publish_slot: clock.slot,
+    }
+    .validate(&reserve.config.oracle, clock)
 }
 
 /// Issue a spl_token `InitializeAccount` instruction.
@@ -1866,7 +2367,7 @@ fn spl_token_init_account(params: TokenInitializeAccountParams<'_>) -> ProgramRe
         rent,
         token_program,
     } = params;
-    let ix = spl_token::instruction::initialize_account(
+    let ix = spl_token_2022::instruction::initialize_account(
         token_program.key,
         account.key,
         mint.key,
@@ -1886,7 +2387,7 @@ fn spl_token_init_mint(params: TokenInitializeMintParams<'_, '_>) -> ProgramResu
         token_program,
         decimals,
     } = params;
-    let ix = spl_token::instruction::initialize_mint(
+    let ix = spl_token_2022::instruction::initialize_mint(
         token_program.key,
         mint.key,
         authority,
@@ -1911,7 +2412,8 @@ fn invoke_optionally_signed(
     }
 }
 
-/// Issue a spl_token `Transfer` instruction.
+/// Issue a spl_token `Transfer` instruction, or a spl_token_2022
+/// `TransferChecked` instruction when the liquidity mint is provided.
 #[inline(always)]
 fn spl_token_transfer(params: TokenTransferParams<'_, '_>) -> ProgramResult {
     let TokenTransferParams {
@@ -1921,19 +2423,41 @@ fn spl_token_transfer(params: TokenTransferParams<'_, '_>) -> ProgramResult {
         token_program,
         amount,
         authority_signer_seeds,
+        liquidity_mint,
     } = params;
-    let result = invoke_optionally_signed(
-        &spl_token::instruction::transfer(
+    let result = if let Some(liquidity_mint) = liquidity_mint {
+        let signers_seeds = [authority_signer_seeds];
+        spl_token_2022::onchain::invoke_transfer_checked(
+            token_program.key,
+            source,
+            liquidity_mint.info.clone(),
+            destination,
+            authority,
+            liquidity_mint.additional_accounts,
+            amount,
+            liquidity_mint.decimals,
+            if authority_signer_seeds.is_empty() {
+                &[]
+            } else {
+                &signers_seeds
+            },
+        )
+    } else {
+        #[allow(deprecated)]
+        let ix = spl_token_2022::instruction::transfer(
             token_program.key,
             source.key,
             destination.key,
             authority.key,
             &[],
             amount,
-        )?,
-        &[source, destination, authority, token_program],
-        authority_signer_seeds,
-    );
+        )?;
+        invoke_optionally_signed(
+            &ix,
+            &[source, destination, authority, token_program],
+            authority_signer_seeds,
+        )
+    };
     result.map_err(|_| LendingError::TokenTransferFailed.into())
 }
 
@@ -1948,7 +2472,7 @@ fn spl_token_mint_to(params: TokenMintToParams<'_, '_>) -> ProgramResult {
         authority_signer_seeds,
     } = params;
     let result = invoke_optionally_signed(
-        &spl_token::instruction::mint_to(
+        &spl_token_2022::instruction::mint_to(
             token_program.key,
             mint.key,
             destination.key,
@@ -1974,7 +2498,7 @@ fn spl_token_burn(params: TokenBurnParams<'_, '_>) -> ProgramResult {
         authority_signer_seeds,
     } = params;
     let result = invoke_optionally_signed(
-        &spl_token::instruction::burn(
+        &spl_token_2022::instruction::burn(
             token_program.key,
             source.key,
             mint.key,
@@ -2011,6 +2535,74 @@ struct TokenTransferParams<'a: 'b, 'b> {
     authority: AccountInfo<'a>,
     authority_signer_seeds: &'b [&'b [u8]],
     token_program: AccountInfo<'a>,
+    /// Required to transfer Token-2022 liquidity
+    liquidity_mint: Option<&'b LiquidityMint<'a, 'b>>,
+}
+
+/// Reserve liquidity mint of a Token-2022 reserve
+struct LiquidityMint<'a, 'b> {
+    info: &'b AccountInfo<'a>,
+    decimals: u8,
+    /// Accounts passed along to a transfer hook of the mint
+    additional_accounts: &'b [AccountInfo<'a>],
+}
+
+impl LiquidityMint<'_, '_> {
+    /// Fee withheld by the mint when transferring `amount`
+    fn transfer_fee(&self, epoch: Epoch, amount: u64) -> Result<u64, ProgramError> {
+        let mint_data = self.info.try_borrow_data()?;
+        let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        match mint.get_extension::<TransferFeeConfig>() {
+            Ok(transfer_fee_config) => transfer_fee_config
+                .calculate_epoch_fee(epoch, amount)
+                .ok_or_else(|| LendingError::MathOverflow.into()),
+            Err(_) => Ok(0),
+        }
+    }
+
+    /// Fee withheld by the mint when transferring enough for `amount` to be
+    /// received
+    fn inverse_transfer_fee(&self, epoch: Epoch, amount: u64) -> Result<u64, ProgramError> {
+        let mint_data = self.info.try_borrow_data()?;
+        let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        match mint.get_extension::<TransferFeeConfig>() {
+            Ok(transfer_fee_config) => transfer_fee_config
+                .calculate_inverse_epoch_fee(epoch, amount)
+                .ok_or_else(|| LendingError::MathOverflow.into()),
+            Err(_) => Ok(0),
+        }
+    }
+}
+
+/// Accounts and amounts of a reserve lending in a `MultiReserveFlashLoan`
+struct ReserveFlashLoan<'a, 'b> {
+    reserve_info: &'b AccountInfo<'a>,
+    reserve_liquidity_supply_info: &'b AccountInfo<'a>,
+    destination_liquidity_info: &'b AccountInfo<'a>,
+    reserve_liquidity_fee_receiver_info: &'b AccountInfo<'a>,
+    host_fee_receiver_info: &'b AccountInfo<'a>,
+    liquidity_mint_info: &'b AccountInfo<'a>,
+    token_program_id: &'b AccountInfo<'a>,
+    mint_decimals: u8,
+    amount: u64,
+    origination_fee: u64,
+    host_fee: u64,
+    expected_balance_after_flash_loan: u64,
+}
+
+impl<'a, 'b> ReserveFlashLoan<'a, 'b> {
+    /// Liquidity mint to transfer Token-2022 liquidity, passing the receiver
+    /// accounts along to its transfer hook
+    fn liquidity_mint(
+        &self,
+        additional_accounts: &'b [AccountInfo<'a>],
+    ) -> Option<LiquidityMint<'a, 'b>> {
+        (*self.token_program_id.key == spl_token_2022::id()).then_some(LiquidityMint {
+            info: self.liquidity_mint_info,
+            decimals: self.mint_decimals,
+            additional_accounts,
+        })
+    }
 }
 
 struct TokenMintToParams<'a: 'b, 'b> {
@@ -115,19 +115,53 @@ impl Obligation {
         self.allowed_borrow_value.try_sub(self.borrowed_value)
     }
 
-    /// Calculate the maximum liquidation amount for a given liquidity
+    /// Calculate the maximum liquidation amount for a given liquidity and close
+    /// factor, as a percentage of the borrowed value
     pub fn max_liquidation_amount(
         &self,
         liquidity: &ObligationLiquidity,
+        close_factor: u8,
     ) -> Result<Decimal, ProgramError> {
         let max_liquidation_value = self
             .borrowed_value
-            .try_mul(Rate::from_percent(LIQUIDATION_CLOSE_FACTOR))?
+            .try_mul(Rate::from_percent(close_factor))?
             .min(liquidity.market_value);
         let max_liquidation_pct = max_liquidation_value.try_div(liquidity.market_value)?;
         liquidity.borrowed_amount_wads.try_mul(max_liquidation_pct)
     }
 
+    /// Whether repaying `settle_amount` of a borrow and withdrawing
+    /// `withdraw_amount` of a deposit with the given liquidation threshold
+    /// keeps the ratio of the unhealthy borrow value to the borrowed value
+    pub fn is_health_preserved(
+        &self,
+        liquidity: &ObligationLiquidity,
+        settle_amount: Decimal,
+        collateral: &ObligationCollateral,
+        withdraw_amount: u64,
+        liquidation_threshold: u8,
+    ) -> Result<bool, ProgramError> {
+        let settle_value = liquidity
+            .market_value
+            .try_mul(settle_amount.try_div(liquidity.borrowed_amount_wads)?)?;
+        let withdraw_value = collateral
+            .market_value
+            .try_mul(withdraw_amount)?
+            .try_div(collateral.deposited_amount)?;
+
+        let borrowed_value = self.borrowed_value.try_sub(settle_value)?;
+        if borrowed_value == Decimal::zero() {
+            return Ok(true);
+        }
+        let unhealthy_borrow_value = self.unhealthy_borrow_value.try_sub(
+            withdraw_value
+                .try_mul(Rate::from_percent(liquidation_threshold))?
+                .min(self.unhealthy_borrow_value),
+        )?;
+        Ok(unhealthy_borrow_value.try_div(borrowed_value)?
+            >= self.unhealthy_borrow_value.try_div(self.borrowed_value)?)
+    }
+
     /// Find collateral by deposit reserve
     pub fn find_collateral_in_deposits(
         &self,
@@ -543,6 +577,44 @@ mod test {
         );
     }
 
+    #[test]
+    fn obligation_health_preserved() {
+        // 100 collateral tokens worth 2000 at an 80% threshold, 1700 borrowed
+        let obligation = Obligation {
+            borrowed_value: Decimal::from(1_700u64),
+            unhealthy_borrow_value: Decimal::from(1_600u64),
+            ..Obligation::default()
+        };
+        let liquidity = ObligationLiquidity {
+            borrowed_amount_wads: Decimal::from(1_700u64),
+            market_value: Decimal::from(1_700u64),
+            ..ObligationLiquidity::default()
+        };
+        let collateral = ObligationCollateral {
+            deposited_amount: 100,
+            market_value: Decimal::from(2_000u64),
+            ..ObligationCollateral::default()
+        };
+        let is_health_preserved = |settle_amount: u64, withdraw_amount| {
+            obligation
+                .is_health_preserved(
+                    &liquidity,
+                    Decimal::from(settle_amount),
+                    &collateral,
+                    withdraw_amount,
+                    80,
+                )
+                .unwrap()
+        };
+
+        // repaying the value withdrawn improves health
+        assert!(is_health_preserved(200, 10));
+        // withdrawing more than the value repaid does not
+        assert!(!is_health_preserved(200, 20));
+        // repaying everything closes the borrow
+        assert!(is_health_preserved(1_700, 85));
+    }
+
     // Creates rates (r1, r2) where 0 < r1 <= r2 <= 100*r1
     prop_compose! {
         fn cumulative_rates()(rate in 1..=u128::MAX)(
@@ -3,10 +3,11 @@ use {
     crate::{
         error::LendingError,
         math::{Decimal, Rate, TryAdd, TryDiv, TryMul, TrySub},
+        oracle::{OracleKind, DEFAULT_MAX_STALENESS_SLOTS, DEFAULT_TWAP_WINDOW_SECONDS},
     },
     arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
     solana_program::{
-        clock::Slot,
+        clock::{Slot, UnixTimestamp},
         entrypoint::ProgramResult,
         msg,
         program_error::ProgramError,
@@ -22,6 +23,14 @@ use {
 /// Percentage of an obligation that can be repaid during each liquidation call
 pub const LIQUIDATION_CLOSE_FACTOR: u8 = 50;
 
+/// Percentage of an obligation that can be repaid during each liquidation call
+/// inside the soft liquidation band
+pub const SOFT_LIQUIDATION_CLOSE_FACTOR: u8 = 20;
+
+/// Percentage of an obligation that its owner can repay during each
+/// self-liquidation call
+pub const SELF_LIQUIDATION_CLOSE_FACTOR: u8 = 100;
+
 /// Obligation borrow amount that is small enough to close out
 pub const LIQUIDATION_CLOSE_AMOUNT: u64 = 2;
 
@@ -40,6 +49,8 @@ pub struct Reserve {
     pub collateral: ReserveCollateral,
     /// Reserve configuration values
     pub config: ReserveConfig,
+    /// Token program of the reserve liquidity and collateral mints
+    pub token_program_id: Pubkey,
 }
 
 impl Reserve {
@@ -58,6 +69,17 @@ impl Reserve {
         self.liquidity = params.liquidity;
         self.collateral = params.collateral;
         self.config = params.config;
+        self.token_program_id = params.token_program_id;
+    }
+
+    /// Token program of the reserve liquidity and collateral mints. Reserves
+    /// created before it was recorded use the lending market token program.
+    pub fn mint_token_program_id<'a>(&'a self, lending_market: &'a LendingMarket) -> &'a Pubkey {
+        if self.token_program_id == Pubkey::default() {
+            &lending_market.token_program_id
+        } else {
+            &self.token_program_id
+        }
     }
 
     /// Record deposited liquidity and return amount of collateral tokens to
@@ -85,9 +107,43 @@ impl Reserve {
         Ok(liquidity_amount)
     }
 
+    /// Check that depositing liquidity keeps the reserve within its deposit
+    /// limit
+    pub fn check_deposit_limit(&self, liquidity_amount: u64) -> ProgramResult {
+        if self.config.deposit_limit == 0 {
+            return Ok(());
+        }
+        let total_supply = self
+            .liquidity
+            .total_supply()?
+            .try_add(Decimal::from(liquidity_amount))?;
+        if total_supply > Decimal::from(self.config.deposit_limit) {
+            msg!("Deposit would exceed the reserve deposit limit");
+            return Err(LendingError::DepositLimitExceeded.into());
+        }
+        Ok(())
+    }
+
+    /// Check that borrowing liquidity keeps the reserve within its borrow limit
+    pub fn check_borrow_limit(&self, borrow_amount: Decimal) -> ProgramResult {
+        if self.config.borrow_limit == 0 {
+            return Ok(());
+        }
+        let borrowed_amount = self.liquidity.borrowed_amount_wads.try_add(borrow_amount)?;
+        if borrowed_amount > Decimal::from(self.config.borrow_limit) {
+            msg!("Borrow would exceed the reserve borrow limit");
+            return Err(LendingError::BorrowLimitExceeded.into());
+        }
+        Ok(())
+    }
+
     /// Calculate the current borrow rate
     pub fn current_borrow_rate(&self) -> Result<Rate, ProgramError> {
         let utilization_rate = self.liquidity.utilization_rate()?;
+        if !self.config.rate_curve.is_empty() {
+            return self.config.rate_curve.borrow_rate(utilization_rate);
+        }
+
         let optimal_utilization_rate = Rate::from_percent(self.config.optimal_utilization_rate);
         let low_utilization = utilization_rate < optimal_utilization_rate;
         if low_utilization || self.config.optimal_utilization_rate == 100 {
@@ -213,6 +269,17 @@ impl Reserve {
         })
     }
 
+    /// Whether an unhealthy obligation is within the soft liquidation band of
+    /// the reserve its collateral is withdrawn from
+    pub fn is_soft_liquidation(&self, obligation: &Obligation) -> Result<bool, ProgramError> {
+        if self.config.soft_liquidation_band == 0 {
+            return Ok(false);
+        }
+        let band_rate =
+            Rate::from_percent(self.config.soft_liquidation_band).try_add(Rate::one())?;
+        Ok(obligation.borrowed_value < obligation.unhealthy_borrow_value.try_mul(band_rate)?)
+    }
+
     /// Liquidate some or all of an unhealthy obligation
     pub fn calculate_liquidation(
         &self,
@@ -221,7 +288,53 @@ impl Reserve {
         liquidity: &ObligationLiquidity,
         collateral: &ObligationCollateral,
     ) -> Result<CalculateLiquidationResult, ProgramError> {
-        let bonus_rate = Rate::from_percent(self.config.liquidation_bonus).try_add(Rate::one())?;
+        let (liquidation_bonus, close_factor) = if self.is_soft_liquidation(obligation)? {
+            (
+                self.config.soft_liquidation_bonus,
+                SOFT_LIQUIDATION_CLOSE_FACTOR,
+            )
+        } else {
+            (self.config.liquidation_bonus, LIQUIDATION_CLOSE_FACTOR)
+        };
+        self.calculate_liquidation_with(
+            amount_to_liquidate,
+            obligation,
+            liquidity,
+            collateral,
+            liquidation_bonus,
+            close_factor,
+        )
+    }
+
+    /// Deleverage some or all of an obligation on behalf of its owner, without
+    /// a liquidation bonus
+    pub fn calculate_self_liquidation(
+        &self,
+        amount_to_liquidate: u64,
+        obligation: &Obligation,
+        liquidity: &ObligationLiquidity,
+        collateral: &ObligationCollateral,
+    ) -> Result<CalculateLiquidationResult, ProgramError> {
+        self.calculate_liquidation_with(
+            amount_to_liquidate,
+            obligation,
+            liquidity,
+            collateral,
+            0,
+            SELF_LIQUIDATION_CLOSE_FACTOR,
+        )
+    }
+
+    fn calculate_liquidation_with(
+        &self,
+        amount_to_liquidate: u64,
+        obligation: &Obligation,
+        liquidity: &ObligationLiquidity,
+        collateral: &ObligationCollateral,
+        liquidation_bonus: u8,
+        close_factor: u8,
+    ) -> Result<CalculateLiquidationResult, ProgramError> {
+        let bonus_rate = Rate::from_percent(liquidation_bonus).try_add(Rate::one())?;
 
         let max_amount = if amount_to_liquidate == u64::MAX {
             liquidity.borrowed_amount_wads
@@ -261,7 +374,7 @@ impl Reserve {
             // calculate settle_amount and withdraw_amount, repay_amount is settle_amount
             // rounded
             let liquidation_amount = obligation
-                .max_liquidation_amount(liquidity)?
+                .max_liquidation_amount(liquidity, close_factor)?
                 .min(max_amount);
             let liquidation_pct = liquidation_amount.try_div(liquidity.borrowed_amount_wads)?;
             let liquidation_value = liquidity
@@ -312,6 +425,8 @@ pub struct InitReserveParams {
     pub collateral: ReserveCollateral,
     /// Reserve configuration values
     pub config: ReserveConfig,
+    /// Token program of the reserve liquidity and collateral mints
+    pub token_program_id: Pubkey,
 }
 
 /// Calculate borrow result
@@ -369,6 +484,16 @@ pub struct ReserveLiquidity {
     pub cumulative_borrow_rate_wads: Decimal,
     /// Reserve liquidity market price in quote currency
     pub market_price: Decimal,
+    /// Source of the market price
+    pub oracle_kind: OracleKind,
+    /// Decimals of the quote currency mint, for token-swap oracles
+    pub oracle_quote_decimals: u8,
+    /// Token-swap price accumulator at the start of the current averaging
+    /// window, for token-swap oracles
+    pub oracle_twap_cumulative: u128,
+    /// Timestamp of the start of the current averaging window, for
+    /// token-swap oracles
+    pub oracle_twap_timestamp: UnixTimestamp,
 }
 
 impl ReserveLiquidity {
@@ -384,6 +509,10 @@ impl ReserveLiquidity {
             borrowed_amount_wads: Decimal::zero(),
             cumulative_borrow_rate_wads: Decimal::one(),
             market_price: params.market_price,
+            oracle_kind: params.oracle_kind,
+            oracle_quote_decimals: params.oracle_quote_decimals,
+            oracle_twap_cumulative: 0,
+            oracle_twap_timestamp: 0,
         }
     }
 
@@ -486,6 +615,10 @@ pub struct NewReserveLiquidityParams {
     pub oracle_pubkey: Pubkey,
     /// Reserve liquidity market price in quote currency
     pub market_price: Decimal,
+    /// Source of the market price
+    pub oracle_kind: OracleKind,
+    /// Decimals of the quote currency mint, for token-swap oracles
+    pub oracle_quote_decimals: u8,
 }
 
 /// Reserve collateral
@@ -613,6 +746,28 @@ pub struct ReserveConfig {
     pub max_borrow_rate: u8,
     /// Program owner fees assessed, separate from gains due to interest accrual
     pub fees: ReserveFees,
+    /// Checks applied to the market price read from the oracle
+    pub oracle: ReserveOracleConfig,
+    /// Maximum total liquidity supplied to the reserve, including borrowed
+    /// liquidity, or 0 for no limit
+    pub deposit_limit: u64,
+    /// Maximum liquidity borrowed from the reserve, or 0 for no limit
+    pub borrow_limit: u64,
+    /// Whether the reserve collateral can only back an obligation on its own.
+    /// Obligations depositing isolated collateral cannot deposit any other
+    /// collateral, so that a long-tail asset cannot be used to borrow against
+    /// the rest of the market.
+    pub isolated: bool,
+    /// Borrow rate curve, replacing the utilization and borrow rates above
+    /// unless empty
+    pub rate_curve: RateCurve,
+    /// Band above the liquidation threshold, as a percentage of the unhealthy
+    /// borrow value, in which obligations are soft liquidated with a smaller
+    /// close factor and bonus. 0 disables soft liquidation.
+    pub soft_liquidation_band: u8,
+    /// Bonus a liquidator gets when repaying part of an obligation inside the
+    /// soft liquidation band, as a percentage
+    pub soft_liquidation_bonus: u8,
 }
 
 impl ReserveConfig {
@@ -657,11 +812,213 @@ impl ReserveConfig {
             msg!("Host fee percentage must be in range [0, 100]");
             return Err(LendingError::InvalidConfig.into());
         }
+        if self.oracle.max_confidence_bps > 10_000 {
+            msg!("Oracle max confidence must be in range [0, 10_000] basis points");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        if !self.rate_curve.is_empty() {
+            self.rate_curve.validate()?;
+        }
+        if self.soft_liquidation_band > 100 {
+            msg!("Soft liquidation band must be in range [0, 100]");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        if self.soft_liquidation_bonus > self.liquidation_bonus {
+            msg!("Soft liquidation bonus must be <= liquidation bonus");
+            return Err(LendingError::InvalidConfig.into());
+        }
 
         Ok(())
     }
 }
 
+/// Maximum number of points of a borrow rate curve
+pub const MAX_RATE_CURVE_POINTS: usize = 8;
+
+/// Maximum borrow rate of a borrow rate curve, in basis points
+pub const MAX_RATE_CURVE_BORROW_RATE_BPS: u32 = 1_000_000;
+
+const RATE_CURVE_POINT_LEN: usize = 6; // 2 + 4
+/// Packed length of a borrow rate curve
+pub const RATE_CURVE_LEN: usize = 49; // 1 + 6 * MAX_RATE_CURVE_POINTS
+
+/// Point of a borrow rate curve
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RateCurvePoint {
+    /// Utilization rate, in basis points
+    pub utilization_bps: u16,
+    /// Borrow APY at this utilization rate, in basis points
+    pub borrow_rate_bps: u32,
+}
+
+/// Piecewise-linear borrow rate curve
+///
+/// The borrow rate is interpolated linearly between consecutive points. The
+/// first point is at 0% utilization and the last one at 100%, with as many
+/// breakpoints in between as needed, for example to make the rate of a
+/// stablecoin reserve steep above its optimal utilization.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RateCurve {
+    /// Number of points used in `points`
+    pub num_points: u8,
+    /// Points of the curve, by increasing utilization rate
+    pub points: [RateCurvePoint; MAX_RATE_CURVE_POINTS],
+}
+
+impl RateCurve {
+    /// Curve without points, using the utilization and borrow rates of the
+    /// reserve config instead
+    pub const fn empty() -> Self {
+        Self {
+            num_points: 0,
+            points: [RateCurvePoint {
+                utilization_bps: 0,
+                borrow_rate_bps: 0,
+            }; MAX_RATE_CURVE_POINTS],
+        }
+    }
+
+    /// Create a curve from its points
+    pub fn new(points: &[RateCurvePoint]) -> Result<Self, ProgramError> {
+        if points.len() > MAX_RATE_CURVE_POINTS {
+            msg!(
+                "Rate curve cannot have more than {} points",
+                MAX_RATE_CURVE_POINTS
+            );
+            return Err(LendingError::InvalidConfig.into());
+        }
+        let mut curve = Self::empty();
+        curve.num_points = points.len() as u8;
+        curve.points[..points.len()].copy_from_slice(points);
+        Ok(curve)
+    }
+
+    /// Points used by the curve
+    pub fn points(&self) -> &[RateCurvePoint] {
+        &self.points[..(self.num_points as usize).min(MAX_RATE_CURVE_POINTS)]
+    }
+
+    /// Whether the curve has no points
+    pub fn is_empty(&self) -> bool {
+        self.num_points == 0
+    }
+
+    /// Validate the points of the curve
+    pub fn validate(&self) -> ProgramResult {
+        let points = self.points();
+        if points.len() < 2 || self.num_points as usize > MAX_RATE_CURVE_POINTS {
+            msg!(
+                "Rate curve must have between 2 and {} points",
+                MAX_RATE_CURVE_POINTS
+            );
+            return Err(LendingError::InvalidConfig.into());
+        }
+        if points[0].utilization_bps != 0 || points[points.len() - 1].utilization_bps != 10_000 {
+            msg!("Rate curve must start at 0% utilization and end at 100% utilization");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        for window in points.windows(2) {
+            if window[1].utilization_bps <= window[0].utilization_bps {
+                msg!("Rate curve utilization rates must be strictly increasing");
+                return Err(LendingError::InvalidConfig.into());
+            }
+            if window[1].borrow_rate_bps < window[0].borrow_rate_bps {
+                msg!("Rate curve borrow rates must not decrease");
+                return Err(LendingError::InvalidConfig.into());
+            }
+        }
+        if points[points.len() - 1].borrow_rate_bps > MAX_RATE_CURVE_BORROW_RATE_BPS {
+            msg!(
+                "Rate curve borrow rates must be in range [0, {}] basis points",
+                MAX_RATE_CURVE_BORROW_RATE_BPS
+            );
+            return Err(LendingError::InvalidConfig.into());
+        }
+        Ok(())
+    }
+
+    /// Borrow rate at a utilization rate
+    pub fn borrow_rate(&self, utilization_rate: Rate) -> Result<Rate, ProgramError> {
+        let points = self.points();
+        let last = points.last().ok_or(LendingError::InvalidConfig)?;
+        for window in points.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let end_utilization_rate = Rate::from_bps(end.utilization_bps as u32);
+            if utilization_rate <= end_utilization_rate {
+                let start_utilization_rate = Rate::from_bps(start.utilization_bps as u32);
+                let start_rate = Rate::from_bps(start.borrow_rate_bps);
+                let normalized_rate = utilization_rate
+                    .try_sub(start_utilization_rate)?
+                    .try_div(end_utilization_rate.try_sub(start_utilization_rate)?)?;
+                let rate_range = Rate::from_bps(end.borrow_rate_bps)
+                    .try_sub(Rate::from_bps(start.borrow_rate_bps))?;
+                return normalized_rate.try_mul(rate_range)?.try_add(start_rate);
+            }
+        }
+        Ok(Rate::from_bps(last.borrow_rate_bps))
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8; RATE_CURVE_LEN]) {
+        let (num_points, points) = mut_array_refs![output, 1, RATE_CURVE_LEN - 1];
+        *num_points = self.num_points.to_le_bytes();
+        for (point, output) in self
+            .points
+            .iter()
+            .zip(points.chunks_exact_mut(RATE_CURVE_POINT_LEN))
+        {
+            let output = array_mut_ref![output, 0, RATE_CURVE_POINT_LEN];
+            let (utilization_bps, borrow_rate_bps) = mut_array_refs![output, 2, 4];
+            *utilization_bps = point.utilization_bps.to_le_bytes();
+            *borrow_rate_bps = point.borrow_rate_bps.to_le_bytes();
+        }
+    }
+
+    fn unpack_from_slice(input: &[u8; RATE_CURVE_LEN]) -> Result<Self, ProgramError> {
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (num_points, points) = array_refs![input, 1, RATE_CURVE_LEN - 1];
+        let num_points = u8::from_le_bytes(*num_points);
+        if num_points as usize > MAX_RATE_CURVE_POINTS {
+            msg!("Rate curve cannot be unpacked");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut curve = Self {
+            num_points,
+            ..Self::empty()
+        };
+        for (point, input) in curve
+            .points
+            .iter_mut()
+            .zip(points.chunks_exact(RATE_CURVE_POINT_LEN))
+        {
+            let input = array_ref![input, 0, RATE_CURVE_POINT_LEN];
+            let (utilization_bps, borrow_rate_bps) = array_refs![input, 2, 4];
+            point.utilization_bps = u16::from_le_bytes(*utilization_bps);
+            point.borrow_rate_bps = u32::from_le_bytes(*borrow_rate_bps);
+        }
+        Ok(curve)
+    }
+
+    /// Serialize the curve into an instruction buffer
+    pub(crate) fn extend_buffer(&self, buf: &mut Vec<u8>) {
+        let mut output = [0u8; RATE_CURVE_LEN];
+        self.pack_into_slice(&mut output);
+        buf.extend_from_slice(&output);
+    }
+
+    /// Deserialize a curve from an instruction buffer, returning the rest of
+    /// the buffer
+    pub(crate) fn unpack_from_buffer(input: &[u8]) -> Result<(Self, &[u8]), ProgramError> {
+        if input.len() < RATE_CURVE_LEN {
+            msg!("Rate curve cannot be unpacked");
+            return Err(LendingError::InstructionUnpackError.into());
+        }
+        let (curve, rest) = input.split_at(RATE_CURVE_LEN);
+        let curve = Self::unpack_from_slice(array_ref![curve, 0, RATE_CURVE_LEN])
+            .map_err(|_| LendingError::InstructionUnpackError)?;
+        Ok((curve, rest))
+    }
+}
+
 /// Additional fee information on a reserve
 ///
 /// These exist separately from interest accrual fees, and are specifically for
@@ -755,6 +1112,54 @@ impl ReserveFees {
     }
 }
 
+/// Checks applied to the market price of a reserve
+///
+/// Zero values select the defaults, so that reserves created before these
+/// checks existed keep their behavior.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReserveOracleConfig {
+    /// Number of slots after which an oracle price is stale, or 0 for
+    /// `DEFAULT_MAX_STALENESS_SLOTS`
+    pub max_staleness_slots: u64,
+    /// Maximum confidence interval of an oracle price, in basis points of the
+    /// price, or 0 to accept any confidence interval. For token-swap oracles,
+    /// the interval is the distance between the spot and average prices, and
+    /// must be bounded.
+    pub max_confidence_bps: u16,
+    /// Minimum number of seconds over which token-swap prices are averaged, or
+    /// 0 for `DEFAULT_TWAP_WINDOW_SECONDS`
+    pub twap_window_seconds: u32,
+}
+
+impl ReserveOracleConfig {
+    /// Number of slots after which an oracle price is stale
+    pub fn max_staleness_slots(&self) -> u64 {
+        if self.max_staleness_slots == 0 {
+            DEFAULT_MAX_STALENESS_SLOTS
+        } else {
+            self.max_staleness_slots
+        }
+    }
+
+    /// Minimum number of seconds over which token-swap prices are averaged
+    pub fn twap_window_seconds(&self) -> u32 {
+        if self.twap_window_seconds == 0 {
+            DEFAULT_TWAP_WINDOW_SECONDS
+        } else {
+            self.twap_window_seconds
+        }
+    }
+
+    /// Check that the config bounds the prices of the given kind of oracle
+    pub fn validate_for(&self, oracle_kind: OracleKind) -> ProgramResult {
+        if oracle_kind == OracleKind::TokenSwapTwap && self.max_confidence_bps == 0 {
+            msg!("Token swap oracles require a max confidence");
+            return Err(LendingError::InvalidConfig.into());
+        }
+        Ok(())
+    }
+}
+
 /// Calculate fees exlusive or inclusive of an amount
 pub enum FeeCalculation {
     /// Fee added to amount: fee = rate * amount
@@ -771,7 +1176,8 @@ impl IsInitialized for Reserve {
 }
 
 const RESERVE_LEN: usize = 571; // 1 + 8 + 1 + 32 + 32 + 1 + 32 + 32 + 32 + 8 + 16 + 16 + 16 + 32 + 8 + 32 + 1 +
-                                // 1 + 1 + 1 + 1 + 1 + 1 + 8 + 8 + 1 + 248
+                                // 1 + 1 + 1 + 1 + 1 + 1 + 8 + 8 + 1 + 1 + 1 + 16 + 8 + 8 + 2 + 4 + 8 + 8 + 1 +
+                                // 49 + 32 + 1 + 1 + 108
 impl Pack for Reserve {
     const LEN: usize = RESERVE_LEN;
 
@@ -806,6 +1212,20 @@ impl Pack for Reserve {
             config_fees_borrow_fee_wad,
             config_fees_flash_loan_fee_wad,
             config_fees_host_fee_percentage,
+            liquidity_oracle_kind,
+            liquidity_oracle_quote_decimals,
+            liquidity_oracle_twap_cumulative,
+            liquidity_oracle_twap_timestamp,
+            config_oracle_max_staleness_slots,
+            config_oracle_max_confidence_bps,
+            config_oracle_twap_window_seconds,
+            config_deposit_limit,
+            config_borrow_limit,
+            config_isolated,
+            config_rate_curve,
+            token_program_id,
+            config_soft_liquidation_band,
+            config_soft_liquidation_bonus,
             _padding,
         ) = mut_array_refs![
             output,
@@ -835,7 +1255,21 @@ impl Pack for Reserve {
             8,
             8,
             1,
-            248
+            1,
+            1,
+            16,
+            8,
+            8,
+            2,
+            4,
+            8,
+            8,
+            1,
+            RATE_CURVE_LEN,
+            PUBKEY_BYTES,
+            1,
+            1,
+            108
         ];
 
         // reserve
@@ -877,6 +1311,22 @@ impl Pack for Reserve {
         *config_fees_borrow_fee_wad = self.config.fees.borrow_fee_wad.to_le_bytes();
         *config_fees_flash_loan_fee_wad = self.config.fees.flash_loan_fee_wad.to_le_bytes();
         *config_fees_host_fee_percentage = self.config.fees.host_fee_percentage.to_le_bytes();
+
+        // oracle
+        *liquidity_oracle_kind = (self.liquidity.oracle_kind as u8).to_le_bytes();
+        *liquidity_oracle_quote_decimals = self.liquidity.oracle_quote_decimals.to_le_bytes();
+        *liquidity_oracle_twap_cumulative = self.liquidity.oracle_twap_cumulative.to_le_bytes();
+        *liquidity_oracle_twap_timestamp = self.liquidity.oracle_twap_timestamp.to_le_bytes();
+        *config_oracle_max_staleness_slots = self.config.oracle.max_staleness_slots.to_le_bytes();
+        *config_oracle_max_confidence_bps = self.config.oracle.max_confidence_bps.to_le_bytes();
+        *config_oracle_twap_window_seconds = self.config.oracle.twap_window_seconds.to_le_bytes();
+        *config_deposit_limit = self.config.deposit_limit.to_le_bytes();
+        *config_borrow_limit = self.config.borrow_limit.to_le_bytes();
+        pack_bool(self.config.isolated, config_isolated);
+        self.config.rate_curve.pack_into_slice(config_rate_curve);
+        token_program_id.copy_from_slice(self.token_program_id.as_ref());
+        *config_soft_liquidation_band = self.config.soft_liquidation_band.to_le_bytes();
+        *config_soft_liquidation_bonus = self.config.soft_liquidation_bonus.to_le_bytes();
     }
 
     /// Unpacks a byte buffer into a [ReserveInfo](struct.ReserveInfo.html).
@@ -910,6 +1360,20 @@ impl Pack for Reserve {
             config_fees_borrow_fee_wad,
             config_fees_flash_loan_fee_wad,
             config_fees_host_fee_percentage,
+            liquidity_oracle_kind,
+            liquidity_oracle_quote_decimals,
+            liquidity_oracle_twap_cumulative,
+            liquidity_oracle_twap_timestamp,
+            config_oracle_max_staleness_slots,
+            config_oracle_max_confidence_bps,
+            config_oracle_twap_window_seconds,
+            config_deposit_limit,
+            config_borrow_limit,
+            config_isolated,
+            config_rate_curve,
+            token_program_id,
+            config_soft_liquidation_band,
+            config_soft_liquidation_bonus,
             _padding,
         ) = array_refs![
             input,
@@ -939,7 +1403,21 @@ impl Pack for Reserve {
             8,
             8,
             1,
-            248
+            1,
+            1,
+            16,
+            8,
+            8,
+            2,
+            4,
+            8,
+            8,
+            1,
+            RATE_CURVE_LEN,
+            PUBKEY_BYTES,
+            1,
+            1,
+            108
         ];
 
         let version = u8::from_le_bytes(*version);
@@ -965,6 +1443,11 @@ impl Pack for Reserve {
                 borrowed_amount_wads: unpack_decimal(liquidity_borrowed_amount_wads),
                 cumulative_borrow_rate_wads: unpack_decimal(liquidity_cumulative_borrow_rate_wads),
                 market_price: unpack_decimal(liquidity_market_price),
+                oracle_kind: OracleKind::try_from(u8::from_le_bytes(*liquidity_oracle_kind))
+                    .map_err(|_| ProgramError::InvalidAccountData)?,
+                oracle_quote_decimals: u8::from_le_bytes(*liquidity_oracle_quote_decimals),
+                oracle_twap_cumulative: u128::from_le_bytes(*liquidity_oracle_twap_cumulative),
+                oracle_twap_timestamp: i64::from_le_bytes(*liquidity_oracle_twap_timestamp),
             },
             collateral: ReserveCollateral {
                 mint_pubkey: Pubkey::new_from_array(*collateral_mint_pubkey),
@@ -984,7 +1467,19 @@ impl Pack for Reserve {
                     flash_loan_fee_wad: u64::from_le_bytes(*config_fees_flash_loan_fee_wad),
                     host_fee_percentage: u8::from_le_bytes(*config_fees_host_fee_percentage),
                 },
+                oracle: ReserveOracleConfig {
+                    max_staleness_slots: u64::from_le_bytes(*config_oracle_max_staleness_slots),
+                    max_confidence_bps: u16::from_le_bytes(*config_oracle_max_confidence_bps),
+                    twap_window_seconds: u32::from_le_bytes(*config_oracle_twap_window_seconds),
+                },
+                deposit_limit: u64::from_le_bytes(*config_deposit_limit),
+                borrow_limit: u64::from_le_bytes(*config_borrow_limit),
+                isolated: unpack_bool(config_isolated)?,
+                rate_curve: RateCurve::unpack_from_slice(config_rate_curve)?,
+                soft_liquidation_band: u8::from_le_bytes(*config_soft_liquidation_band),
+                soft_liquidation_bonus: u8::from_le_bytes(*config_soft_liquidation_bonus),
             },
+            token_program_id: Pubkey::new_from_array(*token_program_id),
         })
     }
 }
@@ -1367,4 +1862,330 @@ mod test {
         assert_eq!(total_fee, 10); // 1% of 1000
         assert_eq!(host_fee, 0); // 0 host fee
     }
+
+    #[test]
+    fn pack_unpack_oracle_and_limits() {
+        let reserve = Reserve {
+            version: PROGRAM_VERSION,
+            liquidity: ReserveLiquidity {
+                oracle_kind: OracleKind::TokenSwapTwap,
+                oracle_quote_decimals: 6,
+                oracle_twap_cumulative: u128::MAX - 1,
+                oracle_twap_timestamp: -42,
+                ..ReserveLiquidity::default()
+            },
+            config: ReserveConfig {
+                oracle: ReserveOracleConfig {
+                    max_staleness_slots: 25,
+                    max_confidence_bps: 150,
+                    twap_window_seconds: 1_800,
+                },
+                deposit_limit: 1_000_000,
+                borrow_limit: 500_000,
+                isolated: true,
+                rate_curve: test_rate_curve(),
+                soft_liquidation_band: 10,
+                soft_liquidation_bonus: 3,
+                ..ReserveConfig::default()
+            },
+            token_program_id: Pubkey::new_unique(),
+            ..Reserve::default()
+        };
+        let mut data = [0u8; Reserve::LEN];
+        Reserve::pack(reserve.clone(), &mut data).unwrap();
+        assert_eq!(Reserve::unpack(&data).unwrap(), reserve);
+
+        // reserves created before oracle configuration and limits use the
+        // defaults
+        let mut data = [0u8; Reserve::LEN];
+        Reserve::pack(
+            Reserve {
+                version: PROGRAM_VERSION,
+                ..Reserve::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let reserve = Reserve::unpack(&data).unwrap();
+        assert_eq!(reserve.liquidity.oracle_kind, OracleKind::Pyth);
+        assert_eq!(
+            reserve.config.oracle.max_staleness_slots(),
+            DEFAULT_MAX_STALENESS_SLOTS
+        );
+        assert_eq!(
+            reserve.config.oracle.twap_window_seconds(),
+            DEFAULT_TWAP_WINDOW_SECONDS
+        );
+        assert_eq!(reserve.config.deposit_limit, 0);
+        assert_eq!(reserve.config.borrow_limit, 0);
+        assert!(!reserve.config.isolated);
+        assert!(reserve.config.rate_curve.is_empty());
+        assert_eq!(reserve.config.soft_liquidation_band, 0);
+        assert_eq!(reserve.config.soft_liquidation_bonus, 0);
+
+        // and the lending market token program
+        let lending_market = LendingMarket {
+            token_program_id: Pubkey::new_unique(),
+            ..LendingMarket::default()
+        };
+        assert_eq!(
+            reserve.mint_token_program_id(&lending_market),
+            &lending_market.token_program_id
+        );
+    }
+
+    #[test]
+    fn deposit_and_borrow_limits() {
+        let mut reserve = Reserve {
+            liquidity: ReserveLiquidity {
+                available_amount: 600,
+                borrowed_amount_wads: Decimal::from(300u64),
+                ..ReserveLiquidity::default()
+            },
+            ..Reserve::default()
+        };
+        // no limits
+        assert_eq!(reserve.check_deposit_limit(u64::MAX / 2), Ok(()));
+        assert_eq!(reserve.check_borrow_limit(Decimal::from(600u64)), Ok(()));
+
+        reserve.config.deposit_limit = 1_000;
+        reserve.config.borrow_limit = 500;
+        assert_eq!(reserve.check_deposit_limit(100), Ok(()));
+        assert_eq!(
+            reserve.check_deposit_limit(101),
+            Err(LendingError::DepositLimitExceeded.into())
+        );
+        assert_eq!(reserve.check_borrow_limit(Decimal::from(200u64)), Ok(()));
+        assert_eq!(
+            reserve.check_borrow_limit(Decimal::from(201u64)),
+            Err(LendingError::BorrowLimitExceeded.into())
+        );
+    }
+
+    #[test]
+    fn soft_and_self_liquidation() {
+        // 100 collateral tokens worth 2000 at an 80% threshold, 1700 borrowed
+        let obligation = Obligation {
+            borrowed_value: Decimal::from(1_700u64),
+            unhealthy_borrow_value: Decimal::from(1_600u64),
+            ..Obligation::default()
+        };
+        let liquidity = ObligationLiquidity {
+            borrowed_amount_wads: Decimal::from(1_700u64),
+            market_value: Decimal::from(1_700u64),
+            ..ObligationLiquidity::default()
+        };
+        let collateral = ObligationCollateral {
+            deposited_amount: 100,
+            market_value: Decimal::from(2_000u64),
+            ..ObligationCollateral::default()
+        };
+        let mut reserve = Reserve {
+            config: ReserveConfig {
+                liquidation_bonus: 10,
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+
+        // without a soft liquidation band, half of the borrow is liquidated at
+        // the full bonus
+        assert!(!reserve.is_soft_liquidation(&obligation).unwrap());
+        let result = reserve
+            .calculate_liquidation(u64::MAX, &obligation, &liquidity, &collateral)
+            .unwrap();
+        assert_eq!(result.repay_amount, 850);
+        assert_eq!(result.withdraw_amount, 46);
+
+        // within the soft liquidation band, a fifth of the borrow is
+        // liquidated at the soft bonus
+        reserve.config.soft_liquidation_band = 10;
+        reserve.config.soft_liquidation_bonus = 5;
+        assert!(reserve.is_soft_liquidation(&obligation).unwrap());
+        let result = reserve
+            .calculate_liquidation(u64::MAX, &obligation, &liquidity, &collateral)
+            .unwrap();
+        assert_eq!(result.repay_amount, 340);
+        assert_eq!(result.withdraw_amount, 17);
+
+        // past the band, hard liquidation applies again
+        let deep_obligation = Obligation {
+            borrowed_value: Decimal::from(1_760u64),
+            ..obligation.clone()
+        };
+        assert!(!reserve.is_soft_liquidation(&deep_obligation).unwrap());
+
+        // the owner can repay the whole borrow without a bonus
+        let result = reserve
+            .calculate_self_liquidation(u64::MAX, &obligation, &liquidity, &collateral)
+            .unwrap();
+        assert_eq!(result.repay_amount, 1_700);
+        assert_eq!(result.withdraw_amount, 85);
+        let result = reserve
+            .calculate_self_liquidation(340, &obligation, &liquidity, &collateral)
+            .unwrap();
+        assert_eq!(result.repay_amount, 340);
+        assert_eq!(result.withdraw_amount, 17);
+
+        // the soft bonus cannot exceed the liquidation bonus
+        reserve.config.liquidation_threshold = 1;
+        assert_eq!(reserve.config.validate(), Ok(()));
+        reserve.config.soft_liquidation_bonus = 11;
+        assert_eq!(
+            reserve.config.validate(),
+            Err(LendingError::InvalidConfig.into())
+        );
+        reserve.config.soft_liquidation_bonus = 5;
+        reserve.config.soft_liquidation_band = 101;
+        assert_eq!(
+            reserve.config.validate(),
+            Err(LendingError::InvalidConfig.into())
+        );
+    }
+
+    #[test]
+    fn validate_oracle_config() {
+        let config = ReserveConfig {
+            liquidation_threshold: 1,
+            oracle: ReserveOracleConfig {
+                max_confidence_bps: 10_000,
+                ..ReserveOracleConfig::default()
+            },
+            ..ReserveConfig::default()
+        };
+        assert_eq!(config.validate(), Ok(()));
+        let config = ReserveConfig {
+            oracle: ReserveOracleConfig {
+                max_confidence_bps: 10_001,
+                ..ReserveOracleConfig::default()
+            },
+            ..config
+        };
+        assert_eq!(config.validate(), Err(LendingError::InvalidConfig.into()));
+
+        // token-swap prices must have a bounded confidence interval
+        let oracle = ReserveOracleConfig::default();
+        assert_eq!(oracle.validate_for(OracleKind::Pyth), Ok(()));
+        assert_eq!(oracle.validate_for(OracleKind::Switchboard), Ok(()));
+        assert_eq!(
+            oracle.validate_for(OracleKind::TokenSwapTwap),
+            Err(LendingError::InvalidConfig.into())
+        );
+        let oracle = ReserveOracleConfig {
+            max_confidence_bps: 100,
+            ..oracle
+        };
+        assert_eq!(oracle.validate_for(OracleKind::TokenSwapTwap), Ok(()));
+    }
+
+    fn test_rate_curve() -> RateCurve {
+        RateCurve::new(&[
+            RateCurvePoint {
+                utilization_bps: 0,
+                borrow_rate_bps: 100,
+            },
+            RateCurvePoint {
+                utilization_bps: 8_000,
+                borrow_rate_bps: 500,
+            },
+            RateCurvePoint {
+                utilization_bps: 9_000,
+                borrow_rate_bps: 1_500,
+            },
+            RateCurvePoint {
+                utilization_bps: 10_000,
+                borrow_rate_bps: 30_000,
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn rate_curve_borrow_rate() {
+        let curve = test_rate_curve();
+        assert_eq!(curve.borrow_rate(Rate::zero()), Ok(Rate::from_bps(100)));
+        assert_eq!(
+            curve.borrow_rate(Rate::from_bps(4_000)),
+            Ok(Rate::from_bps(300))
+        );
+        assert_eq!(
+            curve.borrow_rate(Rate::from_bps(8_000)),
+            Ok(Rate::from_bps(500))
+        );
+        assert_eq!(
+            curve.borrow_rate(Rate::from_bps(8_500)),
+            Ok(Rate::from_bps(1_000))
+        );
+        assert_eq!(
+            curve.borrow_rate(Rate::from_bps(9_500)),
+            Ok(Rate::from_bps(15_750))
+        );
+        assert_eq!(curve.borrow_rate(Rate::one()), Ok(Rate::from_bps(30_000)));
+
+        // the curve replaces the legacy rates when set
+        let reserve = Reserve {
+            liquidity: ReserveLiquidity {
+                available_amount: 150,
+                borrowed_amount_wads: Decimal::from(850u64),
+                ..ReserveLiquidity::default()
+            },
+            config: ReserveConfig {
+                rate_curve: curve,
+                ..ReserveConfig::default()
+            },
+            ..Reserve::default()
+        };
+        assert_eq!(reserve.current_borrow_rate(), Ok(Rate::from_bps(1_000)));
+    }
+
+    #[test]
+    fn validate_rate_curve() {
+        let point = |utilization_bps, borrow_rate_bps| RateCurvePoint {
+            utilization_bps,
+            borrow_rate_bps,
+        };
+        assert_eq!(test_rate_curve().validate(), Ok(()));
+        assert_eq!(
+            RateCurve::new(&[point(0, 0), point(10_000, MAX_RATE_CURVE_BORROW_RATE_BPS)])
+                .unwrap()
+                .validate(),
+            Ok(())
+        );
+        assert!(RateCurve::new(&[point(0, 0); MAX_RATE_CURVE_POINTS + 1]).is_err());
+
+        for points in [
+            // too few points
+            &[point(0, 100)][..],
+            // not starting at 0%
+            &[point(100, 0), point(10_000, 100)][..],
+            // not ending at 100%
+            &[point(0, 0), point(9_000, 100)][..],
+            // utilization not increasing
+            &[
+                point(0, 0),
+                point(5_000, 100),
+                point(5_000, 200),
+                point(10_000, 300),
+            ][..],
+            // rate decreasing
+            &[point(0, 0), point(5_000, 200), point(10_000, 100)][..],
+            // rate too high
+            &[
+                point(0, 0),
+                point(10_000, MAX_RATE_CURVE_BORROW_RATE_BPS + 1),
+            ][..],
+        ] {
+            assert_eq!(
+                RateCurve::new(points).unwrap().validate(),
+                Err(LendingError::InvalidConfig.into())
+            );
+        }
+
+        let config = ReserveConfig {
+            liquidation_threshold: 1,
+            rate_curve: RateCurve::new(&[point(0, 0), point(9_000, 100)]).unwrap(),
+            ..ReserveConfig::default()
+        };
+        assert_eq!(config.validate(), Err(LendingError::InvalidConfig.into()));
+    }
 }
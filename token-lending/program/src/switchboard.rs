@@ -0,0 +1,151 @@
+//! Reader for Switchboard V2 style aggregator accounts
+//!
+//! Only the fields needed to price a reserve are read, at fixed offsets in the
+//! account data:
+//!
+//! | Offset | Field                                    |
+//! |--------|------------------------------------------|
+//! | 0      | account discriminator, 8 bytes           |
+//! | 236    | `min_oracle_results`, u32                |
+//! | 341    | `latest_confirmed_round.num_success`, u32|
+//! | 350    | `latest_confirmed_round.round_open_slot` |
+//! | 366    | `latest_confirmed_round.result`          |
+//! | 386    | `latest_confirmed_round.std_deviation`   |
+//!
+//! Decimal values are stored as an `i128` mantissa followed by a `u32` scale,
+//! such that the value is `mantissa / 10^scale`.
+
+use {
+    arrayref::{array_mut_ref, array_ref},
+    solana_program::{clock::Slot, program_error::ProgramError},
+};
+
+/// Discriminator of aggregator accounts
+pub const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+/// Minimum length of the data of an aggregator account read by this module
+pub const AGGREGATOR_LEN: usize = 406;
+
+const MIN_ORACLE_RESULTS_OFFSET: usize = 236;
+const LATEST_ROUND_OFFSET: usize = 341;
+const NUM_SUCCESS_OFFSET: usize = LATEST_ROUND_OFFSET;
+const ROUND_OPEN_SLOT_OFFSET: usize = LATEST_ROUND_OFFSET + 9;
+const RESULT_OFFSET: usize = LATEST_ROUND_OFFSET + 25;
+const STD_DEVIATION_OFFSET: usize = LATEST_ROUND_OFFSET + 45;
+
+/// Decimal number made of a mantissa and a base 10 scale
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SwitchboardDecimal {
+    /// Mantissa
+    pub mantissa: i128,
+    /// Number of decimal places of the mantissa
+    pub scale: u32,
+}
+
+impl SwitchboardDecimal {
+    fn unpack(input: &[u8; 20]) -> Self {
+        Self {
+            mantissa: i128::from_le_bytes(*array_ref![input, 0, 16]),
+            scale: u32::from_le_bytes(*array_ref![input, 16, 4]),
+        }
+    }
+
+    fn pack(&self, output: &mut [u8; 20]) {
+        array_mut_ref![output, 0, 16].copy_from_slice(&self.mantissa.to_le_bytes());
+        array_mut_ref![output, 16, 4].copy_from_slice(&self.scale.to_le_bytes());
+    }
+}
+
+/// Fields of an aggregator account needed to price a reserve
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Aggregator {
+    /// Minimum number of oracle responses for a round to be valid
+    pub min_oracle_results: u32,
+    /// Number of oracle responses in the latest confirmed round
+    pub num_success: u32,
+    /// Slot at which the latest confirmed round was opened
+    pub round_open_slot: Slot,
+    /// Result of the latest confirmed round
+    pub result: SwitchboardDecimal,
+    /// Standard deviation of the oracle responses of the latest confirmed
+    /// round
+    pub std_deviation: SwitchboardDecimal,
+}
+
+impl Aggregator {
+    /// Read an aggregator from account data
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < AGGREGATOR_LEN || input[..8] != AGGREGATOR_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            min_oracle_results: u32::from_le_bytes(*array_ref![
+                input,
+                MIN_ORACLE_RESULTS_OFFSET,
+                4
+            ]),
+            num_success: u32::from_le_bytes(*array_ref![input, NUM_SUCCESS_OFFSET, 4]),
+            round_open_slot: u64::from_le_bytes(*array_ref![input, ROUND_OPEN_SLOT_OFFSET, 8]),
+            result: SwitchboardDecimal::unpack(array_ref![input, RESULT_OFFSET, 20]),
+            std_deviation: SwitchboardDecimal::unpack(array_ref![input, STD_DEVIATION_OFFSET, 20]),
+        })
+    }
+
+    /// Write an aggregator into account data, leaving the fields not read by
+    /// this module untouched. Useful to create aggregator accounts in tests.
+    pub fn pack(&self, output: &mut [u8]) -> Result<(), ProgramError> {
+        if output.len() < AGGREGATOR_LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        output[..8].copy_from_slice(&AGGREGATOR_DISCRIMINATOR);
+        array_mut_ref![output, MIN_ORACLE_RESULTS_OFFSET, 4]
+            .copy_from_slice(&self.min_oracle_results.to_le_bytes());
+        array_mut_ref![output, NUM_SUCCESS_OFFSET, 4]
+            .copy_from_slice(&self.num_success.to_le_bytes());
+        array_mut_ref![output, ROUND_OPEN_SLOT_OFFSET, 8]
+            .copy_from_slice(&self.round_open_slot.to_le_bytes());
+        self.result.pack(array_mut_ref![output, RESULT_OFFSET, 20]);
+        self.std_deviation
+            .pack(array_mut_ref![output, STD_DEVIATION_OFFSET, 20]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pack_unpack() {
+        let aggregator = Aggregator {
+            min_oracle_results: 3,
+            num_success: 4,
+            round_open_slot: 1_234,
+            result: SwitchboardDecimal {
+                mantissa: 21_345,
+                scale: 3,
+            },
+            std_deviation: SwitchboardDecimal {
+                mantissa: 12,
+                scale: 2,
+            },
+        };
+        let mut data = vec![0; AGGREGATOR_LEN];
+        aggregator.pack(&mut data).unwrap();
+        assert_eq!(Aggregator::unpack(&data).unwrap(), aggregator);
+    }
+
+    #[test]
+    fn unpack_invalid() {
+        let data = vec![0; AGGREGATOR_LEN];
+        assert_eq!(
+            Aggregator::unpack(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut data = vec![0; AGGREGATOR_LEN];
+        Aggregator::default().pack(&mut data).unwrap();
+        assert_eq!(
+            Aggregator::unpack(&data[..AGGREGATOR_LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
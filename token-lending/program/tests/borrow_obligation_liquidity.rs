@@ -36,7 +36,7 @@ async fn test_borrow_usdc_fixed_amount() {
     const FEE_AMOUNT: u64 = 100;
     const HOST_FEE_AMOUNT: u64 = 20;
 
-    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_MLN * INITIAL_COLLATERAL_RATIO;
     const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = USDC_TOTAL_BORROW_FRACTIONAL - FEE_AMOUNT;
     const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
     const USDC_RESERVE_LIQUIDITY_FRACTIONAL: u64 = 2 * USDC_TOTAL_BORROW_FRACTIONAL;
@@ -47,7 +47,7 @@ async fn test_borrow_usdc_fixed_amount() {
     let mut reserve_config = TEST_RESERVE_CONFIG;
     reserve_config.loan_to_value_ratio = 50;
 
-    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_oracle = add_mln_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
         &lending_market,
@@ -185,7 +185,7 @@ async fn test_borrow_sol_max_amount() {
 
     const USDC_DEPOSIT_AMOUNT_FRACTIONAL: u64 =
         2_000 * FRACTIONAL_TO_USDC * INITIAL_COLLATERAL_RATIO;
-    const SOL_BORROW_AMOUNT_LAMPORTS: u64 = 50 * LAMPORTS_TO_SOL;
+    const SOL_BORROW_AMOUNT_LAMPORTS: u64 = 50 * LAMPORTS_TO_MLN;
     const USDC_RESERVE_COLLATERAL_FRACTIONAL: u64 = 2 * USDC_DEPOSIT_AMOUNT_FRACTIONAL;
     const SOL_RESERVE_LIQUIDITY_LAMPORTS: u64 = 2 * SOL_BORROW_AMOUNT_LAMPORTS;
 
@@ -212,7 +212,7 @@ async fn test_borrow_sol_max_amount() {
         },
     );
 
-    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_oracle = add_mln_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
         &lending_market,
@@ -319,7 +319,7 @@ async fn test_borrow_too_large() {
         processor!(process_instruction),
     );
 
-    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_MLN * INITIAL_COLLATERAL_RATIO;
     const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = 1_000 * FRACTIONAL_TO_USDC + 1;
     const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
     const USDC_RESERVE_LIQUIDITY_FRACTIONAL: u64 = 2 * USDC_BORROW_AMOUNT_FRACTIONAL;
@@ -330,7 +330,7 @@ async fn test_borrow_too_large() {
     let mut reserve_config = TEST_RESERVE_CONFIG;
     reserve_config.loan_to_value_ratio = 50;
 
-    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_oracle = add_mln_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
         &lending_market,
@@ -431,7 +431,7 @@ async fn test_borrow_max_receive_minimum() {
 
     const USDC_DEPOSIT_AMOUNT_FRACTIONAL: u64 =
         2_000 * FRACTIONAL_TO_USDC * INITIAL_COLLATERAL_RATIO;
-    const SOL_BORROW_AMOUNT_LAMPORTS: u64 = 50 * LAMPORTS_TO_SOL;
+    const SOL_BORROW_AMOUNT_LAMPORTS: u64 = 50 * LAMPORTS_TO_MLN;
     const USDC_RESERVE_COLLATERAL_FRACTIONAL: u64 = 2 * USDC_DEPOSIT_AMOUNT_FRACTIONAL;
     const SOL_RESERVE_LIQUIDITY_LAMPORTS: u64 = 2 * SOL_BORROW_AMOUNT_LAMPORTS;
     const SLIPPAGE_LIMIT: u64 = SOL_BORROW_AMOUNT_LAMPORTS - FEE_AMOUNT;
@@ -459,7 +459,7 @@ async fn test_borrow_max_receive_minimum() {
         },
     );
 
-    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_oracle = add_mln_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
         &lending_market,
@@ -573,7 +573,7 @@ async fn test_borrow_max_receive_less_than_slippage() {
 
     const USDC_DEPOSIT_AMOUNT_FRACTIONAL: u64 =
         2_000 * FRACTIONAL_TO_USDC * INITIAL_COLLATERAL_RATIO;
-    const SOL_BORROW_AMOUNT_LAMPORTS: u64 = 50 * LAMPORTS_TO_SOL;
+    const SOL_BORROW_AMOUNT_LAMPORTS: u64 = 50 * LAMPORTS_TO_MLN;
     const USDC_RESERVE_COLLATERAL_FRACTIONAL: u64 = 2 * USDC_DEPOSIT_AMOUNT_FRACTIONAL;
     const SOL_RESERVE_LIQUIDITY_LAMPORTS: u64 = 2 * SOL_BORROW_AMOUNT_LAMPORTS;
     const SLIPPAGE_LIMIT: u64 = SOL_BORROW_AMOUNT_LAMPORTS - FEE_AMOUNT + 1;
@@ -601,7 +601,7 @@ async fn test_borrow_max_receive_less_than_slippage() {
         },
     );
 
-    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_oracle = add_mln_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
         &lending_market,
@@ -678,7 +678,7 @@ async fn test_borrow_less_than_max_with_slippage() {
     const USDC_TOTAL_BORROW_FRACTIONAL: u64 = 1_000 * FRACTIONAL_TO_USDC;
     const FEE_AMOUNT: u64 = 100;
 
-    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_SOL * INITIAL_COLLATERAL_RATIO;
+    const SOL_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_MLN * INITIAL_COLLATERAL_RATIO;
     const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = USDC_TOTAL_BORROW_FRACTIONAL - FEE_AMOUNT;
     const SOL_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * SOL_DEPOSIT_AMOUNT_LAMPORTS;
     const USDC_RESERVE_LIQUIDITY_FRACTIONAL: u64 = 2 * USDC_TOTAL_BORROW_FRACTIONAL;
@@ -690,7 +690,7 @@ async fn test_borrow_less_than_max_with_slippage() {
     let mut reserve_config = TEST_RESERVE_CONFIG;
     reserve_config.loan_to_value_ratio = 50;
 
-    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_oracle = add_mln_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
         &lending_market,
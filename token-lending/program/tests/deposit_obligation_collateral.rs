@@ -7,13 +7,14 @@ use {
     helpers::*,
     solana_program_test::*,
     solana_sdk::{
+        instruction::InstructionError,
         signature::{Keypair, Signer},
-        transaction::Transaction,
+        transaction::{Transaction, TransactionError},
     },
     spl_token::instruction::approve,
     spl_token_lending::{
-        instruction::deposit_obligation_collateral, processor::process_instruction,
-        state::INITIAL_COLLATERAL_RATIO,
+        error::LendingError, instruction::deposit_obligation_collateral,
+        processor::process_instruction, state::INITIAL_COLLATERAL_RATIO,
     },
 };
 
@@ -36,7 +37,7 @@ async fn test_success() {
 
     let lending_market = add_lending_market(&mut test);
 
-    let sol_oracle = add_sol_oracle(&mut test);
+    let sol_oracle = add_mln_oracle(&mut test);
     let sol_test_reserve = add_reserve(
         &mut test,
         &lending_market,
@@ -115,3 +116,114 @@ async fn test_success() {
         initial_user_collateral_balance - MLN_DEPOSIT_AMOUNT_LAMPORTS
     );
 }
+
+#[tokio::test]
+async fn test_isolated_collateral() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    const MLN_DEPOSIT_AMOUNT_LAMPORTS: u64 = 10 * LAMPORTS_TO_MLN * INITIAL_COLLATERAL_RATIO;
+    const MLN_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * MLN_DEPOSIT_AMOUNT_LAMPORTS;
+    const USDC_DEPOSIT_AMOUNT_FRACTIONAL: u64 =
+        1_000 * FRACTIONAL_TO_USDC * INITIAL_COLLATERAL_RATIO;
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+
+    let lending_market = add_lending_market(&mut test);
+
+    let mut isolated_config = TEST_RESERVE_CONFIG;
+    isolated_config.isolated = true;
+
+    let sol_oracle = add_mln_oracle(&mut test);
+    let sol_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            user_liquidity_amount: MLN_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_amount: MLN_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: isolated_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let usdc_mint = add_usdc_mint(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let usdc_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: USDC_DEPOSIT_AMOUNT_FRACTIONAL,
+            liquidity_amount: USDC_DEPOSIT_AMOUNT_FRACTIONAL,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            config: TEST_RESERVE_CONFIG,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&usdc_test_reserve, USDC_DEPOSIT_AMOUNT_FRACTIONAL)],
+            ..AddObligationArgs::default()
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &sol_test_reserve.user_collateral_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                MLN_DEPOSIT_AMOUNT_LAMPORTS,
+            )
+            .unwrap(),
+            deposit_obligation_collateral(
+                spl_token_lending::id(),
+                MLN_DEPOSIT_AMOUNT_LAMPORTS,
+                sol_test_reserve.user_collateral_pubkey,
+                sol_test_reserve.collateral_supply_pubkey,
+                sol_test_reserve.pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                test_obligation.owner,
+                user_transfer_authority.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(
+        &vec![&payer, &user_accounts_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::IsolatedCollateral as u32)
+        )
+    );
+}
@@ -1,6 +1,9 @@
 use {
     crate::helpers::flash_loan_receiver::FlashLoanReceiverError::InvalidInstruction,
     solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg, pubkey::Pubkey},
+    spl_flash_loan_receiver_interface::instruction::{
+        FlashLoanAmount, FlashLoanReceiverInstruction as InterfaceInstruction, ACCOUNTS_PER_LOAN,
+    },
     spl_token::{
         solana_program::{
             account_info::next_account_info, program::invoke_signed, program_error::ProgramError,
@@ -29,6 +32,16 @@ pub enum FlashLoanReceiverInstruction {
         /// The amount that is loaned
         amount: u64,
     },
+    /// Receive a multi-reserve flash loan through the flash loan receiver
+    /// interface, and return each loan with its fee.
+    ///
+    /// Accounts expected: the accounts of each loan defined by the interface,
+    /// followed by the program derived account owning the destination
+    /// liquidity accounts.
+    ReceiveMultiReserveFlashLoan {
+        /// The amount and fee of each loan
+        loans: Vec<FlashLoanAmount>,
+    },
 }
 
 solana_program::entrypoint!(process_instruction);
@@ -54,7 +67,66 @@ impl Processor {
                 msg!("Instruction: Receive Flash Loan");
                 Self::process_receive_flash_loan(accounts, amount, program_id)
             }
+            FlashLoanReceiverInstruction::ReceiveMultiReserveFlashLoan { loans } => {
+                msg!("Instruction: Receive Multi-Reserve Flash Loan");
+                Self::process_receive_multi_reserve_flash_loan(accounts, loans, program_id)
+            }
+        }
+    }
+
+    fn process_receive_multi_reserve_flash_loan(
+        accounts: &[AccountInfo],
+        loans: Vec<FlashLoanAmount>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let loan_accounts_len = loans.len() * ACCOUNTS_PER_LOAN;
+        if accounts.len() <= loan_accounts_len {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (loan_accounts, additional_accounts) = accounts.split_at(loan_accounts_len);
+        let program_derived_account_info = &additional_accounts[0];
+
+        let (expected_program_derived_account_pubkey, bump_seed) =
+            Pubkey::find_program_address(&[b"flashloan"], program_id);
+        if &expected_program_derived_account_pubkey != program_derived_account_info.key {
+            msg!("Supplied program derived account doesn't match with expectation.");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        for (loan, accounts) in loans.iter().zip(loan_accounts.chunks(ACCOUNTS_PER_LOAN)) {
+            let source_liquidity_token_account_info = &accounts[0];
+            let destination_liquidity_token_account_info = &accounts[1];
+            let token_program_id = &accounts[3];
+
+            let source_liquidity_token_account = Account::unpack_from_slice(
+                &source_liquidity_token_account_info.try_borrow_data()?,
+            )?;
+            if source_liquidity_token_account.owner != expected_program_derived_account_pubkey {
+                msg!("Destination liquidity token account is not owned by the program");
+                return Err(ProgramError::IncorrectProgramId);
+            }
+
+            let transfer_ix = spl_token::instruction::transfer(
+                token_program_id.key,
+                source_liquidity_token_account_info.key,
+                destination_liquidity_token_account_info.key,
+                &expected_program_derived_account_pubkey,
+                &[],
+                min(source_liquidity_token_account.amount, loan.repay_amount()?),
+            )?;
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    source_liquidity_token_account_info.clone(),
+                    destination_liquidity_token_account_info.clone(),
+                    program_derived_account_info.clone(),
+                    token_program_id.clone(),
+                ],
+                &[&[&b"flashloan"[..], &[bump_seed]]],
+            )?;
         }
+
+        Ok(())
     }
 
     fn process_receive_flash_loan(
@@ -112,6 +184,11 @@ impl Processor {
 
 impl FlashLoanReceiverInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if let Ok(InterfaceInstruction::ReceiveFlashLoan { loans }) =
+            InterfaceInstruction::unpack(input)
+        {
+            return Ok(Self::ReceiveMultiReserveFlashLoan { loans });
+        }
         let (tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
 
         Ok(match tag {
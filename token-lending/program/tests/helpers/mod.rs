@@ -4,7 +4,7 @@ pub mod flash_loan_receiver;
 
 use {
     assert_matches::*,
-    solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey},
+    solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey, rent::Rent},
     solana_program_test::*,
     solana_sdk::{
         account::Account,
@@ -16,19 +16,23 @@ use {
         instruction::approve,
         state::{Account as Token, AccountState, Mint},
     },
+    spl_token_2022::extension::StateWithExtensions,
     spl_token_lending::{
         instruction::{
             borrow_obligation_liquidity, deposit_reserve_liquidity, init_lending_market,
             init_obligation, init_reserve, liquidate_obligation, refresh_reserve,
         },
         math::{Decimal, Rate, TryAdd, TryMul},
+        oracle::OracleKind,
         pyth,
         state::{
             InitLendingMarketParams, InitObligationParams, InitReserveParams, LendingMarket,
             NewReserveCollateralParams, NewReserveLiquidityParams, Obligation,
-            ObligationCollateral, ObligationLiquidity, Reserve, ReserveCollateral, ReserveConfig,
-            ReserveFees, ReserveLiquidity, INITIAL_COLLATERAL_RATIO, PROGRAM_VERSION,
+            ObligationCollateral, ObligationLiquidity, RateCurve, Reserve, ReserveCollateral,
+            ReserveConfig, ReserveFees, ReserveLiquidity, ReserveOracleConfig,
+            INITIAL_COLLATERAL_RATIO, PROGRAM_VERSION,
         },
+        switchboard,
     },
     std::{convert::TryInto, str::FromStr},
 };
@@ -36,7 +40,7 @@ use {
 pub const QUOTE_CURRENCY: [u8; 32] =
     *b"USD\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
 
-pub const LAMPORTS_TO_SOL: u64 = 1_000_000_000;
+pub const LAMPORTS_TO_MLN: u64 = 1_000_000_000;
 pub const FRACTIONAL_TO_USDC: u64 = 1_000_000;
 
 pub const TEST_RESERVE_CONFIG: ReserveConfig = ReserveConfig {
@@ -54,6 +58,17 @@ pub const TEST_RESERVE_CONFIG: ReserveConfig = ReserveConfig {
         flash_loan_fee_wad: 3_000_000_000_000_000,
         host_fee_percentage: 20,
     },
+    oracle: ReserveOracleConfig {
+        max_staleness_slots: 0,
+        max_confidence_bps: 0,
+        twap_window_seconds: 0,
+    },
+    deposit_limit: 0,
+    borrow_limit: 0,
+    isolated: false,
+    rate_curve: RateCurve::empty(),
+    soft_liquidation_band: 0,
+    soft_liquidation_bonus: 0,
 };
 
 pub const SOL_PYTH_PRODUCT: &str = "3Mnn2fX6rQyUsyELYms1sBJyChWofzSNRoqYzvgMVz5E";
@@ -345,12 +360,15 @@ pub fn add_reserve(
             fee_receiver: liquidity_fee_receiver_pubkey,
             oracle_pubkey: oracle.price_pubkey,
             market_price: oracle.price,
+            oracle_kind: oracle.kind,
+            oracle_quote_decimals: 0,
         }),
         collateral: ReserveCollateral::new(NewReserveCollateralParams {
             mint_pubkey: collateral_mint_pubkey,
             supply_pubkey: collateral_supply_pubkey,
         }),
         config,
+        token_program_id: spl_token::id(),
     });
     reserve.deposit_liquidity(liquidity_amount).unwrap();
     reserve.liquidity.borrow(borrow_amount.into()).unwrap();
@@ -430,16 +448,25 @@ pub fn add_account_for_program(
     amount: u64,
     mint_pubkey: &Pubkey,
 ) -> Pubkey {
+    // a native account holds its token amount as lamports, on top of its rent
+    // exemption
+    let (lamports, is_native) = if *mint_pubkey == spl_token::native_mint::id() {
+        let rent_reserve = Rent::default().minimum_balance(Token::LEN);
+        (amount + rent_reserve, COption::Some(rent_reserve))
+    } else {
+        (u32::MAX as u64, COption::None)
+    };
+
     let program_owned_token_account = Keypair::new();
     test.add_packable_account(
         program_owned_token_account.pubkey(),
-        u32::MAX as u64,
+        lamports,
         &Token {
             mint: *mint_pubkey,
             owner: *program_derived_account,
             amount,
             state: AccountState::Initialized,
-            is_native: COption::None,
+            is_native,
             ..Token::default()
         },
         &spl_token::id(),
@@ -1064,12 +1091,13 @@ pub fn add_usdc_mint(test: &mut ProgramTest) -> TestMint {
 }
 
 pub struct TestOracle {
+    pub kind: OracleKind,
     pub product_pubkey: Pubkey,
     pub price_pubkey: Pubkey,
     pub price: Decimal,
 }
 
-pub fn add_sol_oracle(test: &mut ProgramTest) -> TestOracle {
+pub fn add_mln_oracle(test: &mut ProgramTest) -> TestOracle {
     add_oracle(
         test,
         Pubkey::from_str(SOL_PYTH_PRODUCT).unwrap(),
@@ -1139,12 +1167,54 @@ pub fn add_oracle(
     );
 
     TestOracle {
+        kind: OracleKind::Pyth,
         product_pubkey,
         price_pubkey,
         price,
     }
 }
 
+pub fn add_switchboard_oracle(
+    test: &mut ProgramTest,
+    price: Decimal,
+    round_open_slot: u64,
+) -> TestOracle {
+    let switchboard_program_id = Pubkey::new_unique();
+    let aggregator_pubkey = Pubkey::new_unique();
+
+    let mut data = vec![0; switchboard::AGGREGATOR_LEN];
+    switchboard::Aggregator {
+        min_oracle_results: 1,
+        num_success: 1,
+        round_open_slot,
+        result: switchboard::SwitchboardDecimal {
+            mantissa: price.to_scaled_val().unwrap() as i128,
+            scale: 18,
+        },
+        std_deviation: switchboard::SwitchboardDecimal::default(),
+    }
+    .pack(&mut data)
+    .unwrap();
+
+    test.add_account(
+        aggregator_pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data,
+            owner: switchboard_program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    TestOracle {
+        kind: OracleKind::Switchboard,
+        product_pubkey: switchboard_program_id,
+        price_pubkey: aggregator_pubkey,
+        price,
+    }
+}
+
 pub async fn create_and_mint_to_token_account(
     banks_client: &mut BanksClient,
     mint_pubkey: Pubkey,
@@ -1251,7 +1321,8 @@ pub async fn mint_to(
 pub async fn get_token_balance(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
     let token: Account = banks_client.get_account(pubkey).await.unwrap().unwrap();
 
-    spl_token::state::Account::unpack(&token.data[..])
+    StateWithExtensions::<spl_token_2022::state::Account>::unpack(&token.data[..])
         .unwrap()
+        .base
         .amount
 }
@@ -17,8 +17,8 @@ use {
         instruction::modify_reserve_config,
         processor::process_instruction,
         state::{
-            InitLendingMarketParams, LendingMarket, ReserveConfig, ReserveFees,
-            INITIAL_COLLATERAL_RATIO,
+            InitLendingMarketParams, LendingMarket, RateCurve, RateCurvePoint, ReserveConfig,
+            ReserveFees, ReserveOracleConfig, INITIAL_COLLATERAL_RATIO,
         },
     },
 };
@@ -74,6 +74,31 @@ async fn modify_reserve_config_success() {
             flash_loan_fee_wad: 3_000_000_000_000_000,
             host_fee_percentage: 20,
         },
+        oracle: ReserveOracleConfig::default(),
+        deposit_limit: 0,
+        borrow_limit: 0,
+        isolated: false,
+        rate_curve: RateCurve::new(&[
+            RateCurvePoint {
+                utilization_bps: 0,
+                borrow_rate_bps: 0,
+            },
+            RateCurvePoint {
+                utilization_bps: 8_000,
+                borrow_rate_bps: 400,
+            },
+            RateCurvePoint {
+                utilization_bps: 9_500,
+                borrow_rate_bps: 1_500,
+            },
+            RateCurvePoint {
+                utilization_bps: 10_000,
+                borrow_rate_bps: 10_000,
+            },
+        ])
+        .unwrap(),
+        soft_liquidation_band: 10,
+        soft_liquidation_bonus: 3,
     };
 
     let mut transaction = Transaction::new_with_payer(
@@ -155,6 +180,13 @@ async fn wrong_signer_of_lending_market_cannot_change_reserve_config() {
             flash_loan_fee_wad: 3_000_000_000_000_000,
             host_fee_percentage: 20,
         },
+        oracle: ReserveOracleConfig::default(),
+        deposit_limit: 0,
+        borrow_limit: 0,
+        isolated: false,
+        rate_curve: RateCurve::empty(),
+        soft_liquidation_band: 0,
+        soft_liquidation_bonus: 0,
     };
 
     let mut instruction = modify_reserve_config(
@@ -269,6 +301,13 @@ async fn owner_of_different_lending_market_cannot_change_reserve_config() {
             flash_loan_fee_wad: 3_000_000_000_000_000,
             host_fee_percentage: 20,
         },
+        oracle: ReserveOracleConfig::default(),
+        deposit_limit: 0,
+        borrow_limit: 0,
+        isolated: false,
+        rate_curve: RateCurve::empty(),
+        soft_liquidation_band: 0,
+        soft_liquidation_bonus: 0,
     };
 
     let mut transaction = Transaction::new_with_payer(
@@ -359,6 +398,13 @@ async fn correct_owner_providing_wrong_lending_market_fails() {
             flash_loan_fee_wad: 3_000_000_000_000_000,
             host_fee_percentage: 20,
         },
+        oracle: ReserveOracleConfig::default(),
+        deposit_limit: 0,
+        borrow_limit: 0,
+        isolated: false,
+        rate_curve: RateCurve::empty(),
+        soft_liquidation_band: 0,
+        soft_liquidation_bonus: 0,
     };
 
     let mut transaction = Transaction::new_with_payer(
@@ -392,3 +438,77 @@ async fn correct_owner_providing_wrong_lending_market_fails() {
     let reserve_info = mln_test_reserve.get_state(&mut banks_client).await;
     assert_eq!(reserve_info.config, TEST_RESERVE_CONFIG);
 }
+
+#[tokio::test]
+async fn invalid_rate_curve_cannot_be_set() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let mln_oracle = add_mln_oracle(&mut test);
+
+    let mln_test_reserve = add_reserve(
+        &mut test,
+        &lending_market,
+        &mln_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: 10 * LAMPORTS_TO_MLN,
+            liquidity_mint_decimals: 9,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            config: TEST_RESERVE_CONFIG,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    // the curve must end at 100% utilization
+    let new_config = ReserveConfig {
+        rate_curve: RateCurve::new(&[
+            RateCurvePoint {
+                utilization_bps: 0,
+                borrow_rate_bps: 0,
+            },
+            RateCurvePoint {
+                utilization_bps: 8_000,
+                borrow_rate_bps: 400,
+            },
+        ])
+        .unwrap(),
+        ..TEST_RESERVE_CONFIG
+    };
+
+    let mut transaction = Transaction::new_with_payer(
+        &[modify_reserve_config(
+            spl_token_lending::id(),
+            new_config,
+            mln_test_reserve.pubkey,
+            lending_market.pubkey,
+            lending_market.owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(&[&payer, &lending_market.owner], recent_blockhash);
+
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidConfig as u32)
+        )
+    );
+
+    let reserve_info = mln_test_reserve.get_state(&mut banks_client).await;
+    assert_eq!(reserve_info.config, TEST_RESERVE_CONFIG);
+}
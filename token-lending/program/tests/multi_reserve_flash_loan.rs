@@ -0,0 +1,337 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program::instruction::AccountMeta,
+    solana_program_test::*,
+    solana_sdk::{
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::solana_program::instruction::InstructionError,
+    spl_token_lending::{
+        error::LendingError,
+        instruction::{multi_reserve_flash_loan, FlashLoanReserve},
+        processor::process_instruction,
+    },
+};
+
+const USDC_FLASH_LOAN_AMOUNT: u64 = 1_000 * FRACTIONAL_TO_USDC;
+const USDC_FEE_AMOUNT: u64 = 3_000_000;
+const USDC_HOST_FEE_AMOUNT: u64 = 600_000;
+const SOL_FLASH_LOAN_AMOUNT: u64 = 10 * LAMPORTS_TO_MLN;
+const SOL_FEE_AMOUNT: u64 = 30_000_000;
+const SOL_HOST_FEE_AMOUNT: u64 = 6_000_000;
+
+struct TestFlashLoan {
+    lending_market: TestLendingMarket,
+    receiver_program_id: Pubkey,
+    receiver_authority_pubkey: Pubkey,
+    usdc_test_reserve: TestReserve,
+    usdc_receiver_account: Pubkey,
+    sol_test_reserve: TestReserve,
+    sol_receiver_account: Pubkey,
+}
+
+fn setup(test: &mut ProgramTest, sol_receiver_balance: u64) -> TestFlashLoan {
+    let receiver_program_id = Keypair::new().pubkey();
+    test.prefer_bpf(false);
+    test.add_program(
+        "flash_loan_receiver",
+        receiver_program_id,
+        processor!(helpers::flash_loan_receiver::process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let lending_market = add_lending_market(test);
+
+    let mut reserve_config = TEST_RESERVE_CONFIG;
+    reserve_config.fees.flash_loan_fee_wad = 3_000_000_000_000_000;
+
+    let usdc_mint = add_usdc_mint(test);
+    let usdc_oracle = add_usdc_oracle(test);
+    let usdc_test_reserve = add_reserve(
+        test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: USDC_FLASH_LOAN_AMOUNT,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            config: reserve_config,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let sol_oracle = add_mln_oracle(test);
+    let sol_test_reserve = add_reserve(
+        test,
+        &lending_market,
+        &sol_oracle,
+        &user_accounts_owner,
+        AddReserveArgs {
+            liquidity_amount: SOL_FLASH_LOAN_AMOUNT,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: reserve_config,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let (receiver_authority_pubkey, _) =
+        Pubkey::find_program_address(&[b"flashloan"], &receiver_program_id);
+    let usdc_receiver_account = add_account_for_program(
+        test,
+        &receiver_authority_pubkey,
+        USDC_FEE_AMOUNT,
+        &usdc_mint.pubkey,
+    );
+    let sol_receiver_account = add_account_for_program(
+        test,
+        &receiver_authority_pubkey,
+        sol_receiver_balance,
+        &spl_token::native_mint::id(),
+    );
+
+    TestFlashLoan {
+        lending_market,
+        receiver_program_id,
+        receiver_authority_pubkey,
+        usdc_test_reserve,
+        usdc_receiver_account,
+        sol_test_reserve,
+        sol_receiver_account,
+    }
+}
+
+fn flash_loan_reserve(
+    test_reserve: &TestReserve,
+    destination_liquidity_pubkey: Pubkey,
+    liquidity_amount: u64,
+) -> FlashLoanReserve {
+    FlashLoanReserve {
+        reserve_pubkey: test_reserve.pubkey,
+        liquidity_supply_pubkey: test_reserve.liquidity_supply_pubkey,
+        destination_liquidity_pubkey,
+        liquidity_fee_receiver_pubkey: test_reserve.liquidity_fee_receiver_pubkey,
+        host_fee_receiver_pubkey: test_reserve.liquidity_host_pubkey,
+        liquidity_mint_pubkey: test_reserve.liquidity_mint_pubkey,
+        token_program_id: spl_token::id(),
+        liquidity_amount,
+    }
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    // limit to track compute unit increase
+    test.set_compute_max_units(100_000);
+
+    let flash_loan = setup(&mut test, SOL_FEE_AMOUNT);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[multi_reserve_flash_loan(
+            spl_token_lending::id(),
+            flash_loan.lending_market.pubkey,
+            flash_loan.receiver_program_id,
+            &[
+                flash_loan_reserve(
+                    &flash_loan.usdc_test_reserve,
+                    flash_loan.usdc_receiver_account,
+                    USDC_FLASH_LOAN_AMOUNT,
+                ),
+                flash_loan_reserve(
+                    &flash_loan.sol_test_reserve,
+                    flash_loan.sol_receiver_account,
+                    u64::MAX,
+                ),
+            ],
+            vec![AccountMeta::new_readonly(
+                flash_loan.receiver_authority_pubkey,
+                false,
+            )],
+        )],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(&[&payer], recent_blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    for (test_reserve, receiver_account, amount, fee, host_fee) in [
+        (
+            &flash_loan.usdc_test_reserve,
+            flash_loan.usdc_receiver_account,
+            USDC_FLASH_LOAN_AMOUNT,
+            USDC_FEE_AMOUNT,
+            USDC_HOST_FEE_AMOUNT,
+        ),
+        (
+            &flash_loan.sol_test_reserve,
+            flash_loan.sol_receiver_account,
+            SOL_FLASH_LOAN_AMOUNT,
+            SOL_FEE_AMOUNT,
+            SOL_HOST_FEE_AMOUNT,
+        ),
+    ] {
+        let reserve = test_reserve.get_state(&mut banks_client).await;
+        assert_eq!(reserve.liquidity.available_amount, amount);
+        assert_eq!(
+            reserve
+                .config
+                .fees
+                .calculate_flash_loan_fees(amount.into())
+                .unwrap(),
+            (fee, host_fee)
+        );
+
+        assert_eq!(
+            get_token_balance(&mut banks_client, test_reserve.liquidity_supply_pubkey).await,
+            amount
+        );
+        assert_eq!(
+            get_token_balance(&mut banks_client, receiver_account).await,
+            0
+        );
+        assert_eq!(
+            get_token_balance(
+                &mut banks_client,
+                test_reserve.liquidity_fee_receiver_pubkey
+            )
+            .await,
+            fee - host_fee
+        );
+        assert_eq!(
+            get_token_balance(&mut banks_client, test_reserve.liquidity_host_pubkey).await,
+            host_fee
+        );
+    }
+
+    // the native reserve moves the fees as lamports
+    let sol_reserve = &flash_loan.sol_test_reserve;
+    assert_eq!(
+        banks_client
+            .get_balance(sol_reserve.liquidity_supply_pubkey)
+            .await
+            .unwrap(),
+        SOL_FLASH_LOAN_AMOUNT + 1
+    );
+    assert_eq!(
+        banks_client
+            .get_balance(sol_reserve.liquidity_fee_receiver_pubkey)
+            .await
+            .unwrap(),
+        u32::MAX as u64 + SOL_FEE_AMOUNT - SOL_HOST_FEE_AMOUNT
+    );
+    assert_eq!(
+        banks_client
+            .get_balance(sol_reserve.liquidity_host_pubkey)
+            .await
+            .unwrap(),
+        u32::MAX as u64 + SOL_HOST_FEE_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn test_failure() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let flash_loan = setup(&mut test, SOL_FEE_AMOUNT - 1);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[multi_reserve_flash_loan(
+            spl_token_lending::id(),
+            flash_loan.lending_market.pubkey,
+            flash_loan.receiver_program_id,
+            &[
+                flash_loan_reserve(
+                    &flash_loan.usdc_test_reserve,
+                    flash_loan.usdc_receiver_account,
+                    USDC_FLASH_LOAN_AMOUNT,
+                ),
+                flash_loan_reserve(
+                    &flash_loan.sol_test_reserve,
+                    flash_loan.sol_receiver_account,
+                    SOL_FLASH_LOAN_AMOUNT,
+                ),
+            ],
+            vec![AccountMeta::new_readonly(
+                flash_loan.receiver_authority_pubkey,
+                false,
+            )],
+        )],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::NotEnoughLiquidityAfterFlashLoan as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_duplicate_reserve() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let flash_loan = setup(&mut test, SOL_FEE_AMOUNT);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let usdc_flash_loan_reserve = flash_loan_reserve(
+        &flash_loan.usdc_test_reserve,
+        flash_loan.usdc_receiver_account,
+        USDC_FLASH_LOAN_AMOUNT / 2,
+    );
+    let mut transaction = Transaction::new_with_payer(
+        &[multi_reserve_flash_loan(
+            spl_token_lending::id(),
+            flash_loan.lending_market.pubkey,
+            flash_loan.receiver_program_id,
+            &[usdc_flash_loan_reserve, usdc_flash_loan_reserve],
+            vec![AccountMeta::new_readonly(
+                flash_loan.receiver_authority_pubkey,
+                false,
+            )],
+        )],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidAccountInput as u32)
+        )
+    );
+}
@@ -0,0 +1,319 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program_test::*,
+    solana_sdk::{
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token::instruction::approve,
+    spl_token_lending::{
+        error::LendingError,
+        instruction::{refresh_obligation, self_liquidate_obligation},
+        processor::process_instruction,
+        state::INITIAL_COLLATERAL_RATIO,
+    },
+};
+
+// 100 MLN collateral
+const MLN_DEPOSIT_AMOUNT_LAMPORTS: u64 = 100 * LAMPORTS_TO_MLN * INITIAL_COLLATERAL_RATIO;
+// 100 MLN * 20 USDC * 80% threshold -> 1600 USDC unhealthy, 1000 USDC borrow
+const USDC_BORROW_AMOUNT_FRACTIONAL: u64 = 1_000 * FRACTIONAL_TO_USDC;
+// 400 USDC repaid by the owner
+const USDC_REPAY_AMOUNT_FRACTIONAL: u64 = 400 * FRACTIONAL_TO_USDC;
+// 400 USDC / 20 USDC per MLN -> 20 MLN, without bonus
+const MLN_WITHDRAW_AMOUNT_LAMPORTS: u64 = 20 * LAMPORTS_TO_MLN * INITIAL_COLLATERAL_RATIO;
+// 40 MLN * 20 USDC -> 800 USDC collateral, under the 1000 USDC borrow
+const MLN_UNDERWATER_DEPOSIT_AMOUNT_LAMPORTS: u64 = 40 * LAMPORTS_TO_MLN * INITIAL_COLLATERAL_RATIO;
+
+const MLN_RESERVE_COLLATERAL_LAMPORTS: u64 = 2 * MLN_DEPOSIT_AMOUNT_LAMPORTS;
+const USDC_RESERVE_LIQUIDITY_FRACTIONAL: u64 = 2 * USDC_BORROW_AMOUNT_FRACTIONAL;
+
+fn setup(
+    test: &mut ProgramTest,
+    user_accounts_owner: &Keypair,
+    deposit_amount: u64,
+) -> (TestLendingMarket, TestReserve, TestReserve, TestObligation) {
+    let lending_market = add_lending_market(test);
+
+    let mut reserve_config = TEST_RESERVE_CONFIG;
+    reserve_config.loan_to_value_ratio = 50;
+    reserve_config.liquidation_threshold = 80;
+    reserve_config.liquidation_bonus = 10;
+
+    let mln_oracle = add_mln_oracle(test);
+    let mln_test_reserve = add_reserve(
+        test,
+        &lending_market,
+        &mln_oracle,
+        user_accounts_owner,
+        AddReserveArgs {
+            collateral_amount: MLN_RESERVE_COLLATERAL_LAMPORTS,
+            liquidity_mint_pubkey: spl_token::native_mint::id(),
+            liquidity_mint_decimals: 9,
+            config: reserve_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let usdc_mint = add_usdc_mint(test);
+    let usdc_oracle = add_usdc_oracle(test);
+    let usdc_test_reserve = add_reserve(
+        test,
+        &lending_market,
+        &usdc_oracle,
+        user_accounts_owner,
+        AddReserveArgs {
+            borrow_amount: USDC_BORROW_AMOUNT_FRACTIONAL,
+            user_liquidity_amount: USDC_BORROW_AMOUNT_FRACTIONAL,
+            liquidity_amount: USDC_RESERVE_LIQUIDITY_FRACTIONAL,
+            liquidity_mint_pubkey: usdc_mint.pubkey,
+            liquidity_mint_decimals: usdc_mint.decimals,
+            config: reserve_config,
+            mark_fresh: true,
+            ..AddReserveArgs::default()
+        },
+    );
+
+    let test_obligation = add_obligation(
+        test,
+        &lending_market,
+        user_accounts_owner,
+        AddObligationArgs {
+            deposits: &[(&mln_test_reserve, deposit_amount)],
+            borrows: &[(&usdc_test_reserve, USDC_BORROW_AMOUNT_FRACTIONAL)],
+            ..AddObligationArgs::default()
+        },
+    );
+
+    (
+        lending_market,
+        mln_test_reserve,
+        usdc_test_reserve,
+        test_obligation,
+    )
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    // limit to track compute unit increase
+    test.set_compute_max_units(70_000);
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+    let (lending_market, mln_test_reserve, usdc_test_reserve, test_obligation) =
+        setup(&mut test, &user_accounts_owner, MLN_DEPOSIT_AMOUNT_LAMPORTS);
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let initial_user_liquidity_balance =
+        get_token_balance(&mut banks_client, usdc_test_reserve.user_liquidity_pubkey).await;
+    let initial_user_collateral_balance =
+        get_token_balance(&mut banks_client, mln_test_reserve.user_collateral_pubkey).await;
+
+    // the obligation is healthy, the owner deleverages it anyway
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &usdc_test_reserve.user_liquidity_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                USDC_REPAY_AMOUNT_FRACTIONAL,
+            )
+            .unwrap(),
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![mln_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+            self_liquidate_obligation(
+                spl_token_lending::id(),
+                USDC_REPAY_AMOUNT_FRACTIONAL,
+                usdc_test_reserve.user_liquidity_pubkey,
+                mln_test_reserve.user_collateral_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                mln_test_reserve.pubkey,
+                mln_test_reserve.collateral_supply_pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                user_transfer_authority.pubkey(),
+                test_obligation.owner,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let user_liquidity_balance =
+        get_token_balance(&mut banks_client, usdc_test_reserve.user_liquidity_pubkey).await;
+    assert_eq!(
+        user_liquidity_balance,
+        initial_user_liquidity_balance - USDC_REPAY_AMOUNT_FRACTIONAL
+    );
+
+    let user_collateral_balance =
+        get_token_balance(&mut banks_client, mln_test_reserve.user_collateral_pubkey).await;
+    assert_eq!(
+        user_collateral_balance,
+        initial_user_collateral_balance + MLN_WITHDRAW_AMOUNT_LAMPORTS
+    );
+
+    let obligation = test_obligation.get_state(&mut banks_client).await;
+    assert_eq!(
+        obligation.deposits[0].deposited_amount,
+        MLN_DEPOSIT_AMOUNT_LAMPORTS - MLN_WITHDRAW_AMOUNT_LAMPORTS
+    );
+    assert_eq!(
+        obligation.borrows[0].borrowed_amount_wads,
+        (USDC_BORROW_AMOUNT_FRACTIONAL - USDC_REPAY_AMOUNT_FRACTIONAL).into()
+    )
+}
+
+#[tokio::test]
+async fn test_invalid_owner() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let (lending_market, mln_test_reserve, usdc_test_reserve, test_obligation) =
+        setup(&mut test, &user_accounts_owner, MLN_DEPOSIT_AMOUNT_LAMPORTS);
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    // a third party cannot self-liquidate someone else's obligation
+    let liquidator = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![mln_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+            self_liquidate_obligation(
+                spl_token_lending::id(),
+                USDC_REPAY_AMOUNT_FRACTIONAL,
+                usdc_test_reserve.user_liquidity_pubkey,
+                mln_test_reserve.user_collateral_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                mln_test_reserve.pubkey,
+                mln_test_reserve.collateral_supply_pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                user_accounts_owner.pubkey(),
+                liquidator.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &liquidator],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::InvalidObligationOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_health_decreased() {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+    let (lending_market, mln_test_reserve, usdc_test_reserve, test_obligation) = setup(
+        &mut test,
+        &user_accounts_owner,
+        MLN_UNDERWATER_DEPOSIT_AMOUNT_LAMPORTS,
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    // without a bonus, deleveraging an underwater obligation only makes it
+    // worse
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &usdc_test_reserve.user_liquidity_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                USDC_REPAY_AMOUNT_FRACTIONAL,
+            )
+            .unwrap(),
+            refresh_obligation(
+                spl_token_lending::id(),
+                test_obligation.pubkey,
+                vec![mln_test_reserve.pubkey, usdc_test_reserve.pubkey],
+            ),
+            self_liquidate_obligation(
+                spl_token_lending::id(),
+                USDC_REPAY_AMOUNT_FRACTIONAL,
+                usdc_test_reserve.user_liquidity_pubkey,
+                mln_test_reserve.user_collateral_pubkey,
+                usdc_test_reserve.pubkey,
+                usdc_test_reserve.liquidity_supply_pubkey,
+                mln_test_reserve.pubkey,
+                mln_test_reserve.collateral_supply_pubkey,
+                test_obligation.pubkey,
+                lending_market.pubkey,
+                user_transfer_authority.pubkey(),
+                test_obligation.owner,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(LendingError::ObligationHealthDecreased as u32)
+        )
+    );
+}
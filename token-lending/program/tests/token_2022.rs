@@ -0,0 +1,510 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![cfg(feature = "test-sbf")]
+
+mod helpers;
+
+use {
+    helpers::*,
+    solana_program::{program_option::COption, pubkey::Pubkey},
+    solana_program_test::*,
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_2022::{
+        extension::{
+            transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+            ExtensionType, StateWithExtensionsMut,
+        },
+        instruction::approve,
+        state::{Account as Token, AccountState, Mint},
+    },
+    spl_token_lending::{
+        error::LendingError,
+        instruction::{deposit_reserve_liquidity, repay_obligation_liquidity, with_token_program},
+        processor::process_instruction,
+        state::{
+            InitReserveParams, NewReserveCollateralParams, NewReserveLiquidityParams, Reserve,
+            ReserveCollateral, ReserveLiquidity,
+        },
+    },
+};
+
+const TRANSFER_FEE_BASIS_POINTS: u16 = 100;
+
+fn program_test() -> ProgramTest {
+    let mut test = ProgramTest::new(
+        "spl_token_lending",
+        spl_token_lending::id(),
+        processor!(process_instruction),
+    );
+    test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(spl_token_2022::processor::Processor::process),
+    );
+    test
+}
+
+fn transfer_fee() -> TransferFee {
+    TransferFee {
+        epoch: 0.into(),
+        maximum_fee: u64::MAX.into(),
+        transfer_fee_basis_points: TRANSFER_FEE_BASIS_POINTS.into(),
+    }
+}
+
+fn add_transfer_fee_mint(test: &mut ProgramTest, decimals: u8) -> Pubkey {
+    let mint_pubkey = Pubkey::new_unique();
+    let space =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let mut account = Account::new(u32::MAX as u64, space, &spl_token_2022::id());
+    let mut state =
+        StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut account.data).unwrap();
+    let transfer_fee_config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    transfer_fee_config.older_transfer_fee = transfer_fee();
+    transfer_fee_config.newer_transfer_fee = transfer_fee();
+    state.base = Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        decimals,
+        is_initialized: true,
+        ..Mint::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    test.add_account(mint_pubkey, account);
+    mint_pubkey
+}
+
+fn add_token_account(
+    test: &mut ProgramTest,
+    mint_pubkey: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+    extension_types: &[ExtensionType],
+) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    let space = ExtensionType::try_calculate_account_len::<Token>(extension_types).unwrap();
+    let mut account = Account::new(u32::MAX as u64, space, &spl_token_2022::id());
+    let mut state =
+        StateWithExtensionsMut::<Token>::unpack_uninitialized(&mut account.data).unwrap();
+    if extension_types.contains(&ExtensionType::TransferFeeAmount) {
+        state.init_extension::<TransferFeeAmount>(true).unwrap();
+    }
+    state.base = Token {
+        mint: mint_pubkey,
+        owner,
+        amount,
+        state: AccountState::Initialized,
+        ..Token::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    test.add_account(pubkey, account);
+    pubkey
+}
+
+fn add_transfer_fee_reserve(
+    test: &mut ProgramTest,
+    lending_market: &TestLendingMarket,
+    oracle: &TestOracle,
+    user_accounts_owner: &Keypair,
+    liquidity_amount: u64,
+    borrow_amount: u64,
+    user_liquidity_amount: u64,
+) -> TestReserve {
+    let decimals = 6;
+    let liquidity_mint_pubkey = add_transfer_fee_mint(test, decimals);
+
+    let collateral_mint_pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        collateral_mint_pubkey,
+        u32::MAX as u64,
+        &Mint {
+            is_initialized: true,
+            decimals,
+            mint_authority: COption::Some(lending_market.authority),
+            supply: liquidity_amount,
+            ..Mint::default()
+        },
+        &spl_token_2022::id(),
+    );
+    let collateral_supply_pubkey = add_token_account(
+        test,
+        collateral_mint_pubkey,
+        lending_market.authority,
+        0,
+        &[],
+    );
+
+    let liquidity_extensions = [ExtensionType::TransferFeeAmount];
+    let liquidity_supply_pubkey = add_token_account(
+        test,
+        liquidity_mint_pubkey,
+        lending_market.authority,
+        liquidity_amount - borrow_amount,
+        &liquidity_extensions,
+    );
+    let liquidity_fee_receiver_pubkey = add_token_account(
+        test,
+        liquidity_mint_pubkey,
+        lending_market.owner.pubkey(),
+        0,
+        &liquidity_extensions,
+    );
+    let liquidity_host_pubkey = add_token_account(
+        test,
+        liquidity_mint_pubkey,
+        user_accounts_owner.pubkey(),
+        0,
+        &liquidity_extensions,
+    );
+    let user_liquidity_pubkey = add_token_account(
+        test,
+        liquidity_mint_pubkey,
+        user_accounts_owner.pubkey(),
+        user_liquidity_amount,
+        &liquidity_extensions,
+    );
+    let user_collateral_pubkey = add_token_account(
+        test,
+        collateral_mint_pubkey,
+        user_accounts_owner.pubkey(),
+        liquidity_amount,
+        &[],
+    );
+
+    let reserve_pubkey = Pubkey::new_unique();
+    let mut reserve = Reserve::new(InitReserveParams {
+        current_slot: 1,
+        lending_market: lending_market.pubkey,
+        liquidity: ReserveLiquidity::new(NewReserveLiquidityParams {
+            mint_pubkey: liquidity_mint_pubkey,
+            mint_decimals: decimals,
+            supply_pubkey: liquidity_supply_pubkey,
+            fee_receiver: liquidity_fee_receiver_pubkey,
+            oracle_pubkey: oracle.price_pubkey,
+            market_price: oracle.price,
+            oracle_kind: oracle.kind,
+            oracle_quote_decimals: 0,
+        }),
+        collateral: ReserveCollateral::new(NewReserveCollateralParams {
+            mint_pubkey: collateral_mint_pubkey,
+            supply_pubkey: collateral_supply_pubkey,
+        }),
+        config: TEST_RESERVE_CONFIG,
+        token_program_id: spl_token_2022::id(),
+    });
+    reserve.deposit_liquidity(liquidity_amount).unwrap();
+    reserve.liquidity.borrow(borrow_amount.into()).unwrap();
+    reserve.last_update.update_slot(1);
+    test.add_packable_account(
+        reserve_pubkey,
+        u32::MAX as u64,
+        &reserve,
+        &spl_token_lending::id(),
+    );
+
+    TestReserve {
+        name: "fee".to_owned(),
+        pubkey: reserve_pubkey,
+        lending_market_pubkey: lending_market.pubkey,
+        config: TEST_RESERVE_CONFIG,
+        liquidity_mint_pubkey,
+        liquidity_mint_decimals: decimals,
+        liquidity_supply_pubkey,
+        liquidity_fee_receiver_pubkey,
+        liquidity_host_pubkey,
+        liquidity_oracle_pubkey: oracle.price_pubkey,
+        collateral_mint_pubkey,
+        collateral_supply_pubkey,
+        user_liquidity_pubkey,
+        user_collateral_pubkey,
+        market_price: oracle.price,
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_net_of_transfer_fee() {
+    let mut test = program_test();
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let test_reserve = add_transfer_fee_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        10_000 * FRACTIONAL_TO_USDC,
+        0,
+        100 * FRACTIONAL_TO_USDC,
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let liquidity_amount = 100 * FRACTIONAL_TO_USDC;
+    let received_amount =
+        liquidity_amount - transfer_fee().calculate_fee(liquidity_amount).unwrap();
+    let initial_supply_balance =
+        get_token_balance(&mut banks_client, test_reserve.liquidity_supply_pubkey).await;
+    let initial_collateral_balance =
+        get_token_balance(&mut banks_client, test_reserve.user_collateral_pubkey).await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token_2022::id(),
+                &test_reserve.user_liquidity_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                liquidity_amount,
+            )
+            .unwrap(),
+            with_token_program(
+                deposit_reserve_liquidity(
+                    spl_token_lending::id(),
+                    liquidity_amount,
+                    test_reserve.user_liquidity_pubkey,
+                    test_reserve.user_collateral_pubkey,
+                    test_reserve.pubkey,
+                    test_reserve.liquidity_supply_pubkey,
+                    test_reserve.collateral_mint_pubkey,
+                    lending_market.pubkey,
+                    user_transfer_authority.pubkey(),
+                ),
+                spl_token_2022::id(),
+                Some(test_reserve.liquidity_mint_pubkey),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let supply_balance =
+        get_token_balance(&mut banks_client, test_reserve.liquidity_supply_pubkey).await;
+    assert_eq!(supply_balance, initial_supply_balance + received_amount);
+
+    let collateral_balance =
+        get_token_balance(&mut banks_client, test_reserve.user_collateral_pubkey).await;
+    assert_eq!(
+        collateral_balance,
+        initial_collateral_balance + received_amount
+    );
+
+    let reserve = test_reserve.get_state(&mut banks_client).await;
+    assert_eq!(reserve.liquidity.available_amount, supply_balance);
+}
+
+#[tokio::test]
+async fn test_repay_grossed_up_for_transfer_fee() {
+    let mut test = program_test();
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+
+    let borrow_amount = 1_000 * FRACTIONAL_TO_USDC;
+    let test_reserve = add_transfer_fee_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        10_000 * FRACTIONAL_TO_USDC,
+        borrow_amount,
+        2 * borrow_amount,
+    );
+    let test_obligation = add_obligation(
+        &mut test,
+        &lending_market,
+        &user_accounts_owner,
+        AddObligationArgs {
+            borrows: &[(&test_reserve, borrow_amount)],
+            mark_fresh: true,
+            ..AddObligationArgs::default()
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let transfer_amount =
+        borrow_amount + transfer_fee().calculate_inverse_fee(borrow_amount).unwrap();
+    let initial_supply_balance =
+        get_token_balance(&mut banks_client, test_reserve.liquidity_supply_pubkey).await;
+    let initial_user_balance =
+        get_token_balance(&mut banks_client, test_reserve.user_liquidity_pubkey).await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token_2022::id(),
+                &test_reserve.user_liquidity_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                transfer_amount,
+            )
+            .unwrap(),
+            with_token_program(
+                repay_obligation_liquidity(
+                    spl_token_lending::id(),
+                    u64::MAX,
+                    test_reserve.user_liquidity_pubkey,
+                    test_reserve.liquidity_supply_pubkey,
+                    test_reserve.pubkey,
+                    test_obligation.pubkey,
+                    lending_market.pubkey,
+                    user_transfer_authority.pubkey(),
+                ),
+                spl_token_2022::id(),
+                Some(test_reserve.liquidity_mint_pubkey),
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_ok());
+
+    let supply_balance =
+        get_token_balance(&mut banks_client, test_reserve.liquidity_supply_pubkey).await;
+    assert_eq!(supply_balance, initial_supply_balance + borrow_amount);
+
+    let user_balance =
+        get_token_balance(&mut banks_client, test_reserve.user_liquidity_pubkey).await;
+    assert_eq!(user_balance, initial_user_balance - transfer_amount);
+
+    let reserve = test_reserve.get_state(&mut banks_client).await;
+    assert_eq!(reserve.liquidity.available_amount, supply_balance);
+    let obligation = test_obligation.get_state(&mut banks_client).await;
+    assert!(obligation.borrows.is_empty());
+}
+
+#[tokio::test]
+async fn test_liquidity_mint_required() {
+    let mut test = program_test();
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let test_reserve = add_transfer_fee_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        10_000 * FRACTIONAL_TO_USDC,
+        0,
+        100 * FRACTIONAL_TO_USDC,
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let liquidity_amount = 100 * FRACTIONAL_TO_USDC;
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token_2022::id(),
+                &test_reserve.user_liquidity_pubkey,
+                &user_transfer_authority.pubkey(),
+                &user_accounts_owner.pubkey(),
+                &[],
+                liquidity_amount,
+            )
+            .unwrap(),
+            with_token_program(
+                deposit_reserve_liquidity(
+                    spl_token_lending::id(),
+                    liquidity_amount,
+                    test_reserve.user_liquidity_pubkey,
+                    test_reserve.user_collateral_pubkey,
+                    test_reserve.pubkey,
+                    test_reserve.liquidity_supply_pubkey,
+                    test_reserve.collateral_mint_pubkey,
+                    lending_market.pubkey,
+                    user_transfer_authority.pubkey(),
+                ),
+                spl_token_2022::id(),
+                None,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(LendingError::InvalidAccountInput as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_legacy_token_program_rejected() {
+    let mut test = program_test();
+
+    let user_accounts_owner = Keypair::new();
+    let user_transfer_authority = Keypair::new();
+    let lending_market = add_lending_market(&mut test);
+    let usdc_oracle = add_usdc_oracle(&mut test);
+    let test_reserve = add_transfer_fee_reserve(
+        &mut test,
+        &lending_market,
+        &usdc_oracle,
+        &user_accounts_owner,
+        10_000 * FRACTIONAL_TO_USDC,
+        0,
+        100 * FRACTIONAL_TO_USDC,
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[deposit_reserve_liquidity(
+            spl_token_lending::id(),
+            100 * FRACTIONAL_TO_USDC,
+            test_reserve.user_liquidity_pubkey,
+            test_reserve.user_collateral_pubkey,
+            test_reserve.pubkey,
+            test_reserve.liquidity_supply_pubkey,
+            test_reserve.collateral_mint_pubkey,
+            lending_market.pubkey,
+            user_transfer_authority.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &user_transfer_authority], recent_blockhash);
+
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(LendingError::InvalidTokenProgram as u32)
+        )
+    );
+}
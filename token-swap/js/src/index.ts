@@ -62,6 +62,29 @@ export class Numberu64 extends BN {
   }
 }
 
+export const TokenSwapLayoutV1 = BufferLayout.struct([
+  BufferLayout.u8('version'),
+  BufferLayout.u8('isInitialized'),
+  BufferLayout.u8('bumpSeed'),
+  Layout.publicKey('poolTokenProgramId'),
+  Layout.publicKey('tokenAccountA'),
+  Layout.publicKey('tokenAccountB'),
+  Layout.publicKey('tokenPool'),
+  Layout.publicKey('mintA'),
+  Layout.publicKey('mintB'),
+  Layout.publicKey('feeAccount'),
+  Layout.uint64('tradeFeeNumerator'),
+  Layout.uint64('tradeFeeDenominator'),
+  Layout.uint64('ownerTradeFeeNumerator'),
+  Layout.uint64('ownerTradeFeeDenominator'),
+  Layout.uint64('ownerWithdrawFeeNumerator'),
+  Layout.uint64('ownerWithdrawFeeDenominator'),
+  Layout.uint64('hostFeeNumerator'),
+  Layout.uint64('hostFeeDenominator'),
+  BufferLayout.u8('curveType'),
+  BufferLayout.blob(32, 'curveParameters'),
+]);
+
 export const TokenSwapLayout = BufferLayout.struct([
   BufferLayout.u8('version'),
   BufferLayout.u8('isInitialized'),
@@ -83,12 +106,32 @@ export const TokenSwapLayout = BufferLayout.struct([
   Layout.uint64('hostFeeDenominator'),
   BufferLayout.u8('curveType'),
   BufferLayout.blob(32, 'curveParameters'),
+  BufferLayout.u32('pauseAuthorityOption'),
+  Layout.publicKey('pauseAuthority'),
+  BufferLayout.u8('isPaused'),
+  BufferLayout.blob(16, 'priceACumulative'),
+  BufferLayout.blob(16, 'priceBCumulative'),
+  Layout.uint64('lastUpdateSlot'),
+  BufferLayout.blob(8, 'lastUpdateTimestamp'),
+  BufferLayout.u8('dynamicFeeOption'),
+  BufferLayout.u16('floorBps'),
+  BufferLayout.u16('ceilingBps'),
+  BufferLayout.u32('volatilityFactorBps'),
+  BufferLayout.u32('decayPeriod'),
+  Layout.uint64('volatilityBps'),
+  BufferLayout.u8('pendingFeesOption'),
+  BufferLayout.blob(64, 'pendingFees'),
+  BufferLayout.u8('pendingSwapCurveOption'),
+  BufferLayout.blob(33, 'pendingSwapCurve'),
+  BufferLayout.blob(8, 'pendingActivationTimestamp'),
 ]);
 
 export const CurveType = Object.freeze({
   ConstantProduct: 0, // Constant product curve, Uniswap-style
   ConstantPrice: 1, // Constant price curve, always X amount of A token for 1 B token, where X is defined at init
   Offset: 2, // Offset curve, like Uniswap, but with an additional offset on the token B side
+  Stable: 3, // Stable curve, like curve.fi, flattened around a 1:1 price by an amplification coefficient
+  StakePoolStable: 4, // Stable curve between mLN and a stake pool token, flattened around the stake pool's exchange rate
 });
 
 /**
@@ -263,7 +306,10 @@ export class TokenSwap {
     payer: Account,
   ): Promise<TokenSwap> {
     const data = await loadAccount(connection, address, programId);
-    const tokenSwapData = TokenSwapLayout.decode(data);
+    const tokenSwapData =
+      data[0] === 1
+        ? TokenSwapLayoutV1.decode(data)
+        : TokenSwapLayout.decode(data);
     if (!tokenSwapData.isInitialized) {
       throw new Error(`Invalid token swap state`);
     }
@@ -545,7 +591,7 @@ export class TokenSwap {
     );
 
     const keys = [
-      {pubkey: tokenSwap, isSigner: false, isWritable: false},
+      {pubkey: tokenSwap, isSigner: false, isWritable: true},
       {pubkey: authority, isSigner: false, isWritable: false},
       {pubkey: userTransferAuthority, isSigner: true, isWritable: false},
       {pubkey: userSource, isSigner: false, isWritable: true},
@@ -882,7 +928,7 @@ export class TokenSwap {
     );
 
     const keys = [
-      {pubkey: tokenSwap, isSigner: false, isWritable: false},
+      {pubkey: tokenSwap, isSigner: false, isWritable: true},
       {pubkey: authority, isSigner: false, isWritable: false},
       {pubkey: userTransferAuthority, isSigner: true, isWritable: false},
       {pubkey: source, isSigner: false, isWritable: true},
@@ -986,7 +1032,7 @@ export class TokenSwap {
     );
 
     const keys = [
-      {pubkey: tokenSwap, isSigner: false, isWritable: false},
+      {pubkey: tokenSwap, isSigner: false, isWritable: true},
       {pubkey: authority, isSigner: false, isWritable: false},
       {pubkey: userTransferAuthority, isSigner: true, isWritable: false},
       {pubkey: poolMint, isSigner: false, isWritable: true},
@@ -12,6 +12,8 @@ use {
             constant_product::ConstantProductCurve,
             fees::Fees,
             offset::OffsetCurve,
+            stable::StableCurve,
+            stake_pool_stable::StakePoolStableCurve,
         },
         error::SwapError,
         instruction::{
@@ -87,6 +89,11 @@ fn main() {
 }
 
 fn run_fuzz(fuzz_data: FuzzData) {
+    // The native swap does not provide the stake pool account required to
+    // price the stake pool stable curve
+    if fuzz_data.curve_type == CurveType::StakePoolStable {
+        return;
+    }
     let trade_fee_numerator = 25;
     let trade_fee_denominator = 10000;
     let owner_trade_fee_numerator = 5;
@@ -471,6 +478,11 @@ fn get_swap_curve(curve_type: CurveType) -> SwapCurve {
             CurveType::Offset => Arc::new(OffsetCurve {
                 token_b_offset: 100_000_000_000,
             }),
+            CurveType::Stable => Arc::new(StableCurve { amp: 100 }),
+            CurveType::StakePoolStable => Arc::new(StakePoolStableCurve {
+                amp: 100,
+                ..StakePoolStableCurve::default()
+            }),
         },
     }
 }
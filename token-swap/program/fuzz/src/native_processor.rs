@@ -1,8 +1,13 @@
 use {
     crate::native_account_data::NativeAccountData,
     solana_program::{
-        account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction,
-        program_error::ProgramError, program_stubs, pubkey::Pubkey,
+        account_info::AccountInfo,
+        clock::Clock,
+        entrypoint::{ProgramResult, SUCCESS},
+        instruction::Instruction,
+        program_error::ProgramError,
+        program_stubs,
+        pubkey::Pubkey,
     },
 };
 
@@ -43,6 +48,13 @@ impl program_stubs::SyscallStubs for TestSyscallStubs {
             &instruction.data,
         )
     }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut _ as *mut Clock) = Clock::default();
+        }
+        SUCCESS
+    }
 }
 
 fn test_syscall_stubs() {
@@ -65,7 +77,11 @@ pub fn do_process_instruction(instruction: Instruction, accounts: &[AccountInfo]
         .collect::<Vec<_>>();
     let account_infos = account_data
         .iter_mut()
-        .map(NativeAccountData::as_account_info)
+        .zip(instruction.accounts.iter())
+        .map(|(account_data, account_meta)| AccountInfo {
+            is_writable: account_meta.is_writable,
+            ..account_data.as_account_info()
+        })
         .collect::<Vec<_>>();
     let res = if instruction.program_id == spl_token_swap::id() {
         spl_token_swap::processor::Processor::process(
@@ -92,6 +92,8 @@ impl NativeTokenSwap {
             &pool_token_account.key,
             fees.clone(),
             swap_curve.clone(),
+            None,
+            None,
         )
         .unwrap();
 
@@ -6,7 +6,7 @@ use {
     crate::{
         curve::{
             base::{CurveType, SwapCurve},
-            fees::Fees,
+            fees::{DynamicFee, Fees, BASIS_POINTS},
         },
         error::SwapError,
     },
@@ -58,6 +58,18 @@ impl<'a> SwapConstraints<'a> {
             Err(SwapError::InvalidFee.into())
         }
     }
+
+    /// Checks that the provided dynamic fee never charges less than the trade
+    /// fee of the constraints
+    pub fn validate_dynamic_fee(&self, dynamic_fee: &DynamicFee) -> Result<(), ProgramError> {
+        let floor = u128::from(dynamic_fee.floor_bps) * u128::from(self.fees.trade_fee_denominator);
+        let minimum = u128::from(self.fees.trade_fee_numerator) * u128::from(BASIS_POINTS);
+        if floor >= minimum {
+            Ok(())
+        } else {
+            Err(SwapError::InvalidFee.into())
+        }
+    }
 }
 
 #[cfg(feature = "production")]
@@ -74,7 +86,12 @@ const FEES: &Fees = &Fees {
     host_fee_denominator: 100,
 };
 #[cfg(feature = "production")]
-const VALID_CURVE_TYPES: &[CurveType] = &[CurveType::ConstantPrice, CurveType::ConstantProduct];
+const VALID_CURVE_TYPES: &[CurveType] = &[
+    CurveType::ConstantPrice,
+    CurveType::ConstantProduct,
+    CurveType::Stable,
+    CurveType::StakePoolStable,
+];
 
 /// Fee structure defined by program creator in order to enforce certain
 /// fees when others use the program.  Adds checks on pool creation and
@@ -196,4 +213,31 @@ mod tests {
             constraints.validate_curve(&swap_curve),
         );
     }
+
+    #[test]
+    fn validate_dynamic_fee() {
+        let fees = Fees {
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            ..Fees::default()
+        };
+        let constraints = SwapConstraints {
+            owner_key: "",
+            valid_curve_types: &[CurveType::ConstantProduct],
+            fees: &fees,
+        };
+        let mut dynamic_fee = DynamicFee {
+            floor_bps: 25,
+            ceiling_bps: 100,
+            volatility_factor_bps: 1_000,
+            decay_period: 600,
+        };
+        constraints.validate_dynamic_fee(&dynamic_fee).unwrap();
+
+        dynamic_fee.floor_bps = 24;
+        assert_eq!(
+            Err(SwapError::InvalidFee.into()),
+            constraints.validate_dynamic_fee(&dynamic_fee),
+        );
+    }
 }
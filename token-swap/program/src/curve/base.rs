@@ -9,6 +9,8 @@ use {
         constant_product::ConstantProductCurve,
         fees::Fees,
         offset::OffsetCurve,
+        stable::StableCurve,
+        stake_pool_stable::StakePoolStableCurve,
     },
     arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
     solana_program::{
@@ -34,6 +36,12 @@ pub enum CurveType {
     ConstantPrice,
     /// Offset curve, like Uniswap, but the token B side has a faked offset
     Offset,
+    /// Stable curve from curve.fi, with an amplification coefficient that
+    /// flattens the curve around a 1:1 price
+    Stable,
+    /// Stable curve between mLN and a stake pool token, flattened around the
+    /// stake pool's exchange rate instead of 1:1
+    StakePoolStable,
 }
 
 /// Encodes all results of swapping from a source token to a destination token
@@ -229,6 +237,10 @@ impl Pack for SwapCurve {
                     Arc::new(ConstantPriceCurve::unpack_from_slice(calculator)?)
                 }
                 CurveType::Offset => Arc::new(OffsetCurve::unpack_from_slice(calculator)?),
+                CurveType::Stable => Arc::new(StableCurve::unpack_from_slice(calculator)?),
+                CurveType::StakePoolStable => {
+                    Arc::new(StakePoolStableCurve::unpack_from_slice(calculator)?)
+                }
             },
         })
     }
@@ -258,6 +270,8 @@ impl TryFrom<u8> for CurveType {
             0 => Ok(CurveType::ConstantProduct),
             1 => Ok(CurveType::ConstantPrice),
             2 => Ok(CurveType::Offset),
+            3 => Ok(CurveType::Stable),
+            4 => Ok(CurveType::StakePoolStable),
             _ => Err(ProgramError::InvalidAccountData),
         }
     }
@@ -4,6 +4,7 @@ use {
     crate::error::SwapError,
     arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
     solana_program::{
+        clock::UnixTimestamp,
         program_error::ProgramError,
         program_pack::{IsInitialized, Pack, Sealed},
     },
@@ -238,6 +239,135 @@ impl Pack for Fees {
     }
 }
 
+/// Number of basis points in one, the denominator of dynamic trade fees
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// Optional trade fee that rises with the recent price movement of a pool,
+/// replacing the fixed trade fee of its `Fees`.
+///
+/// Every change to the price of the pool adds its size, in basis points, to
+/// the pool's volatility, which then decays linearly back to zero over
+/// `decay_period` seconds. The trade fee is the floor, plus a share of the
+/// volatility given by the volatility factor, capped at the ceiling.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DynamicFee {
+    /// Trade fee when the price has not moved recently, in basis points
+    pub floor_bps: u16,
+    /// Maximum trade fee, in basis points
+    pub ceiling_bps: u16,
+    /// Share of the volatility added to the trade fee, in basis points
+    pub volatility_factor_bps: u32,
+    /// Number of seconds for the volatility to decay back to zero
+    pub decay_period: u32,
+}
+
+impl DynamicFee {
+    /// Volatility left after `elapsed` seconds of decay
+    pub fn decay(&self, volatility_bps: u64, elapsed: UnixTimestamp) -> u64 {
+        let decay_period = i64::from(self.decay_period);
+        if elapsed <= 0 {
+            volatility_bps
+        } else if elapsed >= decay_period {
+            0
+        } else {
+            // cannot overflow, since the remaining time is less than u32::MAX
+            (volatility_bps as u128 * (decay_period - elapsed) as u128 / decay_period as u128)
+                as u64
+        }
+    }
+
+    /// Trade fee for the given volatility, in basis points
+    pub fn trade_fee_bps(&self, volatility_bps: u64) -> u64 {
+        let volatility_fee =
+            volatility_bps as u128 * self.volatility_factor_bps as u128 / BASIS_POINTS as u128;
+        u64::try_from(volatility_fee)
+            .unwrap_or(u64::MAX)
+            .saturating_add(self.floor_bps.into())
+            .min(self.ceiling_bps.into())
+    }
+
+    /// Fees of a swap with its trade fee replaced by the dynamic fee, given
+    /// the volatility of the pool after decay
+    pub fn fees(&self, fees: &Fees, volatility_bps: u64) -> Fees {
+        Fees {
+            trade_fee_numerator: self.trade_fee_bps(volatility_bps),
+            trade_fee_denominator: BASIS_POINTS,
+            ..fees.clone()
+        }
+    }
+
+    /// Validate that the dynamic fee is reasonable
+    pub fn validate(&self) -> Result<(), SwapError> {
+        if self.floor_bps > self.ceiling_bps
+            || u64::from(self.ceiling_bps) >= BASIS_POINTS
+            || self.decay_period == 0
+        {
+            Err(SwapError::InvalidFee)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Size of a price movement in basis points, given the amounts of token A and
+/// B in a pool before and after the movement. Saturates instead of
+/// overflowing, and is zero if the pool was empty on either side before.
+pub fn price_change_bps(
+    old_token_a_amount: u128,
+    old_token_b_amount: u128,
+    new_token_a_amount: u128,
+    new_token_b_amount: u128,
+) -> u64 {
+    if old_token_a_amount == 0 || old_token_b_amount == 0 {
+        return 0;
+    }
+    // compare old_b / old_a with new_b / new_a, both multiplied by old_a * new_a
+    let old_price = old_token_b_amount.saturating_mul(new_token_a_amount);
+    let new_price = new_token_b_amount.saturating_mul(old_token_a_amount);
+    if old_price == 0 {
+        return u64::MAX;
+    }
+    let change = old_price.abs_diff(new_price);
+    change
+        .checked_mul(BASIS_POINTS as u128)
+        .map(|change| change / old_price)
+        .and_then(|change_bps| u64::try_from(change_bps).ok())
+        .unwrap_or(u64::MAX)
+}
+
+impl Sealed for DynamicFee {}
+impl IsInitialized for DynamicFee {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+
+impl Pack for DynamicFee {
+    const LEN: usize = 12;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 12];
+        let (floor_bps, ceiling_bps, volatility_factor_bps, decay_period) =
+            mut_array_refs![output, 2, 2, 4, 4];
+        *floor_bps = self.floor_bps.to_le_bytes();
+        *ceiling_bps = self.ceiling_bps.to_le_bytes();
+        *volatility_factor_bps = self.volatility_factor_bps.to_le_bytes();
+        *decay_period = self.decay_period.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<DynamicFee, ProgramError> {
+        let input = array_ref![input, 0, 12];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (floor_bps, ceiling_bps, volatility_factor_bps, decay_period) =
+            array_refs![input, 2, 2, 4, 4];
+        Ok(Self {
+            floor_bps: u16::from_le_bytes(*floor_bps),
+            ceiling_bps: u16::from_le_bytes(*ceiling_bps),
+            volatility_factor_bps: u32::from_le_bytes(*volatility_factor_bps),
+            decay_period: u32::from_le_bytes(*decay_period),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -280,4 +410,91 @@ mod tests {
         let unpacked = Fees::unpack_from_slice(&packed).unwrap();
         assert_eq!(fees, unpacked);
     }
+
+    const TEST_DYNAMIC_FEE: DynamicFee = DynamicFee {
+        floor_bps: 5,
+        ceiling_bps: 100,
+        volatility_factor_bps: 1_000,
+        decay_period: 600,
+    };
+
+    #[test]
+    fn pack_dynamic_fee() {
+        let mut packed = [0u8; DynamicFee::LEN];
+        Pack::pack_into_slice(&TEST_DYNAMIC_FEE, &mut packed[..]);
+        let unpacked = DynamicFee::unpack_from_slice(&packed).unwrap();
+        assert_eq!(TEST_DYNAMIC_FEE, unpacked);
+
+        let mut packed = vec![];
+        packed.extend_from_slice(&TEST_DYNAMIC_FEE.floor_bps.to_le_bytes());
+        packed.extend_from_slice(&TEST_DYNAMIC_FEE.ceiling_bps.to_le_bytes());
+        packed.extend_from_slice(&TEST_DYNAMIC_FEE.volatility_factor_bps.to_le_bytes());
+        packed.extend_from_slice(&TEST_DYNAMIC_FEE.decay_period.to_le_bytes());
+        let unpacked = DynamicFee::unpack_from_slice(&packed).unwrap();
+        assert_eq!(TEST_DYNAMIC_FEE, unpacked);
+    }
+
+    #[test]
+    fn dynamic_fee_decay() {
+        assert_eq!(TEST_DYNAMIC_FEE.decay(1_000, -5), 1_000);
+        assert_eq!(TEST_DYNAMIC_FEE.decay(1_000, 0), 1_000);
+        assert_eq!(TEST_DYNAMIC_FEE.decay(1_000, 150), 750);
+        assert_eq!(TEST_DYNAMIC_FEE.decay(1_000, 599), 1);
+        assert_eq!(TEST_DYNAMIC_FEE.decay(1_000, 600), 0);
+        assert_eq!(TEST_DYNAMIC_FEE.decay(u64::MAX, 300), u64::MAX / 2);
+    }
+
+    #[test]
+    fn dynamic_fee_trade_fee() {
+        assert_eq!(TEST_DYNAMIC_FEE.trade_fee_bps(0), 5);
+        assert_eq!(TEST_DYNAMIC_FEE.trade_fee_bps(9), 5);
+        assert_eq!(TEST_DYNAMIC_FEE.trade_fee_bps(200), 25);
+        assert_eq!(TEST_DYNAMIC_FEE.trade_fee_bps(950), 100);
+        assert_eq!(TEST_DYNAMIC_FEE.trade_fee_bps(u64::MAX), 100);
+
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 4,
+            owner_trade_fee_numerator: 2,
+            owner_trade_fee_denominator: 5,
+            ..Fees::default()
+        };
+        let dynamic_fees = TEST_DYNAMIC_FEE.fees(&fees, 200);
+        assert_eq!(dynamic_fees.trade_fee_numerator, 25);
+        assert_eq!(dynamic_fees.trade_fee_denominator, BASIS_POINTS);
+        assert_eq!(dynamic_fees.owner_trade_fee_numerator, 2);
+        assert_eq!(dynamic_fees.owner_trade_fee_denominator, 5);
+    }
+
+    #[test]
+    fn dynamic_fee_validate() {
+        TEST_DYNAMIC_FEE.validate().unwrap();
+        let mut dynamic_fee = TEST_DYNAMIC_FEE;
+        dynamic_fee.floor_bps = dynamic_fee.ceiling_bps + 1;
+        assert_eq!(dynamic_fee.validate(), Err(SwapError::InvalidFee));
+        let mut dynamic_fee = TEST_DYNAMIC_FEE;
+        dynamic_fee.ceiling_bps = BASIS_POINTS as u16;
+        assert_eq!(dynamic_fee.validate(), Err(SwapError::InvalidFee));
+        let mut dynamic_fee = TEST_DYNAMIC_FEE;
+        dynamic_fee.decay_period = 0;
+        assert_eq!(dynamic_fee.validate(), Err(SwapError::InvalidFee));
+    }
+
+    #[test]
+    fn price_change() {
+        assert_eq!(price_change_bps(100, 100, 100, 100), 0);
+        // price of A doubles
+        assert_eq!(price_change_bps(100, 100, 100, 200), 10_000);
+        // price of A halves
+        assert_eq!(price_change_bps(100, 100, 200, 100), 5_000);
+        // a swap of 1% of the pool on a constant product curve
+        assert_eq!(
+            price_change_bps(1_000_000, 1_000_000, 1_010_000, 990_100),
+            197
+        );
+        assert_eq!(price_change_bps(0, 100, 100, 100), 0);
+        assert_eq!(price_change_bps(100, 100, 100, 0), 10_000);
+        assert_eq!(price_change_bps(100, 100, 0, 100), u64::MAX);
+        assert_eq!(price_change_bps(1, 1, 1, u128::MAX), u64::MAX);
+    }
 }
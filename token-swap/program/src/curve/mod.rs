@@ -6,3 +6,5 @@ pub mod constant_price;
 pub mod constant_product;
 pub mod fees;
 pub mod offset;
+pub mod stable;
+pub mod stake_pool_stable;
@@ -0,0 +1,503 @@
+//! The curve.fi invariant calculator, priced against a stake pool's exchange
+//! rate
+
+use {
+    crate::{
+        curve::{
+            calculator::{
+                CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult, TradeDirection,
+                TradingTokenResult,
+            },
+            constant_product::pool_tokens_to_trading_tokens,
+            stable::StableCurve,
+        },
+        error::SwapError,
+    },
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    solana_program::{
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+    },
+    spl_math::precise_number::PreciseNumber,
+    spl_stake_pool::state::StakePool,
+};
+
+/// Stable curve between mLN (token A) and a stake pool token (token B).
+///
+/// Token B amounts are converted to their mLN value at the stake pool's
+/// exchange rate, `total_lamports / pool_token_supply`, before running the
+/// stable curve, so that the curve is flattest around the true value of the
+/// stake pool token rather than around 1:1.
+///
+/// The exchange rate stored here is only a snapshot: the processor refreshes
+/// it from the stake pool account on every instruction that needs it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StakePoolStableCurve {
+    /// Amplifier constant
+    pub amp: u64,
+    /// Total lamports managed by the stake pool
+    pub total_lamports: u64,
+    /// Total supply of stake pool tokens
+    pub pool_token_supply: u64,
+}
+
+impl StakePoolStableCurve {
+    /// Create a new curve using the current exchange rate of the stake pool
+    pub fn with_stake_pool(&self, stake_pool: &StakePool) -> Self {
+        Self {
+            amp: self.amp,
+            total_lamports: stake_pool.total_lamports,
+            pool_token_supply: stake_pool.pool_token_supply,
+        }
+    }
+
+    fn stable_curve(&self) -> StableCurve {
+        StableCurve { amp: self.amp }
+    }
+
+    /// The exchange rate as (lamports, pool tokens), following the stake pool
+    /// convention that an empty pool trades at 1:1
+    fn exchange_rate(&self) -> (u128, u128) {
+        if self.total_lamports == 0 || self.pool_token_supply == 0 {
+            (1, 1)
+        } else {
+            (self.total_lamports as u128, self.pool_token_supply as u128)
+        }
+    }
+
+    /// Convert an amount of stake pool tokens to its value in lamports
+    fn pool_tokens_to_lamports(
+        &self,
+        pool_tokens: u128,
+        round_direction: RoundDirection,
+    ) -> Option<u128> {
+        let (total_lamports, pool_token_supply) = self.exchange_rate();
+        let numerator = pool_tokens.checked_mul(total_lamports)?;
+        let lamports = numerator.checked_div(pool_token_supply)?;
+        match round_direction {
+            RoundDirection::Floor => Some(lamports),
+            RoundDirection::Ceiling => {
+                if numerator.checked_rem(pool_token_supply)? > 0 {
+                    lamports.checked_add(1)
+                } else {
+                    Some(lamports)
+                }
+            }
+        }
+    }
+
+    /// Convert an amount of lamports to stake pool tokens, rounding down
+    fn lamports_to_pool_tokens(&self, lamports: u128) -> Option<u128> {
+        let (total_lamports, pool_token_supply) = self.exchange_rate();
+        lamports
+            .checked_mul(pool_token_supply)?
+            .checked_div(total_lamports)
+    }
+}
+
+impl CurveCalculator for StakePoolStableCurve {
+    /// Stable curve swap with the stake pool token side valued in lamports.
+    /// All conversions round in favor of the pool.
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<SwapWithoutFeesResult> {
+        let stable_curve = self.stable_curve();
+        match trade_direction {
+            TradeDirection::AtoB => {
+                let result = stable_curve.swap_without_fees(
+                    source_amount,
+                    swap_source_amount,
+                    self.pool_tokens_to_lamports(swap_destination_amount, RoundDirection::Floor)?,
+                    trade_direction,
+                )?;
+                Some(SwapWithoutFeesResult {
+                    source_amount_swapped: result.source_amount_swapped,
+                    destination_amount_swapped: self
+                        .lamports_to_pool_tokens(result.destination_amount_swapped)?,
+                })
+            }
+            TradeDirection::BtoA => {
+                let result = stable_curve.swap_without_fees(
+                    self.pool_tokens_to_lamports(source_amount, RoundDirection::Floor)?,
+                    self.pool_tokens_to_lamports(swap_source_amount, RoundDirection::Floor)?,
+                    swap_destination_amount,
+                    trade_direction,
+                )?;
+                Some(SwapWithoutFeesResult {
+                    source_amount_swapped: source_amount,
+                    destination_amount_swapped: result.destination_amount_swapped,
+                })
+            }
+        }
+    }
+
+    /// Balanced deposits and withdrawals do not depend on the exchange rate
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            round_direction,
+        )
+    }
+
+    fn deposit_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let source_amount = match trade_direction {
+            TradeDirection::AtoB => source_amount,
+            TradeDirection::BtoA => {
+                self.pool_tokens_to_lamports(source_amount, RoundDirection::Floor)?
+            }
+        };
+        self.stable_curve().deposit_single_token_type(
+            source_amount,
+            swap_token_a_amount,
+            self.pool_tokens_to_lamports(swap_token_b_amount, RoundDirection::Floor)?,
+            pool_supply,
+            trade_direction,
+        )
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Option<u128> {
+        let source_amount = match trade_direction {
+            TradeDirection::AtoB => source_amount,
+            TradeDirection::BtoA => {
+                self.pool_tokens_to_lamports(source_amount, RoundDirection::Ceiling)?
+            }
+        };
+        self.stable_curve().withdraw_single_token_type_exact_out(
+            source_amount,
+            swap_token_a_amount,
+            self.pool_tokens_to_lamports(swap_token_b_amount, RoundDirection::Floor)?,
+            pool_supply,
+            trade_direction,
+            round_direction,
+        )
+    }
+
+    fn validate(&self) -> Result<(), SwapError> {
+        self.stable_curve().validate()
+    }
+
+    /// The normalized value is the stable curve's value with token B valued in
+    /// lamports
+    fn normalized_value(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+    ) -> Option<PreciseNumber> {
+        self.stable_curve().normalized_value(
+            swap_token_a_amount,
+            self.pool_tokens_to_lamports(swap_token_b_amount, RoundDirection::Floor)?,
+        )
+    }
+}
+
+/// IsInitialized is required to use `Pack::pack` and `Pack::unpack`
+impl IsInitialized for StakePoolStableCurve {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+impl Sealed for StakePoolStableCurve {}
+impl Pack for StakePoolStableCurve {
+    const LEN: usize = 24;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        (self as &dyn DynPack).pack_into_slice(output);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<StakePoolStableCurve, ProgramError> {
+        let input = array_ref![input, 0, 24];
+        let (amp, total_lamports, pool_token_supply) = array_refs![input, 8, 8, 8];
+        Ok(Self {
+            amp: u64::from_le_bytes(*amp),
+            total_lamports: u64::from_le_bytes(*total_lamports),
+            pool_token_supply: u64::from_le_bytes(*pool_token_supply),
+        })
+    }
+}
+
+impl DynPack for StakePoolStableCurve {
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 24];
+        let (amp, total_lamports, pool_token_supply) = mut_array_refs![output, 8, 8, 8];
+        *amp = self.amp.to_le_bytes();
+        *total_lamports = self.total_lamports.to_le_bytes();
+        *pool_token_supply = self.pool_token_supply.to_le_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::curve::calculator::test::{
+            check_pool_value_from_deposit, check_pool_value_from_withdraw, total_and_intermediate,
+        },
+        proptest::prelude::*,
+    };
+
+    #[test]
+    fn pack_curve() {
+        let curve = StakePoolStableCurve {
+            amp: 100,
+            total_lamports: 1_050_000_000,
+            pool_token_supply: 1_000_000_000,
+        };
+
+        let mut packed = [0u8; StakePoolStableCurve::LEN];
+        Pack::pack_into_slice(&curve, &mut packed[..]);
+        let unpacked = StakePoolStableCurve::unpack(&packed).unwrap();
+        assert_eq!(curve, unpacked);
+
+        let mut packed = vec![];
+        packed.extend_from_slice(&curve.amp.to_le_bytes());
+        packed.extend_from_slice(&curve.total_lamports.to_le_bytes());
+        packed.extend_from_slice(&curve.pool_token_supply.to_le_bytes());
+        let unpacked = StakePoolStableCurve::unpack(&packed).unwrap();
+        assert_eq!(curve, unpacked);
+    }
+
+    #[test]
+    fn with_stake_pool() {
+        let curve = StakePoolStableCurve {
+            amp: 100,
+            ..StakePoolStableCurve::default()
+        };
+        let stake_pool = StakePool {
+            total_lamports: 1_050_000_000,
+            pool_token_supply: 1_000_000_000,
+            ..StakePool::default()
+        };
+        assert_eq!(
+            curve.with_stake_pool(&stake_pool),
+            StakePoolStableCurve {
+                amp: 100,
+                total_lamports: 1_050_000_000,
+                pool_token_supply: 1_000_000_000,
+            }
+        );
+    }
+
+    #[test]
+    fn swap_at_par_matches_stable() {
+        let curve = StakePoolStableCurve {
+            amp: 100,
+            total_lamports: 1_000_000_000,
+            pool_token_supply: 1_000_000_000,
+        };
+        let stable = StableCurve { amp: 100 };
+        for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+            assert_eq!(
+                curve.swap_without_fees(10_000, 1_000_000, 1_000_000, trade_direction),
+                stable.swap_without_fees(10_000, 1_000_000, 1_000_000, trade_direction),
+            );
+        }
+    }
+
+    #[test]
+    fn swap_at_exchange_rate() {
+        // 1 pool token is worth 1.05 mLN, and both sides hold the same value
+        let curve = StakePoolStableCurve {
+            amp: 100,
+            total_lamports: 105_000_000,
+            pool_token_supply: 100_000_000,
+        };
+        let result = curve
+            .swap_without_fees(10_000, 1_050_000, 1_000_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 10_000);
+        assert_eq!(result.destination_amount_swapped, 9_522);
+
+        let result = curve
+            .swap_without_fees(10_000, 1_000_000, 1_050_000, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 10_000);
+        assert_eq!(result.destination_amount_swapped, 10_498);
+    }
+
+    #[test]
+    fn empty_stake_pool_trades_at_par() {
+        let curve = StakePoolStableCurve {
+            amp: 100,
+            total_lamports: 0,
+            pool_token_supply: 0,
+        };
+        let stable = StableCurve { amp: 100 };
+        assert_eq!(
+            curve.swap_without_fees(10_000, 1_000_000, 1_000_000, TradeDirection::AtoB),
+            stable.swap_without_fees(10_000, 1_000_000, 1_000_000, TradeDirection::AtoB),
+        );
+    }
+
+    prop_compose! {
+        pub fn exchange_rate()(pool_token_supply in 1_000_000_000..u32::MAX as u64)
+                        (total_lamports in pool_token_supply..pool_token_supply * 2,
+                         pool_token_supply in Just(pool_token_supply))
+                        -> (u64, u64) {
+           (total_lamports, pool_token_supply)
+       }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u32::MAX,
+            swap_source_amount in u32::MAX as u64..u64::MAX / 4,
+            swap_destination_amount in u32::MAX as u64..u64::MAX / 4,
+            (total_lamports, pool_token_supply) in exchange_rate(),
+            amp in 1..100u64,
+        ) {
+            let curve = StakePoolStableCurve { amp, total_lamports, pool_token_supply };
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                let results = curve
+                    .swap_without_fees(
+                        source_token_amount as u128,
+                        swap_source_amount as u128,
+                        swap_destination_amount as u128,
+                        trade_direction,
+                    )
+                    .unwrap();
+                let new_swap_source_amount = swap_source_amount as u128 + results.source_amount_swapped;
+                let new_swap_destination_amount =
+                    swap_destination_amount as u128 - results.destination_amount_swapped;
+                let (previous_value, new_value) = match trade_direction {
+                    TradeDirection::AtoB => (
+                        curve.normalized_value(swap_source_amount as u128, swap_destination_amount as u128),
+                        curve.normalized_value(new_swap_source_amount, new_swap_destination_amount),
+                    ),
+                    TradeDirection::BtoA => (
+                        curve.normalized_value(swap_destination_amount as u128, swap_source_amount as u128),
+                        curve.normalized_value(new_swap_destination_amount, new_swap_source_amount),
+                    ),
+                };
+                assert!(new_value.unwrap().greater_than_or_equal(&previous_value.unwrap()));
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn swap_at_par_matches_stable_curve(
+            source_token_amount in 1..u32::MAX,
+            swap_source_amount in u32::MAX as u64..u64::MAX / 4,
+            swap_destination_amount in u32::MAX as u64..u64::MAX / 4,
+            amp in 1..100u64,
+        ) {
+            // At par, no conversion takes place, so the stable curve's
+            // guarantees carry over unchanged
+            let curve = StakePoolStableCurve {
+                amp,
+                total_lamports: 1,
+                pool_token_supply: 1,
+            };
+            let stable = StableCurve { amp };
+            prop_assert_eq!(
+                curve.swap_without_fees(
+                    source_token_amount as u128,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    TradeDirection::AtoB,
+                ),
+                stable.swap_without_fees(
+                    source_token_amount as u128,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    TradeDirection::AtoB,
+                )
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in u32::MAX as u64..u64::MAX / 4,
+            swap_token_b_amount in u32::MAX as u64..u64::MAX / 4,
+            (total_lamports, stake_pool_token_supply) in exchange_rate(),
+            amp in 1..100u64,
+        ) {
+            let curve = StakePoolStableCurve {
+                amp,
+                total_lamports,
+                pool_token_supply: stake_pool_token_supply,
+            };
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_deposit(
+                &curve,
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in u32::MAX as u64..u64::MAX / 4,
+            swap_token_b_amount in u32::MAX as u64..u64::MAX / 4,
+            amp in 1..100u64,
+        ) {
+            // Withdrawals are pro-rata on both sides, so they are checked at
+            // par where the normalized value is exact in both tokens
+            let curve = StakePoolStableCurve {
+                amp,
+                total_lamports: 1,
+                pool_token_supply: 1,
+            };
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_withdraw(
+                &curve,
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+}
@@ -117,6 +117,31 @@ pub enum SwapError {
     /// The pool fee account is invalid.
     #[error("The pool fee account is invalid")]
     InvalidFeeAccount,
+
+    // 30.
+    /// The provided stake pool account does not back the swap's token B.
+    #[error("The provided stake pool account is incorrect")]
+    IncorrectStakePool,
+    /// The stake pool has not been updated for the current epoch.
+    #[error("The stake pool has not been updated for the current epoch")]
+    StaleStakePool,
+    /// The swap account version does not support the operation.
+    #[error("The swap account version does not support the operation")]
+    UnsupportedSwapVersion,
+    /// The swap is paused.
+    #[error("The swap is paused")]
+    SwapPaused,
+    /// The provided pause authority does not match the swap's pause authority.
+    #[error("The provided pause authority is incorrect")]
+    InvalidPauseAuthority,
+
+    // 35.
+    /// The timelock of the pending fees or curve has not elapsed yet.
+    #[error("The timelock of the pending update has not elapsed")]
+    TimelockNotElapsed,
+    /// The swap has no pending fees or curve to apply.
+    #[error("The swap has no pending update")]
+    NoPendingUpdate,
 }
 impl From<SwapError> for ProgramError {
     fn from(e: SwapError) -> Self {
@@ -200,6 +225,23 @@ impl PrintProgramError for SwapError {
             SwapError::InvalidFeeAccount => {
                 msg!("Error: The pool fee account is invalid")
             }
+            SwapError::IncorrectStakePool => {
+                msg!("Error: The provided stake pool account is incorrect")
+            }
+            SwapError::StaleStakePool => {
+                msg!("Error: The stake pool has not been updated for the current epoch")
+            }
+            SwapError::UnsupportedSwapVersion => {
+                msg!("Error: The swap account version does not support the operation")
+            }
+            SwapError::SwapPaused => msg!("Error: The swap is paused"),
+            SwapError::InvalidPauseAuthority => {
+                msg!("Error: The provided pause authority is incorrect")
+            }
+            SwapError::TimelockNotElapsed => {
+                msg!("Error: The timelock of the pending update has not elapsed")
+            }
+            SwapError::NoPendingUpdate => msg!("Error: The swap has no pending update"),
         }
     }
 }
@@ -6,12 +6,16 @@
 use arbitrary::Arbitrary;
 use {
     crate::{
-        curve::{base::SwapCurve, fees::Fees},
+        curve::{
+            base::SwapCurve,
+            fees::{DynamicFee, Fees},
+        },
         error::SwapError,
     },
     solana_program::{
         instruction::{AccountMeta, Instruction},
         program_error::ProgramError,
+        program_option::COption,
         program_pack::Pack,
         pubkey::Pubkey,
     },
@@ -27,6 +31,11 @@ pub struct Initialize {
     /// swap curve info for pool, including CurveType and anything
     /// else that may be required
     pub swap_curve: SwapCurve,
+    /// optional authority that can pause and unpause the swap
+    pub pause_authority: COption<Pubkey>,
+    /// optional trade fee rising with volatility, replacing the trade fee
+    /// of `fees`
+    pub dynamic_fee: Option<DynamicFee>,
 }
 
 /// Swap instruction data
@@ -42,6 +51,35 @@ pub struct Swap {
     pub minimum_amount_out: u64,
 }
 
+/// RouteSwap instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteSwap {
+    /// SOURCE amount to transfer into the first pool of the route
+    pub amount_in: u64,
+    /// Minimum amount of the final DESTINATION token to output, prevents
+    /// excessive slippage over the whole route
+    pub minimum_amount_out: u64,
+}
+
+/// SetFees instruction data
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct SetFees {
+    /// new swap fees, applied once the timelock elapsed
+    pub fees: Fees,
+}
+
+/// SetCurveParameters instruction data
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct SetCurveParameters {
+    /// new swap curve info for pool, applied once the timelock elapsed. The
+    /// curve type must stay the same.
+    pub swap_curve: SwapCurve,
+}
+
 /// DepositAllTokenTypes instruction data
 #[cfg_attr(feature = "fuzz", derive(Arbitrary))]
 #[repr(C)]
@@ -112,11 +150,14 @@ pub enum SwapInstruction {
     ///   6. `[writable]` Pool Token Account to deposit the initial pool token
     ///   supply.  Must be empty, not owned by swap authority.
     ///   7. `[]` Pool Token program id
+    ///   8. `[]` Stake pool backing token B, only for the `StakePoolStable`
+    ///      curve
     Initialize(Initialize),
 
     ///   Swap the tokens in the pool.
     ///
-    ///   0. `[]` Token-swap
+    ///   0. `[writable]` Token-swap, to update its price oracle. May be
+    ///      read-only for swaps created before the price oracle existed.
     ///   1. `[]` swap authority
     ///   2. `[]` user transfer authority
     ///   3. `[writable]` token_(A|B) SOURCE Account, amount is transferable by
@@ -134,8 +175,10 @@ pub enum SwapInstruction {
     ///   11. `[]` Token (A|B) SOURCE program id
     ///   12. `[]` Token (A|B) DESTINATION program id
     ///   13. `[]` Pool Token program id
-    ///   14. `[optional, writable]` Host fee account to receive additional
-    ///       trading fees
+    ///   14. `[]` Stake pool backing token B, only for the `StakePoolStable`
+    ///       curve
+    ///   14 or 15. `[optional, writable]` Host fee account to receive
+    ///       additional trading fees
     Swap(Swap),
 
     ///   Deposit both types of tokens into the pool.  The output is a "pool"
@@ -185,7 +228,8 @@ pub enum SwapInstruction {
     ///   token representing ownership into the pool. Input token is
     ///   converted as if a swap and deposit all token types were performed.
     ///
-    ///   0. `[]` Token-swap
+    ///   0. `[writable]` Token-swap, to update its price oracle. May be
+    ///      read-only for swaps created before the price oracle existed.
     ///   1. `[]` swap authority
     ///   2. `[]` user transfer authority
     ///   3. `[writable]` token_(A|B) SOURCE Account, amount is transferable by
@@ -198,12 +242,15 @@ pub enum SwapInstruction {
     ///   8. `[]` Token (A|B) SOURCE mint
     ///   9. `[]` Token (A|B) SOURCE program id
     ///   10. `[]` Pool Token program id
+    ///   11. `[]` Stake pool backing token B, only for the `StakePoolStable`
+    ///       curve
     DepositSingleTokenTypeExactAmountIn(DepositSingleTokenTypeExactAmountIn),
 
     ///   Withdraw one token type from the pool at the current ratio given the
     ///   exact amount out expected.
     ///
-    ///   0. `[]` Token-swap
+    ///   0. `[writable]` Token-swap, to update its price oracle. May be
+    ///      read-only for swaps created before the price oracle existed.
     ///   1. `[]` swap authority
     ///   2. `[]` user transfer authority
     ///   3. `[writable]` Pool mint account, swap authority is the owner
@@ -216,7 +263,60 @@ pub enum SwapInstruction {
     ///   9. `[]` Token (A|B) DESTINATION mint
     ///   10. `[]` Pool Token program id
     ///   11. `[]` Token (A|B) DESTINATION program id
+    ///   12. `[]` Stake pool backing token B, only for the `StakePoolStable`
+    ///       curve
     WithdrawSingleTokenTypeExactAmountOut(WithdrawSingleTokenTypeExactAmountOut),
+
+    ///   Pause the swap. While paused, swaps, deposits, and single token
+    ///   withdrawals are rejected, but `WithdrawAllTokenTypes` is still
+    ///   allowed so that liquidity providers can exit.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Pause authority
+    Pause,
+
+    ///   Unpause the swap, allowing all instructions again.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Pause authority
+    Unpause,
+
+    ///   Swap the tokens through an ordered list of pools, e.g. A to B in a
+    ///   first pool, then B to C in a second one. The output of each leg is
+    ///   the input of the next, and only the output of the last leg is
+    ///   checked against `minimum_amount_out`.
+    ///
+    ///   For each leg, in order, the accounts expected by `Swap`, without a
+    ///   host fee account. The DESTINATION account of each leg must be the
+    ///   SOURCE account of the next one.
+    RouteSwap(RouteSwap),
+
+    ///   Stage new fees for the swap, validated against the program's
+    ///   constraints. They replace the current fees once `ApplyPendingUpdate`
+    ///   is processed, at least `UPDATE_TIMELOCK` seconds later.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Owner: the owner from the program's constraints, or the
+    ///      owner of the pool fee account if the program has none
+    ///   2. `[]` Pool fee account
+    SetFees(SetFees),
+
+    ///   Stage new curve parameters for the swap, e.g. a new `token_b_offset`
+    ///   for the `Offset` curve. They replace the current curve once
+    ///   `ApplyPendingUpdate` is processed, at least `UPDATE_TIMELOCK` seconds
+    ///   later.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Owner: the owner from the program's constraints, or the
+    ///      owner of the pool fee account if the program has none
+    ///   2. `[]` Pool fee account
+    SetCurveParameters(SetCurveParameters),
+
+    ///   Apply the pending fees and curve parameters of the swap, once their
+    ///   timelock elapsed. Anyone can apply them.
+    ///
+    ///   0. `[writable]` Token-swap
+    ApplyPendingUpdate,
 }
 
 impl SwapInstruction {
@@ -229,8 +329,29 @@ impl SwapInstruction {
                 if rest.len() >= Fees::LEN {
                     let (fees, rest) = rest.split_at(Fees::LEN);
                     let fees = Fees::unpack_unchecked(fees)?;
-                    let swap_curve = SwapCurve::unpack_unchecked(rest)?;
-                    Self::Initialize(Initialize { fees, swap_curve })
+                    if rest.len() < SwapCurve::LEN {
+                        return Err(SwapError::InvalidInstruction.into());
+                    }
+                    let (swap_curve, rest) = rest.split_at(SwapCurve::LEN);
+                    let swap_curve = SwapCurve::unpack_unchecked(swap_curve)?;
+                    // the pause authority and dynamic fee are optional for
+                    // backwards compatibility
+                    let (pause_authority, rest) = if rest.is_empty() {
+                        (COption::None, rest)
+                    } else {
+                        Self::unpack_pubkey_option(rest)?
+                    };
+                    let dynamic_fee = if rest.is_empty() {
+                        None
+                    } else {
+                        Self::unpack_dynamic_fee_option(rest)?
+                    };
+                    Self::Initialize(Initialize {
+                        fees,
+                        swap_curve,
+                        pause_authority,
+                        dynamic_fee,
+                    })
                 } else {
                     return Err(SwapError::InvalidInstruction.into());
                 }
@@ -279,10 +400,79 @@ impl SwapInstruction {
                     maximum_pool_token_amount,
                 })
             }
+            6 => Self::Pause,
+            7 => Self::Unpause,
+            8 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, _rest) = Self::unpack_u64(rest)?;
+                Self::RouteSwap(RouteSwap {
+                    amount_in,
+                    minimum_amount_out,
+                })
+            }
+            9 => {
+                if rest.len() < Fees::LEN {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+                let fees = Fees::unpack_unchecked(&rest[..Fees::LEN])?;
+                Self::SetFees(SetFees { fees })
+            }
+            10 => {
+                if rest.len() < SwapCurve::LEN {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+                let swap_curve = SwapCurve::unpack_unchecked(&rest[..SwapCurve::LEN])?;
+                Self::SetCurveParameters(SetCurveParameters { swap_curve })
+            }
+            11 => Self::ApplyPendingUpdate,
             _ => return Err(SwapError::InvalidInstruction.into()),
         })
     }
 
+    fn unpack_pubkey_option(input: &[u8]) -> Result<(COption<Pubkey>, &[u8]), ProgramError> {
+        match input.split_first() {
+            Some((&0, rest)) => Ok((COption::None, rest)),
+            Some((&1, rest)) if rest.len() >= 32 => {
+                let (key, rest) = rest.split_at(32);
+                let pk = Pubkey::try_from(key).map_err(|_| SwapError::InvalidInstruction)?;
+                Ok((COption::Some(pk), rest))
+            }
+            _ => Err(SwapError::InvalidInstruction.into()),
+        }
+    }
+
+    fn unpack_dynamic_fee_option(input: &[u8]) -> Result<Option<DynamicFee>, ProgramError> {
+        match input.split_first() {
+            Some((&0, _)) => Ok(None),
+            Some((&1, rest)) if rest.len() >= DynamicFee::LEN => Ok(Some(
+                DynamicFee::unpack_unchecked(&rest[..DynamicFee::LEN])?,
+            )),
+            _ => Err(SwapError::InvalidInstruction.into()),
+        }
+    }
+
+    fn pack_dynamic_fee_option(value: &Option<DynamicFee>, buf: &mut Vec<u8>) {
+        match value {
+            Some(dynamic_fee) => {
+                buf.push(1);
+                let mut dynamic_fee_slice = [0u8; DynamicFee::LEN];
+                Pack::pack_into_slice(dynamic_fee, &mut dynamic_fee_slice[..]);
+                buf.extend_from_slice(&dynamic_fee_slice);
+            }
+            None => buf.push(0),
+        }
+    }
+
+    fn pack_pubkey_option(value: &COption<Pubkey>, buf: &mut Vec<u8>) {
+        match *value {
+            COption::Some(ref key) => {
+                buf.push(1);
+                buf.extend_from_slice(&key.to_bytes());
+            }
+            COption::None => buf.push(0),
+        }
+    }
+
     fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
         if input.len() >= 8 {
             let (amount, rest) = input.split_at(8);
@@ -301,7 +491,12 @@ impl SwapInstruction {
     pub fn pack(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(size_of::<Self>());
         match self {
-            Self::Initialize(Initialize { fees, swap_curve }) => {
+            Self::Initialize(Initialize {
+                fees,
+                swap_curve,
+                pause_authority,
+                dynamic_fee,
+            }) => {
                 buf.push(0);
                 let mut fees_slice = [0u8; Fees::LEN];
                 Pack::pack_into_slice(fees, &mut fees_slice[..]);
@@ -309,6 +504,8 @@ impl SwapInstruction {
                 let mut swap_curve_slice = [0u8; SwapCurve::LEN];
                 Pack::pack_into_slice(swap_curve, &mut swap_curve_slice[..]);
                 buf.extend_from_slice(&swap_curve_slice);
+                Self::pack_pubkey_option(pause_authority, &mut buf);
+                Self::pack_dynamic_fee_option(dynamic_fee, &mut buf);
             }
             Self::Swap(Swap {
                 amount_in,
@@ -356,6 +553,29 @@ impl SwapInstruction {
                 buf.extend_from_slice(&destination_token_amount.to_le_bytes());
                 buf.extend_from_slice(&maximum_pool_token_amount.to_le_bytes());
             }
+            Self::Pause => buf.push(6),
+            Self::Unpause => buf.push(7),
+            Self::RouteSwap(RouteSwap {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                buf.push(8);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+            }
+            Self::SetFees(SetFees { fees }) => {
+                buf.push(9);
+                let mut fees_slice = [0u8; Fees::LEN];
+                Pack::pack_into_slice(fees, &mut fees_slice[..]);
+                buf.extend_from_slice(&fees_slice);
+            }
+            Self::SetCurveParameters(SetCurveParameters { swap_curve }) => {
+                buf.push(10);
+                let mut swap_curve_slice = [0u8; SwapCurve::LEN];
+                Pack::pack_into_slice(swap_curve, &mut swap_curve_slice[..]);
+                buf.extend_from_slice(&swap_curve_slice);
+            }
+            Self::ApplyPendingUpdate => buf.push(11),
         }
         buf
     }
@@ -374,8 +594,16 @@ pub fn initialize(
     destination_pubkey: &Pubkey,
     fees: Fees,
     swap_curve: SwapCurve,
+    pause_authority: Option<&Pubkey>,
+    dynamic_fee: Option<DynamicFee>,
 ) -> Result<Instruction, ProgramError> {
-    let init_data = SwapInstruction::Initialize(Initialize { fees, swap_curve });
+    let pause_authority = pause_authority.cloned().into();
+    let init_data = SwapInstruction::Initialize(Initialize {
+        fees,
+        swap_curve,
+        pause_authority,
+        dynamic_fee,
+    });
     let data = init_data.pack();
 
     let accounts = vec![
@@ -507,7 +735,7 @@ pub fn deposit_single_token_type_exact_amount_in(
     let data = SwapInstruction::DepositSingleTokenTypeExactAmountIn(instruction).pack();
 
     let accounts = vec![
-        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*swap_pubkey, false),
         AccountMeta::new_readonly(*authority_pubkey, false),
         AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
         AccountMeta::new(*source_token_pubkey, false),
@@ -547,7 +775,7 @@ pub fn withdraw_single_token_type_exact_amount_out(
     let data = SwapInstruction::WithdrawSingleTokenTypeExactAmountOut(instruction).pack();
 
     let accounts = vec![
-        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*swap_pubkey, false),
         AccountMeta::new_readonly(*authority_pubkey, false),
         AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
         AccountMeta::new(*pool_mint_pubkey, false),
@@ -591,7 +819,7 @@ pub fn swap(
     let data = SwapInstruction::Swap(instruction).pack();
 
     let mut accounts = vec![
-        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*swap_pubkey, false),
         AccountMeta::new_readonly(*authority_pubkey, false),
         AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
         AccountMeta::new(*source_pubkey, false),
@@ -617,6 +845,158 @@ pub fn swap(
     })
 }
 
+/// Creates a 'route_swap' instruction from the 'swap' instructions of each
+/// leg, built without a host fee account.
+pub fn route_swap(
+    program_id: &Pubkey,
+    legs: &[Instruction],
+    instruction: RouteSwap,
+) -> Result<Instruction, ProgramError> {
+    if legs.is_empty() {
+        return Err(SwapError::InvalidInstruction.into());
+    }
+    let data = SwapInstruction::RouteSwap(instruction).pack();
+
+    let mut accounts = vec![];
+    for leg in legs {
+        if leg.program_id != *program_id || leg.data.first() != Some(&1) {
+            return Err(SwapError::InvalidInstruction.into());
+        }
+        accounts.extend_from_slice(&leg.accounts);
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'pause' instruction.
+pub fn pause(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    pause_authority_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Pause.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*pause_authority_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'unpause' instruction.
+pub fn unpause(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    pause_authority_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Unpause.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*pause_authority_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_fees' instruction.
+pub fn set_fees(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    fee_pubkey: &Pubkey,
+    fees: Fees,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetFees(SetFees { fees }).pack();
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*fee_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_curve_parameters' instruction.
+pub fn set_curve_parameters(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    fee_pubkey: &Pubkey,
+    swap_curve: SwapCurve,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetCurveParameters(SetCurveParameters { swap_curve }).pack();
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*fee_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'apply_pending_update' instruction.
+pub fn apply_pending_update(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ApplyPendingUpdate.pack();
+
+    let accounts = vec![AccountMeta::new(*swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Adds the stake pool account required by the `StakePoolStable` curve to an
+/// 'initialize', 'swap', 'deposit_single_token_type_exact_amount_in' or
+/// 'withdraw_single_token_type_exact_amount_out' instruction.
+pub fn with_stake_pool(
+    mut instruction: Instruction,
+    stake_pool_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let index = match instruction.data.first() {
+        Some(0) => 8,
+        Some(1) => 14,
+        Some(4) => 11,
+        Some(5) => 12,
+        _ => return Err(SwapError::InvalidInstruction.into()),
+    };
+    if instruction.accounts.len() < index {
+        return Err(SwapError::InvalidInstruction.into());
+    }
+    instruction
+        .accounts
+        .insert(index, AccountMeta::new_readonly(*stake_pool_pubkey, false));
+    Ok(instruction)
+}
+
 /// Unpacks a reference from a bytes buffer.
 /// TODO actually pack / unpack instead of relying on normal memory layout.
 pub fn unpack<T>(input: &[u8]) -> Result<&T, ProgramError> {
@@ -663,7 +1043,19 @@ mod tests {
             curve_type,
             calculator,
         };
-        let check = SwapInstruction::Initialize(Initialize { fees, swap_curve });
+        let pause_authority = Pubkey::new_from_array([1u8; 32]);
+        let dynamic_fee = DynamicFee {
+            floor_bps: 5,
+            ceiling_bps: 100,
+            volatility_factor_bps: 1_000,
+            decay_period: 600,
+        };
+        let check = SwapInstruction::Initialize(Initialize {
+            fees: fees.clone(),
+            swap_curve: swap_curve.clone(),
+            pause_authority: COption::Some(pause_authority),
+            dynamic_fee: Some(dynamic_fee),
+        });
         let packed = check.pack();
         let mut expect = vec![0u8];
         expect.extend_from_slice(&trade_fee_numerator.to_le_bytes());
@@ -677,9 +1069,38 @@ mod tests {
         expect.push(curve_type as u8);
         expect.extend_from_slice(&token_b_offset.to_le_bytes());
         expect.extend_from_slice(&[0u8; 24]);
+        let without_pause_authority = expect.clone();
+        expect.push(1);
+        expect.extend_from_slice(&pause_authority.to_bytes());
+        let without_dynamic_fee = expect.clone();
+        expect.push(1);
+        expect.extend_from_slice(&dynamic_fee.floor_bps.to_le_bytes());
+        expect.extend_from_slice(&dynamic_fee.ceiling_bps.to_le_bytes());
+        expect.extend_from_slice(&dynamic_fee.volatility_factor_bps.to_le_bytes());
+        expect.extend_from_slice(&dynamic_fee.decay_period.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
+
+        // instructions packed before the dynamic fee existed still unpack
+        let check = SwapInstruction::Initialize(Initialize {
+            fees: fees.clone(),
+            swap_curve: swap_curve.clone(),
+            pause_authority: COption::Some(pause_authority),
+            dynamic_fee: None,
+        });
+        let unpacked = SwapInstruction::unpack(&without_dynamic_fee).unwrap();
+        assert_eq!(unpacked, check);
+
+        // instructions packed before the pause authority existed still unpack
+        let check = SwapInstruction::Initialize(Initialize {
+            fees,
+            swap_curve,
+            pause_authority: COption::None,
+            dynamic_fee: None,
+        });
+        let unpacked = SwapInstruction::unpack(&without_pause_authority).unwrap();
+        assert_eq!(unpacked, check);
     }
 
     #[test]
@@ -776,4 +1197,97 @@ mod tests {
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
+
+    #[test]
+    fn pack_pause() {
+        let check = SwapInstruction::Pause;
+        let packed = check.pack();
+        let expect = vec![6];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn pack_unpause() {
+        let check = SwapInstruction::Unpause;
+        let packed = check.pack();
+        let expect = vec![7];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn pack_route_swap() {
+        let amount_in: u64 = 2;
+        let minimum_amount_out: u64 = 10;
+        let check = SwapInstruction::RouteSwap(RouteSwap {
+            amount_in,
+            minimum_amount_out,
+        });
+        let packed = check.pack();
+        let mut expect = vec![8];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&minimum_amount_out.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn pack_set_fees() {
+        let fees = Fees {
+            trade_fee_numerator: 3,
+            trade_fee_denominator: 1_000,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 1_000,
+            owner_withdraw_fee_numerator: 0,
+            owner_withdraw_fee_denominator: 0,
+            host_fee_numerator: 5,
+            host_fee_denominator: 20,
+        };
+        let check = SwapInstruction::SetFees(SetFees { fees: fees.clone() });
+        let packed = check.pack();
+        let mut expect = vec![9u8];
+        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.owner_trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.owner_trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.owner_withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.owner_withdraw_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.host_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.host_fee_denominator.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn pack_set_curve_parameters() {
+        let token_b_offset: u64 = 2_000_000_000;
+        let curve_type = CurveType::Offset;
+        let swap_curve = SwapCurve {
+            curve_type,
+            calculator: Arc::new(OffsetCurve { token_b_offset }),
+        };
+        let check = SwapInstruction::SetCurveParameters(SetCurveParameters { swap_curve });
+        let packed = check.pack();
+        let mut expect = vec![10u8, curve_type as u8];
+        expect.extend_from_slice(&token_b_offset.to_le_bytes());
+        expect.extend_from_slice(&[0u8; 24]);
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn pack_apply_pending_update() {
+        let check = SwapInstruction::ApplyPendingUpdate;
+        let packed = check.pack();
+        let expect = vec![11];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
 }
@@ -0,0 +1,207 @@
+//! Time-weighted average price oracle
+//!
+//! Every trade that moves the price of a swap first accumulates the current
+//! prices, weighted by the number of seconds since the last update, into the
+//! swap state. The time-weighted average price over an interval is then the
+//! difference between two observations of the accumulators, divided by the
+//! time elapsed between them.
+//!
+//! Prices are fixed-point numbers with `PRICE_FRACTIONAL_BITS` fractional
+//! bits. Accumulators are expected to overflow and wrap around: differences
+//! remain correct as long as the average price multiplied by the elapsed time
+//! over an interval fits in the integer part of a price.
+
+use {
+    crate::state::SwapState,
+    solana_program::clock::{Slot, UnixTimestamp},
+};
+
+/// Number of fractional bits in the fixed-point prices of the oracle
+pub const PRICE_FRACTIONAL_BITS: u32 = 64;
+
+/// Price of one token in terms of the other, as a fixed-point number, or
+/// `None` if the pool is empty on either side
+pub fn spot_price(base_amount: u64, quote_amount: u64) -> Option<u128> {
+    if base_amount == 0 {
+        return None;
+    }
+    ((quote_amount as u128) << PRICE_FRACTIONAL_BITS).checked_div(base_amount as u128)
+}
+
+/// Price accumulators of a swap at a point in time
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Observation {
+    /// Sum of the price of token A in token B, for each second
+    pub price_a_cumulative: u128,
+    /// Sum of the price of token B in token A, for each second
+    pub price_b_cumulative: u128,
+    /// Slot of the last update
+    pub slot: Slot,
+    /// Timestamp of the last update
+    pub timestamp: UnixTimestamp,
+}
+
+impl Observation {
+    /// The accumulators as last written to the swap state
+    pub fn from_swap_state(swap_state: &dyn SwapState) -> Self {
+        Self {
+            price_a_cumulative: swap_state.price_a_cumulative(),
+            price_b_cumulative: swap_state.price_b_cumulative(),
+            slot: swap_state.last_update_slot(),
+            timestamp: swap_state.last_update_timestamp(),
+        }
+    }
+
+    /// The accumulators at a later time, given the current amounts of token A
+    /// and B in the swap, which lets a reader observe the swap without writing
+    /// to it. The current amounts are assumed to have held since the last
+    /// update, which only holds for amounts moved by the swap program:
+    /// tokens transferred directly into the swap's token accounts move the
+    /// price without updating the accumulators, and are credited for the whole
+    /// time since the last update.
+    pub fn accumulate(
+        &self,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        slot: Slot,
+        timestamp: UnixTimestamp,
+    ) -> Self {
+        let mut observation = Self {
+            slot,
+            timestamp,
+            ..*self
+        };
+        let elapsed = timestamp.saturating_sub(self.timestamp);
+        if elapsed <= 0 {
+            return observation;
+        }
+        let elapsed = elapsed as u128;
+        if let (Some(price_a), Some(price_b)) = (
+            spot_price(token_a_amount, token_b_amount),
+            spot_price(token_b_amount, token_a_amount),
+        ) {
+            observation.price_a_cumulative = self
+                .price_a_cumulative
+                .wrapping_add(price_a.wrapping_mul(elapsed));
+            observation.price_b_cumulative = self
+                .price_b_cumulative
+                .wrapping_add(price_b.wrapping_mul(elapsed));
+        }
+        observation
+    }
+}
+
+/// Time-weighted average prices between two observations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeWeightedAveragePrice {
+    /// Average price of token A in token B
+    pub price_a: u128,
+    /// Average price of token B in token A
+    pub price_b: u128,
+}
+
+/// Compute the time-weighted average prices between two observations of the
+/// same swap, or `None` if no time elapsed between them
+pub fn time_weighted_average_price(
+    older: &Observation,
+    newer: &Observation,
+) -> Option<TimeWeightedAveragePrice> {
+    let elapsed = newer.timestamp.checked_sub(older.timestamp)?;
+    if elapsed <= 0 {
+        return None;
+    }
+    let elapsed = elapsed as u128;
+    Some(TimeWeightedAveragePrice {
+        price_a: newer
+            .price_a_cumulative
+            .wrapping_sub(older.price_a_cumulative)
+            .checked_div(elapsed)?,
+        price_b: newer
+            .price_b_cumulative
+            .wrapping_sub(older.price_b_cumulative)
+            .checked_div(elapsed)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 1 << PRICE_FRACTIONAL_BITS;
+
+    #[test]
+    fn spot_price_empty() {
+        assert_eq!(spot_price(0, 100), None);
+        assert_eq!(spot_price(100, 0), Some(0));
+        assert_eq!(spot_price(100, 250), Some(ONE * 5 / 2));
+    }
+
+    #[test]
+    fn accumulate_weights_by_time() {
+        let start = Observation {
+            timestamp: 1_000,
+            ..Observation::default()
+        };
+        // price of A is 2 B for 10 seconds
+        let middle = start.accumulate(1_000, 2_000, 20, 1_010);
+        assert_eq!(middle.price_a_cumulative, ONE * 2 * 10);
+        assert_eq!(middle.price_b_cumulative, ONE / 2 * 10);
+        assert_eq!(middle.slot, 20);
+        assert_eq!(middle.timestamp, 1_010);
+        // price of A is 4 B for 30 seconds
+        let end = middle.accumulate(1_000, 4_000, 80, 1_040);
+
+        let twap = time_weighted_average_price(&start, &end).unwrap();
+        assert_eq!(twap.price_a, (ONE * 2 * 10 + ONE * 4 * 30) / 40);
+        assert_eq!(twap.price_b, (ONE / 2 * 10 + ONE / 4 * 30) / 40);
+        let twap = time_weighted_average_price(&middle, &end).unwrap();
+        assert_eq!(twap.price_a, ONE * 4);
+        assert_eq!(twap.price_b, ONE / 4);
+    }
+
+    #[test]
+    fn accumulate_same_timestamp() {
+        let start = Observation {
+            price_a_cumulative: 5,
+            price_b_cumulative: 7,
+            slot: 1,
+            timestamp: 1_000,
+        };
+        let next = start.accumulate(1, 1_000_000, 2, 1_000);
+        assert_eq!(next.price_a_cumulative, 5);
+        assert_eq!(next.price_b_cumulative, 7);
+        assert_eq!(next.slot, 2);
+        assert_eq!(time_weighted_average_price(&start, &next), None);
+    }
+
+    #[test]
+    fn accumulate_empty_pool() {
+        let start = Observation::default();
+        let next = start.accumulate(0, 1_000, 10, 100);
+        assert_eq!(next.price_a_cumulative, 0);
+        assert_eq!(next.price_b_cumulative, 0);
+        assert_eq!(next.timestamp, 100);
+    }
+
+    #[test]
+    fn twap_across_overflow() {
+        let start = Observation {
+            price_a_cumulative: u128::MAX - ONE,
+            price_b_cumulative: u128::MAX,
+            slot: 0,
+            timestamp: 0,
+        };
+        let end = start.accumulate(1_000, 3_000, 100, 50);
+        assert!(end.price_a_cumulative < start.price_a_cumulative);
+        let twap = time_weighted_average_price(&start, &end).unwrap();
+        assert_eq!(twap.price_a, ONE * 3);
+        assert_eq!(twap.price_b, ONE / 3);
+    }
+
+    #[test]
+    fn twap_out_of_order() {
+        let start = Observation::default().accumulate(1, 1, 10, 100);
+        let end = start.accumulate(1, 1, 20, 200);
+        assert_eq!(time_weighted_average_price(&end, &start), None);
+    }
+}
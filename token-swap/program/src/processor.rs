@@ -4,20 +4,24 @@ use {
     crate::{
         constraints::{SwapConstraints, SWAP_CONSTRAINTS},
         curve::{
-            base::SwapCurve,
+            base::{CurveType, SwapCurve},
             calculator::{RoundDirection, TradeDirection},
-            fees::Fees,
+            fees::{price_change_bps, DynamicFee, Fees},
+            stake_pool_stable::StakePoolStableCurve,
         },
         error::SwapError,
         instruction::{
-            DepositAllTokenTypes, DepositSingleTokenTypeExactAmountIn, Initialize, Swap,
-            SwapInstruction, WithdrawAllTokenTypes, WithdrawSingleTokenTypeExactAmountOut,
+            DepositAllTokenTypes, DepositSingleTokenTypeExactAmountIn, Initialize, RouteSwap,
+            SetCurveParameters, SetFees, Swap, SwapInstruction, WithdrawAllTokenTypes,
+            WithdrawSingleTokenTypeExactAmountOut,
         },
-        state::{SwapState, SwapV1, SwapVersion},
+        oracle::Observation,
+        state::{SwapState, SwapV2, SwapVersion, UPDATE_TIMELOCK},
     },
     num_traits::FromPrimitive,
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        borsh1::try_from_slice_unchecked,
         clock::Clock,
         decode_error::DecodeError,
         entrypoint::ProgramResult,
@@ -26,9 +30,11 @@ use {
         program::invoke_signed,
         program_error::{PrintProgramError, ProgramError},
         program_option::COption,
+        program_pack::Pack,
         pubkey::Pubkey,
         sysvar::Sysvar,
     },
+    spl_stake_pool::state::StakePool,
     spl_token_2022::{
         check_spl_token_program_account,
         error::TokenError,
@@ -38,9 +44,16 @@ use {
         },
         state::{Account, Mint},
     },
-    std::{convert::TryInto, error::Error},
+    std::{convert::TryInto, error::Error, sync::Arc},
 };
 
+/// Outcome of a swap through a single pool, to chain the legs of a route
+struct SwapLeg {
+    source: Pubkey,
+    destination: Pubkey,
+    amount_received: u64,
+}
+
 /// Program state handler.
 pub struct Processor {}
 impl Processor {
@@ -243,11 +256,118 @@ impl Processor {
         Ok(())
     }
 
+    /// Returns the curve to price an instruction with. A stake pool stable
+    /// curve takes the next account as the stake pool backing token B, and
+    /// uses that stake pool's exchange rate for the current epoch.
+    fn current_swap_curve<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+        swap_curve: &SwapCurve,
+        token_b_mint: &Pubkey,
+        account_info_iter: &mut I,
+    ) -> Result<SwapCurve, ProgramError> {
+        if swap_curve.curve_type != CurveType::StakePoolStable {
+            // `SwapCurve` holds its calculator behind a trait object, so
+            // repack it to get an owned copy
+            let mut packed_swap_curve = [0u8; SwapCurve::LEN];
+            swap_curve.pack_into_slice(&mut packed_swap_curve);
+            return SwapCurve::unpack_from_slice(&packed_swap_curve);
+        }
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        if *stake_pool_info.owner != spl_stake_pool::id() {
+            return Err(SwapError::IncorrectStakePool.into());
+        }
+        let stake_pool = try_from_slice_unchecked::<StakePool>(&stake_pool_info.data.borrow())
+            .map_err(|_| SwapError::IncorrectStakePool)?;
+        if !stake_pool.is_valid() || stake_pool.pool_mint != *token_b_mint {
+            return Err(SwapError::IncorrectStakePool.into());
+        }
+        if stake_pool.last_update_epoch < Clock::get()?.epoch {
+            return Err(SwapError::StaleStakePool.into());
+        }
+
+        let mut packed_calculator = [0u8; StakePoolStableCurve::LEN];
+        swap_curve
+            .calculator
+            .pack_into_slice(&mut packed_calculator);
+        let calculator = StakePoolStableCurve::unpack_from_slice(&packed_calculator)?;
+        Ok(SwapCurve {
+            curve_type: CurveType::StakePoolStable,
+            calculator: Arc::new(calculator.with_stake_pool(&stake_pool)),
+        })
+    }
+
+    /// Returns the fees to charge now: the fees of the swap, with the trade
+    /// fee replaced by its dynamic fee if it has one.
+    fn current_fees(token_swap: &dyn SwapState) -> Result<Fees, ProgramError> {
+        match token_swap.dynamic_fee() {
+            Some(dynamic_fee) => {
+                let elapsed = Clock::get()?
+                    .unix_timestamp
+                    .saturating_sub(token_swap.last_update_timestamp());
+                let volatility_bps = dynamic_fee.decay(token_swap.volatility_bps(), elapsed);
+                Ok(dynamic_fee.fees(token_swap.fees(), volatility_bps))
+            }
+            None => Ok(token_swap.fees().clone()),
+        }
+    }
+
+    /// Records a change of the amounts of token A and B held by the swap:
+    /// accumulates the prices before the change into the price oracle, and
+    /// adds the size of the price movement to the volatility used by the
+    /// dynamic fee. Swaps created before the oracle existed are left
+    /// untouched, so they can still be used with a read-only swap account.
+    fn record_price_change(
+        swap_info: &AccountInfo,
+        old_token_a_amount: u64,
+        old_token_b_amount: u64,
+        new_token_a_amount: u64,
+        new_token_b_amount: u64,
+    ) -> ProgramResult {
+        let mut token_swap = match SwapVersion::unpack_latest(&swap_info.data.borrow()) {
+            Ok(token_swap) => token_swap,
+            Err(err) if err == SwapError::UnsupportedSwapVersion.into() => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        if !swap_info.is_writable {
+            msg!("Swap account must be writable to update its price oracle");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let clock = Clock::get()?;
+        if let Some(dynamic_fee) = token_swap.dynamic_fee {
+            let elapsed = clock
+                .unix_timestamp
+                .saturating_sub(token_swap.last_update_timestamp);
+            token_swap.volatility_bps = dynamic_fee
+                .decay(token_swap.volatility_bps, elapsed)
+                .saturating_add(price_change_bps(
+                    old_token_a_amount.into(),
+                    old_token_b_amount.into(),
+                    new_token_a_amount.into(),
+                    new_token_b_amount.into(),
+                ));
+        }
+        let observation = Observation::from_swap_state(&token_swap).accumulate(
+            old_token_a_amount,
+            old_token_b_amount,
+            clock.slot,
+            clock.unix_timestamp,
+        );
+        token_swap.price_a_cumulative = observation.price_a_cumulative;
+        token_swap.price_b_cumulative = observation.price_b_cumulative;
+        token_swap.last_update_slot = observation.slot;
+        token_swap.last_update_timestamp = observation.timestamp;
+        SwapVersion::pack(
+            SwapVersion::SwapV2(token_swap),
+            &mut swap_info.data.borrow_mut(),
+        )
+    }
+
     /// Processes an [Initialize](enum.Instruction.html).
     pub fn process_initialize(
         program_id: &Pubkey,
         fees: Fees,
         swap_curve: SwapCurve,
+        pause_authority: COption<Pubkey>,
+        dynamic_fee: Option<DynamicFee>,
         accounts: &[AccountInfo],
         swap_constraints: &Option<SwapConstraints>,
     ) -> ProgramResult {
@@ -309,6 +429,7 @@ impl Processor {
         if token_a.mint == token_b.mint {
             return Err(SwapError::RepeatedMint.into());
         }
+        let swap_curve = Self::current_swap_curve(&swap_curve, &token_b.mint, account_info_iter)?;
         swap_curve
             .calculator
             .validate_supply(token_a.amount, token_b.amount)?;
@@ -345,8 +466,14 @@ impl Processor {
             }
             swap_constraints.validate_curve(&swap_curve)?;
             swap_constraints.validate_fees(&fees)?;
+            if let Some(dynamic_fee) = &dynamic_fee {
+                swap_constraints.validate_dynamic_fee(dynamic_fee)?;
+            }
         }
         fees.validate()?;
+        if let Some(dynamic_fee) = &dynamic_fee {
+            dynamic_fee.validate()?;
+        }
         swap_curve.calculator.validate()?;
 
         let initial_amount = swap_curve.calculator.new_pool_supply();
@@ -361,7 +488,8 @@ impl Processor {
             to_u64(initial_amount)?,
         )?;
 
-        let obj = SwapVersion::SwapV1(SwapV1 {
+        let clock = Clock::get()?;
+        let obj = SwapVersion::SwapV2(SwapV2 {
             is_initialized: true,
             bump_seed,
             token_program_id,
@@ -373,6 +501,17 @@ impl Processor {
             pool_fee_account: *fee_account_info.key,
             fees,
             swap_curve,
+            pause_authority,
+            is_paused: false,
+            price_a_cumulative: 0,
+            price_b_cumulative: 0,
+            last_update_slot: clock.slot,
+            last_update_timestamp: clock.unix_timestamp,
+            dynamic_fee,
+            volatility_bps: 0,
+            pending_fees: None,
+            pending_swap_curve: None,
+            pending_activation_timestamp: 0,
         });
         SwapVersion::pack(obj, &mut swap_info.data.borrow_mut())?;
         Ok(())
@@ -386,6 +525,56 @@ impl Processor {
         accounts: &[AccountInfo],
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
+        Self::process_swap_leg(
+            program_id,
+            amount_in,
+            minimum_amount_out,
+            account_info_iter,
+            true,
+        )?;
+        Ok(())
+    }
+
+    /// Processes a [RouteSwap](enum.Instruction.html), executing each leg in
+    /// order with the output of the previous leg as its input.
+    pub fn process_route_swap(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter().peekable();
+        let mut leg = Self::process_swap_leg(program_id, amount_in, 0, account_info_iter, false)?;
+        while account_info_iter.peek().is_some() {
+            let next_leg = Self::process_swap_leg(
+                program_id,
+                leg.amount_received,
+                0,
+                account_info_iter,
+                false,
+            )?;
+            if next_leg.source != leg.destination {
+                return Err(SwapError::InvalidInput.into());
+            }
+            leg = next_leg;
+        }
+        if leg.amount_received < minimum_amount_out {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        Ok(())
+    }
+
+    /// Swaps through a single pool, taking the accounts of a `Swap`
+    /// instruction from the iterator. The host fee account is only taken if
+    /// `with_host_fee` is set, since it cannot be told apart from the
+    /// accounts of the next leg of a route.
+    fn process_swap_leg<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        account_info_iter: &mut I,
+        with_host_fee: bool,
+    ) -> Result<SwapLeg, ProgramError> {
         let swap_info = next_account_info(account_info_iter)?;
         let authority_info = next_account_info(account_info_iter)?;
         let user_transfer_authority_info = next_account_info(account_info_iter)?;
@@ -405,6 +594,14 @@ impl Processor {
             return Err(ProgramError::IncorrectProgramId);
         }
         let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.is_paused() {
+            return Err(SwapError::SwapPaused.into());
+        }
+        let swap_curve = Self::current_swap_curve(
+            token_swap.swap_curve(),
+            token_swap.token_b_mint(),
+            account_info_iter,
+        )?;
 
         if *authority_info.key
             != Self::authority_id(program_id, swap_info.key, token_swap.bump_seed())?
@@ -472,14 +669,14 @@ impl Processor {
         } else {
             TradeDirection::BtoA
         };
-        let result = token_swap
-            .swap_curve()
+        let fees = Self::current_fees(token_swap.as_ref())?;
+        let result = swap_curve
             .swap(
                 to_u128(actual_amount_in)?,
                 to_u128(source_account.amount)?,
                 to_u128(dest_account.amount)?,
                 trade_direction,
-                token_swap.fees(),
+                &fees,
             )
             .ok_or(SwapError::ZeroTradingTokens)?;
 
@@ -506,7 +703,7 @@ impl Processor {
             (amount, source_mint.base.decimals)
         };
 
-        let (destination_transfer_amount, destination_mint_decimals) = {
+        let (destination_transfer_amount, destination_mint_decimals, amount_received) = {
             let destination_mint_data = destination_token_mint_info.data.borrow();
             let destination_mint = Self::unpack_mint_with_extensions(
                 &destination_mint_data,
@@ -528,7 +725,7 @@ impl Processor {
             if amount_received < minimum_amount_out {
                 return Err(SwapError::ExceededSlippage.into());
             }
-            (amount_out, destination_mint.base.decimals)
+            (amount_out, destination_mint.base.decimals, amount_received)
         };
 
         let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
@@ -542,6 +739,18 @@ impl Processor {
             ),
         };
 
+        let (old_token_a_amount, old_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (source_account.amount, dest_account.amount),
+            TradeDirection::BtoA => (dest_account.amount, source_account.amount),
+        };
+        Self::record_price_change(
+            swap_info,
+            old_token_a_amount,
+            old_token_b_amount,
+            to_u64(swap_token_a_amount)?,
+            to_u64(swap_token_b_amount)?,
+        )?;
+
         Self::token_transfer(
             swap_info.key,
             source_token_program_info.clone(),
@@ -555,8 +764,7 @@ impl Processor {
         )?;
 
         if result.owner_fee > 0 {
-            let mut pool_token_amount = token_swap
-                .swap_curve()
+            let mut pool_token_amount = swap_curve
                 .calculator
                 .withdraw_single_token_type_exact_out(
                     result.owner_fee,
@@ -568,7 +776,12 @@ impl Processor {
                 )
                 .ok_or(SwapError::FeeCalculationFailure)?;
             // Allow error to fall through
-            if let Ok(host_fee_account_info) = next_account_info(account_info_iter) {
+            let host_fee_account_info = if with_host_fee {
+                next_account_info(account_info_iter).ok()
+            } else {
+                None
+            };
+            if let Some(host_fee_account_info) = host_fee_account_info {
                 let host_fee_account = Self::unpack_token_account(
                     host_fee_account_info,
                     token_swap.token_program_id(),
@@ -623,7 +836,11 @@ impl Processor {
             destination_mint_decimals,
         )?;
 
-        Ok(())
+        Ok(SwapLeg {
+            source: *source_info.key,
+            destination: *destination_info.key,
+            amount_received,
+        })
     }
 
     /// Processes an [DepositAllTokenTypes](enum.Instruction.html).
@@ -651,6 +868,9 @@ impl Processor {
         let pool_token_program_info = next_account_info(account_info_iter)?;
 
         let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.is_paused() {
+            return Err(SwapError::SwapPaused.into());
+        }
         let calculator = &token_swap.swap_curve().calculator;
         if !calculator.allows_deposits() {
             return Err(SwapError::UnsupportedCurveOperation.into());
@@ -903,7 +1123,15 @@ impl Processor {
         let pool_token_program_info = next_account_info(account_info_iter)?;
 
         let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
-        let calculator = &token_swap.swap_curve().calculator;
+        if token_swap.is_paused() {
+            return Err(SwapError::SwapPaused.into());
+        }
+        let swap_curve = Self::current_swap_curve(
+            token_swap.swap_curve(),
+            token_swap.token_b_mint(),
+            account_info_iter,
+        )?;
+        let calculator = &swap_curve.calculator;
         if !calculator.allows_deposits() {
             return Err(SwapError::UnsupportedCurveOperation.into());
         }
@@ -944,15 +1172,14 @@ impl Processor {
         let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
         let pool_mint_supply = to_u128(pool_mint.supply)?;
         let pool_token_amount = if pool_mint_supply > 0 {
-            token_swap
-                .swap_curve()
+            swap_curve
                 .deposit_single_token_type(
                     to_u128(source_token_amount)?,
                     to_u128(swap_token_a.amount)?,
                     to_u128(swap_token_b.amount)?,
                     pool_mint_supply,
                     trade_direction,
-                    token_swap.fees(),
+                    &Self::current_fees(token_swap.as_ref())?,
                 )
                 .ok_or(SwapError::ZeroTradingTokens)?
         } else {
@@ -967,6 +1194,24 @@ impl Processor {
             return Err(SwapError::ZeroTradingTokens.into());
         }
 
+        let (new_token_a_amount, new_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (
+                swap_token_a.amount.saturating_add(source_token_amount),
+                swap_token_b.amount,
+            ),
+            TradeDirection::BtoA => (
+                swap_token_a.amount,
+                swap_token_b.amount.saturating_add(source_token_amount),
+            ),
+        };
+        Self::record_price_change(
+            swap_info,
+            swap_token_a.amount,
+            swap_token_b.amount,
+            new_token_a_amount,
+            new_token_b_amount,
+        )?;
+
         match trade_direction {
             TradeDirection::AtoB => {
                 Self::token_transfer(
@@ -1033,6 +1278,14 @@ impl Processor {
         let destination_token_program_info = next_account_info(account_info_iter)?;
 
         let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.is_paused() {
+            return Err(SwapError::SwapPaused.into());
+        }
+        let swap_curve = Self::current_swap_curve(
+            token_swap.swap_curve(),
+            token_swap.token_b_mint(),
+            account_info_iter,
+        )?;
         let destination_account =
             Self::unpack_token_account(destination_info, token_swap.token_program_id())?;
         let swap_token_a =
@@ -1071,15 +1324,14 @@ impl Processor {
         let swap_token_a_amount = to_u128(swap_token_a.amount)?;
         let swap_token_b_amount = to_u128(swap_token_b.amount)?;
 
-        let burn_pool_token_amount = token_swap
-            .swap_curve()
+        let burn_pool_token_amount = swap_curve
             .withdraw_single_token_type_exact_out(
                 to_u128(destination_token_amount)?,
                 swap_token_a_amount,
                 swap_token_b_amount,
                 pool_mint_supply,
                 trade_direction,
-                token_swap.fees(),
+                &Self::current_fees(token_swap.as_ref())?,
             )
             .ok_or(SwapError::ZeroTradingTokens)?;
 
@@ -1108,6 +1360,24 @@ impl Processor {
             return Err(SwapError::ZeroTradingTokens.into());
         }
 
+        let (new_token_a_amount, new_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (
+                swap_token_a.amount.saturating_sub(destination_token_amount),
+                swap_token_b.amount,
+            ),
+            TradeDirection::BtoA => (
+                swap_token_a.amount,
+                swap_token_b.amount.saturating_sub(destination_token_amount),
+            ),
+        };
+        Self::record_price_change(
+            swap_info,
+            swap_token_a.amount,
+            swap_token_b.amount,
+            new_token_a_amount,
+            new_token_b_amount,
+        )?;
+
         if withdraw_fee > 0 {
             Self::token_transfer(
                 swap_info.key,
@@ -1165,6 +1435,167 @@ impl Processor {
         Ok(())
     }
 
+    /// Processes a [Pause](enum.Instruction.html) or
+    /// [Unpause](enum.Instruction.html).
+    pub fn process_set_paused(
+        program_id: &Pubkey,
+        is_paused: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let pause_authority_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut token_swap = SwapVersion::unpack_latest(&swap_info.data.borrow())?;
+        if token_swap.pause_authority != COption::Some(*pause_authority_info.key) {
+            return Err(SwapError::InvalidPauseAuthority.into());
+        }
+        if !pause_authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        token_swap.is_paused = is_paused;
+        SwapVersion::pack(
+            SwapVersion::SwapV2(token_swap),
+            &mut swap_info.data.borrow_mut(),
+        )?;
+        Ok(())
+    }
+
+    /// Checks that the owner allowed to update the fees and curve of a swap
+    /// signed: the owner from the constraints, or the owner of the pool fee
+    /// account if there are none
+    fn check_update_owner(
+        token_swap: &SwapV2,
+        owner_info: &AccountInfo,
+        fee_account_info: &AccountInfo,
+        swap_constraints: &Option<SwapConstraints>,
+    ) -> ProgramResult {
+        let owner_key = if let Some(swap_constraints) = swap_constraints {
+            swap_constraints
+                .owner_key
+                .parse::<Pubkey>()
+                .map_err(|_| SwapError::InvalidOwner)?
+        } else {
+            if *fee_account_info.key != token_swap.pool_fee_account {
+                return Err(SwapError::IncorrectFeeAccount.into());
+            }
+            Self::unpack_token_account(fee_account_info, &token_swap.token_program_id)?.owner
+        };
+        if *owner_info.key != owner_key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if !owner_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
+
+    /// Processes a [SetFees](enum.Instruction.html), staging the fees until
+    /// the timelock elapsed.
+    pub fn process_set_fees(
+        program_id: &Pubkey,
+        fees: Fees,
+        accounts: &[AccountInfo],
+        swap_constraints: &Option<SwapConstraints>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let fee_account_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut token_swap = SwapVersion::unpack_latest(&swap_info.data.borrow())?;
+        Self::check_update_owner(&token_swap, owner_info, fee_account_info, swap_constraints)?;
+
+        if let Some(swap_constraints) = swap_constraints {
+            swap_constraints.validate_fees(&fees)?;
+        }
+        fees.validate()?;
+
+        token_swap.pending_fees = Some(fees);
+        token_swap.pending_activation_timestamp =
+            Clock::get()?.unix_timestamp.saturating_add(UPDATE_TIMELOCK);
+        SwapVersion::pack(
+            SwapVersion::SwapV2(token_swap),
+            &mut swap_info.data.borrow_mut(),
+        )
+    }
+
+    /// Processes a [SetCurveParameters](enum.Instruction.html), staging the
+    /// curve until the timelock elapsed.
+    pub fn process_set_curve_parameters(
+        program_id: &Pubkey,
+        swap_curve: SwapCurve,
+        accounts: &[AccountInfo],
+        swap_constraints: &Option<SwapConstraints>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let fee_account_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut token_swap = SwapVersion::unpack_latest(&swap_info.data.borrow())?;
+        Self::check_update_owner(&token_swap, owner_info, fee_account_info, swap_constraints)?;
+
+        if swap_curve.curve_type != token_swap.swap_curve.curve_type {
+            return Err(SwapError::InvalidCurve.into());
+        }
+        if let Some(swap_constraints) = swap_constraints {
+            swap_constraints.validate_curve(&swap_curve)?;
+        }
+        swap_curve.calculator.validate()?;
+
+        token_swap.pending_swap_curve = Some(swap_curve);
+        token_swap.pending_activation_timestamp =
+            Clock::get()?.unix_timestamp.saturating_add(UPDATE_TIMELOCK);
+        SwapVersion::pack(
+            SwapVersion::SwapV2(token_swap),
+            &mut swap_info.data.borrow_mut(),
+        )
+    }
+
+    /// Processes an [ApplyPendingUpdate](enum.Instruction.html), replacing
+    /// the fees and curve of the swap with the pending ones.
+    pub fn process_apply_pending_update(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut token_swap = SwapVersion::unpack_latest(&swap_info.data.borrow())?;
+        if token_swap.pending_fees.is_none() && token_swap.pending_swap_curve.is_none() {
+            return Err(SwapError::NoPendingUpdate.into());
+        }
+        if Clock::get()?.unix_timestamp < token_swap.pending_activation_timestamp {
+            return Err(SwapError::TimelockNotElapsed.into());
+        }
+
+        if let Some(fees) = token_swap.pending_fees.take() {
+            token_swap.fees = fees;
+        }
+        if let Some(swap_curve) = token_swap.pending_swap_curve.take() {
+            token_swap.swap_curve = swap_curve;
+        }
+        token_swap.pending_activation_timestamp = 0;
+        SwapVersion::pack(
+            SwapVersion::SwapV2(token_swap),
+            &mut swap_info.data.borrow_mut(),
+        )
+    }
+
     /// Processes an [Instruction](enum.Instruction.html).
     pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
         Self::process_with_constraints(program_id, accounts, input, &SWAP_CONSTRAINTS)
@@ -1179,9 +1610,22 @@ impl Processor {
     ) -> ProgramResult {
         let instruction = SwapInstruction::unpack(input)?;
         match instruction {
-            SwapInstruction::Initialize(Initialize { fees, swap_curve }) => {
+            SwapInstruction::Initialize(Initialize {
+                fees,
+                swap_curve,
+                pause_authority,
+                dynamic_fee,
+            }) => {
                 msg!("Instruction: Init");
-                Self::process_initialize(program_id, fees, swap_curve, accounts, swap_constraints)
+                Self::process_initialize(
+                    program_id,
+                    fees,
+                    swap_curve,
+                    pause_authority,
+                    dynamic_fee,
+                    accounts,
+                    swap_constraints,
+                )
             }
             SwapInstruction::Swap(Swap {
                 amount_in,
@@ -1190,6 +1634,13 @@ impl Processor {
                 msg!("Instruction: Swap");
                 Self::process_swap(program_id, amount_in, minimum_amount_out, accounts)
             }
+            SwapInstruction::RouteSwap(RouteSwap {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                msg!("Instruction: RouteSwap");
+                Self::process_route_swap(program_id, amount_in, minimum_amount_out, accounts)
+            }
             SwapInstruction::DepositAllTokenTypes(DepositAllTokenTypes {
                 pool_token_amount,
                 maximum_token_a_amount,
@@ -1246,6 +1697,31 @@ impl Processor {
                     accounts,
                 )
             }
+            SwapInstruction::Pause => {
+                msg!("Instruction: Pause");
+                Self::process_set_paused(program_id, true, accounts)
+            }
+            SwapInstruction::Unpause => {
+                msg!("Instruction: Unpause");
+                Self::process_set_paused(program_id, false, accounts)
+            }
+            SwapInstruction::SetFees(SetFees { fees }) => {
+                msg!("Instruction: SetFees");
+                Self::process_set_fees(program_id, fees, accounts, swap_constraints)
+            }
+            SwapInstruction::SetCurveParameters(SetCurveParameters { swap_curve }) => {
+                msg!("Instruction: SetCurveParameters");
+                Self::process_set_curve_parameters(
+                    program_id,
+                    swap_curve,
+                    accounts,
+                    swap_constraints,
+                )
+            }
+            SwapInstruction::ApplyPendingUpdate => {
+                msg!("Instruction: ApplyPendingUpdate");
+                Self::process_apply_pending_update(program_id, accounts)
+            }
         }
     }
 }
@@ -1283,11 +1759,19 @@ mod tests {
                 constant_price::ConstantPriceCurve,
                 constant_product::ConstantProductCurve,
                 offset::OffsetCurve,
+                stable::StableCurve,
             },
             instruction::{
-                deposit_all_token_types, deposit_single_token_type_exact_amount_in, initialize,
-                swap, withdraw_all_token_types, withdraw_single_token_type_exact_amount_out,
+                apply_pending_update, deposit_all_token_types,
+                deposit_single_token_type_exact_amount_in, initialize, pause, route_swap,
+                set_curve_parameters, set_fees, swap, unpause, with_stake_pool,
+                withdraw_all_token_types, withdraw_single_token_type_exact_amount_out,
             },
+            oracle::{
+                spot_price, time_weighted_average_price, TimeWeightedAveragePrice,
+                PRICE_FRACTIONAL_BITS,
+            },
+            state::SwapV1,
         },
         solana_program::{
             clock::Clock, entrypoint::SUCCESS, instruction::Instruction, program_pack::Pack,
@@ -1296,6 +1780,7 @@ mod tests {
         solana_sdk::account::{
             create_account_for_test, create_is_signer_account_infos, Account as SolanaAccount,
         },
+        spl_stake_pool::state::AccountType as StakePoolAccountType,
         spl_token_2022::{
             error::TokenError,
             extension::{
@@ -1308,7 +1793,7 @@ mod tests {
                 mint_to, revoke, set_authority, AuthorityType,
             },
         },
-        std::sync::Arc,
+        std::{cell::RefCell, sync::Arc},
         test_case::test_case,
     };
 
@@ -1370,12 +1855,28 @@ mod tests {
 
         fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
             unsafe {
-                *(var_addr as *mut _ as *mut Clock) = Clock::default();
+                *(var_addr as *mut _ as *mut Clock) =
+                    TEST_CLOCK.with(|clock| clock.borrow().clone());
             }
             SUCCESS
         }
     }
 
+    thread_local! {
+        // Clock returned to the program, per test thread
+        static TEST_CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+    }
+
+    fn set_test_clock(slot: u64, unix_timestamp: i64) {
+        TEST_CLOCK.with(|clock| {
+            *clock.borrow_mut() = Clock {
+                slot,
+                unix_timestamp,
+                ..Clock::default()
+            }
+        });
+    }
+
     fn test_syscall_stubs() {
         use std::sync::Once;
         static ONCE: Once = Once::new();
@@ -1398,8 +1899,11 @@ mod tests {
         fees: Fees,
         transfer_fees: SwapTransferFees,
         swap_curve: SwapCurve,
+        pause_authority_key: Option<Pubkey>,
+        dynamic_fee: Option<DynamicFee>,
         swap_key: Pubkey,
         swap_account: SolanaAccount,
+        swap_account_is_writable: bool,
         pool_mint_key: Pubkey,
         pool_mint_account: SolanaAccount,
         pool_fee_key: Pubkey,
@@ -1497,8 +2001,11 @@ mod tests {
                 fees,
                 transfer_fees,
                 swap_curve,
+                pause_authority_key: None,
+                dynamic_fee: None,
                 swap_key,
                 swap_account,
+                swap_account_is_writable: true,
                 pool_mint_key,
                 pool_mint_account,
                 pool_fee_key,
@@ -1533,6 +2040,8 @@ mod tests {
                     &self.pool_token_key,
                     self.fees.clone(),
                     self.swap_curve.clone(),
+                    self.pause_authority_key.as_ref(),
+                    self.dynamic_fee,
                 )
                 .unwrap(),
                 vec![
@@ -1690,30 +2199,32 @@ mod tests {
             let mut swap_destination_account = self.get_token_account(swap_destination_key).clone();
 
             // perform the swap
+            let mut instruction = swap(
+                &SWAP_PROGRAM_ID,
+                source_token_program_id,
+                destination_token_program_id,
+                &self.pool_token_program_id,
+                &self.swap_key,
+                &self.authority_key,
+                &user_transfer_key,
+                user_source_key,
+                swap_source_key,
+                swap_destination_key,
+                user_destination_key,
+                &self.pool_mint_key,
+                &self.pool_fee_key,
+                &source_mint_key,
+                &destination_mint_key,
+                None,
+                Swap {
+                    amount_in,
+                    minimum_amount_out,
+                },
+            )
+            .unwrap();
+            instruction.accounts[0].is_writable = self.swap_account_is_writable;
             do_process_instruction(
-                swap(
-                    &SWAP_PROGRAM_ID,
-                    source_token_program_id,
-                    destination_token_program_id,
-                    &self.pool_token_program_id,
-                    &self.swap_key,
-                    &self.authority_key,
-                    &user_transfer_key,
-                    user_source_key,
-                    swap_source_key,
-                    swap_destination_key,
-                    user_destination_key,
-                    &self.pool_mint_key,
-                    &self.pool_fee_key,
-                    &source_mint_key,
-                    &destination_mint_key,
-                    None,
-                    Swap {
-                        amount_in,
-                        minimum_amount_out,
-                    },
-                )
-                .unwrap(),
+                instruction,
                 vec![
                     &mut self.swap_account,
                     &mut SolanaAccount::default(),
@@ -1738,6 +2249,22 @@ mod tests {
             Ok(())
         }
 
+        pub fn set_paused(
+            &mut self,
+            pause_authority_key: &Pubkey,
+            is_paused: bool,
+        ) -> ProgramResult {
+            let instruction = if is_paused {
+                pause(&SWAP_PROGRAM_ID, &self.swap_key, pause_authority_key)
+            } else {
+                unpause(&SWAP_PROGRAM_ID, &self.swap_key, pause_authority_key)
+            };
+            do_process_instruction(
+                instruction.unwrap(),
+                vec![&mut self.swap_account, &mut SolanaAccount::default()],
+            )
+        }
+
         #[allow(clippy::too_many_arguments)]
         pub fn deposit_all_token_types(
             &mut self,
@@ -2100,6 +2627,9 @@ mod tests {
             .map(|(account_meta, account)| (&account_meta.pubkey, account_meta.is_signer, account))
             .collect::<Vec<_>>();
         let mut account_infos = create_is_signer_account_infos(&mut meta);
+        for (account_info, account_meta) in account_infos.iter_mut().zip(&instruction.accounts) {
+            account_info.is_writable = account_meta.is_writable;
+        }
         let res = if instruction.program_id == SWAP_PROGRAM_ID {
             Processor::process_with_constraints(
                 &instruction.program_id,
@@ -2151,6 +2681,60 @@ mod tests {
         do_process_instruction_with_fee_constraints(instruction, accounts, &SWAP_CONSTRAINTS)
     }
 
+    // accounts are given once per key, and shared by every position of the
+    // instruction using that key, as in the actual runtime
+    fn do_process_instruction_with_shared_accounts(
+        instruction: Instruction,
+        accounts: Vec<(&Pubkey, &mut SolanaAccount)>,
+    ) -> ProgramResult {
+        test_syscall_stubs();
+
+        let mut account_clones = accounts
+            .iter()
+            .map(|(key, account)| (**key, (*account).clone()))
+            .collect::<Vec<_>>();
+        let res = {
+            let mut meta = account_clones
+                .iter_mut()
+                .map(|(key, account)| {
+                    let is_signer = instruction
+                        .accounts
+                        .iter()
+                        .any(|account_meta| account_meta.pubkey == *key && account_meta.is_signer);
+                    (&*key, is_signer, account)
+                })
+                .collect::<Vec<_>>();
+            let shared_account_infos = create_is_signer_account_infos(&mut meta);
+            let account_infos = instruction
+                .accounts
+                .iter()
+                .map(|account_meta| {
+                    let account_info = shared_account_infos
+                        .iter()
+                        .find(|account_info| *account_info.key == account_meta.pubkey)
+                        .unwrap();
+                    AccountInfo {
+                        is_writable: account_meta.is_writable,
+                        ..account_info.clone()
+                    }
+                })
+                .collect::<Vec<_>>();
+            Processor::process_with_constraints(
+                &instruction.program_id,
+                &account_infos,
+                &instruction.data,
+                &SWAP_CONSTRAINTS,
+            )
+        };
+
+        if res.is_ok() {
+            for ((_, account), (_, account_clone)) in accounts.into_iter().zip(account_clones) {
+                *account = account_clone;
+            }
+        }
+        res
+    }
+
     fn mint_token(
         program_id: &Pubkey,
         mint_key: &Pubkey,
@@ -2921,6 +3505,8 @@ mod tests {
                         &accounts.pool_token_key,
                         accounts.fees.clone(),
                         accounts.swap_curve.clone(),
+                        None,
+                        None,
                     )
                     .unwrap(),
                     vec![
@@ -3089,9 +3675,74 @@ mod tests {
             accounts.initialize_swap().unwrap();
         }
 
-        // wrong owner key in constraint
+        // create invalid stable swap
         {
-            let new_key = Pubkey::new_unique();
+            let amp = 0;
+            let fees = Fees {
+                trade_fee_numerator,
+                trade_fee_denominator,
+                owner_trade_fee_numerator,
+                owner_trade_fee_denominator,
+                owner_withdraw_fee_numerator,
+                owner_withdraw_fee_denominator,
+                host_fee_numerator,
+                host_fee_denominator,
+            };
+            let swap_curve = SwapCurve {
+                curve_type: CurveType::Stable,
+                calculator: Arc::new(StableCurve { amp }),
+            };
+            let mut accounts = SwapAccountInfo::new(
+                &user_key,
+                fees,
+                SwapTransferFees::default(),
+                swap_curve,
+                token_a_amount,
+                token_b_amount,
+                &pool_token_program_id,
+                &token_a_program_id,
+                &token_b_program_id,
+            );
+            assert_eq!(
+                Err(SwapError::InvalidCurve.into()),
+                accounts.initialize_swap()
+            );
+        }
+
+        // create valid stable swap
+        {
+            let amp = 100;
+            let fees = Fees {
+                trade_fee_numerator,
+                trade_fee_denominator,
+                owner_trade_fee_numerator,
+                owner_trade_fee_denominator,
+                owner_withdraw_fee_numerator,
+                owner_withdraw_fee_denominator,
+                host_fee_numerator,
+                host_fee_denominator,
+            };
+            let swap_curve = SwapCurve {
+                curve_type: CurveType::Stable,
+                calculator: Arc::new(StableCurve { amp }),
+            };
+            let mut accounts = SwapAccountInfo::new(
+                &user_key,
+                fees,
+                SwapTransferFees::default(),
+                swap_curve,
+                token_a_amount,
+                token_b_amount,
+                &pool_token_program_id,
+                &token_a_program_id,
+                &token_b_program_id,
+            );
+            accounts.initialize_swap().unwrap();
+        }
+
+        // wrong owner key in constraint
+        {
+            let new_key = Pubkey::new_unique();
             let trade_fee_numerator = 25;
             let trade_fee_denominator = 10000;
             let owner_trade_fee_numerator = 5;
@@ -3146,6 +3797,8 @@ mod tests {
                         &accounts.pool_token_key,
                         accounts.fees.clone(),
                         accounts.swap_curve.clone(),
+                        None,
+                        None,
                     )
                     .unwrap(),
                     vec![
@@ -3221,6 +3874,8 @@ mod tests {
                         &accounts.pool_token_key,
                         accounts.fees.clone(),
                         accounts.swap_curve.clone(),
+                        None,
+                        None,
                     )
                     .unwrap(),
                     vec![
@@ -3292,6 +3947,8 @@ mod tests {
                     &accounts.pool_token_key,
                     accounts.fees,
                     accounts.swap_curve.clone(),
+                    None,
+                    None,
                 )
                 .unwrap(),
                 vec![
@@ -6365,7 +7022,7 @@ mod tests {
         );
         let token_b_offset = 10_000_000_000;
         check_valid_swap_curve(
-            fees,
+            fees.clone(),
             SwapTransferFees::default(),
             CurveType::Offset,
             Arc::new(OffsetCurve { token_b_offset }),
@@ -6375,6 +7032,18 @@ mod tests {
             &token_a_program_id,
             &token_b_program_id,
         );
+        let amp = 100;
+        check_valid_swap_curve(
+            fees,
+            SwapTransferFees::default(),
+            CurveType::Stable,
+            Arc::new(StableCurve { amp }),
+            token_a_amount,
+            token_b_amount,
+            &pool_token_program_id,
+            &token_a_program_id,
+            &token_b_program_id,
+        );
     }
 
     #[test_case(spl_token::id(), spl_token::id(), spl_token::id(); "all-token")]
@@ -6433,7 +7102,7 @@ mod tests {
         );
         let token_b_offset = 1;
         check_valid_swap_curve(
-            fees,
+            fees.clone(),
             SwapTransferFees::default(),
             CurveType::Offset,
             Arc::new(OffsetCurve { token_b_offset }),
@@ -6443,6 +7112,18 @@ mod tests {
             &token_a_program_id,
             &token_b_program_id,
         );
+        let amp = 100;
+        check_valid_swap_curve(
+            fees,
+            SwapTransferFees::default(),
+            CurveType::Stable,
+            Arc::new(StableCurve { amp }),
+            token_a_amount,
+            token_b_amount,
+            &pool_token_program_id,
+            &token_a_program_id,
+            &token_b_program_id,
+        );
     }
 
     #[test_case(spl_token::id(), spl_token::id(), spl_token::id(); "all-token")]
@@ -6518,6 +7199,8 @@ mod tests {
                 &accounts.pool_token_key,
                 accounts.fees.clone(),
                 accounts.swap_curve.clone(),
+                None,
+                None,
             )
             .unwrap(),
             vec![
@@ -8223,6 +8906,8 @@ mod tests {
                 &accounts.pool_token_key,
                 accounts.fees.clone(),
                 accounts.swap_curve.clone(),
+                None,
+                None,
             )
             .unwrap(),
             vec![
@@ -8377,4 +9062,1298 @@ mod tests {
             &token_b_program_id,
         );
     }
+
+    fn create_stake_pool(
+        pool_mint: &Pubkey,
+        total_lamports: u64,
+        pool_token_supply: u64,
+    ) -> (Pubkey, SolanaAccount) {
+        let stake_pool = StakePool {
+            account_type: StakePoolAccountType::StakePool,
+            pool_mint: *pool_mint,
+            total_lamports,
+            pool_token_supply,
+            ..StakePool::default()
+        };
+        let mut stake_pool_account = SolanaAccount::new(0, 0, &spl_stake_pool::id());
+        stake_pool_account.data = borsh::to_vec(&stake_pool).unwrap();
+        (Pubkey::new_unique(), stake_pool_account)
+    }
+
+    #[test_case(spl_token::id(); "token")]
+    #[test_case(spl_token_2022::id(); "token-2022")]
+    fn test_stake_pool_stable_curve(token_program_id: Pubkey) {
+        let user_key = Pubkey::new_unique();
+        let swapper_key = Pubkey::new_unique();
+        let token_a_amount = 1_050_000;
+        let token_b_amount = 1_000_000;
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::StakePoolStable,
+            calculator: Arc::new(StakePoolStableCurve {
+                amp: 100,
+                ..StakePoolStableCurve::default()
+            }),
+        };
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            Fees::default(),
+            SwapTransferFees::default(),
+            swap_curve,
+            token_a_amount,
+            token_b_amount,
+            &token_program_id,
+            &token_program_id,
+            &token_program_id,
+        );
+        let (stake_pool_key, mut stake_pool_account) =
+            create_stake_pool(&accounts.token_b_mint_key, 105_000_000, 100_000_000);
+
+        let initialize_instruction = initialize(
+            &SWAP_PROGRAM_ID,
+            &accounts.pool_token_program_id,
+            &accounts.swap_key,
+            &accounts.authority_key,
+            &accounts.token_a_key,
+            &accounts.token_b_key,
+            &accounts.pool_mint_key,
+            &accounts.pool_fee_key,
+            &accounts.pool_token_key,
+            accounts.fees.clone(),
+            accounts.swap_curve.clone(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        // missing stake pool
+        {
+            assert_eq!(
+                Err(ProgramError::NotEnoughAccountKeys),
+                accounts.initialize_swap()
+            );
+        }
+
+        // stake pool not owned by the stake pool program
+        {
+            let mut wrong_owner_account = stake_pool_account.clone();
+            wrong_owner_account.owner = Pubkey::new_unique();
+            assert_eq!(
+                Err(SwapError::IncorrectStakePool.into()),
+                do_process_instruction(
+                    with_stake_pool(initialize_instruction.clone(), &stake_pool_key).unwrap(),
+                    vec![
+                        &mut accounts.swap_account,
+                        &mut SolanaAccount::default(),
+                        &mut accounts.token_a_account,
+                        &mut accounts.token_b_account,
+                        &mut accounts.pool_mint_account,
+                        &mut accounts.pool_fee_account,
+                        &mut accounts.pool_token_account,
+                        &mut SolanaAccount::default(),
+                        &mut wrong_owner_account,
+                    ],
+                )
+            );
+        }
+
+        // stake pool for a different mint
+        {
+            let (wrong_mint_key, mut wrong_mint_account) =
+                create_stake_pool(&accounts.token_a_mint_key, 105_000_000, 100_000_000);
+            assert_eq!(
+                Err(SwapError::IncorrectStakePool.into()),
+                do_process_instruction(
+                    with_stake_pool(initialize_instruction.clone(), &wrong_mint_key).unwrap(),
+                    vec![
+                        &mut accounts.swap_account,
+                        &mut SolanaAccount::default(),
+                        &mut accounts.token_a_account,
+                        &mut accounts.token_b_account,
+                        &mut accounts.pool_mint_account,
+                        &mut accounts.pool_fee_account,
+                        &mut accounts.pool_token_account,
+                        &mut SolanaAccount::default(),
+                        &mut wrong_mint_account,
+                    ],
+                )
+            );
+        }
+
+        // correct stake pool, exchange rate is stored at initialization
+        {
+            do_process_instruction(
+                with_stake_pool(initialize_instruction, &stake_pool_key).unwrap(),
+                vec![
+                    &mut accounts.swap_account,
+                    &mut SolanaAccount::default(),
+                    &mut accounts.token_a_account,
+                    &mut accounts.token_b_account,
+                    &mut accounts.pool_mint_account,
+                    &mut accounts.pool_fee_account,
+                    &mut accounts.pool_token_account,
+                    &mut SolanaAccount::default(),
+                    &mut stake_pool_account,
+                ],
+            )
+            .unwrap();
+            let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+            assert_eq!(
+                swap_state.swap_curve().curve_type,
+                CurveType::StakePoolStable
+            );
+            let mut packed_calculator = [0u8; StakePoolStableCurve::LEN];
+            swap_state
+                .swap_curve()
+                .calculator
+                .pack_into_slice(&mut packed_calculator);
+            assert_eq!(
+                StakePoolStableCurve::unpack_from_slice(&packed_calculator).unwrap(),
+                StakePoolStableCurve {
+                    amp: 100,
+                    total_lamports: 105_000_000,
+                    pool_token_supply: 100_000_000,
+                }
+            );
+        }
+
+        // swap mLN for stake pool tokens at the stake pool's exchange rate
+        {
+            let amount_in = 10_000;
+            let (
+                token_a_key,
+                mut token_a_account,
+                token_b_key,
+                mut token_b_account,
+                _pool_key,
+                _pool_account,
+            ) = accounts.setup_token_accounts(&user_key, &swapper_key, amount_in, 0, 0);
+            let user_transfer_key = Pubkey::new_unique();
+            do_process_instruction(
+                approve(
+                    &token_program_id,
+                    &token_a_key,
+                    &user_transfer_key,
+                    &swapper_key,
+                    &[],
+                    amount_in,
+                )
+                .unwrap(),
+                vec![
+                    &mut token_a_account,
+                    &mut SolanaAccount::default(),
+                    &mut SolanaAccount::default(),
+                ],
+            )
+            .unwrap();
+            let swap_instruction = swap(
+                &SWAP_PROGRAM_ID,
+                &token_program_id,
+                &token_program_id,
+                &token_program_id,
+                &accounts.swap_key,
+                &accounts.authority_key,
+                &user_transfer_key,
+                &token_a_key,
+                &accounts.token_a_key,
+                &accounts.token_b_key,
+                &token_b_key,
+                &accounts.pool_mint_key,
+                &accounts.pool_fee_key,
+                &accounts.token_a_mint_key,
+                &accounts.token_b_mint_key,
+                None,
+                Swap {
+                    amount_in,
+                    minimum_amount_out: 0,
+                },
+            )
+            .unwrap();
+
+            // the stake pool is required to price the swap
+            assert_eq!(
+                Err(ProgramError::NotEnoughAccountKeys),
+                do_process_instruction(
+                    swap_instruction.clone(),
+                    vec![
+                        &mut accounts.swap_account,
+                        &mut SolanaAccount::default(),
+                        &mut SolanaAccount::default(),
+                        &mut token_a_account,
+                        &mut accounts.token_a_account,
+                        &mut accounts.token_b_account,
+                        &mut token_b_account,
+                        &mut accounts.pool_mint_account,
+                        &mut accounts.pool_fee_account,
+                        &mut accounts.token_a_mint_account,
+                        &mut accounts.token_b_mint_account,
+                        &mut SolanaAccount::default(),
+                        &mut SolanaAccount::default(),
+                        &mut SolanaAccount::default(),
+                    ],
+                )
+            );
+
+            do_process_instruction(
+                with_stake_pool(swap_instruction, &stake_pool_key).unwrap(),
+                vec![
+                    &mut accounts.swap_account,
+                    &mut SolanaAccount::default(),
+                    &mut SolanaAccount::default(),
+                    &mut token_a_account,
+                    &mut accounts.token_a_account,
+                    &mut accounts.token_b_account,
+                    &mut token_b_account,
+                    &mut accounts.pool_mint_account,
+                    &mut accounts.pool_fee_account,
+                    &mut accounts.token_a_mint_account,
+                    &mut accounts.token_b_mint_account,
+                    &mut SolanaAccount::default(),
+                    &mut SolanaAccount::default(),
+                    &mut SolanaAccount::default(),
+                    &mut stake_pool_account,
+                ],
+            )
+            .unwrap();
+
+            let token_b = StateWithExtensions::<Account>::unpack(&token_b_account.data).unwrap();
+            assert_eq!(token_b.base.amount, 9_522);
+            let swap_token_a =
+                StateWithExtensions::<Account>::unpack(&accounts.token_a_account.data).unwrap();
+            assert_eq!(swap_token_a.base.amount, token_a_amount + amount_in);
+        }
+    }
+
+    #[test_case(spl_token::id(); "token")]
+    #[test_case(spl_token_2022::id(); "token-2022")]
+    fn test_pause(token_program_id: Pubkey) {
+        let user_key = Pubkey::new_unique();
+        let pause_authority_key = Pubkey::new_unique();
+        let trader_key = Pubkey::new_unique();
+        let token_a_amount = 1_000_000;
+        let token_b_amount = 1_000_000;
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve {}),
+        };
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            Fees::default(),
+            SwapTransferFees::default(),
+            swap_curve,
+            token_a_amount,
+            token_b_amount,
+            &token_program_id,
+            &token_program_id,
+            &token_program_id,
+        );
+        accounts.pause_authority_key = Some(pause_authority_key);
+        accounts.initialize_swap().unwrap();
+
+        let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+        assert_eq!(
+            swap_state.pause_authority(),
+            COption::Some(pause_authority_key)
+        );
+        assert!(!swap_state.is_paused());
+
+        let initial_a = 10_000;
+        let initial_b = 10_000;
+        let initial_pool = 10_000_000;
+        let (
+            token_a_key,
+            mut token_a_account,
+            token_b_key,
+            mut token_b_account,
+            pool_key,
+            mut pool_account,
+        ) = accounts.setup_token_accounts(
+            &user_key,
+            &trader_key,
+            initial_a,
+            initial_b,
+            initial_pool,
+        );
+        let swap_token_a_key = accounts.token_a_key;
+        let swap_token_b_key = accounts.token_b_key;
+
+        // wrong pause authority
+        {
+            assert_eq!(
+                Err(SwapError::InvalidPauseAuthority.into()),
+                accounts.set_paused(&Pubkey::new_unique(), true)
+            );
+        }
+
+        // pause authority did not sign
+        {
+            let mut instruction =
+                pause(&SWAP_PROGRAM_ID, &accounts.swap_key, &pause_authority_key).unwrap();
+            instruction.accounts[1].is_signer = false;
+            assert_eq!(
+                Err(ProgramError::MissingRequiredSignature),
+                do_process_instruction(
+                    instruction,
+                    vec![&mut accounts.swap_account, &mut SolanaAccount::default()],
+                )
+            );
+        }
+
+        accounts.set_paused(&pause_authority_key, true).unwrap();
+        let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+        assert!(swap_state.is_paused());
+
+        // swaps are rejected
+        {
+            assert_eq!(
+                Err(SwapError::SwapPaused.into()),
+                accounts.swap(
+                    &trader_key,
+                    &token_a_key,
+                    &mut token_a_account,
+                    &swap_token_a_key,
+                    &swap_token_b_key,
+                    &token_b_key,
+                    &mut token_b_account,
+                    initial_a,
+                    0,
+                )
+            );
+        }
+
+        // deposits are rejected
+        {
+            assert_eq!(
+                Err(SwapError::SwapPaused.into()),
+                accounts.deposit_all_token_types(
+                    &trader_key,
+                    &token_a_key,
+                    &mut token_a_account,
+                    &token_b_key,
+                    &mut token_b_account,
+                    &pool_key,
+                    &mut pool_account,
+                    initial_pool,
+                    initial_a,
+                    initial_b,
+                )
+            );
+            assert_eq!(
+                Err(SwapError::SwapPaused.into()),
+                accounts.deposit_single_token_type_exact_amount_in(
+                    &trader_key,
+                    &token_a_key,
+                    &mut token_a_account,
+                    &pool_key,
+                    &mut pool_account,
+                    initial_a,
+                    0,
+                )
+            );
+        }
+
+        // single token withdrawals are rejected, since they are priced by the
+        // curve
+        {
+            assert_eq!(
+                Err(SwapError::SwapPaused.into()),
+                accounts.withdraw_single_token_type_exact_amount_out(
+                    &trader_key,
+                    &pool_key,
+                    &mut pool_account,
+                    &token_a_key,
+                    &mut token_a_account,
+                    1_000,
+                    initial_pool,
+                )
+            );
+        }
+
+        // withdrawing both tokens is allowed so that liquidity providers can
+        // exit
+        {
+            accounts
+                .withdraw_all_token_types(
+                    &trader_key,
+                    &pool_key,
+                    &mut pool_account,
+                    &token_a_key,
+                    &mut token_a_account,
+                    &token_b_key,
+                    &mut token_b_account,
+                    initial_pool,
+                    0,
+                    0,
+                )
+                .unwrap();
+        }
+
+        accounts.set_paused(&pause_authority_key, false).unwrap();
+        let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+        assert!(!swap_state.is_paused());
+
+        // swaps are allowed again
+        {
+            accounts
+                .swap(
+                    &trader_key,
+                    &token_a_key,
+                    &mut token_a_account,
+                    &swap_token_a_key,
+                    &swap_token_b_key,
+                    &token_b_key,
+                    &mut token_b_account,
+                    initial_a,
+                    0,
+                )
+                .unwrap();
+        }
+    }
+
+    #[test_case(spl_token::id(); "token")]
+    #[test_case(spl_token_2022::id(); "token-2022")]
+    fn test_pause_without_pause_authority(token_program_id: Pubkey) {
+        let user_key = Pubkey::new_unique();
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve {}),
+        };
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            Fees::default(),
+            SwapTransferFees::default(),
+            swap_curve,
+            1_000_000,
+            1_000_000,
+            &token_program_id,
+            &token_program_id,
+            &token_program_id,
+        );
+        accounts.initialize_swap().unwrap();
+
+        assert_eq!(
+            Err(SwapError::InvalidPauseAuthority.into()),
+            accounts.set_paused(&user_key, true)
+        );
+    }
+
+    #[test_case(spl_token::id(); "token")]
+    #[test_case(spl_token_2022::id(); "token-2022")]
+    fn test_price_oracle(token_program_id: Pubkey) {
+        let user_key = Pubkey::new_unique();
+        let trader_key = Pubkey::new_unique();
+        let token_a_amount = 1_000_000;
+        let token_b_amount = 1_000_000;
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve {}),
+        };
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            Fees::default(),
+            SwapTransferFees::default(),
+            swap_curve,
+            token_a_amount,
+            token_b_amount,
+            &token_program_id,
+            &token_program_id,
+            &token_program_id,
+        );
+        set_test_clock(1, 1_000);
+        accounts.initialize_swap().unwrap();
+
+        let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+        let initial = Observation::from_swap_state(swap_state.as_ref());
+        assert_eq!(initial.price_a_cumulative, 0);
+        assert_eq!(initial.price_b_cumulative, 0);
+        assert_eq!(initial.slot, 1);
+        assert_eq!(initial.timestamp, 1_000);
+
+        let initial_a = 100_000;
+        let initial_b = 100_000;
+        let (token_a_key, mut token_a_account, token_b_key, mut token_b_account, _, _) =
+            accounts.setup_token_accounts(&user_key, &trader_key, initial_a, initial_b, 0);
+        let swap_token_a_key = accounts.token_a_key;
+        let swap_token_b_key = accounts.token_b_key;
+
+        // the initial price accumulates until the first swap
+        set_test_clock(5, 1_010);
+        accounts
+            .swap(
+                &trader_key,
+                &token_a_key,
+                &mut token_a_account,
+                &swap_token_a_key,
+                &swap_token_b_key,
+                &token_b_key,
+                &mut token_b_account,
+                initial_a,
+                0,
+            )
+            .unwrap();
+        let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+        let first = Observation::from_swap_state(swap_state.as_ref());
+        assert_eq!(
+            first,
+            initial.accumulate(token_a_amount, token_b_amount, 5, 1_010)
+        );
+        assert_eq!(
+            time_weighted_average_price(&initial, &first).unwrap(),
+            TimeWeightedAveragePrice {
+                price_a: 1 << PRICE_FRACTIONAL_BITS,
+                price_b: 1 << PRICE_FRACTIONAL_BITS,
+            }
+        );
+
+        // the price after the first swap accumulates until the second one
+        let swap_token_a_amount =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_account.data)
+                .unwrap()
+                .base
+                .amount;
+        let swap_token_b_amount =
+            StateWithExtensions::<Account>::unpack(&accounts.token_b_account.data)
+                .unwrap()
+                .base
+                .amount;
+        set_test_clock(9, 1_040);
+        accounts
+            .swap(
+                &trader_key,
+                &token_b_key,
+                &mut token_b_account,
+                &swap_token_b_key,
+                &swap_token_a_key,
+                &token_a_key,
+                &mut token_a_account,
+                initial_b,
+                0,
+            )
+            .unwrap();
+        let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+        let second = Observation::from_swap_state(swap_state.as_ref());
+        assert_eq!(
+            second,
+            first.accumulate(swap_token_a_amount, swap_token_b_amount, 9, 1_040)
+        );
+        assert_eq!(
+            time_weighted_average_price(&first, &second).unwrap(),
+            TimeWeightedAveragePrice {
+                price_a: spot_price(swap_token_a_amount, swap_token_b_amount).unwrap(),
+                price_b: spot_price(swap_token_b_amount, swap_token_a_amount).unwrap(),
+            }
+        );
+
+        set_test_clock(0, 0);
+    }
+
+    #[test]
+    fn test_read_only_swap_account() {
+        let user_key = Pubkey::new_unique();
+        let trader_key = Pubkey::new_unique();
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve {}),
+        };
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            Fees::default(),
+            SwapTransferFees::default(),
+            swap_curve,
+            1_000_000,
+            1_000_000,
+            &spl_token::id(),
+            &spl_token::id(),
+            &spl_token::id(),
+        );
+        accounts.initialize_swap().unwrap();
+
+        let initial_a = 100_000;
+        let (token_a_key, mut token_a_account, token_b_key, mut token_b_account, _, _) =
+            accounts.setup_token_accounts(&user_key, &trader_key, initial_a, 0, 0);
+        let swap_token_a_key = accounts.token_a_key;
+        let swap_token_b_key = accounts.token_b_key;
+        accounts.swap_account_is_writable = false;
+
+        // the price oracle of the latest version needs a writable swap account
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            accounts.swap(
+                &trader_key,
+                &token_a_key,
+                &mut token_a_account,
+                &swap_token_a_key,
+                &swap_token_b_key,
+                &token_b_key,
+                &mut token_b_account,
+                initial_a,
+                0,
+            )
+        );
+
+        // swaps created before the oracle existed are not written to
+        let token_swap = SwapVersion::unpack_latest(&accounts.swap_account.data[..]).unwrap();
+        accounts.swap_account.data.truncate(1 + SwapV1::LEN);
+        SwapVersion::pack(
+            SwapVersion::SwapV1(SwapV1 {
+                is_initialized: token_swap.is_initialized,
+                bump_seed: token_swap.bump_seed,
+                token_program_id: token_swap.token_program_id,
+                token_a: token_swap.token_a,
+                token_b: token_swap.token_b,
+                pool_mint: token_swap.pool_mint,
+                token_a_mint: token_swap.token_a_mint,
+                token_b_mint: token_swap.token_b_mint,
+                pool_fee_account: token_swap.pool_fee_account,
+                fees: token_swap.fees,
+                swap_curve: token_swap.swap_curve,
+            }),
+            &mut accounts.swap_account.data,
+        )
+        .unwrap();
+        let swap_data = accounts.swap_account.data.clone();
+        accounts
+            .swap(
+                &trader_key,
+                &token_a_key,
+                &mut token_a_account,
+                &swap_token_a_key,
+                &swap_token_b_key,
+                &token_b_key,
+                &mut token_b_account,
+                initial_a,
+                0,
+            )
+            .unwrap();
+        assert_eq!(accounts.swap_account.data, swap_data);
+    }
+
+    #[test_case(spl_token::id(); "token")]
+    #[test_case(spl_token_2022::id(); "token-2022")]
+    fn test_dynamic_fee(token_program_id: Pubkey) {
+        let user_key = Pubkey::new_unique();
+        let trader_key = Pubkey::new_unique();
+        let token_a_amount = 1_000_000;
+        let token_b_amount = 1_000_000;
+        let dynamic_fee = DynamicFee {
+            floor_bps: 30,
+            ceiling_bps: 500,
+            volatility_factor_bps: 10_000,
+            decay_period: 100,
+        };
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve {}),
+        };
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            Fees::default(),
+            SwapTransferFees::default(),
+            swap_curve.clone(),
+            token_a_amount,
+            token_b_amount,
+            &token_program_id,
+            &token_program_id,
+            &token_program_id,
+        );
+        accounts.dynamic_fee = Some(dynamic_fee);
+        set_test_clock(1, 1_000);
+        accounts.initialize_swap().unwrap();
+
+        let initial_a = 100_000;
+        let initial_b = 100_000;
+        let (token_a_key, mut token_a_account, token_b_key, mut token_b_account, _, _) =
+            accounts.setup_token_accounts(&user_key, &trader_key, initial_a, initial_b, 0);
+        let swap_token_a_key = accounts.token_a_key;
+        let swap_token_b_key = accounts.token_b_key;
+        let swap_amounts = |accounts: &SwapAccountInfo| {
+            (
+                StateWithExtensions::<Account>::unpack(&accounts.token_a_account.data)
+                    .unwrap()
+                    .base
+                    .amount,
+                StateWithExtensions::<Account>::unpack(&accounts.token_b_account.data)
+                    .unwrap()
+                    .base
+                    .amount,
+            )
+        };
+        let volatility_bps = |accounts: &SwapAccountInfo| {
+            SwapVersion::unpack(&accounts.swap_account.data)
+                .unwrap()
+                .volatility_bps()
+        };
+        assert_eq!(volatility_bps(&accounts), 0);
+
+        // each swap pays the fee for the volatility before it, then moves it
+        let amount_in = 10_000;
+        let mut expected_volatility_bps = 0;
+        let mut last_timestamp = 1_000;
+        let mut trade_fees_bps = vec![];
+        for (slot, timestamp) in [(2, 1_000), (3, 1_010), (4, 1_200)] {
+            let decayed_bps =
+                dynamic_fee.decay(expected_volatility_bps, timestamp - last_timestamp);
+            last_timestamp = timestamp;
+            let fees = dynamic_fee.fees(&Fees::default(), decayed_bps);
+            trade_fees_bps.push(fees.trade_fee_numerator);
+            let (old_a, old_b) = swap_amounts(&accounts);
+            let expected = swap_curve
+                .swap(
+                    amount_in.into(),
+                    old_a.into(),
+                    old_b.into(),
+                    TradeDirection::AtoB,
+                    &fees,
+                )
+                .unwrap();
+
+            set_test_clock(slot, timestamp);
+            let before = StateWithExtensions::<Account>::unpack(&token_b_account.data)
+                .unwrap()
+                .base
+                .amount;
+            accounts
+                .swap(
+                    &trader_key,
+                    &token_a_key,
+                    &mut token_a_account,
+                    &swap_token_a_key,
+                    &swap_token_b_key,
+                    &token_b_key,
+                    &mut token_b_account,
+                    amount_in,
+                    0,
+                )
+                .unwrap();
+            let after = StateWithExtensions::<Account>::unpack(&token_b_account.data)
+                .unwrap()
+                .base
+                .amount;
+            assert_eq!(
+                u128::from(after - before),
+                expected.destination_amount_swapped
+            );
+
+            let (new_a, new_b) = swap_amounts(&accounts);
+            expected_volatility_bps = decayed_bps.saturating_add(price_change_bps(
+                old_a.into(),
+                old_b.into(),
+                new_a.into(),
+                new_b.into(),
+            ));
+            assert_eq!(volatility_bps(&accounts), expected_volatility_bps);
+            assert!(expected_volatility_bps > decayed_bps);
+        }
+        // the fee rises with the volatility of the first swap, and falls back
+        // to the floor once the decay period elapsed
+        assert_eq!(trade_fees_bps[0], u64::from(dynamic_fee.floor_bps));
+        assert!(trade_fees_bps[1] > trade_fees_bps[0]);
+        assert_eq!(trade_fees_bps[2], u64::from(dynamic_fee.floor_bps));
+
+        set_test_clock(0, 0);
+    }
+
+    #[test_case(spl_token::id(); "token")]
+    #[test_case(spl_token_2022::id(); "token-2022")]
+    fn test_set_fees_and_curve_parameters(token_program_id: Pubkey) {
+        let user_key = Pubkey::new_unique();
+        let other_key = Pubkey::new_unique();
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            ..Fees::default()
+        };
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::Offset,
+            calculator: Arc::new(OffsetCurve {
+                token_b_offset: 1_000_000,
+            }),
+        };
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            fees.clone(),
+            SwapTransferFees::default(),
+            swap_curve.clone(),
+            1_000_000,
+            0,
+            &token_program_id,
+            &token_program_id,
+            &token_program_id,
+        );
+        set_test_clock(1, 1_000);
+        accounts.initialize_swap().unwrap();
+
+        let new_fees = Fees {
+            trade_fee_numerator: 2,
+            ..fees.clone()
+        };
+        let new_swap_curve = SwapCurve {
+            curve_type: CurveType::Offset,
+            calculator: Arc::new(OffsetCurve {
+                token_b_offset: 2_000_000,
+            }),
+        };
+
+        // only the owner of the pool fee account can stage an update
+        {
+            let instruction = set_fees(
+                &SWAP_PROGRAM_ID,
+                &accounts.swap_key,
+                &other_key,
+                &accounts.pool_fee_key,
+                new_fees.clone(),
+            )
+            .unwrap();
+            assert_eq!(
+                Err(SwapError::InvalidOwner.into()),
+                do_process_instruction(
+                    instruction,
+                    vec![
+                        &mut accounts.swap_account,
+                        &mut SolanaAccount::default(),
+                        &mut accounts.pool_fee_account,
+                    ],
+                )
+            );
+        }
+
+        // the pool fee account must be the swap's
+        {
+            let wrong_fee_key = Pubkey::new_unique();
+            let instruction = set_fees(
+                &SWAP_PROGRAM_ID,
+                &accounts.swap_key,
+                &user_key,
+                &wrong_fee_key,
+                new_fees.clone(),
+            )
+            .unwrap();
+            assert_eq!(
+                Err(SwapError::IncorrectFeeAccount.into()),
+                do_process_instruction(
+                    instruction,
+                    vec![
+                        &mut accounts.swap_account,
+                        &mut SolanaAccount::default(),
+                        &mut accounts.pool_fee_account.clone(),
+                    ],
+                )
+            );
+        }
+
+        // fees must be valid
+        {
+            let instruction = set_fees(
+                &SWAP_PROGRAM_ID,
+                &accounts.swap_key,
+                &user_key,
+                &accounts.pool_fee_key,
+                Fees {
+                    trade_fee_numerator: 2,
+                    trade_fee_denominator: 1,
+                    ..fees.clone()
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                Err(SwapError::InvalidFee.into()),
+                do_process_instruction(
+                    instruction,
+                    vec![
+                        &mut accounts.swap_account,
+                        &mut SolanaAccount::default(),
+                        &mut accounts.pool_fee_account,
+                    ],
+                )
+            );
+        }
+
+        // the curve type cannot change
+        {
+            let instruction = set_curve_parameters(
+                &SWAP_PROGRAM_ID,
+                &accounts.swap_key,
+                &user_key,
+                &accounts.pool_fee_key,
+                SwapCurve {
+                    curve_type: CurveType::ConstantProduct,
+                    calculator: Arc::new(ConstantProductCurve {}),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                Err(SwapError::InvalidCurve.into()),
+                do_process_instruction(
+                    instruction,
+                    vec![
+                        &mut accounts.swap_account,
+                        &mut SolanaAccount::default(),
+                        &mut accounts.pool_fee_account,
+                    ],
+                )
+            );
+        }
+
+        // nothing to apply yet
+        assert_eq!(
+            Err(SwapError::NoPendingUpdate.into()),
+            do_process_instruction(
+                apply_pending_update(&SWAP_PROGRAM_ID, &accounts.swap_key).unwrap(),
+                vec![&mut accounts.swap_account],
+            )
+        );
+
+        // stage the fees, then the curve, which pushes back the timelock
+        do_process_instruction(
+            set_fees(
+                &SWAP_PROGRAM_ID,
+                &accounts.swap_key,
+                &user_key,
+                &accounts.pool_fee_key,
+                new_fees.clone(),
+            )
+            .unwrap(),
+            vec![
+                &mut accounts.swap_account,
+                &mut SolanaAccount::default(),
+                &mut accounts.pool_fee_account,
+            ],
+        )
+        .unwrap();
+        set_test_clock(2, 1_010);
+        do_process_instruction(
+            set_curve_parameters(
+                &SWAP_PROGRAM_ID,
+                &accounts.swap_key,
+                &user_key,
+                &accounts.pool_fee_key,
+                new_swap_curve.clone(),
+            )
+            .unwrap(),
+            vec![
+                &mut accounts.swap_account,
+                &mut SolanaAccount::default(),
+                &mut accounts.pool_fee_account,
+            ],
+        )
+        .unwrap();
+        let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+        assert_eq!(*swap_state.fees(), fees);
+        assert_eq!(*swap_state.swap_curve(), swap_curve);
+        assert_eq!(swap_state.pending_fees(), Some(&new_fees));
+        assert_eq!(swap_state.pending_swap_curve(), Some(&new_swap_curve));
+        assert_eq!(
+            swap_state.pending_activation_timestamp(),
+            1_010 + UPDATE_TIMELOCK
+        );
+
+        // the update cannot be applied before the timelock elapsed
+        set_test_clock(3, 1_009 + UPDATE_TIMELOCK);
+        assert_eq!(
+            Err(SwapError::TimelockNotElapsed.into()),
+            do_process_instruction(
+                apply_pending_update(&SWAP_PROGRAM_ID, &accounts.swap_key).unwrap(),
+                vec![&mut accounts.swap_account],
+            )
+        );
+
+        // anyone can apply it afterwards
+        set_test_clock(4, 1_010 + UPDATE_TIMELOCK);
+        do_process_instruction(
+            apply_pending_update(&SWAP_PROGRAM_ID, &accounts.swap_key).unwrap(),
+            vec![&mut accounts.swap_account],
+        )
+        .unwrap();
+        let swap_state = SwapVersion::unpack(&accounts.swap_account.data).unwrap();
+        assert_eq!(*swap_state.fees(), new_fees);
+        assert_eq!(*swap_state.swap_curve(), new_swap_curve);
+        assert_eq!(swap_state.pending_fees(), None);
+        assert_eq!(swap_state.pending_swap_curve(), None);
+        assert_eq!(
+            Err(SwapError::NoPendingUpdate.into()),
+            do_process_instruction(
+                apply_pending_update(&SWAP_PROGRAM_ID, &accounts.swap_key).unwrap(),
+                vec![&mut accounts.swap_account],
+            )
+        );
+
+        // with constraints, only their owner can stage fees, within them
+        let owner_key_str = &user_key.to_string();
+        let constraints = Some(SwapConstraints {
+            owner_key: owner_key_str,
+            valid_curve_types: &[CurveType::Offset],
+            fees: &new_fees,
+        });
+        assert_eq!(
+            Err(SwapError::InvalidOwner.into()),
+            do_process_instruction_with_fee_constraints(
+                set_fees(
+                    &SWAP_PROGRAM_ID,
+                    &accounts.swap_key,
+                    &other_key,
+                    &accounts.pool_fee_key,
+                    new_fees.clone(),
+                )
+                .unwrap(),
+                vec![
+                    &mut accounts.swap_account,
+                    &mut SolanaAccount::default(),
+                    &mut accounts.pool_fee_account,
+                ],
+                &constraints,
+            )
+        );
+        assert_eq!(
+            Err(SwapError::InvalidFee.into()),
+            do_process_instruction_with_fee_constraints(
+                set_fees(
+                    &SWAP_PROGRAM_ID,
+                    &accounts.swap_key,
+                    &user_key,
+                    &accounts.pool_fee_key,
+                    fees,
+                )
+                .unwrap(),
+                vec![
+                    &mut accounts.swap_account,
+                    &mut SolanaAccount::default(),
+                    &mut accounts.pool_fee_account,
+                ],
+                &constraints,
+            )
+        );
+
+        set_test_clock(0, 0);
+    }
+
+    #[test_case(spl_token::id(); "token")]
+    #[test_case(spl_token_2022::id(); "token-2022")]
+    fn test_route_swap(token_program_id: Pubkey) {
+        fn keyed_accounts(swap: &mut SwapAccountInfo) -> Vec<(&Pubkey, &mut SolanaAccount)> {
+            vec![
+                (&swap.swap_key, &mut swap.swap_account),
+                (&swap.pool_mint_key, &mut swap.pool_mint_account),
+                (&swap.pool_fee_key, &mut swap.pool_fee_account),
+                (&swap.token_a_key, &mut swap.token_a_account),
+                (&swap.token_b_key, &mut swap.token_b_account),
+                (&swap.token_a_mint_key, &mut swap.token_a_mint_account),
+                (&swap.token_b_mint_key, &mut swap.token_b_mint_account),
+            ]
+        }
+
+        let user_key = Pubkey::new_unique();
+        let trader_key = Pubkey::new_unique();
+        let user_transfer_key = Pubkey::new_unique();
+        let pool_amount = 1_000_000;
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            ..Fees::default()
+        };
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve {}),
+        };
+
+        // the first pool trades A for B, and the second one B for C
+        let mut first = SwapAccountInfo::new(
+            &user_key,
+            fees.clone(),
+            SwapTransferFees::default(),
+            swap_curve.clone(),
+            pool_amount,
+            pool_amount,
+            &token_program_id,
+            &token_program_id,
+            &token_program_id,
+        );
+        first.initialize_swap().unwrap();
+        let mut second = SwapAccountInfo::new(
+            &user_key,
+            fees.clone(),
+            SwapTransferFees::default(),
+            swap_curve.clone(),
+            pool_amount,
+            pool_amount * 2,
+            &token_program_id,
+            &token_program_id,
+            &token_program_id,
+        );
+        let (second_token_a_key, second_token_a_account) = mint_token(
+            &token_program_id,
+            &first.token_b_mint_key,
+            &mut first.token_b_mint_account,
+            &user_key,
+            &second.authority_key,
+            pool_amount,
+        );
+        second.token_a_key = second_token_a_key;
+        second.token_a_account = second_token_a_account;
+        second.token_a_mint_key = first.token_b_mint_key;
+        second.token_a_mint_account = first.token_b_mint_account.clone();
+        second.initialize_swap().unwrap();
+
+        let amount_in = 100_000;
+        let (token_a_key, token_a_account, token_b_key, token_b_account, _, _) =
+            first.setup_token_accounts(&user_key, &trader_key, amount_in, 0, 0);
+        let (other_token_b_key, other_token_b_account, token_c_key, token_c_account, _, _) =
+            second.setup_token_accounts(&user_key, &trader_key, amount_in, 0, 0);
+        let mut user_accounts = vec![
+            (token_a_key, token_a_account),
+            (token_b_key, token_b_account),
+            (other_token_b_key, other_token_b_account),
+            (token_c_key, token_c_account),
+            (user_transfer_key, SolanaAccount::default()),
+            (first.authority_key, SolanaAccount::default()),
+            (second.authority_key, SolanaAccount::default()),
+            (token_program_id, SolanaAccount::default()),
+        ];
+        for (key, account) in user_accounts.iter_mut().take(3) {
+            do_process_instruction(
+                approve(
+                    &token_program_id,
+                    key,
+                    &user_transfer_key,
+                    &trader_key,
+                    &[],
+                    u64::MAX,
+                )
+                .unwrap(),
+                vec![
+                    account,
+                    &mut SolanaAccount::default(),
+                    &mut SolanaAccount::default(),
+                ],
+            )
+            .unwrap();
+        }
+
+        let first_result = swap_curve
+            .swap(
+                amount_in.into(),
+                pool_amount.into(),
+                pool_amount.into(),
+                TradeDirection::AtoB,
+                &fees,
+            )
+            .unwrap();
+        let second_result = swap_curve
+            .swap(
+                first_result.destination_amount_swapped,
+                pool_amount.into(),
+                (pool_amount * 2).into(),
+                TradeDirection::AtoB,
+                &fees,
+            )
+            .unwrap();
+        let amount_out = to_u64(second_result.destination_amount_swapped).unwrap();
+
+        let route_swap_instruction = |intermediate_key: &Pubkey, minimum_amount_out: u64| {
+            let first_leg = swap(
+                &SWAP_PROGRAM_ID,
+                &token_program_id,
+                &token_program_id,
+                &token_program_id,
+                &first.swap_key,
+                &first.authority_key,
+                &user_transfer_key,
+                &token_a_key,
+                &first.token_a_key,
+                &first.token_b_key,
+                &token_b_key,
+                &first.pool_mint_key,
+                &first.pool_fee_key,
+                &first.token_a_mint_key,
+                &first.token_b_mint_key,
+                None,
+                Swap {
+                    amount_in,
+                    minimum_amount_out: 0,
+                },
+            )
+            .unwrap();
+            let second_leg = swap(
+                &SWAP_PROGRAM_ID,
+                &token_program_id,
+                &token_program_id,
+                &token_program_id,
+                &second.swap_key,
+                &second.authority_key,
+                &user_transfer_key,
+                intermediate_key,
+                &second.token_a_key,
+                &second.token_b_key,
+                &token_c_key,
+                &second.pool_mint_key,
+                &second.pool_fee_key,
+                &second.token_a_mint_key,
+                &second.token_b_mint_key,
+                None,
+                Swap {
+                    amount_in: 0,
+                    minimum_amount_out,
+                },
+            )
+            .unwrap();
+            route_swap(
+                &SWAP_PROGRAM_ID,
+                &[first_leg, second_leg],
+                RouteSwap {
+                    amount_in,
+                    minimum_amount_out,
+                },
+            )
+            .unwrap()
+        };
+        let exceeded_slippage = route_swap_instruction(&token_b_key, amount_out + 1);
+        let broken_route = route_swap_instruction(&other_token_b_key, 0);
+        let route = route_swap_instruction(&token_b_key, amount_out);
+
+        let mut process_route_swap = |instruction| {
+            let mut accounts = keyed_accounts(&mut first);
+            accounts.extend(keyed_accounts(&mut second));
+            accounts.extend(
+                user_accounts
+                    .iter_mut()
+                    .map(|(key, account)| (&*key, account)),
+            );
+            do_process_instruction_with_shared_accounts(instruction, accounts)
+        };
+
+        // only the output of the last leg is checked for slippage
+        assert_eq!(
+            Err(SwapError::ExceededSlippage.into()),
+            process_route_swap(exceeded_slippage)
+        );
+
+        // each leg must swap the output of the previous one
+        assert_eq!(
+            Err(SwapError::InvalidInput.into()),
+            process_route_swap(broken_route)
+        );
+
+        process_route_swap(route).unwrap();
+
+        let amount = |key: &Pubkey| {
+            let (_, account) = user_accounts.iter().find(|(k, _)| k == key).unwrap();
+            StateWithExtensions::<Account>::unpack(&account.data)
+                .unwrap()
+                .base
+                .amount
+        };
+        // the curve may not need all of the input to produce its output
+        assert_eq!(
+            amount(&token_a_key),
+            amount_in - to_u64(first_result.source_amount_swapped).unwrap()
+        );
+        assert_eq!(
+            amount(&token_b_key),
+            to_u64(first_result.destination_amount_swapped - second_result.source_amount_swapped)
+                .unwrap()
+        );
+        assert_eq!(amount(&other_token_b_key), amount_in);
+        assert_eq!(amount(&token_c_key), amount_out);
+        let swap_token_b =
+            StateWithExtensions::<Account>::unpack(&second.token_b_account.data).unwrap();
+        assert_eq!(swap_token_b.base.amount, pool_amount * 2 - amount_out);
+    }
 }
@@ -2,15 +2,20 @@
 
 use {
     crate::{
-        curve::{base::SwapCurve, fees::Fees},
+        curve::{
+            base::SwapCurve,
+            fees::{DynamicFee, Fees},
+        },
         error::SwapError,
     },
     arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
     enum_dispatch::enum_dispatch,
     solana_program::{
         account_info::AccountInfo,
+        clock::{Slot, UnixTimestamp},
         msg,
         program_error::ProgramError,
+        program_option::COption,
         program_pack::{IsInitialized, Pack, Sealed},
         pubkey::Pubkey,
     },
@@ -52,13 +57,47 @@ pub trait SwapState {
     fn fees(&self) -> &Fees;
     /// Curve associated with swap
     fn swap_curve(&self) -> &SwapCurve;
+
+    /// Authority allowed to pause and unpause the swap
+    fn pause_authority(&self) -> COption<Pubkey>;
+    /// Is the swap paused, rejecting swaps and deposits
+    fn is_paused(&self) -> bool;
+
+    /// Cumulative price of token A in token B, see the `oracle` module
+    fn price_a_cumulative(&self) -> u128;
+    /// Cumulative price of token B in token A, see the `oracle` module
+    fn price_b_cumulative(&self) -> u128;
+    /// Slot of the last update to the cumulative prices
+    fn last_update_slot(&self) -> Slot;
+    /// Timestamp of the last update to the cumulative prices
+    fn last_update_timestamp(&self) -> UnixTimestamp;
+
+    /// Dynamic trade fee, replacing the trade fee of `fees` if present
+    fn dynamic_fee(&self) -> Option<&DynamicFee>;
+    /// Recent price movement in basis points, as of the last update
+    fn volatility_bps(&self) -> u64;
+
+    /// Fees staged by the owner, applied after `pending_activation_timestamp`
+    fn pending_fees(&self) -> Option<&Fees>;
+    /// Curve staged by the owner, applied after `pending_activation_timestamp`
+    fn pending_swap_curve(&self) -> Option<&SwapCurve>;
+    /// Timestamp after which the pending fees and curve can be applied
+    fn pending_activation_timestamp(&self) -> UnixTimestamp;
 }
 
+/// Number of seconds between the owner staging new fees or curve parameters
+/// and the moment they can be applied, giving liquidity providers time to
+/// withdraw if they disagree with the change
+pub const UPDATE_TIMELOCK: UnixTimestamp = 3 * 24 * 60 * 60;
+
 /// All versions of SwapState
+#[allow(clippy::large_enum_variant)]
 #[enum_dispatch(SwapState)]
 pub enum SwapVersion {
-    /// Latest version, used for all new swaps
+    /// Original version, without a pause authority
     SwapV1,
+    /// Latest version, used for all new swaps
+    SwapV2,
 }
 
 /// SwapVersion does not implement program_pack::Pack because there are size
@@ -66,7 +105,7 @@ pub enum SwapVersion {
 /// special implementations are provided here
 impl SwapVersion {
     /// Size of the latest version of the SwapState
-    pub const LATEST_LEN: usize = 1 + SwapV1::LEN; // add one for the version enum
+    pub const LATEST_LEN: usize = 1 + SwapV2::LEN; // add one for the version enum
 
     /// Pack a swap into a byte array, based on its version
     pub fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
@@ -75,6 +114,10 @@ impl SwapVersion {
                 dst[0] = 1;
                 SwapV1::pack(swap_info, &mut dst[1..])
             }
+            Self::SwapV2(swap_info) => {
+                dst[0] = 2;
+                SwapV2::pack(swap_info, &mut dst[1..])
+            }
         }
     }
 
@@ -86,6 +129,20 @@ impl SwapVersion {
             .ok_or(ProgramError::InvalidAccountData)?;
         match version {
             1 => Ok(Arc::new(SwapV1::unpack(rest)?)),
+            2 => Ok(Arc::new(SwapV2::unpack(rest)?)),
+            _ => Err(ProgramError::UninitializedAccount),
+        }
+    }
+
+    /// Unpack the swap account as the latest version, for instructions that
+    /// update swap state that older versions have no room for
+    pub fn unpack_latest(input: &[u8]) -> Result<SwapV2, ProgramError> {
+        let (&version, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidAccountData)?;
+        match version {
+            1 => Err(SwapError::UnsupportedSwapVersion.into()),
+            2 => SwapV2::unpack(rest),
             _ => Err(ProgramError::UninitializedAccount),
         }
     }
@@ -179,22 +236,7 @@ impl SwapState for SwapV1 {
     }
 
     fn check_pool_fee_info(&self, pool_fee_info: &AccountInfo) -> Result<(), ProgramError> {
-        let data = &pool_fee_info.data.borrow();
-        let token_account =
-            StateWithExtensions::<Account>::unpack(data).map_err(|err| match err {
-                ProgramError::InvalidAccountData | ProgramError::UninitializedAccount => {
-                    SwapError::InvalidFeeAccount.into()
-                }
-                _ => err,
-            })?;
-        if pool_fee_info.owner != &self.token_program_id
-            || token_account.base.state != AccountState::Initialized
-            || token_account.base.mint != self.pool_mint
-        {
-            msg!("Pool fee account is not owned by token program, is not initialized, or does not match stake pool's mint");
-            return Err(SwapError::InvalidFeeAccount.into());
-        }
-        Ok(())
+        check_pool_fee_info(pool_fee_info, &self.token_program_id, &self.pool_mint)
     }
 
     fn fees(&self) -> &Fees {
@@ -204,6 +246,50 @@ impl SwapState for SwapV1 {
     fn swap_curve(&self) -> &SwapCurve {
         &self.swap_curve
     }
+
+    fn pause_authority(&self) -> COption<Pubkey> {
+        COption::None
+    }
+
+    fn is_paused(&self) -> bool {
+        false
+    }
+
+    fn price_a_cumulative(&self) -> u128 {
+        0
+    }
+
+    fn price_b_cumulative(&self) -> u128 {
+        0
+    }
+
+    fn last_update_slot(&self) -> Slot {
+        0
+    }
+
+    fn last_update_timestamp(&self) -> UnixTimestamp {
+        0
+    }
+
+    fn dynamic_fee(&self) -> Option<&DynamicFee> {
+        None
+    }
+
+    fn volatility_bps(&self) -> u64 {
+        0
+    }
+
+    fn pending_fees(&self) -> Option<&Fees> {
+        None
+    }
+
+    fn pending_swap_curve(&self) -> Option<&SwapCurve> {
+        None
+    }
+
+    fn pending_activation_timestamp(&self) -> UnixTimestamp {
+        0
+    }
 }
 
 impl Sealed for SwapV1 {}
@@ -281,6 +367,396 @@ impl Pack for SwapV1 {
     }
 }
 
+/// Program states, with a pause authority, price accumulators, and an
+/// optional dynamic fee.
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct SwapV2 {
+    /// Initialized state.
+    pub is_initialized: bool,
+    /// Bump seed used in program address.
+    /// The program address is created deterministically with the bump seed,
+    /// swap program id, and swap account pubkey.  This program address has
+    /// authority over the swap's token A account, token B account, and pool
+    /// token mint.
+    pub bump_seed: u8,
+
+    /// Program ID of the tokens being exchanged.
+    pub token_program_id: Pubkey,
+
+    /// Token A
+    pub token_a: Pubkey,
+    /// Token B
+    pub token_b: Pubkey,
+
+    /// Pool tokens are issued when A or B tokens are deposited.
+    /// Pool tokens can be withdrawn back to the original A or B token.
+    pub pool_mint: Pubkey,
+
+    /// Mint information for token A
+    pub token_a_mint: Pubkey,
+    /// Mint information for token B
+    pub token_b_mint: Pubkey,
+
+    /// Pool token account to receive trading and / or withdrawal fees
+    pub pool_fee_account: Pubkey,
+
+    /// All fee information
+    pub fees: Fees,
+
+    /// Swap curve parameters, to be unpacked and used by the SwapCurve, which
+    /// calculates swaps, deposits, and withdrawals
+    pub swap_curve: SwapCurve,
+
+    /// Optional authority that can pause and unpause the swap. If not
+    /// present, the swap can never be paused.
+    pub pause_authority: COption<Pubkey>,
+    /// While paused, swaps, deposits, and single token withdrawals are
+    /// rejected, but liquidity providers can still withdraw both tokens.
+    pub is_paused: bool,
+
+    /// Sum over time of the price of token A in token B, updated before
+    /// every change to the reserves, as a wrapping fixed-point number.
+    pub price_a_cumulative: u128,
+    /// Sum over time of the price of token B in token A, updated before
+    /// every change to the reserves, as a wrapping fixed-point number.
+    pub price_b_cumulative: u128,
+    /// Slot of the last update to the cumulative prices
+    pub last_update_slot: Slot,
+    /// Timestamp of the last update to the cumulative prices
+    pub last_update_timestamp: UnixTimestamp,
+
+    /// Optional trade fee rising with the volatility of the pool, replacing
+    /// the trade fee of `fees`
+    pub dynamic_fee: Option<DynamicFee>,
+    /// Recent price movement of the pool in basis points, decayed up to
+    /// `last_update_timestamp`
+    pub volatility_bps: u64,
+
+    /// Fees staged by the owner, replacing `fees` once the timelock elapsed
+    pub pending_fees: Option<Fees>,
+    /// Curve staged by the owner, replacing `swap_curve` once the timelock
+    /// elapsed
+    pub pending_swap_curve: Option<SwapCurve>,
+    /// Timestamp after which the pending fees and curve can be applied,
+    /// pushed back by every new update staged by the owner
+    pub pending_activation_timestamp: UnixTimestamp,
+}
+
+impl SwapState for SwapV2 {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    fn bump_seed(&self) -> u8 {
+        self.bump_seed
+    }
+
+    fn token_program_id(&self) -> &Pubkey {
+        &self.token_program_id
+    }
+
+    fn token_a_account(&self) -> &Pubkey {
+        &self.token_a
+    }
+
+    fn token_b_account(&self) -> &Pubkey {
+        &self.token_b
+    }
+
+    fn pool_mint(&self) -> &Pubkey {
+        &self.pool_mint
+    }
+
+    fn token_a_mint(&self) -> &Pubkey {
+        &self.token_a_mint
+    }
+
+    fn token_b_mint(&self) -> &Pubkey {
+        &self.token_b_mint
+    }
+
+    fn pool_fee_account(&self) -> &Pubkey {
+        &self.pool_fee_account
+    }
+
+    fn check_pool_fee_info(&self, pool_fee_info: &AccountInfo) -> Result<(), ProgramError> {
+        check_pool_fee_info(pool_fee_info, &self.token_program_id, &self.pool_mint)
+    }
+
+    fn fees(&self) -> &Fees {
+        &self.fees
+    }
+
+    fn swap_curve(&self) -> &SwapCurve {
+        &self.swap_curve
+    }
+
+    fn pause_authority(&self) -> COption<Pubkey> {
+        self.pause_authority
+    }
+
+    fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    fn price_a_cumulative(&self) -> u128 {
+        self.price_a_cumulative
+    }
+
+    fn price_b_cumulative(&self) -> u128 {
+        self.price_b_cumulative
+    }
+
+    fn last_update_slot(&self) -> Slot {
+        self.last_update_slot
+    }
+
+    fn last_update_timestamp(&self) -> UnixTimestamp {
+        self.last_update_timestamp
+    }
+
+    fn dynamic_fee(&self) -> Option<&DynamicFee> {
+        self.dynamic_fee.as_ref()
+    }
+
+    fn volatility_bps(&self) -> u64 {
+        self.volatility_bps
+    }
+
+    fn pending_fees(&self) -> Option<&Fees> {
+        self.pending_fees.as_ref()
+    }
+
+    fn pending_swap_curve(&self) -> Option<&SwapCurve> {
+        self.pending_swap_curve.as_ref()
+    }
+
+    fn pending_activation_timestamp(&self) -> UnixTimestamp {
+        self.pending_activation_timestamp
+    }
+}
+
+impl Sealed for SwapV2 {}
+impl IsInitialized for SwapV2 {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for SwapV2 {
+    const LEN: usize = 536;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 536];
+        let (
+            is_initialized,
+            bump_seed,
+            token_program_id,
+            token_a,
+            token_b,
+            pool_mint,
+            token_a_mint,
+            token_b_mint,
+            pool_fee_account,
+            fees,
+            swap_curve,
+            pause_authority,
+            is_paused,
+            price_a_cumulative,
+            price_b_cumulative,
+            last_update_slot,
+            last_update_timestamp,
+            dynamic_fee,
+            volatility_bps,
+            pending_fees,
+            pending_swap_curve,
+            pending_activation_timestamp,
+        ) = mut_array_refs![
+            output, 1, 1, 32, 32, 32, 32, 32, 32, 32, 64, 33, 36, 1, 16, 16, 8, 8, 13, 8, 65, 34, 8
+        ];
+        is_initialized[0] = self.is_initialized as u8;
+        bump_seed[0] = self.bump_seed;
+        token_program_id.copy_from_slice(self.token_program_id.as_ref());
+        token_a.copy_from_slice(self.token_a.as_ref());
+        token_b.copy_from_slice(self.token_b.as_ref());
+        pool_mint.copy_from_slice(self.pool_mint.as_ref());
+        token_a_mint.copy_from_slice(self.token_a_mint.as_ref());
+        token_b_mint.copy_from_slice(self.token_b_mint.as_ref());
+        pool_fee_account.copy_from_slice(self.pool_fee_account.as_ref());
+        self.fees.pack_into_slice(&mut fees[..]);
+        self.swap_curve.pack_into_slice(&mut swap_curve[..]);
+        pack_coption_key(&self.pause_authority, pause_authority);
+        is_paused[0] = self.is_paused as u8;
+        *price_a_cumulative = self.price_a_cumulative.to_le_bytes();
+        *price_b_cumulative = self.price_b_cumulative.to_le_bytes();
+        *last_update_slot = self.last_update_slot.to_le_bytes();
+        *last_update_timestamp = self.last_update_timestamp.to_le_bytes();
+        pack_dynamic_fee_option(&self.dynamic_fee, dynamic_fee);
+        *volatility_bps = self.volatility_bps.to_le_bytes();
+        pack_option(&self.pending_fees, pending_fees);
+        pack_option(&self.pending_swap_curve, pending_swap_curve);
+        *pending_activation_timestamp = self.pending_activation_timestamp.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [SwapV2](struct.SwapV2.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, 536];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            bump_seed,
+            token_program_id,
+            token_a,
+            token_b,
+            pool_mint,
+            token_a_mint,
+            token_b_mint,
+            pool_fee_account,
+            fees,
+            swap_curve,
+            pause_authority,
+            is_paused,
+            price_a_cumulative,
+            price_b_cumulative,
+            last_update_slot,
+            last_update_timestamp,
+            dynamic_fee,
+            volatility_bps,
+            pending_fees,
+            pending_swap_curve,
+            pending_activation_timestamp,
+        ) = array_refs![
+            input, 1, 1, 32, 32, 32, 32, 32, 32, 32, 64, 33, 36, 1, 16, 16, 8, 8, 13, 8, 65, 34, 8
+        ];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump_seed: bump_seed[0],
+            token_program_id: Pubkey::new_from_array(*token_program_id),
+            token_a: Pubkey::new_from_array(*token_a),
+            token_b: Pubkey::new_from_array(*token_b),
+            pool_mint: Pubkey::new_from_array(*pool_mint),
+            token_a_mint: Pubkey::new_from_array(*token_a_mint),
+            token_b_mint: Pubkey::new_from_array(*token_b_mint),
+            pool_fee_account: Pubkey::new_from_array(*pool_fee_account),
+            fees: Fees::unpack_from_slice(fees)?,
+            swap_curve: SwapCurve::unpack_from_slice(swap_curve)?,
+            pause_authority: unpack_coption_key(pause_authority)?,
+            is_paused: match is_paused {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            price_a_cumulative: u128::from_le_bytes(*price_a_cumulative),
+            price_b_cumulative: u128::from_le_bytes(*price_b_cumulative),
+            last_update_slot: Slot::from_le_bytes(*last_update_slot),
+            last_update_timestamp: UnixTimestamp::from_le_bytes(*last_update_timestamp),
+            dynamic_fee: unpack_dynamic_fee_option(dynamic_fee)?,
+            volatility_bps: u64::from_le_bytes(*volatility_bps),
+            pending_fees: unpack_option(pending_fees)?,
+            pending_swap_curve: unpack_option(pending_swap_curve)?,
+            pending_activation_timestamp: UnixTimestamp::from_le_bytes(
+                *pending_activation_timestamp,
+            ),
+        })
+    }
+}
+
+/// Check if the pool fee info is a valid token program account capable of
+/// receiving tokens from the pool mint.
+fn check_pool_fee_info(
+    pool_fee_info: &AccountInfo,
+    token_program_id: &Pubkey,
+    pool_mint: &Pubkey,
+) -> Result<(), ProgramError> {
+    let data = &pool_fee_info.data.borrow();
+    let token_account = StateWithExtensions::<Account>::unpack(data).map_err(|err| match err {
+        ProgramError::InvalidAccountData | ProgramError::UninitializedAccount => {
+            SwapError::InvalidFeeAccount.into()
+        }
+        _ => err,
+    })?;
+    if pool_fee_info.owner != token_program_id
+        || token_account.base.state != AccountState::Initialized
+        || token_account.base.mint != *pool_mint
+    {
+        msg!("Pool fee account is not owned by token program, is not initialized, or does not match stake pool's mint");
+        return Err(SwapError::InvalidFeeAccount.into());
+    }
+    Ok(())
+}
+
+fn pack_coption_key(src: &COption<Pubkey>, dst: &mut [u8; 36]) {
+    let (tag, body) = mut_array_refs![dst, 4, 32];
+    match src {
+        COption::Some(key) => {
+            *tag = [1, 0, 0, 0];
+            body.copy_from_slice(key.as_ref());
+        }
+        COption::None => {
+            *tag = [0; 4];
+        }
+    }
+}
+
+fn unpack_coption_key(src: &[u8; 36]) -> Result<COption<Pubkey>, ProgramError> {
+    let (tag, body) = array_refs![src, 4, 32];
+    match *tag {
+        [0, 0, 0, 0] => Ok(COption::None),
+        [1, 0, 0, 0] => Ok(COption::Some(Pubkey::new_from_array(*body))),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+fn pack_dynamic_fee_option(src: &Option<DynamicFee>, dst: &mut [u8; 13]) {
+    let (tag, body) = mut_array_refs![dst, 1, 12];
+    match src {
+        Some(dynamic_fee) => {
+            *tag = [1];
+            dynamic_fee.pack_into_slice(body);
+        }
+        None => {
+            *tag = [0];
+        }
+    }
+}
+
+fn unpack_dynamic_fee_option(src: &[u8; 13]) -> Result<Option<DynamicFee>, ProgramError> {
+    let (tag, body) = array_refs![src, 1, 12];
+    match *tag {
+        [0] => Ok(None),
+        [1] => Ok(Some(DynamicFee::unpack_from_slice(body)?)),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+fn pack_option<T: Pack>(src: &Option<T>, dst: &mut [u8]) {
+    let (tag, body) = dst.split_at_mut(1);
+    match src {
+        Some(value) => {
+            tag[0] = 1;
+            value.pack_into_slice(body);
+        }
+        None => {
+            tag[0] = 0;
+            body.fill(0);
+        }
+    }
+}
+
+fn unpack_option<T: Pack>(src: &[u8]) -> Result<Option<T>, ProgramError> {
+    let (tag, body) = src.split_at(1);
+    match tag {
+        [0] => Ok(None),
+        [1] => Ok(Some(T::unpack_from_slice(body)?)),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, crate::curve::offset::OffsetCurve, std::convert::TryInto};
@@ -304,6 +780,30 @@ mod tests {
     const TEST_TOKEN_A_MINT: Pubkey = Pubkey::new_from_array([5u8; 32]);
     const TEST_TOKEN_B_MINT: Pubkey = Pubkey::new_from_array([6u8; 32]);
     const TEST_POOL_FEE_ACCOUNT: Pubkey = Pubkey::new_from_array([7u8; 32]);
+    const TEST_PAUSE_AUTHORITY: Pubkey = Pubkey::new_from_array([8u8; 32]);
+    const TEST_PRICE_A_CUMULATIVE: u128 = 1 << 100;
+    const TEST_PRICE_B_CUMULATIVE: u128 = u128::MAX - 1;
+    const TEST_LAST_UPDATE_SLOT: Slot = 4_242;
+    const TEST_LAST_UPDATE_TIMESTAMP: UnixTimestamp = 1_700_000_000;
+    const TEST_DYNAMIC_FEE: DynamicFee = DynamicFee {
+        floor_bps: 5,
+        ceiling_bps: 100,
+        volatility_factor_bps: 1_000,
+        decay_period: 600,
+    };
+    const TEST_VOLATILITY_BPS: u64 = 250;
+    const TEST_PENDING_FEES: Fees = Fees {
+        trade_fee_numerator: 3,
+        trade_fee_denominator: 1_000,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 1_000,
+        owner_withdraw_fee_numerator: 0,
+        owner_withdraw_fee_denominator: 0,
+        host_fee_numerator: 5,
+        host_fee_denominator: 20,
+    };
+    const TEST_PENDING_TOKEN_B_OFFSET: u64 = 2_000_000_000;
+    const TEST_PENDING_ACTIVATION_TIMESTAMP: UnixTimestamp = 1_700_259_200;
 
     const TEST_CURVE_TYPE: u8 = 2;
     const TEST_TOKEN_B_OFFSET: u64 = 1_000_000_000;
@@ -333,6 +833,77 @@ mod tests {
             swap_curve: swap_curve.clone(),
         });
 
+        let mut packed = [0u8; 1 + SwapV1::LEN];
+        SwapVersion::pack(swap_info, &mut packed).unwrap();
+        let unpacked = SwapVersion::unpack(&packed).unwrap();
+
+        assert!(unpacked.is_initialized());
+        assert_eq!(unpacked.bump_seed(), TEST_BUMP_SEED);
+        assert_eq!(*unpacked.token_program_id(), TEST_TOKEN_PROGRAM_ID);
+        assert_eq!(*unpacked.token_a_account(), TEST_TOKEN_A);
+        assert_eq!(*unpacked.token_b_account(), TEST_TOKEN_B);
+        assert_eq!(*unpacked.pool_mint(), TEST_POOL_MINT);
+        assert_eq!(*unpacked.token_a_mint(), TEST_TOKEN_A_MINT);
+        assert_eq!(*unpacked.token_b_mint(), TEST_TOKEN_B_MINT);
+        assert_eq!(*unpacked.pool_fee_account(), TEST_POOL_FEE_ACCOUNT);
+        assert_eq!(*unpacked.fees(), TEST_FEES);
+        assert_eq!(*unpacked.swap_curve(), swap_curve);
+        assert_eq!(unpacked.pause_authority(), COption::None);
+        assert!(!unpacked.is_paused());
+        assert_eq!(unpacked.price_a_cumulative(), 0);
+        assert_eq!(unpacked.price_b_cumulative(), 0);
+        assert_eq!(unpacked.last_update_slot(), 0);
+        assert_eq!(unpacked.last_update_timestamp(), 0);
+        assert_eq!(unpacked.dynamic_fee(), None);
+        assert_eq!(unpacked.volatility_bps(), 0);
+        assert_eq!(unpacked.pending_fees(), None);
+        assert_eq!(unpacked.pending_swap_curve(), None);
+        assert_eq!(unpacked.pending_activation_timestamp(), 0);
+        assert_eq!(
+            SwapVersion::unpack_latest(&packed).unwrap_err(),
+            SwapError::UnsupportedSwapVersion.into()
+        );
+    }
+
+    #[test]
+    fn swap_version_pack_latest() {
+        let curve_type = TEST_CURVE_TYPE.try_into().unwrap();
+        let calculator = Arc::new(TEST_CURVE);
+        let swap_curve = SwapCurve {
+            curve_type,
+            calculator,
+        };
+        let pending_swap_curve = SwapCurve {
+            curve_type,
+            calculator: Arc::new(OffsetCurve {
+                token_b_offset: TEST_PENDING_TOKEN_B_OFFSET,
+            }),
+        };
+        let swap_info = SwapVersion::SwapV2(SwapV2 {
+            is_initialized: true,
+            bump_seed: TEST_BUMP_SEED,
+            token_program_id: TEST_TOKEN_PROGRAM_ID,
+            token_a: TEST_TOKEN_A,
+            token_b: TEST_TOKEN_B,
+            pool_mint: TEST_POOL_MINT,
+            token_a_mint: TEST_TOKEN_A_MINT,
+            token_b_mint: TEST_TOKEN_B_MINT,
+            pool_fee_account: TEST_POOL_FEE_ACCOUNT,
+            fees: TEST_FEES,
+            swap_curve: swap_curve.clone(),
+            pause_authority: COption::Some(TEST_PAUSE_AUTHORITY),
+            is_paused: true,
+            price_a_cumulative: TEST_PRICE_A_CUMULATIVE,
+            price_b_cumulative: TEST_PRICE_B_CUMULATIVE,
+            last_update_slot: TEST_LAST_UPDATE_SLOT,
+            last_update_timestamp: TEST_LAST_UPDATE_TIMESTAMP,
+            dynamic_fee: Some(TEST_DYNAMIC_FEE),
+            volatility_bps: TEST_VOLATILITY_BPS,
+            pending_fees: Some(TEST_PENDING_FEES),
+            pending_swap_curve: Some(pending_swap_curve.clone()),
+            pending_activation_timestamp: TEST_PENDING_ACTIVATION_TIMESTAMP,
+        });
+
         let mut packed = [0u8; SwapVersion::LATEST_LEN];
         SwapVersion::pack(swap_info, &mut packed).unwrap();
         let unpacked = SwapVersion::unpack(&packed).unwrap();
@@ -348,6 +919,30 @@ mod tests {
         assert_eq!(*unpacked.pool_fee_account(), TEST_POOL_FEE_ACCOUNT);
         assert_eq!(*unpacked.fees(), TEST_FEES);
         assert_eq!(*unpacked.swap_curve(), swap_curve);
+        assert_eq!(
+            unpacked.pause_authority(),
+            COption::Some(TEST_PAUSE_AUTHORITY)
+        );
+        assert!(unpacked.is_paused());
+        assert_eq!(unpacked.price_a_cumulative(), TEST_PRICE_A_CUMULATIVE);
+        assert_eq!(unpacked.price_b_cumulative(), TEST_PRICE_B_CUMULATIVE);
+        assert_eq!(unpacked.last_update_slot(), TEST_LAST_UPDATE_SLOT);
+        assert_eq!(unpacked.last_update_timestamp(), TEST_LAST_UPDATE_TIMESTAMP);
+        assert_eq!(unpacked.dynamic_fee(), Some(&TEST_DYNAMIC_FEE));
+        assert_eq!(unpacked.volatility_bps(), TEST_VOLATILITY_BPS);
+        assert_eq!(unpacked.pending_fees(), Some(&TEST_PENDING_FEES));
+        assert_eq!(unpacked.pending_swap_curve(), Some(&pending_swap_curve));
+        assert_eq!(
+            unpacked.pending_activation_timestamp(),
+            TEST_PENDING_ACTIVATION_TIMESTAMP
+        );
+
+        let unpacked = SwapVersion::unpack_latest(&packed).unwrap();
+        assert_eq!(
+            unpacked.pause_authority,
+            COption::Some(TEST_PAUSE_AUTHORITY)
+        );
+        assert!(unpacked.is_paused);
     }
 
     #[test]
@@ -406,4 +1001,109 @@ mod tests {
         let err = SwapV1::unpack(&packed).unwrap_err();
         assert_eq!(err, ProgramError::UninitializedAccount);
     }
+
+    #[test]
+    fn swap_v2_pack() {
+        let curve_type = TEST_CURVE_TYPE.try_into().unwrap();
+        let calculator = Arc::new(TEST_CURVE);
+        let swap_curve = SwapCurve {
+            curve_type,
+            calculator,
+        };
+        let pending_swap_curve = SwapCurve {
+            curve_type,
+            calculator: Arc::new(OffsetCurve {
+                token_b_offset: TEST_PENDING_TOKEN_B_OFFSET,
+            }),
+        };
+        let swap_info = SwapV2 {
+            is_initialized: true,
+            bump_seed: TEST_BUMP_SEED,
+            token_program_id: TEST_TOKEN_PROGRAM_ID,
+            token_a: TEST_TOKEN_A,
+            token_b: TEST_TOKEN_B,
+            pool_mint: TEST_POOL_MINT,
+            token_a_mint: TEST_TOKEN_A_MINT,
+            token_b_mint: TEST_TOKEN_B_MINT,
+            pool_fee_account: TEST_POOL_FEE_ACCOUNT,
+            fees: TEST_FEES,
+            swap_curve,
+            pause_authority: COption::Some(TEST_PAUSE_AUTHORITY),
+            is_paused: true,
+            price_a_cumulative: TEST_PRICE_A_CUMULATIVE,
+            price_b_cumulative: TEST_PRICE_B_CUMULATIVE,
+            last_update_slot: TEST_LAST_UPDATE_SLOT,
+            last_update_timestamp: TEST_LAST_UPDATE_TIMESTAMP,
+            dynamic_fee: Some(TEST_DYNAMIC_FEE),
+            volatility_bps: TEST_VOLATILITY_BPS,
+            pending_fees: Some(TEST_PENDING_FEES),
+            pending_swap_curve: Some(pending_swap_curve.clone()),
+            pending_activation_timestamp: TEST_PENDING_ACTIVATION_TIMESTAMP,
+        };
+
+        let mut packed = [0u8; SwapV2::LEN];
+        SwapV2::pack_into_slice(&swap_info, &mut packed);
+        let unpacked = SwapV2::unpack(&packed).unwrap();
+        assert_eq!(swap_info, unpacked);
+
+        let mut packed = vec![1u8, TEST_BUMP_SEED];
+        packed.extend_from_slice(&TEST_TOKEN_PROGRAM_ID.to_bytes());
+        packed.extend_from_slice(&TEST_TOKEN_A.to_bytes());
+        packed.extend_from_slice(&TEST_TOKEN_B.to_bytes());
+        packed.extend_from_slice(&TEST_POOL_MINT.to_bytes());
+        packed.extend_from_slice(&TEST_TOKEN_A_MINT.to_bytes());
+        packed.extend_from_slice(&TEST_TOKEN_B_MINT.to_bytes());
+        packed.extend_from_slice(&TEST_POOL_FEE_ACCOUNT.to_bytes());
+        packed.extend_from_slice(&TEST_FEES.trade_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&TEST_FEES.trade_fee_denominator.to_le_bytes());
+        packed.extend_from_slice(&TEST_FEES.owner_trade_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&TEST_FEES.owner_trade_fee_denominator.to_le_bytes());
+        packed.extend_from_slice(&TEST_FEES.owner_withdraw_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&TEST_FEES.owner_withdraw_fee_denominator.to_le_bytes());
+        packed.extend_from_slice(&TEST_FEES.host_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&TEST_FEES.host_fee_denominator.to_le_bytes());
+        packed.push(TEST_CURVE_TYPE);
+        packed.extend_from_slice(&TEST_TOKEN_B_OFFSET.to_le_bytes());
+        packed.extend_from_slice(&[0u8; 24]);
+        packed.extend_from_slice(&[1, 0, 0, 0]);
+        packed.extend_from_slice(&TEST_PAUSE_AUTHORITY.to_bytes());
+        packed.push(1);
+        packed.extend_from_slice(&TEST_PRICE_A_CUMULATIVE.to_le_bytes());
+        packed.extend_from_slice(&TEST_PRICE_B_CUMULATIVE.to_le_bytes());
+        packed.extend_from_slice(&TEST_LAST_UPDATE_SLOT.to_le_bytes());
+        packed.extend_from_slice(&TEST_LAST_UPDATE_TIMESTAMP.to_le_bytes());
+        packed.push(1);
+        packed.extend_from_slice(&TEST_DYNAMIC_FEE.floor_bps.to_le_bytes());
+        packed.extend_from_slice(&TEST_DYNAMIC_FEE.ceiling_bps.to_le_bytes());
+        packed.extend_from_slice(&TEST_DYNAMIC_FEE.volatility_factor_bps.to_le_bytes());
+        packed.extend_from_slice(&TEST_DYNAMIC_FEE.decay_period.to_le_bytes());
+        packed.extend_from_slice(&TEST_VOLATILITY_BPS.to_le_bytes());
+        packed.push(1);
+        packed.extend_from_slice(&TEST_PENDING_FEES.trade_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&TEST_PENDING_FEES.trade_fee_denominator.to_le_bytes());
+        packed.extend_from_slice(&TEST_PENDING_FEES.owner_trade_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&TEST_PENDING_FEES.owner_trade_fee_denominator.to_le_bytes());
+        packed.extend_from_slice(&TEST_PENDING_FEES.owner_withdraw_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(
+            &TEST_PENDING_FEES
+                .owner_withdraw_fee_denominator
+                .to_le_bytes(),
+        );
+        packed.extend_from_slice(&TEST_PENDING_FEES.host_fee_numerator.to_le_bytes());
+        packed.extend_from_slice(&TEST_PENDING_FEES.host_fee_denominator.to_le_bytes());
+        packed.push(1);
+        packed.push(TEST_CURVE_TYPE);
+        packed.extend_from_slice(&TEST_PENDING_TOKEN_B_OFFSET.to_le_bytes());
+        packed.extend_from_slice(&[0u8; 24]);
+        packed.extend_from_slice(&TEST_PENDING_ACTIVATION_TIMESTAMP.to_le_bytes());
+        let unpacked = SwapV2::unpack(&packed).unwrap();
+        assert_eq!(swap_info, unpacked);
+
+        let packed = [0u8; SwapV2::LEN];
+        let swap_info: SwapV2 = Default::default();
+        let unpack_unchecked = SwapV2::unpack_unchecked(&packed).unwrap();
+        assert_eq!(unpack_unchecked, swap_info);
+        let err = SwapV2::unpack(&packed).unwrap_err();
+        assert_eq!(err, ProgramError::UninitializedAccount);
+    }
 }
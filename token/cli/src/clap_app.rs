@@ -118,6 +118,9 @@ pub enum CommandName {
     ApplyPendingBalance,
     UpdateGroupAddress,
     UpdateMemberAddress,
+    UpdateUiAmountMultiplier,
+    Pause,
+    Resume,
 }
 impl fmt::Display for CommandName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -188,6 +191,8 @@ pub enum CliAuthorityType {
     Metadata,
     GroupPointer,
     GroupMemberPointer,
+    ScaledUiAmount,
+    Pause,
 }
 impl TryFrom<CliAuthorityType> for AuthorityType {
     type Error = Error;
@@ -215,6 +220,8 @@ impl TryFrom<CliAuthorityType> for AuthorityType {
             }
             CliAuthorityType::GroupPointer => Ok(AuthorityType::GroupPointer),
             CliAuthorityType::GroupMemberPointer => Ok(AuthorityType::GroupMemberPointer),
+            CliAuthorityType::ScaledUiAmount => Ok(AuthorityType::ScaledUiAmount),
+            CliAuthorityType::Pause => Ok(AuthorityType::Pause),
         }
     }
 }
@@ -665,6 +672,18 @@ pub fn app<'a, 'b>(
                             Rate authority defaults to the mint authority."
                         ),
                 )
+                .arg(
+                    Arg::with_name("ui_amount_multiplier")
+                        .long("ui-amount-multiplier")
+                        .value_name("MULTIPLIER")
+                        .validator(is_parsable::<f64>)
+                        .takes_value(true)
+                        .conflicts_with("interest_rate")
+                        .help(
+                            "Specify the multiplier applied to UI amounts. \
+                            Multiplier authority defaults to the mint authority."
+                        ),
+                )
                 .arg(
                     Arg::with_name("metadata_address")
                         .long("metadata-address")
@@ -738,6 +757,15 @@ pub fn app<'a, 'b>(
                             "Enable the mint authority to be permanent delegate for this mint"
                         ),
                 )
+                .arg(
+                    Arg::with_name("enable_pause")
+                        .long("enable-pause")
+                        .takes_value(false)
+                        .help(
+                            "Enable the mint authority to pause minting, burning and \
+                            transferring for this mint"
+                        ),
+                )
                 .arg(
                     Arg::with_name("enable_confidential_transfers")
                         .long("enable-confidential-transfers")
@@ -816,6 +844,98 @@ pub fn app<'a, 'b>(
                     )
                 )
         )
+        .subcommand(
+            SubCommand::with_name(CommandName::UpdateUiAmountMultiplier.into())
+                .about("Update the UI amount multiplier for a scaled UI amount token")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .index(1)
+                        .help("The scaled UI amount token address"),
+                )
+                .arg(
+                    Arg::with_name("multiplier")
+                        .value_name("MULTIPLIER")
+                        .validator(is_parsable::<f64>)
+                        .takes_value(true)
+                        .required(true)
+                        .index(2)
+                        .help("The new multiplier"),
+                )
+                .arg(
+                    Arg::with_name("timestamp")
+                        .long("timestamp")
+                        .value_name("UNIX_TIMESTAMP")
+                        .validator(is_parsable::<i64>)
+                        .takes_value(true)
+                        .help(
+                            "Unix timestamp at which the new multiplier takes effect. \
+                            Defaults to immediately."
+                        ),
+                )
+                .arg(
+                    Arg::with_name("ui_multiplier_authority")
+                    .long("ui-multiplier-authority")
+                    .validator(is_valid_signer)
+                    .value_name("SIGNER")
+                    .takes_value(true)
+                    .help(
+                        "Specify the multiplier authority keypair. \
+                        Defaults to the client keypair address."
+                    )
+                )
+        )
+        .subcommand(
+            SubCommand::with_name(CommandName::Pause.into())
+                .about("Pause minting, burning and transferring for a pausable token")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .index(1)
+                        .help("The pausable token address"),
+                )
+                .arg(
+                    Arg::with_name("pause_authority")
+                    .long("pause-authority")
+                    .validator(is_valid_signer)
+                    .value_name("SIGNER")
+                    .takes_value(true)
+                    .help(
+                        "Specify the pause authority keypair. \
+                        Defaults to the client keypair address."
+                    )
+                )
+        )
+        .subcommand(
+            SubCommand::with_name(CommandName::Resume.into())
+                .about("Resume minting, burning and transferring for a pausable token")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .index(1)
+                        .help("The pausable token address"),
+                )
+                .arg(
+                    Arg::with_name("pause_authority")
+                    .long("pause-authority")
+                    .validator(is_valid_signer)
+                    .value_name("SIGNER")
+                    .takes_value(true)
+                    .help(
+                        "Specify the pause authority keypair. \
+                        Defaults to the client keypair address."
+                    )
+                )
+        )
         .subcommand(
             SubCommand::with_name(CommandName::SetTransferHook.into())
                 .about("Set the transfer hook program id for a token")
@@ -52,7 +52,9 @@ use {
             memo_transfer::MemoTransfer,
             metadata_pointer::MetadataPointer,
             mint_close_authority::MintCloseAuthority,
+            pausable::PausableConfig,
             permanent_delegate::PermanentDelegate,
+            scaled_ui_amount::ScaledUiAmountConfig,
             transfer_fee::{TransferFeeAmount, TransferFeeConfig},
             transfer_hook::TransferHook,
             BaseStateWithExtensions, ExtensionType, StateWithExtensionsOwned,
@@ -195,11 +197,13 @@ async fn command_create_token(
     enable_close: bool,
     enable_non_transferable: bool,
     enable_permanent_delegate: bool,
+    enable_pause: bool,
     memo: Option<String>,
     metadata_address: Option<Pubkey>,
     group_address: Option<Pubkey>,
     member_address: Option<Pubkey>,
     rate_bps: Option<i16>,
+    ui_amount_multiplier: Option<f64>,
     default_account_state: Option<AccountState>,
     transfer_fee: Option<(u16, u64)>,
     confidential_transfer_auto_approve: Option<bool>,
@@ -235,6 +239,10 @@ async fn command_create_token(
         });
     }
 
+    if enable_pause {
+        extensions.push(ExtensionInitializationParams::PausableConfig { authority });
+    }
+
     if let Some(rate_bps) = rate_bps {
         extensions.push(ExtensionInitializationParams::InterestBearingConfig {
             rate_authority: Some(authority),
@@ -242,6 +250,13 @@ async fn command_create_token(
         })
     }
 
+    if let Some(multiplier) = ui_amount_multiplier {
+        extensions.push(ExtensionInitializationParams::ScaledUiAmountConfig {
+            authority: Some(authority),
+            multiplier,
+        })
+    }
+
     if enable_non_transferable {
         extensions.push(ExtensionInitializationParams::NonTransferable);
     }
@@ -448,6 +463,118 @@ async fn command_set_interest_rate(
     })
 }
 
+async fn command_update_ui_amount_multiplier(
+    config: &Config<'_>,
+    token_pubkey: Pubkey,
+    multiplier_authority: Pubkey,
+    multiplier: f64,
+    timestamp: i64,
+    bulk_signers: Vec<Arc<dyn Signer>>,
+) -> CommandResult {
+    let token = token_client_from_config(config, &token_pubkey, None)?;
+
+    if !config.sign_only {
+        let mint_account = config.get_account_checked(&token_pubkey).await?;
+
+        let mint_state = StateWithExtensionsOwned::<Mint>::unpack(mint_account.data)
+            .map_err(|_| format!("Could not deserialize token mint {}", token_pubkey))?;
+
+        if let Ok(scaled_ui_amount_config) = mint_state.get_extension::<ScaledUiAmountConfig>() {
+            let mint_multiplier_authority_pubkey =
+                Option::<Pubkey>::from(scaled_ui_amount_config.authority);
+
+            if mint_multiplier_authority_pubkey != Some(multiplier_authority) {
+                return Err(format!(
+                    "Mint {} has multiplier authority {}, but {} was provided",
+                    token_pubkey,
+                    mint_multiplier_authority_pubkey
+                        .map(|pubkey| pubkey.to_string())
+                        .unwrap_or_else(|| "disabled".to_string()),
+                    multiplier_authority
+                )
+                .into());
+            }
+        } else {
+            return Err(format!("Mint {} does not scale UI amounts", token_pubkey).into());
+        }
+    }
+
+    println_display(
+        config,
+        format!(
+            "Setting UI amount multiplier for {} to {}",
+            token_pubkey, multiplier
+        ),
+    );
+
+    let res = token
+        .update_multiplier(&multiplier_authority, multiplier, timestamp, &bulk_signers)
+        .await?;
+
+    let tx_return = finish_tx(config, &res, false).await?;
+    Ok(match tx_return {
+        TransactionReturnData::CliSignature(signature) => {
+            config.output_format.formatted_string(&signature)
+        }
+        TransactionReturnData::CliSignOnlyData(sign_only_data) => {
+            config.output_format.formatted_string(&sign_only_data)
+        }
+    })
+}
+
+async fn command_pause(
+    config: &Config<'_>,
+    token_pubkey: Pubkey,
+    pause_authority: Pubkey,
+    pause: bool,
+    bulk_signers: Vec<Arc<dyn Signer>>,
+) -> CommandResult {
+    let token = token_client_from_config(config, &token_pubkey, None)?;
+
+    if !config.sign_only {
+        let mint_account = config.get_account_checked(&token_pubkey).await?;
+
+        let mint_state = StateWithExtensionsOwned::<Mint>::unpack(mint_account.data)
+            .map_err(|_| format!("Could not deserialize token mint {}", token_pubkey))?;
+
+        if let Ok(extension) = mint_state.get_extension::<PausableConfig>() {
+            let authority_pubkey = Option::<Pubkey>::from(extension.authority);
+
+            if authority_pubkey != Some(pause_authority) {
+                return Err(format!(
+                    "Mint {} has pause authority {}, but {} was provided",
+                    token_pubkey,
+                    authority_pubkey
+                        .map(|pubkey| pubkey.to_string())
+                        .unwrap_or_else(|| "disabled".to_string()),
+                    pause_authority
+                )
+                .into());
+            }
+        } else {
+            return Err(format!("Mint {} is not pausable", token_pubkey).into());
+        }
+    }
+
+    let res = if pause {
+        println_display(config, format!("Pausing token {}", token_pubkey));
+        token.pause(&pause_authority, &bulk_signers).await?
+    } else {
+        println_display(config, format!("Resuming token {}", token_pubkey));
+        token.resume(&pause_authority, &bulk_signers).await?
+    };
+
+    let tx_return = finish_tx(config, &res, false).await?;
+    Ok(match tx_return {
+        TransactionReturnData::CliSignature(signature) => {
+            config.output_format.formatted_string(&signature)
+        }
+        TransactionReturnData::CliSignOnlyData(sign_only_data) => {
+            config.output_format.formatted_string(&sign_only_data)
+        }
+    })
+}
+
 async fn command_set_transfer_hook_program(
     config: &Config<'_>,
     token_pubkey: Pubkey,
@@ -928,6 +1055,20 @@ async fn command_authorize(
                         ))
                     }
                 }
+                CliAuthorityType::ScaledUiAmount => {
+                    if let Ok(extension) = mint.get_extension::<ScaledUiAmountConfig>() {
+                        Ok(Option::<Pubkey>::from(extension.authority))
+                    } else {
+                        Err(format!("Mint `{}` does not scale UI amounts", account))
+                    }
+                }
+                CliAuthorityType::Pause => {
+                    if let Ok(extension) = mint.get_extension::<PausableConfig>() {
+                        Ok(Option::<Pubkey>::from(extension.authority))
+                    } else {
+                        Err(format!("Mint `{}` is not pausable", account))
+                    }
+                }
             }?;
 
             Ok((account, previous_authority))
@@ -968,7 +1109,9 @@ async fn command_authorize(
                 | CliAuthorityType::MetadataPointer
                 | CliAuthorityType::Metadata
                 | CliAuthorityType::GroupPointer
-                | CliAuthorityType::GroupMemberPointer => Err(format!(
+                | CliAuthorityType::GroupMemberPointer
+                | CliAuthorityType::ScaledUiAmount
+                | CliAuthorityType::Pause => Err(format!(
                     "Authority type `{auth_str}` not supported for Solarti Token accounts",
                 )),
                 CliAuthorityType::Owner => {
@@ -3300,6 +3443,7 @@ pub async fn process_command<'a>(
                 config.pubkey_or_default(arg_matches, "mint_authority", &mut wallet_manager)?;
             let memo = value_t!(arg_matches, "memo", String).ok();
             let rate_bps = value_t!(arg_matches, "interest_rate", i16).ok();
+            let ui_amount_multiplier = value_t!(arg_matches, "ui_amount_multiplier", f64).ok();
             let metadata_address = value_t!(arg_matches, "metadata_address", Pubkey).ok();
             let group_address = value_t!(arg_matches, "group_address", Pubkey).ok();
             let member_address = value_t!(arg_matches, "member_address", Pubkey).ok();
@@ -3345,11 +3489,13 @@ pub async fn process_command<'a>(
                 arg_matches.is_present("enable_close"),
                 arg_matches.is_present("enable_non_transferable"),
                 arg_matches.is_present("enable_permanent_delegate"),
+                arg_matches.is_present("enable_pause"),
                 memo,
                 metadata_address,
                 group_address,
                 member_address,
                 rate_bps,
+                ui_amount_multiplier,
                 default_account_state,
                 transfer_fee,
                 confidential_transfer_auto_approve,
@@ -3379,6 +3525,60 @@ pub async fn process_command<'a>(
             )
             .await
         }
+        (CommandName::UpdateUiAmountMultiplier, arg_matches) => {
+            let token_pubkey = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let multiplier = value_t_or_exit!(arg_matches, "multiplier", f64);
+            let timestamp = value_t!(arg_matches, "timestamp", i64).unwrap_or(0);
+            let (multiplier_authority_signer, multiplier_authority_pubkey) = config
+                .signer_or_default(arg_matches, "ui_multiplier_authority", &mut wallet_manager);
+            let bulk_signers = vec![multiplier_authority_signer];
+
+            command_update_ui_amount_multiplier(
+                config,
+                token_pubkey,
+                multiplier_authority_pubkey,
+                multiplier,
+                timestamp,
+                bulk_signers,
+            )
+            .await
+        }
+        (CommandName::Pause, arg_matches) => {
+            let token_pubkey = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let (pause_authority_signer, pause_authority_pubkey) =
+                config.signer_or_default(arg_matches, "pause_authority", &mut wallet_manager);
+            let bulk_signers = vec![pause_authority_signer];
+
+            command_pause(
+                config,
+                token_pubkey,
+                pause_authority_pubkey,
+                true,
+                bulk_signers,
+            )
+            .await
+        }
+        (CommandName::Resume, arg_matches) => {
+            let token_pubkey = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let (pause_authority_signer, pause_authority_pubkey) =
+                config.signer_or_default(arg_matches, "pause_authority", &mut wallet_manager);
+            let bulk_signers = vec![pause_authority_signer];
+
+            command_pause(
+                config,
+                token_pubkey,
+                pause_authority_pubkey,
+                false,
+                bulk_signers,
+            )
+            .await
+        }
         (CommandName::SetTransferHook, arg_matches) => {
             let token_pubkey = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
                 .unwrap()
@@ -27,6 +27,8 @@ use {
             memo_transfer::MemoTransfer,
             metadata_pointer::MetadataPointer,
             non_transferable::NonTransferable,
+            pausable::PausableConfig,
+            scaled_ui_amount::ScaledUiAmountConfig,
             transfer_fee::{TransferFeeAmount, TransferFeeConfig},
             transfer_hook::TransferHook,
             BaseStateWithExtensions, StateWithExtensionsOwned,
@@ -88,6 +90,8 @@ async fn main() {
         async_trial!(create_token_default, test_validator, payer),
         async_trial!(create_token_interest_bearing, test_validator, payer),
         async_trial!(set_interest_rate, test_validator, payer),
+        async_trial!(scaled_ui_amount, test_validator, payer),
+        async_trial!(pausable, test_validator, payer),
         async_trial!(supply, test_validator, payer),
         async_trial!(create_account_default, test_validator, payer),
         async_trial!(account_info, test_validator, payer),
@@ -512,6 +516,185 @@ async fn set_interest_rate(test_validator: &TestValidator, payer: &Keypair) {
     assert_eq!(i16::from(extension.current_rate), new_rate);
 }
 
+async fn scaled_ui_amount(test_validator: &TestValidator, payer: &Keypair) {
+    let config = test_config_with_default_signer(test_validator, payer, &spl_token_2022::id());
+    let result = process_test_command(
+        &config,
+        payer,
+        &[
+            "solarti-token",
+            CommandName::CreateToken.into(),
+            "--ui-amount-multiplier",
+            "1.5",
+        ],
+    )
+    .await;
+    let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+    let token = Pubkey::from_str(value["commandOutput"]["address"].as_str().unwrap()).unwrap();
+    let account = config.rpc_client.get_account(&token).await.unwrap();
+    let mint_account = StateWithExtensionsOwned::<Mint>::unpack(account.data).unwrap();
+    let extension = mint_account
+        .get_extension::<ScaledUiAmountConfig>()
+        .unwrap();
+    assert_eq!(f64::from(extension.multiplier), 1.5);
+    assert_eq!(
+        Option::<Pubkey>::from(extension.authority),
+        Some(payer.pubkey())
+    );
+
+    // immediately
+    process_test_command(
+        &config,
+        payer,
+        &[
+            "solarti-token",
+            CommandName::UpdateUiAmountMultiplier.into(),
+            &token.to_string(),
+            "2",
+        ],
+    )
+    .await
+    .unwrap();
+    let account = config.rpc_client.get_account(&token).await.unwrap();
+    let mint_account = StateWithExtensionsOwned::<Mint>::unpack(account.data).unwrap();
+    let extension = mint_account
+        .get_extension::<ScaledUiAmountConfig>()
+        .unwrap();
+    assert_eq!(f64::from(extension.multiplier), 2.);
+    assert_eq!(f64::from(extension.new_multiplier), 2.);
+
+    // scheduled far in the future
+    let timestamp = i64::MAX.to_string();
+    process_test_command(
+        &config,
+        payer,
+        &[
+            "solarti-token",
+            CommandName::UpdateUiAmountMultiplier.into(),
+            &token.to_string(),
+            "3",
+            "--timestamp",
+            &timestamp,
+        ],
+    )
+    .await
+    .unwrap();
+    let account = config.rpc_client.get_account(&token).await.unwrap();
+    let mint_account = StateWithExtensionsOwned::<Mint>::unpack(account.data).unwrap();
+    let extension = mint_account
+        .get_extension::<ScaledUiAmountConfig>()
+        .unwrap();
+    assert_eq!(f64::from(extension.multiplier), 2.);
+    assert_eq!(f64::from(extension.new_multiplier), 3.);
+    assert_eq!(
+        i64::from(extension.new_multiplier_effective_timestamp),
+        i64::MAX
+    );
+}
+
+async fn pausable(test_validator: &TestValidator, payer: &Keypair) {
+    let config = test_config_with_default_signer(test_validator, payer, &spl_token_2022::id());
+
+    let token = Keypair::new();
+    let token_keypair_file = NamedTempFile::new().unwrap();
+    write_keypair_file(&token, &token_keypair_file).unwrap();
+    let token_pubkey = token.pubkey();
+    process_test_command(
+        &config,
+        payer,
+        &[
+            "solarti-token",
+            CommandName::CreateToken.into(),
+            token_keypair_file.path().to_str().unwrap(),
+            "--enable-pause",
+        ],
+    )
+    .await
+    .unwrap();
+
+    let account = config.rpc_client.get_account(&token_pubkey).await.unwrap();
+    let test_mint = StateWithExtensionsOwned::<Mint>::unpack(account.data).unwrap();
+    let extension = test_mint.get_extension::<PausableConfig>().unwrap();
+    assert_eq!(
+        Option::<Pubkey>::from(extension.authority),
+        Some(payer.pubkey())
+    );
+    assert!(!bool::from(extension.paused));
+
+    let associated_account =
+        create_associated_account(&config, payer, &token_pubkey, &payer.pubkey()).await;
+    let aux_account = create_auxiliary_account(&config, payer, token_pubkey).await;
+    mint_tokens(&config, payer, token_pubkey, 100.0, associated_account)
+        .await
+        .unwrap();
+
+    process_test_command(
+        &config,
+        payer,
+        &[
+            "solarti-token",
+            CommandName::Pause.into(),
+            &token_pubkey.to_string(),
+        ],
+    )
+    .await
+    .unwrap();
+    let account = config.rpc_client.get_account(&token_pubkey).await.unwrap();
+    let test_mint = StateWithExtensionsOwned::<Mint>::unpack(account.data).unwrap();
+    let extension = test_mint.get_extension::<PausableConfig>().unwrap();
+    assert!(bool::from(extension.paused));
+
+    // transfer not allowed
+    process_test_command(
+        &config,
+        payer,
+        &[
+            "solarti-token",
+            CommandName::Transfer.into(),
+            "--from",
+            &associated_account.to_string(),
+            &token_pubkey.to_string(),
+            "1",
+            &aux_account.to_string(),
+        ],
+    )
+    .await
+    .unwrap_err();
+
+    process_test_command(
+        &config,
+        payer,
+        &[
+            "solarti-token",
+            CommandName::Resume.into(),
+            &token_pubkey.to_string(),
+        ],
+    )
+    .await
+    .unwrap();
+    let account = config.rpc_client.get_account(&token_pubkey).await.unwrap();
+    let test_mint = StateWithExtensionsOwned::<Mint>::unpack(account.data).unwrap();
+    let extension = test_mint.get_extension::<PausableConfig>().unwrap();
+    assert!(!bool::from(extension.paused));
+
+    // transfer allowed again
+    process_test_command(
+        &config,
+        payer,
+        &[
+            "solarti-token",
+            CommandName::Transfer.into(),
+            "--from",
+            &associated_account.to_string(),
+            &token_pubkey.to_string(),
+            "1",
+            &aux_account.to_string(),
+        ],
+    )
+    .await
+    .unwrap();
+}
+
 async fn supply(test_validator: &TestValidator, payer: &Keypair) {
     for program_id in VALID_TOKEN_PROGRAM_IDS.iter() {
         let config = test_config_with_default_signer(test_validator, payer, program_id);
@@ -1,5 +1,6 @@
 //! Helper functions to generate split zero-knowledge proofs for confidential
-//! transfers in the Confidential Transfer Extension.
+//! transfers in the Confidential Transfer Extension and for confidential mints
+//! in the Confidential Mint-Burn Extension.
 //!
 //! The logic in this submodule should belong to the `miraland-zk-token-sdk` and
 //! will be removed with an upgrade to the Miraland program.
@@ -25,7 +26,7 @@ use {
                     FeeParameters, TransferAmountCiphertext,
                 },
                 BatchedGroupedCiphertext2HandlesValidityProofData, BatchedRangeProofU256Data,
-                CiphertextCommitmentEqualityProofData, FeeSigmaProofData,
+                BatchedRangeProofU64Data, CiphertextCommitmentEqualityProofData, FeeSigmaProofData,
             },
             zk_token_elgamal::ops::subtract_with_lo_hi,
         },
@@ -326,6 +327,84 @@ pub fn transfer_with_fee_split_proof_data(
     ))
 }
 
+/// The main logic to create the ciphertext validity and range proof data for a
+/// confidential mint.
+///
+/// The mint amount is encrypted under the destination and the supply ElGamal
+/// public keys so that the program can credit the destination account and the
+/// encrypted supply with the same ciphertexts.
+pub fn mint_split_proof_data(
+    mint_amount: u64,
+    destination_elgamal_pubkey: &ElGamalPubkey,
+    supply_elgamal_pubkey: &ElGamalPubkey,
+) -> Result<
+    (
+        BatchedGroupedCiphertext2HandlesValidityProofData,
+        BatchedRangeProofU64Data,
+    ),
+    TokenError,
+> {
+    // Split the mint amount into the low and high bit components.
+    let (mint_amount_lo, mint_amount_hi) = verify_and_split_deposit_amount(mint_amount)?;
+
+    // encrypt the mint amount under the destination and supply ElGamal public key
+    let mint_amount_opening_lo = PedersenOpening::new_rand();
+    let mint_amount_opening_hi = PedersenOpening::new_rand();
+    let mint_amount_ciphertext_lo = GroupedElGamal::encrypt_with(
+        [destination_elgamal_pubkey, supply_elgamal_pubkey],
+        mint_amount_lo,
+        &mint_amount_opening_lo,
+    );
+    let mint_amount_ciphertext_hi = GroupedElGamal::encrypt_with(
+        [destination_elgamal_pubkey, supply_elgamal_pubkey],
+        mint_amount_hi,
+        &mint_amount_opening_hi,
+    );
+
+    // generate mint amount ciphertext validity data
+    let ciphertext_validity_proof_data = BatchedGroupedCiphertext2HandlesValidityProofData::new(
+        destination_elgamal_pubkey,
+        supply_elgamal_pubkey,
+        &mint_amount_ciphertext_lo,
+        &mint_amount_ciphertext_hi,
+        mint_amount_lo,
+        mint_amount_hi,
+        &mint_amount_opening_lo,
+        &mint_amount_opening_hi,
+    )
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    // generate range proof data
+    const MINT_AMOUNT_LO_BIT_LENGTH: usize = 16;
+    const MINT_AMOUNT_HI_BIT_LENGTH: usize = 32;
+    const PADDING_BIT_LENGTH: usize = 16;
+
+    // the range proof must be over 64 bits, so pad with a zero commitment
+    let (padding_commitment, padding_opening) = Pedersen::new(0_u64);
+
+    let range_proof_data = BatchedRangeProofU64Data::new(
+        vec![
+            &Pedersen::with(mint_amount_lo, &mint_amount_opening_lo),
+            &Pedersen::with(mint_amount_hi, &mint_amount_opening_hi),
+            &padding_commitment,
+        ],
+        vec![mint_amount_lo, mint_amount_hi, 0],
+        vec![
+            MINT_AMOUNT_LO_BIT_LENGTH,
+            MINT_AMOUNT_HI_BIT_LENGTH,
+            PADDING_BIT_LENGTH,
+        ],
+        vec![
+            &mint_amount_opening_lo,
+            &mint_amount_opening_hi,
+            &padding_opening,
+        ],
+    )
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    Ok((ciphertext_validity_proof_data, range_proof_data))
+}
+
 /// Calculate transfer fee and the "delta" value. The function returns the raw
 /// fee, which could be greater than the maximum fee amount of a fee parameter.
 ///
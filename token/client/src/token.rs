@@ -1,7 +1,7 @@
 use {
     crate::{
         client::{ProgramClient, ProgramClientError, SendTransaction, SimulateTransaction},
-        proof_generation::transfer_with_fee_split_proof_data,
+        proof_generation::{mint_split_proof_data, transfer_with_fee_split_proof_data},
     },
     futures::{future::join_all, try_join},
     futures_util::TryFutureExt,
@@ -26,6 +26,7 @@ use {
     },
     spl_token_2022::{
         extension::{
+            confidential_mint_burn::{self, ConfidentialMintBurn},
             confidential_transfer::{
                 self,
                 account_info::{
@@ -36,21 +37,22 @@ use {
                 instruction::{
                     TransferSplitContextStateAccounts, TransferWithFeeSplitContextStateAccounts,
                 },
-                ConfidentialTransferAccount, DecryptableBalance,
+                ConfidentialTransferAccount, ConfidentialTransferMint, DecryptableBalance,
             },
             confidential_transfer_fee::{
                 self, account_info::WithheldTokensInfo, ConfidentialTransferFeeAmount,
                 ConfidentialTransferFeeConfig,
             },
             cpi_guard, default_account_state, group_member_pointer, group_pointer,
-            interest_bearing_mint, memo_transfer, metadata_pointer, transfer_fee, transfer_hook,
-            BaseStateWithExtensions, Extension, ExtensionType, StateWithExtensionsOwned,
+            interest_bearing_mint, memo_transfer, metadata_pointer, pausable, scaled_ui_amount,
+            transfer_fee, transfer_hook, vesting, BaseStateWithExtensions, Extension,
+            ExtensionType, StateWithExtensionsOwned,
         },
         instruction, offchain,
         proof::ProofLocation,
         solana_zk_token_sdk::{
             encryption::{
-                auth_encryption::AeKey,
+                auth_encryption::{AeCiphertext, AeKey},
                 elgamal::{ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey, ElGamalSecretKey},
             },
             instruction::*,
@@ -180,6 +182,20 @@ pub enum ExtensionInitializationParams {
         authority: Option<Pubkey>,
         member_address: Option<Pubkey>,
     },
+    ScaledUiAmountConfig {
+        authority: Option<Pubkey>,
+        multiplier: f64,
+    },
+    PausableConfig {
+        authority: Pubkey,
+    },
+    ConfidentialMintBurnMint {
+        supply_elgamal_pubkey: PodElGamalPubkey,
+        decryptable_supply: DecryptableBalance,
+    },
+    VestingConfig {
+        authority: Pubkey,
+    },
 }
 impl ExtensionInitializationParams {
     /// Get the extension type associated with the init params
@@ -199,6 +215,10 @@ impl ExtensionInitializationParams {
             }
             Self::GroupPointer { .. } => ExtensionType::GroupPointer,
             Self::GroupMemberPointer { .. } => ExtensionType::GroupMemberPointer,
+            Self::ScaledUiAmountConfig { .. } => ExtensionType::ScaledUiAmount,
+            Self::PausableConfig { .. } => ExtensionType::PausableConfig,
+            Self::ConfidentialMintBurnMint { .. } => ExtensionType::ConfidentialMintBurn,
+            Self::VestingConfig { .. } => ExtensionType::VestingConfig,
         }
     }
     /// Generate an appropriate initialization instruction for the given mint
@@ -308,6 +328,30 @@ impl ExtensionInitializationParams {
                 authority,
                 member_address,
             ),
+            Self::ScaledUiAmountConfig {
+                authority,
+                multiplier,
+            } => scaled_ui_amount::instruction::initialize(
+                token_program_id,
+                mint,
+                authority,
+                multiplier,
+            ),
+            Self::PausableConfig { authority } => {
+                pausable::instruction::initialize(token_program_id, mint, &authority)
+            }
+            Self::ConfidentialMintBurnMint {
+                supply_elgamal_pubkey,
+                decryptable_supply,
+            } => confidential_mint_burn::instruction::initialize_mint(
+                token_program_id,
+                mint,
+                supply_elgamal_pubkey,
+                decryptable_supply,
+            ),
+            Self::VestingConfig { authority } => {
+                vesting::instruction::initialize(token_program_id, mint, &authority)
+            }
         }
     }
 }
@@ -1645,6 +1689,105 @@ where
         .await
     }
 
+    /// Update the UI amount multiplier, effective from the given timestamp
+    pub async fn update_multiplier<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        new_multiplier: f64,
+        effective_timestamp: i64,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[scaled_ui_amount::instruction::update_multiplier(
+                &self.program_id,
+                self.get_address(),
+                authority,
+                &multisig_signers,
+                new_multiplier,
+                effective_timestamp,
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Pause minting, burning and transferring
+    pub async fn pause<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[pausable::instruction::pause(
+                &self.program_id,
+                self.get_address(),
+                authority,
+                &multisig_signers,
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Resume minting, burning and transferring
+    pub async fn resume<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[pausable::instruction::resume(
+                &self.program_id,
+                self.get_address(),
+                authority,
+                &multisig_signers,
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Set the vesting schedule of an account
+    #[allow(clippy::too_many_arguments)]
+    pub async fn set_vesting_schedule<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        locked_amount: u64,
+        start_timestamp: i64,
+        cliff_timestamp: i64,
+        end_timestamp: i64,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[vesting::instruction::set_schedule(
+                &self.program_id,
+                account,
+                self.get_address(),
+                authority,
+                &multisig_signers,
+                locked_amount,
+                start_timestamp,
+                cliff_timestamp,
+                end_timestamp,
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
     /// Update transfer hook program id
     pub async fn update_transfer_hook_program_id<S: Signers>(
         &self,
@@ -3463,6 +3606,310 @@ where
         .await
     }
 
+    /// Update the decryptable supply of a mint with confidential mint-burn
+    pub async fn confidential_mint_burn_update_decryptable_supply<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        supply: u64,
+        supply_aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[
+                confidential_mint_burn::instruction::update_decryptable_supply(
+                    &self.program_id,
+                    &self.pubkey,
+                    authority,
+                    &multisig_signers,
+                    supply_aes_key.encrypt(supply).into(),
+                )?,
+            ],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Mint tokens confidentially into the pending balance of an account
+    ///
+    /// This function internally generates the ZK Token proof instructions to
+    /// create the necessary proof context states, which are closed once the
+    /// tokens are minted.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_mint<S: Signers>(
+        &self,
+        destination_account: &Pubkey,
+        authority: &Pubkey,
+        mint_amount: u64,
+        supply_aes_key: &AeKey,
+        ciphertext_validity_proof_account: &dyn Signer,
+        range_proof_account: &dyn Signer,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let mint = self.get_mint_info().await?;
+        let confidential_mint_burn = mint.get_extension::<ConfidentialMintBurn>()?;
+        let supply_elgamal_pubkey: ElGamalPubkey = confidential_mint_burn
+            .supply_elgamal_pubkey
+            .try_into()
+            .map_err(|_| TokenError::AccountDecryption)?;
+        let current_decryptable_supply: AeCiphertext = confidential_mint_burn
+            .decryptable_supply
+            .try_into()
+            .map_err(|_| TokenError::AccountDecryption)?;
+        let new_supply = current_decryptable_supply
+            .decrypt(supply_aes_key)
+            .ok_or(TokenError::AccountDecryption)?
+            .checked_add(mint_amount)
+            .ok_or(TokenError::Program(ProgramError::ArithmeticOverflow))?;
+
+        let account = self.get_account_info(destination_account).await?;
+        let destination_elgamal_pubkey: ElGamalPubkey = account
+            .get_extension::<ConfidentialTransferAccount>()?
+            .elgamal_pubkey
+            .try_into()
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        let (ciphertext_validity_proof_data, range_proof_data) = mint_split_proof_data(
+            mint_amount,
+            &destination_elgamal_pubkey,
+            &supply_elgamal_pubkey,
+        )
+        .map_err(|_| TokenError::ProofGeneration)?;
+
+        // create the ciphertext validity proof context state
+        let context_state_authority = self.payer.pubkey();
+        let ciphertext_validity_proof_context = ciphertext_validity_proof_account.pubkey();
+        let instruction_type = ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity;
+        let space =
+            size_of::<ProofContextState<BatchedGroupedCiphertext2HandlesValidityProofContext>>();
+        let rent = self
+            .client
+            .get_minimum_balance_for_rent_exemption(space)
+            .await
+            .map_err(TokenError::Client)?;
+        self.process_ixs(
+            &[
+                system_instruction::create_account(
+                    &self.payer.pubkey(),
+                    &ciphertext_validity_proof_context,
+                    rent,
+                    space as u64,
+                    &zk_token_proof_program::id(),
+                ),
+                instruction_type.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &ciphertext_validity_proof_context,
+                        context_state_authority: &context_state_authority,
+                    }),
+                    &ciphertext_validity_proof_data,
+                ),
+            ],
+            &[ciphertext_validity_proof_account],
+        )
+        .await?;
+
+        // create the range proof context state
+        let range_proof_context = range_proof_account.pubkey();
+        let instruction_type = ProofInstruction::VerifyBatchedRangeProofU64;
+        let space = size_of::<ProofContextState<BatchedRangeProofContext>>();
+        let rent = self
+            .client
+            .get_minimum_balance_for_rent_exemption(space)
+            .await
+            .map_err(TokenError::Client)?;
+        self.process_ixs(
+            &[
+                system_instruction::create_account(
+                    &self.payer.pubkey(),
+                    &range_proof_context,
+                    rent,
+                    space as u64,
+                    &zk_token_proof_program::id(),
+                ),
+                instruction_type.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &range_proof_context,
+                        context_state_authority: &context_state_authority,
+                    }),
+                    &range_proof_data,
+                ),
+            ],
+            &[range_proof_account],
+        )
+        .await?;
+
+        let result = self
+            .process_ixs(
+                &[confidential_mint_burn::instruction::confidential_mint(
+                    &self.program_id,
+                    destination_account,
+                    &self.pubkey,
+                    supply_aes_key.encrypt(new_supply).into(),
+                    authority,
+                    &multisig_signers,
+                    &ciphertext_validity_proof_context,
+                    &range_proof_context,
+                )?],
+                signing_keypairs,
+            )
+            .await?;
+
+        self.close_context_states(&[&ciphertext_validity_proof_context, &range_proof_context])
+            .await?;
+
+        Ok(result)
+    }
+
+    /// Burn tokens confidentially from the available balance of an account
+    ///
+    /// The burn amount is proven as a confidential transfer to the supply
+    /// ElGamal public key. This function internally generates the ZK Token
+    /// proof instructions to create the necessary proof context states, which
+    /// are closed once the tokens are burned.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_burn<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        source_authority: &Pubkey,
+        burn_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        equality_proof_account: &dyn Signer,
+        ciphertext_validity_proof_account: &dyn Signer,
+        range_proof_account: &dyn Signer,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            let account = self.get_account_info(source_account).await?;
+            let confidential_transfer_account =
+                account.get_extension::<ConfidentialTransferAccount>()?;
+            TransferAccountInfo::new(confidential_transfer_account)
+        };
+
+        let mint = self.get_mint_info().await?;
+        let supply_elgamal_pubkey: ElGamalPubkey = mint
+            .get_extension::<ConfidentialMintBurn>()?
+            .supply_elgamal_pubkey
+            .try_into()
+            .map_err(|_| TokenError::AccountDecryption)?;
+        let auditor_elgamal_pubkey: Option<ElGamalPubkey> = Option::<PodElGamalPubkey>::from(
+            mint.get_extension::<ConfidentialTransferMint>()?
+                .auditor_elgamal_pubkey,
+        )
+        .map(|pubkey| pubkey.try_into())
+        .transpose()
+        .map_err(|_| TokenError::AccountDecryption)?;
+
+        let (
+            equality_proof_data,
+            ciphertext_validity_proof_data,
+            range_proof_data,
+            source_decrypt_handles,
+        ) = account_info
+            .generate_split_transfer_proof_data(
+                burn_amount,
+                source_elgamal_keypair,
+                source_aes_key,
+                &supply_elgamal_pubkey,
+                auditor_elgamal_pubkey.as_ref(),
+            )
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(burn_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        let context_state_authority = self.payer.pubkey();
+        let equality_proof_context = equality_proof_account.pubkey();
+        let ciphertext_validity_proof_context = ciphertext_validity_proof_account.pubkey();
+        let range_proof_context = range_proof_account.pubkey();
+        let context_state_accounts = TransferSplitContextStateAccounts {
+            equality_proof: &equality_proof_context,
+            ciphertext_validity_proof: &ciphertext_validity_proof_context,
+            range_proof: &range_proof_context,
+            authority: &context_state_authority,
+            no_op_on_uninitialized_split_context_state: false,
+            close_split_context_state_accounts: None,
+        };
+
+        self.create_equality_and_ciphertext_validity_proof_context_states_for_transfer(
+            context_state_accounts,
+            &equality_proof_data,
+            &ciphertext_validity_proof_data,
+            &[equality_proof_account, ciphertext_validity_proof_account],
+        )
+        .await?;
+        self.create_range_proof_context_state_with_optional_transfer(
+            context_state_accounts,
+            &range_proof_data,
+            None,
+            &[range_proof_account],
+        )
+        .await?;
+
+        let result = self
+            .process_ixs(
+                &[confidential_mint_burn::instruction::confidential_burn(
+                    &self.program_id,
+                    source_account,
+                    &self.pubkey,
+                    new_decryptable_available_balance.into(),
+                    &source_decrypt_handles,
+                    source_authority,
+                    &multisig_signers,
+                    &equality_proof_context,
+                    &ciphertext_validity_proof_context,
+                    &range_proof_context,
+                )?],
+                signing_keypairs,
+            )
+            .await?;
+
+        self.close_context_states(&[
+            &equality_proof_context,
+            &ciphertext_validity_proof_context,
+            &range_proof_context,
+        ])
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Close ZK Token proof program context states whose authority is the
+    /// payer, sending the lamports back to the payer
+    async fn close_context_states(
+        &self,
+        context_state_accounts: &[&Pubkey],
+    ) -> TokenResult<T::Output> {
+        let context_state_authority = &self.payer.pubkey();
+        let instructions = context_state_accounts
+            .iter()
+            .map(|context_state_account| {
+                zk_token_proof_instruction::close_context_state(
+                    ContextStateInfo {
+                        context_state_account,
+                        context_state_authority,
+                    },
+                    &self.payer.pubkey(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        self.process_ixs(&instructions, &[] as &[&dyn Signer; 0])
+            .await
+    }
+
     pub async fn withdraw_excess_lamports<S: Signers>(
         &self,
         source: &Pubkey,
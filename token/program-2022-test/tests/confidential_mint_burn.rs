@@ -0,0 +1,356 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+#[cfg(feature = "zk-ops")]
+use spl_token_2022::extension::confidential_transfer::ConfidentialTransferAccount;
+use {
+    program_test::{TestContext, TokenContext},
+    solana_program_test::tokio,
+    solana_sdk::{
+        instruction::InstructionError, pubkey::Pubkey, signature::Signer, signer::keypair::Keypair,
+        transaction::TransactionError, transport::TransportError,
+    },
+    spl_token_2022::{
+        error::TokenError,
+        extension::{
+            confidential_mint_burn::ConfidentialMintBurn, BaseStateWithExtensions, ExtensionType,
+        },
+        solana_zk_token_sdk::encryption::{auth_encryption::*, elgamal::*},
+    },
+    spl_token_client::{
+        client::{SendTransaction, SimulateTransaction},
+        token::{ExtensionInitializationParams, Token, TokenError as TokenClientError},
+    },
+    std::convert::TryInto,
+};
+
+struct ConfidentialTokenAccountMeta {
+    token_account: Pubkey,
+    elgamal_keypair: ElGamalKeypair,
+    aes_key: AeKey,
+}
+
+impl ConfidentialTokenAccountMeta {
+    async fn new<T>(token: &Token<T>, owner: &Keypair) -> Self
+    where
+        T: SendTransaction + SimulateTransaction,
+    {
+        let token_account_keypair = Keypair::new();
+        token
+            .create_auxiliary_token_account_with_extension_space(
+                &token_account_keypair,
+                &owner.pubkey(),
+                vec![ExtensionType::ConfidentialTransferAccount],
+            )
+            .await
+            .unwrap();
+        let token_account = token_account_keypair.pubkey();
+
+        let elgamal_keypair =
+            ElGamalKeypair::new_from_signer(owner, &token_account.to_bytes()).unwrap();
+        let aes_key = AeKey::new_from_signer(owner, &token_account.to_bytes()).unwrap();
+
+        token
+            .confidential_transfer_configure_token_account(
+                &token_account,
+                &owner.pubkey(),
+                None,
+                None,
+                &elgamal_keypair,
+                &aes_key,
+                &[owner],
+            )
+            .await
+            .unwrap();
+
+        Self {
+            token_account,
+            elgamal_keypair,
+            aes_key,
+        }
+    }
+}
+
+struct SupplyKeys {
+    elgamal_keypair: ElGamalKeypair,
+    aes_key: AeKey,
+}
+
+async fn setup() -> (TokenContext, SupplyKeys) {
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let supply_keys = SupplyKeys {
+        elgamal_keypair: ElGamalKeypair::new_rand(),
+        aes_key: AeKey::new_rand(),
+    };
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: None,
+                auto_approve_new_accounts: true,
+                auditor_elgamal_pubkey: Some((*auditor_elgamal_keypair.pubkey()).into()),
+            },
+            ExtensionInitializationParams::ConfidentialMintBurnMint {
+                supply_elgamal_pubkey: (*supply_keys.elgamal_keypair.pubkey()).into(),
+                decryptable_supply: supply_keys.aes_key.encrypt(0).into(),
+            },
+        ])
+        .await
+        .unwrap();
+    (context.token_context.unwrap(), supply_keys)
+}
+
+#[tokio::test]
+async fn success_init() {
+    let (token_context, supply_keys) = setup().await;
+
+    let state = token_context.token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<ConfidentialMintBurn>().unwrap();
+    assert_eq!(
+        extension.supply_elgamal_pubkey,
+        (*supply_keys.elgamal_keypair.pubkey()).into()
+    );
+    assert_eq!(
+        supply_keys
+            .aes_key
+            .decrypt(&extension.decryptable_supply.try_into().unwrap())
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn fail_init_without_confidential_transfer_mint() {
+    let supply_elgamal_keypair = ElGamalKeypair::new_rand();
+    let supply_aes_key = AeKey::new_rand();
+
+    let mut context = TestContext::new().await;
+    let err = context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialMintBurnMint {
+                supply_elgamal_pubkey: (*supply_elgamal_keypair.pubkey()).into(),
+                decryptable_supply: supply_aes_key.encrypt(0).into(),
+            },
+        ])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                2,
+                InstructionError::Custom(TokenError::InvalidExtensionCombination as u32),
+            )
+        )))
+    );
+}
+
+#[tokio::test]
+async fn fail_public_mint() {
+    let (token_context, _) = setup().await;
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        ..
+    } = token_context;
+
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+
+    let err = token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            1,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::IllegalMintBurnConversion as u32),
+            )
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_mint_and_burn() {
+    let (token_context, supply_keys) = setup().await;
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        ..
+    } = token_context;
+
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+
+    // mint into the pending balance
+    token
+        .confidential_mint(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &supply_keys.aes_key,
+            &Keypair::new(),
+            &Keypair::new(),
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(
+        extension
+            .pending_balance_lo
+            .decrypt(alice_meta.elgamal_keypair.secret())
+            .unwrap(),
+        42
+    );
+
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // the supply auditor can decrypt the supply, the public supply stays at zero
+    let state = token.get_mint_info().await.unwrap();
+    assert_eq!(state.base.supply, 0);
+    let extension = state.get_extension::<ConfidentialMintBurn>().unwrap();
+    assert_eq!(
+        extension
+            .confidential_supply
+            .decrypt(supply_keys.elgamal_keypair.secret())
+            .unwrap(),
+        42
+    );
+    assert_eq!(
+        supply_keys
+            .aes_key
+            .decrypt(&extension.decryptable_supply.try_into().unwrap())
+            .unwrap(),
+        42
+    );
+
+    // burn from the available balance
+    token
+        .confidential_burn(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            10,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &Keypair::new(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(
+        extension
+            .available_balance
+            .decrypt(alice_meta.elgamal_keypair.secret())
+            .unwrap(),
+        32
+    );
+    assert_eq!(
+        alice_meta
+            .aes_key
+            .decrypt(&extension.decryptable_available_balance.try_into().unwrap())
+            .unwrap(),
+        32
+    );
+
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<ConfidentialMintBurn>().unwrap();
+    assert_eq!(
+        extension
+            .confidential_supply
+            .decrypt(supply_keys.elgamal_keypair.secret())
+            .unwrap(),
+        32
+    );
+
+    // burns leave the decryptable supply stale until the mint authority
+    // updates it
+    assert_eq!(
+        supply_keys
+            .aes_key
+            .decrypt(&extension.decryptable_supply.try_into().unwrap())
+            .unwrap(),
+        42
+    );
+    token
+        .confidential_mint_burn_update_decryptable_supply(
+            &mint_authority.pubkey(),
+            32,
+            &supply_keys.aes_key,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<ConfidentialMintBurn>().unwrap();
+    assert_eq!(
+        supply_keys
+            .aes_key
+            .decrypt(&extension.decryptable_supply.try_into().unwrap())
+            .unwrap(),
+        32
+    );
+}
+
+#[tokio::test]
+async fn fail_update_decryptable_supply_wrong_authority() {
+    let (token_context, supply_keys) = setup().await;
+    let TokenContext { token, .. } = token_context;
+
+    let wrong = Keypair::new();
+    let err = token
+        .confidential_mint_burn_update_decryptable_supply(
+            &wrong.pubkey(),
+            1,
+            &supply_keys.aes_key,
+            &[&wrong],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::OwnerMismatch as u32),
+            )
+        )))
+    );
+}
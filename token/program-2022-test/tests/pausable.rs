@@ -0,0 +1,291 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{TestContext, TokenContext},
+    solana_program_test::tokio,
+    solana_sdk::{
+        instruction::InstructionError, pubkey::Pubkey, signature::Signer, signer::keypair::Keypair,
+        transaction::TransactionError, transport::TransportError,
+    },
+    spl_token_2022::{
+        error::TokenError,
+        extension::{
+            pausable::{PausableAccount, PausableConfig},
+            BaseStateWithExtensions,
+        },
+        instruction,
+    },
+    spl_token_client::token::{ExtensionInitializationParams, TokenError as TokenClientError},
+    std::convert::TryInto,
+};
+
+fn paused_error() -> TokenClientError {
+    TokenClientError::Client(Box::new(TransportError::TransactionError(
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenError::MintPaused as u32),
+        ),
+    )))
+}
+
+async fn setup(pause_authority: &Pubkey) -> TokenContext {
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![ExtensionInitializationParams::PausableConfig {
+            authority: *pause_authority,
+        }])
+        .await
+        .unwrap();
+    context.token_context.unwrap()
+}
+
+async fn setup_accounts(token_context: &TokenContext, amount: u64) -> (Pubkey, Pubkey) {
+    let alice_account = Keypair::new();
+    token_context
+        .token
+        .create_auxiliary_token_account(&alice_account, &token_context.alice.pubkey())
+        .await
+        .unwrap();
+    let alice_account = alice_account.pubkey();
+    let bob_account = Keypair::new();
+    token_context
+        .token
+        .create_auxiliary_token_account(&bob_account, &token_context.bob.pubkey())
+        .await
+        .unwrap();
+    let bob_account = bob_account.pubkey();
+
+    // mint tokens
+    token_context
+        .token
+        .mint_to(
+            &alice_account,
+            &token_context.mint_authority.pubkey(),
+            amount,
+            &[&token_context.mint_authority],
+        )
+        .await
+        .unwrap();
+    (alice_account, bob_account)
+}
+
+#[tokio::test]
+async fn success_init() {
+    let pause_authority = Pubkey::new_unique();
+    let TokenContext { token, alice, .. } = setup(&pause_authority).await;
+
+    let state = token.get_mint_info().await.unwrap();
+    assert!(state.base.is_initialized);
+    let extension = state.get_extension::<PausableConfig>().unwrap();
+    assert_eq!(
+        extension.authority,
+        Some(pause_authority).try_into().unwrap()
+    );
+    assert!(!bool::from(extension.paused));
+
+    // accounts get the account extension
+    let account = Keypair::new();
+    token
+        .create_auxiliary_token_account(&account, &alice.pubkey())
+        .await
+        .unwrap();
+    let state = token.get_account_info(&account.pubkey()).await.unwrap();
+    state.get_extension::<PausableAccount>().unwrap();
+}
+
+#[tokio::test]
+async fn pause_and_resume() {
+    let pause_authority = Keypair::new();
+    let token_context = setup(&pause_authority.pubkey()).await;
+    let amount = 10;
+    let (alice_account, bob_account) = setup_accounts(&token_context, amount).await;
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        ..
+    } = token_context;
+
+    token
+        .pause(&pause_authority.pubkey(), &[&pause_authority])
+        .await
+        .unwrap();
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<PausableConfig>().unwrap();
+    assert!(bool::from(extension.paused));
+
+    // transfer checked fails
+    let err = token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap_err();
+    assert_eq!(err, paused_error());
+
+    // transfer without the mint fails, the mint is needed to check the pause
+    #[allow(deprecated)]
+    let err = token
+        .process_ixs(
+            &[instruction::transfer(
+                &spl_token_2022::id(),
+                &alice_account,
+                &bob_account,
+                &alice.pubkey(),
+                &[],
+                1,
+            )
+            .unwrap()],
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::MintRequiredForTransfer as u32),
+            )
+        )))
+    );
+
+    // mint fails
+    let err = token
+        .mint_to(
+            &alice_account,
+            &mint_authority.pubkey(),
+            1,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, paused_error());
+
+    // burn fails
+    let err = token
+        .burn(&alice_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap_err();
+    assert_eq!(err, paused_error());
+
+    // everything works again after resuming
+    token
+        .resume(&pause_authority.pubkey(), &[&pause_authority])
+        .await
+        .unwrap();
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<PausableConfig>().unwrap();
+    assert!(!bool::from(extension.paused));
+
+    token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap();
+    token
+        .mint_to(
+            &alice_account,
+            &mint_authority.pubkey(),
+            1,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .burn(&alice_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap();
+    let alice_state = token.get_account_info(&alice_account).await.unwrap();
+    assert_eq!(alice_state.base.amount, amount - 1);
+    let bob_state = token.get_account_info(&bob_account).await.unwrap();
+    assert_eq!(bob_state.base.amount, 1);
+}
+
+#[tokio::test]
+async fn fail_wrong_authority() {
+    let pause_authority = Keypair::new();
+    let TokenContext { token, .. } = setup(&pause_authority.pubkey()).await;
+
+    let wrong = Keypair::new();
+    let err = token.pause(&wrong.pubkey(), &[&wrong]).await.unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::OwnerMismatch as u32)
+            )
+        )))
+    );
+}
+
+#[tokio::test]
+async fn set_authority() {
+    let pause_authority = Keypair::new();
+    let TokenContext { token, .. } = setup(&pause_authority.pubkey()).await;
+
+    // success
+    let new_pause_authority = Keypair::new();
+    token
+        .set_authority(
+            token.get_address(),
+            &pause_authority.pubkey(),
+            Some(&new_pause_authority.pubkey()),
+            instruction::AuthorityType::Pause,
+            &[&pause_authority],
+        )
+        .await
+        .unwrap();
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<PausableConfig>().unwrap();
+    assert_eq!(
+        extension.authority,
+        Some(new_pause_authority.pubkey()).try_into().unwrap(),
+    );
+    token
+        .pause(&new_pause_authority.pubkey(), &[&new_pause_authority])
+        .await
+        .unwrap();
+    let err = token
+        .resume(&pause_authority.pubkey(), &[&pause_authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::OwnerMismatch as u32)
+            )
+        )))
+    );
+
+    // set to none, the mint stays paused for good
+    token
+        .set_authority(
+            token.get_address(),
+            &new_pause_authority.pubkey(),
+            None,
+            instruction::AuthorityType::Pause,
+            &[&new_pause_authority],
+        )
+        .await
+        .unwrap();
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<PausableConfig>().unwrap();
+    assert_eq!(extension.authority, None.try_into().unwrap(),);
+    assert!(bool::from(extension.paused));
+
+    let err = token
+        .resume(&new_pause_authority.pubkey(), &[&new_pause_authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::NoAuthorityExists as u32)
+            )
+        )))
+    );
+}
@@ -5,14 +5,12 @@ use {
     program_test::TestContext,
     solana_program_test::{processor, tokio, ProgramTest},
     solana_sdk::{
-        borsh0_10::try_from_slice_unchecked, program::MAX_RETURN_DATA, pubkey::Pubkey,
+        borsh1::try_from_slice_unchecked, program::MAX_RETURN_DATA, pubkey::Pubkey,
         signature::Signer, signer::keypair::Keypair, transaction::Transaction,
     },
     spl_token_2022::processor::Processor,
     spl_token_client::token::ExtensionInitializationParams,
-    spl_token_metadata_interface::{
-        borsh::BorshSerialize, instruction::emit, state::TokenMetadata,
-    },
+    spl_token_metadata_interface::{instruction::emit, state::TokenMetadata},
     std::{convert::TryInto, sync::Arc},
     test_case::test_case,
 };
@@ -113,7 +111,7 @@ async fn success(start: Option<u64>, end: Option<u64>) {
         .await
         .unwrap();
 
-    let metadata_buffer = token_metadata.try_to_vec().unwrap();
+    let metadata_buffer = borsh::to_vec(&token_metadata).unwrap();
     if let Some(check_buffer) = TokenMetadata::get_slice(&metadata_buffer, start, end) {
         if !check_buffer.is_empty() {
             // pad the data if necessary
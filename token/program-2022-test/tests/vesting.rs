@@ -0,0 +1,300 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{TestContext, TokenContext},
+    solana_program_test::tokio,
+    solana_sdk::{
+        clock::Clock, instruction::InstructionError, pubkey::Pubkey, signature::Signer,
+        signer::keypair::Keypair, transaction::TransactionError, transport::TransportError,
+    },
+    spl_token_2022::{
+        error::TokenError,
+        extension::{
+            vesting::{VestingAccount, VestingConfig},
+            BaseStateWithExtensions,
+        },
+        instruction,
+    },
+    spl_token_client::token::{ExtensionInitializationParams, TokenError as TokenClientError},
+    std::convert::TryInto,
+};
+
+fn client_error(error: TokenError) -> TokenClientError {
+    TokenClientError::Client(Box::new(TransportError::TransactionError(
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32)),
+    )))
+}
+
+async fn setup(vesting_authority: &Pubkey) -> TestContext {
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![ExtensionInitializationParams::VestingConfig {
+            authority: *vesting_authority,
+        }])
+        .await
+        .unwrap();
+    context
+}
+
+async fn setup_account(token_context: &TokenContext, amount: u64) -> Pubkey {
+    let alice_account = Keypair::new();
+    token_context
+        .token
+        .create_auxiliary_token_account(&alice_account, &token_context.alice.pubkey())
+        .await
+        .unwrap();
+    let alice_account = alice_account.pubkey();
+
+    token_context
+        .token
+        .mint_to(
+            &alice_account,
+            &token_context.mint_authority.pubkey(),
+            amount,
+            &[&token_context.mint_authority],
+        )
+        .await
+        .unwrap();
+    alice_account
+}
+
+async fn set_unix_timestamp(context: &TestContext, unix_timestamp: i64) {
+    let mut context = context.context.lock().await;
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+#[tokio::test]
+async fn success_init() {
+    let vesting_authority = Pubkey::new_unique();
+    let mut context = setup(&vesting_authority).await;
+    let TokenContext { token, alice, .. } = context.token_context.take().unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    assert!(state.base.is_initialized);
+    let extension = state.get_extension::<VestingConfig>().unwrap();
+    assert_eq!(
+        extension.authority,
+        Some(vesting_authority).try_into().unwrap()
+    );
+
+    // accounts get an empty schedule
+    let account = Keypair::new();
+    token
+        .create_auxiliary_token_account(&account, &alice.pubkey())
+        .await
+        .unwrap();
+    let state = token.get_account_info(&account.pubkey()).await.unwrap();
+    let extension = state.get_extension::<VestingAccount>().unwrap();
+    assert_eq!(*extension, VestingAccount::default());
+}
+
+#[tokio::test]
+async fn lock_and_unlock() {
+    let vesting_authority = Keypair::new();
+    let mut context = setup(&vesting_authority.pubkey()).await;
+    let token_context = context.token_context.take().unwrap();
+    let amount = 100;
+    let alice_account = setup_account(&token_context, amount).await;
+    let bob_account = Keypair::new();
+    token_context
+        .token
+        .create_auxiliary_token_account(&bob_account, &token_context.bob.pubkey())
+        .await
+        .unwrap();
+    let bob_account = bob_account.pubkey();
+    let TokenContext { token, alice, .. } = token_context;
+
+    // lock 60 tokens, releasing half of them at the cliff and the rest linearly
+    // until the end
+    let start = 1_000;
+    let cliff = 1_100;
+    let end = 1_200;
+    set_unix_timestamp(&context, start).await;
+    token
+        .set_vesting_schedule(
+            &alice_account,
+            &vesting_authority.pubkey(),
+            60,
+            start,
+            cliff,
+            end,
+            &[&vesting_authority],
+        )
+        .await
+        .unwrap();
+    let state = token.get_account_info(&alice_account).await.unwrap();
+    let extension = state.get_extension::<VestingAccount>().unwrap();
+    assert_eq!(u64::from(extension.locked_amount), 60);
+    assert_eq!(i64::from(extension.start_timestamp), start);
+    assert_eq!(i64::from(extension.cliff_timestamp), cliff);
+    assert_eq!(i64::from(extension.end_timestamp), end);
+
+    // only the unlocked balance can be transferred
+    let err = token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 41, &[&alice])
+        .await
+        .unwrap_err();
+    assert_eq!(err, client_error(TokenError::VestingBalanceLocked));
+    token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 40, &[&alice])
+        .await
+        .unwrap();
+
+    // or burned
+    let err = token
+        .burn(&alice_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap_err();
+    assert_eq!(err, client_error(TokenError::VestingBalanceLocked));
+
+    // nothing unlocks before the cliff
+    set_unix_timestamp(&context, cliff - 1).await;
+    let err = token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 2, &[&alice])
+        .await
+        .unwrap_err();
+    assert_eq!(err, client_error(TokenError::VestingBalanceLocked));
+
+    // a quarter of the locked amount is still locked halfway between the cliff
+    // and the end
+    set_unix_timestamp(&context, 1_150).await;
+    let err = token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 46, &[&alice])
+        .await
+        .unwrap_err();
+    assert_eq!(err, client_error(TokenError::VestingBalanceLocked));
+    token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 45, &[&alice])
+        .await
+        .unwrap();
+
+    // everything is unlocked at the end
+    set_unix_timestamp(&context, end).await;
+    token
+        .burn(&alice_account, &alice.pubkey(), 15, &[&alice])
+        .await
+        .unwrap();
+    let state = token.get_account_info(&alice_account).await.unwrap();
+    assert_eq!(state.base.amount, 0);
+    let state = token.get_account_info(&bob_account).await.unwrap();
+    assert_eq!(state.base.amount, 85);
+}
+
+#[tokio::test]
+async fn fail_invalid_schedule() {
+    let vesting_authority = Keypair::new();
+    let mut context = setup(&vesting_authority.pubkey()).await;
+    let token_context = context.token_context.take().unwrap();
+    let alice_account = setup_account(&token_context, 10).await;
+    let TokenContext { token, .. } = token_context;
+
+    let err = token
+        .set_vesting_schedule(
+            &alice_account,
+            &vesting_authority.pubkey(),
+            10,
+            0,
+            200,
+            100,
+            &[&vesting_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, client_error(TokenError::InvalidVestingSchedule));
+}
+
+#[tokio::test]
+async fn fail_wrong_authority() {
+    let vesting_authority = Keypair::new();
+    let mut context = setup(&vesting_authority.pubkey()).await;
+    let token_context = context.token_context.take().unwrap();
+    let alice_account = setup_account(&token_context, 10).await;
+    let TokenContext { token, alice, .. } = token_context;
+
+    // not even the owner can change the schedule
+    let err = token
+        .set_vesting_schedule(&alice_account, &alice.pubkey(), 0, 0, 0, 0, &[&alice])
+        .await
+        .unwrap_err();
+    assert_eq!(err, client_error(TokenError::OwnerMismatch));
+}
+
+#[tokio::test]
+async fn set_authority() {
+    let vesting_authority = Keypair::new();
+    let mut context = setup(&vesting_authority.pubkey()).await;
+    let token_context = context.token_context.take().unwrap();
+    let alice_account = setup_account(&token_context, 10).await;
+    let TokenContext { token, .. } = token_context;
+
+    let new_vesting_authority = Keypair::new();
+    token
+        .set_authority(
+            token.get_address(),
+            &vesting_authority.pubkey(),
+            Some(&new_vesting_authority.pubkey()),
+            instruction::AuthorityType::Vesting,
+            &[&vesting_authority],
+        )
+        .await
+        .unwrap();
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<VestingConfig>().unwrap();
+    assert_eq!(
+        extension.authority,
+        Some(new_vesting_authority.pubkey()).try_into().unwrap(),
+    );
+    token
+        .set_vesting_schedule(
+            &alice_account,
+            &new_vesting_authority.pubkey(),
+            10,
+            0,
+            0,
+            i64::MAX,
+            &[&new_vesting_authority],
+        )
+        .await
+        .unwrap();
+    let err = token
+        .set_vesting_schedule(
+            &alice_account,
+            &vesting_authority.pubkey(),
+            0,
+            0,
+            0,
+            0,
+            &[&vesting_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, client_error(TokenError::OwnerMismatch));
+
+    // set to none, schedules can no longer change
+    token
+        .set_authority(
+            token.get_address(),
+            &new_vesting_authority.pubkey(),
+            None,
+            instruction::AuthorityType::Vesting,
+            &[&new_vesting_authority],
+        )
+        .await
+        .unwrap();
+    let err = token
+        .set_vesting_schedule(
+            &alice_account,
+            &new_vesting_authority.pubkey(),
+            0,
+            0,
+            0,
+            0,
+            &[&new_vesting_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, client_error(TokenError::NoAuthorityExists));
+}
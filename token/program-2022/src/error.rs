@@ -243,6 +243,22 @@ pub enum TokenError {
     /// Ciphertext arithmetic failed
     #[error("Ciphertext arithmetic failed")]
     CiphertextArithmeticFailed,
+    /// Multiplier for scaled UI amounts must be positive and finite
+    #[error("Multiplier for scaled UI amounts must be positive and finite")]
+    InvalidScale,
+    /// Transferring, minting, and burning is paused on this mint
+    #[error("Transferring, minting, and burning is paused on this mint")]
+    MintPaused,
+    /// Public minting, burning, deposits and withdrawals are disabled on a
+    /// mint with confidential mint-burn
+    #[error("Public minting, burning, deposits and withdrawals are disabled on a mint with confidential mint-burn")]
+    IllegalMintBurnConversion,
+    /// Amount exceeds the unlocked balance of a vesting account
+    #[error("Amount exceeds the unlocked balance of a vesting account")]
+    VestingBalanceLocked,
+    /// Vesting schedule cliff must be between its start and end
+    #[error("Vesting schedule cliff must be between its start and end")]
+    InvalidVestingSchedule,
 }
 impl From<TokenError> for ProgramError {
     fn from(e: TokenError) -> Self {
@@ -418,6 +434,21 @@ impl PrintProgramError for TokenError {
             TokenError::CiphertextArithmeticFailed => {
                 msg!("Ciphertext arithmetic failed")
             }
+            TokenError::InvalidScale => {
+                msg!("Multiplier for scaled UI amounts must be positive and finite")
+            }
+            TokenError::MintPaused => {
+                msg!("Transferring, minting, and burning is paused on this mint")
+            }
+            TokenError::IllegalMintBurnConversion => {
+                msg!("Public minting, burning, deposits and withdrawals are disabled on a mint with confidential mint-burn")
+            }
+            TokenError::VestingBalanceLocked => {
+                msg!("Amount exceeds the unlocked balance of a vesting account")
+            }
+            TokenError::InvalidVestingSchedule => {
+                msg!("Vesting schedule cliff must be between its start and end")
+            }
         }
     }
 }
@@ -0,0 +1,322 @@
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::{aeciphertext_fromstr, elgamalpubkey_fromstr},
+    serde::{Deserialize, Serialize},
+};
+use {
+    crate::{
+        check_program_account,
+        extension::confidential_transfer::{
+            ciphertext_extraction::SourceDecryptHandles, DecryptableBalance,
+        },
+        instruction::{encode_instruction, TokenInstruction},
+    },
+    bytemuck::{Pod, Zeroable},
+    num_enum::{IntoPrimitive, TryFromPrimitive},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    solana_zk_token_sdk::zk_token_elgamal::pod::ElGamalPubkey,
+};
+
+/// Confidential Mint-Burn extension instructions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum ConfidentialMintBurnInstruction {
+    /// Initializes confidential mints and burns for a mint.
+    ///
+    /// The `ConfidentialMintBurnInstruction::InitializeMint` instruction
+    /// requires no signers and MUST be included within the same Transaction
+    /// as `TokenInstruction::InitializeMint`. Otherwise another party can
+    /// initialize the configuration.
+    ///
+    /// The mint must also be initialized with the `ConfidentialTransferMint`
+    /// extension.
+    ///
+    /// The instruction fails if the `TokenInstruction::InitializeMint`
+    /// instruction has already executed for the mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    ///
+    /// Data expected by this instruction:
+    ///   `InitializeMintData`
+    InitializeMint,
+
+    /// Updates the decryptable supply of the mint.
+    ///
+    /// Confidential burns do not update the decryptable supply, so the mint
+    /// authority should decrypt the confidential supply with the supply
+    /// ElGamal secret key and store the result with this instruction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[signer]` The mint authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[]` The multisig mint authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    ///
+    /// Data expected by this instruction:
+    ///   `UpdateDecryptableSupplyData`
+    UpdateDecryptableSupply,
+
+    /// Mints tokens confidentially into the pending balance of a token account.
+    ///
+    /// The mint amount is encrypted under the ElGamal public keys of the
+    /// destination account and of the supply auditor. The pending balance of
+    /// the destination and the confidential supply of the mint are credited
+    /// with the respective ciphertexts.
+    ///
+    /// The zero-knowledge proofs must be pre-verified into context state
+    /// accounts:
+    ///   * `BatchedGroupedCiphertext2HandlesValidity` for the destination and
+    ///     supply ElGamal public keys
+    ///   * `BatchedRangeProofU64` for the low 16 and high 32 bits of the mint
+    ///     amount
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The SPL Token account to mint to.
+    ///   1. `[writable]` The SPL Token mint.
+    ///   2. `[]` Context state account for the ciphertext validity proof.
+    ///   3. `[]` Context state account for the range proof.
+    ///   4. `[signer]` The mint authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The SPL Token account to mint to.
+    ///   1. `[writable]` The SPL Token mint.
+    ///   2. `[]` Context state account for the ciphertext validity proof.
+    ///   3. `[]` Context state account for the range proof.
+    ///   4. `[]` The multisig mint authority.
+    ///   5. ..5+M `[signer]` M signer accounts.
+    ///
+    /// Data expected by this instruction:
+    ///   `MintInstructionData`
+    Mint,
+
+    /// Burns tokens confidentially from the available balance of a token
+    /// account.
+    ///
+    /// The burn amount is encrypted under the ElGamal public keys of the
+    /// source account, the supply auditor and the auditor of the
+    /// `ConfidentialTransferMint` extension. The available balance of the
+    /// source and the confidential supply of the mint are debited with the
+    /// respective ciphertexts.
+    ///
+    /// The zero-knowledge proofs are the same as those of a split-proof
+    /// confidential transfer, with the supply ElGamal public key taking the
+    /// place of the destination public key. They must be pre-verified into
+    /// context state accounts:
+    ///   * `CiphertextCommitmentEquality` for the new source available balance
+    ///   * `BatchedGroupedCiphertext2HandlesValidity` for the supply and
+    ///     auditor ElGamal public keys
+    ///   * `BatchedRangeProofU128` for the new source available balance and the
+    ///     low 16 and high 32 bits of the burn amount
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The SPL Token account to burn from.
+    ///   1. `[writable]` The SPL Token mint.
+    ///   2. `[]` Context state account for the equality proof.
+    ///   3. `[]` Context state account for the ciphertext validity proof.
+    ///   4. `[]` Context state account for the range proof.
+    ///   5. `[signer]` The single account owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The SPL Token account to burn from.
+    ///   1. `[writable]` The SPL Token mint.
+    ///   2. `[]` Context state account for the equality proof.
+    ///   3. `[]` Context state account for the ciphertext validity proof.
+    ///   4. `[]` Context state account for the range proof.
+    ///   5. `[]` The multisig account owner.
+    ///   6. ..6+M `[signer]` M signer accounts.
+    ///
+    /// Data expected by this instruction:
+    ///   `BurnInstructionData`
+    Burn,
+}
+
+/// Data expected by `ConfidentialMintBurnInstruction::InitializeMint`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct InitializeMintData {
+    /// The ElGamal public key of the supply auditor
+    #[cfg_attr(feature = "serde-traits", serde(with = "elgamalpubkey_fromstr"))]
+    pub supply_elgamal_pubkey: ElGamalPubkey,
+    /// The initial decryptable supply (always 0)
+    #[cfg_attr(feature = "serde-traits", serde(with = "aeciphertext_fromstr"))]
+    pub decryptable_supply: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialMintBurnInstruction::UpdateDecryptableSupply`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct UpdateDecryptableSupplyData {
+    /// The new decryptable supply
+    #[cfg_attr(feature = "serde-traits", serde(with = "aeciphertext_fromstr"))]
+    pub new_decryptable_supply: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialMintBurnInstruction::Mint`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct MintInstructionData {
+    /// The new decryptable supply if the mint succeeds
+    #[cfg_attr(feature = "serde-traits", serde(with = "aeciphertext_fromstr"))]
+    pub new_decryptable_supply: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialMintBurnInstruction::Burn`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct BurnInstructionData {
+    /// The new source decryptable balance if the burn succeeds
+    #[cfg_attr(feature = "serde-traits", serde(with = "aeciphertext_fromstr"))]
+    pub new_decryptable_available_balance: DecryptableBalance,
+    /// The ElGamal decryption handles pertaining to the low and high bits of
+    /// the burn amount with respect to the source public key.
+    pub source_decrypt_handles: SourceDecryptHandles,
+}
+
+/// Create a `InitializeMint` instruction
+pub fn initialize_mint(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    supply_elgamal_pubkey: ElGamalPubkey,
+    decryptable_supply: DecryptableBalance,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let accounts = vec![AccountMeta::new(*mint, false)];
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialMintBurnExtension,
+        ConfidentialMintBurnInstruction::InitializeMint,
+        &InitializeMintData {
+            supply_elgamal_pubkey,
+            decryptable_supply,
+        },
+    ))
+}
+
+/// Create a `UpdateDecryptableSupply` instruction
+pub fn update_decryptable_supply(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    new_decryptable_supply: DecryptableBalance,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialMintBurnExtension,
+        ConfidentialMintBurnInstruction::UpdateDecryptableSupply,
+        &UpdateDecryptableSupplyData {
+            new_decryptable_supply,
+        },
+    ))
+}
+
+/// Create a `Mint` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn confidential_mint(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    new_decryptable_supply: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    ciphertext_validity_proof_context: &Pubkey,
+    range_proof_context: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(*ciphertext_validity_proof_context, false),
+        AccountMeta::new_readonly(*range_proof_context, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialMintBurnExtension,
+        ConfidentialMintBurnInstruction::Mint,
+        &MintInstructionData {
+            new_decryptable_supply,
+        },
+    ))
+}
+
+/// Create a `Burn` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn confidential_burn(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    new_decryptable_available_balance: DecryptableBalance,
+    source_decrypt_handles: &SourceDecryptHandles,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    equality_proof_context: &Pubkey,
+    ciphertext_validity_proof_context: &Pubkey,
+    range_proof_context: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(*equality_proof_context, false),
+        AccountMeta::new_readonly(*ciphertext_validity_proof_context, false),
+        AccountMeta::new_readonly(*range_proof_context, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialMintBurnExtension,
+        ConfidentialMintBurnInstruction::Burn,
+        &BurnInstructionData {
+            new_decryptable_available_balance,
+            source_decrypt_handles: *source_decrypt_handles,
+        },
+    ))
+}
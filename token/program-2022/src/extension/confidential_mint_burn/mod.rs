@@ -0,0 +1,41 @@
+use {
+    crate::extension::{
+        confidential_transfer::{DecryptableBalance, EncryptedBalance},
+        Extension, ExtensionType,
+    },
+    bytemuck::{Pod, Zeroable},
+    solana_zk_token_sdk::zk_token_elgamal::pod::ElGamalPubkey,
+};
+
+/// Confidential Mint-Burn Extension instructions
+pub mod instruction;
+
+/// Confidential Mint-Burn Extension processor
+pub mod processor;
+
+/// Helper functions to verify zero-knowledge proofs in the Confidential
+/// Mint-Burn Extension
+pub mod verify_proof;
+
+/// Confidential mint-burn mint configuration
+///
+/// Tokens of a mint with this extension are only ever minted into and burned
+/// from the encrypted balances of the `ConfidentialTransferAccount` extension,
+/// so the public supply of the mint stays at zero. The total supply is instead
+/// kept encrypted under the supply ElGamal public key.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialMintBurn {
+    /// The total supply of the mint, encrypted under `supply_elgamal_pubkey`
+    pub confidential_supply: EncryptedBalance,
+    /// The decryptable total supply of the mint, kept up to date by the mint
+    /// authority
+    pub decryptable_supply: DecryptableBalance,
+    /// The ElGamal public key of the supply auditor, who can decrypt
+    /// `confidential_supply` and the amount of every mint and burn
+    pub supply_elgamal_pubkey: ElGamalPubkey,
+}
+
+impl Extension for ConfidentialMintBurn {
+    const TYPE: ExtensionType = ExtensionType::ConfidentialMintBurn;
+}
@@ -0,0 +1,348 @@
+use {
+    crate::{
+        check_program_account,
+        error::TokenError,
+        extension::{
+            confidential_mint_burn::{instruction::*, ConfidentialMintBurn},
+            confidential_transfer::{DecryptableBalance, EncryptedBalance},
+            StateWithExtensionsMut,
+        },
+        instruction::{decode_instruction_data, decode_instruction_type},
+        processor::Processor,
+        state::Mint,
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        program_option::COption,
+        pubkey::Pubkey,
+    },
+    solana_zk_token_sdk::zk_token_elgamal::pod::ElGamalPubkey,
+};
+// Remove feature once zk ops syscalls are enabled on all networks
+#[cfg(feature = "zk-ops")]
+use {
+    crate::{
+        extension::{
+            confidential_mint_burn::verify_proof::{verify_burn_proof, verify_mint_proof},
+            confidential_transfer::{
+                ciphertext_extraction::{
+                    transfer_amount_destination_ciphertext, transfer_amount_source_ciphertext,
+                    SourceDecryptHandles,
+                },
+                ConfidentialTransferAccount, ConfidentialTransferMint,
+            },
+            pausable::check_not_paused,
+            BaseStateWithExtensions,
+        },
+        state::Account,
+    },
+    solana_zk_token_sdk::zk_token_elgamal::ops as syscall,
+};
+
+/// Processes an [InitializeMint] instruction.
+fn process_initialize_mint(
+    accounts: &[AccountInfo],
+    supply_elgamal_pubkey: &ElGamalPubkey,
+    decryptable_supply: &DecryptableBalance,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_info = next_account_info(account_info_iter)?;
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(mint_data)?;
+    let confidential_mint_burn = mint.init_extension::<ConfidentialMintBurn>(true)?;
+
+    confidential_mint_burn.confidential_supply = EncryptedBalance::zeroed();
+    confidential_mint_burn.decryptable_supply = *decryptable_supply;
+    confidential_mint_burn.supply_elgamal_pubkey = *supply_elgamal_pubkey;
+
+    Ok(())
+}
+
+/// Returns the mint authority, which is also the authority for confidential
+/// mints
+fn get_mint_authority(mint: &StateWithExtensionsMut<Mint>) -> Result<Pubkey, ProgramError> {
+    match mint.base.mint_authority {
+        COption::Some(mint_authority) => Ok(mint_authority),
+        COption::None => Err(TokenError::FixedSupply.into()),
+    }
+}
+
+/// Processes an [UpdateDecryptableSupply] instruction.
+fn process_update_decryptable_supply(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_decryptable_supply: &DecryptableBalance,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack(mint_data)?;
+
+    let mint_authority = get_mint_authority(&mint)?;
+    Processor::validate_owner(
+        program_id,
+        &mint_authority,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    let confidential_mint_burn = mint.get_extension_mut::<ConfidentialMintBurn>()?;
+    confidential_mint_burn.decryptable_supply = *new_decryptable_supply;
+
+    Ok(())
+}
+
+/// Processes a [Mint] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_decryptable_supply: &DecryptableBalance,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    // zero-knowledge proofs certify that the mint amount is a 48-bit number
+    // encrypted under the destination and supply ElGamal public keys
+    let proof_context = verify_mint_proof(account_info_iter)?;
+
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack(mint_data)?;
+
+    check_not_paused(&mint)?;
+
+    let mint_authority = get_mint_authority(&mint)?;
+    Processor::validate_owner(
+        program_id,
+        &mint_authority,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    let confidential_mint_burn = mint.get_extension_mut::<ConfidentialMintBurn>()?;
+
+    // Check that the supply ElGamal public key is consistent with what was
+    // actually used to generate the zkp.
+    if confidential_mint_burn.supply_elgamal_pubkey != proof_context.supply_pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    let (supply_ciphertext_lo, supply_ciphertext_hi) = proof_context.supply_ciphertexts();
+    confidential_mint_burn.confidential_supply = syscall::add_with_lo_hi(
+        &confidential_mint_burn.confidential_supply,
+        &supply_ciphertext_lo,
+        &supply_ciphertext_hi,
+    )
+    .ok_or(TokenError::CiphertextArithmeticFailed)?;
+    confidential_mint_burn.decryptable_supply = *new_decryptable_supply;
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    if token_account.base.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+
+    let confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    confidential_transfer_account.valid_as_destination()?;
+
+    // Check that the destination ElGamal public key is consistent with what was
+    // actually used to generate the zkp.
+    if confidential_transfer_account.elgamal_pubkey != proof_context.destination_pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    let (destination_ciphertext_lo, destination_ciphertext_hi) =
+        proof_context.destination_ciphertexts();
+    confidential_transfer_account.pending_balance_lo = syscall::add(
+        &confidential_transfer_account.pending_balance_lo,
+        &destination_ciphertext_lo,
+    )
+    .ok_or(TokenError::CiphertextArithmeticFailed)?;
+    confidential_transfer_account.pending_balance_hi = syscall::add(
+        &confidential_transfer_account.pending_balance_hi,
+        &destination_ciphertext_hi,
+    )
+    .ok_or(TokenError::CiphertextArithmeticFailed)?;
+
+    confidential_transfer_account.increment_pending_balance_credit_counter()?;
+
+    Ok(())
+}
+
+/// Processes a [Burn] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_burn(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_decryptable_available_balance: &DecryptableBalance,
+    source_decrypt_handles: &SourceDecryptHandles,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    // zero-knowledge proofs certify that the burn amount is a 48-bit number
+    // encrypted under the source, supply and auditor ElGamal public keys, and
+    // that the source account has enough balance to burn the amount
+    let proof_context = verify_burn_proof(account_info_iter, source_decrypt_handles)?;
+
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack(mint_data)?;
+
+    check_not_paused(&mint)?;
+
+    // Check that the auditor ElGamal public key associated with the confidential
+    // mint is consistent with what was actually used to generate the zkp.
+    let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+    if !confidential_transfer_mint
+        .auditor_elgamal_pubkey
+        .equals(&proof_context.transfer_pubkeys.auditor)
+    {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    let confidential_mint_burn = mint.get_extension_mut::<ConfidentialMintBurn>()?;
+
+    // The burn amount is proven as a transfer to the supply ElGamal public key
+    if confidential_mint_burn.supply_elgamal_pubkey != proof_context.transfer_pubkeys.destination {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    let supply_ciphertext_lo = transfer_amount_destination_ciphertext(&proof_context.ciphertext_lo);
+    let supply_ciphertext_hi = transfer_amount_destination_ciphertext(&proof_context.ciphertext_hi);
+    confidential_mint_burn.confidential_supply = syscall::subtract_with_lo_hi(
+        &confidential_mint_burn.confidential_supply,
+        &supply_ciphertext_lo,
+        &supply_ciphertext_hi,
+    )
+    .ok_or(TokenError::CiphertextArithmeticFailed)?;
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    if token_account.base.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+
+    let confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    confidential_transfer_account.valid_as_source()?;
+
+    // Check that the source ElGamal public key is consistent with what was
+    // actually used to generate the zkp.
+    if proof_context.transfer_pubkeys.source != confidential_transfer_account.elgamal_pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    let source_ciphertext_lo = transfer_amount_source_ciphertext(&proof_context.ciphertext_lo);
+    let source_ciphertext_hi = transfer_amount_source_ciphertext(&proof_context.ciphertext_hi);
+    let new_available_balance = syscall::subtract_with_lo_hi(
+        &confidential_transfer_account.available_balance,
+        &source_ciphertext_lo,
+        &source_ciphertext_hi,
+    )
+    .ok_or(TokenError::CiphertextArithmeticFailed)?;
+
+    // Check that the computed available balance is consistent with what was
+    // actually used to generate the zkp on the client side.
+    if new_available_balance != proof_context.new_source_ciphertext {
+        return Err(TokenError::ConfidentialTransferBalanceMismatch.into());
+    }
+
+    confidential_transfer_account.available_balance = new_available_balance;
+    confidential_transfer_account.decryptable_available_balance =
+        *new_decryptable_available_balance;
+
+    Ok(())
+}
+
+pub(crate) fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    match decode_instruction_type(input)? {
+        ConfidentialMintBurnInstruction::InitializeMint => {
+            msg!("ConfidentialMintBurnInstruction::InitializeMint");
+            let data = decode_instruction_data::<InitializeMintData>(input)?;
+            process_initialize_mint(
+                accounts,
+                &data.supply_elgamal_pubkey,
+                &data.decryptable_supply,
+            )
+        }
+        ConfidentialMintBurnInstruction::UpdateDecryptableSupply => {
+            msg!("ConfidentialMintBurnInstruction::UpdateDecryptableSupply");
+            let data = decode_instruction_data::<UpdateDecryptableSupplyData>(input)?;
+            process_update_decryptable_supply(program_id, accounts, &data.new_decryptable_supply)
+        }
+        ConfidentialMintBurnInstruction::Mint => {
+            msg!("ConfidentialMintBurnInstruction::Mint");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_instruction_data::<MintInstructionData>(input)?;
+                process_mint(program_id, accounts, &data.new_decryptable_supply)
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            Err(ProgramError::InvalidInstructionData)
+        }
+        ConfidentialMintBurnInstruction::Burn => {
+            msg!("ConfidentialMintBurnInstruction::Burn");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_instruction_data::<BurnInstructionData>(input)?;
+                process_burn(
+                    program_id,
+                    accounts,
+                    &data.new_decryptable_available_balance,
+                    &data.source_decrypt_handles,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            Err(ProgramError::InvalidInstructionData)
+        }
+    }
+}
@@ -0,0 +1,215 @@
+#[cfg(feature = "zk-ops")]
+use {
+    crate::{
+        check_zk_token_proof_program_account,
+        extension::confidential_transfer::{
+            ciphertext_extraction::{
+                extract_commitment_from_grouped_ciphertext, SourceDecryptHandles,
+                TransferProofContextInfo,
+            },
+            instruction::{
+                BatchedGroupedCiphertext2HandlesValidityProofContext, BatchedRangeProofContext,
+                ProofContextState, ProofType,
+            },
+            verify_proof::{
+                verify_ciphertext_validity_proof, verify_equality_proof,
+                verify_transfer_range_proof,
+            },
+        },
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        program_error::ProgramError,
+    },
+    solana_zk_token_sdk::zk_token_elgamal::pod::{
+        ElGamalCiphertext, ElGamalPubkey, GroupedElGamalCiphertext2Handles,
+    },
+    spl_pod::bytemuck::pod_from_bytes,
+    std::slice::Iter,
+};
+
+/// The proof context information needed to process a [Mint] instruction.
+#[cfg(feature = "zk-ops")]
+pub struct MintProofContextInfo {
+    /// ElGamal public key of the destination account
+    pub destination_pubkey: ElGamalPubkey,
+    /// ElGamal public key of the supply auditor
+    pub supply_pubkey: ElGamalPubkey,
+    /// Ciphertext containing the low 16 bits of the mint amount
+    pub ciphertext_lo: GroupedElGamalCiphertext2Handles,
+    /// Ciphertext containing the high 32 bits of the mint amount
+    pub ciphertext_hi: GroupedElGamalCiphertext2Handles,
+}
+
+#[cfg(feature = "zk-ops")]
+impl MintProofContextInfo {
+    /// Create the proof context information needed to process a [Mint]
+    /// instruction from split proof contexts after verifying their
+    /// consistency.
+    pub fn verify_and_extract(
+        ciphertext_validity_proof_context: &BatchedGroupedCiphertext2HandlesValidityProofContext,
+        range_proof_context: &BatchedRangeProofContext,
+    ) -> Result<Self, ProgramError> {
+        // The ciphertext validity proof context consists of the destination and
+        // supply ElGamal public keys and the mint amount ciphertexts. The
+        // commitments of the ciphertexts must be checked with the range proof.
+        let BatchedGroupedCiphertext2HandlesValidityProofContext {
+            destination_pubkey,
+            auditor_pubkey: supply_pubkey,
+            grouped_ciphertext_lo: mint_amount_ciphertext_lo,
+            grouped_ciphertext_hi: mint_amount_ciphertext_hi,
+        } = ciphertext_validity_proof_context;
+
+        let BatchedRangeProofContext {
+            commitments: range_proof_commitments,
+            bit_lengths: range_proof_bit_lengths,
+        } = range_proof_context;
+
+        // check that the range proof was created for the correct set of Pedersen
+        // commitments
+        let expected_commitments = [
+            extract_commitment_from_grouped_ciphertext(mint_amount_ciphertext_lo),
+            extract_commitment_from_grouped_ciphertext(mint_amount_ciphertext_hi),
+            // the third dummy commitment can be any commitment
+        ];
+
+        if !range_proof_commitments
+            .iter()
+            .zip(expected_commitments.iter())
+            .all(|(proof_commitment, expected_commitment)| proof_commitment == expected_commitment)
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // check that the range proof was created for the correct number of bits
+        const MINT_AMOUNT_LO_BIT_LENGTH: u8 = 16;
+        const MINT_AMOUNT_HI_BIT_LENGTH: u8 = 32;
+        const PADDING_BIT_LENGTH: u8 = 16;
+        let expected_bit_lengths = [
+            MINT_AMOUNT_LO_BIT_LENGTH,
+            MINT_AMOUNT_HI_BIT_LENGTH,
+            PADDING_BIT_LENGTH,
+        ]
+        .iter();
+
+        if !range_proof_bit_lengths
+            .iter()
+            .zip(expected_bit_lengths)
+            .all(|(proof_len, expected_len)| proof_len == expected_len)
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            destination_pubkey: *destination_pubkey,
+            supply_pubkey: *supply_pubkey,
+            ciphertext_lo: *mint_amount_ciphertext_lo,
+            ciphertext_hi: *mint_amount_ciphertext_hi,
+        })
+    }
+
+    /// The low and high mint amount ciphertexts under the destination ElGamal
+    /// public key
+    pub fn destination_ciphertexts(&self) -> (ElGamalCiphertext, ElGamalCiphertext) {
+        (
+            grouped_ciphertext_component(&self.ciphertext_lo, 0),
+            grouped_ciphertext_component(&self.ciphertext_hi, 0),
+        )
+    }
+
+    /// The low and high mint amount ciphertexts under the supply ElGamal
+    /// public key
+    pub fn supply_ciphertexts(&self) -> (ElGamalCiphertext, ElGamalCiphertext) {
+        (
+            grouped_ciphertext_component(&self.ciphertext_lo, 1),
+            grouped_ciphertext_component(&self.ciphertext_hi, 1),
+        )
+    }
+}
+
+/// Extract the ElGamal ciphertext for the public key at `index` from a grouped
+/// ciphertext with 2 handles.
+///
+/// A grouped ciphertext with 2 handles consists of the 32-byte `commitment`
+/// component followed by the two 32-byte `decryption handle` components.
+#[cfg(feature = "zk-ops")]
+fn grouped_ciphertext_component(
+    grouped_ciphertext: &GroupedElGamalCiphertext2Handles,
+    index: usize,
+) -> ElGamalCiphertext {
+    let grouped_ciphertext_bytes = bytemuck::bytes_of(grouped_ciphertext);
+    let handle_start = 32 + index * 32;
+
+    let mut ciphertext_bytes = [0u8; 64];
+    ciphertext_bytes[..32].copy_from_slice(&grouped_ciphertext_bytes[..32]);
+    ciphertext_bytes[32..]
+        .copy_from_slice(&grouped_ciphertext_bytes[handle_start..handle_start + 32]);
+
+    ElGamalCiphertext(ciphertext_bytes)
+}
+
+/// Verify zero-knowledge proofs needed for a [Mint] instruction and return the
+/// corresponding proof context information.
+#[cfg(feature = "zk-ops")]
+pub fn verify_mint_proof(
+    account_info_iter: &mut Iter<'_, AccountInfo<'_>>,
+) -> Result<MintProofContextInfo, ProgramError> {
+    let ciphertext_validity_proof_context_state_account_info =
+        next_account_info(account_info_iter)?;
+    let range_proof_context_state_account_info = next_account_info(account_info_iter)?;
+
+    let ciphertext_validity_proof_context =
+        verify_ciphertext_validity_proof(ciphertext_validity_proof_context_state_account_info)?;
+    let range_proof_context = verify_mint_range_proof(range_proof_context_state_account_info)?;
+
+    MintProofContextInfo::verify_and_extract(
+        &ciphertext_validity_proof_context,
+        &range_proof_context,
+    )
+}
+
+/// Verify zero-knowledge proofs needed for a [Burn] instruction and return the
+/// corresponding proof context information.
+///
+/// A burn is proven like a confidential transfer to the supply auditor, so the
+/// destination public key of the returned context is the supply ElGamal public
+/// key.
+#[cfg(feature = "zk-ops")]
+pub fn verify_burn_proof(
+    account_info_iter: &mut Iter<'_, AccountInfo<'_>>,
+    source_decrypt_handles: &SourceDecryptHandles,
+) -> Result<TransferProofContextInfo, ProgramError> {
+    let equality_proof_context_state_account_info = next_account_info(account_info_iter)?;
+    let ciphertext_validity_proof_context_state_account_info =
+        next_account_info(account_info_iter)?;
+    let range_proof_context_state_account_info = next_account_info(account_info_iter)?;
+
+    let equality_proof_context = verify_equality_proof(equality_proof_context_state_account_info)?;
+    let ciphertext_validity_proof_context =
+        verify_ciphertext_validity_proof(ciphertext_validity_proof_context_state_account_info)?;
+    let range_proof_context = verify_transfer_range_proof(range_proof_context_state_account_info)?;
+
+    TransferProofContextInfo::verify_and_extract(
+        &equality_proof_context,
+        &ciphertext_validity_proof_context,
+        &range_proof_context,
+        source_decrypt_handles,
+    )
+}
+
+/// Verify and process range proof for [Mint] instruction.
+#[cfg(feature = "zk-ops")]
+fn verify_mint_range_proof(
+    account_info: &AccountInfo<'_>,
+) -> Result<BatchedRangeProofContext, ProgramError> {
+    check_zk_token_proof_program_account(account_info.owner)?;
+    let context_state_account_data = account_info.data.borrow();
+    let range_proof_context_state =
+        pod_from_bytes::<ProofContextState<BatchedRangeProofContext>>(&context_state_account_data)?;
+
+    if range_proof_context_state.proof_type != ProofType::BatchedRangeProofU64.into() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(range_proof_context_state.proof_context)
+}
@@ -1,7 +1,10 @@
 // Remove feature once zk ops syscalls are enabled on all networks
 #[cfg(feature = "zk-ops")]
 use {
-    crate::extension::non_transferable::NonTransferable,
+    crate::extension::{
+        confidential_mint_burn::ConfidentialMintBurn, non_transferable::NonTransferable,
+        pausable::check_not_paused, vesting::check_unlocked_amount,
+    },
     solana_zk_token_sdk::zk_token_elgamal::ops as syscall,
 };
 use {
@@ -269,6 +272,13 @@ fn process_deposit(
     if mint.get_extension::<NonTransferable>().is_ok() {
         return Err(TokenError::NonTransferable.into());
     }
+    check_not_paused(&mint)?;
+
+    // The public and confidential balances of a confidential mint-burn mint
+    // cannot be converted into each other
+    if mint.get_extension::<ConfidentialMintBurn>().is_ok() {
+        return Err(TokenError::IllegalMintBurnConversion.into());
+    }
 
     check_program_account(token_account_info.owner)?;
     let token_account_data = &mut token_account_info.data.borrow_mut();
@@ -290,6 +300,10 @@ fn process_deposit(
         return Err(TokenError::MintMismatch.into());
     }
 
+    // Locked tokens must stay in the public balance, where the vesting
+    // schedule can be enforced
+    check_unlocked_amount(&token_account, amount)?;
+
     // Wrapped SOL deposits are not supported because lamports cannot be vanished.
     assert!(!token_account.base.is_native());
 
@@ -369,6 +383,13 @@ fn process_withdraw(
     if mint.get_extension::<NonTransferable>().is_ok() {
         return Err(TokenError::NonTransferable.into());
     }
+    check_not_paused(&mint)?;
+
+    // The public and confidential balances of a confidential mint-burn mint
+    // cannot be converted into each other
+    if mint.get_extension::<ConfidentialMintBurn>().is_ok() {
+        return Err(TokenError::IllegalMintBurnConversion.into());
+    }
 
     check_program_account(token_account_info.owner)?;
     let token_account_data = &mut token_account_info.data.borrow_mut();
@@ -454,6 +475,7 @@ fn process_transfer(
     if mint.get_extension::<NonTransferable>().is_ok() {
         return Err(TokenError::NonTransferable.into());
     }
+    check_not_paused(&mint)?;
     let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
 
     // A `Transfer` instruction must be accompanied by a zero-knowledge proof
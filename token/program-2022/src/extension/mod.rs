@@ -6,6 +6,7 @@ use {
     crate::{
         error::TokenError,
         extension::{
+            confidential_mint_burn::ConfidentialMintBurn,
             confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
             confidential_transfer_fee::{
                 ConfidentialTransferFeeAmount, ConfidentialTransferFeeConfig,
@@ -20,9 +21,12 @@ use {
             metadata_pointer::MetadataPointer,
             mint_close_authority::MintCloseAuthority,
             non_transferable::{NonTransferable, NonTransferableAccount},
+            pausable::{PausableAccount, PausableConfig},
             permanent_delegate::PermanentDelegate,
+            scaled_ui_amount::ScaledUiAmountConfig,
             transfer_fee::{TransferFeeAmount, TransferFeeConfig},
             transfer_hook::{TransferHook, TransferHookAccount},
+            vesting::{VestingAccount, VestingConfig},
         },
         state::{Account, Mint, Multisig},
     },
@@ -46,6 +50,8 @@ use {
     },
 };
 
+/// Confidential Mint-Burn extension
+pub mod confidential_mint_burn;
 /// Confidential Transfer extension
 pub mod confidential_transfer;
 /// Confidential Transfer Fee extension
@@ -70,10 +76,14 @@ pub mod metadata_pointer;
 pub mod mint_close_authority;
 /// Non Transferable extension
 pub mod non_transferable;
+/// Pausable extension
+pub mod pausable;
 /// Permanent Delegate extension
 pub mod permanent_delegate;
 /// Utility to reallocate token accounts
 pub mod reallocate;
+/// Scaled UI Amount extension
+pub mod scaled_ui_amount;
 /// Token-group extension
 pub mod token_group;
 /// Token-metadata extension
@@ -82,6 +92,8 @@ pub mod token_metadata;
 pub mod transfer_fee;
 /// Transfer Hook extension
 pub mod transfer_hook;
+/// Vesting extension
+pub mod vesting;
 
 /// Length in TLV structure
 #[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
@@ -818,6 +830,12 @@ impl<'data, S: BaseState> StateWithExtensionsMut<'data, S> {
             ExtensionType::TransferHookAccount => {
                 self.init_extension::<TransferHookAccount>(true).map(|_| ())
             }
+            ExtensionType::PausableAccount => {
+                self.init_extension::<PausableAccount>(true).map(|_| ())
+            }
+            ExtensionType::VestingAccount => {
+                self.init_extension::<VestingAccount>(true).map(|_| ())
+            }
             // ConfidentialTransfers are currently opt-in only, so this is a no-op for extra safety
             // on InitializeAccount
             ExtensionType::ConfidentialTransferAccount => Ok(()),
@@ -961,6 +979,18 @@ pub enum ExtensionType {
     GroupMemberPointer,
     /// Mint contains token group member configurations
     TokenGroupMember,
+    /// Mint scales the UI amounts of its tokens by a multiplier
+    ScaledUiAmount,
+    /// Mint can pause minting, burning and transferring
+    PausableConfig,
+    /// Indicates that the account belongs to a pausable mint
+    PausableAccount,
+    /// Tokens are minted and burned confidentially, with an encrypted supply
+    ConfidentialMintBurn,
+    /// Mint can lock part of the balance of its accounts on a vesting schedule
+    VestingConfig,
+    /// Vesting schedule locking part of the balance of the account
+    VestingAccount,
     /// Test variable-length mint extension
     #[cfg(test)]
     VariableLenMintTest = u16::MAX - 2,
@@ -1041,6 +1071,12 @@ impl ExtensionType {
             ExtensionType::TokenGroup => pod_get_packed_len::<TokenGroup>(),
             ExtensionType::GroupMemberPointer => pod_get_packed_len::<GroupMemberPointer>(),
             ExtensionType::TokenGroupMember => pod_get_packed_len::<TokenGroupMember>(),
+            ExtensionType::ScaledUiAmount => pod_get_packed_len::<ScaledUiAmountConfig>(),
+            ExtensionType::PausableConfig => pod_get_packed_len::<PausableConfig>(),
+            ExtensionType::PausableAccount => pod_get_packed_len::<PausableAccount>(),
+            ExtensionType::ConfidentialMintBurn => pod_get_packed_len::<ConfidentialMintBurn>(),
+            ExtensionType::VestingConfig => pod_get_packed_len::<VestingConfig>(),
+            ExtensionType::VestingAccount => pod_get_packed_len::<VestingAccount>(),
             #[cfg(test)]
             ExtensionType::AccountPaddingTest => pod_get_packed_len::<AccountPaddingTest>(),
             #[cfg(test)]
@@ -1104,7 +1140,11 @@ impl ExtensionType {
             | ExtensionType::GroupPointer
             | ExtensionType::TokenGroup
             | ExtensionType::GroupMemberPointer
-            | ExtensionType::TokenGroupMember => AccountType::Mint,
+            | ExtensionType::TokenGroupMember
+            | ExtensionType::ScaledUiAmount
+            | ExtensionType::PausableConfig
+            | ExtensionType::ConfidentialMintBurn
+            | ExtensionType::VestingConfig => AccountType::Mint,
             ExtensionType::ImmutableOwner
             | ExtensionType::TransferFeeAmount
             | ExtensionType::ConfidentialTransferAccount
@@ -1112,7 +1152,9 @@ impl ExtensionType {
             | ExtensionType::NonTransferableAccount
             | ExtensionType::TransferHookAccount
             | ExtensionType::CpiGuard
-            | ExtensionType::ConfidentialTransferFeeAmount => AccountType::Account,
+            | ExtensionType::ConfidentialTransferFeeAmount
+            | ExtensionType::PausableAccount
+            | ExtensionType::VestingAccount => AccountType::Account,
             #[cfg(test)]
             ExtensionType::VariableLenMintTest => AccountType::Mint,
             #[cfg(test)]
@@ -1137,6 +1179,12 @@ impl ExtensionType {
                 ExtensionType::TransferHook => {
                     account_extension_types.push(ExtensionType::TransferHookAccount);
                 }
+                ExtensionType::PausableConfig => {
+                    account_extension_types.push(ExtensionType::PausableAccount);
+                }
+                ExtensionType::VestingConfig => {
+                    account_extension_types.push(ExtensionType::VestingAccount);
+                }
                 #[cfg(test)]
                 ExtensionType::MintPaddingTest => {
                     account_extension_types.push(ExtensionType::AccountPaddingTest);
@@ -1154,6 +1202,9 @@ impl ExtensionType {
         let mut transfer_fee_config = false;
         let mut confidential_transfer_mint = false;
         let mut confidential_transfer_fee_config = false;
+        let mut interest_bearing_config = false;
+        let mut scaled_ui_amount = false;
+        let mut confidential_mint_burn = false;
 
         for extension_type in mint_extension_types {
             match extension_type {
@@ -1162,6 +1213,9 @@ impl ExtensionType {
                 ExtensionType::ConfidentialTransferFeeConfig => {
                     confidential_transfer_fee_config = true
                 }
+                ExtensionType::InterestBearingConfig => interest_bearing_config = true,
+                ExtensionType::ScaledUiAmount => scaled_ui_amount = true,
+                ExtensionType::ConfidentialMintBurn => confidential_mint_burn = true,
                 _ => (),
             }
         }
@@ -1175,6 +1229,17 @@ impl ExtensionType {
             return Err(TokenError::InvalidExtensionCombination);
         }
 
+        // Both extensions rewrite the UI amount, so they can't be combined
+        if interest_bearing_config && scaled_ui_amount {
+            return Err(TokenError::InvalidExtensionCombination);
+        }
+
+        // Confidential mints credit the balances of the confidential transfer
+        // extension, so it must be present
+        if confidential_mint_burn && !confidential_transfer_mint {
+            return Err(TokenError::InvalidExtensionCombination);
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,264 @@
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+use {
+    crate::extension::{Extension, ExtensionType},
+    bytemuck::{Pod, Zeroable},
+    solana_program::program_error::ProgramError,
+    spl_pod::{optional_keys::OptionalNonZeroPubkey, primitives::PodI64},
+};
+
+/// Scaled UI amount extension instructions
+pub mod instruction;
+
+/// Scaled UI amount extension processor
+pub mod processor;
+
+/// UnixTimestamp expressed with an alignment-independent type
+pub type UnixTimestamp = PodI64;
+
+/// `f64` type that can be used in `Pod`s
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(from = "f64", into = "f64"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct PodF64(pub [u8; 8]);
+impl PodF64 {
+    fn from_primitive(n: f64) -> Self {
+        Self(n.to_le_bytes())
+    }
+}
+impl From<f64> for PodF64 {
+    fn from(n: f64) -> Self {
+        Self::from_primitive(n)
+    }
+}
+impl From<PodF64> for f64 {
+    fn from(pod: PodF64) -> Self {
+        Self::from_le_bytes(pod.0)
+    }
+}
+
+/// Scaled UI amount extension data for mints
+///
+/// The UI amount of a token is its raw amount, scaled by the mint decimals,
+/// then multiplied by `multiplier`. The raw amounts held in accounts never
+/// change, so a rebasing token only updates the multiplier.
+///
+/// To let wallets and indexers prepare for a change, the authority may
+/// schedule `new_multiplier` to replace `multiplier` at
+/// `new_multiplier_effective_timestamp`.
+#[repr(C)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ScaledUiAmountConfig {
+    /// Authority that can set the multiplier and authority
+    pub authority: OptionalNonZeroPubkey,
+    /// Multiplier in use before `new_multiplier_effective_timestamp`
+    pub multiplier: PodF64,
+    /// Unix timestamp at which `new_multiplier` comes into effect
+    pub new_multiplier_effective_timestamp: UnixTimestamp,
+    /// Multiplier in use from `new_multiplier_effective_timestamp` onwards
+    pub new_multiplier: PodF64,
+}
+impl ScaledUiAmountConfig {
+    /// Get the multiplier in effect at the given timestamp
+    pub fn effective_multiplier(&self, unix_timestamp: i64) -> f64 {
+        if unix_timestamp >= i64::from(self.new_multiplier_effective_timestamp) {
+            self.new_multiplier.into()
+        } else {
+            self.multiplier.into()
+        }
+    }
+
+    fn total_multiplier(&self, decimals: u8, unix_timestamp: i64) -> f64 {
+        self.effective_multiplier(unix_timestamp) / 10_f64.powi(decimals as i32)
+    }
+
+    /// Convert a raw amount to its UI representation using the given decimals
+    /// field. Excess zeroes or unneeded decimal point are trimmed.
+    pub fn amount_to_ui_amount(
+        &self,
+        amount: u64,
+        decimals: u8,
+        unix_timestamp: i64,
+    ) -> Option<String> {
+        let scaled_amount = (amount as f64) * self.total_multiplier(decimals, unix_timestamp);
+        if scaled_amount.is_finite() {
+            Some(scaled_amount.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Try to convert a UI representation of a token amount to its raw amount
+    /// using the given decimals field
+    pub fn try_ui_amount_into_amount(
+        &self,
+        ui_amount: &str,
+        decimals: u8,
+        unix_timestamp: i64,
+    ) -> Result<u64, ProgramError> {
+        let scaled_amount = ui_amount
+            .parse::<f64>()
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        let amount = scaled_amount / self.total_multiplier(decimals, unix_timestamp);
+        if amount > (u64::MAX as f64) || amount < (u64::MIN as f64) || amount.is_nan() {
+            Err(ProgramError::InvalidArgument)
+        } else {
+            // this is important, if you round earlier, you'll get wrong "inf"
+            // answers
+            Ok(amount.round() as u64)
+        }
+    }
+}
+impl Extension for ScaledUiAmountConfig {
+    const TYPE: ExtensionType = ExtensionType::ScaledUiAmount;
+}
+
+/// Check that a multiplier can scale token amounts
+pub(crate) fn is_valid_multiplier(multiplier: f64) -> bool {
+    multiplier.is_finite() && multiplier > 0.
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, proptest::prelude::*};
+
+    const TEST_DECIMALS: u8 = 2;
+
+    fn config_with(multiplier: f64, new_multiplier: f64, timestamp: i64) -> ScaledUiAmountConfig {
+        ScaledUiAmountConfig {
+            authority: OptionalNonZeroPubkey::default(),
+            multiplier: multiplier.into(),
+            new_multiplier_effective_timestamp: timestamp.into(),
+            new_multiplier: new_multiplier.into(),
+        }
+    }
+
+    #[test]
+    fn effective_multiplier() {
+        let config = config_with(2., 3., 100);
+        assert_eq!(config.effective_multiplier(0), 2.);
+        assert_eq!(config.effective_multiplier(99), 2.);
+        assert_eq!(config.effective_multiplier(100), 3.);
+        assert_eq!(config.effective_multiplier(i64::MAX), 3.);
+    }
+
+    #[test]
+    fn specific_amount_to_ui_amount() {
+        let config = config_with(1.5, 1.5, 0);
+        for (amount, expected) in [(0, "0"), (1, "0.015"), (100, "1.5"), (4200, "63")] {
+            let ui_amount = config
+                .amount_to_ui_amount(amount, TEST_DECIMALS, 0)
+                .unwrap();
+            assert_eq!(ui_amount, expected);
+        }
+
+        // the scheduled multiplier only applies from its timestamp
+        let config = config_with(1., 5., 100);
+        let ui_amount = config.amount_to_ui_amount(100, TEST_DECIMALS, 99).unwrap();
+        assert_eq!(ui_amount, "1");
+        let ui_amount = config.amount_to_ui_amount(100, TEST_DECIMALS, 100).unwrap();
+        assert_eq!(ui_amount, "5");
+
+        // huge values
+        let config = config_with(2., 2., 0);
+        let ui_amount = config.amount_to_ui_amount(u64::MAX, 0, 0).unwrap();
+        assert_eq!(ui_amount, "36893488147419103000");
+    }
+
+    #[test]
+    fn specific_ui_amount_to_amount() {
+        let config = config_with(1.5, 1.5, 0);
+        for (ui_amount, expected) in [
+            ("0", 0),
+            ("0.015", 1),
+            ("1.5", 100),
+            ("1.50", 100),
+            ("63", 4200),
+            ("63.", 4200),
+        ] {
+            let amount = config
+                .try_ui_amount_into_amount(ui_amount, TEST_DECIMALS, 0)
+                .unwrap();
+            assert_eq!(amount, expected);
+        }
+
+        // the scheduled multiplier only applies from its timestamp
+        let config = config_with(1., 5., 100);
+        let amount = config
+            .try_ui_amount_into_amount("5", TEST_DECIMALS, 99)
+            .unwrap();
+        assert_eq!(amount, 500);
+        let amount = config
+            .try_ui_amount_into_amount("5", TEST_DECIMALS, 100)
+            .unwrap();
+        assert_eq!(amount, 100);
+
+        // huge values
+        let config = config_with(2., 2., 0);
+        let amount = config
+            .try_ui_amount_into_amount("36893488147419103000", 0, 0)
+            .unwrap();
+        assert_eq!(amount, u64::MAX);
+
+        // overflow u64 fail
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            config.try_ui_amount_into_amount("36893488147419203000", 0, 0)
+        );
+
+        for fail_ui_amount in [
+            "",
+            ".",
+            "0.t",
+            "-0.0000000000000000000001",
+            "inf",
+            "-inf",
+            "NaN",
+        ] {
+            assert_eq!(
+                Err(ProgramError::InvalidArgument),
+                config.try_ui_amount_into_amount(fail_ui_amount, 0, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn valid_multiplier() {
+        assert!(is_valid_multiplier(1.));
+        assert!(is_valid_multiplier(0.000_001));
+        assert!(is_valid_multiplier(1_000_000.));
+        for multiplier in [0., -0., -1., f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            assert!(!is_valid_multiplier(multiplier));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn amount_to_ui_amount(
+            multiplier in 0.000_001f64..1_000_000f64,
+            amount in 0..=u64::MAX,
+            decimals in 0u8..20u8,
+        ) {
+            let config = config_with(multiplier, multiplier, 0);
+            let ui_amount = config.amount_to_ui_amount(amount, decimals, 0);
+            assert!(ui_amount.is_some());
+        }
+
+        #[test]
+        fn round_trip(
+            multiplier in 0.000_001f64..1_000_000f64,
+            amount in 0..=u32::MAX as u64,
+            decimals in 0u8..9u8,
+        ) {
+            let config = config_with(multiplier, multiplier, 0);
+            let ui_amount = config.amount_to_ui_amount(amount, decimals, 0).unwrap();
+            let round_trip_amount = config
+                .try_ui_amount_into_amount(&ui_amount, decimals, 0)
+                .unwrap();
+            assert_eq!(round_trip_amount, amount);
+        }
+    }
+}
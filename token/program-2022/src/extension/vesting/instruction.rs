@@ -0,0 +1,140 @@
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+use {
+    crate::{
+        check_program_account,
+        extension::vesting::UnixTimestamp,
+        instruction::{encode_instruction, TokenInstruction},
+    },
+    bytemuck::{Pod, Zeroable},
+    num_enum::{IntoPrimitive, TryFromPrimitive},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    spl_pod::primitives::PodU64,
+};
+
+/// Vesting extension instructions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum VestingInstruction {
+    /// Initialize the vesting extension for the given mint account
+    ///
+    /// Fails if the account has already been initialized, so must be called
+    /// before `InitializeMint`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint account to initialize.
+    ///
+    /// Data expected by this instruction:
+    ///   `crate::extension::vesting::instruction::InitializeInstructionData`
+    Initialize,
+    /// Set the vesting schedule of a token account, replacing any previous
+    /// schedule. Setting a locked amount of zero removes the lock.
+    ///
+    /// Fails if the cliff is before the start, or after the end, of the
+    /// schedule.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The token account.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The mint's vesting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The token account.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The mint's multisignature vesting authority.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    ///
+    /// Data expected by this instruction:
+    ///   `crate::extension::vesting::instruction::SetScheduleInstructionData`
+    SetSchedule,
+}
+
+/// Data expected by `VestingInstruction::Initialize`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct InitializeInstructionData {
+    /// The public key for the account that can set vesting schedules
+    pub authority: Pubkey,
+}
+
+/// Data expected by `VestingInstruction::SetSchedule`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct SetScheduleInstructionData {
+    /// Amount of tokens locked by the schedule
+    pub locked_amount: PodU64,
+    /// Unix timestamp at which the tokens start unlocking
+    pub start_timestamp: UnixTimestamp,
+    /// Unix timestamp before which none of the tokens are unlocked
+    pub cliff_timestamp: UnixTimestamp,
+    /// Unix timestamp at which all of the tokens are unlocked
+    pub end_timestamp: UnixTimestamp,
+}
+
+/// Create an `Initialize` instruction
+pub fn initialize(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let accounts = vec![AccountMeta::new(*mint, false)];
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::VestingExtension,
+        VestingInstruction::Initialize,
+        &InitializeInstructionData {
+            authority: *authority,
+        },
+    ))
+}
+
+/// Create a `SetSchedule` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn set_schedule(
+    token_program_id: &Pubkey,
+    account: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&Pubkey],
+    locked_amount: u64,
+    start_timestamp: i64,
+    cliff_timestamp: i64,
+    end_timestamp: i64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*account, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*authority, signers.is_empty()),
+    ];
+    for signer_pubkey in signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::VestingExtension,
+        VestingInstruction::SetSchedule,
+        &SetScheduleInstructionData {
+            locked_amount: locked_amount.into(),
+            start_timestamp: start_timestamp.into(),
+            cliff_timestamp: cliff_timestamp.into(),
+            end_timestamp: end_timestamp.into(),
+        },
+    ))
+}
@@ -0,0 +1,178 @@
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+use {
+    crate::{
+        error::TokenError,
+        extension::{BaseStateWithExtensions, Extension, ExtensionType, StateWithExtensionsMut},
+        state::Account,
+    },
+    bytemuck::{Pod, Zeroable},
+    solana_program::{clock::Clock, entrypoint::ProgramResult, sysvar::Sysvar},
+    spl_pod::{
+        optional_keys::OptionalNonZeroPubkey,
+        primitives::{PodI64, PodU64},
+    },
+};
+
+/// Instructions for the Vesting extension
+pub mod instruction;
+/// Instruction processor for the Vesting extension
+pub mod processor;
+
+/// UnixTimestamp expressed with an alignment-independent type
+pub type UnixTimestamp = PodI64;
+
+/// Vesting extension data for mints.
+#[repr(C)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct VestingConfig {
+    /// Authority that can set the vesting schedule of accounts for the mint
+    pub authority: OptionalNonZeroPubkey,
+}
+
+/// Vesting schedule of an account for a mint with the Vesting extension.
+///
+/// All of `locked_amount` is locked until `cliff_timestamp`. From then on, it
+/// unlocks linearly as if it had been unlocking since `start_timestamp`, until
+/// it is fully unlocked at `end_timestamp`. A cliff schedule sets the cliff to
+/// the end, and a purely linear schedule sets the cliff to the start.
+///
+/// The default schedule locks nothing.
+#[repr(C)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct VestingAccount {
+    /// Amount of tokens locked by the schedule
+    pub locked_amount: PodU64,
+    /// Unix timestamp at which the tokens start unlocking
+    pub start_timestamp: UnixTimestamp,
+    /// Unix timestamp before which none of the tokens are unlocked
+    pub cliff_timestamp: UnixTimestamp,
+    /// Unix timestamp at which all of the tokens are unlocked
+    pub end_timestamp: UnixTimestamp,
+}
+
+impl Extension for VestingConfig {
+    const TYPE: ExtensionType = ExtensionType::VestingConfig;
+}
+
+impl Extension for VestingAccount {
+    const TYPE: ExtensionType = ExtensionType::VestingAccount;
+}
+
+impl VestingAccount {
+    /// Check that the timestamps of the schedule are in order
+    pub fn is_valid(&self) -> bool {
+        let start = i64::from(self.start_timestamp);
+        let cliff = i64::from(self.cliff_timestamp);
+        let end = i64::from(self.end_timestamp);
+        start <= cliff && cliff <= end
+    }
+
+    /// Amount of tokens still locked at the given time, rounded up
+    pub fn locked_amount_at(&self, unix_timestamp: i64) -> u64 {
+        let locked_amount = u64::from(self.locked_amount);
+        let start = i64::from(self.start_timestamp);
+        let cliff = i64::from(self.cliff_timestamp);
+        let end = i64::from(self.end_timestamp);
+        if unix_timestamp < cliff {
+            locked_amount
+        } else if unix_timestamp >= end {
+            0
+        } else {
+            // start <= cliff <= unix_timestamp < end, so none of these overflow
+            // and the locked amount is smaller than the full amount
+            let remaining = end.abs_diff(unix_timestamp) as u128;
+            let duration = end.abs_diff(start) as u128;
+            let locked = (locked_amount as u128)
+                .saturating_mul(remaining)
+                .saturating_add(duration.saturating_sub(1))
+                / duration;
+            locked as u64
+        }
+    }
+}
+
+/// Fails with `VestingBalanceLocked` if moving `amount` out of the account
+/// would dip into the balance still locked by its vesting schedule
+pub fn check_unlocked_amount(
+    account: &StateWithExtensionsMut<Account>,
+    amount: u64,
+) -> ProgramResult {
+    if let Ok(extension) = account.get_extension::<VestingAccount>() {
+        if u64::from(extension.locked_amount) == 0 {
+            return Ok(());
+        }
+        let locked_amount = extension.locked_amount_at(Clock::get()?.unix_timestamp);
+        if account.base.amount.saturating_sub(amount) < locked_amount {
+            return Err(TokenError::VestingBalanceLocked.into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(locked_amount: u64, start: i64, cliff: i64, end: i64) -> VestingAccount {
+        VestingAccount {
+            locked_amount: locked_amount.into(),
+            start_timestamp: start.into(),
+            cliff_timestamp: cliff.into(),
+            end_timestamp: end.into(),
+        }
+    }
+
+    #[test]
+    fn validity() {
+        assert!(VestingAccount::default().is_valid());
+        assert!(schedule(1, 0, 0, 100).is_valid());
+        assert!(schedule(1, 0, 100, 100).is_valid());
+        assert!(!schedule(1, 10, 0, 100).is_valid());
+        assert!(!schedule(1, 0, 100, 10).is_valid());
+    }
+
+    #[test]
+    fn linear_schedule() {
+        let vesting = schedule(1_000, 100, 100, 200);
+        assert_eq!(vesting.locked_amount_at(i64::MIN), 1_000);
+        assert_eq!(vesting.locked_amount_at(99), 1_000);
+        assert_eq!(vesting.locked_amount_at(100), 1_000);
+        assert_eq!(vesting.locked_amount_at(150), 500);
+        assert_eq!(vesting.locked_amount_at(199), 10);
+        assert_eq!(vesting.locked_amount_at(200), 0);
+        assert_eq!(vesting.locked_amount_at(i64::MAX), 0);
+    }
+
+    #[test]
+    fn cliff_schedule() {
+        let vesting = schedule(1_000, 100, 200, 200);
+        assert_eq!(vesting.locked_amount_at(199), 1_000);
+        assert_eq!(vesting.locked_amount_at(200), 0);
+
+        // with a cliff before the end, the amount unlocked since the start is
+        // released at the cliff
+        let vesting = schedule(1_000, 100, 150, 200);
+        assert_eq!(vesting.locked_amount_at(149), 1_000);
+        assert_eq!(vesting.locked_amount_at(150), 500);
+    }
+
+    #[test]
+    fn rounds_locked_amount_up() {
+        let vesting = schedule(10, 0, 0, 3);
+        assert_eq!(vesting.locked_amount_at(1), 7);
+        assert_eq!(vesting.locked_amount_at(2), 4);
+    }
+
+    #[test]
+    fn extreme_timestamps() {
+        let vesting = schedule(u64::MAX, i64::MIN, i64::MIN, i64::MAX);
+        assert_eq!(vesting.locked_amount_at(i64::MIN), u64::MAX);
+        assert_eq!(vesting.locked_amount_at(0), u64::MAX / 2);
+        assert_eq!(vesting.locked_amount_at(i64::MAX), 0);
+    }
+}
@@ -0,0 +1,109 @@
+use {
+    crate::{
+        check_program_account,
+        error::TokenError,
+        extension::{
+            vesting::{
+                instruction::{
+                    InitializeInstructionData, SetScheduleInstructionData, VestingInstruction,
+                },
+                VestingAccount, VestingConfig,
+            },
+            BaseStateWithExtensions, StateWithExtensions, StateWithExtensionsMut,
+        },
+        instruction::{decode_instruction_data, decode_instruction_type},
+        processor::Processor,
+        state::{Account, Mint},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        pubkey::Pubkey,
+    },
+};
+
+fn process_initialize(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    authority: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let mut mint_data = mint_account_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut mint_data)?;
+
+    let extension = mint.init_extension::<VestingConfig>(true)?;
+    extension.authority = Some(*authority).try_into()?;
+
+    Ok(())
+}
+
+/// Set the vesting schedule of a token account
+fn process_set_schedule(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &SetScheduleInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    let authority = Option::<Pubkey>::from(mint.get_extension::<VestingConfig>()?.authority)
+        .ok_or(TokenError::NoAuthorityExists)?;
+
+    Processor::validate_owner(
+        program_id,
+        &authority,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    check_program_account(token_account_info.owner)?;
+    let mut token_account_data = token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(&mut token_account_data)?;
+    if token_account.base.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+
+    let schedule = VestingAccount {
+        locked_amount: data.locked_amount,
+        start_timestamp: data.start_timestamp,
+        cliff_timestamp: data.cliff_timestamp,
+        end_timestamp: data.end_timestamp,
+    };
+    if !schedule.is_valid() {
+        return Err(TokenError::InvalidVestingSchedule.into());
+    }
+
+    let extension = token_account.get_extension_mut::<VestingAccount>()?;
+    *extension = schedule;
+    Ok(())
+}
+
+pub(crate) fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    match decode_instruction_type(input)? {
+        VestingInstruction::Initialize => {
+            msg!("VestingInstruction::Initialize");
+            let InitializeInstructionData { authority } = decode_instruction_data(input)?;
+            process_initialize(program_id, accounts, authority)
+        }
+        VestingInstruction::SetSchedule => {
+            msg!("VestingInstruction::SetSchedule");
+            let data = decode_instruction_data::<SetScheduleInstructionData>(input)?;
+            process_set_schedule(program_id, accounts, data)
+        }
+    }
+}
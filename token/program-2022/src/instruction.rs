@@ -700,6 +700,32 @@ pub enum TokenInstruction<'a> {
     /// for further details about the extended instructions that share this
     /// instruction prefix
     GroupMemberPointerExtension,
+    /// The common instruction prefix for scaled UI amount extension
+    /// instructions.
+    ///
+    /// See `extension::scaled_ui_amount::instruction::ScaledUiAmountMintInstruction`
+    /// for further details about the extended instructions that share this
+    /// instruction prefix
+    ScaledUiAmountExtension,
+    /// The common instruction prefix for pausable extension instructions.
+    ///
+    /// See `extension::pausable::instruction::PausableInstruction`
+    /// for further details about the extended instructions that share this
+    /// instruction prefix
+    PausableExtension,
+    /// The common instruction prefix for confidential mint-burn extension
+    /// instructions.
+    ///
+    /// See `extension::confidential_mint_burn::instruction::ConfidentialMintBurnInstruction`
+    /// for further details about the extended instructions that share this
+    /// instruction prefix
+    ConfidentialMintBurnExtension,
+    /// The common instruction prefix for vesting extension instructions.
+    ///
+    /// See `extension::vesting::instruction::VestingInstruction`
+    /// for further details about the extended instructions that share this
+    /// instruction prefix
+    VestingExtension,
 }
 impl<'a> TokenInstruction<'a> {
     /// Unpacks a byte buffer into a
@@ -842,6 +868,10 @@ impl<'a> TokenInstruction<'a> {
             39 => Self::MetadataPointerExtension,
             40 => Self::GroupPointerExtension,
             41 => Self::GroupMemberPointerExtension,
+            42 => Self::ScaledUiAmountExtension,
+            43 => Self::PausableExtension,
+            44 => Self::ConfidentialMintBurnExtension,
+            45 => Self::VestingExtension,
             _ => return Err(TokenError::InvalidInstruction.into()),
         })
     }
@@ -1014,6 +1044,18 @@ impl<'a> TokenInstruction<'a> {
             &Self::GroupMemberPointerExtension => {
                 buf.push(41);
             }
+            &Self::ScaledUiAmountExtension => {
+                buf.push(42);
+            }
+            &Self::PausableExtension => {
+                buf.push(43);
+            }
+            &Self::ConfidentialMintBurnExtension => {
+                buf.push(44);
+            }
+            &Self::VestingExtension => {
+                buf.push(45);
+            }
         };
         buf
     }
@@ -1111,6 +1153,12 @@ pub enum AuthorityType {
     GroupPointer,
     /// Authority to set the group member address
     GroupMemberPointer,
+    /// Authority to set the UI amount multiplier
+    ScaledUiAmount,
+    /// Authority to pause or resume minting, burning and transferring
+    Pause,
+    /// Authority to set the vesting schedule of accounts
+    Vesting,
 }
 
 impl AuthorityType {
@@ -1131,6 +1179,9 @@ impl AuthorityType {
             AuthorityType::MetadataPointer => 12,
             AuthorityType::GroupPointer => 13,
             AuthorityType::GroupMemberPointer => 14,
+            AuthorityType::ScaledUiAmount => 15,
+            AuthorityType::Pause => 16,
+            AuthorityType::Vesting => 17,
         }
     }
 
@@ -1151,6 +1202,9 @@ impl AuthorityType {
             12 => Ok(AuthorityType::MetadataPointer),
             13 => Ok(AuthorityType::GroupPointer),
             14 => Ok(AuthorityType::GroupMemberPointer),
+            15 => Ok(AuthorityType::ScaledUiAmount),
+            16 => Ok(AuthorityType::Pause),
+            17 => Ok(AuthorityType::Vesting),
             _ => Err(TokenError::InvalidInstruction.into()),
         }
     }
@@ -5,6 +5,7 @@ use {
         check_program_account, cmp_pubkeys,
         error::TokenError,
         extension::{
+            confidential_mint_burn::{self, ConfidentialMintBurn},
             confidential_transfer::{self, ConfidentialTransferAccount, ConfidentialTransferMint},
             confidential_transfer_fee::{
                 self, ConfidentialTransferFeeAmount, ConfidentialTransferFeeConfig,
@@ -19,10 +20,14 @@ use {
             metadata_pointer::{self, MetadataPointer},
             mint_close_authority::MintCloseAuthority,
             non_transferable::{NonTransferable, NonTransferableAccount},
+            pausable::{self, check_not_paused, PausableAccount, PausableConfig},
             permanent_delegate::{get_permanent_delegate, PermanentDelegate},
-            reallocate, token_group, token_metadata,
+            reallocate,
+            scaled_ui_amount::{self, ScaledUiAmountConfig},
+            token_group, token_metadata,
             transfer_fee::{self, TransferFeeAmount, TransferFeeConfig},
             transfer_hook::{self, TransferHook, TransferHookAccount},
+            vesting::{self, check_unlocked_amount, VestingConfig},
             AccountType, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
             StateWithExtensionsMut,
         },
@@ -303,6 +308,7 @@ impl Processor {
         if source_account.base.amount < amount {
             return Err(TokenError::InsufficientFunds.into());
         }
+        check_unlocked_amount(&source_account, amount)?;
         if source_account
             .get_extension::<NonTransferableAccount>()
             .is_ok()
@@ -322,6 +328,8 @@ impl Processor {
                     return Err(TokenError::MintDecimalsMismatch.into());
                 }
 
+                check_not_paused(&mint)?;
+
                 let fee = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>()
                 {
                     transfer_fee_config
@@ -349,6 +357,12 @@ impl Processor {
                     return Err(TokenError::MintRequiredForTransfer.into());
                 }
 
+                // Pausable extension exists on the account, but no mint
+                // was provided to see if it's paused, abort
+                if source_account.get_extension::<PausableAccount>().is_ok() {
+                    return Err(TokenError::MintRequiredForTransfer.into());
+                }
+
                 // Transfer fee amount extension exists on the account, but no mint
                 // was provided to calculate the fee, abort
                 if source_account
@@ -880,6 +894,45 @@ impl Processor {
                     )?;
                     extension.authority = new_authority.try_into()?;
                 }
+                AuthorityType::ScaledUiAmount => {
+                    let extension = mint.get_extension_mut::<ScaledUiAmountConfig>()?;
+                    let maybe_authority: Option<Pubkey> = extension.authority.into();
+                    let authority = maybe_authority.ok_or(TokenError::AuthorityTypeNotSupported)?;
+                    Self::validate_owner(
+                        program_id,
+                        &authority,
+                        authority_info,
+                        authority_info_data_len,
+                        account_info_iter.as_slice(),
+                    )?;
+                    extension.authority = new_authority.try_into()?;
+                }
+                AuthorityType::Pause => {
+                    let extension = mint.get_extension_mut::<PausableConfig>()?;
+                    let maybe_authority: Option<Pubkey> = extension.authority.into();
+                    let authority = maybe_authority.ok_or(TokenError::AuthorityTypeNotSupported)?;
+                    Self::validate_owner(
+                        program_id,
+                        &authority,
+                        authority_info,
+                        authority_info_data_len,
+                        account_info_iter.as_slice(),
+                    )?;
+                    extension.authority = new_authority.try_into()?;
+                }
+                AuthorityType::Vesting => {
+                    let extension = mint.get_extension_mut::<VestingConfig>()?;
+                    let maybe_authority: Option<Pubkey> = extension.authority.into();
+                    let authority = maybe_authority.ok_or(TokenError::AuthorityTypeNotSupported)?;
+                    Self::validate_owner(
+                        program_id,
+                        &authority,
+                        authority_info,
+                        authority_info_data_len,
+                        account_info_iter.as_slice(),
+                    )?;
+                    extension.authority = new_authority.try_into()?;
+                }
                 _ => {
                     return Err(TokenError::AuthorityTypeNotSupported.into());
                 }
@@ -921,6 +974,14 @@ impl Processor {
         let mut mint_data = mint_info.data.borrow_mut();
         let mut mint = StateWithExtensionsMut::<Mint>::unpack(&mut mint_data)?;
 
+        check_not_paused(&mint)?;
+
+        // Tokens of a confidential mint-burn mint only exist in encrypted
+        // balances
+        if mint.get_extension::<ConfidentialMintBurn>().is_ok() {
+            return Err(TokenError::IllegalMintBurnConversion.into());
+        }
+
         // If the mint if non-transferable, only allow minting to accounts
         // with immutable ownership.
         if mint.get_extension::<NonTransferable>().is_ok()
@@ -1001,6 +1062,7 @@ impl Processor {
         if source_account.base.amount < amount {
             return Err(TokenError::InsufficientFunds.into());
         }
+        check_unlocked_amount(&source_account, amount)?;
         if mint_info.key != &source_account.base.mint {
             return Err(TokenError::MintMismatch.into());
         }
@@ -1010,6 +1072,10 @@ impl Processor {
                 return Err(TokenError::MintDecimalsMismatch.into());
             }
         }
+        check_not_paused(&mint)?;
+        if mint.get_extension::<ConfidentialMintBurn>().is_ok() {
+            return Err(TokenError::IllegalMintBurnConversion.into());
+        }
         let maybe_permanent_delegate = get_permanent_delegate(&mint);
 
         if !source_account
@@ -1329,6 +1395,11 @@ impl Processor {
             extension
                 .amount_to_ui_amount(amount, mint.base.decimals, unix_timestamp)
                 .ok_or(ProgramError::InvalidArgument)?
+        } else if let Ok(extension) = mint.get_extension::<ScaledUiAmountConfig>() {
+            let unix_timestamp = Clock::get()?.unix_timestamp;
+            extension
+                .amount_to_ui_amount(amount, mint.base.decimals, unix_timestamp)
+                .ok_or(ProgramError::InvalidArgument)?
         } else {
             crate::amount_to_ui_amount_string_trimmed(amount, mint.base.decimals)
         };
@@ -1349,6 +1420,9 @@ impl Processor {
         let amount = if let Ok(extension) = mint.get_extension::<InterestBearingConfig>() {
             let unix_timestamp = Clock::get()?.unix_timestamp;
             extension.try_ui_amount_into_amount(ui_amount, mint.base.decimals, unix_timestamp)?
+        } else if let Ok(extension) = mint.get_extension::<ScaledUiAmountConfig>() {
+            let unix_timestamp = Clock::get()?.unix_timestamp;
+            extension.try_ui_amount_into_amount(ui_amount, mint.base.decimals, unix_timestamp)?
         } else {
             crate::try_ui_amount_into_amount(ui_amount.to_string(), mint.base.decimals)?
         };
@@ -1709,6 +1783,26 @@ impl Processor {
                         &input[1..],
                     )
                 }
+                TokenInstruction::ScaledUiAmountExtension => {
+                    scaled_ui_amount::processor::process_instruction(
+                        program_id,
+                        accounts,
+                        &input[1..],
+                    )
+                }
+                TokenInstruction::PausableExtension => {
+                    pausable::processor::process_instruction(program_id, accounts, &input[1..])
+                }
+                TokenInstruction::ConfidentialMintBurnExtension => {
+                    confidential_mint_burn::processor::process_instruction(
+                        program_id,
+                        accounts,
+                        &input[1..],
+                    )
+                }
+                TokenInstruction::VestingExtension => {
+                    vesting::processor::process_instruction(program_id, accounts, &input[1..])
+                }
             }
         } else if let Ok(instruction) = TokenMetadataInstruction::unpack(input) {
             token_metadata::processor::process_instruction(program_id, accounts, instruction)
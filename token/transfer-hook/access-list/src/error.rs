@@ -0,0 +1,39 @@
+//! Error types
+
+use spl_program_error::*;
+
+/// Errors that may be returned by the access list program.
+#[spl_program_error(hash_error_code_start = 2_909_206_368)]
+pub enum AccessListError {
+    /// Invalid list mode
+    #[error("Invalid list mode")]
+    InvalidListMode,
+    /// Incorrect list authority has signed the instruction
+    #[error("Incorrect list authority has signed the instruction")]
+    IncorrectListAuthority,
+    /// The list has no authority and can no longer be modified
+    #[error("The list has no authority and can no longer be modified")]
+    ImmutableList,
+    /// The wallet is already on the list
+    #[error("The wallet is already on the list")]
+    WalletAlreadyListed,
+    /// The wallet is not on the list
+    #[error("The wallet is not on the list")]
+    WalletNotListed,
+    /// The owner of the source account is not on the allowlist
+    #[error("The owner of the source account is not on the allowlist")]
+    SourceNotAllowed,
+    /// The owner of the destination account is not on the allowlist
+    #[error("The owner of the destination account is not on the allowlist")]
+    DestinationNotAllowed,
+    /// The owner of the source account is on the denylist
+    #[error("The owner of the source account is on the denylist")]
+    SourceDenied,
+    /// The owner of the destination account is on the denylist
+    #[error("The owner of the destination account is on the denylist")]
+    DestinationDenied,
+    /// The extra account metas are managed by the program and cannot be
+    /// written directly
+    #[error("The extra account metas are managed by the program and cannot be written directly")]
+    ExtraAccountMetasManagedByProgram,
+}
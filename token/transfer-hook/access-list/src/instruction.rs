@@ -0,0 +1,282 @@
+//! Instruction types
+
+use {
+    crate::{get_list_config_address, get_list_entry_address, state::ListMode},
+    bytemuck::{Pod, Zeroable},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+    spl_discriminator::{ArrayDiscriminator, SplDiscriminate},
+    spl_pod::{
+        bytemuck::{pod_bytes_of, pod_from_bytes},
+        optional_keys::OptionalNonZeroPubkey,
+    },
+    spl_transfer_hook_interface::get_extra_account_metas_address,
+};
+
+/// Instruction data for initializing the list of a mint
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable, SplDiscriminate)]
+#[discriminator_hash_input("solarti_transfer_hook_access_list:initialize_list")]
+pub struct InitializeList {
+    /// Authority that can add and remove list entries
+    pub authority: OptionalNonZeroPubkey,
+    /// The list mode, see `ListMode`
+    pub mode: u8,
+}
+
+/// Instruction data for adding a wallet to the list
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable, SplDiscriminate)]
+#[discriminator_hash_input("solarti_transfer_hook_access_list:add_to_list")]
+pub struct AddToList {
+    /// The wallet to add
+    pub wallet: Pubkey,
+}
+
+/// Instruction data for removing a wallet from the list
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable, SplDiscriminate)]
+#[discriminator_hash_input("solarti_transfer_hook_access_list:remove_from_list")]
+pub struct RemoveFromList {
+    /// The wallet to remove
+    pub wallet: Pubkey,
+}
+
+/// Instruction data for updating the authority of the list
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable, SplDiscriminate)]
+#[discriminator_hash_input("solarti_transfer_hook_access_list:update_list_authority")]
+pub struct UpdateListAuthority {
+    /// New authority for the list, or unset if `None`
+    pub new_authority: OptionalNonZeroPubkey,
+}
+
+/// Instructions supported by the access list program, on top of the transfer
+/// hook interface.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AccessListInstruction {
+    /// Initialize the list of a mint, along with the extra account metas
+    /// resolving the list accounts on `Execute`.
+    ///
+    /// The list configuration and the extra account metas accounts must be
+    /// funded for rent exemption beforehand.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` List configuration
+    ///   1. `[w]` Account with extra account metas
+    ///   2. `[]` Mint
+    ///   3. `[s]` Mint authority
+    ///   4. `[]` System program
+    InitializeList(InitializeList),
+
+    /// Add a wallet to the list.
+    ///
+    /// The list entry account must be funded for rent exemption beforehand.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` List entry
+    ///   1. `[]` List configuration
+    ///   2. `[]` Mint
+    ///   3. `[s]` List authority
+    ///   4. `[]` System program
+    AddToList(AddToList),
+
+    /// Remove a wallet from the list, closing its list entry.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` List entry
+    ///   1. `[]` List configuration
+    ///   2. `[]` Mint
+    ///   3. `[s]` List authority
+    ///   4. `[w]` Destination for the list entry lamports
+    RemoveFromList(RemoveFromList),
+
+    /// Update the authority of the list.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` List configuration
+    ///   1. `[]` Mint
+    ///   2. `[s]` Current list authority
+    UpdateListAuthority(UpdateListAuthority),
+}
+impl AccessListInstruction {
+    /// Unpacks a byte buffer into an `AccessListInstruction`
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < ArrayDiscriminator::LENGTH {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (discriminator, rest) = input.split_at(ArrayDiscriminator::LENGTH);
+        Ok(match discriminator {
+            InitializeList::SPL_DISCRIMINATOR_SLICE => {
+                let data = pod_from_bytes::<InitializeList>(rest)?;
+                Self::InitializeList(*data)
+            }
+            AddToList::SPL_DISCRIMINATOR_SLICE => {
+                let data = pod_from_bytes::<AddToList>(rest)?;
+                Self::AddToList(*data)
+            }
+            RemoveFromList::SPL_DISCRIMINATOR_SLICE => {
+                let data = pod_from_bytes::<RemoveFromList>(rest)?;
+                Self::RemoveFromList(*data)
+            }
+            UpdateListAuthority::SPL_DISCRIMINATOR_SLICE => {
+                let data = pod_from_bytes::<UpdateListAuthority>(rest)?;
+                Self::UpdateListAuthority(*data)
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+
+    /// Packs an `AccessListInstruction` into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        match self {
+            Self::InitializeList(data) => {
+                buf.extend_from_slice(InitializeList::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(pod_bytes_of(data));
+            }
+            Self::AddToList(data) => {
+                buf.extend_from_slice(AddToList::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(pod_bytes_of(data));
+            }
+            Self::RemoveFromList(data) => {
+                buf.extend_from_slice(RemoveFromList::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(pod_bytes_of(data));
+            }
+            Self::UpdateListAuthority(data) => {
+                buf.extend_from_slice(UpdateListAuthority::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(pod_bytes_of(data));
+            }
+        };
+        buf
+    }
+}
+
+/// Creates an `InitializeList` instruction
+pub fn initialize_list(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    list_authority: Option<Pubkey>,
+    mode: ListMode,
+) -> Instruction {
+    let authority = OptionalNonZeroPubkey::try_from(list_authority)
+        .expect("Failed to deserialize `Option<Pubkey>`");
+    let data = AccessListInstruction::InitializeList(InitializeList {
+        authority,
+        mode: mode.into(),
+    })
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(get_list_config_address(mint, program_id), false),
+            AccountMeta::new(get_extra_account_metas_address(mint, program_id), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Creates an `AddToList` instruction
+pub fn add_to_list(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    list_authority: &Pubkey,
+    wallet: &Pubkey,
+) -> Instruction {
+    let data = AccessListInstruction::AddToList(AddToList { wallet: *wallet }).pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(get_list_entry_address(mint, wallet, program_id), false),
+            AccountMeta::new_readonly(get_list_config_address(mint, program_id), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*list_authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Creates a `RemoveFromList` instruction
+pub fn remove_from_list(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    list_authority: &Pubkey,
+    wallet: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let data = AccessListInstruction::RemoveFromList(RemoveFromList { wallet: *wallet }).pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(get_list_entry_address(mint, wallet, program_id), false),
+            AccountMeta::new_readonly(get_list_config_address(mint, program_id), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*list_authority, true),
+            AccountMeta::new(*destination, false),
+        ],
+        data,
+    }
+}
+
+/// Creates an `UpdateListAuthority` instruction
+pub fn update_list_authority(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    current_authority: &Pubkey,
+    new_authority: Option<Pubkey>,
+) -> Instruction {
+    let new_authority = OptionalNonZeroPubkey::try_from(new_authority)
+        .expect("Failed to deserialize `Option<Pubkey>`");
+    let data =
+        AccessListInstruction::UpdateListAuthority(UpdateListAuthority { new_authority }).pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(get_list_config_address(mint, program_id), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*current_authority, true),
+        ],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pack_unpack() {
+        let instructions = [
+            AccessListInstruction::InitializeList(InitializeList {
+                authority: OptionalNonZeroPubkey::try_from(Some(Pubkey::new_unique())).unwrap(),
+                mode: ListMode::Deny.into(),
+            }),
+            AccessListInstruction::AddToList(AddToList {
+                wallet: Pubkey::new_unique(),
+            }),
+            AccessListInstruction::RemoveFromList(RemoveFromList {
+                wallet: Pubkey::new_unique(),
+            }),
+            AccessListInstruction::UpdateListAuthority(UpdateListAuthority {
+                new_authority: OptionalNonZeroPubkey::default(),
+            }),
+        ];
+        for instruction in instructions {
+            let packed = instruction.pack();
+            assert_eq!(AccessListInstruction::unpack(&packed).unwrap(), instruction);
+        }
+    }
+}
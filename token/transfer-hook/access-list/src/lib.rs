@@ -0,0 +1,82 @@
+//! Crate defining a transfer hook program that enforces an allowlist or a
+//! denylist on the owners of the source and destination token accounts of
+//! every transfer.
+//!
+//! The list for a mint is made of a `ListConfig` PDA, holding the list mode
+//! and the list authority, and one `ListEntry` PDA per listed wallet. The
+//! entries for the source and destination owners are resolved by the token
+//! program through the `ExtraAccountMetaList` written at list initialization.
+
+#![allow(clippy::arithmetic_side_effects)]
+#![deny(missing_docs)]
+#![cfg_attr(not(test), forbid(unsafe_code))]
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint;
+
+// Export current sdk types for downstream users building with a different sdk
+// version
+pub use solana_program;
+use solana_program::pubkey::Pubkey;
+
+/// Seed for the list configuration
+pub const LIST_CONFIG_SEED: &[u8] = b"list-config";
+
+/// Seed for the list entries
+pub const LIST_ENTRY_SEED: &[u8] = b"list-entry";
+
+/// Get the list configuration address PDA
+pub fn get_list_config_address(mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_list_config_address_and_bump_seed(mint, program_id).0
+}
+
+/// Get the list configuration address PDA and its bump seed
+pub fn get_list_config_address_and_bump_seed(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&collect_list_config_seeds(mint), program_id)
+}
+
+/// Get all of the seeds for the list configuration PDA
+pub fn collect_list_config_seeds(mint: &Pubkey) -> [&[u8]; 2] {
+    [LIST_CONFIG_SEED, mint.as_ref()]
+}
+
+/// Get all of the seeds required to sign for the list configuration PDA
+pub fn collect_list_config_signer_seeds<'a>(
+    mint: &'a Pubkey,
+    bump_seed: &'a [u8],
+) -> [&'a [u8]; 3] {
+    [LIST_CONFIG_SEED, mint.as_ref(), bump_seed]
+}
+
+/// Get the list entry address PDA for a wallet
+pub fn get_list_entry_address(mint: &Pubkey, wallet: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_list_entry_address_and_bump_seed(mint, wallet, program_id).0
+}
+
+/// Get the list entry address PDA for a wallet and its bump seed
+pub fn get_list_entry_address_and_bump_seed(
+    mint: &Pubkey,
+    wallet: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&collect_list_entry_seeds(mint, wallet), program_id)
+}
+
+/// Get all of the seeds for the list entry PDA of a wallet
+pub fn collect_list_entry_seeds<'a>(mint: &'a Pubkey, wallet: &'a Pubkey) -> [&'a [u8]; 3] {
+    [LIST_ENTRY_SEED, mint.as_ref(), wallet.as_ref()]
+}
+
+/// Get all of the seeds required to sign for the list entry PDA of a wallet
+pub fn collect_list_entry_signer_seeds<'a>(
+    mint: &'a Pubkey,
+    wallet: &'a Pubkey,
+    bump_seed: &'a [u8],
+) -> [&'a [u8]; 4] {
+    [LIST_ENTRY_SEED, mint.as_ref(), wallet.as_ref(), bump_seed]
+}
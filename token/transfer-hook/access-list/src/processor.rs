@@ -0,0 +1,405 @@
+//! Program state processor
+
+use {
+    crate::{
+        collect_list_config_signer_seeds, collect_list_entry_signer_seeds,
+        error::AccessListError,
+        get_list_config_address, get_list_config_address_and_bump_seed,
+        get_list_entry_address_and_bump_seed,
+        instruction::{
+            AccessListInstruction, AddToList, InitializeList, RemoveFromList, UpdateListAuthority,
+        },
+        state::{list_extra_account_metas, ListConfig, ListEntry, ListMode},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program::invoke_signed,
+        program_error::ProgramError,
+        program_option::COption,
+        pubkey::Pubkey,
+        system_instruction, system_program,
+    },
+    spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_tlv_account_resolution::state::ExtraAccountMetaList,
+    spl_token_2022::{
+        extension::{
+            transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::{Account, Mint},
+    },
+    spl_transfer_hook_interface::{
+        collect_extra_account_metas_signer_seeds,
+        error::TransferHookError,
+        get_extra_account_metas_address, get_extra_account_metas_address_and_bump_seed,
+        instruction::{ExecuteInstruction, TransferHookInstruction},
+    },
+    spl_type_length_value::state::{TlvState, TlvStateBorrowed, TlvStateMut},
+};
+
+fn check_token_account_is_transferring(account_info: &AccountInfo) -> Result<(), ProgramError> {
+    let account_data = account_info.try_borrow_data()?;
+    let token_account = StateWithExtensions::<Account>::unpack(&account_data)?;
+    let extension = token_account.get_extension::<TransferHookAccount>()?;
+    if bool::from(extension.transferring) {
+        Ok(())
+    } else {
+        Err(TransferHookError::ProgramCalledOutsideOfTransfer.into())
+    }
+}
+
+fn check_mint_authority(mint_info: &AccountInfo, authority_info: &AccountInfo) -> ProgramResult {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    if mint.base.mint_authority.is_none() {
+        return Err(TransferHookError::MintHasNoMintAuthority.into());
+    }
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if mint.base.mint_authority.as_ref() != COption::Some(authority_info.key) {
+        return Err(TransferHookError::IncorrectMintAuthority.into());
+    }
+    Ok(())
+}
+
+fn check_list_authority(
+    authority_info: &AccountInfo,
+    expected_authority: &OptionalNonZeroPubkey,
+) -> ProgramResult {
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let authority =
+        Option::<Pubkey>::from(*expected_authority).ok_or(AccessListError::ImmutableList)?;
+    if authority != *authority_info.key {
+        return Err(AccessListError::IncorrectListAuthority.into());
+    }
+    Ok(())
+}
+
+/// Gets the list configuration of the mint, checking that it was created by
+/// this program at the expected address
+fn get_list_config(
+    program_id: &Pubkey,
+    list_config_info: &AccountInfo,
+    mint_info: &AccountInfo,
+) -> Result<ListConfig, ProgramError> {
+    if get_list_config_address(mint_info.key, program_id) != *list_config_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if list_config_info.owner != program_id {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let data = list_config_info.try_borrow_data()?;
+    let state = TlvStateBorrowed::unpack(&data)?;
+    state.get_first_value::<ListConfig>().copied()
+}
+
+/// A wallet is on the list for as long as its list entry exists. The address
+/// of the entry must be checked by the caller.
+fn is_listed(program_id: &Pubkey, list_entry_info: &AccountInfo) -> Result<bool, ProgramError> {
+    if list_entry_info.owner != program_id {
+        return Ok(false);
+    }
+    let data = list_entry_info.try_borrow_data()?;
+    let state = TlvStateBorrowed::unpack(&data)?;
+    Ok(state.get_first_value::<ListEntry>().is_ok())
+}
+
+/// Allocates and assigns a PDA that was already funded for rent exemption
+fn create_pda_account(
+    program_id: &Pubkey,
+    account_info: &AccountInfo,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, space as u64),
+        &[account_info.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account_info.key, program_id),
+        &[account_info.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Processes an [Execute](enum.TransferHookInstruction.html) instruction.
+pub fn process_execute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let source_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let destination_account_info = next_account_info(account_info_iter)?;
+    let _authority_info = next_account_info(account_info_iter)?;
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let list_config_info = next_account_info(account_info_iter)?;
+    let source_entry_info = next_account_info(account_info_iter)?;
+    let destination_entry_info = next_account_info(account_info_iter)?;
+
+    // Check that the accounts are properly in "transferring" mode
+    check_token_account_is_transferring(source_account_info)?;
+    check_token_account_is_transferring(destination_account_info)?;
+
+    let expected_validation_address = get_extra_account_metas_address(mint_info.key, program_id);
+    if expected_validation_address != *extra_account_metas_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if extra_account_metas_info.owner != program_id {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Check that the list accounts are the ones derived from the mint and the
+    // owners of the token accounts
+    {
+        let data = extra_account_metas_info.try_borrow_data()?;
+        ExtraAccountMetaList::check_account_infos::<ExecuteInstruction>(
+            accounts,
+            &TransferHookInstruction::Execute { amount }.pack(),
+            program_id,
+            &data,
+        )?;
+    }
+
+    let list_config = get_list_config(program_id, list_config_info, mint_info)?;
+    let source_listed = is_listed(program_id, source_entry_info)?;
+    let destination_listed = is_listed(program_id, destination_entry_info)?;
+    match list_config.list_mode()? {
+        ListMode::Allow => {
+            if !source_listed {
+                return Err(AccessListError::SourceNotAllowed.into());
+            }
+            if !destination_listed {
+                return Err(AccessListError::DestinationNotAllowed.into());
+            }
+        }
+        ListMode::Deny => {
+            if source_listed {
+                return Err(AccessListError::SourceDenied.into());
+            }
+            if destination_listed {
+                return Err(AccessListError::DestinationDenied.into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Processes an [InitializeList](enum.AccessListInstruction.html)
+/// instruction.
+pub fn process_initialize_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: InitializeList,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let list_config_info = next_account_info(account_info_iter)?;
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let mint_authority_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    check_mint_authority(mint_info, mint_authority_info)?;
+    ListMode::try_from(data.mode)?;
+
+    // Check the list and validation accounts
+    let (expected_list_config_address, list_config_bump_seed) =
+        get_list_config_address_and_bump_seed(mint_info.key, program_id);
+    if expected_list_config_address != *list_config_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let (expected_validation_address, validation_bump_seed) =
+        get_extra_account_metas_address_and_bump_seed(mint_info.key, program_id);
+    if expected_validation_address != *extra_account_metas_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Create the list configuration
+    let bump_seed = [list_config_bump_seed];
+    let signer_seeds = collect_list_config_signer_seeds(mint_info.key, &bump_seed);
+    create_pda_account(
+        program_id,
+        list_config_info,
+        ListConfig::account_size(),
+        &signer_seeds,
+    )?;
+    {
+        let mut buffer = list_config_info.try_borrow_mut_data()?;
+        let mut state = TlvStateMut::unpack(&mut buffer)?;
+        let (list_config, _) = state.init_value::<ListConfig>(false)?;
+        *list_config = ListConfig {
+            authority: data.authority,
+            mint: *mint_info.key,
+            mode: data.mode,
+        };
+    }
+
+    // Create the extra account metas resolving the list accounts
+    let extra_account_metas = list_extra_account_metas()?;
+    let bump_seed = [validation_bump_seed];
+    let signer_seeds = collect_extra_account_metas_signer_seeds(mint_info.key, &bump_seed);
+    create_pda_account(
+        program_id,
+        extra_account_metas_info,
+        ExtraAccountMetaList::size_of(extra_account_metas.len())?,
+        &signer_seeds,
+    )?;
+    let mut data = extra_account_metas_info.try_borrow_mut_data()?;
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &extra_account_metas)?;
+
+    Ok(())
+}
+
+/// Processes an [AddToList](enum.AccessListInstruction.html) instruction.
+pub fn process_add_to_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: AddToList,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let list_entry_info = next_account_info(account_info_iter)?;
+    let list_config_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    let list_config = get_list_config(program_id, list_config_info, mint_info)?;
+    check_list_authority(authority_info, &list_config.authority)?;
+
+    let (expected_list_entry_address, bump_seed) =
+        get_list_entry_address_and_bump_seed(mint_info.key, &data.wallet, program_id);
+    if expected_list_entry_address != *list_entry_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if is_listed(program_id, list_entry_info)? {
+        return Err(AccessListError::WalletAlreadyListed.into());
+    }
+
+    let bump_seed = [bump_seed];
+    let signer_seeds = collect_list_entry_signer_seeds(mint_info.key, &data.wallet, &bump_seed);
+    create_pda_account(
+        program_id,
+        list_entry_info,
+        ListEntry::account_size(),
+        &signer_seeds,
+    )?;
+    let mut buffer = list_entry_info.try_borrow_mut_data()?;
+    let mut state = TlvStateMut::unpack(&mut buffer)?;
+    let (list_entry, _) = state.init_value::<ListEntry>(false)?;
+    *list_entry = ListEntry {
+        mint: *mint_info.key,
+        wallet: data.wallet,
+    };
+
+    Ok(())
+}
+
+/// Processes a [RemoveFromList](enum.AccessListInstruction.html)
+/// instruction.
+pub fn process_remove_from_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: RemoveFromList,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let list_entry_info = next_account_info(account_info_iter)?;
+    let list_config_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+
+    let list_config = get_list_config(program_id, list_config_info, mint_info)?;
+    check_list_authority(authority_info, &list_config.authority)?;
+
+    let (expected_list_entry_address, _) =
+        get_list_entry_address_and_bump_seed(mint_info.key, &data.wallet, program_id);
+    if expected_list_entry_address != *list_entry_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !is_listed(program_id, list_entry_info)? {
+        return Err(AccessListError::WalletNotListed.into());
+    }
+
+    // Close the list entry
+    let destination_starting_lamports = destination_info.lamports();
+    **destination_info.lamports.borrow_mut() = destination_starting_lamports
+        .checked_add(list_entry_info.lamports())
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **list_entry_info.lamports.borrow_mut() = 0;
+    list_entry_info.assign(&system_program::id());
+    list_entry_info.realloc(0, false)?;
+
+    Ok(())
+}
+
+/// Processes an [UpdateListAuthority](enum.AccessListInstruction.html)
+/// instruction.
+pub fn process_update_list_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: UpdateListAuthority,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let list_config_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+
+    let list_config = get_list_config(program_id, list_config_info, mint_info)?;
+    check_list_authority(authority_info, &list_config.authority)?;
+
+    let mut buffer = list_config_info.try_borrow_mut_data()?;
+    let mut state = TlvStateMut::unpack(&mut buffer)?;
+    let list_config = state.get_first_value_mut::<ListConfig>()?;
+    list_config.authority = data.new_authority;
+
+    Ok(())
+}
+
+/// Processes an [Instruction](enum.Instruction.html).
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+    if let Ok(instruction) = TransferHookInstruction::unpack(input) {
+        return match instruction {
+            TransferHookInstruction::Execute { amount } => {
+                msg!("Instruction: Execute");
+                process_execute(program_id, accounts, amount)
+            }
+            TransferHookInstruction::InitializeExtraAccountMetaList { .. }
+            | TransferHookInstruction::UpdateExtraAccountMetaList { .. } => {
+                // The extra account metas are written by `InitializeList`, so
+                // that they always resolve the list accounts
+                Err(AccessListError::ExtraAccountMetasManagedByProgram.into())
+            }
+        };
+    }
+
+    match AccessListInstruction::unpack(input)? {
+        AccessListInstruction::InitializeList(data) => {
+            msg!("Instruction: InitializeList");
+            process_initialize_list(program_id, accounts, data)
+        }
+        AccessListInstruction::AddToList(data) => {
+            msg!("Instruction: AddToList");
+            process_add_to_list(program_id, accounts, data)
+        }
+        AccessListInstruction::RemoveFromList(data) => {
+            msg!("Instruction: RemoveFromList");
+            process_remove_from_list(program_id, accounts, data)
+        }
+        AccessListInstruction::UpdateListAuthority(data) => {
+            msg!("Instruction: UpdateListAuthority");
+            process_update_list_authority(program_id, accounts, data)
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! State types for the access list program
+
+use {
+    crate::{error::AccessListError, LIST_CONFIG_SEED, LIST_ENTRY_SEED},
+    bytemuck::{Pod, Zeroable},
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    spl_discriminator::SplDiscriminate,
+    spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed},
+    spl_type_length_value::state::{TlvState, TlvStateBorrowed},
+    std::mem::size_of,
+};
+
+/// How the list is applied to the owners of transferring accounts
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListMode {
+    /// Only listed owners may send and receive tokens
+    Allow,
+    /// Listed owners may not send or receive tokens
+    Deny,
+}
+impl TryFrom<u8> for ListMode {
+    type Error = ProgramError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Allow),
+            1 => Ok(Self::Deny),
+            _ => Err(AccessListError::InvalidListMode.into()),
+        }
+    }
+}
+impl From<ListMode> for u8 {
+    fn from(mode: ListMode) -> Self {
+        mode as u8
+    }
+}
+
+/// Configuration of the list for a mint, stored in the PDA derived from
+/// `LIST_CONFIG_SEED` and the mint
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, SplDiscriminate)]
+#[discriminator_hash_input("solarti_transfer_hook_access_list:list_config")]
+pub struct ListConfig {
+    /// Authority that can add and remove list entries
+    pub authority: OptionalNonZeroPubkey,
+    /// The mint using the list
+    pub mint: Pubkey,
+    /// The list mode, see `ListMode`
+    pub mode: u8,
+}
+impl ListConfig {
+    /// Size of the account holding the list configuration
+    pub fn account_size() -> usize {
+        TlvStateBorrowed::get_base_len() + size_of::<Self>()
+    }
+
+    /// Get the list mode
+    pub fn list_mode(&self) -> Result<ListMode, ProgramError> {
+        ListMode::try_from(self.mode)
+    }
+}
+
+/// Membership of a wallet on the list for a mint, stored in the PDA derived
+/// from `LIST_ENTRY_SEED`, the mint and the wallet. The wallet is on the list
+/// for as long as the account exists.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, SplDiscriminate)]
+#[discriminator_hash_input("solarti_transfer_hook_access_list:list_entry")]
+pub struct ListEntry {
+    /// The mint using the list
+    pub mint: Pubkey,
+    /// The listed wallet
+    pub wallet: Pubkey,
+}
+impl ListEntry {
+    /// Size of the account holding a list entry
+    pub fn account_size() -> usize {
+        TlvStateBorrowed::get_base_len() + size_of::<Self>()
+    }
+}
+
+/// Offset of the owner in a token account
+const TOKEN_ACCOUNT_OWNER_OFFSET: u8 = 32;
+
+/// The extra accounts required by `Execute`, resolved from the accounts of the
+/// transfer:
+///
+///   5. `[]` List configuration
+///   6. `[]` List entry for the source account owner
+///   7. `[]` List entry for the destination account owner
+pub fn list_extra_account_metas() -> Result<Vec<ExtraAccountMeta>, ProgramError> {
+    let entry_meta = |token_account_index| {
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: LIST_ENTRY_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+                Seed::AccountData {
+                    account_index: token_account_index,
+                    data_index: TOKEN_ACCOUNT_OWNER_OFFSET,
+                    length: 32,
+                },
+            ],
+            false,
+            false,
+        )
+    };
+    Ok(vec![
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: LIST_CONFIG_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            false,
+        )?,
+        entry_meta(0)?,
+        entry_meta(2)?,
+    ])
+}
@@ -0,0 +1,467 @@
+// Mark this test as SBF-only due to current `ProgramTest` limitations when
+// CPIing into the system program
+#![cfg(feature = "test-sbf")]
+
+use {
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account as SolanaAccount,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        program_option::COption,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_tlv_account_resolution::{error::AccountResolutionError, state::ExtraAccountMetaList},
+    spl_token_2022::{
+        extension::{transfer_hook::TransferHookAccount, ExtensionType, StateWithExtensionsMut},
+        state::{Account, AccountState, Mint},
+    },
+    spl_transfer_hook_access_list::{
+        error::AccessListError,
+        get_list_config_address, get_list_entry_address,
+        instruction::{add_to_list, initialize_list, remove_from_list, update_list_authority},
+        state::{ListConfig, ListEntry, ListMode},
+    },
+    spl_transfer_hook_interface::{
+        error::TransferHookError,
+        get_extra_account_metas_address,
+        instruction::{execute_with_extra_account_metas, initialize_extra_account_meta_list},
+    },
+};
+
+struct TestAccounts {
+    mint: Pubkey,
+    mint_authority: Keypair,
+    list_authority: Keypair,
+    alice: Pubkey,
+    alice_account: Pubkey,
+    bob: Pubkey,
+    bob_account: Pubkey,
+}
+
+fn add_token_account(
+    program_test: &mut ProgramTest,
+    address: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    transferring: bool,
+) {
+    // add the account by hand to always force the "transferring" flag
+    let account_size =
+        ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferHookAccount])
+            .unwrap();
+    let mut account_data = vec![0; account_size];
+    let mut state =
+        StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut account_data).unwrap();
+    let extension = state.init_extension::<TransferHookAccount>(true).unwrap();
+    extension.transferring = transferring.into();
+    state.base = Account {
+        mint: *mint,
+        owner: *owner,
+        amount: 1_000_000_000_000,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    program_test.add_account(
+        *address,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data: account_data,
+            owner: spl_token_2022::id(),
+            ..SolanaAccount::default()
+        },
+    );
+}
+
+fn setup(program_id: &Pubkey, transferring: bool) -> (ProgramTest, TestAccounts) {
+    let mut program_test = ProgramTest::new(
+        "spl_transfer_hook_access_list",
+        *program_id,
+        processor!(spl_transfer_hook_access_list::processor::process),
+    );
+
+    program_test.prefer_bpf(false); // simplicity in the build
+
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(spl_token_2022::processor::Processor::process),
+    );
+
+    let accounts = TestAccounts {
+        mint: Pubkey::new_unique(),
+        mint_authority: Keypair::new(),
+        list_authority: Keypair::new(),
+        alice: Pubkey::new_unique(),
+        alice_account: Pubkey::new_unique(),
+        bob: Pubkey::new_unique(),
+        bob_account: Pubkey::new_unique(),
+    };
+
+    let mint_size = ExtensionType::try_calculate_account_len::<Mint>(&[]).unwrap();
+    let mut mint_data = vec![0; mint_size];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut mint_data).unwrap();
+    state.base = Mint {
+        mint_authority: COption::Some(accounts.mint_authority.pubkey()),
+        supply: 2_000_000_000_000,
+        decimals: 2,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    program_test.add_account(
+        accounts.mint,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data: mint_data,
+            owner: spl_token_2022::id(),
+            ..SolanaAccount::default()
+        },
+    );
+    add_token_account(
+        &mut program_test,
+        &accounts.alice_account,
+        &accounts.mint,
+        &accounts.alice,
+        transferring,
+    );
+    add_token_account(
+        &mut program_test,
+        &accounts.bob_account,
+        &accounts.mint,
+        &accounts.bob,
+        transferring,
+    );
+
+    (program_test, accounts)
+}
+
+async fn process_instructions(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn rent_transfer(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    space: usize,
+) -> Instruction {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    system_instruction::transfer(
+        &context.payer.pubkey(),
+        address,
+        rent.minimum_balance(space),
+    )
+}
+
+async fn initialize(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    accounts: &TestAccounts,
+    mode: ListMode,
+) {
+    let list_config_address = get_list_config_address(&accounts.mint, program_id);
+    let extra_account_metas_address = get_extra_account_metas_address(&accounts.mint, program_id);
+    let instructions = [
+        rent_transfer(context, &list_config_address, ListConfig::account_size()).await,
+        rent_transfer(
+            context,
+            &extra_account_metas_address,
+            ExtraAccountMetaList::size_of(3).unwrap(),
+        )
+        .await,
+        initialize_list(
+            program_id,
+            &accounts.mint,
+            &accounts.mint_authority.pubkey(),
+            Some(accounts.list_authority.pubkey()),
+            mode,
+        ),
+    ];
+    process_instructions(context, &instructions, &[&accounts.mint_authority])
+        .await
+        .unwrap();
+}
+
+async fn add(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    accounts: &TestAccounts,
+    wallet: &Pubkey,
+) {
+    let list_entry_address = get_list_entry_address(&accounts.mint, wallet, program_id);
+    let instructions = [
+        rent_transfer(context, &list_entry_address, ListEntry::account_size()).await,
+        add_to_list(
+            program_id,
+            &accounts.mint,
+            &accounts.list_authority.pubkey(),
+            wallet,
+        ),
+    ];
+    process_instructions(context, &instructions, &[&accounts.list_authority])
+        .await
+        .unwrap();
+}
+
+async fn execute(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    accounts: &TestAccounts,
+) -> Result<(), TransactionError> {
+    let extra_account_metas = [
+        AccountMeta::new_readonly(get_list_config_address(&accounts.mint, program_id), false),
+        AccountMeta::new_readonly(
+            get_list_entry_address(&accounts.mint, &accounts.alice, program_id),
+            false,
+        ),
+        AccountMeta::new_readonly(
+            get_list_entry_address(&accounts.mint, &accounts.bob, program_id),
+            false,
+        ),
+    ];
+    let instruction = execute_with_extra_account_metas(
+        program_id,
+        &accounts.alice_account,
+        &accounts.mint,
+        &accounts.bob_account,
+        &accounts.alice,
+        &get_extra_account_metas_address(&accounts.mint, program_id),
+        &extra_account_metas,
+        100,
+    );
+    process_instructions(context, &[instruction], &[]).await
+}
+
+fn custom_error(error: AccessListError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn success_allowlist() {
+    let program_id = Pubkey::new_unique();
+    let (program_test, accounts) = setup(&program_id, true);
+    let mut context = program_test.start_with_context().await;
+    initialize(&mut context, &program_id, &accounts, ListMode::Allow).await;
+
+    let error = execute(&mut context, &program_id, &accounts)
+        .await
+        .unwrap_err();
+    assert_eq!(error, custom_error(AccessListError::SourceNotAllowed));
+
+    add(&mut context, &program_id, &accounts, &accounts.alice).await;
+    let error = execute(&mut context, &program_id, &accounts)
+        .await
+        .unwrap_err();
+    assert_eq!(error, custom_error(AccessListError::DestinationNotAllowed));
+
+    add(&mut context, &program_id, &accounts, &accounts.bob).await;
+    execute(&mut context, &program_id, &accounts).await.unwrap();
+
+    // removing bob closes the entry and blocks transfers again
+    let destination = Pubkey::new_unique();
+    let instruction = remove_from_list(
+        &program_id,
+        &accounts.mint,
+        &accounts.list_authority.pubkey(),
+        &accounts.bob,
+        &destination,
+    );
+    process_instructions(&mut context, &[instruction], &[&accounts.list_authority])
+        .await
+        .unwrap();
+    let list_entry_address = get_list_entry_address(&accounts.mint, &accounts.bob, &program_id);
+    assert!(context
+        .banks_client
+        .get_account(list_entry_address)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(context
+        .banks_client
+        .get_account(destination)
+        .await
+        .unwrap()
+        .is_some());
+
+    let error = execute(&mut context, &program_id, &accounts)
+        .await
+        .unwrap_err();
+    assert_eq!(error, custom_error(AccessListError::DestinationNotAllowed));
+}
+
+#[tokio::test]
+async fn success_denylist() {
+    let program_id = Pubkey::new_unique();
+    let (program_test, accounts) = setup(&program_id, true);
+    let mut context = program_test.start_with_context().await;
+    initialize(&mut context, &program_id, &accounts, ListMode::Deny).await;
+
+    execute(&mut context, &program_id, &accounts).await.unwrap();
+
+    add(&mut context, &program_id, &accounts, &accounts.bob).await;
+    let error = execute(&mut context, &program_id, &accounts)
+        .await
+        .unwrap_err();
+    assert_eq!(error, custom_error(AccessListError::DestinationDenied));
+
+    add(&mut context, &program_id, &accounts, &accounts.alice).await;
+    let error = execute(&mut context, &program_id, &accounts)
+        .await
+        .unwrap_err();
+    assert_eq!(error, custom_error(AccessListError::SourceDenied));
+}
+
+#[tokio::test]
+async fn fail_wrong_list_entry() {
+    let program_id = Pubkey::new_unique();
+    let (program_test, accounts) = setup(&program_id, true);
+    let mut context = program_test.start_with_context().await;
+    initialize(&mut context, &program_id, &accounts, ListMode::Allow).await;
+    add(&mut context, &program_id, &accounts, &accounts.alice).await;
+
+    // bob cannot pass off alice's entry as his own
+    let alice_entry = get_list_entry_address(&accounts.mint, &accounts.alice, &program_id);
+    let extra_account_metas = [
+        AccountMeta::new_readonly(get_list_config_address(&accounts.mint, &program_id), false),
+        AccountMeta::new_readonly(alice_entry, false),
+        AccountMeta::new_readonly(alice_entry, false),
+    ];
+    let instruction = execute_with_extra_account_metas(
+        &program_id,
+        &accounts.alice_account,
+        &accounts.mint,
+        &accounts.bob_account,
+        &accounts.alice,
+        &get_extra_account_metas_address(&accounts.mint, &program_id),
+        &extra_account_metas,
+        100,
+    );
+    let error = process_instructions(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AccountResolutionError::IncorrectAccount as u32),
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_without_transferring_flag() {
+    let program_id = Pubkey::new_unique();
+    let (program_test, accounts) = setup(&program_id, false);
+    let mut context = program_test.start_with_context().await;
+    initialize(&mut context, &program_id, &accounts, ListMode::Deny).await;
+
+    let error = execute(&mut context, &program_id, &accounts)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TransferHookError::ProgramCalledOutsideOfTransfer as u32),
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_list_authority() {
+    let program_id = Pubkey::new_unique();
+    let (program_test, accounts) = setup(&program_id, true);
+    let mut context = program_test.start_with_context().await;
+    initialize(&mut context, &program_id, &accounts, ListMode::Allow).await;
+
+    // the mint authority is not the list authority
+    let list_entry_address = get_list_entry_address(&accounts.mint, &accounts.alice, &program_id);
+    let instructions = [
+        rent_transfer(&mut context, &list_entry_address, ListEntry::account_size()).await,
+        add_to_list(
+            &program_id,
+            &accounts.mint,
+            &accounts.mint_authority.pubkey(),
+            &accounts.alice,
+        ),
+    ];
+    let error = process_instructions(&mut context, &instructions, &[&accounts.mint_authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(AccessListError::IncorrectListAuthority as u32),
+        )
+    );
+
+    // once the authority is removed, the list cannot change
+    let instruction = update_list_authority(
+        &program_id,
+        &accounts.mint,
+        &accounts.list_authority.pubkey(),
+        None,
+    );
+    process_instructions(&mut context, &[instruction], &[&accounts.list_authority])
+        .await
+        .unwrap();
+    let instruction = remove_from_list(
+        &program_id,
+        &accounts.mint,
+        &accounts.list_authority.pubkey(),
+        &accounts.alice,
+        &accounts.list_authority.pubkey(),
+    );
+    let error = process_instructions(&mut context, &[instruction], &[&accounts.list_authority])
+        .await
+        .unwrap_err();
+    assert_eq!(error, custom_error(AccessListError::ImmutableList));
+}
+
+#[tokio::test]
+async fn fail_write_extra_account_metas() {
+    let program_id = Pubkey::new_unique();
+    let (program_test, accounts) = setup(&program_id, true);
+    let mut context = program_test.start_with_context().await;
+
+    let instruction = initialize_extra_account_meta_list(
+        &program_id,
+        &get_extra_account_metas_address(&accounts.mint, &program_id),
+        &accounts.mint,
+        &accounts.mint_authority.pubkey(),
+        &[],
+    );
+    let error = process_instructions(&mut context, &[instruction], &[&accounts.mint_authority])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        custom_error(AccessListError::ExtraAccountMetasManagedByProgram)
+    );
+}
@@ -16,6 +16,11 @@ use {
         transaction::Transaction,
     },
     spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList},
+    spl_transfer_hook_access_list::{
+        get_list_config_address, get_list_entry_address,
+        instruction::{add_to_list, initialize_list, remove_from_list},
+        state::{ListConfig, ListEntry, ListMode},
+    },
     spl_transfer_hook_interface::{
         get_extra_account_metas_address,
         instruction::{initialize_extra_account_meta_list, update_extra_account_meta_list},
@@ -103,11 +108,11 @@ async fn sign_and_send_transaction(
     transaction: &mut Transaction,
     rpc_client: &RpcClient,
     payer: &dyn Signer,
-    mint_authority: &dyn Signer,
+    authority: &dyn Signer,
 ) -> Result<Signature, Box<dyn std::error::Error>> {
     let mut signers = vec![payer];
-    if payer.pubkey() != mint_authority.pubkey() {
-        signers.push(mint_authority);
+    if payer.pubkey() != authority.pubkey() {
+        signers.push(authority);
     }
 
     let blockhash = rpc_client
@@ -219,6 +224,129 @@ async fn process_update_extra_account_metas(
     sign_and_send_transaction(&mut transaction, rpc_client, payer, mint_authority).await
 }
 
+// Helper function to fund a PDA for rent exemption before the program
+// allocates it
+async fn push_rent_transfer(
+    instructions: &mut Vec<Instruction>,
+    rpc_client: &RpcClient,
+    payer: &dyn Signer,
+    account_address: &Pubkey,
+    account_size: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let transfer_lamports =
+        calculate_rent_lamports(rpc_client, account_address, account_size).await?;
+    if transfer_lamports > 0 {
+        instructions.push(system_instruction::transfer(
+            &payer.pubkey(),
+            account_address,
+            transfer_lamports,
+        ));
+    }
+    Ok(())
+}
+
+async fn process_create_list(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    token: &Pubkey,
+    mode: ListMode,
+    list_authority: &Pubkey,
+    mint_authority: &dyn Signer,
+    payer: &dyn Signer,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let list_config_address = get_list_config_address(token, program_id);
+
+    // Check if the list has already been initialized
+    let list_config_account = rpc_client.get_account(&list_config_address).await;
+    if let Ok(account) = &list_config_account {
+        if account.owner != system_program::id() {
+            return Err(format!(
+                "error: list for mint {token} and program {program_id} already exists"
+            )
+            .into());
+        }
+    }
+
+    let extra_account_metas_address = get_extra_account_metas_address(token, program_id);
+    let mut instructions = vec![];
+    push_rent_transfer(
+        &mut instructions,
+        rpc_client,
+        payer,
+        &list_config_address,
+        ListConfig::account_size(),
+    )
+    .await?;
+    push_rent_transfer(
+        &mut instructions,
+        rpc_client,
+        payer,
+        &extra_account_metas_address,
+        ExtraAccountMetaList::size_of(3)?,
+    )
+    .await?;
+    instructions.push(initialize_list(
+        program_id,
+        token,
+        &mint_authority.pubkey(),
+        Some(*list_authority),
+        mode,
+    ));
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    sign_and_send_transaction(&mut transaction, rpc_client, payer, mint_authority).await
+}
+
+async fn process_add_to_list(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    token: &Pubkey,
+    wallet: &Pubkey,
+    list_authority: &dyn Signer,
+    payer: &dyn Signer,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let list_entry_address = get_list_entry_address(token, wallet, program_id);
+    let mut instructions = vec![];
+    push_rent_transfer(
+        &mut instructions,
+        rpc_client,
+        payer,
+        &list_entry_address,
+        ListEntry::account_size(),
+    )
+    .await?;
+    instructions.push(add_to_list(
+        program_id,
+        token,
+        &list_authority.pubkey(),
+        wallet,
+    ));
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    sign_and_send_transaction(&mut transaction, rpc_client, payer, list_authority).await
+}
+
+async fn process_remove_from_list(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    token: &Pubkey,
+    wallet: &Pubkey,
+    list_authority: &dyn Signer,
+    payer: &dyn Signer,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    // The list entry rent goes back to the fee payer
+    let instruction = remove_from_list(
+        program_id,
+        token,
+        &list_authority.pubkey(),
+        wallet,
+        &payer.pubkey(),
+    );
+
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    sign_and_send_transaction(&mut transaction, rpc_client, payer, list_authority).await
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app_matches = Command::new(crate_name!())
@@ -352,7 +480,133 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .global(true)
                         .help("Filepath or URL to mint-authority keypair [default: client keypair]"),
                 )
-        ).get_matches();
+        )
+        .subcommand(
+            Command::new("create-list")
+                .about("Create the allowlist or denylist of a mint for the transfer hook access list program")
+                .arg(
+                    Arg::with_name("program_id")
+                        .validator(clap_is_valid_pubkey)
+                        .value_name("TRANSFER_HOOK_PROGRAM")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The transfer hook access list program id"),
+                )
+                .arg(
+                    Arg::with_name("token")
+                        .validator(clap_is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("The token mint address for the transfer hook"),
+                )
+                .arg(
+                    Arg::new("mode")
+                        .long("mode")
+                        .value_name("MODE")
+                        .takes_value(true)
+                        .possible_values(["allow", "deny"])
+                        .required(true)
+                        .help("Whether only listed wallets may transfer, or listed wallets may not"),
+                )
+                .arg(
+                    Arg::new("list_authority")
+                        .long("list-authority")
+                        .value_name("ADDRESS")
+                        .validator(clap_is_valid_pubkey)
+                        .takes_value(true)
+                        .help("Address of the authority managing list membership [default: mint authority]"),
+                )
+                .arg(
+                    Arg::new("mint_authority")
+                        .long("mint-authority")
+                        .value_name("KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .help("Filepath or URL to mint-authority keypair [default: client keypair]"),
+                )
+        )
+        .subcommand(
+            Command::new("add-to-list")
+                .about("Add a wallet to the list of a mint for the transfer hook access list program")
+                .arg(
+                    Arg::with_name("program_id")
+                        .validator(clap_is_valid_pubkey)
+                        .value_name("TRANSFER_HOOK_PROGRAM")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The transfer hook access list program id"),
+                )
+                .arg(
+                    Arg::with_name("token")
+                        .validator(clap_is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("The token mint address for the transfer hook"),
+                )
+                .arg(
+                    Arg::with_name("wallet")
+                        .validator(clap_is_valid_pubkey)
+                        .value_name("WALLET_ADDRESS")
+                        .takes_value(true)
+                        .index(3)
+                        .required(true)
+                        .help("The wallet owning token accounts"),
+                )
+                .arg(
+                    Arg::new("list_authority")
+                        .long("list-authority")
+                        .value_name("KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .help("Filepath or URL to list-authority keypair [default: client keypair]"),
+                )
+        )
+        .subcommand(
+            Command::new("remove-from-list")
+                .about("Remove a wallet from the list of a mint for the transfer hook access list program")
+                .arg(
+                    Arg::with_name("program_id")
+                        .validator(clap_is_valid_pubkey)
+                        .value_name("TRANSFER_HOOK_PROGRAM")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The transfer hook access list program id"),
+                )
+                .arg(
+                    Arg::with_name("token")
+                        .validator(clap_is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("The token mint address for the transfer hook"),
+                )
+                .arg(
+                    Arg::with_name("wallet")
+                        .validator(clap_is_valid_pubkey)
+                        .value_name("WALLET_ADDRESS")
+                        .takes_value(true)
+                        .index(3)
+                        .required(true)
+                        .help("The wallet owning token accounts"),
+                )
+                .arg(
+                    Arg::new("list_authority")
+                        .long("list-authority")
+                        .value_name("KEYPAIR")
+                        .validator(|s| is_valid_signer(s))
+                        .takes_value(true)
+                        .help("Filepath or URL to list-authority keypair [default: client keypair]"),
+                )
+        )
+        .get_matches();
 
     let (command, matches) = app_matches.subcommand().unwrap();
     let mut wallet_manager: Option<Rc<RemoteWalletManager>> = None;
@@ -477,6 +731,98 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             });
             println!("Signature: {signature}");
         }
+        ("create-list", arg_matches) => {
+            let program_id = pubkey_of_signer(arg_matches, "program_id", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let mode = match arg_matches.value_of("mode").unwrap() {
+                "allow" => ListMode::Allow,
+                _ => ListMode::Deny,
+            };
+            let mint_authority = DefaultSigner::new(
+                "mint_authority",
+                matches
+                    .value_of("mint_authority")
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| cli_config.keypair_path.clone()),
+            )
+            .signer_from_path(matches, &mut wallet_manager)
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+            let list_authority =
+                pubkey_of_signer(arg_matches, "list_authority", &mut wallet_manager)
+                    .unwrap()
+                    .unwrap_or_else(|| mint_authority.pubkey());
+            let signature = process_create_list(
+                &rpc_client,
+                &program_id,
+                &token,
+                mode,
+                &list_authority,
+                mint_authority.as_ref(),
+                config.default_signer.as_ref(),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: send transaction: {err}");
+                exit(1);
+            });
+            println!("Signature: {signature}");
+        }
+        ("add-to-list", arg_matches) | ("remove-from-list", arg_matches) => {
+            let program_id = pubkey_of_signer(arg_matches, "program_id", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let wallet = pubkey_of_signer(arg_matches, "wallet", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let list_authority = DefaultSigner::new(
+                "list_authority",
+                matches
+                    .value_of("list_authority")
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| cli_config.keypair_path.clone()),
+            )
+            .signer_from_path(matches, &mut wallet_manager)
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+            let signature = if command == "add-to-list" {
+                process_add_to_list(
+                    &rpc_client,
+                    &program_id,
+                    &token,
+                    &wallet,
+                    list_authority.as_ref(),
+                    config.default_signer.as_ref(),
+                )
+                .await
+            } else {
+                process_remove_from_list(
+                    &rpc_client,
+                    &program_id,
+                    &token,
+                    &wallet,
+                    list_authority.as_ref(),
+                    config.default_signer.as_ref(),
+                )
+                .await
+            }
+            .unwrap_or_else(|err| {
+                eprintln!("error: send transaction: {err}");
+                exit(1);
+            });
+            println!("Signature: {signature}");
+        }
         _ => unreachable!(),
     };
 
@@ -499,13 +845,16 @@ mod test {
         std::{path::PathBuf, sync::Arc},
     };
 
-    async fn new_validator_for_test(program_id: Pubkey) -> (TestValidator, Keypair) {
+    async fn new_validator_for_test(
+        program_id: Pubkey,
+        program_name: &str,
+    ) -> (TestValidator, Keypair) {
         miraland_logger::setup();
         let mut test_validator_genesis = TestValidatorGenesis::default();
         test_validator_genesis.add_upgradeable_programs_with_path(&[UpgradeableProgramInfo {
             program_id,
             loader: bpf_loader_upgradeable::id(),
-            program_path: PathBuf::from("../../../target/deploy/spl_transfer_hook_example.so"),
+            program_path: PathBuf::from(format!("../../../target/deploy/{program_name}.so")),
             upgrade_authority: Pubkey::new_unique(),
         }]);
         test_validator_genesis.start_async().await
@@ -537,7 +886,8 @@ mod test {
     async fn test_create() {
         let program_id = Pubkey::new_unique();
 
-        let (test_validator, payer) = new_validator_for_test(program_id).await;
+        let (test_validator, payer) =
+            new_validator_for_test(program_id, "spl_transfer_hook_example").await;
         let payer: Arc<dyn Signer> = Arc::new(payer);
         let rpc_client = Arc::new(test_validator.get_async_rpc_client());
         let client = Arc::new(ProgramRpcClient::new(
@@ -578,4 +928,79 @@ mod test {
             .unwrap();
         assert_eq!(account.owner, program_id);
     }
+    #[tokio::test]
+    async fn test_list_membership() {
+        let program_id = Pubkey::new_unique();
+
+        let (test_validator, payer) =
+            new_validator_for_test(program_id, "spl_transfer_hook_access_list").await;
+        let payer: Arc<dyn Signer> = Arc::new(payer);
+        let rpc_client = Arc::new(test_validator.get_async_rpc_client());
+        let client = Arc::new(ProgramRpcClient::new(
+            rpc_client.clone(),
+            ProgramRpcClientSendTransaction,
+        ));
+
+        let mint_authority = Keypair::new();
+        let list_authority = Keypair::new();
+        let decimals = 2;
+
+        let token = setup_mint(
+            &spl_token_2022::id(),
+            &mint_authority.pubkey(),
+            decimals,
+            payer.clone(),
+            client.clone(),
+        )
+        .await;
+
+        process_create_list(
+            &rpc_client,
+            &program_id,
+            token.get_address(),
+            ListMode::Allow,
+            &list_authority.pubkey(),
+            &mint_authority,
+            payer.as_ref(),
+        )
+        .await
+        .unwrap();
+        let list_config_address = get_list_config_address(token.get_address(), &program_id);
+        let account = rpc_client.get_account(&list_config_address).await.unwrap();
+        assert_eq!(account.owner, program_id);
+        let extra_account_metas_address =
+            get_extra_account_metas_address(token.get_address(), &program_id);
+        let account = rpc_client
+            .get_account(&extra_account_metas_address)
+            .await
+            .unwrap();
+        assert_eq!(account.owner, program_id);
+
+        let wallet = Pubkey::new_unique();
+        process_add_to_list(
+            &rpc_client,
+            &program_id,
+            token.get_address(),
+            &wallet,
+            &list_authority,
+            payer.as_ref(),
+        )
+        .await
+        .unwrap();
+        let list_entry_address = get_list_entry_address(token.get_address(), &wallet, &program_id);
+        let account = rpc_client.get_account(&list_entry_address).await.unwrap();
+        assert_eq!(account.owner, program_id);
+
+        process_remove_from_list(
+            &rpc_client,
+            &program_id,
+            token.get_address(),
+            &wallet,
+            &list_authority,
+            payer.as_ref(),
+        )
+        .await
+        .unwrap();
+        assert!(rpc_client.get_account(&list_entry_address).await.is_err());
+    }
 }